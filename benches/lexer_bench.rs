@@ -0,0 +1,51 @@
+// Lexer throughput benchmark
+// Tokenizes about 1 MB of source, mixing code, strings, comments and non-ASCII text
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use jounce_compiler::lexer::Lexer;
+use jounce_compiler::token::TokenKind;
+
+const CHUNK: &str = r#"
+/// Adds a greeting to the list
+fn greet(names: Vec<String>, prefix: String) -> Vec<String> {
+    let mut greetings = Vec::new();
+    for name in names {
+        // Skip empty names
+        if name.len() == 0 { continue; }
+        greetings.push(prefix + ", " + name + "! Ça va? 👋");
+    }
+    let total = 1_000 * 3.5 + 0xFF;
+    /* block comments are skipped too */
+    return greetings;
+}
+"#;
+
+/// About `bytes` of source, made of whole chunks
+fn source_of(bytes: usize) -> String {
+    CHUNK.repeat(bytes / CHUNK.len() + 1)
+}
+
+fn lex_all(source: &str) -> usize {
+    let mut lexer = Lexer::new(source.to_string());
+    let mut count = 0;
+    while lexer.next_token().kind != TokenKind::Eof {
+        count += 1;
+    }
+    count
+}
+
+fn bench_lexing(c: &mut Criterion) {
+    let source = source_of(1024 * 1024);
+    let mut group = c.benchmark_group("lexer");
+    group.throughput(Throughput::Bytes(source.len() as u64));
+    group.sample_size(20);
+
+    group.bench_function("lex_1mb", |b| {
+        b.iter(|| black_box(lex_all(black_box(&source))));
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_lexing);
+criterion_main!(benches);
@@ -0,0 +1,900 @@
+// Jounce Client Runtime
+// Provides JSX rendering and client-side utilities
+
+import { escape, unsafe_raw, RawHtml, I18n, JsValue, memoize } from './shared-runtime.js';
+
+export { escape, unsafe_raw, RawHtml, I18n, JsValue, memoize };
+
+// Test ids: stamp component roots and keyed items with data-jounce-* attributes.
+// Off unless the bundle was built with --test-ids.
+let testIdsEnabled = false;
+
+export function enableTestIds(enabled = true) {
+    testIdsEnabled = enabled;
+}
+
+function stampComponent(node, name) {
+    if (testIdsEnabled && node instanceof Element && name && !node.hasAttribute('data-jounce-component')) {
+        node.setAttribute('data-jounce-component', name);
+    }
+    return node;
+}
+
+// Devtools: a registry of mounted component instances on
+// window.__JOUNCE_DEVTOOLS__. Each instance has an id that only ever
+// increases, links to its parent and children, its props (debug form) and the
+// signals it owns. Off unless the bundle is a dev build.
+let devtools = null;
+let currentInstance = null;
+
+class DevtoolsRegistry {
+    constructor() {
+        this.nextId = 1;
+        this.instances = new Map();
+        this.roots = [];
+        this.listeners = new Set();
+    }
+
+    // listener(event, registry) runs after every mount, unmount and signal
+    // change; events are { type: 'mount' | 'unmount' | 'signal', id, ... }
+    subscribe(listener) {
+        this.listeners.add(listener);
+        return () => this.listeners.delete(listener);
+    }
+
+    // Plain snapshot of the component tree, roots first
+    tree() {
+        const snapshot = (id) => {
+            const instance = this.instances.get(id);
+            return {
+                id,
+                name: instance.name,
+                props: instance.props,
+                signals: instance.signals.map(({ name, value }) => ({ name, value })),
+                children: instance.children.map(snapshot),
+            };
+        };
+        return this.roots.map(snapshot);
+    }
+
+    open(component, props) {
+        const meta = component.__jounceMeta || { name: component.name, props: [] };
+        const instance = {
+            id: this.nextId++,
+            name: meta.name || component.name || 'Anonymous',
+            propTypes: meta.props,
+            props: debugProps(props),
+            parent: currentInstance ? currentInstance.id : null,
+            children: [],
+            signals: [],
+        };
+        this.instances.set(instance.id, instance);
+        (currentInstance ? currentInstance.children : this.roots).push(instance.id);
+        return instance;
+    }
+
+    close(instance) {
+        if (!this.instances.delete(instance.id)) {
+            return;
+        }
+        const parent = this.instances.get(instance.parent);
+        const siblings = parent ? parent.children : this.roots;
+        const index = siblings.indexOf(instance.id);
+        if (index !== -1) {
+            siblings.splice(index, 1);
+        }
+        this.emit({ type: 'unmount', id: instance.id });
+    }
+
+    emit(event) {
+        for (const listener of Array.from(this.listeners)) {
+            listener(event, this);
+        }
+    }
+}
+
+export function enableDevtools() {
+    if (!devtools) {
+        devtools = new DevtoolsRegistry();
+        if (typeof window !== 'undefined') {
+            window.__JOUNCE_DEVTOOLS__ = devtools;
+        }
+    }
+    return devtools;
+}
+
+// Record a signal as owned by the component rendering now, under its binding
+// name, and report every change to devtools listeners
+export function trackSignal(sig, name) {
+    if (!devtools || !currentInstance) {
+        return sig;
+    }
+    let proto = Object.getPrototypeOf(sig);
+    while (proto && !Object.getOwnPropertyDescriptor(proto, 'value')) {
+        proto = Object.getPrototypeOf(proto);
+    }
+    const accessor = proto && Object.getOwnPropertyDescriptor(proto, 'value');
+    if (!accessor || !accessor.set) {
+        return sig;
+    }
+
+    const instance = currentInstance;
+    const entry = { name, raw: sig._value, value: debugValue(sig._value) };
+    instance.signals.push(entry);
+    Object.defineProperty(sig, 'value', {
+        configurable: true,
+        get() {
+            return accessor.get.call(this);
+        },
+        set(value) {
+            accessor.set.call(this, value);
+            if (!Object.is(entry.raw, value) && devtools.instances.has(instance.id)) {
+                entry.raw = value;
+                entry.value = debugValue(value);
+                devtools.emit({ type: 'signal', id: instance.id, name, value: entry.value });
+            }
+        },
+    });
+    return sig;
+}
+
+// Debug representation of a runtime value, as `{:?}` would print it
+function debugValue(value, depth = 0) {
+    if (value === null || value === undefined) {
+        return '()';
+    }
+    if (typeof value === 'string') {
+        return JSON.stringify(value);
+    }
+    if (typeof value === 'function') {
+        return `<fn ${value.name || 'anonymous'}>`;
+    }
+    if (typeof value !== 'object') {
+        return String(value);
+    }
+    if (typeof Node !== 'undefined' && value instanceof Node) {
+        return value.tagName ? `<${value.tagName.toLowerCase()}>` : '<node>';
+    }
+    if (depth > 3) {
+        return '..';
+    }
+    if (Array.isArray(value)) {
+        return `[${value.map(item => debugValue(item, depth + 1)).join(', ')}]`;
+    }
+    if (typeof value.variant === 'string') {
+        return 'data' in value && value.data !== undefined
+            ? `${value.variant}(${debugValue(value.data, depth + 1)})`
+            : value.variant;
+    }
+    const fields = Object.entries(value).map(([key, field]) => `${key}: ${debugValue(field, depth + 1)}`);
+    return fields.length > 0 ? `{ ${fields.join(', ')} }` : '{}';
+}
+
+function debugProps(props) {
+    const result = {};
+    for (const [key, value] of Object.entries(props || {})) {
+        result[key] = debugValue(value);
+    }
+    return result;
+}
+
+// Component ownership: cleanups registered with onCleanup while a component
+// renders are attached to its root node and run when that node is unmounted.
+let currentCleanups = null;
+
+export function onCleanup(fn) {
+    if (currentCleanups) {
+        currentCleanups.push(fn);
+    }
+}
+
+// Server calls made while a component renders, or from its event handlers,
+// are owned by it: unmounting the component aborts the ones still in flight.
+// The generated RPC stubs take the owner's signal as a last argument.
+let currentOwner = null;
+
+// AbortSignal of the component rendering (or whose handler is running),
+// created on first use
+export function rpcSignal() {
+    const owner = currentOwner;
+    if (!owner) {
+        return undefined;
+    }
+    if (!owner.controller) {
+        owner.controller = new AbortController();
+        if (owner.disposed) {
+            owner.controller.abort();
+        }
+    }
+    return owner.controller.signal;
+}
+
+function disposeOwner(owner) {
+    owner.disposed = true;
+    if (owner.controller) {
+        owner.controller.abort();
+    }
+}
+
+// Run an event handler as its component, so the server calls it starts are
+// cancelled when the component unmounts
+function ownedHandler(owner, handler) {
+    owner.handlers = true;
+    return function (...args) {
+        const previous = currentOwner;
+        currentOwner = owner;
+        try {
+            return handler.apply(this, args);
+        } finally {
+            currentOwner = previous;
+        }
+    };
+}
+
+// Context: <Provide context={ctx} value={v}> makes v the value of ctx for every
+// component rendered inside it. Providers form a stack while rendering. A
+// component that reads a provided signal renders again, under the same
+// providers, whenever the signal changes.
+let contextStack = null;
+let currentSignalReads = null;
+
+export class Context {
+    constructor(defaultValue) {
+        this.defaultValue = defaultValue;
+    }
+}
+
+export function createContext(defaultValue) {
+    return new Context(defaultValue);
+}
+
+function isSignal(value) {
+    return value !== null && typeof value === 'object' && value._subscribers instanceof Set;
+}
+
+// The value of the nearest enclosing provider, or the context's default
+export function useContext(context) {
+    for (let entry = contextStack; entry; entry = entry.parent) {
+        if (entry.context === context) {
+            if (!isSignal(entry.value)) {
+                return entry.value;
+            }
+            if (currentSignalReads) {
+                currentSignalReads.add(entry.value);
+            }
+            return entry.value._value;
+        }
+    }
+    return context.defaultValue;
+}
+
+// Built-in <Provide>: the compiler passes its children as a function so they
+// render after the value is pushed
+export function Provide(props, children) {
+    const { context, value } = props || {};
+    const previous = contextStack;
+    contextStack = { context, value, parent: previous };
+    let nodes;
+    try {
+        nodes = children.flat().map(child => (typeof child === 'function' ? child() : child)).flat();
+    } finally {
+        contextStack = previous;
+    }
+    nodes = nodes.filter(node => node !== null && node !== undefined);
+    if (nodes.length === 1 && nodes[0] instanceof Node) {
+        return nodes[0];
+    }
+    return appendChildren(document.createDocumentFragment(), nodes);
+}
+
+// Render a consumer again under the providers it was first rendered with, in
+// place of its old output
+function rerenderComponent(old, component, props, children, contexts) {
+    if (!old.parentNode) {
+        return;
+    }
+    const previous = contextStack;
+    contextStack = contexts;
+    let rendered;
+    try {
+        rendered = renderComponent(component, props, children);
+    } finally {
+        contextStack = previous;
+    }
+    if (canPatch(old, rendered)) {
+        patchElement(old, rendered);
+    } else {
+        disposeTree(old);
+        old.parentNode.replaceChild(rendered, old);
+    }
+}
+
+// Reconciliation: the new output of a component rendering again is matched
+// against the old DOM, which is kept wherever it can be. An element with the
+// same tag and key stays, taking the new attributes, handlers and cleanups;
+// children with keys are matched by key wherever they moved, the others by
+// tag in order. Only what has no match is created or removed, so reordering
+// a keyed list moves its existing nodes.
+
+// Code that outlives a render, like a binding or a re-render, reaches its
+// node through a ref, which follows it when an old node takes its place
+function nodeRef(node) {
+    const ref = { node };
+    (node.__jounceRefs || (node.__jounceRefs = [])).push(ref);
+    return ref;
+}
+
+function listen(element, event, handler, options) {
+    element.addEventListener(event, handler, options);
+    (element.__jounceListeners || (element.__jounceListeners = [])).push([event, handler, options]);
+}
+
+function canPatch(old, next) {
+    return old instanceof Element && next instanceof Element
+        && old.tagName === next.tagName && old.__jounceKey === next.__jounceKey;
+}
+
+// Make `old` what `next` is, and let it stand in for `next` from now on
+function patchElement(old, next) {
+    for (const name of old.getAttributeNames()) {
+        if (!next.hasAttribute(name)) {
+            old.removeAttribute(name);
+        }
+    }
+    for (const name of next.getAttributeNames()) {
+        const value = next.getAttribute(name);
+        if (old.getAttribute(name) !== value) {
+            old.setAttribute(name, value);
+        }
+    }
+
+    for (const [event, handler, options] of old.__jounceListeners || []) {
+        old.removeEventListener(event, handler, options);
+    }
+    for (const [event, handler, options] of next.__jounceListeners || []) {
+        old.addEventListener(event, handler, options);
+    }
+    old.__jounceListeners = next.__jounceListeners;
+
+    // The components rendered at this node now are the new ones
+    const cleanups = old.__jounceCleanups || [];
+    old.__jounceCleanups = next.__jounceCleanups;
+    for (const cleanup of cleanups) {
+        cleanup();
+    }
+    for (const ref of next.__jounceRefs || []) {
+        ref.node = old;
+    }
+    old.__jounceRefs = next.__jounceRefs;
+    old.__jounceRef = next.__jounceRef;
+    if (old.__jounceRef) {
+        old.__jounceRef(old);
+    }
+
+    patchChildren(old, Array.from(next.childNodes));
+}
+
+function patchChildren(parent, nextChildren) {
+    const keyed = new Map();
+    const unkeyed = [];
+    for (const child of parent.childNodes) {
+        if (child.__jounceKey !== undefined) {
+            keyed.set(child.__jounceKey, child);
+        } else {
+            unkeyed.push(child);
+        }
+    }
+
+    let position = 0;
+    const kept = new Set();
+    const children = nextChildren.map(next => {
+        let old;
+        if (next.__jounceKey !== undefined) {
+            old = keyed.get(next.__jounceKey);
+        } else {
+            const index = unkeyed.findIndex((candidate, i) => i >= position && canPatch(candidate, next));
+            if (index >= 0) {
+                old = unkeyed[index];
+                position = index + 1;
+            }
+        }
+        if (old && !kept.has(old) && canPatch(old, next)) {
+            kept.add(old);
+            patchElement(old, next);
+            return old;
+        }
+        return next;
+    });
+
+    for (const child of Array.from(parent.childNodes)) {
+        if (!kept.has(child)) {
+            disposeTree(child);
+            parent.removeChild(child);
+        }
+    }
+    children.forEach((child, i) => {
+        const current = parent.childNodes[i];
+        if (current !== child) {
+            parent.insertBefore(child, current || null);
+        }
+    });
+}
+
+function renderComponent(component, props, children) {
+    const previous = currentCleanups;
+    const cleanups = [];
+    currentCleanups = cleanups;
+    const previousOwner = currentOwner;
+    const owner = { controller: null, handlers: false, disposed: false };
+    currentOwner = owner;
+    const previousReads = currentSignalReads;
+    const signalReads = new Set();
+    currentSignalReads = signalReads;
+    const contexts = contextStack;
+    const parentInstance = currentInstance;
+    const instance = devtools ? devtools.open(component, props) : null;
+    if (instance) {
+        currentInstance = instance;
+        cleanups.push(() => devtools.close(instance));
+    }
+    let rendered;
+    try {
+        rendered = stampComponent(component(props, children), component.name);
+    } finally {
+        currentCleanups = previous;
+        currentOwner = previousOwner;
+        currentSignalReads = previousReads;
+        currentInstance = parentInstance;
+    }
+    if (instance) {
+        devtools.emit({ type: 'mount', id: instance.id });
+    }
+    if (owner.controller || owner.handlers) {
+        cleanups.push(() => disposeOwner(owner));
+    }
+
+    if (signalReads.size > 0 && rendered instanceof Node) {
+        const ref = nodeRef(rendered);
+        const observer = {
+            disposed: false,
+            _dependencies: new Set(),
+            _execute() {
+                if (!this.disposed) {
+                    rerenderComponent(ref.node, component, props, children, contexts);
+                }
+            },
+        };
+        for (const sig of signalReads) {
+            sig._subscribers.add(observer);
+            cleanups.push(() => {
+                observer.disposed = true;
+                sig._unsubscribe(observer);
+            });
+        }
+    }
+
+    if (cleanups.length > 0) {
+        if (rendered instanceof Node) {
+            rendered.__jounceCleanups = (rendered.__jounceCleanups || []).concat(cleanups);
+        } else if (previous) {
+            // Nothing to attach to: the enclosing component owns them
+            previous.push(...cleanups);
+        }
+    }
+    return rendered;
+}
+
+// Run the cleanups of every component rendered inside `node`, children first
+function disposeTree(node) {
+    for (const child of Array.from(node.childNodes || [])) {
+        disposeTree(child);
+    }
+    const cleanups = node.__jounceCleanups;
+    if (cleanups) {
+        delete node.__jounceCleanups;
+        for (const cleanup of cleanups) {
+            cleanup();
+        }
+    }
+}
+
+// Remove a rendered component from the DOM and release its subscriptions
+export function unmount(node) {
+    disposeTree(node);
+    if (node.parentNode) {
+        node.parentNode.removeChild(node);
+    }
+}
+
+// Attributes that load or navigate to a URL, where a script URL would run
+const URL_ATTRIBUTES = new Set(['href', 'src', 'action', 'formaction', 'xlink:href', 'poster', 'cite', 'background']);
+const SCRIPT_URL = /^(?:javascript:|vbscript:|data:text\/html)/i;
+
+function attributeValue(name, value) {
+    // Browsers ignore whitespace and control characters inside the scheme
+    if (URL_ATTRIBUTES.has(name.toLowerCase()) && SCRIPT_URL.test(String(value).replace(/[\u0000- ]/g, ''))) {
+        console.warn(`Blocked a script URL in the "${name}" attribute`);
+        return 'about:blank';
+    }
+    return String(value);
+}
+
+// Attributes that spell booleans out as "true" and "false"; the rest are
+// present or absent
+const BOOLEAN_TEXT = /^(?:aria-|data-)|^(?:contenteditable|draggable|spellcheck)$/i;
+
+function rawHtmlFragment(raw) {
+    const template = document.createElement('template');
+    template.innerHTML = raw.html;
+    return template.content;
+}
+
+// Simple JSX createElement function (h function)
+export function h(tag, props, ...children) {
+    if (typeof tag === 'function') {
+        // A key is for the list the component is in, not one of its props
+        const { key, ...rest } = props || {};
+        const rendered = renderComponent(tag, props ? rest : props, children);
+        if (key !== undefined && rendered instanceof Element) {
+            rendered.__jounceKey = key;
+            if (testIdsEnabled) {
+                rendered.setAttribute('data-jounce-key', String(key));
+            }
+        }
+        return rendered;
+    }
+
+    const element = document.createElement(tag);
+    const bound = [];
+
+    // Set properties
+    if (props) {
+        for (const [key, value] of Object.entries(props)) {
+            if ((key === 'value' || key === 'checked') && isSignal(value)) {
+                // bind:value; a <select> takes its value once its options are in
+                bound.push([key, value]);
+            } else if (key === 'key') {
+                // Keys identify list items; they are not DOM attributes
+                element.__jounceKey = value;
+                if (testIdsEnabled) {
+                    element.setAttribute('data-jounce-key', String(value));
+                }
+            } else if (key === 'ref') {
+                // Given the element once it's built, not an attribute
+                if (typeof value === 'function') {
+                    element.__jounceRef = value;
+                }
+            } else if (key === 'className') {
+                element.className = value;
+            } else if (key === 'class') {
+                element.className = value;
+            } else if (key === 'inner_html' || key === 'innerHTML') {
+                // Markup only through unsafe_raw(), which `unsafe_html={markup}`
+                // compiles to; anything else is text
+                if (value instanceof RawHtml) {
+                    element.innerHTML = value.html;
+                } else {
+                    element.textContent = value === null || value === undefined ? '' : String(value);
+                }
+            } else if (key === 'srcdoc' && !(value instanceof RawHtml)) {
+                element.setAttribute(key, escape(value, 'text'));
+            } else if (key.startsWith('on')) {
+                // Handlers are functions, or `{ handleEvent, once, capture, passive }`
+                // for one with listener options; a string here would be script from data
+                const listener = typeof value === 'function' ? { handleEvent: value } : value;
+                if (listener && typeof listener.handleEvent === 'function') {
+                    const { handleEvent, ...options } = listener;
+                    const handler = currentOwner ? ownedHandler(currentOwner, handleEvent) : handleEvent;
+                    listen(element, key.substring(2).toLowerCase(), handler, options);
+                } else if (value !== null && value !== undefined) {
+                    console.warn(`Ignored a non-function "${key}" handler`);
+                }
+            } else if (key === 'style' && typeof value === 'object') {
+                // Each property is set on its own through the CSSOM, which
+                // never lets a value end the declaration, so it goes in as-is
+                Object.assign(element.style, value);
+            } else if (value === null || value === undefined) {
+                continue;
+            } else if (typeof value === 'boolean' && !BOOLEAN_TEXT.test(key)) {
+                // `disabled="false"` would still disable, so false leaves it out
+                if (value) {
+                    element.setAttribute(key, '');
+                }
+            } else {
+                element.setAttribute(key, attributeValue(key, value));
+            }
+        }
+    }
+
+    // Append children
+    appendChildren(element, children);
+    for (const [key, sig] of bound) {
+        bindProperty(element, key, sig, props);
+    }
+    if (element.__jounceRef) {
+        element.__jounceRef(element);
+    }
+    return element;
+}
+
+// ref={x} compiles to bindRef(x, assign): a signal in x is given the element
+// as its value, and anything else is replaced by it through `assign`
+export function bindRef(target, assign) {
+    return isSignal(target) ? (node) => { target.value = node; } : assign;
+}
+
+// bind:value={sig} compiles to the signal as a form field's `value` or
+// `checked` prop plus a handler writing edits back. The property follows the
+// signal until the component that rendered the field unmounts.
+function bindProperty(field, key, sig, props) {
+    const ref = nodeRef(field);
+    const update = () => {
+        const element = ref.node;
+        const value = sig._value;
+        if (key === 'checked') {
+            // A radio is checked when the signal holds its value, a checkbox
+            // with a value when the signal's list does
+            element.checked = props.type === 'radio' ? value === props.value
+                : Array.isArray(value) ? value.includes(props.value) : Boolean(value);
+        } else if (props.multiple) {
+            for (const option of element.options) {
+                option.selected = Array.isArray(value) && value.includes(option.value);
+            }
+        } else {
+            const text = value === null || value === undefined ? '' : String(value);
+            // Leave a field alone while it already shows the value, so typing
+            // "1." into a number field isn't rewritten to "1"
+            const shown = typeof value === 'number' && element.value !== '' && Number(element.value) === value;
+            if (element.value !== text && !shown) {
+                element.value = text;
+            }
+        }
+    };
+    update();
+    const observer = { _dependencies: new Set(), _execute: update };
+    sig._subscribers.add(observer);
+    if (currentCleanups) {
+        currentCleanups.push(() => sig._unsubscribe(observer));
+    }
+}
+
+// Slot contents and lists are arrays of children, spliced in at any depth
+function appendChildren(parent, children) {
+    // Dev builds check that siblings' keys are unique
+    const keys = devtools ? new Set() : null;
+    for (const child of children.flat(Infinity)) {
+        if (child === null || child === undefined) {
+            continue;
+        } else if (child instanceof RawHtml) {
+            parent.appendChild(rawHtmlFragment(child));
+        } else if (typeof child === 'string' || typeof child === 'number') {
+            // Text nodes are never parsed as markup
+            parent.appendChild(document.createTextNode(String(child)));
+        } else if (child instanceof Node) {
+            if (keys && child.__jounceKey !== undefined) {
+                if (keys.has(child.__jounceKey)) {
+                    const component = currentInstance ? `<${currentInstance.name}>` : 'the app';
+                    console.warn(`Duplicate key "${child.__jounceKey}" among the children of <${String(parent.tagName).toLowerCase()}> in ${component}; each item of a keyed list needs its own key`);
+                }
+                keys.add(child.__jounceKey);
+            }
+            parent.appendChild(child);
+        }
+    }
+    return parent;
+}
+
+// Mount a component to the DOM
+export function mountComponent(component, selector = '#app') {
+    const container = document.querySelector(selector);
+    if (!container) {
+        console.error(`Mount target "${selector}" not found`);
+        return;
+    }
+
+    // Clear existing content, releasing what the old components registered
+    for (const child of Array.from(container.childNodes)) {
+        disposeTree(child);
+    }
+    container.innerHTML = '';
+
+    // Render component
+    const rendered = typeof component === 'function'
+        ? renderComponent(component)
+        : component;
+
+    if (rendered instanceof Node) {
+        container.appendChild(rendered);
+    } else {
+        console.error('Component did not return a valid DOM node');
+    }
+}
+
+// sleep(ms): a promise that resolves after `ms` milliseconds
+export function sleep(ms) {
+    return new Promise(resolve => setTimeout(resolve, ms));
+}
+
+// EventBus<T>: synchronous, ordered publish/subscribe between components.
+// A value emitted from inside a handler is queued and delivered once the
+// current value has reached every subscriber, so nothing is lost or reordered.
+// The queue is bounded so a handler that re-emits forever fails loudly.
+const EVENT_BUS_MAX_PENDING = 1000;
+const eventChannels = new Map();
+
+export class EventBus {
+    constructor(maxPending = EVENT_BUS_MAX_PENDING) {
+        this.subscriptions = [];
+        this.pending = [];
+        this.delivering = false;
+        this.maxPending = maxPending;
+    }
+
+    static new() {
+        return new EventBus();
+    }
+
+    // Shared bus for a topic name: EventBus::channel::<T>("toasts")
+    static channel(name) {
+        if (!eventChannels.has(name)) {
+            eventChannels.set(name, new EventBus());
+        }
+        return eventChannels.get(name);
+    }
+
+    emit(value) {
+        if (this.pending.length >= this.maxPending) {
+            // A runaway queue is dropped, so the bus is usable again
+            this.pending = [];
+            throw new Error(`EventBus: more than ${this.maxPending} events queued; is a handler emitting on every event?`);
+        }
+        this.pending.push(value);
+        if (this.delivering) {
+            return;
+        }
+
+        this.delivering = true;
+        try {
+            while (this.pending.length > 0) {
+                const next = this.pending.shift();
+                // Handlers subscribed during delivery start with the next value
+                for (const subscription of this.subscriptions.slice()) {
+                    if (subscription.active) {
+                        subscription.handler(next);
+                    }
+                }
+            }
+        } finally {
+            // If a handler threw, the values still queued go out with the next emit
+            this.delivering = false;
+        }
+    }
+
+    // Inside a component, the subscription is released when it unmounts
+    subscribe(handler) {
+        const subscription = new Subscription(this, handler);
+        this.subscriptions.push(subscription);
+        onCleanup(() => subscription.unsubscribe());
+        return subscription;
+    }
+
+    subscriber_count() {
+        return this.subscriptions.length;
+    }
+}
+
+export class Subscription {
+    constructor(bus, handler) {
+        this.bus = bus;
+        this.handler = handler;
+        this.active = true;
+    }
+
+    unsubscribe() {
+        if (!this.active) {
+            return;
+        }
+        this.active = false;
+        const index = this.bus.subscriptions.indexOf(this);
+        if (index !== -1) {
+            this.bus.subscriptions.splice(index, 1);
+        }
+    }
+}
+
+// Why a server call failed. `variant` is what a match on the error tests:
+// `match e { HttpError::Cancelled => ..., HttpError::StatusError { status, body } => ... }`
+export class HttpError extends Error {
+    constructor(variant, message, data) {
+        super(message);
+        this.name = 'HttpError';
+        this.variant = variant;
+        this.data = data;
+    }
+}
+
+function isCancelled(error) {
+    return error instanceof HttpError && error.variant === 'Cancelled';
+}
+
+// A call cancelled by an unmount rejects the async code awaiting it, which
+// stops there; unless that code catches the error, nothing is logged
+if (typeof window !== 'undefined' && typeof window.addEventListener === 'function') {
+    window.addEventListener('unhandledrejection', event => {
+        if (isCancelled(event.reason)) {
+            event.preventDefault();
+        }
+    });
+}
+
+// RPC Client for calling server functions
+export class RPCClient {
+    constructor(baseUrl = '') {
+        this.baseUrl = baseUrl;
+    }
+
+    // `signal` aborts the call; it then rejects with HttpError::Cancelled, even
+    // if the response already arrived, so the caller never continues
+    async call(functionName, params = {}, signal = undefined) {
+        const cancelled = () => new HttpError('Cancelled', `Server call '${functionName}' was cancelled`);
+        if (signal && signal.aborted) {
+            throw cancelled();
+        }
+
+        let response;
+        let result;
+        try {
+            response = await fetch(`${this.baseUrl}/rpc/${functionName}`, {
+                method: 'POST',
+                headers: {
+                    'Content-Type': 'application/json',
+                },
+                body: JSON.stringify(params),
+                signal,
+            });
+            if (response.ok) {
+                result = await response.json();
+            }
+        } catch (error) {
+            if (signal && signal.aborted) {
+                throw cancelled();
+            }
+            throw new HttpError('NetworkError', `RPC call failed: ${error.message}`, error.message);
+        }
+        if (signal && signal.aborted) {
+            throw cancelled();
+        }
+
+        if (!response.ok) {
+            throw new HttpError('StatusError', `RPC call failed: ${response.statusText}`, { status: response.status, body: response.statusText });
+        }
+
+        return result;
+    }
+}
+
+// Export for window.Jounce global
+if (typeof window !== 'undefined') {
+    window.Jounce = {
+        h,
+        mountComponent,
+        unmount,
+        onCleanup,
+        createContext,
+        useContext,
+        Provide,
+        enableTestIds,
+        enableDevtools,
+        trackSignal,
+        escape,
+        unsafe_raw,
+        memoize,
+        sleep,
+        EventBus,
+        JsValue,
+        I18n,
+        HttpError,
+        rpcSignal,
+        RPCClient,
+    };
+}
@@ -1,9 +1,11 @@
 // Auto-generated Jounce Client Bundle
 // DO NOT EDIT - Generated by Jounce compiler
 
-import { h, RPCClient, mountComponent } from './client-runtime.js';
+import { h, RPCClient, mountComponent, sleep, enableDevtools, trackSignal as __jounce_track } from './client-runtime.js';
 import { signal, computed, effect, batch } from './reactivity.js';
 
+enableDevtools();
+
 // Node.js crypto module (for tests and server-side code)
 let __nodeCrypto;
 try {
@@ -262,6 +264,9 @@ if (!Object.prototype.clone) {
 if (!String.from_char_code) {
   String.from_char_code = function(code) { return String.fromCharCode(code); };
 }
+if (!String.from_code_point) {
+  String.from_code_point = function(code) { return String.fromCodePoint(code); };
+}
 if (!String.new) {
   String.new = function() {
     const sb = { __value: "" };
@@ -308,6 +313,10 @@ Option.prototype.is_none = function() { return this.variant === "None"; };
 Option.prototype.unwrap = function() { if (this.variant === "Some") return this.data; throw new Error("Called unwrap on None"); };
 Option.prototype.unwrap_or = function(default_val) { return this.variant === "Some" ? this.data : default_val; };
 
+// The ? operator: an Err or None returns it from the function, which catches this
+class __JounceEarlyReturn { constructor(value) { this.value = value; } }
+function __jounce_try(v) { if (v.variant === "Ok" || v.variant === "Some") return v.data; throw new __JounceEarlyReturn(v); }
+
 // HashMap<K, V> is a JavaScript Map
 const HashMap = Map;
 HashMap.new = function() { return new Map(); };
@@ -337,59 +346,9 @@ const client = new RPCClient(window.location.origin + '/_rpc');
 // Implementations
 // Client function implementations
 // Shared utility functions
-export function App() {
-  return h('div', { class: "container" }, h('header', { class: "header" }, h('h1', "Markdown Previewer 📝"), h('p', { class: "subtitle" }, "Write markdown on the left, see HTML preview on the right")), h('div', { class: "editor-container" }, h('div', { class: "editor-pane" }, h('div', { class: "pane-header" }, h('h2', "Markdown Input"), h('span', { class: "badge" }, "Editor")), h('textarea', { class: "markdown-input", placeholder: "# Welcome to Markdown!\n\n## Features\n- **Bold text**\n- *Italic text*\n- `Code snippets`\n- [Links](https://example.com)\n\n```javascript\nconst code = 'blocks';\n```\n\n> Blockquotes are cool too!\n\n---\n\nTry typing some markdown!" }, "# Hello, Jounce! 🚀
-
-## What is Markdown?
-
-Markdown is a **lightweight markup language** for creating formatted text.
-
-### Features
-
-- Easy to write
-- Easy to read
-- Converts to HTML
-
-### Code Example
-
-```jounce
-let greeting = "Hello, World!";
-console.log(greeting);
-```
-
-### Formatting
-
-You can use:
-- **Bold text** with `**text**`
-- *Italic text* with `*text*`
-- `Inline code` with backticks
-
-### Links
-
-Visit [Jounce](https://github.com/jounce) for more!
-
----
-
-> "Markdown is a simple way to format text that looks great everywhere."
-
-### Task List
-
-- [x] Create markdown parser
-- [x] Add syntax highlighting
-- [ ] Build live preview
-- [ ] Deploy to production
-
-**Try editing the markdown!** The preview updates as you type.")), h('div', { class: "preview-pane" }, h('div', { class: "pane-header" }, h('h2', "HTML Preview"), h('span', { class: "badge" }, "Live")), h('div', { class: "markdown-preview" }, h('h1', "Hello, Jounce! 🚀"), h('h2', "What is Markdown?"), h('p', "Markdown is a", h('strong', "lightweight markup language"), "for", "creating formatted text."), h('h3', "Features"), h('ul', h('li', "Easy to write"), h('li', "Easy to read"), h('li', "Converts to HTML")), h('h3', "Code Example"), h('pre', h('code', { class: "language-jounce" }, "let greeting = "Hello, World!";
-console.log(greeting);")), h('h3', "Formatting"), h('p', "You can use:"), h('ul', h('li', h('strong', "Bold text"), "with", h('code', "**text**")), h('li', h('em', "Italic text"), "with", h('code', "*text*")), h('li', h('code', "Inline code"), "with", "backticks")), h('h3', "Links"), h('p', "Visit", h('a', { href: "https://github.com/jounce" }, "Jounce"), "for", "more!"), h('hr', null), h('blockquote', h('p', ""Markdown is a simple way to format text that looks great everywhere."")), h('h3', "Task List"), h('ul', h('li', "✓ Create markdown parser"), h('li', "✓ Add syntax highlighting"), h('li', "☐ Build live preview"), h('li', "☐ Deploy to production")), h('p', h('strong', "Try editing the markdown!"), "The", "preview updates as you type.")))), h('footer', { class: "info" }, h('p', h('strong', "App 3: Markdown Previewer")), h('p', "This demo shows static markdown rendering. Interactive preview with signal and computed coming soon!"), h('p', "Packages: jounce-markdown for parsing and jounce-sanitizer for XSS protection")));
-
-}
-
 export function main() {
-  console.log("App 3: Markdown Previewer started!");
-  console.log("Features: Split-pane editor, markdown syntax reference");
-  console.log("Coming soon: Package integration for live markdown parsing!");
-  let app_html = App();
-  return console.log("Markdown previewer component created successfully!");
+  let match = 2;
+  return console.log(`${(match + 1)}`);
 
 }
 
@@ -398,6 +357,7 @@ const json = {
   parse: typeof parse !== 'undefined' ? parse : undefined,
   stringify: typeof stringify !== 'undefined' ? stringify : undefined,
   stringify_pretty: typeof stringify_pretty !== 'undefined' ? stringify_pretty : undefined,
+  stringify_indented: typeof stringify_indented !== 'undefined' ? stringify_indented : undefined,
   // Helper functions for creating JSON values
   null_: () => ({ variant: 'Null' }),
   bool: (b) => ({ variant: 'Bool', data: b }),
@@ -468,10 +428,18 @@ const yaml = {
 };
 
 // UI Components
+// Stylesheet
+if (!document.querySelector("link[rel=\"stylesheet\"][href=\"styles.css\"]")) {
+  const link = document.createElement('link');
+  link.rel = 'stylesheet';
+  link.href = "styles.css";
+  document.head.appendChild(link);
+}
+
 // Initialize application
 window.addEventListener('DOMContentLoaded', () => {
   console.log('Jounce client initialized');
-  mountComponent(App);
+  main();
 });
 
 //# sourceMappingURL=client.js.map
@@ -0,0 +1,199 @@
+// Jounce Server Runtime
+// Provides HTTP server and RPC infrastructure for server-side code
+
+const http = require('http');
+const url = require('url');
+const fs = require('fs');
+const path = require('path');
+const { I18n, JsValue, memoize } = require('./shared-runtime.cjs');
+
+class HttpServer {
+    constructor(port = 3000) {
+        this.port = port;
+        this.rpcHandlers = new Map();
+        this.routes = [];
+        this.server = null;
+    }
+
+    // Register an RPC handler
+    rpc(name, handler) {
+        this.rpcHandlers.set(name, handler);
+    }
+
+    // Register a handler for a path like /todos/:id. Routes match in the
+    // order they are registered; the handler gets the params as strings.
+    route(pattern, handler) {
+        this.routes.push({ segments: pattern.split('/').filter(Boolean), handler });
+    }
+
+    // The first route matching a path, with its params
+    matchRoute(pathname) {
+        const segments = pathname.split('/').filter(Boolean);
+        for (const route of this.routes) {
+            if (route.segments.length !== segments.length) {
+                continue;
+            }
+            const params = {};
+            const matched = route.segments.every((segment, i) => {
+                if (!segment.startsWith(':')) {
+                    return segment === segments[i];
+                }
+                try {
+                    params[segment.slice(1)] = decodeURIComponent(segments[i]);
+                    return true;
+                } catch (e) {
+                    return false;
+                }
+            });
+            if (matched) {
+                return { handler: route.handler, params };
+            }
+        }
+        return null;
+    }
+
+    // Start the HTTP server
+    start() {
+        this.server = http.createServer(async (req, res) => {
+            const parsedUrl = url.parse(req.url, true);
+            const pathname = parsedUrl.pathname;
+
+            // Serve static files
+            if (pathname === '/' || pathname === '/index.html') {
+                this.serveFile(res, 'index.html', 'text/html');
+            } else if (pathname === '/client.js') {
+                this.serveFile(res, 'client.js', 'application/javascript');
+            } else if (pathname === '/client-runtime.js') {
+                this.serveFile(res, 'client-runtime.js', 'application/javascript');
+            } else if (pathname === '/reactivity.js') {
+                this.serveFile(res, 'reactivity.js', 'application/javascript');
+            } else if (pathname === '/shared-runtime.js') {
+                this.serveFile(res, 'shared-runtime.js', 'application/javascript');
+            } else if (pathname === '/styles.css') {
+                this.serveFile(res, 'styles.css', 'text/css');
+            } else if (pathname === '/app.wasm') {
+                this.serveFile(res, 'app.wasm', 'application/wasm');
+            } else if (pathname.startsWith('/rpc/')) {
+                // Handle RPC calls
+                const rpcName = pathname.slice(5); // Remove '/rpc/' prefix
+                await this.handleRPC(rpcName, req, res);
+            } else {
+                const route = this.matchRoute(pathname);
+                if (route) {
+                    await this.handleRoute(route, res);
+                } else {
+                    res.writeHead(404, { 'Content-Type': 'text/plain' });
+                    res.end('Not Found');
+                }
+            }
+        });
+
+        this.server.listen(this.port, () => {
+            console.log(`Server running at http://localhost:${this.port}`);
+        });
+    }
+
+    // Serve a static file
+    serveFile(res, filename, contentType) {
+        const filePath = path.join(__dirname, filename);
+        fs.readFile(filePath, (err, data) => {
+            if (err) {
+                res.writeHead(404, { 'Content-Type': 'text/plain' });
+                res.end('File not found');
+            } else {
+                res.writeHead(200, { 'Content-Type': contentType });
+                res.end(data);
+            }
+        });
+    }
+
+    // Handle RPC call
+    async handleRPC(name, req, res) {
+        const handler = this.rpcHandlers.get(name);
+        if (!handler) {
+            res.writeHead(404, { 'Content-Type': 'application/json' });
+            res.end(JSON.stringify({ error: 'RPC handler not found' }));
+            return;
+        }
+
+        // Read request body
+        let body = '';
+        req.on('data', chunk => {
+            body += chunk.toString();
+        });
+
+        req.on('end', async () => {
+            try {
+                const params = JSON.parse(body || '{}');
+                const result = await handler(params);
+                res.writeHead(200, { 'Content-Type': 'application/json' });
+                res.end(JSON.stringify(result));
+            } catch (error) {
+                res.writeHead(500, { 'Content-Type': 'application/json' });
+                res.end(JSON.stringify({ error: error.message }));
+            }
+        });
+    }
+
+    // Handle a matched route; a param that doesn't parse is a 404
+    async handleRoute(route, res) {
+        try {
+            const result = await route.handler(route.params);
+            res.writeHead(200, { 'Content-Type': 'application/json' });
+            res.end(JSON.stringify(result));
+        } catch (error) {
+            const status = error instanceof RouteParamError ? 404 : 500;
+            res.writeHead(status, { 'Content-Type': 'application/json' });
+            res.end(JSON.stringify({ error: error.message }));
+        }
+    }
+}
+
+// Thrown when a route param can't be parsed as its handler's parameter type
+class RouteParamError extends Error {}
+
+const ROUTE_PARAM_FORMATS = {
+    int: /^-?\d+$/,
+    float: /^-?\d+(\.\d+)?([eE][+-]?\d+)?$/,
+    bool: /^(true|false)$/,
+};
+
+// Parse a route param for a handler parameter of the given kind
+// ('int', 'float', 'bool' or 'string')
+function routeParam(params, name, kind) {
+    const raw = params[name];
+    if (kind === 'string') {
+        return raw;
+    }
+    const format = ROUTE_PARAM_FORMATS[kind];
+    if (format && format.test(raw)) {
+        const value = kind === 'bool' ? raw === 'true' : Number(raw);
+        if (kind !== 'int' || Number.isSafeInteger(value)) {
+            return value;
+        }
+    }
+    throw new RouteParamError(`Invalid route param \`${name}\`: expected ${kind}, got ${JSON.stringify(raw)}`);
+}
+
+// WebAssembly loading utility
+function loadWasm(wasmPath) {
+    const wasmBytes = fs.readFileSync(wasmPath);
+    const wasmModule = new WebAssembly.Module(wasmBytes);
+    return new WebAssembly.Instance(wasmModule, {});
+}
+
+// sleep(ms): a promise that resolves after `ms` milliseconds
+function sleep(ms) {
+    return new Promise(resolve => setTimeout(resolve, ms));
+}
+
+module.exports = {
+    HttpServer,
+    loadWasm,
+    routeParam,
+    RouteParamError,
+    memoize,
+    sleep,
+    JsValue,
+    I18n
+};
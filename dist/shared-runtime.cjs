@@ -0,0 +1,306 @@
+// Generated from shared-runtime.js by jnc; edit that file instead
+
+// Jounce Shared Runtime
+// Helpers more than one runtime needs: client-runtime.js, server-runtime.js
+// and the test renderer (snapshot.js) import them from here, so each exists once.
+// The last two are CommonJS and load shared-runtime.cjs, which jnc generates
+// from this file (src/runtime_files.rs)
+
+// Escaping: the same rules as src/escape.rs. escape() is for code that builds
+// HTML or CSS strings, like the test renderer; the client runtime sets text and
+// attributes through DOM APIs, which never parse them as markup. unsafe_raw()
+// is the only way to insert markup.
+const HTML_ESCAPES = { '&': '&amp;', '<': '&lt;', '>': '&gt;', '"': '&quot;', "'": '&#39;' };
+
+function escape(value, mode = 'text') {
+    const text = String(value);
+    switch (mode) {
+        case 'text':
+            return text.replace(/[&<>"']/g, c => HTML_ESCAPES[c]);
+        case 'attribute':
+            // Newlines and tabs would otherwise normalize to spaces
+            return text.replace(/[&<>"'\n\r\t]/g, c => HTML_ESCAPES[c] || `&#${c.charCodeAt(0)};`);
+        case 'css':
+            // Cannot end the declaration or rule, or close a <style> element
+            return text.replace(/[\\"';{}<>`\u0000-\u001f\u007f-\u009f]/g, c => `\\${c.charCodeAt(0).toString(16)} `);
+        case 'css-length':
+            // style={{ width: w }}: a number other than 0 is in px
+            return escape(typeof value === 'number' && value !== 0 ? `${value}px` : value, 'css');
+        default:
+            throw new Error(`Unknown escape mode '${mode}'`);
+    }
+}
+
+// Markup the caller vouches for, rendered as-is
+class RawHtml {
+    constructor(html) {
+        this.html = String(html);
+    }
+
+    toString() {
+        return this.html;
+    }
+}
+
+function unsafe_raw(html) {
+    return new RawHtml(html);
+}
+
+// Memoization for @memo functions: remembers the results of the last
+// `capacity` calls. keyKinds has one entry per parameter: 'ref' compares with
+// === (primitives by value, objects by identity), 'eq' compares structs that
+// derive Eq field by field.
+function memoize(fn, capacity, keyKinds) {
+    const entries = []; // least recently used first
+
+    return function(...args) {
+        for (let i = entries.length - 1; i >= 0; i--) {
+            const entry = entries[i];
+            if (memoArgsMatch(entry.args, args, keyKinds)) {
+                if (i !== entries.length - 1) {
+                    entries.splice(i, 1);
+                    entries.push(entry);
+                }
+                return entry.value;
+            }
+        }
+
+        const value = fn.apply(this, args);
+        entries.push({ args, value });
+        if (entries.length > capacity) {
+            entries.shift();
+        }
+        return value;
+    };
+}
+
+function memoArgsMatch(cached, args, keyKinds) {
+    if (cached.length !== args.length) {
+        return false;
+    }
+    return args.every((arg, i) =>
+        keyKinds[i] === 'eq' ? structEquals(cached[i], arg) : cached[i] === arg
+    );
+}
+
+function structEquals(a, b) {
+    if (a === b) {
+        return true;
+    }
+    if (typeof a !== 'object' || typeof b !== 'object' || a === null || b === null) {
+        return false;
+    }
+    const keys = Object.keys(a);
+    if (keys.length !== Object.keys(b).length) {
+        return false;
+    }
+    return keys.every(key => structEquals(a[key], b[key]));
+}
+
+// JsValue: a value from extern JavaScript whose shape is not assumed. Every
+// read is checked when it happens: a missing property or out-of-range index is
+// an undefined JsValue, a conversion to a typed value is None when the value
+// has another type, and only call() on a non-function throws.
+class JsValue {
+    constructor(raw, receiver = undefined) {
+        this.raw = raw;
+        this.receiver = receiver; // `this` for call() when read with get()
+    }
+
+    static wrap(raw) {
+        return raw instanceof JsValue ? raw : new JsValue(raw);
+    }
+
+    static unwrap(value) {
+        return value instanceof JsValue ? value.raw : value;
+    }
+
+    // Options are plain { variant, data } objects unless a bundle supplies its own
+    static useOption(some, none) {
+        jsValueSome = some;
+        jsValueNone = none;
+    }
+
+    // JsValue::from_json(value): plain JSON data as a JS value
+    static from_json(json) {
+        return new JsValue(jsonToRaw(json));
+    }
+
+    get(key) {
+        const raw = this.raw;
+        if (raw === null || raw === undefined) {
+            return new JsValue(undefined);
+        }
+        return new JsValue(raw[key], raw);
+    }
+
+    index(i) {
+        const raw = this.raw;
+        const inRange = raw !== null && raw !== undefined && typeof raw !== 'function'
+            && typeof raw.length === 'number' && Number.isInteger(i) && i >= 0 && i < raw.length;
+        return new JsValue(inRange ? raw[i] : undefined);
+    }
+
+    call(args = []) {
+        if (typeof this.raw !== 'function') {
+            throw new TypeError(`JsValue.call: value is not a function (got ${describeRaw(this.raw)})`);
+        }
+        return JsValue.wrap(this.raw.apply(this.receiver, args.map(JsValue.unwrap)));
+    }
+
+    is_null() {
+        return this.raw === null;
+    }
+
+    is_undefined() {
+        return this.raw === undefined;
+    }
+
+    as_string() {
+        return typeof this.raw === 'string' ? jsValueSome(this.raw) : jsValueNone;
+    }
+
+    as_f64() {
+        return typeof this.raw === 'number' ? jsValueSome(this.raw) : jsValueNone;
+    }
+
+    as_bool() {
+        return typeof this.raw === 'boolean' ? jsValueSome(this.raw) : jsValueNone;
+    }
+
+    // Some(JsonValue) for plain data: null, booleans, finite numbers, strings,
+    // arrays and plain objects of those, without cycles. None otherwise.
+    to_json() {
+        const json = rawToJson(this.raw, new Set());
+        return json === undefined ? jsValueNone : jsValueSome(json);
+    }
+}
+
+let jsValueSome = data => ({ variant: 'Some', data });
+let jsValueNone = { variant: 'None' };
+
+function rawToJson(raw, seen) {
+    if (raw === null) {
+        return { variant: 'Null' };
+    }
+    switch (typeof raw) {
+        case 'boolean':
+            return { variant: 'Bool', data: raw };
+        case 'number':
+            return Number.isFinite(raw) ? { variant: 'Number', data: raw } : undefined;
+        case 'string':
+            return { variant: 'String', data: raw };
+        case 'object':
+            break;
+        default:
+            return undefined;
+    }
+
+    const proto = Object.getPrototypeOf(raw);
+    const isArray = Array.isArray(raw);
+    if (seen.has(raw) || (!isArray && proto !== Object.prototype && proto !== null)) {
+        return undefined;
+    }
+    seen.add(raw);
+    try {
+        if (isArray) {
+            const items = [];
+            for (const item of raw) {
+                const json = rawToJson(item, seen);
+                if (json === undefined) {
+                    return undefined;
+                }
+                items.push(json);
+            }
+            return { variant: 'Array', data: items };
+        }
+        const fields = {};
+        for (const key of Object.keys(raw)) {
+            const json = rawToJson(raw[key], seen);
+            if (json === undefined) {
+                return undefined;
+            }
+            fields[key] = json;
+        }
+        return { variant: 'Object', data: fields };
+    } finally {
+        seen.delete(raw);
+    }
+}
+
+function jsonToRaw(json) {
+    switch (json && json.variant) {
+        case 'Null':
+            return null;
+        case 'Bool':
+        case 'Number':
+        case 'String':
+            return json.data;
+        case 'Array':
+            return json.data.map(jsonToRaw);
+        case 'Object': {
+            const fields = json.data instanceof Map ? Object.fromEntries(json.data) : json.data;
+            const raw = {};
+            for (const key of Object.keys(fields)) {
+                raw[key] = jsonToRaw(fields[key]);
+            }
+            return raw;
+        }
+        default:
+            throw new TypeError(`JsValue::from_json: not a JsonValue (got ${describeRaw(json)})`);
+    }
+}
+
+function describeRaw(raw) {
+    if (raw === null) {
+        return 'null';
+    }
+    if (Array.isArray(raw)) {
+        return 'array';
+    }
+    return typeof raw;
+}
+
+// I18n: translations for t!("Add {count} items", count), compiled to
+// I18n.t("Add {count} items", { count: count }). I18n::set_locale(locale,
+// catalog) makes a catalog active; the catalog maps each message to its
+// translation, as a JsonValue object or a plain one. A message with no
+// translation in the locale (or its base language: fr-CA falls back to fr)
+// is shown as written.
+const i18nCatalogs = new Map();
+let i18nLocale = null;
+
+const I18n = {
+    set_locale(locale, catalog) {
+        if (catalog !== undefined) {
+            const raw = catalog && typeof catalog.variant === 'string' ? jsonToRaw(catalog) : catalog;
+            i18nCatalogs.set(locale, raw || {});
+        }
+        i18nLocale = locale;
+    },
+
+    locale() {
+        return i18nLocale;
+    },
+
+    t(message, values = {}) {
+        const translated = i18nLookup(i18nLocale, message) ?? message;
+        return translated.replace(/\{(\w+)\}/g, (placeholder, name) =>
+            Object.prototype.hasOwnProperty.call(values, name) ? String(values[name]) : placeholder);
+    },
+};
+
+function i18nLookup(locale, message) {
+    if (locale === null) {
+        return undefined;
+    }
+    for (const candidate of [locale, locale.split('-')[0]]) {
+        const translation = (i18nCatalogs.get(candidate) || {})[message];
+        if (typeof translation === 'string' && translation !== '') {
+            return translation;
+        }
+    }
+    return undefined;
+}
+
+module.exports = { escape, RawHtml, unsafe_raw, memoize, JsValue, I18n };
@@ -0,0 +1,174 @@
+/**
+ * Jounce Devtools
+ *
+ * A registry of mounted component instances on window.__JOUNCE_DEVTOOLS__.
+ * Each instance has an id that only ever increases, links to its parent and
+ * children, its props (debug form) and the signals it owns.
+ *
+ * Dev builds import enableDevtools and trackSignal from here; `jnc compile
+ * --minify` neither imports nor ships this file.
+ */
+
+import { installDevtools } from './client-runtime.js';
+
+let devtools = null;
+
+class DevtoolsRegistry {
+    constructor() {
+        this.nextId = 1;
+        this.instances = new Map();
+        this.roots = [];
+        this.listeners = new Set();
+        // The instance rendering now
+        this.current = null;
+    }
+
+    // listener(event, registry) runs after every mount, unmount and signal
+    // change; events are { type: 'mount' | 'unmount' | 'signal', id, ... }
+    subscribe(listener) {
+        this.listeners.add(listener);
+        return () => this.listeners.delete(listener);
+    }
+
+    // Plain snapshot of the component tree, roots first
+    tree() {
+        const snapshot = (id) => {
+            const instance = this.instances.get(id);
+            return {
+                id,
+                name: instance.name,
+                props: instance.props,
+                signals: instance.signals.map(({ name, value }) => ({ name, value })),
+                children: instance.children.map(snapshot),
+            };
+        };
+        return this.roots.map(snapshot);
+    }
+
+    open(component, props) {
+        const meta = component.__jounceMeta || { name: component.name, props: [] };
+        const instance = {
+            id: this.nextId++,
+            name: meta.name || component.name || 'Anonymous',
+            propTypes: meta.props,
+            props: debugProps(props),
+            parent: this.current ? this.current.id : null,
+            children: [],
+            signals: [],
+        };
+        this.instances.set(instance.id, instance);
+        (this.current ? this.current.children : this.roots).push(instance.id);
+        this.current = instance;
+        return instance;
+    }
+
+    // The instance's render is done; its parent is rendering again
+    leave(instance) {
+        this.current = this.instances.get(instance.parent) || null;
+    }
+
+    close(instance) {
+        if (!this.instances.delete(instance.id)) {
+            return;
+        }
+        const parent = this.instances.get(instance.parent);
+        const siblings = parent ? parent.children : this.roots;
+        const index = siblings.indexOf(instance.id);
+        if (index !== -1) {
+            siblings.splice(index, 1);
+        }
+        this.emit({ type: 'unmount', id: instance.id });
+    }
+
+    emit(event) {
+        for (const listener of Array.from(this.listeners)) {
+            listener(event, this);
+        }
+    }
+}
+
+export function enableDevtools() {
+    if (!devtools) {
+        devtools = new DevtoolsRegistry();
+        if (typeof window !== 'undefined') {
+            window.__JOUNCE_DEVTOOLS__ = devtools;
+        }
+        installDevtools(devtools);
+    }
+    return devtools;
+}
+
+// Record a signal as owned by the component rendering now, under its binding
+// name, and report every change to devtools listeners
+export function trackSignal(sig, name) {
+    if (!devtools || !devtools.current) {
+        return sig;
+    }
+    let proto = Object.getPrototypeOf(sig);
+    while (proto && !Object.getOwnPropertyDescriptor(proto, 'value')) {
+        proto = Object.getPrototypeOf(proto);
+    }
+    const accessor = proto && Object.getOwnPropertyDescriptor(proto, 'value');
+    if (!accessor || !accessor.set) {
+        return sig;
+    }
+
+    const instance = devtools.current;
+    const entry = { name, raw: sig._value, value: debugValue(sig._value) };
+    instance.signals.push(entry);
+    Object.defineProperty(sig, 'value', {
+        configurable: true,
+        get() {
+            return accessor.get.call(this);
+        },
+        set(value) {
+            accessor.set.call(this, value);
+            if (!Object.is(entry.raw, value) && devtools.instances.has(instance.id)) {
+                entry.raw = value;
+                entry.value = debugValue(value);
+                devtools.emit({ type: 'signal', id: instance.id, name, value: entry.value });
+            }
+        },
+    });
+    return sig;
+}
+
+// Debug representation of a runtime value, as `{:?}` would print it
+function debugValue(value, depth = 0) {
+    if (value === null || value === undefined) {
+        return '()';
+    }
+    if (typeof value === 'string') {
+        return JSON.stringify(value);
+    }
+    if (typeof value === 'function') {
+        return `<fn ${value.name || 'anonymous'}>`;
+    }
+    if (typeof value !== 'object') {
+        return String(value);
+    }
+    if (typeof Node !== 'undefined' && value instanceof Node) {
+        return value.tagName ? `<${value.tagName.toLowerCase()}>` : '<node>';
+    }
+    if (depth > 3) {
+        return '..';
+    }
+    if (Array.isArray(value)) {
+        return `[${value.map(item => debugValue(item, depth + 1)).join(', ')}]`;
+    }
+    if (typeof value.variant === 'string') {
+        return 'data' in value && value.data !== undefined
+            ? `${value.variant}(${debugValue(value.data, depth + 1)})`
+            : value.variant;
+    }
+    const fields = Object.entries(value).map(([key, field]) => `${key}: ${debugValue(field, depth + 1)}`);
+    return fields.length > 0 ? `{ ${fields.join(', ')} }` : '{}';
+}
+
+function debugProps(props) {
+    const result = {};
+    for (const [key, value] of Object.entries(props || {})) {
+        result[key] = debugValue(value);
+    }
+    return result;
+}
@@ -0,0 +1,83 @@
+/**
+ * Jounce Runtime Profiling
+ *
+ * Compiled into the client bundle by `jnc compile --profile-runtime`; without
+ * the flag none of this, nor the calls to it, is emitted.
+ *
+ * Every component render and every effect and computed evaluation inside a
+ * component runs through __jounce_measure, which brackets it with
+ * performance marks and records a measure named `jounce:<Component>:<kind>`
+ * (kind is render, effect or computed), so the browser's performance panel
+ * shows them on its timeline. A render's time includes the components it
+ * renders.
+ *
+ * window.__JOUNCE_PROFILE__.summary() aggregates what ran so far into a
+ * plain object, keyed by component and then kind:
+ *
+ *     { "TodoList": { "render": { "count": 3, "total": 4.2, "max": 2.1 },
+ *                     "effect": { "count": 5, "total": 0.8, "max": 0.3 } } }
+ *
+ * Durations are in milliseconds. reset() clears the counts.
+ *
+ * This file is inlined into the ES module bundle as is, so it declares
+ * globals and exports nothing.
+ */
+
+const __jounce_profile_stats = new Map();
+
+function __jounce_profile_record(component, kind, duration) {
+    const key = `${component}\u0000${kind}`;
+    const stat = __jounce_profile_stats.get(key);
+    if (stat) {
+        stat.count += 1;
+        stat.total += duration;
+        stat.max = Math.max(stat.max, duration);
+    } else {
+        __jounce_profile_stats.set(key, { component, kind, count: 1, total: duration, max: duration });
+    }
+}
+
+// Run `run` now, timing it as one `kind` for `component`
+function __jounce_measure(component, kind, run) {
+    const name = `jounce:${component}:${kind}`;
+    const start = performance.now();
+    performance.mark(`${name}:start`);
+    try {
+        return run();
+    } finally {
+        const duration = performance.now() - start;
+        performance.mark(`${name}:end`);
+        performance.measure(name, `${name}:start`, `${name}:end`);
+        performance.clearMarks(`${name}:start`);
+        performance.clearMarks(`${name}:end`);
+        __jounce_profile_record(component, kind, duration);
+    }
+}
+
+// `fn`, timed on every call; for the functions effect() and computed() rerun
+function __jounce_profiled(component, kind, fn) {
+    return function (...args) {
+        return __jounce_measure(component, kind, () => fn.apply(this, args));
+    };
+}
+
+const __jounce_profile = {
+    // A fresh plain object (not a JsValue) each call, so it can be logged,
+    // passed to console.table or JSON.stringify'd directly
+    summary() {
+        const summary = {};
+        for (const { component, kind, count, total, max } of __jounce_profile_stats.values()) {
+            summary[component] = summary[component] || {};
+            summary[component][kind] = { count, total, max };
+        }
+        return summary;
+    },
+
+    reset() {
+        __jounce_profile_stats.clear();
+    },
+};
+
+if (typeof window !== 'undefined') {
+    window.__JOUNCE_PROFILE__ = __jounce_profile;
+}
@@ -5,11 +5,13 @@ const http = require('http');
 const url = require('url');
 const fs = require('fs');
 const path = require('path');
+const { I18n, JsValue, memoize } = require('./shared-runtime.cjs');
 
 class HttpServer {
     constructor(port = 3000) {
         this.port = port;
         this.rpcHandlers = new Map();
+        this.routes = [];
         this.server = null;
     }
 
@@ -18,6 +20,38 @@ class HttpServer {
         this.rpcHandlers.set(name, handler);
     }
 
+    // Register a handler for a path like /todos/:id. Routes match in the
+    // order they are registered; the handler gets the params as strings.
+    route(pattern, handler) {
+        this.routes.push({ segments: pattern.split('/').filter(Boolean), handler });
+    }
+
+    // The first route matching a path, with its params
+    matchRoute(pathname) {
+        const segments = pathname.split('/').filter(Boolean);
+        for (const route of this.routes) {
+            if (route.segments.length !== segments.length) {
+                continue;
+            }
+            const params = {};
+            const matched = route.segments.every((segment, i) => {
+                if (!segment.startsWith(':')) {
+                    return segment === segments[i];
+                }
+                try {
+                    params[segment.slice(1)] = decodeURIComponent(segments[i]);
+                    return true;
+                } catch (e) {
+                    return false;
+                }
+            });
+            if (matched) {
+                return { handler: route.handler, params };
+            }
+        }
+        return null;
+    }
+
     // Start the HTTP server
     start() {
         this.server = http.createServer(async (req, res) => {
@@ -33,6 +67,10 @@ class HttpServer {
                 this.serveFile(res, 'client-runtime.js', 'application/javascript');
             } else if (pathname === '/reactivity.js') {
                 this.serveFile(res, 'reactivity.js', 'application/javascript');
+            } else if (pathname === '/shared-runtime.js') {
+                this.serveFile(res, 'shared-runtime.js', 'application/javascript');
+            } else if (pathname === '/devtools.js') {
+                this.serveFile(res, 'devtools.js', 'application/javascript');
             } else if (pathname === '/styles.css') {
                 this.serveFile(res, 'styles.css', 'text/css');
             } else if (pathname === '/app.wasm') {
@@ -42,8 +80,13 @@ class HttpServer {
                 const rpcName = pathname.slice(5); // Remove '/rpc/' prefix
                 await this.handleRPC(rpcName, req, res);
             } else {
-                res.writeHead(404, { 'Content-Type': 'text/plain' });
-                res.end('Not Found');
+                const route = this.matchRoute(pathname);
+                if (route) {
+                    await this.handleRoute(route, res);
+                } else {
+                    res.writeHead(404, { 'Content-Type': 'text/plain' });
+                    res.end('Not Found');
+                }
             }
         });
 
@@ -93,6 +136,45 @@ class HttpServer {
             }
         });
     }
+
+    // Handle a matched route; a param that doesn't parse is a 404
+    async handleRoute(route, res) {
+        try {
+            const result = await route.handler(route.params);
+            res.writeHead(200, { 'Content-Type': 'application/json' });
+            res.end(JSON.stringify(result));
+        } catch (error) {
+            const status = error instanceof RouteParamError ? 404 : 500;
+            res.writeHead(status, { 'Content-Type': 'application/json' });
+            res.end(JSON.stringify({ error: error.message }));
+        }
+    }
+}
+
+// Thrown when a route param can't be parsed as its handler's parameter type
+class RouteParamError extends Error {}
+
+const ROUTE_PARAM_FORMATS = {
+    int: /^-?\d+$/,
+    float: /^-?\d+(\.\d+)?([eE][+-]?\d+)?$/,
+    bool: /^(true|false)$/,
+};
+
+// Parse a route param for a handler parameter of the given kind
+// ('int', 'float', 'bool' or 'string')
+function routeParam(params, name, kind) {
+    const raw = params[name];
+    if (kind === 'string') {
+        return raw;
+    }
+    const format = ROUTE_PARAM_FORMATS[kind];
+    if (format && format.test(raw)) {
+        const value = kind === 'bool' ? raw === 'true' : Number(raw);
+        if (kind !== 'int' || Number.isSafeInteger(value)) {
+            return value;
+        }
+    }
+    throw new RouteParamError(`Invalid route param \`${name}\`: expected ${kind}, got ${JSON.stringify(raw)}`);
 }
 
 // WebAssembly loading utility
@@ -102,7 +184,18 @@ function loadWasm(wasmPath) {
     return new WebAssembly.Instance(wasmModule, {});
 }
 
+// sleep(ms): a promise that resolves after `ms` milliseconds
+function sleep(ms) {
+    return new Promise(resolve => setTimeout(resolve, ms));
+}
+
 module.exports = {
     HttpServer,
-    loadWasm
+    loadWasm,
+    routeParam,
+    RouteParamError,
+    memoize,
+    sleep,
+    JsValue,
+    I18n
 };
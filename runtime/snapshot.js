@@ -0,0 +1,345 @@
+/**
+ * Jounce Test Rendering and Snapshots
+ *
+ * Included in the runner `jnc test` generates. JSX in tests renders to plain
+ * objects, `render_to_string` turns them into HTML, and `assert_snapshot!`
+ * compares HTML against an inline string or a snapshot file. Text and
+ * attribute values are escaped; only unsafe_raw() markup renders as written.
+ *
+ * Both sides are normalized before comparing: attributes are sorted, runs of
+ * whitespace in text collapse to one space (except inside <pre> and
+ * <textarea>), and every element is printed on its own line. Attribute order
+ * and formatting churn therefore never fail a snapshot.
+ *
+ * File snapshots live next to the test file in __snapshots__/<name>.snap:
+ *
+ *     # Jounce snapshots for tests/card_test.jnc
+ *
+ *     == test_card #1 ==
+ *     <div class="card">
+ *       <h2>Hi</h2>
+ *     </div>
+ *
+ * Each entry is keyed by test name and the assertion's position within the
+ * test. A content line starting with `==` or `\` is escaped with a leading `\`.
+ * Missing snapshots are written on the first run (but fail when CI is set);
+ * JOUNCE_UPDATE_SNAPSHOTS=1 rewrites the ones that changed.
+ */
+
+const VOID_ELEMENTS = new Set([
+    'area', 'base', 'br', 'col', 'embed', 'hr', 'img', 'input',
+    'link', 'meta', 'source', 'track', 'wbr',
+]);
+const PRESERVE_WHITESPACE = new Set(['pre', 'textarea']);
+// Elements whose text is CSS or JavaScript, not HTML
+const RAW_TEXT_ELEMENTS = new Set(['style', 'script']);
+// Attributes that spell booleans out as "true" and "false"
+const BOOLEAN_TEXT = /^(?:aria-|data-)|^(?:contenteditable|draggable|spellcheck)$/i;
+
+// --- Rendering -------------------------------------------------------------
+
+// Marks the elements h() built, so an object from data that happens to
+// have a `tag` renders as text rather than as markup
+const ELEMENT = Symbol('jounce.element');
+
+// JSX createElement for tests: components run immediately, elements stay data
+function h(tag, props, ...children) {
+    if (typeof tag === 'function') {
+        return tag(props || {}, children);
+    }
+    return { [ELEMENT]: true, tag, props: props || {}, children: children.flat() };
+}
+
+// Escaping as in the client runtime: `unsafe_html` compiles to unsafe_raw(),
+// and nothing else renders unescaped
+const { escape, RawHtml, unsafe_raw } = require('./shared-runtime.cjs');
+
+function styleToString(style) {
+    return Object.entries(style)
+        .map(([name, value]) => `${name.replace(/[A-Z]/g, c => '-' + c.toLowerCase())}: ${value}`)
+        .join('; ');
+}
+
+function renderAttributes(props) {
+    let html = '';
+    for (const [key, value] of Object.entries(props)) {
+        // Keys and event handlers never reach the HTML
+        if (key === 'key' || key === 'inner_html' || typeof value === 'function' || value === null || value === undefined) {
+            continue;
+        }
+        const name = key === 'className' ? 'class' : key;
+        if (typeof value === 'boolean' && !BOOLEAN_TEXT.test(name)) {
+            // Present or absent, like `disabled`
+            html += value ? ` ${name}` : '';
+        } else if (name === 'style' && typeof value === 'object') {
+            html += ` style="${escape(styleToString(value), 'attribute')}"`;
+        } else {
+            html += ` ${name}="${escape(value, 'attribute')}"`;
+        }
+    }
+    return html;
+}
+
+function render_to_string(node) {
+    if (node === null || node === undefined || node === false) {
+        return '';
+    }
+    if (Array.isArray(node)) {
+        return node.map(render_to_string).join('');
+    }
+    if (node instanceof RawHtml) {
+        return node.html;
+    }
+    if (typeof node !== 'object' || !node[ELEMENT]) {
+        return escape(node, 'text');
+    }
+    const open = `<${node.tag}${renderAttributes(node.props)}>`;
+    if (VOID_ELEMENTS.has(node.tag)) {
+        return open;
+    }
+    const contents = 'inner_html' in node.props ? [node.props.inner_html] : node.children;
+    const render = RAW_TEXT_ELEMENTS.has(node.tag)
+        ? child => (typeof child === 'string' ? child : render_to_string(child))
+        : render_to_string;
+    return `${open}${contents.map(render).join('')}</${node.tag}>`;
+}
+
+// --- Normalization ---------------------------------------------------------
+
+const TOKEN = /<!--[\s\S]*?-->|<\/([A-Za-z][\w:.-]*)\s*>|<([A-Za-z][\w:.-]*)((?:\s+[^\s"'>\/=]+(?:\s*=\s*(?:"[^"]*"|'[^']*'|[^\s"'>]+))?)*)\s*(\/?)>|[^<]+|</g;
+const ATTRIBUTE = /([^\s"'>\/=]+)(?:\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s"'>]+)))?/g;
+
+function parseAttributes(source) {
+    const attributes = [];
+    for (const match of source.matchAll(ATTRIBUTE)) {
+        const value = match[2] ?? match[3] ?? match[4];
+        attributes.push({ name: match[1], value: value === undefined ? null : escapeQuotes(value) });
+    }
+    return attributes.sort((a, b) => (a.name < b.name ? -1 : a.name > b.name ? 1 : 0));
+}
+
+function parseHtml(html) {
+    const root = { tag: null, attributes: [], children: [] };
+    const stack = [root];
+    for (const match of html.matchAll(TOKEN)) {
+        const parent = stack[stack.length - 1];
+        const [token, closing, opening, attributes, selfClosing] = match;
+        if (token.startsWith('<!--')) {
+            continue;
+        } else if (closing) {
+            const index = stack.map(node => node.tag).lastIndexOf(closing);
+            if (index > 0) {
+                stack.length = index;
+            }
+        } else if (opening) {
+            const element = { tag: opening, attributes: parseAttributes(attributes), children: [] };
+            parent.children.push(element);
+            if (!selfClosing && !VOID_ELEMENTS.has(opening)) {
+                stack.push(element);
+            }
+        } else {
+            parent.children.push(escapeQuotes(token));
+        }
+    }
+    return root;
+}
+
+// Quotes are escaped in rendered output but often not in a written snapshot
+function escapeQuotes(text) {
+    return text.replace(/"/g, '&quot;').replace(/'/g, '&#39;');
+}
+
+function openTag(element) {
+    const attributes = element.attributes
+        .map(({ name, value }) => (value === null ? ` ${name}` : ` ${name}="${value}"`))
+        .join('');
+    return `<${element.tag}${attributes}>`;
+}
+
+function printRaw(node) {
+    if (typeof node === 'string') {
+        return node;
+    }
+    if (VOID_ELEMENTS.has(node.tag)) {
+        return openTag(node);
+    }
+    return `${openTag(node)}${node.children.map(printRaw).join('')}</${node.tag}>`;
+}
+
+function printNode(node, indent, lines) {
+    if (typeof node === 'string') {
+        const text = node.replace(/\s+/g, ' ').trim();
+        if (text) {
+            lines.push(indent + text);
+        }
+        return;
+    }
+    const open = openTag(node);
+    if (VOID_ELEMENTS.has(node.tag)) {
+        lines.push(indent + open);
+        return;
+    }
+    if (PRESERVE_WHITESPACE.has(node.tag)) {
+        lines.push(indent + printRaw(node));
+        return;
+    }
+    const children = node.children.filter(child => typeof child !== 'string' || child.trim());
+    if (children.length === 0) {
+        lines.push(`${indent}${open}</${node.tag}>`);
+    } else if (children.length === 1 && typeof children[0] === 'string') {
+        lines.push(`${indent}${open}${children[0].replace(/\s+/g, ' ').trim()}</${node.tag}>`);
+    } else {
+        lines.push(indent + open);
+        for (const child of children) {
+            printNode(child, indent + '  ', lines);
+        }
+        lines.push(`${indent}</${node.tag}>`);
+    }
+}
+
+// Canonical, pretty-printed form of an HTML fragment
+function normalize_html(html) {
+    const lines = [];
+    for (const node of parseHtml(String(html)).children) {
+        printNode(node, '', lines);
+    }
+    return lines.join('\n');
+}
+
+// --- Diffs -----------------------------------------------------------------
+
+// Line diff (longest common subsequence) of two normalized fragments
+function diffLines(expected, received) {
+    const a = expected.split('\n');
+    const b = received.split('\n');
+    const lengths = Array.from({ length: a.length + 1 }, () => new Array(b.length + 1).fill(0));
+    for (let i = a.length - 1; i >= 0; i--) {
+        for (let j = b.length - 1; j >= 0; j--) {
+            lengths[i][j] = a[i] === b[j] ? lengths[i + 1][j + 1] + 1 : Math.max(lengths[i + 1][j], lengths[i][j + 1]);
+        }
+    }
+    const out = [];
+    let i = 0;
+    let j = 0;
+    while (i < a.length || j < b.length) {
+        if (i < a.length && j < b.length && a[i] === b[j]) {
+            out.push(`    ${a[i]}`);
+            i++;
+            j++;
+        } else if (i < a.length && (j === b.length || lengths[i + 1][j] >= lengths[i][j + 1])) {
+            out.push(`  - ${a[i]}`);
+            i++;
+        } else {
+            out.push(`  + ${b[j]}`);
+            j++;
+        }
+    }
+    return out.join('\n');
+}
+
+// --- Snapshot files --------------------------------------------------------
+
+const HEADER = /^== (.+) ==$/;
+
+function snapshotPath(testFile) {
+    const path = require('path');
+    const name = path.basename(testFile, path.extname(testFile));
+    return path.join(path.dirname(testFile), '__snapshots__', `${name}.snap`);
+}
+
+function readSnapshots(file) {
+    const fs = require('fs');
+    const snapshots = new Map();
+    if (!fs.existsSync(file)) {
+        return snapshots;
+    }
+    let key = null;
+    let lines = [];
+    const finish = () => {
+        if (key !== null) {
+            while (lines.length > 0 && lines[lines.length - 1] === '') {
+                lines.pop();
+            }
+            snapshots.set(key, lines.join('\n'));
+        }
+    };
+    for (const line of fs.readFileSync(file, 'utf8').split('\n')) {
+        const header = line.match(HEADER);
+        if (header) {
+            finish();
+            key = header[1];
+            lines = [];
+        } else if (key !== null) {
+            lines.push(line.startsWith('\\') ? line.slice(1) : line);
+        }
+    }
+    finish();
+    return snapshots;
+}
+
+function compareKeys(a, b) {
+    const [nameA, countA] = a.split(' #');
+    const [nameB, countB] = b.split(' #');
+    return nameA === nameB ? Number(countA) - Number(countB) : (nameA < nameB ? -1 : 1);
+}
+
+function writeSnapshots(file, snapshots, testFile) {
+    const fs = require('fs');
+    const path = require('path');
+    let text = `# Jounce snapshots for ${testFile}\n`;
+    for (const key of [...snapshots.keys()].sort(compareKeys)) {
+        text += `\n== ${key} ==\n`;
+        for (const line of snapshots.get(key).split('\n')) {
+            text += (line.startsWith('==') || line.startsWith('\\') ? '\\' : '') + line + '\n';
+        }
+    }
+    fs.mkdirSync(path.dirname(file), { recursive: true });
+    fs.writeFileSync(file, text);
+}
+
+// --- Assertion -------------------------------------------------------------
+
+let snapshotTest = { name: 'snapshot', file: 'snapshot.jnc', count: 0 };
+
+// Called by the runner before each test
+function __snapshot_context(name, file) {
+    snapshotTest = { name, file, count: 0 };
+}
+
+function assert_snapshot(actual, inline) {
+    snapshotTest.count++;
+    const key = `${snapshotTest.name} #${snapshotTest.count}`;
+    const received = normalize_html(actual);
+
+    if (inline !== undefined) {
+        const expected = normalize_html(inline);
+        if (expected !== received) {
+            throw new Error(`Inline snapshot ${key} does not match:\n${diffLines(expected, received)}\n  New snapshot:\n${received}`);
+        }
+        return;
+    }
+
+    const file = snapshotPath(snapshotTest.file);
+    const snapshots = readSnapshots(file);
+    const update = process.env.JOUNCE_UPDATE_SNAPSHOTS === '1';
+    if (!snapshots.has(key) && process.env.CI && !update) {
+        throw new Error(`Snapshot ${key} is missing from ${file}; run the tests locally to record it`);
+    }
+    if (!snapshots.has(key) || update) {
+        if (snapshots.get(key) !== received) {
+            snapshots.set(key, received);
+            writeSnapshots(file, snapshots, snapshotTest.file);
+            console.log(`    [SNAPSHOT] wrote ${key} to ${file}`);
+        }
+        return;
+    }
+
+    const expected = normalize_html(snapshots.get(key));
+    if (expected !== received) {
+        throw new Error(`Snapshot ${key} does not match ${file}:\n${diffLines(expected, received)}\n  Run with JOUNCE_UPDATE_SNAPSHOTS=1 to accept the new output`);
+    }
+}
+
+if (typeof module !== 'undefined') {
+    module.exports = { h, unsafe_raw, render_to_string, normalize_html, assert_snapshot, __snapshot_context, readSnapshots, snapshotPath };
+}
@@ -0,0 +1,118 @@
+/**
+ * Test suite for bind:value form fields
+ *
+ * Run with: node runtime/test_bind.js
+ */
+
+const { app } = require('./test_dom.js');
+const { h, unmount, mountComponent } = require('./client-runtime.js');
+const { signal } = require('./reactivity.js');
+
+// Simple test framework
+let testsPassed = 0;
+let testsFailed = 0;
+
+function test(name, fn) {
+    try {
+        fn();
+        console.log(`✓ ${name}`);
+        testsPassed++;
+    } catch (error) {
+        console.error(`✗ ${name}`);
+        console.error(`  ${error.message}`);
+        testsFailed++;
+    }
+}
+
+function assertEqual(actual, expected, message) {
+    if (actual !== expected) {
+        throw new Error(`${message}\n  Expected: ${expected}\n  Actual: ${actual}`);
+    }
+}
+
+// The expansions the compiler emits for each kind of field
+test('text inputs follow the signal both ways', () => {
+    const name = signal('Ada');
+    const input = h('input', { type: 'text', value: name, oninput: (event) => { name.value = event.target.value; } });
+    assertEqual(input.value, 'Ada', 'initial value');
+
+    input.edit('input', field => { field.value = 'Grace'; });
+    assertEqual(name.value, 'Grace', 'edit written back');
+
+    name.value = '';
+    assertEqual(input.value, '', 'cleared from code');
+});
+
+test('number inputs convert and skip partial input', () => {
+    const age = signal(30);
+    const input = h('input', {
+        type: 'number',
+        value: age,
+        oninput: (event) => { if (!Number.isNaN(event.target.valueAsNumber)) { age.value = event.target.valueAsNumber; } },
+    });
+    assertEqual(input.value, '30', 'shown as text');
+
+    input.edit('input', field => { field.value = '42'; });
+    assertEqual(age.value, 42, 'a number, not "42"');
+
+    input.edit('input', field => { field.value = ''; });
+    assertEqual(age.value, 42, 'an empty field keeps the last number');
+
+    input.edit('input', field => { field.value = '42.'; });
+    assertEqual(input.value, '42.', 'typing is not rewritten');
+});
+
+test('checkboxes bind a bool, or a list when they have values', () => {
+    const agreed = signal(true);
+    const box = h('input', { type: 'checkbox', checked: agreed, onchange: (event) => { agreed.value = event.target.checked; } });
+    assertEqual(box.checked, true, 'checked from the signal');
+    box.edit('change', field => { field.checked = false; });
+    assertEqual(agreed.value, false, 'unchecked');
+
+    const tags = signal(['news']);
+    const group = ['news', 'offers'].map(value => h('input', {
+        type: 'checkbox',
+        value,
+        checked: tags,
+        onchange: (event) => { tags.value = event.target.checked ? [...tags.value, event.target.value] : tags.value.filter((item) => item !== event.target.value); },
+    }));
+    assertEqual(group[0].checked && !group[1].checked, true, 'membership');
+    group[1].edit('change', field => { field.checked = true; });
+    group[0].edit('change', field => { field.checked = false; });
+    assertEqual(tags.value.join(','), 'offers', 'toggled');
+
+    tags.value = ['news', 'offers'];
+    assertEqual(group[0].checked && group[1].checked, true, 'set from code');
+});
+
+test('selects take their value once the options are in', () => {
+    const color = signal('blue');
+    const select = h('select', { value: color, onchange: (event) => { color.value = event.target.value; } },
+        h('option', { value: 'red' }), h('option', { value: 'blue' }));
+    assertEqual(select.value, 'blue', 'selected');
+
+    const picked = signal(['b']);
+    const multiple = h('select', { multiple: true, value: picked, onchange: (event) => { picked.value = Array.from(event.target.selectedOptions, (option) => option.value); } },
+        h('option', { value: 'a' }), h('option', { value: 'b' }));
+    assertEqual(multiple.options.map(option => option.selected).join(','), 'false,true', 'options selected');
+    multiple.edit('change', field => { field.options[0].selected = true; });
+    assertEqual(picked.value.join(','), 'a,b', 'selected values written back');
+});
+
+test('unmounting stops following the signal', () => {
+    const name = signal('a');
+    function Field() {
+        return h('input', { value: name, oninput: (event) => { name.value = event.target.value; } });
+    }
+    mountComponent(Field);
+    const input = app.childNodes[0];
+    assertEqual(name._subscribers.size, 1, 'subscribed');
+
+    unmount(input);
+    assertEqual(name._subscribers.size, 0, 'released');
+});
+
+console.log(`\n${testsPassed} passed, ${testsFailed} failed`);
+if (testsFailed > 0) {
+    process.exit(1);
+}
@@ -0,0 +1,59 @@
+/**
+ * Test suite for loading the CommonJS runtimes where Node can't require()
+ * an ES module (before 20.19, or with --no-experimental-require-module)
+ *
+ * Run with: node runtime/test_commonjs.js
+ */
+
+const { execFileSync } = require('child_process');
+
+// Simple test framework
+let testsPassed = 0;
+let testsFailed = 0;
+
+function test(name, fn) {
+    try {
+        fn();
+        console.log(`✓ ${name}`);
+        testsPassed++;
+    } catch (error) {
+        console.error(`✗ ${name}`);
+        console.error(`  ${error.message}`);
+        testsFailed++;
+    }
+}
+
+function assertEqual(actual, expected, message) {
+    if (actual !== expected) {
+        throw new Error(`${message}\n  Expected: ${expected}\n  Actual: ${actual}`);
+    }
+}
+
+// Run `script` in a Node that treats require() of an ES module as an error
+function runWithoutRequireModule(script) {
+    return execFileSync(process.execPath, ['--no-experimental-require-module', '-e', script], {
+        cwd: __dirname,
+        encoding: 'utf8',
+    }).trim();
+}
+
+test('server-runtime.js loads', () => {
+    const output = runWithoutRequireModule(`
+        const { I18n, JsValue, memoize } = require('./server-runtime.js');
+        console.log([typeof I18n.t, typeof JsValue.wrap, typeof memoize].join(' '));
+    `);
+    assertEqual(output, 'function function function', 'shared helpers from the server runtime');
+});
+
+test('snapshot.js loads and escapes', () => {
+    const output = runWithoutRequireModule(`
+        const { h, render_to_string } = require('./snapshot.js');
+        console.log(render_to_string(h('p', null, '<b>')));
+    `);
+    assertEqual(output, '<p>&lt;b&gt;</p>', 'rendered with the shared escape()');
+});
+
+console.log(`\n${testsPassed} passed, ${testsFailed} failed`);
+if (testsFailed > 0) {
+    process.exit(1);
+}
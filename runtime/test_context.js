@@ -0,0 +1,99 @@
+/**
+ * Test suite for context providers (create_context, <Provide>, use_context)
+ *
+ * Run with: node runtime/test_context.js
+ */
+
+const { app } = require('./test_dom.js');
+const { h, mountComponent, unmount, Provide, createContext, useContext } = require('./client-runtime.js');
+const { signal, batch } = require('./reactivity.js');
+
+// Simple test framework
+let testsPassed = 0;
+let testsFailed = 0;
+
+function test(name, fn) {
+    try {
+        fn();
+        console.log(`✓ ${name}`);
+        testsPassed++;
+    } catch (error) {
+        console.error(`✗ ${name}`);
+        console.error(`  ${error.message}`);
+        testsFailed++;
+    }
+}
+
+function assertEqual(actual, expected, message) {
+    if (actual !== expected) {
+        throw new Error(`${message}\n  Expected: ${expected}\n  Actual: ${actual}`);
+    }
+}
+
+const themeCtx = createContext('light');
+let renders = 0;
+
+// Same shape the compiler emits for a component calling use_context(theme_ctx)
+function Label() {
+    renders++;
+    const theme = useContext(themeCtx);
+    return h('span', { class: theme }, theme);
+}
+
+test('nested providers override outer ones', () => {
+    // <Provide context={theme_ctx} value={"dark"}><Label /><Provide ... value={"blue"}><Label /></Provide></Provide>
+    mountComponent(() => h('div', null,
+        h(Provide, { context: themeCtx, value: "dark" }, () => [
+            h(Label, null),
+            h(Provide, { context: themeCtx, value: "blue" }, () => [h(Label, null)]),
+            h(Label, null),
+        ])
+    ));
+    assertEqual(app.textContent, 'darkbluedark', 'nearest provider wins');
+});
+
+test('a consumer outside any provider gets the default', () => {
+    mountComponent(() => h('div', null, h(Label, null)));
+    assertEqual(app.textContent, 'light', 'default value');
+
+    // Providers only cover what renders inside them
+    const otherCtx = createContext(0);
+    mountComponent(() => h('div', null,
+        h(Provide, { context: otherCtx, value: 7 }, () => [h(Label, null)]),
+        h(Label, null)
+    ));
+    assertEqual(app.textContent, 'lightlight', 'a different context does not apply');
+});
+
+test('consumers re-render when the provided signal changes', () => {
+    const theme = signal('dark');
+    function Sidebar() {
+        return h('aside', null, h(Label, null), 'static');
+    }
+    mountComponent(() => h('main', null,
+        h(Provide, { context: themeCtx, value: theme }, () => [h(Sidebar, null)])
+    ));
+    assertEqual(app.textContent, 'darkstatic', 'signal value provided');
+
+    renders = 0;
+    theme.value = 'solarized';
+    assertEqual(app.textContent, 'solarizedstatic', 'consumer updated');
+    assertEqual(renders, 1, 'only the consumer rendered again');
+
+    batch(() => {
+        theme.value = 'a';
+        theme.value = 'b';
+    });
+    assertEqual(app.textContent, 'bstatic', 'batched changes');
+
+    unmount(app.childNodes[0]);
+    renders = 0;
+    theme.value = 'gone';
+    assertEqual(renders, 0, 'unmounted consumers stop listening');
+    assertEqual(theme._subscribers.size, 0, 'no subscriptions left');
+});
+
+console.log(`\n${testsPassed} passed, ${testsFailed} failed`);
+if (testsFailed > 0) {
+    process.exit(1);
+}
@@ -0,0 +1,105 @@
+/**
+ * Test suite for the devtools registry (window.__JOUNCE_DEVTOOLS__)
+ *
+ * Run with: node runtime/test_devtools.js
+ */
+
+const { app } = require('./test_dom.js');
+const { h, mountComponent, unmount } = require('./client-runtime.js');
+const { enableDevtools, trackSignal } = require('./devtools.js');
+const { signal } = require('./reactivity.js');
+
+// Simple test framework
+let testsPassed = 0;
+let testsFailed = 0;
+
+function test(name, fn) {
+    try {
+        fn();
+        console.log(`✓ ${name}`);
+        testsPassed++;
+    } catch (error) {
+        console.error(`✗ ${name}`);
+        console.error(`  ${error.message}`);
+        testsFailed++;
+    }
+}
+
+function assertEqual(actual, expected, message) {
+    if (actual !== expected) {
+        throw new Error(`${message}\n  Expected: ${expected}\n  Actual: ${actual}`);
+    }
+}
+
+// Same shape the compiler emits in a dev build for
+// `component Counter(label: String)` owning `let count = signal(0)`
+let counters = [];
+
+function Counter({ label } = {}) {
+    let count = trackSignal(signal(0), "count");
+    counters.push(count);
+    return h('button', { onClick: () => { count.value = count.value + 1; } }, label);
+}
+Counter.__jounceMeta = { name: "Counter", props: [{ name: "label", type: "String" }] };
+
+function App() {
+    let todos = trackSignal(signal(["a"]), "todos");
+    return h('div', null, h(Counter, { label: "One" }), h(Counter, { label: "Two" }));
+}
+App.__jounceMeta = { name: "App", props: [] };
+
+test('mounting records the component tree', () => {
+    const registry = enableDevtools();
+    assertEqual(window.__JOUNCE_DEVTOOLS__, registry, 'exposed on window');
+
+    counters = [];
+    mountComponent(App);
+    const [app] = registry.tree();
+    assertEqual(registry.tree().length, 1, 'one root');
+    assertEqual(app.name, 'App', 'root name');
+    assertEqual(JSON.stringify(app.signals), '[{"name":"todos","value":"[\\"a\\"]"}]', 'root signals');
+    assertEqual(app.children.map(child => child.name).join(','), 'Counter,Counter', 'children');
+    assertEqual(app.children[0].props.label, '"One"', 'props in debug form');
+    assertEqual(app.children[1].signals[0].value, '0', 'signal value');
+
+    const ids = [app.id, ...app.children.map(child => child.id)];
+    assertEqual(ids.join(','), '1,2,3', 'ids in mount order');
+    const first = registry.instances.get(app.children[0].id);
+    assertEqual(first.parent, app.id, 'parent link');
+    assertEqual(first.propTypes[0].type, 'String', 'prop metadata from the compiler');
+});
+
+test('a signal change notifies listeners', () => {
+    const registry = enableDevtools();
+    const events = [];
+    const unsubscribe = registry.subscribe(event => events.push(event));
+
+    counters[1].value = 5;
+    counters[1].value = 5;
+    unsubscribe();
+    counters[1].value = 6;
+
+    assertEqual(events.length, 1, 'one event per change, none after unsubscribe');
+    assertEqual(JSON.stringify(events[0]), '{"type":"signal","id":3,"name":"count","value":"5"}', 'event');
+    assertEqual(registry.instances.get(3).signals[0].value, '6', 'registry keeps the current value');
+});
+
+test('remounting replaces the tree and keeps ids increasing', () => {
+    const registry = enableDevtools();
+    const events = [];
+    registry.subscribe(event => events.push(`${event.type}:${event.id}`));
+
+    mountComponent(App);
+    assertEqual(registry.instances.size, 3, 'old instances removed');
+    assertEqual(registry.tree()[0].id, 4, 'new root id');
+    assertEqual(events.slice(0, 3).sort().join(','), 'unmount:1,unmount:2,unmount:3', 'old tree unmounted');
+    assertEqual(events.slice(3).join(','), 'mount:5,mount:6,mount:4', 'children mount before their parent finishes');
+
+    unmount(app.childNodes[0]);
+    assertEqual(registry.tree().length, 0, 'empty after unmount');
+});
+
+console.log(`\n${testsPassed} passed, ${testsFailed} failed`);
+if (testsFailed > 0) {
+    process.exit(1);
+}
@@ -0,0 +1,170 @@
+/**
+ * Just enough DOM for h(), mountComponent(), patching and form fields
+ *
+ * Shared by the runtime tests: require it before client-runtime.js so the
+ * runtime sees the globals.
+ */
+
+class Node {
+    constructor() {
+        this.childNodes = [];
+        this.parentNode = null;
+    }
+
+    appendChild(child) {
+        return this.insertBefore(child, null);
+    }
+
+    insertBefore(child, before) {
+        if (child instanceof Fragment) {
+            Array.from(child.childNodes).forEach(node => this.insertBefore(node, before));
+            return child;
+        }
+        if (child.parentNode) {
+            child.parentNode.removeChild(child);
+        }
+        const index = before ? this.childNodes.indexOf(before) : this.childNodes.length;
+        this.childNodes.splice(index, 0, child);
+        child.parentNode = this;
+        return child;
+    }
+
+    removeChild(child) {
+        this.childNodes = this.childNodes.filter(node => node !== child);
+        child.parentNode = null;
+        return child;
+    }
+
+    replaceChild(next, old) {
+        this.insertBefore(next, old);
+        return this.removeChild(old);
+    }
+
+    get textContent() {
+        return this.childNodes.map(node => node.textContent).join('');
+    }
+
+    set textContent(text) {
+        this.childNodes = [];
+        this.appendChild(new Text(text));
+    }
+}
+
+class Element extends Node {
+    constructor(tag) {
+        super();
+        this.tagName = tag.toUpperCase();
+        this.attributes = {};
+        this.listeners = [];
+        this.style = {};
+        this.rawHtml = null;
+        this.value = '';
+        this.checked = false;
+        this.selected = false;
+    }
+
+    getAttribute(name) {
+        return name in this.attributes ? this.attributes[name] : null;
+    }
+
+    getAttributeNames() {
+        return Object.keys(this.attributes);
+    }
+
+    setAttribute(name, value) {
+        this.attributes[name] = value;
+        if (name === 'value') {
+            this.value = value;
+        }
+    }
+
+    hasAttribute(name) {
+        return name in this.attributes;
+    }
+
+    removeAttribute(name) {
+        delete this.attributes[name];
+    }
+
+    addEventListener(event, handler) {
+        this.listeners.push([event, handler]);
+    }
+
+    removeEventListener(event, handler) {
+        this.listeners = this.listeners.filter(([e, h]) => e !== event || h !== handler);
+    }
+
+    // Calls each `event` handler and returns what they returned
+    dispatch(event) {
+        return this.listeners.filter(([e]) => e === event).map(([, handler]) => handler({ type: event, target: this }));
+    }
+
+    // The user edits the field, then the browser fires `event`
+    edit(event, change) {
+        change(this);
+        this.dispatch(event);
+    }
+
+    get options() {
+        return this.childNodes.filter(node => node.tagName === 'OPTION');
+    }
+
+    get selectedOptions() {
+        return this.options.filter(option => option.selected);
+    }
+
+    get valueAsNumber() {
+        return this.value === '' ? NaN : Number(this.value);
+    }
+
+    set className(value) {
+        this.attributes.class = value;
+    }
+
+    // Only unsafe_raw() may reach the HTML parser
+    set innerHTML(html) {
+        this.rawHtml = html;
+        this.childNodes = [];
+    }
+}
+
+class Text extends Node {
+    constructor(text) {
+        super();
+        this.text = text;
+    }
+
+    get textContent() {
+        return this.text;
+    }
+}
+
+class Fragment extends Node {}
+
+// Browsers report unhandled rejections to window listeners
+const windowListeners = {};
+const app = new Element('div');
+global.Node = Node;
+global.Element = Element;
+global.window = {
+    location: { origin: 'http://localhost' },
+    addEventListener: (event, listener) => {
+        windowListeners[event] = listener;
+    },
+};
+global.document = {
+    createElement: tag => {
+        const element = new Element(tag);
+        if (tag === 'template') {
+            // Stands in for the parsed markup
+            element.content = new Fragment();
+            Object.defineProperty(element, 'innerHTML', { set: html => element.content.appendChild(new Text(`parsed:${html}`)) });
+        }
+        return element;
+    },
+    createTextNode: text => new Text(text),
+    createDocumentFragment: () => new Fragment(),
+    querySelector: () => app,
+};
+
+module.exports = { Node, Element, Text, Fragment, app, windowListeners };
@@ -0,0 +1,146 @@
+/**
+ * Security test suite for escaping in the client runtime
+ *
+ * Run with: node runtime/test_escape.js
+ */
+
+const { Text } = require('./test_dom.js');
+const { h, escape, unsafe_raw } = require('./client-runtime.js');
+
+// Simple test framework
+let testsPassed = 0;
+let testsFailed = 0;
+
+function test(name, fn) {
+    try {
+        fn();
+        console.log(`✓ ${name}`);
+        testsPassed++;
+    } catch (error) {
+        console.error(`✗ ${name}`);
+        console.error(`  ${error.message}`);
+        testsFailed++;
+    }
+}
+
+function assertEqual(actual, expected, message) {
+    if (actual !== expected) {
+        throw new Error(`${message}\n  Expected: ${expected}\n  Actual: ${actual}`);
+    }
+}
+
+function quietly(fn) {
+    const warn = console.warn;
+    const warnings = [];
+    console.warn = message => warnings.push(message);
+    try {
+        fn();
+    } finally {
+        console.warn = warn;
+    }
+    return warnings;
+}
+
+const SCRIPT = "<script>alert('xss')</script>";
+
+test('script in text content renders as text', () => {
+    const p = h('p', null, 'Hello ', SCRIPT);
+    assertEqual(p.childNodes.length, 2, 'two text nodes');
+    assertEqual(p.childNodes[1] instanceof Text, true, 'a text node, not an element');
+    assertEqual(p.childNodes[1].text, SCRIPT, 'text kept verbatim');
+    assertEqual(p.rawHtml, null, 'never parsed as HTML');
+
+    assertEqual(escape(SCRIPT), '&lt;script&gt;alert(&#39;xss&#39;)&lt;/script&gt;', 'text mode');
+});
+
+test('script in an attribute cannot break out or run', () => {
+    const title = '"><script>alert(1)</script>';
+    const a = h('a', { title });
+    assertEqual(a.attributes.title, title, 'attribute set as a value');
+    assertEqual(a.childNodes.length, 0, 'no element injected');
+
+    const warnings = quietly(() => {
+        assertEqual(h('a', { href: ' java\tscript:alert(1)' }).attributes.href, 'about:blank', 'script URL blocked');
+        assertEqual(h('iframe', { src: 'data:text/html,<script>alert(1)</script>' }).attributes.src, 'about:blank', 'HTML data URL blocked');
+        const button = h('button', { onClick: "alert(1)" });
+        assertEqual(Object.keys(button.listeners).length, 0, 'string handler not attached');
+        assertEqual(button.hasAttribute('onClick'), false, 'nor set as an attribute');
+    });
+    assertEqual(warnings.length, 3, 'each block is reported');
+    assertEqual(h('a', { href: '/docs?q=<b>' }).attributes.href, '/docs?q=<b>', 'ordinary URLs untouched');
+
+    assertEqual(
+        escape(title, 'attribute'),
+        '&quot;&gt;&lt;script&gt;alert(1)&lt;/script&gt;',
+        'attribute mode'
+    );
+    assertEqual(h('iframe', { srcdoc: SCRIPT }).attributes.srcdoc, escape(SCRIPT), 'srcdoc shows text');
+});
+
+test('quotes and ampersands in text and attributes are kept as written', () => {
+    // Same shape the compiler emits for <p title={author}>Tom &amp; "Jerry" {body}</p>
+    const author = 'A & "B" <b>';
+    const body = "<script>alert('x')</script> &amp;";
+    const p = h('p', { title: author }, 'Tom & "Jerry" ', body);
+    assertEqual(p.attributes.title, author, 'attribute value untouched, not entity-encoded twice');
+    assertEqual(p.childNodes.map(node => node.text).join(''), `Tom & "Jerry" ${body}`, 'text kept verbatim');
+    assertEqual(p.childNodes.every(node => node instanceof Text), true, 'only text nodes');
+    assertEqual(h('div', null, { tagName: 'SCRIPT' }).childNodes.length, 0, 'an object from data is not a node');
+});
+
+test('boolean attributes are present or absent', () => {
+    const button = h('button', { disabled: false, hidden: true, 'aria-pressed': false, draggable: true, title: null });
+    assertEqual(button.hasAttribute('disabled'), false, 'false leaves it out');
+    assertEqual(button.attributes.hidden, '', 'true sets it empty');
+    assertEqual(button.attributes['aria-pressed'], 'false', 'aria-* spells booleans out');
+    assertEqual(button.attributes.draggable, 'true', 'so do enumerated attributes');
+    assertEqual(button.hasAttribute('title'), false, 'null leaves it out');
+});
+
+test('CSS value interpolation cannot end the declaration', () => {
+    const color = "red; background-image: url(javascript:alert(1)) } </style><script>alert(1)</script>";
+    // A style object is set property by property, so the CSSOM takes the
+    // whole string as one (invalid) color and nothing needs escaping
+    const div = h('div', { style: { color, padding: '4px 8px' } });
+    assertEqual(div.style.padding, '4px 8px', 'ordinary values untouched');
+    assertEqual(div.style.color, color, 'assigned as-is');
+    assertEqual(escape('a"b;', 'css'), 'a\\22 b\\3b ', 'css mode');
+
+    // Same shape the compiler emits for style="color: {color}; padding: 4px"
+    const inline = "color: " + escape("red; position: fixed", 'css') + "; padding: 4px";
+    assertEqual(h('div', { style: inline }).attributes.style.split(';').length, 2, 'still two declarations');
+});
+
+test('lengths in a style object are px, and still escaped', () => {
+    // Same shape the compiler emits for style={{ width: w, z_index: z }}
+    assertEqual(escape(8, 'css-length'), '8px', 'a number is px');
+    assertEqual(escape(0, 'css-length'), '0', 'except 0');
+    assertEqual(escape('50%', 'css-length'), '50%', 'strings keep their unit');
+    assertEqual(escape('1px; color: red', 'css-length'), '1px\\3b  color: red', 'and are escaped');
+});
+
+test('a JSON-sourced string stays inert everywhere', () => {
+    const user = JSON.parse('{"name": "<img src=x onerror=alert(1)>", "site": "javascript:alert(1)", "bio": "<script>alert(1)</script>"}');
+    let card;
+    quietly(() => {
+        card = h('div', { title: user.name, inner_html: user.bio },
+            h('a', { href: user.site }, user.name));
+    });
+    const link = card.childNodes[1];
+    assertEqual(card.rawHtml, null, 'inner_html without unsafe_raw is not parsed');
+    assertEqual(card.childNodes[0].text, user.bio, 'shown as text instead');
+    assertEqual(link.attributes.href, 'about:blank', 'script URL blocked');
+    assertEqual(link.childNodes[0].text, user.name, 'name rendered as text');
+});
+
+test('unsafe_raw is the only bypass', () => {
+    const trusted = '<em>Hi</em>';
+    assertEqual(h('div', { inner_html: unsafe_raw(trusted) }).rawHtml, trusted, 'inner_html parsed');
+
+    assertEqual(h('p', null, unsafe_raw(trusted)).childNodes[0].text, `parsed:${trusted}`, 'raw child parsed');
+});
+
+console.log(`\n${testsPassed} passed, ${testsFailed} failed`);
+if (testsFailed > 0) {
+    process.exit(1);
+}
@@ -0,0 +1,173 @@
+/**
+ * Test suite for EventBus and component cleanup
+ *
+ * Run with: node runtime/test_event_bus.js
+ */
+
+const { app } = require('./test_dom.js');
+const { h, mountComponent, unmount, onCleanup, EventBus } = require('./client-runtime.js');
+
+// Simple test framework
+let testsPassed = 0;
+let testsFailed = 0;
+
+function test(name, fn) {
+    try {
+        fn();
+        console.log(`✓ ${name}`);
+        testsPassed++;
+    } catch (error) {
+        console.error(`✗ ${name}`);
+        console.error(`  ${error.message}`);
+        testsFailed++;
+    }
+}
+
+function assertEqual(actual, expected, message) {
+    if (actual !== expected) {
+        throw new Error(`${message}\n  Expected: ${expected}\n  Actual: ${actual}`);
+    }
+}
+
+// Same shape the compiler emits for two components sharing
+// `EventBus::channel::<String>("toasts")`
+test('two components communicate through a channel', () => {
+    const received = [];
+
+    function Toasts() {
+        const bus = EventBus.channel("toasts");
+        bus.subscribe((message) => received.push(message));
+        return h('ul', null);
+    }
+
+    function SaveButton() {
+        const bus = EventBus.channel("toasts");
+        return h('button', { onClick: () => bus.emit("Saved") }, "Save");
+    }
+
+    function App() {
+        return h('div', null, h(Toasts, null), h(SaveButton, null));
+    }
+
+    mountComponent(App);
+    EventBus.channel("toasts").emit("Saved");
+    EventBus.channel("toasts").emit("Deleted");
+    assertEqual(received.join(','), 'Saved,Deleted', 'delivered in order');
+
+    mountComponent(() => h('div', null));
+    assertEqual(EventBus.channel("toasts").subscriber_count(), 0, 'remount released the old subscription');
+});
+
+test('unsubscribe stops delivery', () => {
+    const bus = EventBus.new();
+    const seen = [];
+    const subscription = bus.subscribe((n) => seen.push(n));
+
+    bus.emit(1);
+    subscription.unsubscribe();
+    bus.emit(2);
+    subscription.unsubscribe();
+
+    assertEqual(seen.join(','), '1', 'only the first value');
+    assertEqual(bus.subscriber_count(), 0, 'subscription removed');
+});
+
+test('emitting from a handler keeps every event in order', () => {
+    const bus = EventBus.new();
+    const log = [];
+
+    bus.subscribe((n) => {
+        log.push(`a${n}`);
+        if (n < 3) {
+            bus.emit(n + 1);
+        }
+    });
+    bus.subscribe((n) => log.push(`b${n}`));
+
+    bus.emit(1);
+    assertEqual(log.join(','), 'a1,b1,a2,b2,a3,b3', 'each value reaches every handler before the next');
+});
+
+test('a handler that always re-emits hits the queue bound', () => {
+    const bus = new EventBus(10);
+    bus.subscribe((n) => {
+        bus.emit(n + 1);
+        bus.emit(n + 1);
+    });
+
+    let message = '';
+    try {
+        bus.emit(0);
+    } catch (error) {
+        message = error.message;
+    }
+    if (!message.includes('more than 10 events queued')) {
+        throw new Error(`expected a queue overflow, got "${message}"`);
+    }
+
+    // The bus is usable again afterwards
+    const fresh = [];
+    bus.subscriptions = [];
+    bus.subscribe((n) => fresh.push(n));
+    bus.emit(7);
+    assertEqual(fresh.join(','), '7', 'no stale queued values');
+});
+
+test('a throwing handler does not drop queued values', () => {
+    const bus = EventBus.new();
+    const log = [];
+    bus.subscribe((n) => {
+        log.push(n);
+        if (n === 1) {
+            bus.emit(2);
+            throw new Error('handler failed');
+        }
+    });
+
+    let message = '';
+    try {
+        bus.emit(1);
+    } catch (error) {
+        message = error.message;
+    }
+    assertEqual(message, 'handler failed', 'the error reaches the emitter');
+
+    bus.emit(3);
+    assertEqual(log.join(','), '1,2,3', 'the queued value goes out first');
+});
+
+test('unmounting a component releases its subscriptions', () => {
+    const bus = EventBus.new();
+    const seen = [];
+    let cleanedUp = false;
+
+    function Listener() {
+        bus.subscribe((n) => seen.push(n));
+        onCleanup(() => { cleanedUp = true; });
+        return h('span', null, 'listening');
+    }
+
+    const root = h('div', null, h(Listener, null));
+    app.appendChild(root);
+    bus.emit(1);
+    assertEqual(bus.subscriber_count(), 1, 'subscribed while mounted');
+
+    unmount(root);
+    bus.emit(2);
+    assertEqual(seen.join(','), '1', 'no delivery after unmount');
+    assertEqual(bus.subscriber_count(), 0, 'subscription released');
+    assertEqual(cleanedUp, true, 'on_cleanup callback ran');
+    assertEqual(root.parentNode, null, 'removed from the DOM');
+});
+
+test('subscriptions outside a component stay until unsubscribed', () => {
+    const bus = EventBus.new();
+    bus.subscribe(() => {});
+    mountComponent(() => h('div', null));
+    assertEqual(bus.subscriber_count(), 1, 'not owned by any component');
+});
+
+console.log(`\n${testsPassed} passed, ${testsFailed} failed`);
+if (testsFailed > 0) {
+    process.exit(1);
+}
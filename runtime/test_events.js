@@ -0,0 +1,88 @@
+/**
+ * Test suite for event handlers and their listener options
+ *
+ * Run with: node runtime/test_events.js
+ */
+
+// Elements that record their listeners
+class Element {
+    constructor(tag) {
+        this.tagName = tag.toUpperCase();
+        this.listeners = [];
+    }
+
+    appendChild(child) {
+        return child;
+    }
+
+    addEventListener(event, handler, options) {
+        this.listeners.push({ event, handler, options });
+    }
+}
+
+global.Node = Element;
+global.Element = Element;
+global.document = {
+    createElement: tag => new Element(tag),
+    createTextNode: text => ({ text }),
+};
+
+const { h } = require('./client-runtime.js');
+
+// Simple test framework
+let testsPassed = 0;
+let testsFailed = 0;
+
+function test(name, fn) {
+    try {
+        fn();
+        console.log(`✓ ${name}`);
+        testsPassed++;
+    } catch (error) {
+        console.error(`✗ ${name}`);
+        console.error(`  ${error.message}`);
+        testsFailed++;
+    }
+}
+
+function assertEqual(actual, expected, message) {
+    if (actual !== expected) {
+        throw new Error(`${message}\n  Expected: ${expected}\n  Actual: ${actual}`);
+    }
+}
+
+test('a handler function is listened to as is', () => {
+    const go = () => {};
+    const button = h('button', { onClick: go });
+    assertEqual(button.listeners.length, 1, 'one listener');
+    assertEqual(button.listeners[0].event, 'click', 'event name');
+    assertEqual(button.listeners[0].handler, go, 'the handler itself');
+});
+
+test('onClick|once|capture passes its options to addEventListener', () => {
+    // What the compiler emits for <button onClick|once|capture={go}>
+    const go = () => {};
+    const button = h('button', { onClick: { handleEvent: go, once: true, capture: true } });
+    const [{ event, handler, options }] = button.listeners;
+    assertEqual(event, 'click', 'event name');
+    assertEqual(handler, go, 'the handler');
+    assertEqual(JSON.stringify(options), '{"once":true,"capture":true}', 'listener options');
+});
+
+test('strings are not handlers, even with options', () => {
+    const warnings = [];
+    const warn = console.warn;
+    console.warn = message => warnings.push(message);
+    try {
+        const div = h('div', { onClick: 'alert(1)', onScroll: { handleEvent: 'alert(1)', passive: true } });
+        assertEqual(div.listeners.length, 0, 'nothing listened to');
+    } finally {
+        console.warn = warn;
+    }
+    assertEqual(warnings.join('|'), 'Ignored a non-function "onClick" handler|Ignored a non-function "onScroll" handler', 'warnings');
+});
+
+console.log(`\n${testsPassed} passed, ${testsFailed} failed`);
+if (testsFailed > 0) {
+    process.exit(1);
+}
@@ -0,0 +1,78 @@
+/**
+ * Test suite for I18n, the runtime behind t!
+ *
+ * Run with: node runtime/test_i18n.js
+ */
+
+const { I18n } = require('./client-runtime.js');
+const server = require('./server-runtime.js');
+
+// Simple test framework
+let testsPassed = 0;
+let testsFailed = 0;
+
+function test(name, fn) {
+    try {
+        fn();
+        console.log(`✓ ${name}`);
+        testsPassed++;
+    } catch (error) {
+        console.error(`✗ ${name}`);
+        console.error(`  ${error.message}`);
+        testsFailed++;
+    }
+}
+
+function assertEqual(actual, expected, message) {
+    if (actual !== expected) {
+        throw new Error(`${message}\n  Expected: ${expected}\n  Actual: ${actual}`);
+    }
+}
+
+// A catalog as Jounce code holds it: a JsonValue object
+const french = {
+    variant: 'Object',
+    data: {
+        'Add {count} items': { variant: 'String', data: 'Ajouter {count} articles' },
+        'Hello, {name}!': { variant: 'String', data: 'Bonjour, {name} !' },
+        'Your cart': { variant: 'String', data: '' },
+    },
+};
+
+test('without a locale messages are shown as written', () => {
+    assertEqual(I18n.locale(), null, 'no locale');
+    assertEqual(I18n.t('Add {count} items', { count: 3 }), 'Add 3 items', 'substituted');
+});
+
+test('placeholders are substituted into the translation', () => {
+    I18n.set_locale('fr', french);
+    assertEqual(I18n.locale(), 'fr', 'active locale');
+    assertEqual(I18n.t('Add {count} items', { count: 3 }), 'Ajouter 3 articles', 'count');
+    assertEqual(I18n.t('Hello, {name}!', { name: 'Ada' }), 'Bonjour, Ada !', 'name');
+    assertEqual(I18n.t('Hello, {name}!', {}), 'Bonjour, {name} !', 'missing values stay visible');
+});
+
+test('missing translations fall back to the message', () => {
+    I18n.set_locale('fr', french);
+    assertEqual(I18n.t('Checkout'), 'Checkout', 'not in the catalog');
+    assertEqual(I18n.t('Your cart'), 'Your cart', 'empty translation');
+    I18n.set_locale('de');
+    assertEqual(I18n.t('Add {count} items', { count: 1 }), 'Add 1 items', 'no catalog for the locale');
+});
+
+test('regional locales fall back to their language', () => {
+    I18n.set_locale('fr');
+    I18n.set_locale('fr-CA', { 'Your cart': 'Votre panier' });
+    assertEqual(I18n.t('Your cart'), 'Votre panier', 'regional translation');
+    assertEqual(I18n.t('Add {count} items', { count: 2 }), 'Ajouter 2 articles', 'language translation');
+});
+
+test('the server runtime translates the same way', () => {
+    server.I18n.set_locale('fr', french);
+    assertEqual(server.I18n.t('Add {count} items', { count: 5 }), 'Ajouter 5 articles', 'server');
+});
+
+console.log(`\n${testsPassed} passed, ${testsFailed} failed`);
+if (testsFailed > 0) {
+    process.exit(1);
+}
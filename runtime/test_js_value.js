@@ -0,0 +1,116 @@
+/**
+ * Test suite for JsValue, the wrapper for values from extern JavaScript
+ *
+ * Run with: node runtime/test_js_value.js
+ */
+
+const { JsValue } = require('./client-runtime.js');
+const server = require('./server-runtime.js');
+
+// Simple test framework
+let testsPassed = 0;
+let testsFailed = 0;
+
+function test(name, fn) {
+    try {
+        fn();
+        console.log(`✓ ${name}`);
+        testsPassed++;
+    } catch (error) {
+        console.error(`✗ ${name}`);
+        console.error(`  ${error.message}`);
+        testsFailed++;
+    }
+}
+
+function assertEqual(actual, expected, message) {
+    if (actual !== expected) {
+        throw new Error(`${message}\n  Expected: ${expected}\n  Actual: ${actual}`);
+    }
+}
+
+function assertDeepEqual(actual, expected, message) {
+    assertEqual(JSON.stringify(actual), JSON.stringify(expected), message);
+}
+
+// What an `extern "js" { fn user() -> JsValue; }` function might hand back;
+// the compiler wraps the call as JsValue.wrap(user())
+function user() {
+    return {
+        name: 'Ada',
+        tags: ['admin', 'ops'],
+        manager: null,
+        greet(greeting) {
+            return `${greeting}, ${this.name}`;
+        },
+    };
+}
+
+test('reading a fake extern object', () => {
+    const value = JsValue.wrap(user());
+    assertDeepEqual(value.get('name').as_string(), { variant: 'Some', data: 'Ada' }, 'string field');
+    assertDeepEqual(value.get('tags').index(1).as_string(), { variant: 'Some', data: 'ops' }, 'array element');
+    assertEqual(value.get('tags').index(5).is_undefined(), true, 'out of range index');
+    assertEqual(value.get('manager').is_null(), true, 'null field');
+    assertEqual(value.get('manager').get('name').is_undefined(), true, 'reading through null');
+    assertEqual(value.get('missing').is_undefined(), true, 'missing field');
+
+    const greeting = value.get('greet').call([JsValue.from_json({ variant: 'String', data: 'Hello' })]);
+    assertDeepEqual(greeting.as_string(), { variant: 'Some', data: 'Hello, Ada' }, 'method called on its object');
+
+    let error = null;
+    try {
+        value.get('name').call([]);
+    } catch (e) {
+        error = e;
+    }
+    assertEqual(error && error.message, 'JsValue.call: value is not a function (got string)', 'calling a non-function');
+});
+
+test('as_f64 on a string is None', () => {
+    const value = JsValue.wrap('42');
+    assertDeepEqual(value.as_f64(), { variant: 'None' }, 'no implicit number conversion');
+    assertDeepEqual(JsValue.wrap(42).as_f64(), { variant: 'Some', data: 42 }, 'numbers convert');
+    assertDeepEqual(JsValue.wrap(1).as_bool(), { variant: 'None' }, 'no truthiness');
+});
+
+test('JsonValue round trip for plain data', () => {
+    const json = {
+        variant: 'Object',
+        data: {
+            id: { variant: 'Number', data: 7 },
+            ok: { variant: 'Bool', data: true },
+            note: { variant: 'Null' },
+            items: { variant: 'Array', data: [{ variant: 'String', data: 'a' }] },
+        },
+    };
+    const value = JsValue.from_json(json);
+    assertDeepEqual(value.raw, { id: 7, ok: true, note: null, items: ['a'] }, 'raw data');
+    assertDeepEqual(value.to_json(), { variant: 'Some', data: json }, 'converted back');
+
+    assertDeepEqual(JsValue.wrap(user()).to_json(), { variant: 'None' }, 'objects with functions are not data');
+    assertDeepEqual(JsValue.wrap(new Date(0)).to_json(), { variant: 'None' }, 'class instances are not data');
+    assertDeepEqual(JsValue.wrap(NaN).to_json(), { variant: 'None' }, 'non-finite numbers are not data');
+    const cyclic = {};
+    cyclic.self = cyclic;
+    assertDeepEqual(JsValue.wrap(cyclic).to_json(), { variant: 'None' }, 'cycles are not data');
+});
+
+test('bundles can supply their own Option', () => {
+    class Opt {
+        constructor(variant, data) {
+            this.variant = variant;
+            this.data = data;
+        }
+    }
+    const none = new Opt('None');
+    server.JsValue.useOption(data => new Opt('Some', data), none);
+    const some = server.JsValue.wrap('x').as_string();
+    assertEqual(some instanceof Opt && some.data === 'x', true, 'Some from the bundle');
+    assertEqual(server.JsValue.wrap(1).as_string(), none, 'None from the bundle');
+});
+
+console.log(`\n${testsPassed} passed, ${testsFailed} failed`);
+if (testsFailed > 0) {
+    process.exit(1);
+}
@@ -0,0 +1,113 @@
+/**
+ * Test suite for keyed list reconciliation
+ *
+ * Run with: node runtime/test_keyed.js
+ */
+
+const { app } = require('./test_dom.js');
+const { h, mountComponent, Provide, createContext, useContext } = require('./client-runtime.js');
+const { enableDevtools } = require('./devtools.js');
+const { signal } = require('./reactivity.js');
+
+// Simple test framework
+let testsPassed = 0;
+let testsFailed = 0;
+
+function test(name, fn) {
+    try {
+        fn();
+        console.log(`✓ ${name}`);
+        testsPassed++;
+    } catch (error) {
+        console.error(`✗ ${name}`);
+        console.error(`  ${error.message}`);
+        testsFailed++;
+    }
+}
+
+function assertEqual(actual, expected, message) {
+    if (actual !== expected) {
+        throw new Error(`${message}\n  Expected: ${expected}\n  Actual: ${actual}`);
+    }
+}
+
+// Lists re-render when the signal they're given through a context changes
+const todosCtx = createContext([]);
+
+function mountWith(todos, List) {
+    mountComponent(() => h('main', null, h(Provide, { context: todosCtx, value: todos }, () => [h(List, null)])));
+    return app.childNodes[0].childNodes[0];
+}
+
+// Same shape the compiler emits for <li key={todo.id}>{todo.title}</li> in a map
+function TodoList() {
+    const todos = useContext(todosCtx);
+    return h('ul', null, todos.map(todo => h('li', { key: todo.id, class: todo.done ? 'done' : 'open' }, todo.title)));
+}
+
+test('reordering a keyed list moves its existing nodes', () => {
+    const todos = signal([{ id: 1, title: 'a' }, { id: 2, title: 'b' }, { id: 3, title: 'c' }]);
+    const ul = mountWith(todos, TodoList);
+    const [a, b, c] = ul.childNodes;
+
+    todos.value = [{ id: 3, title: 'c' }, { id: 4, title: 'd' }, { id: 1, title: 'a!', done: true }];
+    assertEqual(app.childNodes[0].childNodes[0], ul, 'the list itself is kept');
+    assertEqual(ul.childNodes.length, 3, 'one removed, one added');
+    assertEqual(ul.childNodes[0], c, 'c moved to the front');
+    assertEqual(ul.childNodes[2], a, 'a moved to the end');
+    assertEqual(b.parentNode, null, 'b removed');
+    assertEqual(ul.childNodes[1].textContent, 'd', 'd created');
+    assertEqual(a.textContent, 'a!', 'kept nodes take the new contents');
+    assertEqual(a.attributes.class, 'done', 'and the new attributes');
+});
+
+test('keyed components keep their nodes and get the new handlers', () => {
+    const clicks = [];
+    const seenProps = [];
+    function Row(props) {
+        seenProps.push(Object.keys(props).sort().join(','));
+        return h('li', { onClick: () => clicks.push(props.todo.title) }, props.todo.title);
+    }
+    function Rows() {
+        return h('ul', null, useContext(todosCtx).map(todo => h(Row, { key: todo.id, todo })));
+    }
+
+    const todos = signal([{ id: 1, title: 'first' }, { id: 2, title: 'second' }]);
+    const ul = mountWith(todos, Rows);
+    const [first, second] = ul.childNodes;
+    assertEqual(seenProps[0], 'todo', 'the key is not a prop');
+
+    todos.value = [{ id: 2, title: 'second, edited' }, { id: 1, title: 'first' }];
+    assertEqual(ul.childNodes[0], second, 'second moved up');
+    assertEqual(ul.childNodes[1], first, 'first moved down');
+
+    second.dispatch('click');
+    assertEqual(clicks.join('|'), 'second, edited', 'the current handler runs, once');
+    assertEqual(second.listeners.length, 1, 'the old handler is gone');
+});
+
+test('duplicate keys warn in dev builds, naming the component', () => {
+    const warnings = [];
+    const warn = console.warn;
+    console.warn = message => warnings.push(message);
+    try {
+        mountWith(signal([{ id: 1, title: 'a' }, { id: 1, title: 'b' }]), TodoList);
+        assertEqual(warnings.length, 0, 'silent outside dev builds');
+
+        enableDevtools();
+        mountWith(signal([{ id: 1, title: 'a' }, { id: 1, title: 'b' }]), TodoList);
+    } finally {
+        console.warn = warn;
+    }
+    assertEqual(warnings.length, 1, 'one warning');
+    assertEqual(
+        warnings[0],
+        'Duplicate key "1" among the children of <ul> in <TodoList>; each item of a keyed list needs its own key',
+        'warning text'
+    );
+});
+
+console.log(`\n${testsPassed} passed, ${testsFailed} failed`);
+if (testsFailed > 0) {
+    process.exit(1);
+}
@@ -0,0 +1,103 @@
+/**
+ * Test suite for the @memo runtime cache
+ *
+ * Run with: node runtime/test_memo.js
+ */
+
+const { memoize } = require('./server-runtime.js');
+
+// Simple test framework
+let testsPassed = 0;
+let testsFailed = 0;
+
+function test(name, fn) {
+    try {
+        fn();
+        console.log(`✓ ${name}`);
+        testsPassed++;
+    } catch (error) {
+        console.error(`✗ ${name}`);
+        console.error(`  ${error.message}`);
+        testsFailed++;
+    }
+}
+
+function assertEqual(actual, expected, message) {
+    if (actual !== expected) {
+        throw new Error(`${message}\n  Expected: ${expected}\n  Actual: ${actual}`);
+    }
+}
+
+// Same shape the compiler emits for `@memo fn fib(n: i32) -> i32`
+test('memoized fibonacci hits the cache', () => {
+    let calls = 0;
+    const fib = memoize(function(n) {
+        calls++;
+        if (n < 2) {
+            return n;
+        }
+        return fib(n - 1) + fib(n - 2);
+    }, 64, ['ref']);
+
+    assertEqual(fib(30), 832040, 'fib(30)');
+    assertEqual(calls, 31, 'each n computed once');
+
+    fib(30);
+    assertEqual(calls, 31, 'repeat call served from cache');
+});
+
+test('struct arguments without Eq are keyed by identity', () => {
+    let calls = 0;
+    const area = memoize(function(size) {
+        calls++;
+        return size.w * size.h;
+    }, 8, ['ref']);
+
+    area({ w: 2, h: 3 });
+    area({ w: 2, h: 3 });
+    assertEqual(calls, 2, 'equal but distinct structs miss');
+
+    const size = { w: 2, h: 3 };
+    area(size);
+    area(size);
+    assertEqual(calls, 3, 'same struct hits');
+});
+
+test('struct arguments deriving Eq are keyed by value', () => {
+    let calls = 0;
+    const length = memoize(function(point) {
+        calls++;
+        return Math.abs(point.x) + Math.abs(point.y);
+    }, 8, ['eq']);
+
+    length({ x: 1, y: 2 });
+    length({ x: 1, y: 2 });
+    assertEqual(calls, 1, 'equal structs hit');
+
+    length({ x: 2, y: 1 });
+    assertEqual(calls, 2, 'different fields miss');
+});
+
+test('cache keeps only the last N calls', () => {
+    let calls = 0;
+    const square = memoize(function(n) {
+        calls++;
+        return n * n;
+    }, 2, ['ref']);
+
+    square(1);
+    square(2);
+    square(1); // refreshes 1
+    square(3); // evicts 2
+    assertEqual(calls, 3, 'three distinct computations');
+
+    square(1);
+    assertEqual(calls, 3, '1 was recently used');
+    square(2);
+    assertEqual(calls, 4, '2 was evicted');
+});
+
+console.log(`\n${testsPassed} passed, ${testsFailed} failed`);
+if (testsFailed > 0) {
+    process.exit(1);
+}
@@ -0,0 +1,96 @@
+/**
+ * Test suite for the runtime profiling helpers (`jnc compile --profile-runtime`)
+ *
+ * Run with: node runtime/test_profile.js
+ */
+
+const fs = require('fs');
+const path = require('path');
+
+// profile.js is inlined into the client bundle rather than imported, so
+// evaluate it the same way and pick its globals out
+global.window = {};
+const source = fs.readFileSync(path.join(__dirname, 'profile.js'), 'utf8');
+const { __jounce_measure, __jounce_profiled, __jounce_profile } = new Function(
+    `${source}\nreturn { __jounce_measure, __jounce_profiled, __jounce_profile };`
+)();
+
+// Simple test framework
+let testsPassed = 0;
+let testsFailed = 0;
+
+function test(name, fn) {
+    try {
+        fn();
+        console.log(`✓ ${name}`);
+        testsPassed++;
+    } catch (error) {
+        console.error(`✗ ${name}`);
+        console.error(`  ${error.message}`);
+        testsFailed++;
+    }
+}
+
+function assertEqual(actual, expected, message) {
+    if (actual !== expected) {
+        throw new Error(`${message}\n  Expected: ${expected}\n  Actual: ${actual}`);
+    }
+}
+
+// Keeps the clock busy for about `ms` milliseconds
+function spin(ms) {
+    const end = performance.now() + ms;
+    while (performance.now() < end) {}
+}
+
+test('renders are measured under the component name', () => {
+    __jounce_profile.reset();
+    performance.clearMeasures();
+    const rendered = __jounce_measure('Card', 'render', () => 'node');
+    assertEqual(rendered, 'node', 'the render result passes through');
+
+    const measures = performance.getEntriesByName('jounce:Card:render', 'measure');
+    assertEqual(measures.length, 1, 'one measure');
+    assertEqual(performance.getEntriesByName('jounce:Card:render:start', 'mark').length, 0, 'marks cleared');
+    assertEqual(window.__JOUNCE_PROFILE__, __jounce_profile, 'exposed on window');
+});
+
+test('summary aggregates count, total and max per component and kind', () => {
+    __jounce_profile.reset();
+    const effect = __jounce_profiled('List', 'effect', ms => spin(ms));
+    effect(2);
+    effect(6);
+    __jounce_measure('List', 'render', () => spin(1));
+    __jounce_measure('Item', 'render', () => spin(1));
+
+    const summary = JSON.parse(JSON.stringify(__jounce_profile.summary()));
+    assertEqual(Object.keys(summary).join(','), 'List,Item', 'components');
+    assertEqual(summary.List.effect.count, 2, 'effect runs');
+    assertEqual(summary.List.render.count, 1, 'renders');
+    if (summary.List.effect.max < 6 || summary.List.effect.total < 8) {
+        throw new Error(`durations too short: ${JSON.stringify(summary.List.effect)}`);
+    }
+    if (summary.List.effect.max > summary.List.effect.total) {
+        throw new Error('max exceeds total');
+    }
+
+    __jounce_profile.reset();
+    assertEqual(Object.keys(__jounce_profile.summary()).length, 0, 'reset clears');
+});
+
+test('a render that throws is still recorded', () => {
+    __jounce_profile.reset();
+    try {
+        __jounce_measure('Broken', 'render', () => {
+            throw new Error('boom');
+        });
+    } catch (error) {
+        assertEqual(error.message, 'boom', 'error propagates');
+    }
+    assertEqual(__jounce_profile.summary().Broken.render.count, 1, 'counted');
+});
+
+console.log(`\n${testsPassed} passed, ${testsFailed} failed`);
+if (testsFailed > 0) {
+    process.exit(1);
+}
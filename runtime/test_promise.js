@@ -0,0 +1,65 @@
+/**
+ * Test suite for the Promise helpers used by compiled async code
+ *
+ * Run with: node runtime/test_promise.js
+ */
+
+const { sleep } = require('./server-runtime.js');
+
+// Simple async test framework
+let testsPassed = 0;
+let testsFailed = 0;
+const tests = [];
+
+function test(name, fn) {
+    tests.push({ name, fn });
+}
+
+function assertEqual(actual, expected, message) {
+    if (actual !== expected) {
+        throw new Error(`${message}\n  Expected: ${expected}\n  Actual: ${actual}`);
+    }
+}
+
+test('sleep resolves after the delay', async () => {
+    const start = Date.now();
+    const value = await sleep(20);
+    assertEqual(value, undefined, 'sleep resolves to ()');
+    if (Date.now() - start < 15) {
+        throw new Error('sleep resolved too early');
+    }
+});
+
+// Same shape the compiler emits for
+// `await Promise::race(vec![fetch_value(), sleep(20).then(|_| -1)])`
+test('race with a sleep-based timeout', async () => {
+    const slow = () => sleep(200).then(() => 42);
+    const fast = () => sleep(5).then(() => 7);
+
+    assertEqual(await Promise.race([slow(), sleep(20).then((_) => (-1))]), -1, 'timeout wins');
+    assertEqual(await Promise.race([fast(), sleep(20).then((_) => (-1))]), 7, 'value wins');
+});
+
+test('all waits for every promise, in order', async () => {
+    const results = await Promise.all([sleep(15).then(() => 1), sleep(5).then(() => 2), 3]);
+    assertEqual(results.join(','), '1,2,3', 'results keep argument order');
+});
+
+(async () => {
+    for (const { name, fn } of tests) {
+        try {
+            await fn();
+            console.log(`✓ ${name}`);
+            testsPassed++;
+        } catch (error) {
+            console.error(`✗ ${name}`);
+            console.error(`  ${error.message}`);
+            testsFailed++;
+        }
+    }
+
+    console.log(`\n${testsPassed} passed, ${testsFailed} failed`);
+    if (testsFailed > 0) {
+        process.exit(1);
+    }
+})();
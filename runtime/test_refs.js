@@ -0,0 +1,80 @@
+/**
+ * Test suite for element refs
+ *
+ * Run with: node runtime/test_refs.js
+ */
+
+const { app } = require('./test_dom.js');
+const { h, mountComponent, Provide, createContext, useContext, bindRef } = require('./client-runtime.js');
+const { signal } = require('./reactivity.js');
+
+// Simple test framework
+let testsPassed = 0;
+let testsFailed = 0;
+
+function test(name, fn) {
+    try {
+        fn();
+        console.log(`✓ ${name}`);
+        testsPassed++;
+    } catch (error) {
+        console.error(`✗ ${name}`);
+        console.error(`  ${error.message}`);
+        testsFailed++;
+    }
+}
+
+function assertEqual(actual, expected, message) {
+    if (actual !== expected) {
+        throw new Error(`${message}\n  Expected: ${expected}\n  Actual: ${actual}`);
+    }
+}
+
+// Same shape the compiler emits for ref={x}
+const showCtx = createContext(false);
+const itemsCtx = createContext([]);
+
+test('a ref gives a signal the element, or assigns a variable', () => {
+    const inputEl = signal(null);
+    const input = h('input', { ref: bindRef(inputEl, (node) => { throw new Error(`assigned ${node}`); }) });
+    assertEqual(inputEl.value, input, 'the signal holds the element');
+    assertEqual(input.hasAttribute('ref'), false, 'not an attribute');
+
+    let last = null;
+    const item = h('li', { ref: bindRef(last, (node) => { last = node; }) }, 'x');
+    assertEqual(last, item, 'the variable holds the element');
+});
+
+test('a ref in a conditional branch is set when the branch renders', () => {
+    const show = signal(false);
+    const inputEl = signal(null);
+    function Search() {
+        return h('div', null, useContext(showCtx) ? h('input', { ref: bindRef(inputEl, () => {}) }) : h('span', null, 'view'));
+    }
+    mountComponent(() => h('main', null, h(Provide, { context: showCtx, value: show }, () => [h(Search, null)])));
+    assertEqual(inputEl.value, null, 'nothing to point at yet');
+
+    show.value = true;
+    const div = app.childNodes[0].childNodes[0];
+    assertEqual(inputEl.value, div.childNodes[0], 'the input in the document');
+});
+
+test('a ref in a mapped list follows the nodes kept across renders', () => {
+    const items = signal(['a', 'b']);
+    let last = null;
+    function List() {
+        return h('ul', null, useContext(itemsCtx).map(item => h('li', { key: item, ref: bindRef(last, (node) => { last = node; }) }, item)));
+    }
+    mountComponent(() => h('main', null, h(Provide, { context: itemsCtx, value: items }, () => [h(List, null)])));
+    const ul = app.childNodes[0].childNodes[0];
+    const [a] = ul.childNodes;
+
+    items.value = ['b', 'a'];
+    assertEqual(ul.childNodes[1], a, 'a kept and moved last');
+    assertEqual(last, a, 'the ref is the kept node, not the one built for the render');
+});
+
+console.log(`\n${testsPassed} passed, ${testsFailed} failed`);
+if (testsFailed > 0) {
+    process.exit(1);
+}
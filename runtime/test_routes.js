@@ -0,0 +1,100 @@
+/**
+ * Test suite for @route handlers on HttpServer
+ *
+ * Run with: node runtime/test_routes.js
+ */
+
+const { HttpServer, routeParam: __jounce_route_param } = require('./server-runtime.js');
+
+// Simple test framework
+let testsPassed = 0;
+let testsFailed = 0;
+
+async function test(name, fn) {
+    try {
+        await fn();
+        console.log(`✓ ${name}`);
+        testsPassed++;
+    } catch (error) {
+        console.error(`✗ ${name}`);
+        console.error(`  ${error.message}`);
+        testsFailed++;
+    }
+}
+
+function assertEqual(actual, expected, message) {
+    if (actual !== expected) {
+        throw new Error(`${message}\n  Expected: ${expected}\n  Actual: ${actual}`);
+    }
+}
+
+// Records what a handler writes instead of sending it
+function fakeResponse() {
+    return {
+        status: null,
+        body: null,
+        writeHead(status) {
+            this.status = status;
+        },
+        end(body) {
+            this.body = body;
+        },
+    };
+}
+
+async function request(server, pathname) {
+    const route = server.matchRoute(pathname);
+    if (!route) {
+        return { status: 404, body: null };
+    }
+    const res = fakeResponse();
+    await server.handleRoute(route, res);
+    return { status: res.status, body: JSON.parse(res.body) };
+}
+
+let calls = [];
+function get_todo(id, slug) {
+    calls.push([id, slug]);
+    return { id, slug };
+}
+
+// Same shape the compiler emits for
+// @route("/todos/:id/:slug") fn get_todo(id: i32, slug: String)
+const server = new HttpServer(0);
+server.route('/todos/new', async () => 'form');
+server.route('/todos/:id/:slug', async (params) => {
+    const id = __jounce_route_param(params, 'id', 'int');
+    const slug = __jounce_route_param(params, 'slug', 'string');
+    return await get_todo(id, slug);
+});
+
+(async () => {
+    await test('typed params reach the handler parsed', async () => {
+        const response = await request(server, '/todos/42/buy%20milk');
+        assertEqual(response.status, 200, 'status');
+        assertEqual(response.body.id, 42, 'id is a number');
+        assertEqual(response.body.slug, 'buy milk', 'slug is decoded');
+    });
+
+    await test('a bad value is a 404 and the handler never runs', async () => {
+        calls = [];
+        for (const id of ['abc', '4.5', '1e3', '99999999999999999999']) {
+            const response = await request(server, `/todos/${id}/x`);
+            assertEqual(response.status, 404, `status for ${id}`);
+            assertEqual(response.body.error.startsWith('Invalid route param `id`: expected int'), true, `error for ${id}`);
+        }
+        assertEqual(calls.length, 0, 'handler calls');
+    });
+
+    await test('routes match in registration order', async () => {
+        assertEqual((await request(server, '/todos/new')).body, 'form', 'static route');
+        assertEqual((await request(server, '/todos')).status, 404, 'no route');
+        assertEqual(__jounce_route_param({ on: 'true' }, 'on', 'bool'), true, 'bool param');
+        assertEqual(__jounce_route_param({ x: '-2.5' }, 'x', 'float'), -2.5, 'float param');
+    });
+
+    console.log(`\n${testsPassed} passed, ${testsFailed} failed`);
+    if (testsFailed > 0) {
+        process.exit(1);
+    }
+})();
@@ -0,0 +1,179 @@
+/**
+ * Test suite for cancelling server calls when their component unmounts
+ *
+ * Run with: node runtime/test_rpc_cancel.js
+ */
+
+const { app, windowListeners } = require('./test_dom.js');
+
+// Like a browser, report the click handler's rejection if nothing handles it
+const unhandled = [];
+function click(element) {
+    Promise.resolve(element.dispatch('click')[0]).catch(reason => {
+        const event = { reason, defaultPrevented: false, preventDefault() { this.defaultPrevented = true; } };
+        windowListeners.unhandledrejection(event);
+        if (!event.defaultPrevented) {
+            unhandled.push(reason);
+        }
+    });
+}
+
+// A fetch that answers when told to and honours its AbortSignal
+const requests = [];
+global.fetch = (url, options) => new Promise((resolve, reject) => {
+    const request = {
+        url,
+        signal: options.signal,
+        respond: body => resolve({ ok: true, statusText: 'OK', json: async () => body }),
+    };
+    requests.push(request);
+    if (options.signal) {
+        options.signal.addEventListener('abort', () => reject(new Error('The operation was aborted')));
+    }
+});
+
+const { h, mountComponent, unmount, RPCClient, HttpError, rpcSignal: __jounce_rpc_signal } = require('./client-runtime.js');
+const { signal } = require('./reactivity.js');
+
+// Same shape the compiler emits for `@server fn load_count() -> i32` and
+// `async fn refresh(count: Signal<i32>) { count.value = load_count(); }`
+const client = new RPCClient(window.location.origin + '/_rpc');
+async function load_count(__signal = __jounce_rpc_signal()) {
+    return await client.call('load_count', [], __signal);
+}
+async function refresh(count) {
+    const __jounce_signal = __jounce_rpc_signal();
+    count.value = await load_count(__jounce_signal);
+}
+
+// Simple async test framework
+let testsPassed = 0;
+let testsFailed = 0;
+const tests = [];
+
+function test(name, fn) {
+    tests.push({ name, fn });
+}
+
+function assertEqual(actual, expected, message) {
+    if (actual !== expected) {
+        throw new Error(`${message}\n  Expected: ${expected}\n  Actual: ${actual}`);
+    }
+}
+
+const settle = () => new Promise(resolve => setTimeout(resolve, 0));
+
+function mountCounter(count) {
+    function Counter() {
+        return h('button', { onClick: () => refresh(count) }, 'Refresh');
+    }
+    mountComponent(Counter);
+    return app.childNodes[app.childNodes.length - 1];
+}
+
+test('unmount aborts the fetch a handler started', async () => {
+    requests.length = 0;
+    const button = mountCounter(signal(0));
+    click(button);
+    assertEqual(requests.length, 1, 'one request');
+    assertEqual(requests[0].signal.aborted, false, 'in flight');
+
+    unmount(button);
+    assertEqual(requests[0].signal.aborted, true, 'aborted by the unmount');
+    await settle();
+});
+
+test('no state mutation after unmount in the default path', async () => {
+    requests.length = 0;
+    unhandled.length = 0;
+    const count = signal(0);
+    const button = mountCounter(count);
+    click(button);
+    unmount(button);
+
+    // Even a response that raced the abort is dropped
+    requests[0].respond(5);
+    await settle();
+    await settle();
+    assertEqual(count.value, 0, 'count untouched');
+    assertEqual(unhandled.length, 0, 'the cancellation is silent');
+
+    // Mounted components still get their results
+    const live = signal(0);
+    click(mountCounter(live));
+    requests[requests.length - 1].respond(7);
+    await settle();
+    assertEqual(live.value, 7, 'count set while mounted');
+});
+
+test('Cancelled surfaces when the caller handles it', async () => {
+    requests.length = 0;
+    let caught = null;
+    function Saver() {
+        return h('button', {
+            onClick: () => load_count().catch(e => {
+                caught = e;
+            }),
+        }, 'Save');
+    }
+    mountComponent(Saver);
+    const button = app.childNodes[app.childNodes.length - 1];
+    click(button);
+    unmount(button);
+    await settle();
+
+    assertEqual(caught instanceof HttpError, true, 'an HttpError');
+    assertEqual(caught.variant, 'Cancelled', 'the Cancelled variant');
+});
+
+test('calls outside a component are not cancellable', async () => {
+    requests.length = 0;
+    const pending = load_count();
+    assertEqual(requests[0].signal, undefined, 'no signal');
+    requests[0].respond(3);
+    assertEqual(await pending, 3, 'result');
+});
+
+test('other failures keep their variants', async () => {
+    const saved = global.fetch;
+    global.fetch = async () => ({ ok: false, status: 500, statusText: 'Internal Server Error' });
+    try {
+        await client.call('load_count', []);
+        throw new Error('expected a failure');
+    } catch (e) {
+        assertEqual(e.variant, 'StatusError', 'status error');
+        assertEqual(e.data.status, 500, 'status');
+        assertEqual(e.message, 'RPC call failed: Internal Server Error', 'message');
+    }
+
+    global.fetch = async () => {
+        throw new Error('offline');
+    };
+    try {
+        await client.call('load_count', []);
+        throw new Error('expected a failure');
+    } catch (e) {
+        assertEqual(e.variant, 'NetworkError', 'network error');
+    } finally {
+        global.fetch = saved;
+    }
+});
+
+(async () => {
+    for (const { name, fn } of tests) {
+        try {
+            await fn();
+            console.log(`✓ ${name}`);
+            testsPassed++;
+        } catch (error) {
+            console.error(`✗ ${name}`);
+            console.error(`  ${error.message}`);
+            testsFailed++;
+        }
+    }
+
+    console.log(`\n${testsPassed} passed, ${testsFailed} failed`);
+    if (testsFailed > 0) {
+        process.exit(1);
+    }
+})();
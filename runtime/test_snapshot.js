@@ -0,0 +1,191 @@
+/**
+ * Test suite for snapshot assertions used by `jnc test`
+ *
+ * Run with: node runtime/test_snapshot.js
+ */
+
+const fs = require('fs');
+const os = require('os');
+const path = require('path');
+const {
+    h,
+    unsafe_raw,
+    render_to_string,
+    normalize_html,
+    assert_snapshot,
+    __snapshot_context,
+    readSnapshots,
+    snapshotPath,
+} = require('./snapshot.js');
+
+// Simple test framework
+let testsPassed = 0;
+let testsFailed = 0;
+
+function test(name, fn) {
+    try {
+        fn();
+        console.log(`✓ ${name}`);
+        testsPassed++;
+    } catch (error) {
+        console.error(`✗ ${name}`);
+        console.error(`  ${error.message}`);
+        testsFailed++;
+    }
+}
+
+function assertEqual(actual, expected, message) {
+    if (actual !== expected) {
+        throw new Error(`${message}\n  Expected: ${expected}\n  Actual: ${actual}`);
+    }
+}
+
+function assertThrows(fn, expected, message) {
+    let error = null;
+    try {
+        fn();
+    } catch (e) {
+        error = e;
+    }
+    if (!error) {
+        throw new Error(`${message}: nothing was thrown`);
+    }
+    for (const text of expected) {
+        if (!error.message.includes(text)) {
+            throw new Error(`${message}: "${text}" missing from\n${error.message}`);
+        }
+    }
+}
+
+const dir = fs.mkdtempSync(path.join(os.tmpdir(), 'jounce-snapshot-'));
+const testFile = path.join(dir, 'card_test.jnc');
+delete process.env.CI;
+delete process.env.JOUNCE_UPDATE_SNAPSHOTS;
+const log = console.log;
+
+// Same shape the compiler emits for `component Card(title: String)`
+function Card({ title, footer } = {}) {
+    return h('div', { class: "card", id: "c1" }, h('h2', null, title), footer ? h('p', null, footer) : null);
+}
+
+function run(name, fn) {
+    __snapshot_context(name, testFile);
+    console.log = () => {};
+    try {
+        fn();
+    } finally {
+        console.log = log;
+    }
+}
+
+test('render_to_string renders components and escapes text', () => {
+    const html = render_to_string(h(Card, { title: "Tom & Jerry" }));
+    assertEqual(html, '<div class="card" id="c1"><h2>Tom &amp; Jerry</h2></div>', 'rendered HTML');
+    assertEqual(
+        render_to_string(h('input', { disabled: true, onClick: () => {}, key: 1 })),
+        '<input disabled>',
+        'boolean attributes kept, handlers and keys dropped'
+    );
+    assertEqual(
+        render_to_string(h('button', { disabled: false, 'aria-pressed': false })),
+        '<button aria-pressed="false"></button>',
+        'false leaves a boolean attribute out'
+    );
+});
+
+test('render_to_string escapes expressions and attributes', () => {
+    const payload = '<script>alert("x")</script> & \'';
+    assertEqual(
+        render_to_string(h('p', { title: payload }, payload)),
+        '<p title="&lt;script&gt;alert(&quot;x&quot;)&lt;/script&gt; &amp; &#39;">&lt;script&gt;alert(&quot;x&quot;)&lt;/script&gt; &amp; &#39;</p>',
+        'escaped text and attribute'
+    );
+    assertEqual(
+        render_to_string(h('div', null, { tag: 'script', props: {}, children: ['alert(1)'] })),
+        '<div>[object Object]</div>',
+        'an object from data is not an element'
+    );
+    assertEqual(
+        render_to_string(h('div', { inner_html: payload })),
+        '<div>&lt;script&gt;alert(&quot;x&quot;)&lt;/script&gt; &amp; &#39;</div>',
+        'inner_html without unsafe_raw is text'
+    );
+    assertEqual(
+        render_to_string(h('div', { inner_html: unsafe_raw('<b>hi</b>') })),
+        '<div><b>hi</b></div>',
+        'unsafe_raw markup is kept'
+    );
+});
+
+test('normalization sorts attributes and collapses whitespace', () => {
+    assertEqual(
+        normalize_html('<div id="c1"   class=\'card\'>\n   <h2>  Hi\n there </h2><br/></div>'),
+        '<div class="card" id="c1">\n  <h2>Hi there</h2>\n  <br>\n</div>',
+        'normalized form'
+    );
+    assertEqual(normalize_html('<pre>a\n  b</pre>'), '<pre>a\n  b</pre>', 'pre keeps whitespace');
+    assertEqual(
+        normalize_html(render_to_string(h('p', { title: "it's" }, "Don't"))),
+        normalize_html('<p title="it\'s">Don\'t</p>'),
+        'a snapshot can leave quotes unescaped'
+    );
+});
+
+test('first run writes the snapshot file', () => {
+    run('test_card', () => assert_snapshot(render_to_string(h(Card, { title: "Hi" }))));
+    const file = snapshotPath(testFile);
+    assertEqual(file, path.join(dir, '__snapshots__', 'card_test.snap'), 'stored next to the test');
+    const snapshots = readSnapshots(file);
+    assertEqual(snapshots.get('test_card #1'), '<div class="card" id="c1">\n  <h2>Hi</h2>\n</div>', 'stored normalized');
+    // Passes against the stored snapshot afterwards
+    run('test_card', () => assert_snapshot(render_to_string(h(Card, { title: "Hi" }))));
+});
+
+test('a content change fails with a diff', () => {
+    assertThrows(
+        () => run('test_card', () => assert_snapshot(render_to_string(h(Card, { title: "Hello", footer: "Bye" })))),
+        ['Snapshot test_card #1 does not match', '  -   <h2>Hi</h2>\n  +   <h2>Hello</h2>\n  +   <p>Bye</p>', '    <div class="card" id="c1">'],
+        'mismatch reported'
+    );
+    assertEqual(readSnapshots(snapshotPath(testFile)).get('test_card #1').includes('Hi'), true, 'snapshot left unchanged');
+});
+
+test('reordered attributes and whitespace do not fail', () => {
+    run('test_card', () => assert_snapshot('<div id="c1" class="card">\n      <h2>Hi</h2>   </div>'));
+    run('test_inline', () => assert_snapshot(
+        render_to_string(h(Card, { title: "Hi" })),
+        '<div id="c1" class="card"><h2>Hi</h2></div>'
+    ));
+});
+
+test('inline snapshot mismatch shows the new output', () => {
+    assertThrows(
+        () => run('test_inline', () => assert_snapshot('<p>new</p>', '<p>old</p>')),
+        ['Inline snapshot test_inline #1 does not match', '  - <p>old</p>', '  + <p>new</p>', 'New snapshot:\n<p>new</p>'],
+        'inline mismatch reported'
+    );
+});
+
+test('update mode rewrites and CI refuses to record', () => {
+    process.env.JOUNCE_UPDATE_SNAPSHOTS = '1';
+    run('test_card', () => assert_snapshot('<p>updated</p>'));
+    delete process.env.JOUNCE_UPDATE_SNAPSHOTS;
+    assertEqual(readSnapshots(snapshotPath(testFile)).get('test_card #1'), '<p>updated</p>', 'rewritten');
+
+    process.env.CI = 'true';
+    assertThrows(() => run('test_new', () => assert_snapshot('<p>x</p>')), ['test_new #1 is missing'], 'missing snapshot in CI');
+    delete process.env.CI;
+});
+
+test('content lines that look like headers round-trip', () => {
+    run('test_headers', () => assert_snapshot('<pre>== not a header ==\n\\x</pre>'));
+    run('test_headers', () => assert_snapshot('<pre>== not a header ==\n\\x</pre>'));
+    assertEqual(readSnapshots(snapshotPath(testFile)).size, 2, 'two entries');
+});
+
+fs.rmSync(dir, { recursive: true, force: true });
+
+console.log(`\n${testsPassed} passed, ${testsFailed} failed`);
+if (testsFailed > 0) {
+    process.exit(1);
+}
@@ -0,0 +1,102 @@
+/**
+ * Test suite for --test-ids (data-jounce-component / data-jounce-key)
+ *
+ * Run with: node runtime/test_test_ids.js
+ */
+
+const { app } = require('./test_dom.js');
+const { h, mountComponent, enableTestIds } = require('./client-runtime.js');
+
+// Simple test framework
+let testsPassed = 0;
+let testsFailed = 0;
+
+function test(name, fn) {
+    try {
+        fn();
+        console.log(`✓ ${name}`);
+        testsPassed++;
+    } catch (error) {
+        console.error(`✗ ${name}`);
+        console.error(`  ${error.message}`);
+        testsFailed++;
+    }
+}
+
+function assertEqual(actual, expected, message) {
+    if (actual !== expected) {
+        throw new Error(`${message}\n  Expected: ${expected}\n  Actual: ${actual}`);
+    }
+}
+
+// Same shape the compiler emits for <li key={todo.id}>{todo.title}</li> and <TodoItem key={todo.id} />
+function TodoItem({ todo }) {
+    return h('li', null, todo.title);
+}
+
+function TodoList() {
+    const todos = [{ id: 1, title: 'a' }, { id: 2, title: 'b' }];
+    return h('ul', null, todos.map(todo => h('li', { key: todo.id }, todo.title)));
+}
+
+function ItemList() {
+    const todos = [{ id: 7, title: 'x' }];
+    return h('ol', null, todos.map(todo => h(TodoItem, { key: todo.id, todo })));
+}
+
+function Page() {
+    return h(TodoList, null);
+}
+
+function mount(component) {
+    mountComponent(() => h('main', null, h(component, null)));
+    return app.childNodes[0].childNodes[0];
+}
+
+test('nothing is stamped unless test ids are enabled', () => {
+    const ul = mount(TodoList);
+    assertEqual(ul.hasAttribute('data-jounce-component'), false, 'no component attribute');
+    assertEqual(ul.childNodes[0].hasAttribute('data-jounce-key'), false, 'no key attribute');
+    assertEqual(ul.childNodes[0].__jounceKey, 1, 'the key is still tracked');
+});
+
+test('component roots carry their component name', () => {
+    enableTestIds();
+    try {
+        const ul = mount(TodoList);
+        assertEqual(ul.getAttribute('data-jounce-component'), 'TodoList', 'root stamped');
+        assertEqual(ul.childNodes[0].hasAttribute('data-jounce-component'), false, 'plain elements are not');
+    } finally {
+        enableTestIds(false);
+    }
+});
+
+test('a component returning another keeps the innermost name', () => {
+    enableTestIds();
+    try {
+        assertEqual(mount(Page).getAttribute('data-jounce-component'), 'TodoList', 'inner name kept');
+    } finally {
+        enableTestIds(false);
+    }
+});
+
+test('keyed elements and keyed components carry their key', () => {
+    enableTestIds();
+    try {
+        const ul = mount(TodoList);
+        assertEqual(ul.childNodes[0].getAttribute('data-jounce-key'), '1', 'first item');
+        assertEqual(ul.childNodes[1].getAttribute('data-jounce-key'), '2', 'second item');
+
+        const li = mount(ItemList).childNodes[0];
+        assertEqual(li.getAttribute('data-jounce-component'), 'TodoItem', 'component name');
+        assertEqual(li.getAttribute('data-jounce-key'), '7', 'component key');
+        assertEqual(li.hasAttribute('key'), false, 'the key is not an attribute itself');
+    } finally {
+        enableTestIds(false);
+    }
+});
+
+console.log(`\n${testsPassed} passed, ${testsFailed} failed`);
+if (testsFailed > 0) {
+    process.exit(1);
+}
@@ -0,0 +1,376 @@
+// Accessibility Lint - Checks over JSX (opt in with `jnc compile --lint a11y`)
+//
+// Catches what a compiler can see from the markup alone: images without alt
+// text, clickable elements a keyboard can't reach, form fields without a
+// label, and positive tabindex values. Any of them can be silenced with
+// `@allow(<lint>)` on the element (covering its children too) or on the
+// component or function; `@allow(a11y)` silences the whole group.
+
+use crate::ast::{Expression, JsxAttribute, JsxChild, JsxElement, Program, Statement};
+use crate::diagnostics::{Diagnostic, SourceLocation};
+use std::collections::HashSet;
+
+/// Name of the group, for `--lint` and `@allow`
+pub const A11Y_LINT_GROUP: &str = "a11y";
+
+/// `<img>` without an `alt` attribute
+pub const IMG_ALT_LINT: &str = "a11y_img_alt";
+
+/// Click handler on an element that is neither focusable nor announced as interactive
+pub const CLICK_ROLE_LINT: &str = "a11y_click_role";
+
+/// Form field with no associated `<label>`
+pub const INPUT_LABEL_LINT: &str = "a11y_input_label";
+
+/// `tabindex` greater than zero
+pub const POSITIVE_TABINDEX_LINT: &str = "a11y_tabindex";
+
+/// Elements that are focusable and interactive without extra attributes
+const INTERACTIVE_ELEMENTS: &[&str] = &["a", "button", "details", "input", "option", "select", "summary", "textarea"];
+
+/// `<input>` types that are buttons or invisible, and so need no label
+const UNLABELLED_INPUT_TYPES: &[&str] = &["button", "hidden", "image", "reset", "submit"];
+
+/// Run every accessibility lint over a program's components and functions
+pub fn check_program(program: &Program, file: &str) -> Vec<Diagnostic> {
+    let mut checker = Checker { file, allows: Vec::new(), item: ItemState::default(), diagnostics: Vec::new() };
+    for statement in &program.statements {
+        match statement {
+            Statement::Component(comp) => checker.check_item(&comp.allows, &comp.body.statements),
+            Statement::Function(func) => checker.check_item(&func.allows, &func.body.statements),
+            _ => {}
+        }
+    }
+    // Unlabelled fields are only reported once their item is done
+    checker.diagnostics.sort_by_key(|diagnostic| diagnostic.location.as_ref().map(|location| (location.line, location.column)));
+    checker.diagnostics
+}
+
+/// What the label check learns while walking one item
+#[derive(Default)]
+struct ItemState {
+    /// `for` of every `<label>` in the item
+    label_targets: HashSet<String>,
+    /// How many `<label>` elements enclose the current element
+    label_depth: usize,
+    /// Fields outside a label, by `id`; reported unless a label points at them
+    unlabelled: Vec<(Option<String>, Diagnostic)>,
+}
+
+struct Checker<'a> {
+    file: &'a str,
+    /// Lints silenced by the current item and the elements enclosing the current one
+    allows: Vec<String>,
+    item: ItemState,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Checker<'_> {
+    fn check_item(&mut self, allows: &[String], statements: &[Statement]) {
+        self.allows = allows.to_vec();
+        self.item = ItemState::default();
+        self.check_statements(statements);
+
+        let item = std::mem::take(&mut self.item);
+        for (id, diagnostic) in item.unlabelled {
+            if id.is_none_or(|id| !item.label_targets.contains(&id)) {
+                self.diagnostics.push(diagnostic);
+            }
+        }
+    }
+
+    fn check_statements(&mut self, statements: &[Statement]) {
+        for statement in statements {
+            self.check_statement(statement);
+        }
+    }
+
+    fn check_statement(&mut self, statement: &Statement) {
+        match statement {
+            Statement::Expression(expr) => self.check_expression(expr),
+            Statement::Return(ret) => self.check_expression(&ret.value),
+            Statement::Let(let_stmt) => self.check_expression(&let_stmt.value),
+            Statement::If(if_stmt) => {
+                self.check_statements(&if_stmt.then_branch.statements);
+                if let Some(else_branch) = &if_stmt.else_branch {
+                    self.check_statement(else_branch);
+                }
+            }
+            Statement::While(while_stmt) => self.check_statements(&while_stmt.body.statements),
+            Statement::For(for_stmt) => self.check_statements(&for_stmt.body.statements),
+            Statement::ForIn(for_in) => self.check_statements(&for_in.body.statements),
+            _ => {}
+        }
+    }
+
+    fn check_expression(&mut self, expr: &Expression) {
+        match expr {
+            Expression::JsxElement(jsx) => self.check_element(jsx),
+            Expression::IfExpression(if_expr) => {
+                self.check_expression(&if_expr.then_expr);
+                if let Some(else_expr) = &if_expr.else_expr {
+                    self.check_expression(else_expr);
+                }
+            }
+            Expression::Ternary(ternary) => {
+                self.check_expression(&ternary.true_expr);
+                self.check_expression(&ternary.false_expr);
+            }
+            Expression::Block(block) => self.check_statements(&block.statements),
+            Expression::Match(match_expr) => {
+                for arm in &match_expr.arms {
+                    self.check_expression(&arm.body);
+                }
+            }
+            Expression::FunctionCall(call) => {
+                for arg in &call.arguments {
+                    self.check_expression(arg);
+                }
+            }
+            Expression::ArrayLiteral(array) => {
+                for element in &array.elements {
+                    self.check_expression(element);
+                }
+            }
+            Expression::Lambda(lambda) => self.check_expression(&lambda.body),
+            _ => {}
+        }
+    }
+
+    fn check_element(&mut self, jsx: &JsxElement) {
+        let outer_allows = self.allows.len();
+        self.allows.extend(jsx.opening_tag.allows.iter().cloned());
+
+        let tag = jsx.tag_name();
+        // Components render their own markup; only HTML elements are checked
+        if tag.starts_with(|c: char| c.is_lowercase()) {
+            self.check_img_alt(jsx);
+            self.check_click_role(jsx);
+            self.check_input_label(jsx);
+            self.check_tabindex(jsx);
+        }
+
+        if tag == "label" {
+            if let Some(Expression::StringLiteral(target)) = attribute(jsx, &["for", "htmlFor"]).map(|attr| &attr.value) {
+                self.item.label_targets.insert(target.clone());
+            }
+            self.item.label_depth += 1;
+        }
+        for attr in &jsx.opening_tag.attributes {
+            self.check_expression(&attr.value);
+        }
+        for child in &jsx.children {
+            match child {
+                JsxChild::Element(element) => self.check_element(element),
+                JsxChild::Expression(expr) => self.check_expression(expr),
+                JsxChild::Text(_) => {}
+            }
+        }
+        if tag == "label" {
+            self.item.label_depth -= 1;
+        }
+
+        self.allows.truncate(outer_allows);
+    }
+
+    fn check_img_alt(&mut self, jsx: &JsxElement) {
+        if jsx.tag_name() != "img" || attribute(jsx, &["alt"]).is_some() || has_spread(jsx) || self.allowed(IMG_ALT_LINT) {
+            return;
+        }
+        let diagnostic = self
+            .warning(jsx, "`<img>` has no `alt` text", "W009", IMG_ALT_LINT)
+            .with_suggestion("describe the image with `alt=\"...\"`, or use `alt=\"\"` if it is purely decorative");
+        self.diagnostics.push(diagnostic);
+    }
+
+    fn check_click_role(&mut self, jsx: &JsxElement) {
+        let tag = jsx.tag_name();
+        let Some(handler) = attribute(jsx, &["onClick", "onclick"]) else {
+            return;
+        };
+        if INTERACTIVE_ELEMENTS.contains(&tag) || has_spread(jsx) || self.allowed(CLICK_ROLE_LINT) {
+            return;
+        }
+        let missing: Vec<&str> = [("role", &["role"][..]), ("tabindex", &["tabindex", "tabIndex"][..])]
+            .into_iter()
+            .filter(|(_, names)| attribute(jsx, names).is_none())
+            .map(|(name, _)| name)
+            .collect();
+        if missing.is_empty() {
+            return;
+        }
+        let missing = missing.iter().map(|name| format!("`{}`", name)).collect::<Vec<_>>().join(" or ");
+        let diagnostic = self
+            .warning(jsx, format!("`<{}>` has a click handler but no {}", tag, missing), "W010", CLICK_ROLE_LINT)
+            .with_label(self.location(&handler.name), "keyboard users can't trigger this")
+            .with_suggestion("use a `<button>`, or add `role=\"button\"` and `tabindex={0}`");
+        self.diagnostics.push(diagnostic);
+    }
+
+    fn check_input_label(&mut self, jsx: &JsxElement) {
+        let tag = jsx.tag_name();
+        if !matches!(tag, "input" | "select" | "textarea")
+            || self.item.label_depth > 0
+            || has_spread(jsx)
+            || self.allowed(INPUT_LABEL_LINT)
+        {
+            return;
+        }
+        let input_type = match attribute(jsx, &["type"]).map(|attr| &attr.value) {
+            Some(Expression::StringLiteral(input_type)) => input_type.as_str(),
+            _ => "",
+        };
+        if tag == "input" && UNLABELLED_INPUT_TYPES.contains(&input_type) {
+            return;
+        }
+
+        let id = attribute(jsx, &["id"]);
+        let literal_id = match id.map(|attr| &attr.value) {
+            Some(Expression::StringLiteral(id)) => Some(id.clone()),
+            // A computed id may match a label we can't see; give it the benefit of the doubt
+            Some(_) => return,
+            None => None,
+        };
+        let mut diagnostic = self
+            .warning(jsx, format!("`<{}>` has no label", tag), "W011", INPUT_LABEL_LINT)
+            .with_suggestion("wrap it in a `<label>`, or give it an `id` and add a `<label for=\"...\">`");
+        if let (Some(attr), Some(id)) = (id, &literal_id) {
+            diagnostic = diagnostic.with_label(self.location(&attr.name), format!("no `<label for=\"{}\">` in this item", id));
+        }
+        self.item.unlabelled.push((literal_id, diagnostic));
+    }
+
+    fn check_tabindex(&mut self, jsx: &JsxElement) {
+        let Some(attr) = attribute(jsx, &["tabindex", "tabIndex"]) else {
+            return;
+        };
+        let value = match &attr.value {
+            Expression::IntegerLiteral(value) => *value,
+            Expression::StringLiteral(value) => value.trim().parse().unwrap_or(0),
+            _ => 0,
+        };
+        if value <= 0 || self.allowed(POSITIVE_TABINDEX_LINT) {
+            return;
+        }
+        let diagnostic = Diagnostic::warning(format!(
+            "`tabindex` of {} moves `<{}>` ahead of the page's natural tab order",
+            value,
+            jsx.tag_name()
+        ))
+        .at(self.location(&attr.name))
+        .with_code("W012")
+        .with_label(self.location(&jsx.opening_tag.name), "on this element")
+        .with_suggestion("use `tabindex={0}` to make it focusable in document order")
+        .with_note(silence_note(POSITIVE_TABINDEX_LINT));
+        self.diagnostics.push(diagnostic);
+    }
+
+    fn allowed(&self, lint: &str) -> bool {
+        self.allows.iter().any(|name| name == lint || name == A11Y_LINT_GROUP)
+    }
+
+    /// A warning at the element's tag name
+    fn warning(&self, jsx: &JsxElement, message: impl Into<String>, code: &str, lint: &str) -> Diagnostic {
+        Diagnostic::warning(message)
+            .at(self.location(&jsx.opening_tag.name))
+            .with_code(code)
+            .with_note(silence_note(lint))
+    }
+
+    fn location(&self, ident: &crate::ast::Identifier) -> SourceLocation {
+        SourceLocation { file: self.file.to_string(), ..SourceLocation::from_identifier(ident) }
+    }
+}
+
+/// The first attribute with any of `names`
+fn attribute<'a>(jsx: &'a JsxElement, names: &[&str]) -> Option<&'a JsxAttribute> {
+    jsx.opening_tag.attributes.iter().find(|attr| names.contains(&attr.name.value.as_str()))
+}
+
+/// Whether a `{...props}` spread may supply attributes we can't see; like a
+/// computed id, it gets the benefit of the doubt
+fn has_spread(jsx: &JsxElement) -> bool {
+    jsx.opening_tag.attributes.iter().any(|attr| attr.spread().is_some())
+}
+
+fn silence_note(lint: &str) -> String {
+    format!("silence with `@allow({})` on the element or component", lint)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    /// Lint codes reported for a component rendering `markup`
+    fn codes(markup: &str) -> Vec<String> {
+        let source = format!("component App() {{\n    return {};\n}}\n", markup);
+        let mut lexer = Lexer::new(source);
+        let program = Parser::new(&mut lexer).parse_program().expect("parse");
+        check_program(&program, "app.jnc")
+            .into_iter()
+            .map(|diagnostic| diagnostic.code.unwrap_or_default())
+            .collect()
+    }
+
+    #[test]
+    fn test_img_alt() {
+        assert_eq!(codes(r#"<img src="logo.png" />"#), vec!["W009"]);
+        assert!(codes(r#"<img src="logo.png" alt="Logo" />"#).is_empty());
+        assert!(codes(r#"<img src="divider.png" alt="" />"#).is_empty());
+    }
+
+    #[test]
+    fn test_click_handler_needs_role_and_tabindex() {
+        assert_eq!(codes("<div onClick={|| close()}>\"x\"</div>"), vec!["W010"]);
+        assert_eq!(codes("<div role=\"button\" onClick={|| close()}>\"x\"</div>"), vec!["W010"]);
+        assert!(codes("<div role=\"button\" tabindex={0} onClick={|| close()}>\"x\"</div>").is_empty());
+        assert!(codes("<button onClick={|| close()}>\"x\"</button>").is_empty());
+    }
+
+    #[test]
+    fn test_input_label_association() {
+        assert_eq!(codes(r#"<form><input id="name" /></form>"#), vec!["W011"]);
+        assert_eq!(codes("<form><select></select></form>"), vec!["W011"]);
+        // A label pointing at the field's id, before or after it
+        assert!(codes(r#"<form><label for="name">"Name"</label><input id="name" /></form>"#).is_empty());
+        assert!(codes(r#"<form><input id="name" /><label for="name">"Name"</label></form>"#).is_empty());
+        // A label wrapping the field
+        assert!(codes(r#"<label>"Name"<input type="text" /></label>"#).is_empty());
+        assert!(codes(r#"<form><input type="submit" /></form>"#).is_empty());
+    }
+
+    #[test]
+    fn test_positive_tabindex() {
+        assert_eq!(codes("<span tabindex={3}>\"x\"</span>"), vec!["W012"]);
+        assert!(codes("<span tabindex={0}>\"x\"</span>").is_empty());
+        assert!(codes("<span tabindex={-1}>\"x\"</span>").is_empty());
+    }
+
+    #[test]
+    fn test_allow_on_element_and_component() {
+        assert!(codes(r#"<img @allow(a11y_img_alt) src="logo.png" />"#).is_empty());
+        assert!(codes(r#"<div @allow(a11y)><img src="logo.png" /><input /></div>"#).is_empty());
+        // Only the named lint is silenced
+        assert_eq!(codes(r#"<div @allow(a11y_img_alt)><img src="logo.png" /><input /></div>"#), vec!["W011"]);
+
+        let source = "@allow(a11y_img_alt)\ncomponent Logo() {\n    return <img src=\"logo.png\" />;\n}\n";
+        let mut lexer = Lexer::new(source.to_string());
+        let program = Parser::new(&mut lexer).parse_program().expect("parse");
+        assert!(check_program(&program, "logo.jnc").is_empty());
+    }
+
+    #[test]
+    fn test_spans_point_at_element_and_attribute() {
+        let source = "component App() {\n    return <div onClick={|| close()}>\"x\"</div>;\n}\n";
+        let mut lexer = Lexer::new(source.to_string());
+        let program = Parser::new(&mut lexer).parse_program().expect("parse");
+        let diagnostics = check_program(&program, "app.jnc");
+        let location = diagnostics[0].location.as_ref().unwrap();
+        assert_eq!((location.file.as_str(), location.line, location.length), ("app.jnc", 2, 3));
+        let label = &diagnostics[0].labels[0].location;
+        assert_eq!((label.line, label.length), (2, "onClick".len()));
+        assert!(diagnostics[0].message.contains("`role` or `tabindex`"), "{}", diagnostics[0].message);
+    }
+}
@@ -1,11 +1,27 @@
+//! The Jounce syntax tree, as produced by `Parser::parse_program`.
+//!
+//! These node types are public so that tools outside the compiler (formatters,
+//! lint plugins, codemods) can read and rewrite programs. Traverse them with
+//! [`crate::visit::Visit`], or rewrite them in place with
+//! [`crate::visit_mut::VisitMut`], rather than matching every node by hand.
+//!
+//! Stability: within a major version, node types, variants and fields are
+//! only ever added, never renamed or removed. Additions do break code that
+//! matches every variant, destructures a node without `..`, or builds one
+//! with a struct literal, and may land in a minor release. To keep compiling
+//! across them, match enums with a `_` arm, destructure with `..`, and
+//! prefer the visitor traits, which give new node kinds a default method.
+//! Positions and the serialized (serde) form are not covered and may change
+//! in any release.
+
 use crate::token::Token;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Program {
     pub statements: Vec<Statement>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub enum Statement {
     Use(UseStatement),
     Let(LetStatement),
@@ -32,13 +48,64 @@ pub enum Statement {
     Theme(ThemeBlock),       // Phase 13: theme DarkMode { ... }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct UseStatement {
     pub path: Vec<Identifier>,
-    pub imports: Vec<Identifier>,
+    pub imports: Vec<UseTree>,  // Empty: import everything from `path`
+    pub is_pub: bool,           // `pub use` re-exports the imported names
+}
+
+/// One entry of a use-tree: `Name`, `Name as Alias`, `*`, or `sub::{...}`
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum UseTree {
+    Name { name: Identifier, alias: Option<Identifier> },
+    Glob,
+    Nested { path: Vec<Identifier>, items: Vec<UseTree> },
+}
+
+/// A flattened use-tree entry with its full module path
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum UseItem {
+    Name { module: Vec<String>, name: String, alias: String },
+    Glob { module: Vec<String> },
+}
+
+impl UseStatement {
+    /// Flatten nested trees into one item per imported name or glob
+    pub fn items(&self) -> Vec<UseItem> {
+        let module: Vec<String> = self.path.iter().map(|ident| ident.value.clone()).collect();
+        if self.imports.is_empty() {
+            return vec![UseItem::Glob { module }];
+        }
+        let mut items = Vec::new();
+        for tree in &self.imports {
+            tree.flatten(&module, &mut items);
+        }
+        items
+    }
 }
 
-#[derive(Debug, Clone)]
+impl UseTree {
+    fn flatten(&self, module: &[String], items: &mut Vec<UseItem>) {
+        match self {
+            UseTree::Name { name, alias } => items.push(UseItem::Name {
+                module: module.to_vec(),
+                name: name.value.clone(),
+                alias: alias.as_ref().unwrap_or(name).value.clone(),
+            }),
+            UseTree::Glob => items.push(UseItem::Glob { module: module.to_vec() }),
+            UseTree::Nested { path, items: trees } => {
+                let mut nested = module.to_vec();
+                nested.extend(path.iter().map(|ident| ident.value.clone()));
+                for tree in trees {
+                    tree.flatten(&nested, items);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct LetStatement {
     pub pattern: Pattern,  // Changed from 'name' to support destructuring
     pub mutable: bool,
@@ -46,38 +113,38 @@ pub struct LetStatement {
     pub value: Expression,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ConstDeclaration {
     pub name: Identifier,
     pub type_annotation: Option<TypeExpression>,
     pub value: Expression,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct AssignmentStatement {
     pub target: Expression,  // Can be Identifier, FieldAccess, IndexAccess, etc.
     pub value: Expression,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ReturnStatement {
     pub value: Expression,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct IfStatement {
     pub condition: Expression,
     pub then_branch: BlockStatement,
     pub else_branch: Option<Box<Statement>>,  // Can be another if or block
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct WhileStatement {
     pub condition: Expression,
     pub body: BlockStatement,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ForStatement {
     pub init: Option<Box<Statement>>,  // Optional initialization (let i = 0)
     pub condition: Expression,          // Loop condition
@@ -85,72 +152,84 @@ pub struct ForStatement {
     pub body: BlockStatement,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ForInStatement {
     pub variable: Identifier,          // Loop variable (e.g., "item" in "for item in collection")
     pub iterator: Expression,          // The expression to iterate over
     pub body: BlockStatement,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct LoopStatement {
     pub body: BlockStatement,  // Infinite loop body
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct MacroInvocation {
     pub name: Identifier,
     pub input_tokens: Vec<Token>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct StructDefinition {
     pub name: Identifier,
+    pub doc: Option<String>,  // Doc text from /// comments before it
     pub lifetime_params: Vec<Lifetime>,  // Lifetime parameters like <'a, 'b>
     pub type_params: Vec<TypeParam>,  // Generic type parameters like <T>, <T: Display>
     pub fields: Vec<(Identifier, TypeExpression)>,
     pub derives: Vec<String>,  // Derive macros: #[derive(Debug, Clone, etc.)]
+    pub attributes: Vec<Attribute>,  // #[...] attributes written before it
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct EnumDefinition {
     pub name: Identifier,
+    pub doc: Option<String>,  // Doc text from /// comments before it
     pub lifetime_params: Vec<Lifetime>,  // Lifetime parameters like <'a, 'b>
     pub type_params: Vec<TypeParam>,  // Generic type parameters like <T>, <T: Display>
     pub variants: Vec<EnumVariant>,
     pub derives: Vec<String>,  // Derive macros: #[derive(Debug, Clone, etc.)]
+    pub attributes: Vec<Attribute>,  // #[...] attributes written before it
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct EnumVariant {
     pub name: Identifier,
     pub fields: Option<Vec<(Identifier, TypeExpression)>>,  // For tuple/struct variants
+    pub attributes: Vec<Attribute>,  // #[...] attributes written before it
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct FunctionDefinition {
     pub name: Identifier,
+    pub doc: Option<String>,  // Doc text from /// comments before it, then //! ones opening its body
     pub lifetime_params: Vec<Lifetime>,  // Lifetime parameters like <'a, 'b>
     pub type_params: Vec<TypeParam>,  // Generic type parameters like <T>, <T: Display>
     pub parameters: Vec<FunctionParameter>,
+    pub return_type: Option<TypeExpression>,
+    pub return_type_position: Position,
+    pub memo: Option<usize>,  // @memo / @memo(N): cache results of the last N calls
+    pub allows: Vec<String>,  // @allow(...): lints silenced for this function
+    pub route: Option<RouteAttribute>,  // @route("/path/:param"): serve this function at a URL
+    pub attributes: Vec<Attribute>,  // #[...] attributes written before it
     pub is_server: bool,
     pub is_client: bool,
     pub is_async: bool,
     pub body: BlockStatement,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct BlockStatement {
     pub statements: Vec<Statement>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ExternBlock {
     pub abi: String,
     pub functions: Vec<FunctionDeclaration>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct FunctionDeclaration {
     pub name: Identifier,
     pub parameters: Vec<FunctionParameter>,
@@ -159,53 +238,192 @@ pub struct FunctionDeclaration {
 
 // CSS-related AST nodes
 
+// Source position of a CSS node: line/column of the token that starts it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct CssSpan {
+    pub line: usize,
+    pub column: usize,
+}
+
 // CSS macro expression: css! { ... }
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct CssExpression {
-    pub rules: Vec<CssRule>,
-    pub keyframes: Vec<CssKeyframes>,  // Sprint 2 Task 2.6
+    pub items: Vec<CssItem>,
+    pub imports: Vec<CssImport>,
+    pub font_faces: Vec<CssFontFace>,
+    pub pages: Vec<CssPage>,
+    pub unknown_at_rules: Vec<CssUnknownAtRule>,  // Skipped, and warned about
+}
+
+impl CssExpression {
+    /// The block's top-level rules, leaving out those inside at-rules
+    pub fn rules(&self) -> impl Iterator<Item = &CssRule> {
+        self.items.iter().filter_map(|item| match item {
+            CssItem::Rule(rule) => Some(rule),
+            _ => None,
+        })
+    }
+
+    /// The block's top-level @media blocks
+    pub fn media_queries(&self) -> impl Iterator<Item = &CssMediaQuery> {
+        self.items.iter().filter_map(|item| match item {
+            CssItem::Media(media) => Some(media),
+            _ => None,
+        })
+    }
+
+    /// The block's top-level @supports blocks
+    pub fn supports(&self) -> impl Iterator<Item = &CssSupports> {
+        self.items.iter().filter_map(|item| match item {
+            CssItem::Supports(supports) => Some(supports),
+            _ => None,
+        })
+    }
+
+    /// The block's @keyframes
+    pub fn keyframes(&self) -> impl Iterator<Item = &CssKeyframes> {
+        self.items.iter().filter_map(|item| match item {
+            CssItem::Keyframes(keyframes) => Some(keyframes),
+            _ => None,
+        })
+    }
+
+    /// Every class the block's selectors name, in order, nested rules and
+    /// those in @media and @supports included. Classes inside `:global(...)`
+    /// aren't the block's, and are left out.
+    pub fn classes(&self) -> Vec<CssClassUse> {
+        fn walk(rule: &CssRule, classes: &mut Vec<CssClassUse>) {
+            CssSelector::map_classes(&rule.selector.to_source(), |name, subject, offset| {
+                let span = CssSpan { column: rule.span.column + offset, ..rule.span };
+                classes.push(CssClassUse { name: name.to_string(), subject, span });
+                name.to_string()
+            });
+            for nested in rule.nested_rules.iter().chain(rule.media_queries.iter().flat_map(|media| &media.rules)) {
+                walk(nested, classes);
+            }
+        }
+
+        let mut classes = Vec::new();
+        let rules = self.items.iter()
+            .flat_map(|item| match item {
+                CssItem::Rule(rule) => std::slice::from_ref(rule),
+                CssItem::Media(media) => media.rules.as_slice(),
+                CssItem::Supports(supports) => supports.rules.as_slice(),
+                CssItem::Keyframes(_) => &[],
+            });
+        for rule in rules {
+            walk(rule, &mut classes);
+        }
+        classes
+    }
+}
+
+// A rule, @media, @supports or @keyframes block at the top of css!. They're kept in one list, in
+// source order, because that order decides which of two equally specific
+// rules wins.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub enum CssItem {
+    Rule(CssRule),
+    Media(CssMediaQuery),
+    Supports(CssSupports),
+    Keyframes(CssKeyframes),  // Sprint 2 Task 2.6
+}
+
+// A class named in a css! selector. It's in the subject when it's part of
+// the element the rule styles, `.title` in `.card > .title`; a class only
+// named around it, like `.card` there, is declared by some other rule. Its
+// span points at the class's `.`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CssClassUse {
+    pub name: String,
+    pub subject: bool,
+    pub span: CssSpan,
 }
 
 // CSS rule: .button { ... }
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct CssRule {
     pub selector: CssSelector,
     pub declarations: Vec<CssDeclaration>,
     pub nested_rules: Vec<CssRule>,  // For Sprint 2 nesting
     pub media_queries: Vec<CssMediaQuery>,  // For Sprint 2 media queries
     pub container_queries: Vec<CssContainerQuery>,  // For Phase 8 container queries
+    pub span: CssSpan,
 }
 
-// CSS media query: @media (min-width: 768px) { ... }
-#[derive(Debug, Clone)]
+// CSS media query: @media (min-width: 768px) { ... }, nested in a rule or
+// at the top of css!
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct CssMediaQuery {
-    pub condition: String,  // "(min-width: 768px)"
-    pub declarations: Vec<CssDeclaration>,  // Declarations within this media query
+    pub condition: String,  // "screen and (min-width: 768px)"
+    pub declarations: Vec<CssDeclaration>,  // Declarations within this media query, for the rule it's in
+    pub rules: Vec<CssRule>,  // Whole rules within it: &:hover { ... }, or .card { ... } at the top
+    pub span: CssSpan,
 }
 
 // CSS container query: @container (min-width: 400px) { ... }
 // Phase 8 Sprint 1 Task 1.4
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct CssContainerQuery {
     pub condition: String,  // "(min-width: 400px)"
     pub declarations: Vec<CssDeclaration>,  // Declarations within this container query
+    pub span: CssSpan,
+}
+
+// CSS import: @import url("/theme.css") screen;
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CssImport {
+    pub source: CssValue,  // url("/theme.css") screen, or just "/theme.css"
+    pub span: CssSpan,
+}
+
+// CSS font face: @font-face { font-family: "Inter"; src: url("/inter.woff2") format("woff2"); }
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CssFontFace {
+    pub declarations: Vec<CssDeclaration>,
+    pub span: CssSpan,
+}
+
+// CSS page rule: @page :first { margin: 1in; }
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CssPage {
+    pub selector: String,  // ":first", or empty for every page
+    pub declarations: Vec<CssDeclaration>,
+    pub span: CssSpan,
+}
+
+// CSS feature query: @supports (display: grid) { .grid { ... } }
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CssSupports {
+    pub condition: String,  // "(display: grid)"
+    pub rules: Vec<CssRule>,
+    pub span: CssSpan,
+}
+
+// An at-rule css! doesn't support, like @layer or a nested @page; its block is skipped
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CssUnknownAtRule {
+    pub name: String,  // "layer", without the @
+    pub span: CssSpan,
 }
 
 // CSS keyframes: @keyframes fadeIn { from { ... } to { ... } }
 // Sprint 2 Task 2.6
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct CssKeyframes {
     pub name: String,  // "fadeIn" (will be scoped to "Component_fadeIn_hash")
     pub frames: Vec<CssKeyframeRule>,
+    pub span: CssSpan,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct CssKeyframeRule {
-    pub selector: CssKeyframeSelector,  // from, to, 50%
+    pub selectors: Vec<CssKeyframeSelector>,  // from, to, 50%, or several: 0%, 100%
     pub declarations: Vec<CssDeclaration>,
+    pub span: CssSpan,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub enum CssKeyframeSelector {
     From,
     To,
@@ -213,26 +431,273 @@ pub enum CssKeyframeSelector {
 }
 
 // CSS selector: .button, #id, div, etc.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub enum CssSelector {
     Class(String),           // .button
     Id(String),              // #main
     Element(String),         // div, button
     PseudoClass(String),     // :hover, :focus, :active, :disabled
     PseudoElement(String),   // ::before, ::after, ::first-line
-    Nested(String),          // & for nesting (Sprint 2)
+    Nested(String),          // & for nesting (Sprint 2), or with combinators: .card > .title
+    Attribute(String),       // [type="checkbox"], without the brackets
     Compound(Vec<CssSelector>), // .button:hover
+    List(Vec<CssSelector>),  // h1, h2, .title
+}
+
+impl CssSelector {
+    /// The chars of a selector, each with whether it's at the top level:
+    /// outside `(...)`, `[...]` and quoted strings, where `,` `.` `:` and
+    /// spaces mean nothing to the selector around them. An opening `(` or
+    /// `[` is at the top level; what it opens isn't.
+    pub fn top_level_chars(selector: &str) -> impl Iterator<Item = (usize, char, bool)> + '_ {
+        selector.char_indices().scan((0usize, None, false), |(depth, quote, escaped), (index, ch)| {
+            let top = *depth == 0 && quote.is_none();
+            match *quote {
+                Some(_) if *escaped => *escaped = false,
+                Some(_) if ch == '\\' => *escaped = true,
+                Some(open) if ch == open => *quote = None,
+                Some(_) => {}
+                None => match ch {
+                    '"' | '\'' => *quote = Some(ch),
+                    '(' | '[' => *depth += 1,
+                    ')' | ']' => *depth = depth.saturating_sub(1),
+                    _ => {}
+                },
+            }
+            Some((index, ch, top))
+        })
+    }
+
+    /// Splits a selector list like `h1, h2:is(.a, .b)` at its top-level commas
+    pub fn split_list(selector: &str) -> Vec<&str> {
+        let mut parts = Vec::new();
+        let mut start = 0;
+        for (index, ch, top) in Self::top_level_chars(selector) {
+            if ch == ',' && top {
+                parts.push(selector[start..index].trim());
+                start = index + 1;
+            }
+        }
+        parts.push(selector[start..].trim());
+        parts
+    }
+
+    /// The selector as written: `.button:hover`, `h1, .title`
+    pub fn to_source(&self) -> String {
+        match self {
+            CssSelector::Class(name) => format!(".{}", name),
+            CssSelector::Id(name) => format!("#{}", name),
+            CssSelector::Element(name) | CssSelector::Nested(name) => name.clone(),
+            CssSelector::PseudoClass(name) => format!(":{}", name),
+            CssSelector::PseudoElement(name) => format!("::{}", name),
+            CssSelector::Attribute(attribute) => format!("[{}]", attribute),
+            CssSelector::Compound(selectors) => selectors.iter().map(Self::to_source).collect(),
+            CssSelector::List(selectors) => selectors.iter().map(Self::to_source).collect::<Vec<_>>().join(", "),
+        }
+    }
+
+    /// Rewrites each class of a selector with `rename`, which is also told
+    /// whether the class is in the subject: the last compound selector, not
+    /// in parentheses, like `.title` in `.card > .title` but not `.x` in
+    /// `.title:not(.x)`, and the byte offset of its `.` in `selector`. What
+    /// `:global(...)` wraps is kept as written, and the wrapper dropped; so
+    /// are attribute values.
+    pub fn map_classes(selector: &str, mut rename: impl FnMut(&str, bool, usize) -> String) -> String {
+        // Each list entry's subject starts after its last top-level combinator
+        let mut subjects = Vec::new();
+        let (mut start, mut combinator) = (0, false);
+        for (index, ch, top) in Self::top_level_chars(selector) {
+            match ch {
+                _ if !top => {}
+                ',' => {
+                    subjects.push(start..index);
+                    start = index + 1;
+                    combinator = false;
+                }
+                _ if ch.is_whitespace() || matches!(ch, '>' | '+' | '~') => combinator = true,
+                _ if combinator => {
+                    start = index;
+                    combinator = false;
+                }
+                _ => {}
+            }
+        }
+        subjects.push(start..selector.len());
+
+        let mut mapped = String::with_capacity(selector.len());
+        let (mut parens, mut brackets, mut quote) = (0usize, 0usize, None);
+        let mut index = 0;
+        while let Some(ch) = selector[index..].chars().next() {
+            let rest = &selector[index..];
+            match (ch, quote) {
+                (_, Some(open)) if ch == open => quote = None,
+                (_, Some(_)) => {}
+                ('"' | '\'', None) => quote = Some(ch),
+                ('[', None) => brackets += 1,
+                (']', None) => brackets = brackets.saturating_sub(1),
+                ('(', None) => parens += 1,
+                (')', None) => parens = parens.saturating_sub(1),
+                (':', None) if brackets == 0 && rest.starts_with(":global(") => {
+                    let inner = &rest[":global(".len()..];
+                    let mut depth = 0usize;
+                    let end = inner.char_indices()
+                        .find(|&(_, ch)| match ch {
+                            '(' => { depth += 1; false }
+                            ')' if depth == 0 => true,
+                            ')' => { depth -= 1; false }
+                            _ => false,
+                        })
+                        .map_or(inner.len(), |(end, _)| end);
+                    mapped.push_str(&inner[..end]);
+                    index += ":global(".len() + (end + 1).min(inner.len());
+                    continue;
+                }
+                ('.', None) if brackets == 0 => {
+                    let name = &rest[1..];
+                    let len = name.find(|ch: char| !(ch.is_alphanumeric() || ch == '-' || ch == '_')).unwrap_or(name.len());
+                    if len > 0 {
+                        let subject = parens == 0 && subjects.iter().any(|range| range.contains(&index));
+                        mapped.push('.');
+                        mapped.push_str(&rename(&name[..len], subject, index));
+                        index += 1 + len;
+                        continue;
+                    }
+                }
+                _ => {}
+            }
+            mapped.push(ch);
+            index += ch.len_utf8();
+        }
+        mapped
+    }
+
+    /// Whether a selector combines others with ` `, `>`, `+` or `~`, as in
+    /// `.card > .title` but not `li:nth-child(2n+1)` or `[title="a b"]`
+    pub fn has_combinator(selector: &str) -> bool {
+        Self::top_level_chars(selector).any(|(_, ch, top)| top && (ch.is_whitespace() || matches!(ch, '>' | '+' | '~')))
+    }
 }
 
 // CSS declaration: background: blue;
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct CssDeclaration {
     pub property: String,
     pub value: CssValue,
+    #[serde(default)]
+    pub span: CssSpan,  // Of the property name
+}
+
+// Inline style attribute: style="color: {color}; padding: 4px", or
+// style={{ color: color, padding: 4 }} as an object
+// Parsed at compile time; static values are validated like css! declarations
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct InlineStyle {
+    pub declarations: Vec<InlineStyleDeclaration>,
+    #[serde(default)]
+    pub object: bool,  // Written as an object, where numbers are lengths in px
+}
+
+// classes!("btn", "btn-primary" => is_primary): bare entries are always
+// included, `class => condition` entries only while the condition holds
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ClassesMacro {
+    pub entries: Vec<ClassEntry>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ClassEntry {
+    pub class: Expression,
+    pub condition: Option<Expression>,
+}
+
+impl ClassesMacro {
+    /// Class and condition expressions, in source order
+    pub fn expressions(&self) -> impl Iterator<Item = &Expression> {
+        self.entries.iter().flat_map(|entry| std::iter::once(&entry.class).chain(entry.condition.as_ref()))
+    }
+}
+
+// Template string: `Hello, ${name}!` - text with expressions spliced in,
+// each converted to a string
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TemplateLiteral {
+    pub parts: Vec<TemplatePart>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub enum TemplatePart {
+    Text(String),
+    Interpolation(Box<Expression>),  // ${expr}
+}
+
+impl TemplateLiteral {
+    /// The interpolated expressions, in source order
+    pub fn expressions(&self) -> impl Iterator<Item = &Expression> {
+        self.parts.iter().filter_map(|part| match part {
+            TemplatePart::Interpolation(expr) => Some(expr.as_ref()),
+            TemplatePart::Text(_) => None,
+        })
+    }
+}
+
+// One `property: value` of an inline style, positioned at its property name
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct InlineStyleDeclaration {
+    pub property: String,
+    pub value: Vec<InlineStylePart>,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Properties whose numbers have no unit, as React has them; any other
+/// property in a style object takes a number as px
+const UNITLESS_PROPERTIES: &[&str] = &[
+    "animation-iteration-count", "aspect-ratio", "border-image-outset", "border-image-slice",
+    "border-image-width", "box-flex", "box-flex-group", "box-ordinal-group", "column-count", "columns",
+    "flex", "flex-grow", "flex-positive", "flex-shrink", "flex-negative", "flex-order", "font-weight",
+    "grid-area", "grid-row", "grid-row-end", "grid-row-span", "grid-row-start", "grid-column",
+    "grid-column-end", "grid-column-span", "grid-column-start", "line-clamp", "line-height", "opacity",
+    "order", "orphans", "scale", "tab-size", "widows", "z-index", "zoom", "fill-opacity",
+    "flood-opacity", "stop-opacity", "stroke-dasharray", "stroke-dashoffset", "stroke-miterlimit",
+    "stroke-opacity", "stroke-width",
+];
+
+impl InlineStyleDeclaration {
+    /// Whether a number set in a style object is a length in px: not for
+    /// custom properties, and not for unitless ones like `opacity` or a
+    /// vendor-prefixed `-webkit-line-clamp`
+    pub fn number_is_px(&self) -> bool {
+        if self.property.starts_with("--") {
+            return false;
+        }
+        let unprefixed = match self.property.strip_prefix('-') {
+            Some(prefixed) => prefixed.split_once('-').map_or(prefixed, |(_, rest)| rest),
+            None => &self.property,
+        };
+        !UNITLESS_PROPERTIES.contains(&unprefixed)
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub enum InlineStylePart {
+    Static(String),
+    Dynamic(Box<Expression>),  // {expr}: one value, CSS-escaped at runtime
+}
+
+impl InlineStyle {
+    /// The interpolated expressions, in source order
+    pub fn expressions(&self) -> impl Iterator<Item = &Expression> {
+        self.declarations.iter().flat_map(|decl| {
+            decl.value.iter().filter_map(|part| match part {
+                InlineStylePart::Dynamic(expr) => Some(expr.as_ref()),
+                InlineStylePart::Static(_) => None,
+            })
+        })
+    }
 }
 
 // CSS value types
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub enum CssValue {
     Color(String),           // blue, #ff0000, rgb(255,0,0)
     Length(f64, String),     // 12px, 1.5rem, 50%
@@ -247,7 +712,7 @@ pub enum CssValue {
 // Phase 13: Style System AST Nodes
 
 // Style block: style Button { background: blue; &:hover { ... } }
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct StyleBlock {
     pub name: Identifier,
     pub properties: Vec<StyleProperty>,
@@ -255,53 +720,54 @@ pub struct StyleBlock {
 }
 
 // Theme block: theme DarkMode { primary: #1a1a1a; text: #ffffff; }
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ThemeBlock {
     pub name: Identifier,
     pub properties: Vec<ThemeProperty>,
 }
 
 // Style property: background: blue; or color: theme.DarkMode.text;
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct StyleProperty {
     pub name: String,           // CSS property name (background, padding, etc.)
     pub value: StyleValue,      // Property value
 }
 
 // Theme property: primary: #1a1a1a;
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ThemeProperty {
     pub name: String,           // Theme variable name (primary, text, etc.)
     pub value: String,          // CSS value (color, size, etc.)
 }
 
 // Style value: can be a literal or a theme reference
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub enum StyleValue {
     Literal(String),                              // "blue", "12px", "#ff0000"
     ThemeRef { theme: String, property: String }, // theme.DarkMode.primary
 }
 
 // Nested selector: &:hover { background: red; } or &.active { ... }
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct NestedSelector {
     pub selector: SelectorType,
     pub properties: Vec<StyleProperty>,
 }
 
 // Selector type for nested styles
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub enum SelectorType {
     PseudoClass(String),  // :hover, :focus, :active, :disabled
     Class(String),        // .active, .disabled
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub enum Expression {
     Identifier(Identifier),
     IntegerLiteral(i64),
     FloatLiteral(String),
     StringLiteral(String),
+    Template(TemplateLiteral),  // `text ${expr}`
     BoolLiteral(bool),
     UnitLiteral,  // () - the unit type value
     ArrayLiteral(ArrayLiteral),
@@ -328,6 +794,8 @@ pub enum Expression {
     Await(AwaitExpression),  // await expr (async/await)
     Block(BlockStatement),  // { statements... } as an expression (for match arms, etc.)
     CssMacro(CssExpression),  // css! { ... } macro for styles
+    InlineStyle(InlineStyle),  // style="..." attribute on a JSX element
+    Classes(ClassesMacro),  // classes!("btn", "active" => is_active)
     // Reactivity primitives (Phase 12)
     Signal(SignalExpression),  // signal<T>(initial_value)
     Computed(ComputedExpression),  // computed<T>(() => expr)
@@ -335,136 +803,163 @@ pub enum Expression {
     Batch(BatchExpression),  // batch(() => { })
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
 pub struct Identifier {
     pub value: String,
+    pub position: Position,  // Where the name is, for diagnostics; not part of its identity
+}
+
+impl Identifier {
+    /// An identifier with no source position, for synthesized nodes
+    pub fn new(value: impl Into<String>) -> Self {
+        Identifier { value: value.into(), position: Position::default() }
+    }
+}
+
+impl PartialEq for Identifier {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl Eq for Identifier {}
+
+impl std::hash::Hash for Identifier {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.value.hash(state);
+    }
 }
 
 // Lifetime annotation like 'a, 'b, 'static
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
 pub struct Lifetime {
     pub name: String,  // e.g., "a" for 'a, "static" for 'static
 }
 
 // Type parameter with optional trait bounds
 // Examples: T, T: Display, T: Display + Clone
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
 pub struct TypeParam {
     pub name: Identifier,
     pub bounds: Vec<Identifier>,  // trait bounds (e.g., ["Display", "Clone"])
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct PrefixExpression {
     pub operator: Token,
     pub right: Box<Expression>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct SpreadExpression {
     pub expression: Box<Expression>,  // The expression being spread (e.g., arr in ...arr)
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct InfixExpression {
     pub left: Box<Expression>,
     pub operator: Token,
     pub right: Box<Expression>,
+    /// An `==` written `=` where a condition goes (`if x = 5`), read as the
+    /// comparison and warned about. `operator` is placed at the `=`.
+    #[serde(default)]
+    pub assign_typo: bool,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct BorrowExpression {
     pub expression: Box<Expression>,  // The expression being borrowed
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct MutableBorrowExpression {
     pub expression: Box<Expression>,  // The expression being mutably borrowed
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct DereferenceExpression {
     pub expression: Box<Expression>,  // The expression being dereferenced
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct RangeExpression {
     pub start: Option<Box<Expression>>,  // Start of range (None for ..end)
     pub end: Option<Box<Expression>>,    // End of range (None for start..)
     pub inclusive: bool,                  // true for ..=, false for ..
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct TryOperatorExpression {
     pub expression: Box<Expression>,  // The expression being tried (must return Result<T, E>)
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct TernaryExpression {
     pub condition: Box<Expression>,     // The condition to test
     pub true_expr: Box<Expression>,     // Expression if condition is true
     pub false_expr: Box<Expression>,    // Expression if condition is false
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct TypeCastExpression {
     pub expression: Box<Expression>,  // The expression being cast
     pub target_type: TypeExpression,  // The type to cast to
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct AwaitExpression {
     pub expression: Box<Expression>,  // The expression being awaited (must return Future<T>)
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ArrayLiteral {
     pub elements: Vec<Expression>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct TupleLiteral {
     pub elements: Vec<Expression>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct StructLiteral {
     pub name: Identifier,
     pub fields: Vec<(Identifier, Expression)>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct FieldAccessExpression {
     pub object: Box<Expression>,
     pub field: Identifier,
+    pub optional: bool,  // `object?.field`: missing when the object is
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct IndexExpression {
     pub array: Box<Expression>,
     pub index: Box<Expression>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct MatchExpression {
     pub scrutinee: Box<Expression>,  // The value being matched
     pub arms: Vec<MatchArm>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct MatchArm {
     pub patterns: Vec<Pattern>,  // Support OR patterns: 3 | 4 | 5 => ...
     pub body: Box<Expression>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct IfExpression {
     pub condition: Box<Expression>,
     pub then_expr: Box<Expression>,
     pub else_expr: Option<Box<Expression>>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub enum Pattern {
     Identifier(Identifier),           // x (binds to variable)
     Tuple(Vec<Pattern>),              // (a, b, c) (destructure tuple)
@@ -493,14 +988,52 @@ impl Pattern {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct FunctionParameter {
     pub name: Identifier,
     pub type_annotation: TypeExpression,
+    pub position: Position,  // Where the parameter is declared, for diagnostics
+    pub is_slot: bool,  // `slot header: Element` on a component, filled with <slot:header>...</slot:header>
+}
+
+/// The slot a component's unnamed children fill: `slot children: Element`
+pub const DEFAULT_SLOT: &str = "children";
+
+impl FunctionParameter {
+    /// A slot typed `Option<Element>` may be left out; it then renders nothing
+    pub fn is_optional_slot(&self) -> bool {
+        self.is_slot && matches!(&self.type_annotation, TypeExpression::Generic(name, _) if name.value == "Option")
+    }
+}
+
+/// `@route("/todos/:id")` on a server function
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct RouteAttribute {
+    pub path: String,
+    pub position: Position,  // Where the path string is, for diagnostics
+}
+
+/// `#[name]` or `#[name(...)]` before an item. `tokens` are the ones inside
+/// the parentheses, kept as written for whatever reads the attribute.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Attribute {
+    pub name: Identifier,
+    pub tokens: Vec<Token>,
+}
+
+/// Number of calls a bare `@memo` function remembers
+pub const DEFAULT_MEMO_CAPACITY: usize = 64;
+
+// Line/column of a declaration (parameters, return types), used to point
+// diagnostics back at where an expected type came from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
 }
 
 // This is the single, correct definition for TypeExpression
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub enum TypeExpression {
     Named(Identifier),
     Generic(Identifier, Vec<TypeExpression>),
@@ -526,15 +1059,26 @@ pub enum TypeExpression {
 /// - `opening_tag`: The opening tag with name, attributes, and self-closing flag
 /// - `children`: Child elements, text nodes, or expressions
 /// - `closing_tag`: The closing tag name (None for self-closing tags)
+/// - `unknown_entities`: References in its text that stand for no character
 ///
 /// # Parser Notes
 /// The parser should ensure closing_tag matches opening_tag.name for regular elements.
 /// For self-closing elements (opening_tag.self_closing = true), closing_tag should be None.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct JsxElement {
     pub opening_tag: JsxOpeningTag,
     pub children: Vec<JsxChild>,
     pub closing_tag: Option<Identifier>,
+    #[serde(default)]
+    pub unknown_entities: Vec<JsxUnknownEntity>,
+}
+
+/// A reference in JSX text that stands for no character, like `&chips;`.
+/// It's shown as written, and warned about.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct JsxUnknownEntity {
+    pub reference: String,  // "&chips;", as written
+    pub position: Position,  // Of the `&`
 }
 
 /// Represents the opening tag of a JSX element
@@ -546,13 +1090,20 @@ pub struct JsxElement {
 ///
 /// # Fields
 /// - `name`: Tag name (lowercase for HTML elements, PascalCase for components)
+/// - `type_args`: A generic component's type arguments, `<List<User> ...>`
 /// - `attributes`: List of attributes/props
-/// - `self_closing`: True if tag ends with `/>`
-#[derive(Debug, Clone)]
+/// - `self_closing`: True if tag ends with `/>`, or is a void element like `<br>`
+/// - `slash_omitted`: A void element written without its `/>`
+/// - `allows`: Lints silenced by `@allow(...)` among the attributes
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct JsxOpeningTag {
     pub name: Identifier,
+    pub type_args: Vec<TypeExpression>,
     pub attributes: Vec<JsxAttribute>,
     pub self_closing: bool,
+    pub slash_omitted: bool,
+    pub position: Position,  // Where the tag name is, for diagnostics
+    pub allows: Vec<String>,  // @allow(...): lints silenced for this element and its children
 }
 
 /// Represents a child node within JSX content
@@ -574,7 +1125,7 @@ pub struct JsxOpeningTag {
 /// # Parser Notes
 /// Text is read automatically by the lexer when in JSX mode.
 /// Expressions are enclosed in {} and can be any valid expression.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub enum JsxChild {
     /// A nested JSX element
     Element(Box<JsxElement>),
@@ -591,6 +1142,7 @@ pub enum JsxChild {
 /// - Expression attribute: `value={count}` → name="value", value=Identifier(count)
 /// - Event handler: `onClick={handleClick}` → name="onClick", value=Identifier(handleClick)
 /// - Boolean shorthand: `disabled` → name="disabled", value=BoolLiteral(true)
+/// - Spread: `{...props}` → name="...", value=Spread(props)
 ///
 /// # Attribute Types
 /// - String literals: `attr="value"`
@@ -602,23 +1154,107 @@ pub enum JsxChild {
 /// - For `attr="value"`, value is StringLiteral
 /// - For `attr={expr}`, value is the expression inside {}
 /// - For `attr` alone (no =), value should be BoolLiteral(true)
-#[derive(Debug, Clone)]
+/// - For `{...expr}`, name is `JSX_SPREAD` and value is Spread(expr); its
+///   props are merged with the others in source order, later ones winning
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct JsxAttribute {
     pub name: Identifier,
     pub value: Expression,
 }
 
+/// The name of a `{...props}` attribute
+pub const JSX_SPREAD: &str = "...";
+
+impl JsxAttribute {
+    /// What a `{...props}` attribute spreads: `props`
+    pub fn spread(&self) -> Option<&Expression> {
+        match &self.value {
+            Expression::Spread(spread) if self.name.value == JSX_SPREAD => Some(&spread.expression),
+            _ => None,
+        }
+    }
+}
+
+/// Two-way binding of a form field to a signal: `<input bind:value={text} />`
+pub const BIND_VALUE: &str = "bind:value";
+
+/// Markup set as an HTML element's contents, without escaping:
+/// `<div unsafe_html={markup} />`. The element can't have children too.
+pub const UNSAFE_HTML: &str = "unsafe_html";
+
+/// Elements whose children are one run of text up to the closing tag,
+/// braces and `<` included, as in HTML
+pub fn is_raw_text_element(tag: &str) -> bool {
+    matches!(tag, "style" | "script")
+}
+
+/// Identifies an item of a list across renders: `<li key={todo.id}>`. The
+/// runtime keeps the item's DOM nodes when its key is still there, and neither
+/// components nor the rendered HTML see it as a prop.
+pub const JSX_KEY: &str = "key";
+
+/// Hands an HTML element's DOM node to a variable or signal when the element
+/// is created: `<input ref={input_el} />`. Components don't take it.
+pub const JSX_REF: &str = "ref";
+
+/// What can follow an HTML element's event handler, as in
+/// `onSubmit|prevent={save}`: `prevent`, `stop` and `self` guard the handler,
+/// the others are options of its listener
+pub const EVENT_MODIFIERS: [&str; 6] = ["prevent", "stop", "self", "once", "capture", "passive"];
+
+/// What a `bind:value` field shows of its signal and which event writes back
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueBinding {
+    /// Text inputs and textareas: the string, on every keystroke
+    Text,
+    /// `type="number"` and `"range"`: the number, skipping input that isn't one yet
+    Number,
+    /// A checkbox without a value: whether it's checked
+    Checkbox,
+    /// Checkboxes with a value: a list holding the values of those checked
+    CheckboxGroup,
+    /// Radio buttons: the value of the one checked
+    Radio,
+    /// `<select>`: the selected option's value
+    Select,
+    /// `<select multiple>`: the list of selected values
+    SelectMultiple,
+}
+
+impl ValueBinding {
+    /// The element property the signal drives
+    pub fn property(self) -> &'static str {
+        match self {
+            ValueBinding::Checkbox | ValueBinding::CheckboxGroup | ValueBinding::Radio => "checked",
+            _ => "value",
+        }
+    }
+
+    /// The handler attribute that writes edits back to the signal
+    pub fn handler(self) -> &'static str {
+        match self {
+            ValueBinding::Text | ValueBinding::Number => "oninput",
+            _ => "onchange",
+        }
+    }
+}
+
 impl JsxElement {
     /// Creates a new JSX element with the given tag name and empty children
     pub fn new(tag_name: String) -> Self {
         JsxElement {
             opening_tag: JsxOpeningTag {
-                name: Identifier { value: tag_name.clone() },
+                name: Identifier::new(tag_name.clone()),
+                type_args: Vec::new(),
                 attributes: Vec::new(),
                 self_closing: false,
+                slash_omitted: false,
+                position: Position::default(),
+                allows: Vec::new(),
             },
             children: Vec::new(),
-            closing_tag: Some(Identifier { value: tag_name }),
+            closing_tag: Some(Identifier::new(tag_name)),
+            unknown_entities: Vec::new(),
         }
     }
 
@@ -626,12 +1262,17 @@ impl JsxElement {
     pub fn new_self_closing(tag_name: String) -> Self {
         JsxElement {
             opening_tag: JsxOpeningTag {
-                name: Identifier { value: tag_name },
+                name: Identifier::new(tag_name),
+                type_args: Vec::new(),
                 attributes: Vec::new(),
                 self_closing: true,
+                slash_omitted: false,
+                position: Position::default(),
+                allows: Vec::new(),
             },
             children: Vec::new(),
             closing_tag: None,
+            unknown_entities: Vec::new(),
         }
     }
 
@@ -645,6 +1286,63 @@ impl JsxElement {
         &self.opening_tag.name.value
     }
 
+    /// `header` for a `<slot:header>` element, which fills a slot of the
+    /// component it's a child of
+    pub fn slot_name(&self) -> Option<&str> {
+        self.tag_name().strip_prefix("slot:")
+    }
+
+    /// The attribute named `name`, if the element has one
+    pub fn attribute(&self, name: &str) -> Option<&JsxAttribute> {
+        self.opening_tag.attributes.iter().find(|attr| attr.name.value == name)
+    }
+
+    /// Whether the tag names a component (`Card`, `ui.Button`) rather than
+    /// an HTML element
+    pub fn is_component(&self) -> bool {
+        self.tag_name().starts_with(|c: char| c.is_uppercase()) || self.tag_name().contains('.')
+    }
+
+    /// The `unsafe_html` attribute of an HTML element; a component gets it
+    /// as an ordinary prop
+    pub fn unsafe_html(&self) -> Option<&JsxAttribute> {
+        if self.is_component() { None } else { self.attribute(UNSAFE_HTML) }
+    }
+
+    /// Whether the element's text is CSS or JavaScript, kept as written
+    /// rather than read as JSX: a `<style>` or `<script>`
+    pub fn has_raw_text(&self) -> bool {
+        is_raw_text_element(self.tag_name())
+    }
+
+    /// The `ref` attribute of an HTML element
+    pub fn element_ref(&self) -> Option<&JsxAttribute> {
+        if self.is_component() { None } else { self.attribute(JSX_REF) }
+    }
+
+    /// How `bind:value` works on this element, or None when it isn't a form
+    /// field. Only a literal `type` counts; a computed one binds as text.
+    pub fn value_binding(&self) -> Option<ValueBinding> {
+        let input_type = match self.attribute("type").map(|attr| &attr.value) {
+            Some(Expression::StringLiteral(input_type)) => input_type.as_str(),
+            _ => "text",
+        };
+        let multiple = self.attribute("multiple").is_some_and(|attr| !matches!(attr.value, Expression::BoolLiteral(false)));
+        match self.tag_name() {
+            "select" if multiple => Some(ValueBinding::SelectMultiple),
+            "select" => Some(ValueBinding::Select),
+            "textarea" => Some(ValueBinding::Text),
+            "input" => Some(match input_type {
+                "checkbox" if self.attribute("value").is_some() => ValueBinding::CheckboxGroup,
+                "checkbox" => ValueBinding::Checkbox,
+                "radio" => ValueBinding::Radio,
+                "number" | "range" => ValueBinding::Number,
+                _ => ValueBinding::Text,
+            }),
+            _ => None,
+        }
+    }
+
     /// Adds a child to this element
     pub fn add_child(&mut self, child: JsxChild) {
         self.children.push(child);
@@ -658,7 +1356,7 @@ impl JsxElement {
     /// Adds an attribute to this element
     pub fn add_attribute(&mut self, name: String, value: Expression) {
         self.opening_tag.attributes.push(JsxAttribute {
-            name: Identifier { value: name },
+            name: Identifier::new(name),
             value,
         });
     }
@@ -668,7 +1366,7 @@ impl JsxAttribute {
     /// Creates a new JSX attribute with a string literal value
     pub fn new_string(name: String, value: String) -> Self {
         JsxAttribute {
-            name: Identifier { value: name },
+            name: Identifier::new(name),
             value: Expression::StringLiteral(value),
         }
     }
@@ -676,7 +1374,7 @@ impl JsxAttribute {
     /// Creates a new JSX attribute with an expression value
     pub fn new_expr(name: String, value: Expression) -> Self {
         JsxAttribute {
-            name: Identifier { value: name },
+            name: Identifier::new(name),
             value,
         }
     }
@@ -684,7 +1382,7 @@ impl JsxAttribute {
     /// Creates a new boolean attribute (just the name, implicitly true)
     pub fn new_bool(name: String) -> Self {
         JsxAttribute {
-            name: Identifier { value: name },
+            name: Identifier::new(name),
             value: Expression::BoolLiteral(true),
         }
     }
@@ -693,41 +1391,49 @@ impl JsxAttribute {
     pub fn is_event_handler(&self) -> bool {
         self.name.value.starts_with("on")
     }
+
+    /// `onSubmit` and its modifiers, `["prevent", "once"]`, for an attribute
+    /// named `onSubmit|prevent|once`
+    pub fn event_modifiers(&self) -> (&str, Vec<&str>) {
+        let mut parts = self.name.value.split('|');
+        let handler = parts.next().unwrap_or_default();
+        (handler, parts.collect())
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct FunctionCall {
     pub function: Box<Expression>,
     pub arguments: Vec<Expression>,
     pub type_params: Option<Vec<TypeExpression>>,  // For turbofish syntax: func::<T>()
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct MacroCall {
     pub name: Identifier,
     pub arguments: Vec<Expression>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub enum CaptureMode {
     ByReference,       // Capture by immutable reference (&)
     ByMutableReference, // Capture by mutable reference (&mut)
     ByValue,           // Capture by value (move)
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct CapturedVariable {
     pub name: Identifier,
     pub mode: CaptureMode,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct LambdaParameter {
     pub name: Identifier,
     pub type_annotation: Option<TypeExpression>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct LambdaExpression {
     pub parameters: Vec<LambdaParameter>,
     pub return_type: Option<TypeExpression>,
@@ -735,15 +1441,17 @@ pub struct LambdaExpression {
     pub captures: Vec<CapturedVariable>,  // Variables captured from environment
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ComponentDefinition {
     pub name: Identifier,
+    pub doc: Option<String>,  // Doc text from /// comments before it, then //! ones opening its body
     pub parameters: Vec<FunctionParameter>,
+    pub allows: Vec<String>,  // @allow(...): lints silenced for this component
     pub is_client: bool,  // Components are client-side by default
     pub body: BlockStatement,  // Component body contains statements
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ImplBlock {
     pub trait_name: Option<Identifier>,  // None for inherent impl, Some for trait impl
     pub lifetime_params: Vec<Lifetime>,  // Lifetime parameters like <'a, 'b>
@@ -752,25 +1460,28 @@ pub struct ImplBlock {
     pub methods: Vec<ImplMethod>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ImplMethod {
     pub name: Identifier,
+    pub doc: Option<String>,  // Doc text from /// comments before it
     pub parameters: Vec<FunctionParameter>,  // First parameter is usually &self or self
     pub return_type: Option<TypeExpression>,
     pub body: BlockStatement,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct TraitDefinition {
     pub name: Identifier,
+    pub doc: Option<String>,  // Doc text from /// comments before it
     pub lifetime_params: Vec<Lifetime>,  // Lifetime parameters like <'a, 'b>
     pub type_params: Vec<TypeParam>,  // Generic type parameters like <T>, <T: Display>
     pub methods: Vec<TraitMethod>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct TraitMethod {
     pub name: Identifier,
+    pub doc: Option<String>,  // Doc text from /// comments before it
     pub parameters: Vec<FunctionParameter>,
     pub return_type: Option<TypeExpression>,
 }
@@ -781,7 +1492,7 @@ pub struct TraitMethod {
 
 /// Signal expression: signal<T>(initial_value)
 /// Creates a reactive signal with an initial value
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct SignalExpression {
     pub type_annotation: Option<TypeExpression>,  // Optional type parameter: signal<int>()
     pub initial_value: Box<Expression>,            // The initial value
@@ -789,7 +1500,7 @@ pub struct SignalExpression {
 
 /// Computed expression: computed<T>(() => expr)
 /// Creates a derived reactive value that automatically updates
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ComputedExpression {
     pub type_annotation: Option<TypeExpression>,  // Optional type parameter: computed<int>()
     pub computation: Box<Expression>,              // Must be a lambda/closure
@@ -797,14 +1508,14 @@ pub struct ComputedExpression {
 
 /// Effect expression: effect(() => { ... })
 /// Creates a side effect that re-runs when dependencies change
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct EffectExpression {
     pub callback: Box<Expression>,  // Must be a lambda/closure
 }
 
 /// Batch expression: batch(() => { ... })
 /// Batches multiple signal updates to prevent redundant effect executions
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct BatchExpression {
     pub body: Box<Expression>,  // Must be a lambda/closure
 }
\ No newline at end of file
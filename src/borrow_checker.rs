@@ -461,6 +461,24 @@ impl BorrowChecker {
                 // CSS is checked separately in Sprint 1 Task 1.6
                 Ok(ResolvedType::Unknown)
             }
+            Expression::InlineStyle(style) => {
+                for expr in style.expressions() {
+                    self.check_expression(expr)?;
+                }
+                Ok(ResolvedType::Unknown)
+            }
+            Expression::Classes(classes) => {
+                for expr in classes.expressions() {
+                    self.check_expression(expr)?;
+                }
+                Ok(ResolvedType::Unknown)
+            }
+            Expression::Template(template) => {
+                for expr in template.expressions() {
+                    self.check_expression(expr)?;
+                }
+                Ok(ResolvedType::String)
+            }
             // Reactivity primitives (Phase 12)
             Expression::Signal(signal_expr) => {
                 self.check_expression(&signal_expr.initial_value)?;
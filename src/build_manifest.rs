@@ -0,0 +1,93 @@
+// Build Manifest - Machine-readable summary of a compiled app
+//
+// Written next to the bundles so test harnesses and tooling can map
+// source-level names (like `.button`) to what the build actually emitted.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// File name of the manifest inside the output directory
+pub const MANIFEST_FILE: &str = "jounce-manifest.json";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BuildManifest {
+    /// Source file the build started from
+    pub entry: String,
+    /// Whether this was a release (minified) build
+    pub release: bool,
+    /// Whether `data-jounce-component` / `data-jounce-key` attributes are stamped
+    pub test_ids: bool,
+    /// Scoped class names: component -> (class -> scoped class)
+    pub classes: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl BuildManifest {
+    pub fn new(entry: impl Into<String>) -> Self {
+        BuildManifest {
+            entry: entry.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Resolve a class (`.button` or `button`) to its scoped name.
+    /// When several components define the same class, the first component
+    /// in name order wins; use `resolve_component_class` to disambiguate.
+    pub fn resolve_class(&self, class: &str) -> Option<&str> {
+        let class = class.trim_start_matches('.');
+        self.classes
+            .values()
+            .find_map(|map| map.get(class))
+            .map(String::as_str)
+    }
+
+    /// Resolve a class defined by a specific component
+    pub fn resolve_component_class(&self, component: &str, class: &str) -> Option<&str> {
+        self.classes
+            .get(component)?
+            .get(class.trim_start_matches('.'))
+            .map(String::as_str)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("build manifest is always serializable")
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest() -> BuildManifest {
+        let mut manifest = BuildManifest::new("app.jnc");
+        manifest.classes.insert(
+            "Button".to_string(),
+            BTreeMap::from([("button".to_string(), "Button_button_a3f5c9".to_string())]),
+        );
+        manifest.classes.insert(
+            "Card".to_string(),
+            BTreeMap::from([("button".to_string(), "Card_button_77aa01".to_string())]),
+        );
+        manifest
+    }
+
+    #[test]
+    fn test_resolve_class() {
+        let manifest = manifest();
+        assert_eq!(manifest.resolve_class(".button"), Some("Button_button_a3f5c9"));
+        assert_eq!(manifest.resolve_class("button"), Some("Button_button_a3f5c9"));
+        assert_eq!(manifest.resolve_component_class("Card", ".button"), Some("Card_button_77aa01"));
+        assert_eq!(manifest.resolve_class(".missing"), None);
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let manifest = manifest();
+        let json = manifest.to_json();
+        assert!(json.contains("\"Button_button_a3f5c9\""));
+        assert_eq!(BuildManifest::from_json(&json).unwrap(), manifest);
+    }
+}
@@ -0,0 +1,449 @@
+// Build Report - Where a bundle's bytes come from
+//
+// `jnc compile --report` writes this next to the bundles: emitted size per
+// source module (before and after minification), the stdlib namespaces the
+// client bundle carries and which items use them, CSS size per component,
+// and the module dependency edges. Sizes come from the emitter's per-item
+// accounting; whatever a bundle holds beyond its items is runtime prelude,
+// so module sizes always add up to the bundle sizes.
+
+use crate::css_ast::{CssNode, CssStylesheet};
+use crate::js_emitter::{Bundle, EmittedItem, ItemKind};
+use crate::js_minifier::JSMinifier;
+use crate::module_loader::ModuleLoader;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// File name of the report inside the output directory
+pub const REPORT_FILE: &str = "jounce-report.json";
+
+/// Module name for bundle code no source item accounts for
+/// (runtime imports, built-in helpers, startup code)
+pub const PRELUDE_MODULE: &str = "(prelude)";
+
+/// Component name for CSS no component owns (utility classes, themes, global styles)
+pub const GLOBAL_CSS: &str = "(global)";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BuildReport {
+    /// Source file the build started from
+    pub entry: String,
+    /// Size of each emitted artifact
+    pub bundles: Vec<BundleSize>,
+    /// Emitted JS per source module, largest first
+    pub modules: Vec<ModuleSize>,
+    /// Stdlib namespaces in the client bundle and the items that use them
+    pub stdlib: Vec<StdlibUsage>,
+    /// Rendered CSS per component, largest first
+    pub css: Vec<ComponentCss>,
+    /// `use` edges between source modules
+    pub dependencies: Vec<DependencyEdge>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleSize {
+    pub file: String,
+    pub bytes: usize,
+    pub minified_bytes: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModuleSize {
+    pub module: String,
+    pub bytes: usize,
+    pub minified_bytes: usize,
+    pub items: Vec<ItemSize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ItemSize {
+    pub name: String,
+    pub kind: String,
+    pub bundle: String,
+    pub bytes: usize,
+    pub minified_bytes: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StdlibUsage {
+    pub module: String,
+    pub bytes: usize,
+    pub minified_bytes: usize,
+    /// Items whose code refers to the namespace; empty means it is dead weight
+    pub referenced_by: Vec<ItemRef>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ItemRef {
+    pub module: String,
+    pub item: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComponentCss {
+    pub component: String,
+    pub bytes: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct DependencyEdge {
+    pub from: String,
+    pub to: String,
+}
+
+impl BuildReport {
+    pub fn new(entry: impl Into<String>) -> Self {
+        BuildReport {
+            entry: entry.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Account for a JS bundle from the items the emitter recorded while
+    /// generating it. `origins` maps item names to the module defining them
+    /// (see `ModuleLoader::item_origins`); other items belong to the entry.
+    pub fn add_bundle(&mut self, bundle: Bundle, js: &str, items: &[EmittedItem], origins: &HashMap<String, PathBuf>) {
+        let minifier = JSMinifier::new();
+        let minified_total = minifier.minify(js).len();
+        let (mut accounted, mut minified_accounted) = (0, 0);
+
+        for item in items.iter().filter(|item| item.bundle == bundle) {
+            let bytes = item.code.len();
+            let minified_bytes = minifier.minify(&item.code).len();
+            accounted += bytes;
+            minified_accounted += minified_bytes;
+
+            if item.kind == ItemKind::Stdlib {
+                let usage = self.stdlib_usage(&item.name);
+                usage.bytes += bytes;
+                usage.minified_bytes += minified_bytes;
+                continue;
+            }
+
+            let module = self.module_of(&item.name, origins);
+            for namespace in &item.stdlib_refs {
+                let reference = ItemRef { module: module.clone(), item: item.name.clone() };
+                let usage = self.stdlib_usage(namespace);
+                if !usage.referenced_by.contains(&reference) {
+                    usage.referenced_by.push(reference);
+                }
+            }
+
+            let entry = self.module_size(&module);
+            entry.bytes += bytes;
+            entry.minified_bytes += minified_bytes;
+            entry.items.push(ItemSize {
+                name: item.name.clone(),
+                kind: kind_name(item.kind).to_string(),
+                bundle: bundle.file_name().to_string(),
+                bytes,
+                minified_bytes,
+            });
+        }
+
+        // Minifying items one at a time is not exactly additive; the prelude
+        // absorbs the difference so totals stay exact
+        let prelude = self.module_size(PRELUDE_MODULE);
+        prelude.bytes += js.len().saturating_sub(accounted);
+        prelude.minified_bytes += minified_total.saturating_sub(minified_accounted);
+
+        self.bundles.push(BundleSize {
+            file: bundle.file_name().to_string(),
+            bytes: js.len(),
+            minified_bytes: minified_total,
+        });
+        self.sort();
+    }
+
+    /// Account for the stylesheet: each top-level rule belongs to the component
+    /// whose scoped class it mentions, everything else is global
+    pub fn add_css(&mut self, css: &str, stylesheet: &CssStylesheet, class_maps: &BTreeMap<String, BTreeMap<String, String>>) {
+        let mut per_component: BTreeMap<String, usize> = BTreeMap::new();
+        let mut owned = 0;
+        for node in &stylesheet.nodes {
+            let mut text = String::new();
+            collect_selectors(node, &mut text);
+            let owner = class_maps.iter().find(|(_, classes)| {
+                classes.values().any(|scoped| mentions_class(&text, scoped))
+            });
+            if let Some((component, _)) = owner {
+                let bytes = CssStylesheet { nodes: vec![node.clone()] }.to_css().len();
+                *per_component.entry(component.clone()).or_default() += bytes;
+                owned += bytes;
+            }
+        }
+
+        self.css = per_component.into_iter()
+            .map(|(component, bytes)| ComponentCss { component, bytes })
+            .collect();
+        let global = css.len().saturating_sub(owned);
+        if global > 0 {
+            self.css.push(ComponentCss { component: GLOBAL_CSS.to_string(), bytes: global });
+        }
+        self.css.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.component.cmp(&b.component)));
+
+        self.bundles.push(BundleSize {
+            file: "styles.css".to_string(),
+            bytes: css.len(),
+            minified_bytes: css.len(),
+        });
+    }
+
+    /// Record the module graph: the entry's imports, then every loaded module's
+    pub fn add_dependencies(&mut self, entry_imports: &[PathBuf], loader: &ModuleLoader) {
+        let entry = PathBuf::from(&self.entry);
+        let mut edges: Vec<DependencyEdge> = entry_imports.iter()
+            .map(|to| edge(&entry, to))
+            .collect();
+        for module in loader.modules() {
+            edges.extend(module.imports.iter().map(|to| edge(&module.file_path, to)));
+        }
+        edges.sort();
+        edges.dedup();
+        self.dependencies = edges;
+    }
+
+    /// Size of an emitted artifact, if it was accounted for
+    pub fn bundle(&self, file: &str) -> Option<&BundleSize> {
+        self.bundles.iter().find(|bundle| bundle.file == file)
+    }
+
+    /// Emitted size of a source module, if anything was attributed to it
+    pub fn module(&self, module: &str) -> Option<&ModuleSize> {
+        self.modules.iter().find(|entry| entry.module == module)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("build report is always serializable")
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// The report as aligned plain-text tables
+    pub fn to_table(&self) -> String {
+        let mut out = String::new();
+        out.push_str(&format!("{:<40} {:>10} {:>10}\n", "Bundle", "Bytes", "Minified"));
+        for bundle in &self.bundles {
+            out.push_str(&format!("{:<40} {:>10} {:>10}\n", bundle.file, bundle.bytes, bundle.minified_bytes));
+        }
+
+        out.push_str(&format!("\n{:<40} {:>10} {:>10}\n", "Module", "Bytes", "Minified"));
+        for module in &self.modules {
+            out.push_str(&format!("{:<40} {:>10} {:>10}\n", module.module, module.bytes, module.minified_bytes));
+            for item in &module.items {
+                let label = format!("  {} {} ({})", item.kind, item.name, item.bundle);
+                out.push_str(&format!("{:<40} {:>10} {:>10}\n", label, item.bytes, item.minified_bytes));
+            }
+        }
+
+        if !self.stdlib.is_empty() {
+            out.push_str(&format!("\n{:<40} {:>10} {:>10}  {}\n", "Stdlib", "Bytes", "Minified", "Used by"));
+            for usage in &self.stdlib {
+                let used_by = if usage.referenced_by.is_empty() {
+                    "(unused)".to_string()
+                } else {
+                    usage.referenced_by.iter()
+                        .map(|r| format!("{}::{}", r.module, r.item))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                };
+                out.push_str(&format!("{:<40} {:>10} {:>10}  {}\n", usage.module, usage.bytes, usage.minified_bytes, used_by));
+            }
+        }
+
+        if !self.css.is_empty() {
+            out.push_str(&format!("\n{:<40} {:>10}\n", "CSS", "Bytes"));
+            for css in &self.css {
+                out.push_str(&format!("{:<40} {:>10}\n", css.component, css.bytes));
+            }
+        }
+
+        if !self.dependencies.is_empty() {
+            out.push_str("\nDependencies\n");
+            for dep in &self.dependencies {
+                out.push_str(&format!("  {} -> {}\n", dep.from, dep.to));
+            }
+        }
+        out
+    }
+
+    fn module_of(&self, item: &str, origins: &HashMap<String, PathBuf>) -> String {
+        origins.get(item)
+            .map(|origin| origin.display().to_string())
+            .unwrap_or_else(|| self.entry.clone())
+    }
+
+    fn module_size(&mut self, module: &str) -> &mut ModuleSize {
+        let index = match self.modules.iter().position(|entry| entry.module == module) {
+            Some(index) => index,
+            None => {
+                self.modules.push(ModuleSize { module: module.to_string(), bytes: 0, minified_bytes: 0, items: Vec::new() });
+                self.modules.len() - 1
+            }
+        };
+        &mut self.modules[index]
+    }
+
+    fn stdlib_usage(&mut self, namespace: &str) -> &mut StdlibUsage {
+        let index = match self.stdlib.iter().position(|usage| usage.module == namespace) {
+            Some(index) => index,
+            None => {
+                self.stdlib.push(StdlibUsage { module: namespace.to_string(), bytes: 0, minified_bytes: 0, referenced_by: Vec::new() });
+                self.stdlib.len() - 1
+            }
+        };
+        &mut self.stdlib[index]
+    }
+
+    /// Largest first, so the table leads with what makes the bundle big
+    fn sort(&mut self) {
+        self.modules.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.module.cmp(&b.module)));
+        for module in &mut self.modules {
+            module.items.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.name.cmp(&b.name)));
+        }
+        self.stdlib.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.module.cmp(&b.module)));
+        for usage in &mut self.stdlib {
+            usage.referenced_by.sort();
+        }
+    }
+}
+
+fn kind_name(kind: ItemKind) -> &'static str {
+    match kind {
+        ItemKind::Constant => "const",
+        ItemKind::Struct => "struct",
+        ItemKind::Enum => "enum",
+        ItemKind::Impl => "impl",
+        ItemKind::Function => "fn",
+        ItemKind::Component => "component",
+        ItemKind::Stdlib => "stdlib",
+    }
+}
+
+fn edge(from: &Path, to: &Path) -> DependencyEdge {
+    DependencyEdge { from: from.display().to_string(), to: to.display().to_string() }
+}
+
+/// Selectors and at-rule preludes of a node, space separated
+fn collect_selectors(node: &CssNode, out: &mut String) {
+    match node {
+        CssNode::Rule(rule) => {
+            out.push_str(&rule.selector);
+            out.push(' ');
+        }
+        CssNode::AtRule(at_rule) => {
+            out.push_str(&at_rule.prelude);
+            out.push(' ');
+            for child in &at_rule.children {
+                collect_selectors(child, out);
+            }
+        }
+    }
+}
+
+/// Whether `text` mentions a scoped class as a whole name, not as part of a longer one
+fn mentions_class(text: &str, class: &str) -> bool {
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '-';
+    text.match_indices(class).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + class.len()..].chars().next();
+        !before.is_some_and(is_name_char) && !after.is_some_and(is_name_char)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::js_emitter::JSEmitter;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+    use std::fs;
+
+    #[test]
+    fn test_report_attributes_stdlib_and_matches_bundle_sizes() {
+        let dir = std::env::temp_dir().join(format!("jounce_report_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("codec.jnc"), "fn encode(value: JsonValue) -> String {\n    return json::stringify(value);\n}\n").unwrap();
+        fs::write(dir.join("labels.jnc"), "fn shout(text: String) -> String {\n    return text;\n}\n").unwrap();
+        let main_path = dir.join("main.jnc");
+        let source = "use ./codec::{encode};\nuse ./labels::{shout};\ncomponent App() {\n    return <div>{shout(\"hi\")}</div>;\n}\n";
+
+        let mut lexer = Lexer::new(source.to_string());
+        let mut program = Parser::new(&mut lexer).parse_program().unwrap();
+        let mut loader = ModuleLoader::new(&dir);
+        loader.set_current_file(&main_path);
+        let imported = loader.merge_imports(&mut program).unwrap();
+
+        let emitter = JSEmitter::new(&program);
+        let server_js = emitter.generate_server_js();
+        let client_js = emitter.generate_client_js();
+        let items = emitter.emitted_items();
+        let origins = loader.item_origins();
+
+        let entry = main_path.display().to_string();
+        let mut report = BuildReport::new(entry.clone());
+        report.add_bundle(Bundle::Server, &server_js, &items, &origins);
+        report.add_bundle(Bundle::Client, &client_js, &items, &origins);
+        report.add_dependencies(&imported, &loader);
+        fs::remove_dir_all(&dir).ok();
+
+        let codec = dir.join("codec.jnc").display().to_string();
+        let labels = dir.join("labels.jnc").display().to_string();
+        let json = report.stdlib.iter().find(|usage| usage.module == "json").unwrap();
+        assert_eq!(json.referenced_by, vec![ItemRef { module: codec.clone(), item: "encode".to_string() }]);
+        assert!(json.bytes > 0);
+        assert!(report.stdlib.iter().filter(|usage| usage.module != "json").all(|usage| usage.referenced_by.is_empty()));
+
+        // Modules plus stdlib namespaces add up to the artifacts, before and after minification
+        let minifier = JSMinifier::new();
+        let expected = server_js.len() + client_js.len();
+        let expected_minified = minifier.minify(&server_js).len() + minifier.minify(&client_js).len();
+        let bytes: usize = report.modules.iter().map(|m| m.bytes).chain(report.stdlib.iter().map(|s| s.bytes)).sum();
+        let minified: usize = report.modules.iter().map(|m| m.minified_bytes).chain(report.stdlib.iter().map(|s| s.minified_bytes)).sum();
+        assert_eq!(bytes, expected);
+        assert_eq!(minified, expected_minified);
+        assert_eq!(report.bundle("client.js").unwrap().bytes, client_js.len());
+        assert_eq!(report.bundle("server.js").unwrap().minified_bytes, minifier.minify(&server_js).len());
+
+        assert!(report.module(&codec).unwrap().items.iter().any(|item| item.name == "encode"));
+        assert!(report.module(&entry).unwrap().items.iter().any(|item| item.name == "App" && item.kind == "component"));
+        assert_eq!(report.dependencies, vec![
+            DependencyEdge { from: entry.clone(), to: codec },
+            DependencyEdge { from: entry, to: labels },
+        ]);
+
+        let table = report.to_table();
+        assert!(table.contains("json"), "{}", table);
+        assert!(table.contains("codec.jnc::encode"), "{}", table);
+        assert_eq!(BuildReport::from_json(&report.to_json()).unwrap(), report);
+    }
+
+    #[test]
+    fn test_css_size_per_component() {
+        let mut stylesheet = CssStylesheet::new();
+        let mut button = crate::css_ast::CssStyleRule::new(".Button_button_a3f5c9:hover", Default::default());
+        button.declare("color", "blue");
+        stylesheet.push(CssNode::Rule(button));
+        let mut other = crate::css_ast::CssStyleRule::new(".Button_button_a3f5c9x", Default::default());
+        other.declare("color", "red");
+        stylesheet.push(CssNode::Rule(other));
+
+        let class_maps = BTreeMap::from([(
+            "Button".to_string(),
+            BTreeMap::from([("button".to_string(), "Button_button_a3f5c9".to_string())]),
+        )]);
+        let css = stylesheet.to_css();
+        let mut report = BuildReport::new("app.jnc");
+        report.add_css(&css, &stylesheet, &class_maps);
+
+        let button_bytes = ".Button_button_a3f5c9:hover {\n  color: blue;\n}\n\n".len();
+        assert_eq!(report.css, vec![
+            ComponentCss { component: "Button".to_string(), bytes: button_bytes },
+            ComponentCss { component: GLOBAL_CSS.to_string(), bytes: css.len() - button_bytes },
+        ]);
+        assert_eq!(report.bundle("styles.css").unwrap().bytes, css.len());
+    }
+}
@@ -0,0 +1,293 @@
+// On-disk build cache (.jounce-cache/)
+//
+// Survives across processes, unlike `CompilationCache`. Each module's parsed
+// AST is stored under a hash of its source, the compiler version and the build
+// options, so a cold build of an unchanged project skips lexing and parsing.
+// When every module of a build hits, the emitted output of the previous build
+// is reused too, skipping checking and code generation; the warnings that build
+// reported are stored with it and shown again. Unreadable entries and
+// entries from another compiler version are treated as misses.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use crate::ast::Program;
+use crate::build_manifest::BuildManifest;
+use crate::cache::{compute_hash, CacheStats};
+use crate::diagnostics::Diagnostic;
+use crate::errors::CompileError;
+use crate::lexer::Lexer;
+use crate::parser::Parser;
+
+/// Default cache directory, relative to where the compiler runs
+pub const BUILD_CACHE_DIR: &str = ".jounce-cache";
+
+const COMPILER_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// What a cache file holds; `version` and `key` are checked on load
+#[derive(Serialize, Deserialize)]
+struct Entry<T> {
+    version: String,
+    key: u64,
+    value: T,
+}
+
+/// Everything a build writes besides the runtime files
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BuildOutput {
+    pub server_js: String,
+    pub client_js: String,
+    pub wasm: Vec<u8>,
+    pub css: String,
+    pub manifest: BuildManifest,
+    /// Warnings the build reported (css! lints), shown again when the output is reused
+    pub warnings: Vec<Diagnostic>,
+}
+
+pub struct BuildCache {
+    dir: PathBuf,
+    /// Build options that change the output, part of every key
+    options: String,
+    /// Key of every module parsed through this cache, by file
+    modules: Mutex<Vec<(PathBuf, u64)>>,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+impl BuildCache {
+    pub fn new(dir: impl Into<PathBuf>, options: impl Into<String>) -> Self {
+        Self {
+            dir: dir.into(),
+            options: options.into(),
+            modules: Mutex::new(Vec::new()),
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+        }
+    }
+
+    /// Cache key for a module's source
+    pub fn module_key(&self, source: &str) -> u64 {
+        compute_hash(format!("{}\0{}\0{}", COMPILER_VERSION, self.options, source).as_bytes())
+    }
+
+    /// Parse a module, or load its AST from a previous build
+    pub fn parse(&self, path: &Path, source: &str) -> Result<Program, CompileError> {
+        let key = self.module_key(source);
+        if let Ok(mut modules) = self.modules.lock() {
+            modules.push((path.to_path_buf(), key));
+        }
+
+        if let Some(program) = self.read::<Program>("modules", key) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(program);
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let mut lexer = Lexer::new(source.to_string());
+        let program = Parser::new(&mut lexer).parse_program()?;
+        self.write("modules", key, &program);
+        Ok(program)
+    }
+
+    /// Key of the whole build: every module parsed so far, plus `inputs`
+    /// that affect the output without being a module (entry, configuration)
+    pub fn build_key(&self, inputs: &str) -> u64 {
+        let mut modules = self.modules.lock().map(|modules| modules.clone()).unwrap_or_default();
+        modules.sort();
+        modules.dedup();
+        let listed: Vec<String> = modules
+            .iter()
+            .map(|(path, key)| format!("{}={:016x}", path.display(), key))
+            .collect();
+        compute_hash(format!("{}\0{}\0{}\0{}", COMPILER_VERSION, self.options, listed.join("\n"), inputs).as_bytes())
+    }
+
+    /// Output of an earlier build with the same key
+    pub fn load_output(&self, build_key: u64) -> Option<BuildOutput> {
+        self.read("builds", build_key)
+    }
+
+    pub fn store_output(&self, build_key: u64, output: &BuildOutput) {
+        self.write("builds", build_key, output);
+    }
+
+    /// Module hits and misses so far
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            invalidations: 0,
+        }
+    }
+
+    fn entry_path(&self, kind: &str, key: u64) -> PathBuf {
+        self.dir.join(kind).join(format!("{:016x}.json", key))
+    }
+
+    fn read<T: DeserializeOwned>(&self, kind: &str, key: u64) -> Option<T> {
+        let json = fs::read_to_string(self.entry_path(kind, key)).ok()?;
+        let entry: Entry<T> = serde_json::from_str(&json).ok()?;
+        (entry.version == COMPILER_VERSION && entry.key == key).then_some(entry.value)
+    }
+
+    /// Best effort: a build never fails because the cache can't be written.
+    /// Entries are written to a temporary file first so readers never see half of one.
+    fn write<T: Serialize>(&self, kind: &str, key: u64, value: &T) {
+        let path = self.entry_path(kind, key);
+        let entry = Entry { version: COMPILER_VERSION.to_string(), key, value };
+        let Ok(json) = serde_json::to_string(&entry) else {
+            return;
+        };
+        let Some(dir) = path.parent() else {
+            return;
+        };
+        let temp = path.with_extension(format!("tmp{}", std::process::id()));
+        if fs::create_dir_all(dir).is_ok() && fs::write(&temp, json).is_ok() && fs::rename(&temp, &path).is_err() {
+            let _ = fs::remove_file(&temp);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::js_emitter::JSEmitter;
+    use crate::module_loader::ModuleLoader;
+    use std::sync::Arc;
+
+    const MAIN: &str = "use ./math::{double};\nuse ./labels::{label};\n\nfn main() {\n    console::log(label(double(21)));\n}\n";
+    const MATH: &str = "fn double(n: i32) -> i32 {\n    return n * 2;\n}\n";
+    const LABELS: &str = "fn label(n: i32) -> String {\n    return \"value\";\n}\n";
+
+    /// A fresh project (main.jnc importing two modules) and cache directory
+    fn fixture(name: &str) -> (PathBuf, PathBuf) {
+        let root = std::env::temp_dir().join(format!("jounce-build-cache-{}", name));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        for (file, source) in [("main.jnc", MAIN), ("math.jnc", MATH), ("labels.jnc", LABELS)] {
+            fs::write(root.join(file), source).unwrap();
+        }
+        let cache_dir = root.join(BUILD_CACHE_DIR);
+        (root, cache_dir)
+    }
+
+    /// Parse the project through a new cache (as a fresh process would) and
+    /// return the cache and the merged program's client JS
+    fn build(root: &Path, cache_dir: &Path) -> (Arc<BuildCache>, String) {
+        let cache = Arc::new(BuildCache::new(cache_dir, "minify=false"));
+        let main = root.join("main.jnc");
+        let mut program = cache.parse(&main, &fs::read_to_string(&main).unwrap()).unwrap();
+        let mut loader = ModuleLoader::new(root).with_build_cache(cache.clone());
+        loader.set_current_file(&main);
+        loader.merge_imports(&mut program).unwrap();
+        let client_js = JSEmitter::new(&program).generate_client_js();
+        (cache, client_js)
+    }
+
+    fn output(js: &str) -> BuildOutput {
+        BuildOutput {
+            server_js: js.to_string(),
+            client_js: String::new(),
+            wasm: vec![0, 97, 115, 109],
+            css: String::new(),
+            manifest: BuildManifest::default(),
+            warnings: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_unchanged_build_reuses_cached_modules() {
+        let (root, cache_dir) = fixture("unchanged");
+
+        let (first, client_js) = build(&root, &cache_dir);
+        assert_eq!((first.stats().hits, first.stats().misses), (0, 3));
+        assert!(client_js.contains("function double"));
+        let key = first.build_key("entry");
+        assert_eq!(first.load_output(key), None);
+        first.store_output(key, &output("server"));
+
+        // Cached ASTs emit exactly what freshly parsed ones do
+        let (second, cached_js) = build(&root, &cache_dir);
+        assert_eq!((second.stats().hits, second.stats().misses), (3, 0));
+        assert_eq!(cached_js, client_js);
+        assert_eq!(second.build_key("entry"), key);
+        assert_eq!(second.load_output(key), Some(output("server")));
+
+        // Build inputs outside the modules are part of the key
+        assert_ne!(second.build_key("other entry"), key);
+    }
+
+    #[test]
+    fn test_reused_build_replays_warnings() {
+        use crate::css_lint;
+        use crate::{BuildTarget, Compiler};
+
+        let (root, cache_dir) = fixture("warnings");
+        let source = "component Card() {\n    let styles = css! {\n        .card { colr: red; }\n    };\n    return <div class={styles.card}>\"card\"</div>;\n}\n";
+        let first = BuildCache::new(&cache_dir, "strict_css=false");
+        first.parse(&root.join("card.jnc"), source).unwrap();
+        let module = Compiler::without_optimization().compile_module(source, BuildTarget::Client).unwrap();
+        let warnings = module.css_warnings();
+        assert_eq!(warnings.len(), 1, "got {:?}", warnings);
+        let key = first.build_key("");
+        first.store_output(key, &BuildOutput { warnings: warnings.clone(), ..output("server") });
+
+        // The second build reuses the output, and reports what the first one did
+        let second = BuildCache::new(&cache_dir, "strict_css=false");
+        second.parse(&root.join("card.jnc"), source).unwrap();
+        let cached = second.load_output(second.build_key("")).expect("unchanged build should hit");
+        assert_eq!(cached.warnings, warnings);
+        let mut replayed = cached.warnings[0].clone();
+        assert!(replayed.message.contains("colr"), "got {}", replayed.message);
+        assert!(css_lint::deny_unknown_property(&mut replayed));
+    }
+
+    #[test]
+    fn test_edited_module_misses() {
+        let (root, cache_dir) = fixture("edited");
+        let (first, _) = build(&root, &cache_dir);
+        let key = first.build_key("");
+        first.store_output(key, &output("server"));
+
+        fs::write(root.join("math.jnc"), MATH.replace("n * 2", "n + n")).unwrap();
+        let (second, _) = build(&root, &cache_dir);
+        assert_eq!((second.stats().hits, second.stats().misses), (2, 1));
+        assert_ne!(second.build_key(""), key);
+
+        // Options are part of every key
+        let other = BuildCache::new(&cache_dir, "minify=true");
+        other.parse(Path::new("math.jnc"), MATH).unwrap();
+        assert_eq!(other.stats().misses, 1);
+    }
+
+    #[test]
+    fn test_poisoned_cache_falls_back_to_clean_build() {
+        let (root, cache_dir) = fixture("poisoned");
+        let (first, client_js) = build(&root, &cache_dir);
+        let key = first.build_key("");
+        first.store_output(key, &output("server"));
+
+        // Garbage in every entry, and an entry written by another compiler version
+        for kind in ["modules", "builds"] {
+            for file in fs::read_dir(cache_dir.join(kind)).unwrap() {
+                fs::write(file.unwrap().path(), "\u{0}not json{").unwrap();
+            }
+        }
+        let math_key = first.module_key(MATH);
+        let stale = Entry { version: "0.0.0".to_string(), key: math_key, value: Program { statements: vec![] } };
+        fs::write(first.entry_path("modules", math_key), serde_json::to_string(&stale).unwrap()).unwrap();
+
+        let (second, rebuilt_js) = build(&root, &cache_dir);
+        assert_eq!((second.stats().hits, second.stats().misses), (0, 3));
+        assert_eq!(rebuilt_js, client_js);
+        assert_eq!(second.load_output(key), None);
+
+        // The clean build rewrote the entries
+        let (third, _) = build(&root, &cache_dir);
+        assert_eq!(third.stats().hits, 3);
+    }
+}
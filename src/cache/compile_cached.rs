@@ -17,7 +17,7 @@ use crate::type_checker::TypeChecker;
 use crate::utility_config;
 use crate::utility_generator;
 use crate::wasm_optimizer::WasmOptimizer;
-use crate::BuildTarget;
+use crate::{BuildTarget, CompiledModule};
 
 /// Compile with caching support
 pub fn compile_source_cached(
@@ -27,6 +27,19 @@ pub fn compile_source_cached(
     cache: &Arc<CompilationCache>,
     optimize: bool,
 ) -> Result<(Vec<u8>, String), CompileError> {
+    let module = compile_module_cached(source, file_path, target, cache, optimize)?;
+    let css_output = module.css();
+    Ok((module.wasm, css_output))
+}
+
+/// Compile with caching support, keeping the CSS structured (see `CompiledModule`)
+pub fn compile_module_cached(
+    source: &str,
+    file_path: &Path,
+    target: BuildTarget,
+    cache: &Arc<CompilationCache>,
+    optimize: bool,
+) -> Result<CompiledModule, CompileError> {
     println!("   - Starting cached compilation for: {:?}", file_path);
 
     // Try to get cached AST or parse new one
@@ -80,22 +93,12 @@ pub fn compile_source_cached(
     // Code generation
     let mut code_generator = CodeGenerator::new(target);
     let mut wasm_bytes = code_generator.generate_program(&program_ast)?;
+    code_generator.record_css_sources(&module_loader.item_origins(), file_path);
 
     // Utility CSS generation
     let utility_config = utility_config::UtilityConfig::load();
     let mut utility_gen = utility_generator::UtilityGenerator::new(utility_config);
     utility_gen.scan_for_utilities(&program_ast);
-    let utility_css = utility_gen.generate_css();
-
-    // Extract CSS output
-    let component_css = code_generator.get_css_output().to_string();
-
-    // Combine CSS
-    let css_output = if utility_css.is_empty() {
-        component_css
-    } else {
-        format!("{}\n{}", utility_css, component_css)
-    };
 
     // Optimization
     if optimize {
@@ -128,7 +131,7 @@ pub fn compile_source_cached(
         );
     }
 
-    Ok((wasm_bytes, css_output))
+    Ok(CompiledModule::from_codegen(wasm_bytes, &mut utility_gen, &code_generator))
 }
 
 /// Compile multiple files in parallel using cached compilation
@@ -2,12 +2,14 @@
 // Phase 9 Sprint 1 - Performance Optimization
 
 pub mod ast_cache;
+pub mod build_cache;
 pub mod compile_cached;
 pub mod dependency_graph;
 pub mod disk_cache;
 
 // Re-export cached compilation functions for convenience
-pub use compile_cached::{compile_source_cached, compile_project_parallel};
+pub use compile_cached::{compile_source_cached, compile_module_cached, compile_project_parallel};
+pub use build_cache::{BuildCache, BuildOutput, BUILD_CACHE_DIR};
 
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicUsize, Ordering};
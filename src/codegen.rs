@@ -5,7 +5,11 @@ use crate::token::TokenKind;
 use crate::vdom::VNode;
 use crate::semantic_analyzer::ResolvedType;
 use crate::css_generator; // CSS generation (Phase 7.5)
-use std::collections::HashMap;
+use crate::css_lint;
+use crate::diagnostics::Diagnostic;
+use crate::css_ast::{walk_node, CssNode, CssSpan, CssStyleRule, CssStylesheet, CssVisitor};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 use wasm_encoder::{
     CodeSection, ExportKind, ExportSection, Function, FunctionSection, ImportSection, Instruction,
     Module, TypeSection, ValType, EntityType, MemoryType, MemorySection,
@@ -134,8 +138,20 @@ pub struct CodeGenerator {
     lambda_encounter_counter: usize,
     // Current lambda context (Some(lambda_index) when generating a lambda body, None otherwise)
     current_lambda_context: Option<usize>,
-    // CSS output (Phase 7.5)
-    css_output: String,
+    // CSS output (Phase 7.5), kept structured for build-tool plugins
+    css_ast: CssStylesheet,
+    // Scoped class names per component: component -> (class -> scoped class)
+    class_maps: BTreeMap<String, BTreeMap<String, String>>,
+    // Stylesheet nodes each item generated: owner name -> node indices
+    css_owners: Vec<(String, std::ops::Range<usize>)>,
+    // Warnings from inline style attributes and css! blocks, and the @allow
+    // lints of the item being scanned
+    css_warnings: Vec<Diagnostic>,
+    current_allows: Vec<String>,
+    // Structured blocks open in the function being generated, and for each
+    // enclosing loop the depths `break` and `continue` branch to
+    block_depth: u32,
+    loop_labels: Vec<(u32, u32)>,
 }
 
 impl CodeGenerator {
@@ -153,45 +169,148 @@ impl CodeGenerator {
             target,
             lambda_encounter_counter: 0,
             current_lambda_context: None,
-            css_output: String::new(),
+            css_ast: CssStylesheet::new(),
+            class_maps: BTreeMap::new(),
+            css_owners: Vec::new(),
+            css_warnings: Vec::new(),
+            current_allows: Vec::new(),
+            block_depth: 0,
+            loop_labels: Vec::new(),
         }
     }
 
     /// Get the generated CSS output (Phase 7.5)
-    pub fn get_css_output(&self) -> &str {
-        &self.css_output
+    pub fn get_css_output(&self) -> String {
+        self.css_ast.to_css()
+    }
+
+    /// Get the generated CSS as a structured stylesheet
+    pub fn get_css_ast(&self) -> &CssStylesheet {
+        &self.css_ast
+    }
+
+    /// Get the scoped class names generated for each component
+    pub fn get_class_maps(&self) -> &BTreeMap<String, BTreeMap<String, String>> {
+        &self.class_maps
+    }
+
+    /// Scoped class names per component, as a build would generate them,
+    /// without generating any code
+    pub fn scoped_class_maps(program: &Program) -> BTreeMap<String, BTreeMap<String, String>> {
+        let mut generator = Self::new(BuildTarget::Client);
+        // CSS errors are reported by the WASM build; classes then stay unscoped
+        let _ = generator.extract_and_generate_css(program);
+        generator.class_maps
+    }
+
+    /// Only the CSS pass: the stylesheet and class maps a build would produce,
+    /// without generating any code. Watch mode rebuilds CSS this way to tell
+    /// style changes apart from code changes.
+    pub fn css_only(program: &Program) -> Result<Self, CompileError> {
+        let mut generator = Self::new(BuildTarget::Client);
+        generator.extract_and_generate_css(program)?;
+        Ok(generator)
+    }
+
+    /// The rendered CSS of each item that has any: components and functions by
+    /// name, style and theme blocks as `style Name` / `theme Name`
+    pub fn css_by_owner(&self) -> BTreeMap<String, String> {
+        let mut parts: BTreeMap<String, String> = BTreeMap::new();
+        for (owner, range) in &self.css_owners {
+            let stylesheet = CssStylesheet { nodes: self.css_ast.nodes[range.clone()].to_vec() };
+            parts.entry(owner.clone()).or_default().push_str(&stylesheet.to_css());
+        }
+        parts
+    }
+
+    /// Note on each CSS rule the file and line it was declared at. `origins`
+    /// gives the file of each item merged in from an imported module; other
+    /// items are in `file`.
+    pub fn record_css_sources(&mut self, origins: &HashMap<String, PathBuf>, file: &Path) {
+        struct RecordSource<'a>(&'a Path);
+        impl CssVisitor for RecordSource<'_> {
+            fn visit_rule(&mut self, rule: &mut CssStyleRule) {
+                rule.sources = vec![format!("{}:{}", self.0.display(), rule.span.line)];
+            }
+        }
+
+        for (owner, range) in &self.css_owners {
+            // Style and theme blocks are owned as `style Name` and `theme Name`
+            let name = owner.rsplit(' ').next().unwrap_or(owner);
+            let file = origins.get(name).map_or(file, PathBuf::as_path);
+            for node in &mut self.css_ast.nodes[range.clone()] {
+                walk_node(&mut RecordSource(file), node);
+            }
+        }
+    }
+
+    /// Warnings from inline `style` attributes (unknown properties) and
+    /// css! blocks (skipped at-rules, undeclared classes)
+    pub fn get_css_warnings(&self) -> &[Diagnostic] {
+        &self.css_warnings
     }
 
     /// Extract CSS expressions from AST and generate scoped CSS (Phase 7.5)
     fn extract_and_generate_css(&mut self, program: &Program) -> Result<(), CompileError> {
         for stmt in &program.statements {
-            match stmt {
+            let first_node = self.css_ast.nodes.len();
+            let owner = match stmt {
                 Statement::Function(func_def) => {
                     // Search function body for CSS macros
+                    self.current_allows = func_def.allows.clone();
                     self.extract_css_from_statements(&func_def.body.statements, &func_def.name.value)?;
+                    self.allow_css_lints(first_node, &func_def.allows);
+                    func_def.name.value.clone()
                 }
                 Statement::Component(comp_def) => {
                     // Search component body for CSS macros
+                    self.current_allows = comp_def.allows.clone();
                     self.extract_css_from_statements(&comp_def.body.statements, &comp_def.name.value)?;
+                    self.allow_css_lints(first_node, &comp_def.allows);
+                    comp_def.name.value.clone()
                 }
                 Statement::Let(let_stmt) => {
                     // Check if let binding has CSS macro
                     self.extract_css_from_expression(&let_stmt.value, "App")?;
+                    "App".to_string()
                 }
                 Statement::Style(style_block) => {
                     // Phase 13: Generate CSS from style block
                     self.generate_style_block_css(style_block)?;
+                    format!("style {}", style_block.name.value)
                 }
                 Statement::Theme(theme_block) => {
                     // Phase 13: Generate CSS custom properties from theme block
                     self.generate_theme_block_css(theme_block)?;
+                    format!("theme {}", theme_block.name.value)
                 }
-                _ => {}
+                _ => String::new(),
+            };
+            self.current_allows.clear();
+            if self.css_ast.nodes.len() > first_node {
+                self.css_owners.push((owner, first_node..self.css_ast.nodes.len()));
             }
         }
         Ok(())
     }
 
+    /// Record an item's `@allow(...)` lints on the CSS rules it generated
+    fn allow_css_lints(&mut self, first_node: usize, allows: &[String]) {
+        struct AllowLints<'a>(&'a [String]);
+        impl CssVisitor for AllowLints<'_> {
+            fn visit_rule(&mut self, rule: &mut CssStyleRule) {
+                rule.allow.extend(self.0.iter().cloned());
+            }
+        }
+
+        if allows.is_empty() {
+            return;
+        }
+        for node in &mut self.css_ast.nodes[first_node..] {
+            walk_node(&mut AllowLints(allows), node);
+        }
+    }
+
     /// Recursively search statements for CSS macros
     fn extract_css_from_statements(&mut self, stmts: &[Statement], component_name: &str) -> Result<(), CompileError> {
         for stmt in stmts {
@@ -202,6 +321,9 @@ impl CodeGenerator {
                 Statement::Expression(expr) => {
                     self.extract_css_from_expression(expr, component_name)?;
                 }
+                Statement::Return(return_stmt) => {
+                    self.extract_css_from_expression(&return_stmt.value, component_name)?;
+                }
                 Statement::If(if_stmt) => {
                     self.extract_css_from_statements(&if_stmt.then_branch.statements, component_name)?;
                     if let Some(else_branch) = &if_stmt.else_branch {
@@ -212,6 +334,9 @@ impl CodeGenerator {
                 Statement::While(while_stmt) => {
                     self.extract_css_from_statements(&while_stmt.body.statements, component_name)?;
                 }
+                Statement::Loop(loop_stmt) => {
+                    self.extract_css_from_statements(&loop_stmt.body.statements, component_name)?;
+                }
                 Statement::For(for_stmt) => {
                     self.extract_css_from_statements(&for_stmt.body.statements, component_name)?;
                 }
@@ -229,9 +354,20 @@ impl CodeGenerator {
         match expr {
             Expression::CssMacro(css_expr) => {
                 // Found a CSS macro! Generate scoped CSS
+                self.css_warnings.extend(css_lint::check_unknown_at_rules(css_expr));
+                if !self.current_allows.iter().any(|lint| lint == css_lint::CSS_UNDECLARED_CLASS_LINT) {
+                    self.css_warnings.extend(css_lint::check_undeclared_classes(css_expr));
+                }
+                if !self.current_allows.iter().any(|lint| lint == css_lint::CSS_UNKNOWN_PROPERTY_LINT) {
+                    self.css_warnings.extend(css_lint::check_unknown_properties(css_expr));
+                }
                 let mut generator = css_generator::CssGenerator::new(component_name.to_string());
-                let css = generator.generate(css_expr);
-                self.css_output.push_str(&css);
+                generator.generate(css_expr);
+                self.class_maps
+                    .entry(component_name.to_string())
+                    .or_default()
+                    .extend(generator.get_class_map().iter().map(|(k, v)| (k.clone(), v.clone())));
+                self.css_ast.extend(generator.into_stylesheet());
             }
             Expression::IfExpression(if_expr) => {
                 self.extract_css_from_expression(&if_expr.condition, component_name)?;
@@ -248,11 +384,43 @@ impl CodeGenerator {
             Expression::Block(block) => {
                 self.extract_css_from_statements(&block.statements, component_name)?;
             }
+            Expression::JsxElement(jsx) => {
+                self.extract_css_from_jsx(jsx, component_name)?;
+            }
+            Expression::FunctionCall(call) => {
+                for arg in &call.arguments {
+                    self.extract_css_from_expression(arg, component_name)?;
+                }
+            }
+            Expression::Lambda(lambda) => {
+                self.extract_css_from_expression(&lambda.body, component_name)?;
+            }
             _ => {}
         }
         Ok(())
     }
 
+    /// Check inline styles and search attribute values and children for CSS macros
+    fn extract_css_from_jsx(&mut self, jsx: &JsxElement, component_name: &str) -> Result<(), CompileError> {
+        for attr in &jsx.opening_tag.attributes {
+            if let Expression::InlineStyle(style) = &attr.value {
+                if !self.current_allows.iter().any(|lint| lint == css_lint::CSS_UNKNOWN_PROPERTY_LINT) {
+                    self.css_warnings.extend(css_lint::check_inline_style(style));
+                }
+            } else {
+                self.extract_css_from_expression(&attr.value, component_name)?;
+            }
+        }
+        for child in &jsx.children {
+            match child {
+                JsxChild::Element(element) => self.extract_css_from_jsx(element, component_name)?,
+                JsxChild::Expression(expr) => self.extract_css_from_expression(expr, component_name)?,
+                JsxChild::Text(_) => {}
+            }
+        }
+        Ok(())
+    }
+
     /// The main entry point for generating a complete Wasm module from an AST.
     pub fn generate_program(&mut self, program: &Program) -> Result<Vec<u8>, CompileError> {
         let mut module = Module::new();
@@ -514,6 +682,9 @@ impl CodeGenerator {
             Statement::While(while_stmt) => {
                 self.count_required_locals(&while_stmt.body.statements)
             }
+            Statement::Loop(loop_stmt) => {
+                self.count_required_locals(&loop_stmt.body.statements)
+            }
             Statement::For(for_stmt) => {
                 let mut count = 0;
                 if let Some(init) = &for_stmt.init {
@@ -755,17 +926,39 @@ impl CodeGenerator {
             Statement::ForIn(for_in_stmt) => {
                 self.generate_for_in_statement(for_in_stmt, f)?;
             }
+            Statement::Loop(loop_stmt) => {
+                self.generate_loop_statement(loop_stmt, f)?;
+            }
+            Statement::Break | Statement::Continue => {
+                let (break_depth, continue_depth) = *self.loop_labels.last().ok_or_else(|| CompileError::Generic(format!(
+                    "`{}` can only be used inside a loop",
+                    if matches!(stmt, Statement::Break) { "break" } else { "continue" }
+                )))?;
+                let target = if matches!(stmt, Statement::Break) { break_depth } else { continue_depth };
+                f.instruction(&Instruction::Br(self.block_depth - target));
+            }
             _ => {}
         }
         Ok(())
     }
 
+    /// Opens a structured block, keeping count so branches find their labels
+    fn open_block(&mut self, instruction: Instruction, f: &mut Function) {
+        f.instruction(&instruction);
+        self.block_depth += 1;
+    }
+
+    fn close_block(&mut self, f: &mut Function) {
+        f.instruction(&Instruction::End);
+        self.block_depth -= 1;
+    }
+
     fn generate_if_statement(&mut self, stmt: &IfStatement, f: &mut Function) -> Result<(), CompileError> {
         // Generate condition
         self.generate_expression(&stmt.condition, f)?;
 
         // Start if block
-        f.instruction(&Instruction::If(wasm_encoder::BlockType::Empty));
+        self.open_block(Instruction::If(wasm_encoder::BlockType::Empty), f);
 
         // Generate then branch
         for s in &stmt.then_branch.statements {
@@ -781,13 +974,15 @@ impl CodeGenerator {
         }
 
         // End if block
-        f.instruction(&Instruction::End);
+        self.close_block(f);
         Ok(())
     }
 
     fn generate_while_statement(&mut self, stmt: &WhileStatement, f: &mut Function) -> Result<(), CompileError> {
-        // Start loop block
-        f.instruction(&Instruction::Loop(wasm_encoder::BlockType::Empty));
+        // Outer block to break out of, then the loop itself
+        self.open_block(Instruction::Block(wasm_encoder::BlockType::Empty), f);
+        self.open_block(Instruction::Loop(wasm_encoder::BlockType::Empty), f);
+        self.loop_labels.push((self.block_depth - 1, self.block_depth));
 
         // Generate condition
         self.generate_expression(&stmt.condition, f)?;
@@ -806,8 +1001,28 @@ impl CodeGenerator {
         // Branch back to start of loop
         f.instruction(&Instruction::Br(0));
 
-        // End loop block
-        f.instruction(&Instruction::End);
+        // End loop and outer blocks
+        self.loop_labels.pop();
+        self.close_block(f);
+        self.close_block(f);
+
+        Ok(())
+    }
+
+    fn generate_loop_statement(&mut self, stmt: &LoopStatement, f: &mut Function) -> Result<(), CompileError> {
+        // Outer block for `break`, then the loop, which only exits through it
+        self.open_block(Instruction::Block(wasm_encoder::BlockType::Empty), f);
+        self.open_block(Instruction::Loop(wasm_encoder::BlockType::Empty), f);
+        self.loop_labels.push((self.block_depth - 1, self.block_depth));
+
+        for s in &stmt.body.statements {
+            self.generate_statement(s, f)?;
+        }
+        f.instruction(&Instruction::Br(0));
+
+        self.loop_labels.pop();
+        self.close_block(f);
+        self.close_block(f);
 
         Ok(())
     }
@@ -818,8 +1033,9 @@ impl CodeGenerator {
             self.generate_statement(init, f)?;
         }
 
-        // Start loop block
-        f.instruction(&Instruction::Loop(wasm_encoder::BlockType::Empty));
+        // Outer block to break out of, then the loop itself
+        self.open_block(Instruction::Block(wasm_encoder::BlockType::Empty), f);
+        self.open_block(Instruction::Loop(wasm_encoder::BlockType::Empty), f);
 
         // Generate condition
         self.generate_expression(&stmt.condition, f)?;
@@ -830,10 +1046,14 @@ impl CodeGenerator {
         // Break out of loop if condition is false
         f.instruction(&Instruction::BrIf(1));
 
-        // Generate loop body
+        // Generate loop body in its own block, so `continue` still runs the update
+        self.open_block(Instruction::Block(wasm_encoder::BlockType::Empty), f);
+        self.loop_labels.push((self.block_depth - 2, self.block_depth));
         for s in &stmt.body.statements {
             self.generate_statement(s, f)?;
         }
+        self.loop_labels.pop();
+        self.close_block(f);
 
         // Generate update statement if present (runs after each iteration)
         if let Some(update) = &stmt.update {
@@ -843,8 +1063,9 @@ impl CodeGenerator {
         // Branch back to start of loop
         f.instruction(&Instruction::Br(0));
 
-        // End loop block
-        f.instruction(&Instruction::End);
+        // End loop and outer blocks
+        self.close_block(f);
+        self.close_block(f);
 
         Ok(())
     }
@@ -889,8 +1110,10 @@ impl CodeGenerator {
         // - If Some, extracts the value, binds it, executes body, continues loop
         // - If None, breaks out of the loop
 
-        // Start the loop block
-        f.instruction(&Instruction::Loop(wasm_encoder::BlockType::Empty));
+        // Start the outer block to break out of, then the loop block
+        self.open_block(Instruction::Block(wasm_encoder::BlockType::Empty), f);
+        self.open_block(Instruction::Loop(wasm_encoder::BlockType::Empty), f);
+        self.loop_labels.push((self.block_depth - 1, self.block_depth));
 
         // Step 3: Call next() on the iterator
         // For now, this is a simplified version that assumes:
@@ -947,8 +1170,10 @@ impl CodeGenerator {
         // Step 6: Continue the loop (branch back to the start)
         f.instruction(&Instruction::Br(0));
 
-        // End the loop block
-        f.instruction(&Instruction::End);
+        // End the loop and outer blocks
+        self.loop_labels.pop();
+        self.close_block(f);
+        self.close_block(f);
 
         Ok(())
     }
@@ -1042,6 +1267,21 @@ impl CodeGenerator {
                 // For now, push a placeholder value
                 f.instruction(&Instruction::I32Const(0));
             }
+            Expression::Infix(infix) if infix.operator.kind == TokenKind::QuestionQuestion => {
+                // The Option is a pointer to [tag: i32] [value]: take the value
+                // when the tag is Some, otherwise evaluate the default
+                let option_local = self.local_count;
+                self.local_count += 1;
+                self.generate_expression(&infix.left, f)?;
+                f.instruction(&Instruction::LocalTee(option_local));
+                f.instruction(&Instruction::I32Load(wasm_encoder::MemArg { offset: 0, align: 2, memory_index: 0 }));
+                self.open_block(Instruction::If(wasm_encoder::BlockType::Result(ValType::I32)), f);
+                f.instruction(&Instruction::LocalGet(option_local));
+                f.instruction(&Instruction::I32Load(wasm_encoder::MemArg { offset: 4, align: 2, memory_index: 0 }));
+                f.instruction(&Instruction::Else);
+                self.generate_expression(&infix.right, f)?;
+                self.close_block(f);
+            }
             Expression::Infix(infix) => {
                 self.generate_expression(&infix.left, f)?;
                 self.generate_expression(&infix.right, f)?;
@@ -1060,6 +1300,11 @@ impl CodeGenerator {
                     TokenKind::GtEq => { f.instruction(&Instruction::I32GeS); }
                     TokenKind::AmpAmp => { f.instruction(&Instruction::I32And); }  // Logical AND (works for booleans as 0/1)
                     TokenKind::PipePipe => { f.instruction(&Instruction::I32Or); }  // Logical OR (works for booleans as 0/1)
+                    TokenKind::Ampersand => { f.instruction(&Instruction::I32And); }
+                    TokenKind::Pipe => { f.instruction(&Instruction::I32Or); }
+                    TokenKind::Caret => { f.instruction(&Instruction::I32Xor); }
+                    TokenKind::LeftShift => { f.instruction(&Instruction::I32Shl); }
+                    TokenKind::RightShift => { f.instruction(&Instruction::I32ShrS); }
                     _ => return Err(CompileError::Generic(format!(
                         "Unsupported operator: {:?}", infix.operator.kind
                     ))),
@@ -1354,7 +1599,7 @@ impl CodeGenerator {
                 // Push the tuple pointer as the result
                 f.instruction(&Instruction::I32Const(tuple_ptr as i32));
             }
-            Expression::StringLiteral(_s) => {
+            Expression::StringLiteral(_) | Expression::Template(_) => {
                 // For now, strings are represented as i32 (pointer to string data)
                 // In a full implementation, we'd allocate string in WASM memory
                 // For now, push a dummy value
@@ -1396,7 +1641,7 @@ impl CodeGenerator {
                 self.generate_expression(&ternary.condition, f)?;
 
                 // Start if block with a result type (produces i32 value)
-                f.instruction(&Instruction::If(wasm_encoder::BlockType::Result(ValType::I32)));
+                self.open_block(Instruction::If(wasm_encoder::BlockType::Result(ValType::I32)), f);
 
                 // Generate the true branch
                 self.generate_expression(&ternary.true_expr, f)?;
@@ -1406,7 +1651,7 @@ impl CodeGenerator {
                 self.generate_expression(&ternary.false_expr, f)?;
 
                 // End if block
-                f.instruction(&Instruction::End);
+                self.close_block(f);
             }
             Expression::TypeCast(type_cast) => {
                 // Generate code for type casting
@@ -1462,7 +1707,7 @@ impl CodeGenerator {
                 self.generate_expression(&if_expr.condition, f)?;
 
                 // Start if block with a result type (produces i32 value)
-                f.instruction(&Instruction::If(wasm_encoder::BlockType::Result(ValType::I32)));
+                self.open_block(Instruction::If(wasm_encoder::BlockType::Result(ValType::I32)), f);
 
                 // Generate the then branch
                 self.generate_expression(&if_expr.then_expr, f)?;
@@ -1477,7 +1722,7 @@ impl CodeGenerator {
                 }
 
                 // End if block
-                f.instruction(&Instruction::End);
+                self.close_block(f);
             }
             Expression::Block(block) => {
                 // Generate code for all statements in the block
@@ -1499,6 +1744,10 @@ impl CodeGenerator {
                 // Return placeholder for now
                 f.instruction(&Instruction::I32Const(0));
             }
+            Expression::InlineStyle(_) | Expression::Classes(_) => {
+                // Inline styles and class lists are applied by the JS runtime
+                f.instruction(&Instruction::I32Const(0));
+            }
             // Reactivity primitives (Phase 12) - Not supported in WASM backend yet
             Expression::Signal(_) | Expression::Computed(_) |
             Expression::Effect(_) | Expression::Batch(_) => {
@@ -1534,6 +1783,8 @@ impl CodeGenerator {
                     // Event handler - we'll handle this specially
                     continue;
                 }
+                // {...props}: what it holds is only known at runtime
+                Expression::Spread(_) => continue,
                 _ => "".to_string(), // Placeholder for other expressions
             };
             attrs.push((attr.name.value.clone(), value));
@@ -1618,6 +1869,22 @@ impl CodeGenerator {
                     return Ok(());
                 }
                 _ => {
+                    // Promises, event buses, contexts, JS values, translations and raw HTML are JavaScript values, handled by the JS runtime
+                    // For WASM, we just push a placeholder value (0)
+                    if ident.value.starts_with("Promise::")
+                        || ident.value.starts_with("EventBus::")
+                        || ident.value.starts_with("JsValue::")
+                        || ident.value.starts_with("I18n::")
+                        || (ident.value == "sleep" && !self.func_symbols.funcs.contains_key("sleep"))
+                        || (ident.value == "on_cleanup" && !self.func_symbols.funcs.contains_key("on_cleanup"))
+                        || (ident.value == "unsafe_raw" && !self.func_symbols.funcs.contains_key("unsafe_raw"))
+                        || (ident.value == "create_context" && !self.func_symbols.funcs.contains_key("create_context"))
+                        || (ident.value == "use_context" && !self.func_symbols.funcs.contains_key("use_context"))
+                    {
+                        f.instruction(&Instruction::I32Const(0));
+                        return Ok(());
+                    }
+
                     // Check if this is a namespaced identifier (e.g., console::log, document::write)
                     // Treat all namespaced identifiers as external imports
                     if ident.value.contains("::") {
@@ -1908,7 +2175,7 @@ impl CodeGenerator {
                 f.instruction(&Instruction::I32Eq);
 
                 // Start an if block (ValType::I32 means the block produces an i32 value)
-                f.instruction(&Instruction::If(wasm_encoder::BlockType::Result(ValType::I32)));
+                self.open_block(Instruction::If(wasm_encoder::BlockType::Result(ValType::I32)), f);
 
                 // If they match, generate this arm's body
                 self.generate_expression(&arm.body, f)?;
@@ -1920,7 +2187,7 @@ impl CodeGenerator {
                 self.generate_match_arm(arms, arm_index + 1, scrutinee_local, f)?;
 
                 // End the if block
-                f.instruction(&Instruction::End);
+                self.close_block(f);
             }
             Pattern::EnumVariant { name, fields } => {
                 // For enum variants like Result::Ok or Result::Err
@@ -1981,6 +2248,11 @@ impl CodeGenerator {
                     self.collect_lambdas_from_statement(s);
                 }
             }
+            Statement::Loop(loop_stmt) => {
+                for s in &loop_stmt.body.statements {
+                    self.collect_lambdas_from_statement(s);
+                }
+            }
             Statement::For(for_stmt) => {
                 if let Some(init) = &for_stmt.init {
                     self.collect_lambdas_from_statement(init);
@@ -2127,6 +2399,21 @@ impl CodeGenerator {
             Expression::CssMacro(_) => {
                 // CSS macro doesn't contain lambdas
             }
+            Expression::InlineStyle(style) => {
+                for expr in style.expressions() {
+                    self.collect_lambdas_from_expression(expr);
+                }
+            }
+            Expression::Classes(classes) => {
+                for expr in classes.expressions() {
+                    self.collect_lambdas_from_expression(expr);
+                }
+            }
+            Expression::Template(template) => {
+                for expr in template.expressions() {
+                    self.collect_lambdas_from_expression(expr);
+                }
+            }
             // Reactivity primitives (Phase 12) - Collect lambdas from callbacks
             Expression::Signal(signal_expr) => {
                 self.collect_lambdas_from_expression(&signal_expr.initial_value);
@@ -2274,6 +2561,21 @@ impl CodeGenerator {
             Expression::CssMacro(_) => {
                 // CSS macro doesn't contain variable references
             }
+            Expression::InlineStyle(style) => {
+                for expr in style.expressions() {
+                    self.collect_variable_references(expr, vars);
+                }
+            }
+            Expression::Classes(classes) => {
+                for expr in classes.expressions() {
+                    self.collect_variable_references(expr, vars);
+                }
+            }
+            Expression::Template(template) => {
+                for expr in template.expressions() {
+                    self.collect_variable_references(expr, vars);
+                }
+            }
             // Reactivity primitives (Phase 12) - Collect variable references
             Expression::Signal(signal_expr) => {
                 self.collect_variable_references(&signal_expr.initial_value, vars);
@@ -2336,6 +2638,11 @@ impl CodeGenerator {
                     self.collect_variable_references_from_statement(s, vars);
                 }
             }
+            Statement::Loop(loop_stmt) => {
+                for s in &loop_stmt.body.statements {
+                    self.collect_variable_references_from_statement(s, vars);
+                }
+            }
             Statement::For(for_stmt) => {
                 if let Some(init) = &for_stmt.init {
                     self.collect_variable_references_from_statement(init, vars);
@@ -2363,14 +2670,14 @@ impl CodeGenerator {
     /// Generate CSS from a theme block (creates CSS custom properties)
     /// theme DarkMode { primary: #1a1a1a; } -> :root { --DarkMode-primary: #1a1a1a; }
     fn generate_theme_block_css(&mut self, theme: &ThemeBlock) -> Result<(), CompileError> {
-        self.css_output.push_str(":root {\n");
+        let mut rule = CssStyleRule::new(":root", CssSpan::default());
 
         for prop in &theme.properties {
             let var_name = format!("--{}-{}", theme.name.value, prop.name);
-            self.css_output.push_str(&format!("  {}: {};\n", var_name, prop.value));
+            rule.declare(var_name, prop.value.clone());
         }
 
-        self.css_output.push_str("}\n\n");
+        self.css_ast.push(CssNode::Rule(rule));
         Ok(())
     }
 
@@ -2382,14 +2689,14 @@ impl CodeGenerator {
 
         // Generate main rule
         if !style.properties.is_empty() {
-            self.css_output.push_str(&format!(".{} {{\n", class_name));
+            let mut rule = CssStyleRule::new(format!(".{}", class_name), CssSpan::default());
 
             for prop in &style.properties {
                 let value = self.resolve_style_value(&prop.value);
-                self.css_output.push_str(&format!("  {}: {};\n", prop.name, value));
+                rule.declare(prop.name.clone(), value);
             }
 
-            self.css_output.push_str("}\n\n");
+            self.css_ast.push(CssNode::Rule(rule));
         }
 
         // Generate nested selectors
@@ -2404,14 +2711,14 @@ impl CodeGenerator {
             };
 
             if !nested.properties.is_empty() {
-                self.css_output.push_str(&format!("{} {{\n", nested_selector));
+                let mut rule = CssStyleRule::new(nested_selector, CssSpan::default());
 
                 for prop in &nested.properties {
                     let value = self.resolve_style_value(&prop.value);
-                    self.css_output.push_str(&format!("  {}: {};\n", prop.name, value));
+                    rule.declare(prop.name.clone(), value);
                 }
 
-                self.css_output.push_str("}\n\n");
+                self.css_ast.push(CssNode::Rule(rule));
             }
         }
 
@@ -0,0 +1,100 @@
+// Condition Lint - `if x = 5`, an assignment where a condition goes
+//
+// There are no assignment expressions, so the parser reads an `=` between a
+// condition's operands as the `==` it was meant to be, and marks the
+// comparison. It's warned about here, after parsing, so cached ASTs warn
+// too, with a fix that tools like the LSP can apply as-is.
+
+use crate::ast::{InfixExpression, Program};
+use crate::diagnostics::{CodeSuggestion, Diagnostic, SourceLocation};
+use crate::visit::{walk_infix, Visit};
+
+/// A warning for each `=` read as `==` in a condition
+pub fn check_program(program: &Program, file: &str) -> Vec<Diagnostic> {
+    let mut checker = Checker { file, diagnostics: Vec::new() };
+    checker.visit_program(program);
+    checker.diagnostics
+}
+
+struct Checker<'a> {
+    file: &'a str,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Visit for Checker<'_> {
+    fn visit_infix(&mut self, infix: &InfixExpression) {
+        if infix.assign_typo {
+            let location = SourceLocation {
+                file: self.file.to_string(),
+                line: infix.operator.line,
+                column: infix.operator.column,
+                length: 1,
+            };
+            self.diagnostics.push(
+                Diagnostic::warning("assignment used as a condition")
+                    .at(location.clone())
+                    .with_note("it's compared as `==`")
+                    .with_code_suggestion(CodeSuggestion {
+                        message: "did you mean `==`?".to_string(),
+                        location,
+                        replacement: "==".to_string(),
+                    }),
+            );
+        }
+        walk_infix(self, infix);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+    use crate::token::TokenKind;
+
+    fn warnings(source: &str) -> Vec<Diagnostic> {
+        let mut lexer = Lexer::new(source.to_string());
+        let program = Parser::new(&mut lexer).parse_program().unwrap_or_else(|e| panic!("{}: {:?}", source, e));
+        check_program(&program, "app.jnc")
+    }
+
+    #[test]
+    fn test_assignment_in_condition_suggests_eq() {
+        for source in [
+            "fn f() {\n    if x = 5 {\n        go();\n    }\n}",
+            "fn f() {\n    while x = next() {\n        go();\n    }\n}",
+            "component C() {\n    return <div>{x = 5 && <p>Five</p>}</div>;\n}",
+            "component C() {\n    return <div>{x = (a, b) ? <p>Yes</p> : <p>No</p>}</div>;\n}",
+            "fn f() {\n    let y = if x = 5 { 1 } else { 2 };\n}",
+        ] {
+            let warnings = warnings(source);
+            assert_eq!(warnings.len(), 1, "{}", source);
+            assert_eq!(warnings[0].message, "assignment used as a condition");
+            let fix = &warnings[0].code_suggestions[0];
+            assert_eq!(fix.message, "did you mean `==`?");
+            assert_eq!(fix.replacement, "==");
+
+            // Replaces exactly the `=` token
+            let mut lexer = Lexer::new(source.to_string());
+            let assign = std::iter::from_fn(|| Some(lexer.next_token()))
+                .take_while(|t| t.kind != TokenKind::Eof)
+                .filter(|t| t.kind == TokenKind::Assign)
+                .last()
+                .unwrap();
+            assert_eq!((fix.location.line, fix.location.column, fix.location.length), (assign.line, assign.column, 1), "{}", source);
+            assert_eq!(fix.location.file, "app.jnc");
+        }
+
+        // Comparisons, and assignments that aren't conditions, aren't warned about
+        assert!(warnings("fn f() {\n    if x == 5 {\n        go();\n    }\n    while x != 3 {\n        x = x + 1;\n    }\n}").is_empty());
+    }
+
+    #[test]
+    fn test_assignment_in_condition_compares() {
+        // Read as the `==` it stands for, binding as tightly as one
+        let mut lexer = Lexer::new("component C() {\n    return <div>{ready && x = 5 && <p>Five</p>}</div>;\n}".to_string());
+        let program = Parser::new(&mut lexer).parse_program().unwrap();
+        let js = crate::js_emitter::JSEmitter::new(&program).generate_client_js();
+        assert!(js.contains("((ready && (x == 5)) && "), "{}", js);
+    }
+}
@@ -0,0 +1,450 @@
+// CSS AST - Structured stylesheet for build-tool plugins
+//
+// The CSS generator lowers css! blocks, style blocks and themes into a
+// CssStylesheet: scoped selectors and resolved values, in emission order.
+// Plugins can walk and rewrite it with a CssVisitor before it is rendered
+// to text, or serialize it to JSON for out-of-process tools.
+
+use crate::ast::CssSelector;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+pub use crate::ast::CssSpan;
+
+/// A complete stylesheet, ready to be rendered with `to_css()`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CssStylesheet {
+    pub nodes: Vec<CssNode>,
+}
+
+/// A top-level or nested stylesheet entry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CssNode {
+    Rule(CssStyleRule),
+    AtRule(CssAtRule),
+}
+
+/// A style rule: `.Button_button_a3f5c9 { color: blue; }`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CssStyleRule {
+    pub selector: String,
+    pub declarations: Vec<CssStyleDeclaration>,
+    pub span: CssSpan,
+    /// Lints silenced by `@allow(...)` on the declaring component
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow: Vec<String>,
+    /// Where the rule was declared, `src/card.jnc:12`; several once
+    /// identical rules are merged. Rendered as a comment in dev builds.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<String>,
+}
+
+/// A single `property: value` pair
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CssStyleDeclaration {
+    pub property: String,
+    pub value: String,
+}
+
+/// An at-rule: `@media (min-width: 768px) { ... }`, `@keyframes name { ... }`,
+/// `@font-face { ... }`. One with neither declarations nor children has no
+/// block at all: `@import url("/theme.css");`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CssAtRule {
+    pub name: String,     // "media", "container", "keyframes", "supports", "font-face", "import", "page"
+    pub prelude: String,  // "(min-width: 768px)", "Button_fadeIn_a3f5c9"
+    /// Declarations directly in the block, as in `@font-face`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub declarations: Vec<CssStyleDeclaration>,
+    pub children: Vec<CssNode>,
+    pub span: CssSpan,
+}
+
+impl CssStyleRule {
+    pub fn new(selector: impl Into<String>, span: CssSpan) -> Self {
+        CssStyleRule {
+            selector: selector.into(),
+            declarations: Vec::new(),
+            span,
+            allow: Vec::new(),
+            sources: Vec::new(),
+        }
+    }
+
+    pub fn declare(&mut self, property: impl Into<String>, value: impl Into<String>) {
+        self.declarations.push(CssStyleDeclaration {
+            property: property.into(),
+            value: value.into(),
+        });
+    }
+}
+
+impl CssStylesheet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn push(&mut self, node: CssNode) {
+        self.nodes.push(node);
+    }
+
+    /// Append every node of another stylesheet
+    pub fn extend(&mut self, other: CssStylesheet) {
+        self.nodes.extend(other.nodes);
+    }
+
+    /// Walk the stylesheet with a visitor, which may rewrite it in place
+    pub fn accept(&mut self, visitor: &mut dyn CssVisitor) {
+        visitor.visit_stylesheet(self);
+    }
+
+    /// Render the stylesheet as CSS text. `@import`s go first, wherever they
+    /// were declared: browsers ignore them after any other rule.
+    pub fn to_css(&self) -> String {
+        self.render(false)
+    }
+
+    /// Render the stylesheet like `to_css`, each rule after a comment saying
+    /// where it was declared: `/* src/card.jnc:12 */`
+    pub fn to_css_with_sources(&self) -> String {
+        self.render(true)
+    }
+
+    fn render(&self, sources: bool) -> String {
+        let (imports, rest) = self.split_imports();
+        let mut output = String::new();
+        for node in imports.nodes.iter().chain(&rest.nodes) {
+            render_node(node, 0, sources, &mut output);
+        }
+        output
+    }
+
+    /// Remove the repetition of a stylesheet collected from many css!
+    /// blocks, without changing which declaration wins anywhere. A rule is
+    /// merged into a later one with the same declarations, `.a, .b { ... }`
+    /// in the later one's place, when no rule between them sets any of its
+    /// properties (or a shorthand or longhand of one); rules keep their
+    /// order otherwise. Repeats of an `@import`, `@font-face` or `@keyframes`
+    /// are dropped. Rules inside at-rules are merged the same way.
+    pub fn dedupe(&mut self) {
+        dedupe_nodes(&mut self.nodes);
+    }
+
+    /// The stylesheet's `@import`s, and everything else
+    pub fn split_imports(&self) -> (CssStylesheet, CssStylesheet) {
+        let (imports, rest) = self.nodes.iter().cloned().partition(|node| {
+            matches!(node, CssNode::AtRule(at_rule) if at_rule.name == "import")
+        });
+        (CssStylesheet { nodes: imports }, CssStylesheet { nodes: rest })
+    }
+
+    /// Serialize the stylesheet to pretty-printed JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("CSS AST is always serializable")
+    }
+
+    /// Read a stylesheet back from its JSON form
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+fn render_node(node: &CssNode, depth: usize, sources: bool, output: &mut String) {
+    let indent = "  ".repeat(depth);
+
+    match node {
+        CssNode::Rule(rule) => {
+            if sources && !rule.sources.is_empty() {
+                output.push_str(&format!("{}/* {} */\n", indent, rule.sources.join(", ").replace("*/", "* /")));
+            }
+            output.push_str(&format!("{}{} {{\n", indent, rule.selector));
+            for decl in &rule.declarations {
+                output.push_str(&format!("{}  {}: {};\n", indent, decl.property, decl.value));
+            }
+            output.push_str(&indent);
+            output.push_str("}\n");
+        }
+        CssNode::AtRule(at_rule) => {
+            output.push_str(&format!("{}@{}", indent, at_rule.name));
+            if !at_rule.prelude.is_empty() {
+                output.push(' ');
+                output.push_str(&at_rule.prelude);
+            }
+            if at_rule.declarations.is_empty() && at_rule.children.is_empty() {
+                output.push_str(";\n");
+            } else {
+                output.push_str(" {\n");
+                for decl in &at_rule.declarations {
+                    output.push_str(&format!("{}  {}: {};\n", indent, decl.property, decl.value));
+                }
+                for child in &at_rule.children {
+                    render_node(child, depth + 1, sources, output);
+                }
+                output.push_str(&indent);
+                output.push_str("}\n");
+            }
+        }
+    }
+
+    // Blank line between top-level blocks
+    if depth == 0 {
+        output.push('\n');
+    }
+}
+
+fn dedupe_nodes(nodes: &mut Vec<CssNode>) {
+    for node in nodes.iter_mut() {
+        if let CssNode::AtRule(at_rule) = node {
+            dedupe_nodes(&mut at_rule.children);
+        }
+    }
+
+    let mut seen_at_rules = HashSet::new();
+    let mut index = 0;
+    while index < nodes.len() {
+        match &nodes[index] {
+            CssNode::Rule(_) => {
+                if let Some(target) = merge_target(nodes, index) {
+                    let CssNode::Rule(earlier) = nodes.remove(index) else { unreachable!() };
+                    if let CssNode::Rule(later) = &mut nodes[target - 1] {
+                        later.merge_earlier(earlier);
+                    }
+                    continue;
+                }
+            }
+            CssNode::AtRule(at_rule) if matches!(at_rule.name.as_str(), "import" | "font-face" | "keyframes") => {
+                let mut rendered = String::new();
+                render_node(&nodes[index], 0, false, &mut rendered);
+                if !seen_at_rules.insert(rendered) {
+                    nodes.remove(index);
+                    continue;
+                }
+            }
+            CssNode::AtRule(_) => {}
+        }
+        index += 1;
+    }
+}
+
+/// The later rule a rule at `index` can be merged into: the first with the
+/// same declarations, unless a node before it sets a related property
+fn merge_target(nodes: &[CssNode], index: usize) -> Option<usize> {
+    let CssNode::Rule(rule) = &nodes[index] else {
+        return None;
+    };
+    let families: HashSet<&str> = rule.declarations.iter().map(|decl| property_family(&decl.property)).collect();
+    for (offset, node) in nodes[index + 1..].iter().enumerate() {
+        match node {
+            CssNode::Rule(later) if later.declarations == rule.declarations => return Some(index + 1 + offset),
+            _ if sets_family(node, &families) => return None,
+            _ => {}
+        }
+    }
+    None
+}
+
+fn sets_family(node: &CssNode, families: &HashSet<&str>) -> bool {
+    let (declarations, children) = match node {
+        CssNode::Rule(rule) => (&rule.declarations, &[][..]),
+        CssNode::AtRule(at_rule) => (&at_rule.declarations, &at_rule.children[..]),
+    };
+    declarations.iter().any(|decl| {
+        let family = property_family(&decl.property);
+        family == "all" || families.contains("all") || families.contains(family)
+    }) || children.iter().any(|child| sets_family(child, families))
+}
+
+/// Properties that can override each other share a family: a shorthand and
+/// its longhands, `margin` and `margin-top`, `font` and `line-height`
+fn property_family(property: &str) -> &str {
+    if property.starts_with("--") {
+        return property;
+    }
+    let property = ["-webkit-", "-moz-", "-ms-", "-o-"]
+        .iter()
+        .find_map(|prefix| property.strip_prefix(prefix))
+        .unwrap_or(property);
+    match property {
+        "line-height" => "font",
+        "top" | "right" | "bottom" | "left" => "inset",
+        "row-gap" | "column-gap" => "gap",
+        "columns" => "column",
+        _ => match property.split('-').next().unwrap_or(property) {
+            "place" | "justify" => "align",
+            family => family,
+        },
+    }
+}
+
+impl CssStyleRule {
+    /// Take in an earlier rule with the same declarations: its selectors
+    /// go first, then this rule's, each once
+    fn merge_earlier(&mut self, earlier: CssStyleRule) {
+        let mut selectors: Vec<String> = Vec::new();
+        for selector in CssSelector::split_list(&earlier.selector)
+            .into_iter()
+            .chain(CssSelector::split_list(&self.selector))
+        {
+            if !selectors.iter().any(|seen| seen == selector) {
+                selectors.push(selector.to_string());
+            }
+        }
+        self.selector = selectors.join(", ");
+        for lint in earlier.allow {
+            if !self.allow.contains(&lint) {
+                self.allow.push(lint);
+            }
+        }
+        let mut sources = earlier.sources;
+        sources.extend(std::mem::take(&mut self.sources));
+        sources.dedup();
+        self.sources = sources;
+    }
+}
+
+/// Mutable visitor over a CssStylesheet
+///
+/// Override the hooks you need; the defaults walk into children via the
+/// `walk_*` functions, so overriding methods should call them to keep recursing.
+pub trait CssVisitor {
+    fn visit_stylesheet(&mut self, stylesheet: &mut CssStylesheet) {
+        walk_stylesheet(self, stylesheet);
+    }
+
+    fn visit_rule(&mut self, rule: &mut CssStyleRule) {
+        walk_rule(self, rule);
+    }
+
+    fn visit_at_rule(&mut self, at_rule: &mut CssAtRule) {
+        walk_at_rule(self, at_rule);
+    }
+
+    fn visit_declaration(&mut self, _declaration: &mut CssStyleDeclaration) {}
+}
+
+pub fn walk_stylesheet<V: CssVisitor + ?Sized>(visitor: &mut V, stylesheet: &mut CssStylesheet) {
+    for node in &mut stylesheet.nodes {
+        walk_node(visitor, node);
+    }
+}
+
+pub fn walk_node<V: CssVisitor + ?Sized>(visitor: &mut V, node: &mut CssNode) {
+    match node {
+        CssNode::Rule(rule) => visitor.visit_rule(rule),
+        CssNode::AtRule(at_rule) => visitor.visit_at_rule(at_rule),
+    }
+}
+
+pub fn walk_rule<V: CssVisitor + ?Sized>(visitor: &mut V, rule: &mut CssStyleRule) {
+    for decl in &mut rule.declarations {
+        visitor.visit_declaration(decl);
+    }
+}
+
+pub fn walk_at_rule<V: CssVisitor + ?Sized>(visitor: &mut V, at_rule: &mut CssAtRule) {
+    for decl in &mut at_rule.declarations {
+        visitor.visit_declaration(decl);
+    }
+    for child in &mut at_rule.children {
+        walk_node(visitor, child);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> CssStylesheet {
+        let mut rule = CssStyleRule::new(".App_card_abc123", CssSpan { line: 2, column: 5 });
+        rule.declare("padding", "8px 16px");
+
+        let mut media_rule = CssStyleRule::new(".App_card_abc123", CssSpan { line: 4, column: 9 });
+        media_rule.declare("padding", "24px");
+
+        let mut sheet = CssStylesheet::new();
+        sheet.push(CssNode::Rule(rule));
+        sheet.push(CssNode::AtRule(CssAtRule {
+            name: "media".to_string(),
+            prelude: "(min-width: 768px)".to_string(),
+            declarations: Vec::new(),
+            children: vec![CssNode::Rule(media_rule)],
+            span: CssSpan { line: 4, column: 9 },
+        }));
+        sheet
+    }
+
+    #[test]
+    fn test_to_css_matches_generator_layout() {
+        assert_eq!(
+            sample().to_css(),
+            ".App_card_abc123 {\n  padding: 8px 16px;\n}\n\n\
+             @media (min-width: 768px) {\n  .App_card_abc123 {\n    padding: 24px;\n  }\n}\n\n"
+        );
+    }
+
+    #[test]
+    fn test_visitor_rewrites_nested_declarations() {
+        struct Upper;
+        impl CssVisitor for Upper {
+            fn visit_declaration(&mut self, decl: &mut CssStyleDeclaration) {
+                decl.value = decl.value.to_uppercase();
+            }
+        }
+
+        let mut sheet = sample();
+        sheet.accept(&mut Upper);
+        let css = sheet.to_css();
+        assert!(css.contains("padding: 8PX 16PX;"));
+        assert!(css.contains("    padding: 24PX;"));
+    }
+
+    fn rule(selector: &str, declarations: &[(&str, &str)], source: &str) -> CssNode {
+        let mut rule = CssStyleRule::new(selector, CssSpan { line: 1, column: 1 });
+        for (property, value) in declarations {
+            rule.declare(*property, *value);
+        }
+        rule.sources = vec![source.to_string()];
+        CssNode::Rule(rule)
+    }
+
+    #[test]
+    fn test_dedupe_merges_identical_rules_and_keeps_sources() {
+        let mut sheet = CssStylesheet::new();
+        sheet.push(rule(".a", &[("color", "red")], "a.jnc:2"));
+        sheet.push(rule(".b", &[("margin", "0")], "a.jnc:3"));
+        sheet.push(rule(".c", &[("color", "red")], "b.jnc:7"));
+        sheet.dedupe();
+
+        assert_eq!(sheet.nodes.len(), 2);
+        assert_eq!(
+            sheet.to_css_with_sources(),
+            "/* a.jnc:3 */\n.b {\n  margin: 0;\n}\n\n\
+             /* a.jnc:2, b.jnc:7 */\n.a, .c {\n  color: red;\n}\n\n"
+        );
+    }
+
+    #[test]
+    fn test_dedupe_keeps_rules_whose_order_matters() {
+        // Moving .a past .b would let .b's border-color win over .a's
+        let mut sheet = CssStylesheet::new();
+        sheet.push(rule(".a", &[("border", "1px solid red")], "a.jnc:1"));
+        sheet.push(rule(".b", &[("border-color", "blue")], "a.jnc:2"));
+        sheet.push(rule(".c", &[("border", "1px solid red")], "a.jnc:3"));
+        sheet.dedupe();
+        assert_eq!(sheet.nodes.len(), 3);
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let sheet = sample();
+        let json = sheet.to_json();
+        assert!(json.contains("\"type\": \"at_rule\""));
+        assert!(json.contains("\"line\": 4"));
+        assert_eq!(CssStylesheet::from_json(&json).unwrap(), sheet);
+    }
+}
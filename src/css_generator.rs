@@ -2,17 +2,21 @@
 // Generates scoped CSS from CssExpression AST nodes
 
 use crate::ast::*;
+use crate::css_ast::{CssAtRule, CssNode, CssStyleDeclaration, CssStyleRule, CssStylesheet};
+use crate::escape::{escape, EscapeMode};
 use std::collections::HashMap;
 
 /// CSS Generator - converts CSS AST to scoped CSS strings
 pub struct CssGenerator {
     /// Component name for scoping (e.g., "Button", "App")
     component_name: String,
-    /// Generated CSS output
-    css_output: String,
+    /// Generated stylesheet, rendered to text on demand
+    stylesheet: CssStylesheet,
     /// Map of original class names to scoped class names
     /// e.g., "button" -> "Button_button_a3f5c9"
     class_map: HashMap<String, String>,
+    /// Hash suffix shared by the block's scoped class names
+    block_hash: String,
     /// Map of original keyframe names to scoped keyframe names
     /// e.g., "fadeIn" -> "Button_fadeIn_a3f5c9"
     /// Sprint 2 Task 2.6
@@ -29,8 +33,9 @@ impl CssGenerator {
     pub fn new(component_name: String) -> Self {
         Self {
             component_name,
-            css_output: String::new(),
+            stylesheet: CssStylesheet::new(),
             class_map: HashMap::new(),
+            block_hash: String::new(),
             keyframes_map: HashMap::new(),
             dynamic_declarations: HashMap::new(),
         }
@@ -38,17 +43,50 @@ impl CssGenerator {
 
     /// Generate CSS from a CssExpression
     pub fn generate(&mut self, css_expr: &CssExpression) -> String {
-        // Generate CSS rules
-        for rule in &css_expr.rules {
-            self.generate_rule(rule);
+        // The block's hash comes from the classes it names, so it's the same
+        // in every build, and editing a declaration keeps the class names
+        let classes: Vec<String> = css_expr.classes().into_iter().map(|class| class.name).collect();
+        let hash = self.generate_hash(&format!("{}{}", self.component_name, classes.join(",")));
+        self.block_hash = hash[0..6].to_string();
+
+        // Scope keyframe names first, so rules can refer to them in `animation`
+        for keyframes in css_expr.keyframes() {
+            self.generate_scoped_keyframe_name(&keyframes.name);
+        }
+
+        for import in &css_expr.imports {
+            self.generate_import(import);
+        }
+
+        for font_face in &css_expr.font_faces {
+            self.generate_font_face(font_face);
+        }
+
+        for page in &css_expr.pages {
+            self.generate_page(page);
         }
 
-        // Generate keyframes (Sprint 2 Task 2.6)
-        for keyframes in &css_expr.keyframes {
-            self.generate_keyframes(keyframes);
+        // Rules and at-rule blocks go out in source order, which is the cascade's
+        for item in &css_expr.items {
+            match item {
+                CssItem::Rule(rule) => self.generate_rule(rule),
+                CssItem::Media(media_query) => self.generate_media_query(media_query, None),
+                CssItem::Supports(supports) => self.generate_supports(supports),
+                CssItem::Keyframes(keyframes) => self.generate_keyframes(keyframes),
+            }
         }
 
-        self.css_output.clone()
+        self.stylesheet.to_css()
+    }
+
+    /// Get the structured stylesheet generated so far
+    pub fn stylesheet(&self) -> &CssStylesheet {
+        &self.stylesheet
+    }
+
+    /// Consume the generator, returning the structured stylesheet
+    pub fn into_stylesheet(self) -> CssStylesheet {
+        self.stylesheet
     }
 
     /// Generate CSS for a single rule (with optional parent for nesting)
@@ -62,8 +100,7 @@ impl CssGenerator {
 
         // Generate rule only if it has declarations
         if !rule.declarations.is_empty() {
-            self.css_output.push_str(&scoped_selector);
-            self.css_output.push_str(" {\n");
+            let mut style_rule = CssStyleRule::new(scoped_selector.clone(), rule.span);
 
             // Generate declarations
             // Extract class name if selector is a class (for dynamic declarations)
@@ -73,11 +110,10 @@ impl CssGenerator {
             };
 
             for decl in &rule.declarations {
-                self.generate_declaration(decl, class_name.as_deref());
+                self.generate_declaration(&mut style_rule, decl, class_name.as_deref());
             }
 
-            // Close rule
-            self.css_output.push_str("}\n\n");
+            self.stylesheet.push(CssNode::Rule(style_rule));
         }
 
         // Generate nested rules recursively with this rule's selector as parent
@@ -87,7 +123,7 @@ impl CssGenerator {
 
         // Generate media queries for this rule
         for media_query in &rule.media_queries {
-            self.generate_media_query(media_query, &scoped_selector);
+            self.generate_media_query(media_query, Some(&scoped_selector));
         }
 
         // Generate container queries for this rule (Phase 8)
@@ -96,62 +132,118 @@ impl CssGenerator {
         }
     }
 
-    /// Generate CSS for a media query
-    fn generate_media_query(&mut self, media_query: &CssMediaQuery, selector: &str) {
-        // Output @media condition
-        self.css_output.push_str("@media ");
-        self.css_output.push_str(&media_query.condition);
-        self.css_output.push_str(" {\n");
-
-        // Output selector block with media query declarations
-        self.css_output.push_str("  ");
-        self.css_output.push_str(selector);
-        self.css_output.push_str(" {\n");
-
-        // Generate declarations with extra indent
-        for decl in &media_query.declarations {
-            self.css_output.push_str("    "); // Extra indent for media query
-            self.css_output.push_str(&decl.property);
-            self.css_output.push_str(": ");
-            self.css_output.push_str(&self.generate_value(&decl.value));
-            self.css_output.push_str(";\n");
+    /// Generate CSS for a media query. One nested in a rule is hoisted out of
+    /// it: its declarations go in a block for the rule's selector, and its
+    /// rules are nested under that selector, all wrapped in @media.
+    fn generate_media_query(&mut self, media_query: &CssMediaQuery, selector: Option<&str>) {
+        let outside = std::mem::take(&mut self.stylesheet);
+        if let Some(selector) = selector.filter(|_| !media_query.declarations.is_empty()) {
+            let mut style_rule = CssStyleRule::new(selector, media_query.span);
+            for decl in &media_query.declarations {
+                style_rule.declare(decl.property.clone(), self.generate_declaration_value(decl));
+            }
+            self.stylesheet.push(CssNode::Rule(style_rule));
+        }
+        for rule in &media_query.rules {
+            self.generate_rule_with_parent(rule, selector);
+        }
+        let inside = std::mem::replace(&mut self.stylesheet, outside);
+        // Nothing left inside, when all it held were skipped at-rules
+        if inside.nodes.is_empty() {
+            return;
         }
 
-        // Close selector block
-        self.css_output.push_str("  }\n");
-
-        // Close media query
-        self.css_output.push_str("}\n\n");
+        self.stylesheet.push(CssNode::AtRule(CssAtRule {
+            name: "media".to_string(),
+            prelude: media_query.condition.clone(),
+            declarations: Vec::new(),
+            children: inside.nodes,
+            span: media_query.span,
+        }));
     }
 
     /// Generate CSS for a container query (Phase 8 Sprint 1 Task 1.4)
     fn generate_container_query(&mut self, container_query: &CssContainerQuery, selector: &str) {
-        
-
-        // Output @container condition
-        self.css_output.push_str("@container ");
-        self.css_output.push_str(&container_query.condition);
-        self.css_output.push_str(" {\n");
+        // Selector block with the container query declarations, wrapped in @container
+        let mut style_rule = CssStyleRule::new(selector, container_query.span);
+        for decl in &container_query.declarations {
+            style_rule.declare(decl.property.clone(), self.generate_declaration_value(decl));
+        }
 
-        // Output selector block with container query declarations
-        self.css_output.push_str("  ");
-        self.css_output.push_str(selector);
-        self.css_output.push_str(" {\n");
+        self.stylesheet.push(CssNode::AtRule(CssAtRule {
+            name: "container".to_string(),
+            prelude: container_query.condition.clone(),
+            declarations: Vec::new(),
+            children: vec![CssNode::Rule(style_rule)],
+            span: container_query.span,
+        }));
+    }
 
-        // Generate declarations with extra indent
-        for decl in &container_query.declarations {
-            self.css_output.push_str("    "); // Extra indent for container query
-            self.css_output.push_str(&decl.property);
-            self.css_output.push_str(": ");
-            self.css_output.push_str(&self.generate_value(&decl.value));
-            self.css_output.push_str(";\n");
+    /// Generate CSS for a feature query: its rules, scoped as usual, inside @supports
+    fn generate_supports(&mut self, supports: &CssSupports) {
+        let outside = std::mem::take(&mut self.stylesheet);
+        for rule in &supports.rules {
+            self.generate_rule(rule);
         }
+        let inside = std::mem::replace(&mut self.stylesheet, outside);
+        if inside.nodes.is_empty() {
+            return;
+        }
+
+        self.stylesheet.push(CssNode::AtRule(CssAtRule {
+            name: "supports".to_string(),
+            prelude: supports.condition.clone(),
+            declarations: Vec::new(),
+            children: inside.nodes,
+            span: supports.span,
+        }));
+    }
 
-        // Close selector block
-        self.css_output.push_str("  }\n");
+    /// Generate CSS for a font face; its declarations go straight in the block
+    fn generate_font_face(&mut self, font_face: &CssFontFace) {
+        let declarations = font_face.declarations.iter()
+            .map(|decl| CssStyleDeclaration {
+                property: decl.property.clone(),
+                value: self.generate_declaration_value(decl),
+            })
+            .collect();
+
+        self.stylesheet.push(CssNode::AtRule(CssAtRule {
+            name: "font-face".to_string(),
+            prelude: String::new(),
+            declarations,
+            children: Vec::new(),
+            span: font_face.span,
+        }));
+    }
+
+    /// Generate CSS for a page rule, which styles printed pages and so isn't scoped
+    fn generate_page(&mut self, page: &CssPage) {
+        let declarations = page.declarations.iter()
+            .map(|decl| CssStyleDeclaration {
+                property: decl.property.clone(),
+                value: self.generate_declaration_value(decl),
+            })
+            .collect();
+
+        self.stylesheet.push(CssNode::AtRule(CssAtRule {
+            name: "page".to_string(),
+            prelude: page.selector.clone(),
+            declarations,
+            children: Vec::new(),
+            span: page.span,
+        }));
+    }
 
-        // Close container query
-        self.css_output.push_str("}\n\n");
+    /// Generate CSS for an import, which has no block: @import url("/theme.css");
+    fn generate_import(&mut self, import: &CssImport) {
+        self.stylesheet.push(CssNode::AtRule(CssAtRule {
+            name: "import".to_string(),
+            prelude: self.generate_value(&import.source),
+            declarations: Vec::new(),
+            children: Vec::new(),
+            span: import.span,
+        }));
     }
 
     /// Generate CSS for keyframes animation (Sprint 2 Task 2.6)
@@ -164,33 +256,28 @@ impl CssGenerator {
         // Generate scoped keyframe name
         let scoped_name = self.generate_scoped_keyframe_name(&keyframes.name);
 
-        // Output @keyframes declaration
-        self.css_output.push_str("@keyframes ");
-        self.css_output.push_str(&scoped_name);
-        self.css_output.push_str(" {\n");
-
-        // Generate each keyframe rule
-        for frame in &keyframes.frames {
-            // Output keyframe selector (from, to, or percentage)
-            self.css_output.push_str("  ");
-            self.css_output.push_str(&self.generate_keyframe_selector(&frame.selector));
-            self.css_output.push_str(" {\n");
-
-            // Generate declarations
-            for decl in &frame.declarations {
-                self.css_output.push_str("    ");
-                self.css_output.push_str(&decl.property);
-                self.css_output.push_str(": ");
-                self.css_output.push_str(&self.generate_value(&decl.value));
-                self.css_output.push_str(";\n");
-            }
-
-            // Close keyframe
-            self.css_output.push_str("  }\n");
-        }
-
-        // Close @keyframes
-        self.css_output.push_str("}\n\n");
+        // Each keyframe (from, to, or percentage) becomes a rule inside @keyframes
+        let frames = keyframes.frames.iter()
+            .map(|frame| {
+                let selector = frame.selectors.iter()
+                    .map(|selector| self.generate_keyframe_selector(selector))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let mut style_rule = CssStyleRule::new(selector, frame.span);
+                for decl in &frame.declarations {
+                    style_rule.declare(decl.property.clone(), self.generate_declaration_value(decl));
+                }
+                CssNode::Rule(style_rule)
+            })
+            .collect();
+
+        self.stylesheet.push(CssNode::AtRule(CssAtRule {
+            name: "keyframes".to_string(),
+            prelude: scoped_name,
+            declarations: Vec::new(),
+            children: frames,
+            span: keyframes.span,
+        }));
     }
 
     /// Generate keyframe selector (from, to, or percentage)
@@ -239,6 +326,10 @@ impl CssGenerator {
 
     /// Generate a scoped selector with optional parent for nesting
     fn generate_scoped_selector_with_parent(&mut self, selector: &CssSelector, parent: Option<&str>) -> String {
+        if let (Some(parent), false) = (parent, matches!(selector, CssSelector::List(_))) {
+            return self.nest_selector(selector, parent);
+        }
+
         match selector {
             CssSelector::Class(class_name) => {
                 // Generate scoped class name: .button -> .Button_button_a3f5c9
@@ -254,38 +345,24 @@ impl CssGenerator {
                 element.clone()
             }
             CssSelector::PseudoClass(pseudo_class) => {
-                // Pseudo-classes are not scoped (:hover, :focus, etc.)
+                // Pseudo-classes are not scoped (:hover, :focus, etc.), classes
+                // in them are, `:not(.active)`, unless they're `:global(.dark)`
                 // Note: These should typically be combined with other selectors
-                format!(":{}", pseudo_class)
+                self.scope_nested_selector(&format!(":{}", pseudo_class))
             }
             CssSelector::PseudoElement(pseudo_element) => {
                 // Pseudo-elements are not scoped (::before, ::after, etc.)
                 // Note: These should typically be combined with other selectors
                 format!("::{}", pseudo_element)
             }
+            CssSelector::Attribute(attribute) => {
+                // Attribute selectors are not scoped ([type="checkbox"])
+                format!("[{}]", attribute)
+            }
             CssSelector::Nested(nested_selector) => {
-                // Handle & selector for nesting
-                if nested_selector.starts_with('&') {
-                    // & refers to the parent selector
-                    if let Some(parent) = parent {
-                        // Replace & with parent selector
-                        let rest = &nested_selector[1..]; // Remove &
-                        if rest.is_empty() {
-                            // Just & by itself
-                            parent.to_string()
-                        } else {
-                            // &:hover, & .title, etc.
-                            format!("{}{}", parent, rest)
-                        }
-                    } else {
-                        // No parent, just output as-is (shouldn't happen in well-formed CSS)
-                        nested_selector.clone()
-                    }
-                } else {
-                    // Regular nested selector like `.card .title` (descendant combinator)
-                    // Scope any classes in it
-                    self.scope_nested_selector(nested_selector)
-                }
+                // Selectors with combinators or `&`, like `.card .title`:
+                // scope any classes in it, `&` is left for nest_selector
+                self.scope_nested_selector(nested_selector)
             }
             CssSelector::Compound(selectors) => {
                 // Compound selectors like `.button.primary` or `.button:hover`
@@ -302,11 +379,35 @@ impl CssGenerator {
                 }
                 result
             }
+            CssSelector::List(selectors) => {
+                // Selector lists like `h1, .title`: each one is scoped on its own
+                selectors.iter()
+                    .map(|sel| self.generate_scoped_selector_with_parent(sel, parent))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            }
         }
     }
 
+    /// Nest a selector inside its parent rule's (already scoped) selector.
+    /// The parent takes the place of each `&`, so `&.active`, `& > li` and
+    /// `.dark &` all work; without a `&` it comes first as an ancestor, so a
+    /// nested `.title` means `.card .title`. A parent list is expanded, each
+    /// parent getting its own copy: `.a, .b { &:hover {} }` gives
+    /// `.a:hover, .b:hover`
+    fn nest_selector(&mut self, selector: &CssSelector, parent: &str) -> String {
+        let own = self.generate_scoped_selector(selector);
+        CssSelector::split_list(parent)
+            .iter()
+            .map(|parent| {
+                substitute_parent(&own, parent).unwrap_or_else(|| format!("{} {}", parent, own))
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
     /// Generate a scoped class name using hash-based approach (like CSS Modules)
-    /// Format: {ComponentName}_{className}_{hash}
+    /// Format: {ComponentName}_{className}_{hash}, the hash being the block's
     /// Example: "button" -> "Button_button_a3f5c9"
     fn generate_scoped_class_name(&mut self, class_name: &str) -> String {
         // Check if we've already generated this class name
@@ -314,13 +415,7 @@ impl CssGenerator {
             return scoped.clone();
         }
 
-        // Generate hash from component name + class name
-        let hash = self.generate_hash(&format!("{}{}", self.component_name, class_name));
-        let scoped_name = format!("{}_{}_{}",
-            self.component_name,
-            class_name,
-            &hash[0..6]  // Use first 6 chars of hash
-        );
+        let scoped_name = format!("{}_{}_{}", self.component_name, class_name, self.block_hash);
 
         // Store in map for reuse
         self.class_map.insert(class_name.to_string(), scoped_name.clone());
@@ -329,29 +424,12 @@ impl CssGenerator {
     }
 
     /// Scope a nested selector string by scoping any class names
-    /// Example: ".card .title" -> ".Card_card_abc123 .title"
-    /// Note: This is a simple implementation for Sprint 1 Day 3
+    /// Example: ".card .title" -> ".Card_card_abc123 .Card_title_abc123"
+    /// Classes are scoped wherever they are, `.list>li.active` too, except
+    /// in `:global(...)`; IDs, elements, combinators and attribute selectors
+    /// are kept as written
     fn scope_nested_selector(&mut self, nested_selector: &str) -> String {
-        // Split by space to handle descendant combinators
-        let parts: Vec<&str> = nested_selector.split_whitespace().collect();
-        let mut scoped_parts = Vec::new();
-
-        for part in parts {
-            if part.starts_with('.') {
-                // It's a class selector - scope it
-                let class_name = &part[1..]; // Remove the leading dot
-                let scoped_name = self.generate_scoped_class_name(class_name);
-                scoped_parts.push(format!(".{}", scoped_name));
-            } else if part.starts_with('#') {
-                // ID selector - don't scope
-                scoped_parts.push(part.to_string());
-            } else {
-                // Element selector or other - don't scope
-                scoped_parts.push(part.to_string());
-            }
-        }
-
-        scoped_parts.join(" ")
+        CssSelector::map_classes(nested_selector, |class_name, _, _| self.generate_scoped_class_name(class_name))
     }
 
     /// Generate a simple hash from a string
@@ -367,7 +445,7 @@ impl CssGenerator {
     }
 
     /// Generate a CSS declaration (property: value;)
-    fn generate_declaration(&mut self, decl: &CssDeclaration, class_name: Option<&str>) {
+    fn generate_declaration(&mut self, style_rule: &mut CssStyleRule, decl: &CssDeclaration, class_name: Option<&str>) {
         // Check if value is dynamic (Sprint 2 Task 2.4)
         if let CssValue::Dynamic(ref expr) = decl.value {
             // Store dynamic declaration for JS emitter
@@ -382,11 +460,28 @@ impl CssGenerator {
         }
 
         // Generate static CSS declaration
-        self.css_output.push_str("  "); // Indent
-        self.css_output.push_str(&decl.property);
-        self.css_output.push_str(": ");
-        self.css_output.push_str(&self.generate_value(&decl.value));
-        self.css_output.push_str(";\n");
+        style_rule.declare(decl.property.clone(), self.generate_declaration_value(decl));
+    }
+
+    /// Generate the value of a declaration; keyframe names in `animation`
+    /// and `animation-name` are swapped for their scoped names
+    fn generate_declaration_value(&self, decl: &CssDeclaration) -> String {
+        let value = self.generate_value(&decl.value);
+        if !matches!(decl.property.as_str(), "animation" | "animation-name") {
+            return value;
+        }
+
+        let mut scoped = String::with_capacity(value.len());
+        let mut rest = value.as_str();
+        while let Some(start) = rest.find(is_name_char) {
+            scoped.push_str(&rest[..start]);
+            let len = rest[start..].find(|c| !is_name_char(c)).unwrap_or(rest.len() - start);
+            let word = &rest[start..start + len];
+            scoped.push_str(self.keyframes_map.get(word).map_or(word, String::as_str));
+            rest = &rest[start + len..];
+        }
+        scoped.push_str(rest);
+        scoped
     }
 
     /// Generate CSS value from CssValue enum
@@ -394,7 +489,7 @@ impl CssGenerator {
         match value {
             CssValue::Color(color) => color.clone(),
             CssValue::Length(num, unit) => format!("{}{}", num, unit),
-            CssValue::String(s) => format!("\"{}\"", s),
+            CssValue::String(s) => format!("\"{}\"", escape(s, EscapeMode::CssValue)),
             CssValue::Number(n) => n.to_string(),
             CssValue::Keyword(kw) => kw.clone(),
             CssValue::Function(name, args) => {
@@ -430,6 +525,35 @@ impl CssGenerator {
     }
 }
 
+/// Characters of a CSS identifier, such as a class or keyframes name
+fn is_name_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '-' || ch == '_'
+}
+
+/// Replace each `&` of a nested selector with `parent`, leaving any inside
+/// quoted attribute values alone. `None` when the selector has no `&`
+fn substitute_parent(selector: &str, parent: &str) -> Option<String> {
+    let mut substituted = String::new();
+    let mut quote = None;
+    let mut found = false;
+
+    for ch in selector.chars() {
+        match (ch, quote) {
+            ('&', None) => {
+                substituted.push_str(parent);
+                found = true;
+                continue;
+            }
+            ('"' | '\'', None) => quote = Some(ch),
+            (ch, Some(open)) if ch == open => quote = None,
+            _ => {}
+        }
+        substituted.push(ch);
+    }
+
+    found.then_some(substituted)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -464,15 +588,17 @@ mod tests {
                 CssDeclaration {
                     property: "color".to_string(),
                     value: CssValue::Raw("blue".to_string()),
+                    span: CssSpan::default(),
                 },
             ],
             nested_rules: vec![],
             media_queries: vec![],
             container_queries: vec![],
+            span: CssSpan::default(),
         };
 
         gen.generate_rule(&rule);
-        let output = gen.css_output;
+        let output = gen.stylesheet.to_css();
 
         // Should contain scoped class name
         assert!(output.contains("Button_button_"));
@@ -485,18 +611,20 @@ mod tests {
         let mut gen = CssGenerator::new("App".to_string());
 
         let css_expr = CssExpression {
-            rules: vec![
+            items: vec![
                 CssRule {
                     selector: CssSelector::Class("header".to_string()),
                     declarations: vec![
                         CssDeclaration {
                             property: "font-size".to_string(),
                             value: CssValue::Raw("24px".to_string()),
+                            span: CssSpan::default(),
                         },
                     ],
                     nested_rules: vec![],
             media_queries: vec![],
             container_queries: vec![],
+            span: CssSpan::default(),
                 },
                 CssRule {
                     selector: CssSelector::Class("footer".to_string()),
@@ -504,14 +632,19 @@ mod tests {
                         CssDeclaration {
                             property: "color".to_string(),
                             value: CssValue::Raw("gray".to_string()),
+                            span: CssSpan::default(),
                         },
                     ],
                     nested_rules: vec![],
             media_queries: vec![],
             container_queries: vec![],
+            span: CssSpan::default(),
                 },
-            ],
-            keyframes: vec![],
+            ].into_iter().map(CssItem::Rule).collect(),
+            imports: vec![],
+            font_faces: vec![],
+            pages: vec![],
+            unknown_at_rules: vec![],
         };
 
         let output = gen.generate(&css_expr);
@@ -536,15 +669,17 @@ mod tests {
                 CssDeclaration {
                     property: "background".to_string(),
                     value: CssValue::Raw("darkblue".to_string()),
+                    span: CssSpan::default(),
                 },
             ],
             nested_rules: vec![],
             media_queries: vec![],
             container_queries: vec![],
+            span: CssSpan::default(),
         };
 
         gen.generate_rule(&rule);
-        let output = gen.css_output;
+        let output = gen.stylesheet.to_css();
 
         // Should contain scoped class with pseudo-class
         assert!(output.contains("Button_button_"));
@@ -565,15 +700,17 @@ mod tests {
                 CssDeclaration {
                     property: "border".to_string(),
                     value: CssValue::Raw("2px solid blue".to_string()),
+                    span: CssSpan::default(),
                 },
             ],
             nested_rules: vec![],
             media_queries: vec![],
             container_queries: vec![],
+            span: CssSpan::default(),
         };
 
         gen.generate_rule(&rule);
-        let output = gen.css_output;
+        let output = gen.stylesheet.to_css();
 
         // Should contain both scoped class names concatenated
         assert!(output.contains("Card_card_"));
@@ -591,15 +728,17 @@ mod tests {
                 CssDeclaration {
                     property: "font-size".to_string(),
                     value: CssValue::Raw("18px".to_string()),
+                    span: CssSpan::default(),
                 },
             ],
             nested_rules: vec![],
             media_queries: vec![],
             container_queries: vec![],
+            span: CssSpan::default(),
         };
 
         gen.generate_rule(&rule);
-        let output = gen.css_output;
+        let output = gen.stylesheet.to_css();
 
         // Should scope .card but not .title (different scoping behavior)
         // Actually, both should be scoped if they're classes
@@ -618,15 +757,17 @@ mod tests {
                 CssDeclaration {
                     property: "margin".to_string(),
                     value: CssValue::Raw("0".to_string()),
+                    span: CssSpan::default(),
                 },
             ],
             nested_rules: vec![],
             media_queries: vec![],
             container_queries: vec![],
+            span: CssSpan::default(),
         };
 
         gen.generate_rule(&rule);
-        let output = gen.css_output;
+        let output = gen.stylesheet.to_css();
 
         // Should scope .header but not h1 (element)
         assert!(output.contains("App_header_"));
@@ -634,6 +775,57 @@ mod tests {
         assert!(output.contains("margin: 0;"));
     }
 
+    #[test]
+    fn test_selector_list_scopes_each_selector() {
+        let mut gen = CssGenerator::new("Nav".to_string());
+
+        let rule = CssRule {
+            selector: CssSelector::List(vec![
+                CssSelector::Element("h1".to_string()),
+                CssSelector::Class("title".to_string()),
+                CssSelector::Nested(".menu>li.active".to_string()),
+            ]),
+            declarations: vec![
+                CssDeclaration {
+                    property: "margin".to_string(),
+                    value: CssValue::Raw("0".to_string()),
+                    span: CssSpan::default(),
+                },
+            ],
+            nested_rules: vec![
+                CssRule {
+                    selector: CssSelector::Nested("&:hover".to_string()),
+                    declarations: vec![
+                        CssDeclaration {
+                            property: "color".to_string(),
+                            value: CssValue::Raw("red".to_string()),
+                            span: CssSpan::default(),
+                        },
+                    ],
+                    nested_rules: vec![],
+                    media_queries: vec![],
+                    container_queries: vec![],
+                    span: CssSpan::default(),
+                },
+            ],
+            media_queries: vec![],
+            container_queries: vec![],
+            span: CssSpan::default(),
+        };
+
+        gen.generate_rule(&rule);
+        let output = gen.stylesheet.to_css();
+        let title = format!(".{}", gen.get_class_map()["title"]);
+        let menu = format!(".{}", gen.get_class_map()["menu"]);
+        let active = format!(".{}", gen.get_class_map()["active"]);
+
+        // Each selector in the list is scoped, and `&` stands for each of them
+        let list = format!("h1, {}, {}>li{}", title, menu, active);
+        assert!(output.contains(&format!("{} {{\n  margin: 0;\n}}", list)), "got:\n{}", output);
+        let hover = format!("h1:hover, {}:hover, {}>li{}:hover", title, menu, active);
+        assert!(output.contains(&format!("{} {{\n  color: red;\n}}", hover)), "got:\n{}", output);
+    }
+
     #[test]
     fn test_get_class_map() {
         let mut gen = CssGenerator::new("Component".to_string());
@@ -661,6 +853,7 @@ mod tests {
                 CssDeclaration {
                     property: "color".to_string(),
                     value: CssValue::Raw("blue".to_string()),
+                    span: CssSpan::default(),
                 },
             ],
             nested_rules: vec![
@@ -670,19 +863,22 @@ mod tests {
                         CssDeclaration {
                             property: "color".to_string(),
                             value: CssValue::Raw("darkblue".to_string()),
+                            span: CssSpan::default(),
                         },
                     ],
                     nested_rules: vec![],
                     media_queries: vec![],
             container_queries: vec![],
+            span: CssSpan::default(),
                 },
             ],
             media_queries: vec![],
             container_queries: vec![],
+            span: CssSpan::default(),
         };
 
         gen.generate_rule(&rule);
-        let output = gen.css_output;
+        let output = gen.stylesheet.to_css();
 
         // Should generate parent rule
         assert!(output.contains("Button_button_"));
@@ -703,6 +899,7 @@ mod tests {
                 CssDeclaration {
                     property: "background".to_string(),
                     value: CssValue::Raw("white".to_string()),
+                    span: CssSpan::default(),
                 },
             ],
             nested_rules: vec![
@@ -712,26 +909,29 @@ mod tests {
                         CssDeclaration {
                             property: "font-size".to_string(),
                             value: CssValue::Raw("24px".to_string()),
+                            span: CssSpan::default(),
                         },
                     ],
                     nested_rules: vec![],
                     media_queries: vec![],
             container_queries: vec![],
+            span: CssSpan::default(),
                 },
             ],
             media_queries: vec![],
             container_queries: vec![],
+            span: CssSpan::default(),
         };
 
         gen.generate_rule(&rule);
-        let output = gen.css_output;
+        let output = gen.stylesheet.to_css();
 
         // Should generate parent rule
         assert!(output.contains("Card_card_"));
         assert!(output.contains("background: white;"));
 
         // Should generate nested rule with & replaced by parent and space preserved
-        assert!(output.contains("Card_card_") && output.contains(" .title"));
+        assert!(output.contains("Card_card_") && output.contains(" .Card_title_"));
         assert!(output.contains("font-size: 24px;"));
     }
 
@@ -745,6 +945,7 @@ mod tests {
                 CssDeclaration {
                     property: "width".to_string(),
                     value: CssValue::Raw("100%".to_string()),
+                    span: CssSpan::default(),
                 },
             ],
             nested_rules: vec![
@@ -754,6 +955,7 @@ mod tests {
                         CssDeclaration {
                             property: "height".to_string(),
                             value: CssValue::Raw("60px".to_string()),
+                            span: CssSpan::default(),
                         },
                     ],
                     nested_rules: vec![
@@ -763,33 +965,57 @@ mod tests {
                                 CssDeclaration {
                                     property: "font-size".to_string(),
                                     value: CssValue::Raw("20px".to_string()),
+                                    span: CssSpan::default(),
                                 },
                             ],
                             nested_rules: vec![],
                             media_queries: vec![],
             container_queries: vec![],
+            span: CssSpan::default(),
                         },
                     ],
                     media_queries: vec![],
             container_queries: vec![],
+            span: CssSpan::default(),
                 },
             ],
             media_queries: vec![],
             container_queries: vec![],
+            span: CssSpan::default(),
         };
 
         gen.generate_rule(&rule);
-        let output = gen.css_output;
+        let output = gen.stylesheet.to_css();
 
         // Should have all three levels
         assert!(output.contains("Container_container_"));
         assert!(output.contains("width: 100%;"));
-        assert!(output.contains(" .header"));
+        assert!(output.contains(" .Container_header_"));
         assert!(output.contains("height: 60px;"));
-        assert!(output.contains(" .title"));
+        assert!(output.contains(" .Container_title_"));
         assert!(output.contains("font-size: 20px;"));
     }
 
+    #[test]
+    fn test_nest_selector_substitutes_parent() {
+        let mut gen = CssGenerator::new("List".to_string());
+        let nested = |raw: &str| CssSelector::Nested(raw.to_string());
+        let item = format!(".{}", gen.generate_scoped_class_name("item"));
+
+        assert_eq!(gen.nest_selector(&nested("& > li"), "ul"), "ul > li");
+        assert_eq!(gen.nest_selector(&nested("&.item"), "li"), format!("li{}", item));
+        assert_eq!(gen.nest_selector(&nested("& + &"), "li"), "li + li");
+        assert_eq!(gen.nest_selector(&nested("section &"), "ul, ol"), "section ul, section ol");
+        assert_eq!(gen.nest_selector(&nested("&:hover"), "ul, ol"), "ul:hover, ol:hover");
+        // No `&`: the parent is an ancestor
+        assert_eq!(
+            gen.nest_selector(&CssSelector::Class("item".to_string()), "ul"),
+            format!("ul {}", item)
+        );
+        // A `&` inside a quoted attribute value is not the parent
+        assert_eq!(gen.nest_selector(&nested(r#"&[title="a&b"]"#), "a"), r#"a[title="a&b"]"#);
+    }
+
     #[test]
     fn test_pseudo_class() {
         let mut gen = CssGenerator::new("Button".to_string());
@@ -803,15 +1029,17 @@ mod tests {
                 CssDeclaration {
                     property: "background".to_string(),
                     value: CssValue::Raw("blue".to_string()),
+                    span: CssSpan::default(),
                 },
             ],
             nested_rules: vec![],
             media_queries: vec![],
             container_queries: vec![],
+            span: CssSpan::default(),
         };
 
         gen.generate_rule(&rule);
-        let output = gen.css_output;
+        let output = gen.stylesheet.to_css();
 
         // Should output scoped class + :hover (single colon)
         assert!(output.contains("Button_button_"));
@@ -833,15 +1061,17 @@ mod tests {
                 CssDeclaration {
                     property: "content".to_string(),
                     value: CssValue::String("→".to_string()),
+                    span: CssSpan::default(),
                 },
             ],
             nested_rules: vec![],
             media_queries: vec![],
             container_queries: vec![],
+            span: CssSpan::default(),
         };
 
         gen.generate_rule(&rule);
-        let output = gen.css_output;
+        let output = gen.stylesheet.to_css();
 
         // Should output scoped class + ::before (double colon)
         assert!(output.contains("Icon_icon_"));
@@ -865,11 +1095,13 @@ mod tests {
                 CssDeclaration {
                     property: "outline".to_string(),
                     value: CssValue::Raw("2px solid blue".to_string()),
+                    span: CssSpan::default(),
                 },
             ],
             nested_rules: vec![],
             media_queries: vec![],
             container_queries: vec![],
+            span: CssSpan::default(),
         };
 
         // Test :disabled
@@ -882,18 +1114,20 @@ mod tests {
                 CssDeclaration {
                     property: "opacity".to_string(),
                     value: CssValue::Raw("0.5".to_string()),
+                    span: CssSpan::default(),
                 },
             ],
             nested_rules: vec![],
             media_queries: vec![],
             container_queries: vec![],
+            span: CssSpan::default(),
         };
 
         gen1.generate_rule(&rule1);
         gen2.generate_rule(&rule2);
 
-        let output1 = gen1.css_output;
-        let output2 = gen2.css_output;
+        let output1 = gen1.stylesheet.to_css();
+        let output2 = gen2.stylesheet.to_css();
 
         assert!(output1.contains(":focus"));
         assert!(output1.contains("outline: 2px solid blue;"));
@@ -917,11 +1151,13 @@ mod tests {
                 CssDeclaration {
                     property: "content".to_string(),
                     value: CssValue::String("«".to_string()),
+                    span: CssSpan::default(),
                 },
             ],
             nested_rules: vec![],
             media_queries: vec![],
             container_queries: vec![],
+            span: CssSpan::default(),
         };
 
         // Test ::after
@@ -934,18 +1170,20 @@ mod tests {
                 CssDeclaration {
                     property: "content".to_string(),
                     value: CssValue::String("»".to_string()),
+                    span: CssSpan::default(),
                 },
             ],
             nested_rules: vec![],
             media_queries: vec![],
             container_queries: vec![],
+            span: CssSpan::default(),
         };
 
         gen1.generate_rule(&rule1);
         gen2.generate_rule(&rule2);
 
-        let output1 = gen1.css_output;
-        let output2 = gen2.css_output;
+        let output1 = gen1.stylesheet.to_css();
+        let output2 = gen2.stylesheet.to_css();
 
         assert!(output1.contains("::before"));
         assert!(output1.contains("content: \"«\";"));
@@ -964,6 +1202,7 @@ mod tests {
                 CssDeclaration {
                     property: "width".to_string(),
                     value: CssValue::Raw("100%".to_string()),
+                    span: CssSpan::default(),
                 },
             ],
             nested_rules: vec![],
@@ -974,15 +1213,19 @@ mod tests {
                         CssDeclaration {
                             property: "width".to_string(),
                             value: CssValue::Raw("750px".to_string()),
+                            span: CssSpan::default(),
                         },
                     ],
+                    rules: vec![],
+                    span: CssSpan::default(),
                 },
             ],
             container_queries: vec![],
+            span: CssSpan::default(),
         };
 
         gen.generate_rule(&rule);
-        let output = gen.css_output;
+        let output = gen.stylesheet.to_css();
 
         // Should contain main rule
         assert!(output.contains("Container_container_"));
@@ -1003,6 +1246,7 @@ mod tests {
                 CssDeclaration {
                     property: "display".to_string(),
                     value: CssValue::Raw("grid".to_string()),
+                    span: CssSpan::default(),
                 },
             ],
             nested_rules: vec![],
@@ -1013,8 +1257,11 @@ mod tests {
                         CssDeclaration {
                             property: "grid-template-columns".to_string(),
                             value: CssValue::Raw("repeat(2, 1fr)".to_string()),
+                            span: CssSpan::default(),
                         },
                     ],
+                    rules: vec![],
+                    span: CssSpan::default(),
                 },
                 CssMediaQuery {
                     condition: "(min-width: 1024px)".to_string(),
@@ -1022,15 +1269,19 @@ mod tests {
                         CssDeclaration {
                             property: "grid-template-columns".to_string(),
                             value: CssValue::Raw("repeat(3, 1fr)".to_string()),
+                            span: CssSpan::default(),
                         },
                     ],
+                    rules: vec![],
+                    span: CssSpan::default(),
                 },
             ],
             container_queries: vec![],
+            span: CssSpan::default(),
         };
 
         gen.generate_rule(&rule);
-        let output = gen.css_output;
+        let output = gen.stylesheet.to_css();
 
         // Should contain main rule
         assert!(output.contains("display: grid;"));
@@ -1052,6 +1303,7 @@ mod tests {
                 CssDeclaration {
                     property: "padding".to_string(),
                     value: CssValue::Raw("16px".to_string()),
+                    span: CssSpan::default(),
                 },
             ],
             nested_rules: vec![
@@ -1061,11 +1313,13 @@ mod tests {
                         CssDeclaration {
                             property: "box-shadow".to_string(),
                             value: CssValue::Raw("0 4px 8px rgba(0,0,0,0.1)".to_string()),
+                            span: CssSpan::default(),
                         },
                     ],
                     nested_rules: vec![],
                     media_queries: vec![],
             container_queries: vec![],
+            span: CssSpan::default(),
                 },
             ],
             media_queries: vec![
@@ -1075,15 +1329,19 @@ mod tests {
                         CssDeclaration {
                             property: "padding".to_string(),
                             value: CssValue::Raw("24px".to_string()),
+                            span: CssSpan::default(),
                         },
                     ],
+                    rules: vec![],
+                    span: CssSpan::default(),
                 },
             ],
             container_queries: vec![],
+            span: CssSpan::default(),
         };
 
         gen.generate_rule(&rule);
-        let output = gen.css_output;
+        let output = gen.stylesheet.to_css();
 
         // Should contain main rule
         assert!(output.contains("padding: 16px;"));
@@ -1107,28 +1365,33 @@ mod tests {
             name: "fadeIn".to_string(),
             frames: vec![
                 CssKeyframeRule {
-                    selector: CssKeyframeSelector::From,
+                    selectors: vec![CssKeyframeSelector::From],
                     declarations: vec![
                         CssDeclaration {
                             property: "opacity".to_string(),
                             value: CssValue::Raw("0".to_string()),
+                            span: CssSpan::default(),
                         },
                     ],
+                    span: CssSpan::default(),
                 },
                 CssKeyframeRule {
-                    selector: CssKeyframeSelector::To,
+                    selectors: vec![CssKeyframeSelector::To],
                     declarations: vec![
                         CssDeclaration {
                             property: "opacity".to_string(),
                             value: CssValue::Raw("1".to_string()),
+                            span: CssSpan::default(),
                         },
                     ],
+                    span: CssSpan::default(),
                 },
             ],
+            span: CssSpan::default(),
         };
 
         gen.generate_keyframes(&keyframes);
-        let output = gen.css_output;
+        let output = gen.stylesheet.to_css();
 
         // Should contain scoped keyframe name
         assert!(output.contains("@keyframes Button_fadeIn_"));
@@ -1148,37 +1411,44 @@ mod tests {
             name: "slideIn".to_string(),
             frames: vec![
                 CssKeyframeRule {
-                    selector: CssKeyframeSelector::Percentage(0.0),
+                    selectors: vec![CssKeyframeSelector::Percentage(0.0)],
                     declarations: vec![
                         CssDeclaration {
                             property: "transform".to_string(),
                             value: CssValue::Raw("translateX(-100%)".to_string()),
+                            span: CssSpan::default(),
                         },
                     ],
+                    span: CssSpan::default(),
                 },
                 CssKeyframeRule {
-                    selector: CssKeyframeSelector::Percentage(50.0),
+                    selectors: vec![CssKeyframeSelector::Percentage(50.0)],
                     declarations: vec![
                         CssDeclaration {
                             property: "transform".to_string(),
                             value: CssValue::Raw("translateX(-50%)".to_string()),
+                            span: CssSpan::default(),
                         },
                     ],
+                    span: CssSpan::default(),
                 },
                 CssKeyframeRule {
-                    selector: CssKeyframeSelector::Percentage(100.0),
+                    selectors: vec![CssKeyframeSelector::Percentage(100.0)],
                     declarations: vec![
                         CssDeclaration {
                             property: "transform".to_string(),
                             value: CssValue::Raw("translateX(0)".to_string()),
+                            span: CssSpan::default(),
                         },
                     ],
+                    span: CssSpan::default(),
                 },
             ],
+            span: CssSpan::default(),
         };
 
         gen.generate_keyframes(&keyframes);
-        let output = gen.css_output;
+        let output = gen.stylesheet.to_css();
 
         // Should contain scoped keyframe name
         assert!(output.contains("@keyframes Slider_slideIn_"));
@@ -1229,36 +1499,43 @@ mod tests {
             name: "pulse".to_string(),
             frames: vec![
                 CssKeyframeRule {
-                    selector: CssKeyframeSelector::From,
+                    selectors: vec![CssKeyframeSelector::From],
                     declarations: vec![
                         CssDeclaration {
                             property: "opacity".to_string(),
                             value: CssValue::Raw("1".to_string()),
+                            span: CssSpan::default(),
                         },
                         CssDeclaration {
                             property: "transform".to_string(),
                             value: CssValue::Raw("scale(1)".to_string()),
+                            span: CssSpan::default(),
                         },
                     ],
+                    span: CssSpan::default(),
                 },
                 CssKeyframeRule {
-                    selector: CssKeyframeSelector::To,
+                    selectors: vec![CssKeyframeSelector::To],
                     declarations: vec![
                         CssDeclaration {
                             property: "opacity".to_string(),
                             value: CssValue::Raw("0.8".to_string()),
+                            span: CssSpan::default(),
                         },
                         CssDeclaration {
                             property: "transform".to_string(),
                             value: CssValue::Raw("scale(1.05)".to_string()),
+                            span: CssSpan::default(),
                         },
                     ],
+                    span: CssSpan::default(),
                 },
             ],
+            span: CssSpan::default(),
         };
 
         gen.generate_keyframes(&keyframes);
-        let output = gen.css_output;
+        let output = gen.stylesheet.to_css();
 
         // Should contain all declarations
         assert!(output.contains("opacity: 1;"));
@@ -1266,4 +1543,62 @@ mod tests {
         assert!(output.contains("opacity: 0.8;"));
         assert!(output.contains("transform: scale(1.05);"));
     }
+
+    #[test]
+    fn test_animation_refers_to_scoped_keyframes() {
+        let css = CssExpression {
+            items: vec![
+                CssItem::Rule(CssRule {
+                    selector: CssSelector::Class("dot".to_string()),
+                    declarations: vec![
+                        CssDeclaration {
+                            property: "animation".to_string(),
+                            value: CssValue::Raw("pulse 1s ease-in-out infinite, spin 2s linear".to_string()),
+                            span: CssSpan::default(),
+                        },
+                        CssDeclaration {
+                            property: "transition".to_string(),
+                            value: CssValue::Raw("pulse 1s".to_string()),
+                            span: CssSpan::default(),
+                        },
+                    ],
+                    nested_rules: vec![],
+                    media_queries: vec![],
+                    container_queries: vec![],
+                    span: CssSpan::default(),
+                }),
+                CssItem::Keyframes(CssKeyframes {
+                    name: "pulse".to_string(),
+                    frames: vec![CssKeyframeRule {
+                        selectors: vec![CssKeyframeSelector::Percentage(0.0), CssKeyframeSelector::Percentage(100.0)],
+                        declarations: vec![
+                            CssDeclaration {
+                                property: "opacity".to_string(),
+                                value: CssValue::Raw("1".to_string()),
+                                span: CssSpan::default(),
+                            },
+                        ],
+                        span: CssSpan::default(),
+                    }],
+                    span: CssSpan::default(),
+                }),
+            ],
+            imports: vec![],
+            font_faces: vec![],
+            pages: vec![],
+            unknown_at_rules: vec![],
+        };
+
+        let mut gen = CssGenerator::new("Loader".to_string());
+        let output = gen.generate(&css);
+        let pulse = gen.get_keyframes_map()["pulse"].clone();
+
+        // The rule comes before the keyframes but still gets the scoped name
+        assert!(output.contains(&format!("animation: {} 1s ease-in-out infinite, spin 2s linear;", pulse)));
+        // Only animation properties name keyframes
+        assert!(output.contains("transition: pulse 1s;"));
+        // Frames with several selectors keep them together
+        assert!(output.contains(&format!("@keyframes {}", pulse)));
+        assert!(output.contains("0%, 100% {"));
+    }
 }
@@ -0,0 +1,653 @@
+// CSS Lint - Checks over the collected stylesheet
+//
+// Runs on the final CssStylesheet (after plugins), so it sees every
+// component's rules in the order they are emitted. Rules can opt out of a
+// lint with `@allow(<lint>)` on the component or function that declares them.
+
+use crate::ast::{CssDeclaration, CssExpression, CssItem, CssRule, InlineStyle};
+use crate::css_ast::{CssNode, CssSpan, CssStyleRule, CssStylesheet};
+use crate::diagnostics::{did_you_mean, suggest_similar, Diagnostic, Severity, SourceLocation};
+use std::collections::HashSet;
+use std::fmt;
+
+/// Later rule loses to an earlier, more specific rule for the same property
+pub const CSS_SPECIFICITY_LINT: &str = "css_specificity";
+
+/// A declaration sets a property that is not part of CSS
+pub const CSS_UNKNOWN_PROPERTY_LINT: &str = "unknown_css_property";
+
+/// A css! selector names a class around its subject that no rule in the block styles
+pub const CSS_UNDECLARED_CLASS_LINT: &str = "undeclared_css_class";
+
+/// Selector specificity as (ids, classes/attributes/pseudo-classes, elements/pseudo-elements)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Specificity(pub u32, pub u32, pub u32);
+
+impl fmt::Display for Specificity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({},{},{})", self.0, self.1, self.2)
+    }
+}
+
+/// One selector of a (possibly comma-separated) selector list
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectorInfo {
+    pub text: String,
+    pub specificity: Specificity,
+    /// Classes of the last compound selector: the element the rule styles
+    pub final_classes: Vec<String>,
+    /// Pseudo-element of the last compound (`placeholder` in
+    /// `.input::placeholder`), which styles a different box than the element
+    pub pseudo_element: Option<String>,
+}
+
+/// Run every stylesheet lint
+pub fn lint_stylesheet(stylesheet: &CssStylesheet) -> Vec<Diagnostic> {
+    check_specificity(stylesheet)
+}
+
+/// Warn when a rule that overrides an earlier one (same element, same
+/// property, later in the stylesheet) has lower specificity and so never wins.
+/// Rules under different @media/@container/@supports conditions apply at
+/// different times, so they are only compared within the same condition.
+pub fn check_specificity(stylesheet: &CssStylesheet) -> Vec<Diagnostic> {
+    let mut rules = Vec::new();
+    collect_rules(&stylesheet.nodes, "", &mut rules);
+
+    let analyzed: Vec<(String, &CssStyleRule, Vec<SelectorInfo>)> = rules
+        .into_iter()
+        .map(|(condition, rule)| (condition, rule, analyze_selector_list(&rule.selector)))
+        .collect();
+
+    let mut diagnostics = Vec::new();
+    for (later_index, (later_condition, later, later_selectors)) in analyzed.iter().enumerate() {
+        if allows(later, CSS_SPECIFICITY_LINT) {
+            continue;
+        }
+        for (earlier_condition, earlier, earlier_selectors) in &analyzed[..later_index] {
+            if earlier_condition != later_condition || allows(earlier, CSS_SPECIFICITY_LINT) {
+                continue;
+            }
+            let Some(property) = shared_property(earlier, later) else {
+                continue;
+            };
+            let losing = later_selectors.iter().find_map(|l| {
+                earlier_selectors
+                    .iter()
+                    .find(|e| l.specificity < e.specificity && targets_same_element(e, l))
+                    .map(|e| (l, e))
+            });
+            if let Some((later_selector, earlier_selector)) = losing {
+                diagnostics.push(specificity_warning(
+                    later,
+                    later_selector,
+                    earlier,
+                    earlier_selector,
+                    property,
+                ));
+            }
+        }
+    }
+    diagnostics
+}
+
+/// Warn about properties in an inline `style` attribute that are not CSS
+pub fn check_inline_style(style: &InlineStyle) -> Vec<Diagnostic> {
+    style
+        .declarations
+        .iter()
+        .filter(|decl| !is_known_property(&decl.property))
+        .map(|decl| {
+            let location = SourceLocation {
+                file: String::new(),
+                line: decl.line,
+                column: decl.column,
+                length: decl.property.len(),
+            };
+            unknown_property_warning(&decl.property, location, " in style attribute")
+        })
+        .collect()
+}
+
+/// Warn about declarations in a css! block that set a property that is not
+/// CSS, like `colr`, which browsers would silently drop. `@font-face`
+/// descriptors aren't properties, and aren't checked.
+pub fn check_unknown_properties(css: &CssExpression) -> Vec<Diagnostic> {
+    fn walk<'a>(rule: &'a CssRule, declarations: &mut Vec<&'a CssDeclaration>) {
+        declarations.extend(&rule.declarations);
+        for media in &rule.media_queries {
+            declarations.extend(&media.declarations);
+        }
+        for container in &rule.container_queries {
+            declarations.extend(&container.declarations);
+        }
+        for nested in rule.nested_rules.iter().chain(rule.media_queries.iter().flat_map(|media| &media.rules)) {
+            walk(nested, declarations);
+        }
+    }
+
+    let mut declarations = Vec::new();
+    let rules = css.items.iter()
+        .flat_map(|item| match item {
+            CssItem::Rule(rule) => std::slice::from_ref(rule),
+            CssItem::Media(media) => media.rules.as_slice(),
+            CssItem::Supports(supports) => supports.rules.as_slice(),
+            CssItem::Keyframes(_) => &[],
+        });
+    for rule in rules {
+        walk(rule, &mut declarations);
+    }
+    declarations.extend(css.keyframes().flat_map(|keyframes| &keyframes.frames).flat_map(|frame| &frame.declarations));
+    // In source order, whichever rule or at-rule each is in
+    declarations.sort_by_key(|decl| (decl.span.line, decl.span.column));
+
+    declarations
+        .into_iter()
+        .filter(|decl| !is_known_property(&decl.property))
+        .map(|decl| {
+            let location = SourceLocation { length: decl.property.len(), ..span_location(decl.span) };
+            unknown_property_warning(&decl.property, location, "")
+        })
+        .collect()
+}
+
+/// Turn an unknown property warning into an error, for `--strict-css`
+/// builds. Returns whether `diagnostic` was one.
+pub fn deny_unknown_property(diagnostic: &mut Diagnostic) -> bool {
+    let unknown_property = diagnostic.code.as_deref() == Some("W007");
+    if unknown_property {
+        diagnostic.severity = Severity::Error;
+    }
+    unknown_property
+}
+
+fn unknown_property_warning(property: &str, location: SourceLocation, context: &str) -> Diagnostic {
+    let mut diagnostic = Diagnostic::warning(format!("unknown CSS property `{}`{}", property, context))
+        .at(location.clone())
+        .with_code("W007")
+        .with_note(format!("silence with `@allow({})` on the component", CSS_UNKNOWN_PROPERTY_LINT));
+    if let Some(similar) = suggest_similar(property, CSS_PROPERTIES.iter().copied()) {
+        diagnostic = diagnostic.with_code_suggestion(did_you_mean(location, similar));
+    }
+    diagnostic
+}
+
+/// Warn about at-rules css! doesn't support, whose blocks the parser skipped
+pub fn check_unknown_at_rules(css: &CssExpression) -> Vec<Diagnostic> {
+    css.unknown_at_rules
+        .iter()
+        .map(|rule| {
+            let location = SourceLocation { length: rule.name.len() + 1, ..span_location(rule.span) };
+            let message = if rule.name == "page" {
+                "`@page` in css! is only supported at the top of the block; this one is skipped".to_string()
+            } else {
+                format!("unknown at-rule `@{}` in css!; its block is skipped", rule.name)
+            };
+            Diagnostic::warning(message)
+                .at(location)
+                .with_code("W015")
+                .with_note("css! supports @media, @container, @supports, @keyframes, @font-face, @import and @page")
+        })
+        .collect()
+}
+
+/// Warn about classes a css! selector names only around its subject, like
+/// `.cardd` in `.cardd .title`, that no rule of the block styles: they're
+/// scoped to the block, so nothing else can declare them. Each class is
+/// reported once, where it's first named.
+pub fn check_undeclared_classes(css: &CssExpression) -> Vec<Diagnostic> {
+    let classes = css.classes();
+    let declared: HashSet<&str> = classes.iter().filter(|class| class.subject).map(|class| class.name.as_str()).collect();
+    let mut reported = HashSet::new();
+    classes
+        .iter()
+        .filter(|class| !declared.contains(class.name.as_str()) && reported.insert(class.name.as_str()))
+        .map(|class| {
+            let mut diagnostic = Diagnostic::warning(format!("class `.{}` is not declared in this css! block", class.name))
+                .at(SourceLocation { length: class.name.len() + 1, ..span_location(class.span) })
+                .with_code("W016");
+            let mut similar: Vec<&str> = declared.iter().copied().collect();
+            similar.sort_unstable();
+            if let Some(similar) = suggest_similar(&class.name, similar) {
+                diagnostic = diagnostic.with_note(format!("did you mean `.{}`?", similar));
+            }
+            diagnostic.with_note(format!(
+                "give it a rule of its own, or write `:global(.{})` for a class defined elsewhere",
+                class.name
+            ))
+        })
+        .collect()
+}
+
+/// Whether `name` is a standard CSS property. Custom properties (`--*`) and
+/// vendor-prefixed names (`-webkit-*`, ...) are always accepted.
+pub fn is_known_property(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    name.starts_with("--")
+        || ["-webkit-", "-moz-", "-ms-", "-o-"].iter().any(|prefix| name.starts_with(prefix))
+        || CSS_PROPERTIES.binary_search(&name.as_str()).is_ok()
+}
+
+/// Standard CSS property names, sorted
+const CSS_PROPERTIES: &[&str] = &[
+    "accent-color", "align-content", "align-items", "align-self", "all", "animation",
+    "animation-composition", "animation-delay", "animation-direction", "animation-duration",
+    "animation-fill-mode", "animation-iteration-count", "animation-name", "animation-play-state",
+    "animation-timing-function", "appearance", "aspect-ratio", "backdrop-filter",
+    "backface-visibility", "background", "background-attachment", "background-blend-mode",
+    "background-clip", "background-color", "background-image", "background-origin",
+    "background-position", "background-position-x", "background-position-y", "background-repeat",
+    "background-size", "block-size", "border", "border-block", "border-block-color",
+    "border-block-end", "border-block-end-color", "border-block-end-style", "border-block-end-width",
+    "border-block-start", "border-block-start-color", "border-block-start-style",
+    "border-block-start-width", "border-block-style", "border-block-width", "border-bottom",
+    "border-bottom-color", "border-bottom-left-radius", "border-bottom-right-radius",
+    "border-bottom-style", "border-bottom-width", "border-collapse", "border-color",
+    "border-end-end-radius", "border-end-start-radius", "border-image", "border-image-outset",
+    "border-image-repeat", "border-image-slice", "border-image-source", "border-image-width",
+    "border-inline", "border-inline-color", "border-inline-end", "border-inline-end-color",
+    "border-inline-end-style", "border-inline-end-width", "border-inline-start",
+    "border-inline-start-color", "border-inline-start-style", "border-inline-start-width",
+    "border-inline-style", "border-inline-width", "border-left", "border-left-color",
+    "border-left-style", "border-left-width", "border-radius", "border-right", "border-right-color",
+    "border-right-style", "border-right-width", "border-spacing", "border-start-end-radius",
+    "border-start-start-radius", "border-style", "border-top", "border-top-color",
+    "border-top-left-radius", "border-top-right-radius", "border-top-style", "border-top-width",
+    "border-width", "bottom", "box-decoration-break", "box-shadow", "box-sizing", "break-after",
+    "break-before", "break-inside", "caption-side", "caret-color", "clear", "clip", "clip-path",
+    "color", "color-scheme", "column-count", "column-fill", "column-gap", "column-rule",
+    "column-rule-color", "column-rule-style", "column-rule-width", "column-span", "column-width",
+    "columns", "contain", "contain-intrinsic-size", "container", "container-name",
+    "container-type", "content", "content-visibility", "counter-increment", "counter-reset",
+    "counter-set", "cursor", "direction", "display", "empty-cells", "fill", "fill-opacity",
+    "filter", "flex", "flex-basis", "flex-direction", "flex-flow", "flex-grow", "flex-shrink",
+    "flex-wrap", "float", "font", "font-family", "font-feature-settings", "font-kerning",
+    "font-optical-sizing", "font-size", "font-size-adjust", "font-stretch", "font-style",
+    "font-synthesis", "font-variant", "font-variant-caps", "font-variant-east-asian",
+    "font-variant-ligatures", "font-variant-numeric", "font-variation-settings", "font-weight",
+    "gap", "grid", "grid-area", "grid-auto-columns", "grid-auto-flow", "grid-auto-rows",
+    "grid-column", "grid-column-end", "grid-column-start", "grid-row", "grid-row-end",
+    "grid-row-start", "grid-template", "grid-template-areas", "grid-template-columns",
+    "grid-template-rows", "hanging-punctuation", "height", "hyphens", "image-rendering", "inline-size",
+    "inset", "inset-block", "inset-block-end", "inset-block-start", "inset-inline",
+    "inset-inline-end", "inset-inline-start", "isolation", "justify-content", "justify-items",
+    "justify-self", "left", "letter-spacing", "line-break", "line-clamp", "line-height",
+    "list-style", "list-style-image", "list-style-position", "list-style-type", "margin",
+    "margin-block", "margin-block-end", "margin-block-start", "margin-bottom", "margin-inline",
+    "margin-inline-end", "margin-inline-start", "margin-left", "margin-right", "margin-top", "marker",
+    "mask", "mask-clip", "mask-composite", "mask-image", "mask-mode", "mask-origin",
+    "mask-position", "mask-repeat", "mask-size", "mask-type", "max-block-size", "max-height",
+    "max-inline-size", "max-width", "min-block-size", "min-height", "min-inline-size", "min-width",
+    "mix-blend-mode", "object-fit", "object-position", "offset", "offset-anchor", "offset-distance",
+    "offset-path", "offset-rotate", "opacity", "order", "orphans", "outline", "outline-color",
+    "outline-offset", "outline-style", "outline-width", "overflow", "overflow-anchor",
+    "overflow-wrap", "overflow-x", "overflow-y", "overscroll-behavior", "overscroll-behavior-x",
+    "overscroll-behavior-y", "padding", "padding-block", "padding-block-end", "padding-block-start",
+    "padding-bottom", "padding-inline", "padding-inline-end", "padding-inline-start",
+    "padding-left", "padding-right", "padding-top", "page-break-after", "page-break-before",
+    "page-break-inside", "paint-order", "perspective", "perspective-origin", "place-content",
+    "place-items", "place-self", "pointer-events", "position", "print-color-adjust", "quotes",
+    "resize", "right", "rotate", "row-gap", "scale", "scroll-behavior", "scroll-margin",
+    "scroll-margin-block", "scroll-margin-bottom", "scroll-margin-inline", "scroll-margin-left",
+    "scroll-margin-right", "scroll-margin-top", "scroll-padding", "scroll-padding-block",
+    "scroll-padding-bottom", "scroll-padding-inline", "scroll-padding-left",
+    "scroll-padding-right", "scroll-padding-top", "scroll-snap-align", "scroll-snap-stop",
+    "scroll-snap-type", "scrollbar-color", "scrollbar-gutter", "scrollbar-width",
+    "shape-image-threshold", "shape-margin", "shape-outside", "stroke", "stroke-dasharray",
+    "stroke-dashoffset", "stroke-linecap", "stroke-linejoin", "stroke-opacity", "stroke-width",
+    "tab-size", "table-layout", "text-align", "text-align-last", "text-combine-upright",
+    "text-decoration", "text-decoration-color", "text-decoration-line", "text-decoration-skip-ink",
+    "text-decoration-style", "text-decoration-thickness", "text-emphasis", "text-indent",
+    "text-justify", "text-orientation", "text-overflow", "text-rendering", "text-shadow",
+    "text-transform", "text-underline-offset", "text-underline-position", "text-wrap", "top",
+    "touch-action", "transform", "transform-box", "transform-origin", "transform-style",
+    "transition", "transition-behavior", "transition-delay", "transition-duration",
+    "transition-property", "transition-timing-function", "translate", "unicode-bidi",
+    "user-select", "vertical-align", "view-transition-name", "visibility", "white-space",
+    "white-space-collapse", "widows", "width", "will-change", "word-break", "word-spacing",
+    "word-wrap", "writing-mode", "z-index", "zoom",
+];
+
+fn specificity_warning(
+    later: &CssStyleRule,
+    later_selector: &SelectorInfo,
+    earlier: &CssStyleRule,
+    earlier_selector: &SelectorInfo,
+    property: &str,
+) -> Diagnostic {
+    Diagnostic::warning(format!(
+        "`{}` sets `{}` after `{}`, but its specificity {} is lower than {} so the earlier rule wins",
+        later_selector.text, property, earlier_selector.text, later_selector.specificity, earlier_selector.specificity
+    ))
+    .at(span_location(later.span))
+    .with_code("W006")
+    .with_label(
+        span_location(earlier.span),
+        format!("earlier rule with specificity {}", earlier_selector.specificity),
+    )
+    .with_suggestion("make the override at least as specific, or move it into the same selector")
+    .with_note(format!("silence with `@allow({})` on the component", CSS_SPECIFICITY_LINT))
+}
+
+fn span_location(span: CssSpan) -> SourceLocation {
+    SourceLocation {
+        file: String::new(),
+        line: span.line,
+        column: span.column,
+        length: 1,
+    }
+}
+
+fn allows(rule: &CssStyleRule, lint: &str) -> bool {
+    rule.allow.iter().any(|name| name == lint)
+}
+
+/// Flatten style rules in document order, each with the conditional
+/// at-rules around it, looking inside @media and @container but not
+/// @keyframes (whose "selectors" are percentages)
+fn collect_rules<'a>(nodes: &'a [CssNode], condition: &str, rules: &mut Vec<(String, &'a CssStyleRule)>) {
+    for node in nodes {
+        match node {
+            CssNode::Rule(rule) => rules.push((condition.to_string(), rule)),
+            CssNode::AtRule(at_rule) if at_rule.name != "keyframes" => {
+                let nested = format!("{}@{} {};", condition, at_rule.name, at_rule.prelude);
+                collect_rules(&at_rule.children, &nested, rules);
+            }
+            CssNode::AtRule(_) => {}
+        }
+    }
+}
+
+fn shared_property<'a>(earlier: &CssStyleRule, later: &'a CssStyleRule) -> Option<&'a str> {
+    later
+        .declarations
+        .iter()
+        .map(|decl| decl.property.as_str())
+        .find(|property| earlier.declarations.iter().any(|decl| decl.property == *property))
+}
+
+/// Whether two selectors style the same element: the same classes on the
+/// last compound, and the same pseudo-element (or neither). `.button.active`
+/// is a different element than `.button`, and `.button::placeholder` a
+/// different box.
+fn targets_same_element(a: &SelectorInfo, b: &SelectorInfo) -> bool {
+    let classes = |info: &SelectorInfo| {
+        let mut classes = info.final_classes.clone();
+        classes.sort();
+        classes.dedup();
+        classes
+    };
+    !a.final_classes.is_empty() && classes(a) == classes(b) && a.pseudo_element == b.pseudo_element
+}
+
+/// Split a selector list on top-level commas and analyze each selector
+pub fn analyze_selector_list(selector_list: &str) -> Vec<SelectorInfo> {
+    split_top_level(selector_list, ',')
+        .into_iter()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(analyze_selector)
+        .collect()
+}
+
+/// Compute the specificity and final classes of a single complex selector
+pub fn analyze_selector(selector: &str) -> SelectorInfo {
+    let chars: Vec<char> = selector.chars().collect();
+    let mut specificity = Specificity::default();
+    let mut final_classes = Vec::new();
+    let mut pseudo_element = None;
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '#' => {
+                let (_, next) = read_ident(&chars, i + 1);
+                specificity.0 += 1;
+                i = next;
+            }
+            '.' => {
+                let (class, next) = read_ident(&chars, i + 1);
+                specificity.1 += 1;
+                final_classes.push(class);
+                i = next;
+            }
+            '[' => {
+                specificity.1 += 1;
+                i = skip_group(&chars, i, '[', ']');
+            }
+            ':' if chars.get(i + 1) == Some(&':') => {
+                let (name, next) = read_ident(&chars, i + 2);
+                specificity.2 += 1;
+                pseudo_element = Some(name);
+                i = skip_arguments(&chars, next);
+            }
+            ':' => {
+                let (name, next) = read_ident(&chars, i + 1);
+                let end = skip_arguments(&chars, next);
+                match name.as_str() {
+                    // Functional pseudo-classes that take the most specific argument
+                    "not" | "is" | "has" if end > next + 1 => {
+                        let arguments: String = chars[next + 1..end - 1].iter().collect();
+                        let most_specific = analyze_selector_list(&arguments)
+                            .into_iter()
+                            .map(|info| info.specificity)
+                            .max()
+                            .unwrap_or_default();
+                        specificity.0 += most_specific.0;
+                        specificity.1 += most_specific.1;
+                        specificity.2 += most_specific.2;
+                    }
+                    "where" => {}
+                    // CSS2 pseudo-elements written with a single colon
+                    "before" | "after" | "first-line" | "first-letter" => {
+                        specificity.2 += 1;
+                        pseudo_element = Some(name);
+                    }
+                    _ => specificity.1 += 1,
+                }
+                i = end;
+            }
+            ' ' | '\t' | '\n' | '>' | '+' | '~' => {
+                // A combinator starts a new compound; only the last one's classes count
+                final_classes.clear();
+                pseudo_element = None;
+                i += 1;
+            }
+            c if c.is_alphabetic() => {
+                let (_, next) = read_ident(&chars, i);
+                specificity.2 += 1;
+                i = next;
+            }
+            _ => i += 1,
+        }
+    }
+
+    SelectorInfo {
+        text: selector.to_string(),
+        specificity,
+        final_classes,
+        pseudo_element,
+    }
+}
+
+fn read_ident(chars: &[char], start: usize) -> (String, usize) {
+    let mut end = start;
+    while end < chars.len() && (chars[end].is_alphanumeric() || chars[end] == '-' || chars[end] == '_') {
+        end += 1;
+    }
+    (chars[start..end].iter().collect(), end)
+}
+
+/// Skip a parenthesized argument list starting at `start`, if there is one
+fn skip_arguments(chars: &[char], start: usize) -> usize {
+    if chars.get(start) == Some(&'(') {
+        skip_group(chars, start, '(', ')')
+    } else {
+        start
+    }
+}
+
+/// Return the index just past the delimiter that closes the group at `start`
+fn skip_group(chars: &[char], start: usize, open: char, close: char) -> usize {
+    let mut depth = 0;
+    for (offset, &c) in chars[start..].iter().enumerate() {
+        if c == open {
+            depth += 1;
+        } else if c == close {
+            depth -= 1;
+            if depth == 0 {
+                return start + offset + 1;
+            }
+        }
+    }
+    chars.len()
+}
+
+fn split_top_level(input: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (index, c) in input.char_indices() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            c if c == separator && depth == 0 => {
+                parts.push(&input[start..index]);
+                start = index + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&input[start..]);
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::css_ast::CssAtRule;
+
+    fn rule(selector: &str, line: usize, property: &str, value: &str) -> CssNode {
+        let mut rule = CssStyleRule::new(selector, CssSpan { line, column: 9 });
+        rule.declare(property, value);
+        CssNode::Rule(rule)
+    }
+
+    #[test]
+    fn test_specificity() {
+        let spec = |s: &str| analyze_selector(s).specificity;
+        assert_eq!(spec(".a"), Specificity(0, 1, 0));
+        assert_eq!(spec("#main .a:hover"), Specificity(1, 2, 0));
+        assert_eq!(spec("ul > li.a::before"), Specificity(0, 1, 3));
+        assert_eq!(spec("a[href]:not(.b, #c)"), Specificity(1, 1, 1));
+        assert_eq!(spec(".a:where(#x)"), Specificity(0, 1, 0));
+        assert_eq!(analyze_selector(".list .item.active").final_classes, vec!["item", "active"]);
+        assert_eq!(analyze_selector_list(".a, .b .c").len(), 2);
+    }
+
+    #[test]
+    fn test_losing_override_flagged() {
+        let sheet = CssStylesheet {
+            nodes: vec![
+                rule(".Card_list_1 .Card_item_2", 3, "color", "red"),
+                rule(".Card_item_2", 7, "color", "blue"),
+            ],
+        };
+        let warnings = check_specificity(&sheet);
+        assert_eq!(warnings.len(), 1);
+        let warning = &warnings[0];
+        assert_eq!(warning.code.as_deref(), Some("W006"));
+        assert!(warning.message.contains("(0,1,0)"), "{}", warning.message);
+        assert!(warning.message.contains("(0,2,0)"), "{}", warning.message);
+        assert_eq!(warning.location.as_ref().unwrap().line, 7);
+        assert_eq!(warning.labels[0].location.line, 3);
+    }
+
+    #[test]
+    fn test_equal_specificity_override_not_flagged() {
+        let sheet = CssStylesheet {
+            nodes: vec![
+                rule(".Card_item_2", 3, "color", "red"),
+                CssNode::AtRule(CssAtRule {
+                    name: "media".to_string(),
+                    prelude: "(min-width: 768px)".to_string(),
+                    declarations: Vec::new(),
+                    children: vec![rule(".Card_item_2", 6, "color", "blue")],
+                    span: CssSpan { line: 6, column: 9 },
+                }),
+                rule(".Card_other_3", 9, "color", "green"),
+            ],
+        };
+        assert!(check_specificity(&sheet).is_empty());
+    }
+
+    #[test]
+    fn test_different_element_not_flagged() {
+        // A pseudo-element, or an extra class, styles a different element
+        let sheet = CssStylesheet {
+            nodes: vec![
+                rule(".Form_button_1.Form_active_2", 3, "color", "red"),
+                rule(".Form_button_1::placeholder", 6, "color", "blue"),
+                rule(".Form_button_1", 9, "color", "green"),
+                rule(".Form_list_3 .Form_button_1::placeholder", 12, "color", "gray"),
+            ],
+        };
+        assert!(check_specificity(&sheet).is_empty(), "{:?}", check_specificity(&sheet));
+        assert_eq!(analyze_selector(".a::placeholder").pseudo_element.as_deref(), Some("placeholder"));
+        assert_eq!(analyze_selector(".a:before").pseudo_element.as_deref(), Some("before"));
+
+        // The same pseudo-element of the same element is compared
+        let sheet = CssStylesheet {
+            nodes: vec![
+                rule(".Form_list_3 .Form_button_1::placeholder", 3, "color", "gray"),
+                rule(".Form_button_1::placeholder", 6, "color", "blue"),
+            ],
+        };
+        assert_eq!(check_specificity(&sheet).len(), 1);
+    }
+
+    #[test]
+    fn test_override_under_other_condition_not_flagged() {
+        let media = |line: usize, children: Vec<CssNode>| {
+            CssNode::AtRule(CssAtRule {
+                name: "media".to_string(),
+                prelude: "(min-width: 768px)".to_string(),
+                declarations: Vec::new(),
+                children,
+                span: CssSpan { line, column: 9 },
+            })
+        };
+        let sheet = CssStylesheet {
+            nodes: vec![
+                rule(".Card_list_1 .Card_item_2", 3, "color", "red"),
+                media(6, vec![rule(".Card_item_2", 7, "color", "blue")]),
+            ],
+        };
+        assert!(check_specificity(&sheet).is_empty());
+
+        // Within the same condition, the override still loses
+        let sheet = CssStylesheet {
+            nodes: vec![
+                media(3, vec![rule(".Card_list_1 .Card_item_2", 4, "color", "red")]),
+                media(6, vec![rule(".Card_item_2", 7, "color", "blue")]),
+            ],
+        };
+        let warnings = check_specificity(&sheet);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].location.as_ref().unwrap().line, 7);
+    }
+
+    #[test]
+    fn test_allowed_rule_not_flagged() {
+        let mut override_rule = CssStyleRule::new(".Card_item_2", CssSpan { line: 7, column: 9 });
+        override_rule.declare("color", "blue");
+        override_rule.allow.push(CSS_SPECIFICITY_LINT.to_string());
+        let sheet = CssStylesheet {
+            nodes: vec![
+                rule(".Card_list_1 .Card_item_2", 3, "color", "red"),
+                CssNode::Rule(override_rule),
+            ],
+        };
+        assert!(check_specificity(&sheet).is_empty());
+    }
+}
@@ -0,0 +1,421 @@
+// CSS Minifier - Compact stylesheet output for release builds
+//
+// Works on the CssStylesheet css! blocks lower to, not on rendered text:
+// - Drops source comments, comments in values, and empty rules
+// - Collapses whitespace in selectors, at-rule preludes and values
+// - Leaves out the semicolon after each block's last declaration
+// - Shortens zero lengths (`0px` to `0`) and hex colors (`#aabbcc` to `#abc`)
+
+use crate::css_ast::{CssAtRule, CssNode, CssStyleDeclaration, CssStyleRule, CssStylesheet};
+use crate::js_minifier::MinifyStats;
+
+/// Units a zero length can drop. Not `%`, and not times or angles, where
+/// a bare `0` isn't always valid.
+const LENGTH_UNITS: &[&str] = &[
+    "px", "em", "rem", "ex", "ch", "vw", "vh", "vmin", "vmax", "cm", "mm", "in", "pt", "pc", "q",
+];
+
+/// Math functions, where a zero length has to keep its unit
+const MATH_FUNCTIONS: &[&str] = &["calc", "min", "max", "clamp"];
+
+pub struct CssMinifier;
+
+impl Default for CssMinifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CssMinifier {
+    pub fn new() -> Self {
+        CssMinifier
+    }
+
+    /// Minifies a stylesheet to CSS text
+    pub fn minify(&self, stylesheet: &CssStylesheet) -> String {
+        let mut output = String::new();
+        for node in &self.minify_stylesheet(stylesheet).nodes {
+            render_node(node, &mut output);
+        }
+        output
+    }
+
+    /// The stylesheet with every selector, prelude and value in its
+    /// shortest form, and without empty rules or source comments
+    pub fn minify_stylesheet(&self, stylesheet: &CssStylesheet) -> CssStylesheet {
+        CssStylesheet { nodes: minify_nodes(&stylesheet.nodes) }
+    }
+
+    /// Returns statistics about minification
+    pub fn stats(&self, original: &str, minified: &str) -> MinifyStats {
+        MinifyStats {
+            original_size: original.len(),
+            minified_size: minified.len(),
+            reduction_bytes: original.len().saturating_sub(minified.len()),
+            reduction_percent: if !original.is_empty() {
+                (original.len().saturating_sub(minified.len()) as f64 / original.len() as f64) * 100.0
+            } else {
+                0.0
+            },
+        }
+    }
+}
+
+fn minify_nodes(nodes: &[CssNode]) -> Vec<CssNode> {
+    nodes
+        .iter()
+        .filter_map(|node| match node {
+            CssNode::Rule(rule) => {
+                let declarations = minify_declarations(&rule.declarations);
+                (!declarations.is_empty()).then(|| {
+                    CssNode::Rule(CssStyleRule {
+                        selector: minify_selector(&rule.selector),
+                        declarations,
+                        span: rule.span,
+                        allow: rule.allow.clone(),
+                        sources: Vec::new(),
+                    })
+                })
+            }
+            CssNode::AtRule(at_rule) => {
+                let declarations = minify_declarations(&at_rule.declarations);
+                let children = minify_nodes(&at_rule.children);
+                // A block that minified away to nothing goes too; `@import` never had one
+                let had_block = !at_rule.declarations.is_empty() || !at_rule.children.is_empty();
+                (!had_block || !declarations.is_empty() || !children.is_empty()).then(|| {
+                    CssNode::AtRule(CssAtRule {
+                        name: at_rule.name.clone(),
+                        prelude: minify_prelude(&at_rule.prelude),
+                        declarations,
+                        children,
+                        span: at_rule.span,
+                    })
+                })
+            }
+        })
+        .collect()
+}
+
+fn minify_declarations(declarations: &[CssStyleDeclaration]) -> Vec<CssStyleDeclaration> {
+    declarations
+        .iter()
+        .map(|decl| CssStyleDeclaration {
+            property: decl.property.clone(),
+            value: minify_value(&decl.property, &decl.value),
+        })
+        .filter(|decl| !decl.value.is_empty())
+        .collect()
+}
+
+fn render_node(node: &CssNode, output: &mut String) {
+    match node {
+        CssNode::Rule(rule) => {
+            output.push_str(&rule.selector);
+            output.push('{');
+            render_declarations(&rule.declarations, output);
+            output.push('}');
+        }
+        CssNode::AtRule(at_rule) => {
+            output.push('@');
+            output.push_str(&at_rule.name);
+            if !at_rule.prelude.is_empty() {
+                output.push(' ');
+                output.push_str(&at_rule.prelude);
+            }
+            if at_rule.declarations.is_empty() && at_rule.children.is_empty() {
+                output.push(';');
+            } else {
+                output.push('{');
+                render_declarations(&at_rule.declarations, output);
+                if !at_rule.declarations.is_empty() && !at_rule.children.is_empty() {
+                    output.push(';');
+                }
+                for child in &at_rule.children {
+                    render_node(child, output);
+                }
+                output.push('}');
+            }
+        }
+    }
+}
+
+fn render_declarations(declarations: &[CssStyleDeclaration], output: &mut String) {
+    for (index, decl) in declarations.iter().enumerate() {
+        if index > 0 {
+            output.push(';');
+        }
+        output.push_str(&decl.property);
+        output.push(':');
+        output.push_str(&decl.value);
+    }
+}
+
+/// `.card  >  .title, .list` to `.card>.title,.list`. Whitespace inside
+/// brackets and parentheses, as in `:nth-child(2n + 1)`, is only collapsed.
+fn minify_selector(selector: &str) -> String {
+    let mut output = String::new();
+    let mut depth = 0usize;
+    let mut quote = None;
+    let mut pending_space = false;
+
+    for ch in selector.chars() {
+        if let Some(open) = quote {
+            output.push(ch);
+            if ch == open {
+                quote = None;
+            }
+            continue;
+        }
+        if ch.is_whitespace() {
+            pending_space = !output.is_empty();
+            continue;
+        }
+        let combinator = depth == 0 && matches!(ch, '>' | '+' | '~' | ',');
+        if pending_space && !combinator && !(depth == 0 && output.ends_with(['>', '+', '~', ','])) {
+            output.push(' ');
+        }
+        pending_space = false;
+        match ch {
+            '"' | '\'' => quote = Some(ch),
+            '[' | '(' => depth += 1,
+            ']' | ')' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        output.push(ch);
+    }
+    output
+}
+
+/// `screen  and (min-width: 768px)` to `screen and (min-width:768px)`
+fn minify_prelude(prelude: &str) -> String {
+    let mut output = String::new();
+    let mut quote = None;
+    let mut pending_space = false;
+
+    for ch in prelude.chars() {
+        if let Some(open) = quote {
+            output.push(ch);
+            if ch == open {
+                quote = None;
+            }
+            continue;
+        }
+        if ch.is_whitespace() {
+            pending_space = !output.is_empty();
+            continue;
+        }
+        if pending_space && !matches!(ch, ',' | ')') && !output.ends_with([',', ':', '(']) {
+            output.push(' ');
+        }
+        pending_space = false;
+        if ch == '"' || ch == '\'' {
+            quote = Some(ch);
+        }
+        output.push(ch);
+    }
+    output
+}
+
+/// A declaration value in its shortest form. Strings and `url(...)` are
+/// copied as written; custom properties only lose comments and extra
+/// whitespace, since where they end up is unknown.
+fn minify_value(property: &str, value: &str) -> String {
+    let custom = property.starts_with("--");
+    // A unitless zero after two flex factors would be read as the basis, but
+    // not on its own
+    let keep_units = custom || property == "flex";
+
+    let chars: Vec<char> = value.chars().collect();
+    let mut output = String::new();
+    let mut functions: Vec<String> = Vec::new();
+    let mut pending_space = false;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let ch = chars[i];
+
+        // Comments
+        if ch == '/' && chars.get(i + 1) == Some(&'*') {
+            let end = (i + 2..chars.len().saturating_sub(1))
+                .find(|&j| chars[j] == '*' && chars[j + 1] == '/')
+                .map_or(chars.len(), |j| j + 2);
+            pending_space = pending_space || !output.is_empty();
+            i = end;
+            continue;
+        }
+        if ch.is_whitespace() {
+            pending_space = !output.is_empty();
+            i += 1;
+            continue;
+        }
+        if pending_space && !matches!(ch, ',' | ')' | '/') && !output.ends_with([',', '(', '/']) {
+            output.push(' ');
+        }
+        pending_space = false;
+
+        match ch {
+            '"' | '\'' => {
+                let end = string_end(&chars, i);
+                output.extend(&chars[i..end]);
+                i = end;
+            }
+            '(' => {
+                let name = function_name(&output);
+                output.push('(');
+                i += 1;
+                let quoted = chars[i..].iter().find(|ch| !ch.is_whitespace()).is_some_and(|ch| matches!(ch, '"' | '\''));
+                if name == "url" && !quoted {
+                    // Unquoted urls can hold anything but `)`
+                    let end = (i..chars.len()).find(|&j| chars[j] == ')').unwrap_or(chars.len());
+                    output.push_str(chars[i..end].iter().collect::<String>().trim());
+                    i = end;
+                } else {
+                    functions.push(name);
+                }
+            }
+            ')' => {
+                functions.pop();
+                output.push(')');
+                i += 1;
+            }
+            ',' | '/' => {
+                output.push(ch);
+                i += 1;
+            }
+            _ => {
+                let end = (i..chars.len())
+                    .find(|&j| chars[j].is_whitespace() || matches!(chars[j], ',' | '/' | '(' | ')' | '"' | '\''))
+                    .unwrap_or(chars.len());
+                let word: String = chars[i..end].iter().collect();
+                let in_math = functions.iter().any(|name| MATH_FUNCTIONS.contains(&name.as_str()));
+                if custom {
+                    output.push_str(&word);
+                } else if !keep_units && !in_math && is_zero_length(&word) {
+                    output.push('0');
+                } else {
+                    output.push_str(&short_hex(&word).unwrap_or(word));
+                }
+                i = end;
+            }
+        }
+    }
+    output
+}
+
+/// Index just past the string starting at `start`
+fn string_end(chars: &[char], start: usize) -> usize {
+    let quote = chars[start];
+    let mut j = start + 1;
+    while j < chars.len() {
+        match chars[j] {
+            '\\' => j += 2,
+            ch if ch == quote => return j + 1,
+            _ => j += 1,
+        }
+    }
+    chars.len()
+}
+
+/// The function name just written before a `(`, lowercased
+fn function_name(output: &str) -> String {
+    let start = output
+        .rfind(|ch: char| !(ch.is_alphanumeric() || ch == '-' || ch == '_'))
+        .map_or(0, |index| index + 1);
+    output[start..].to_ascii_lowercase()
+}
+
+/// `0px`, `0.0em`: a zero with a length unit
+fn is_zero_length(word: &str) -> bool {
+    let digits = word.trim_end_matches(|ch: char| ch.is_ascii_alphabetic());
+    let unit = &word[digits.len()..];
+    let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    !whole.is_empty()
+        && whole.chars().all(|ch| ch == '0')
+        && fraction.chars().all(|ch| ch == '0')
+        && LENGTH_UNITS.contains(&unit.to_ascii_lowercase().as_str())
+}
+
+/// `#aabbcc` as `#abc`, when each pair repeats a digit
+fn short_hex(word: &str) -> Option<String> {
+    let hex = word.strip_prefix('#')?;
+    let digits: Vec<char> = hex.chars().collect();
+    if digits.len() != 6 || !digits.iter().all(|ch| ch.is_ascii_hexdigit()) {
+        return None;
+    }
+    let pairs: Vec<char> = digits.chunks(2).filter(|pair| pair[0].eq_ignore_ascii_case(&pair[1])).map(|pair| pair[0]).collect();
+    (pairs.len() == 3).then(|| format!("#{}", pairs.iter().collect::<String>()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::css_ast::CssSpan;
+
+    #[test]
+    fn test_minify_values() {
+        let cases = [
+            ("margin", "0px  auto", "0 auto"),
+            ("padding", "0.0em 8px 0px 0", "0 8px 0 0"),
+            ("color", "#AABBCC", "#ABC"),
+            ("background", "#aabbcd url( \"/a #ffffff.png\" ) no-repeat", "#aabbcd url(\"/a #ffffff.png\") no-repeat"),
+            ("width", "calc(100% - 0px)", "calc(100% - 0px)"),
+            ("font", "600 14px / 1.5 \"Inter\",  sans-serif", "600 14px/1.5 \"Inter\",sans-serif"),
+            ("transition", "opacity 0.2s ease-in-out, transform 0s", "opacity 0.2s ease-in-out,transform 0s"),
+            ("flex", "1 1 0px", "1 1 0px"),
+            ("--gap", "0px /* spacing */", "0px"),
+            ("color", "red /* brand */ !important", "red !important"),
+            ("box-shadow", "0px 2px 4px rgba( 0, 0, 0, 0.1 )", "0 2px 4px rgba(0,0,0,0.1)"),
+        ];
+        for (property, value, expected) in cases {
+            assert_eq!(minify_value(property, value), expected, "{}: {}", property, value);
+        }
+    }
+
+    #[test]
+    fn test_minify_selectors_and_preludes() {
+        assert_eq!(minify_selector(".a  >  .b, .c ~ .d + .e"), ".a>.b,.c~.d+.e");
+        assert_eq!(minify_selector(".list li:nth-child(2n + 1)"), ".list li:nth-child(2n + 1)");
+        assert_eq!(minify_selector("[data-label=\"a > b\"] .x"), "[data-label=\"a > b\"] .x");
+        assert_eq!(minify_prelude("screen  and (min-width: 768px), print"), "screen and (min-width:768px),print");
+    }
+
+    #[test]
+    fn test_minify_renders_compact_stylesheet() {
+        let mut rule = CssStyleRule::new(".App_card_abc123", CssSpan::default());
+        rule.declare("padding", "0px 16px");
+        rule.declare("color", "#ffffff");
+        rule.sources = vec!["app.jnc:3".to_string()];
+        let empty = CssStyleRule::new(".App_empty_abc123", CssSpan::default());
+
+        let mut sheet = CssStylesheet::new();
+        sheet.push(CssNode::AtRule(CssAtRule {
+            name: "import".to_string(),
+            prelude: "url(\"/theme.css\")".to_string(),
+            declarations: Vec::new(),
+            children: Vec::new(),
+            span: CssSpan::default(),
+        }));
+        sheet.push(CssNode::Rule(rule.clone()));
+        sheet.push(CssNode::AtRule(CssAtRule {
+            name: "media".to_string(),
+            prelude: "(min-width: 768px)".to_string(),
+            declarations: Vec::new(),
+            children: vec![CssNode::Rule(rule), CssNode::Rule(empty.clone())],
+            span: CssSpan::default(),
+        }));
+        sheet.push(CssNode::AtRule(CssAtRule {
+            name: "supports".to_string(),
+            prelude: "(display: grid)".to_string(),
+            declarations: Vec::new(),
+            children: vec![CssNode::Rule(empty)],
+            span: CssSpan::default(),
+        }));
+
+        assert_eq!(
+            CssMinifier::new().minify(&sheet),
+            "@import url(\"/theme.css\");\
+             .App_card_abc123{padding:0 16px;color:#fff}\
+             @media (min-width:768px){.App_card_abc123{padding:0 16px;color:#fff}}"
+        );
+    }
+}
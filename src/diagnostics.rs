@@ -1,7 +1,9 @@
 // Enhanced Diagnostics and Error Reporting for Jounce
 // Beautiful, helpful error messages with colors, context, and suggestions
 
+use crate::ast::Identifier;
 use crate::token::Token;
+use serde::{Deserialize, Serialize};
 
 /// ANSI color codes for terminal output
 pub mod colors {
@@ -22,7 +24,7 @@ pub mod colors {
 }
 
 /// Severity level of a diagnostic
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Severity {
     Error,
     Warning,
@@ -51,7 +53,7 @@ impl Severity {
 }
 
 /// Source location for error reporting
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SourceLocation {
     pub file: String,
     pub line: usize,
@@ -69,6 +71,34 @@ impl SourceLocation {
         }
     }
 
+    /// The span of a parsed name; the file is filled in when displayed
+    pub fn from_identifier(ident: &Identifier) -> Self {
+        SourceLocation {
+            file: String::new(),
+            line: ident.position.line,
+            column: ident.position.column,
+            length: ident.value.chars().count(),
+        }
+    }
+
+    /// LSP range (0-based) covering this location
+    pub fn to_lsp_range(&self) -> crate::lsp::Range {
+        crate::lsp::Range {
+            start: crate::lsp::Position {
+                line: if self.line > 0 { self.line - 1 } else { 0 },
+                character: if self.column > 0 { self.column - 1 } else { 0 },
+            },
+            end: crate::lsp::Position {
+                line: if self.line > 0 { self.line - 1 } else { 0 },
+                character: if self.column > 0 {
+                    self.column - 1 + self.length
+                } else {
+                    self.length
+                },
+            },
+        }
+    }
+
     pub fn unknown() -> Self {
         SourceLocation {
             file: "<unknown>".to_string(),
@@ -79,13 +109,48 @@ impl SourceLocation {
     }
 }
 
+/// A secondary location attached to a diagnostic, e.g. the declaration
+/// an expected type came from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Label {
+    pub location: SourceLocation,
+    pub message: String,
+}
+
+/// A machine-applicable fix: replace the text at `location` with
+/// `replacement`. Editors offer these as quick fixes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CodeSuggestion {
+    pub message: String,
+    pub location: SourceLocation,
+    pub replacement: String,
+}
+
+impl CodeSuggestion {
+    /// The source line the fix touches, with the fix applied
+    pub fn apply_to_line(&self, source: &str) -> Option<String> {
+        let line = source.lines().nth(self.location.line.checked_sub(1)?)?;
+        let chars: Vec<char> = line.chars().collect();
+        let start = self.location.column.checked_sub(1)?;
+        let end = start + self.location.length;
+        if end > chars.len() {
+            return None;
+        }
+        let before: String = chars[..start].iter().collect();
+        let after: String = chars[end..].iter().collect();
+        Some(format!("{}{}{}", before, self.replacement, after))
+    }
+}
+
 /// A diagnostic message (error, warning, info, or help)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Diagnostic {
     pub severity: Severity,
     pub message: String,
     pub location: Option<SourceLocation>,
+    pub labels: Vec<Label>,
     pub suggestions: Vec<String>,
+    pub code_suggestions: Vec<CodeSuggestion>,
     pub notes: Vec<String>,
     pub code: Option<String>, // Error code like E001
 }
@@ -96,7 +161,9 @@ impl Diagnostic {
             severity: Severity::Error,
             message: message.into(),
             location: None,
+            labels: Vec::new(),
             suggestions: Vec::new(),
+            code_suggestions: Vec::new(),
             notes: Vec::new(),
             code: None,
         }
@@ -107,7 +174,9 @@ impl Diagnostic {
             severity: Severity::Warning,
             message: message.into(),
             location: None,
+            labels: Vec::new(),
             suggestions: Vec::new(),
+            code_suggestions: Vec::new(),
             notes: Vec::new(),
             code: None,
         }
@@ -118,11 +187,23 @@ impl Diagnostic {
         self
     }
 
+    /// Point at a related location with a secondary label
+    pub fn with_label(mut self, location: SourceLocation, message: impl Into<String>) -> Self {
+        self.labels.push(Label { location, message: message.into() });
+        self
+    }
+
     pub fn with_suggestion(mut self, suggestion: impl Into<String>) -> Self {
         self.suggestions.push(suggestion.into());
         self
     }
 
+    /// Attach a fix that tools can apply as-is
+    pub fn with_code_suggestion(mut self, suggestion: CodeSuggestion) -> Self {
+        self.code_suggestions.push(suggestion);
+        self
+    }
+
     pub fn with_note(mut self, note: impl Into<String>) -> Self {
         self.notes.push(note.into());
         self
@@ -130,22 +211,7 @@ impl Diagnostic {
 
     /// Get LSP-compatible range from source location
     pub fn to_lsp_range(&self) -> Option<crate::lsp::Range> {
-        self.location.as_ref().map(|loc| {
-            crate::lsp::Range {
-                start: crate::lsp::Position {
-                    line: if loc.line > 0 { loc.line - 1 } else { 0 },
-                    character: if loc.column > 0 { loc.column - 1 } else { 0 },
-                },
-                end: crate::lsp::Position {
-                    line: if loc.line > 0 { loc.line - 1 } else { 0 },
-                    character: if loc.column > 0 {
-                        loc.column - 1 + loc.length
-                    } else {
-                        loc.length
-                    },
-                },
-            }
-        })
+        self.location.as_ref().map(SourceLocation::to_lsp_range)
     }
 
     pub fn with_code(mut self, code: impl Into<String>) -> Self {
@@ -184,6 +250,26 @@ impl Diagnostic {
             }
         }
 
+        // Secondary labels
+        for label in &self.labels {
+            output.push_str(&format!(
+                "  {cyan}note:{reset} {message}\n  {dim}-->{reset} {file}:{line}:{column}\n",
+                cyan = colors::CYAN,
+                dim = colors::DIM,
+                reset = colors::RESET,
+                message = label.message,
+                file = label.location.file,
+                line = label.location.line,
+                column = label.location.column,
+            ));
+
+            // The source is the primary location's file
+            let same_file = self.location.as_ref().is_none_or(|loc| loc.file == label.location.file);
+            if let (Some(source), true) = (source_code, same_file) {
+                output.push_str(&self.format_source_snippet(source, &label.location));
+            }
+        }
+
         // Error code
         if let Some(code) = &self.code {
             output.push_str(&format!(
@@ -205,6 +291,27 @@ impl Diagnostic {
             ));
         }
 
+        // Fixes, shown as the patched line
+        for suggestion in &self.code_suggestions {
+            output.push_str(&format!(
+                "  {green}{bold}help:{reset} {message}\n",
+                green = colors::GREEN,
+                bold = colors::BOLD,
+                reset = colors::RESET,
+                message = suggestion.message,
+            ));
+            if let Some(line) = source_code.and_then(|source| suggestion.apply_to_line(source)) {
+                output.push_str(&format!(
+                    "   {cyan}{:>width$} |{reset} {}\n",
+                    suggestion.location.line,
+                    line,
+                    cyan = colors::CYAN,
+                    reset = colors::RESET,
+                    width = suggestion.location.line.to_string().len(),
+                ));
+            }
+        }
+
         // Notes
         for note in &self.notes {
             output.push_str(&format!(
@@ -642,24 +749,75 @@ pub fn levenshtein_distance(s1: &str, s2: &str) -> usize {
 
 /// Find the most similar string from a list (for "did you mean?" suggestions)
 pub fn find_similar(target: &str, candidates: &[&str]) -> Option<String> {
-    let mut best_match: Option<(String, usize)> = None;
-
-    for candidate in candidates {
-        let distance = levenshtein_distance(target, candidate);
+    suggest_similar(target, candidates.iter().copied()).map(str::to_string)
+}
 
-        // Only consider matches within reasonable distance (< 3 edits)
-        if distance < 3 {
-            if let Some((_, best_distance)) = best_match {
-                if distance < best_distance {
-                    best_match = Some((candidate.to_string(), distance));
-                }
-            } else {
-                best_match = Some((candidate.to_string(), distance));
+/// Damerau-Levenshtein distance (optimal string alignment): like Levenshtein,
+/// but swapping two adjacent characters counts as one edit
+pub fn damerau_levenshtein_distance(s1: &str, s2: &str) -> usize {
+    let a: Vec<char> = s1.chars().collect();
+    let b: Vec<char> = s2.chars().collect();
+    let mut matrix = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in matrix.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in matrix[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (matrix[i - 1][j] + 1)
+                .min(matrix[i][j - 1] + 1)
+                .min(matrix[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(matrix[i - 2][j - 2] + 1);
             }
+            matrix[i][j] = best;
         }
     }
 
-    best_match.map(|(name, _)| name)
+    matrix[a.len()][b.len()]
+}
+
+/// The single best "did you mean?" candidate for a misspelled name.
+///
+/// A candidate that differs only in case always wins. Otherwise the closest
+/// candidate is picked if it is at most a third of the name's length away
+/// (at least one edit, and never the whole name); ties go to the candidate
+/// listed first.
+pub fn suggest_similar<'a>(target: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let length = target.chars().count();
+    let threshold = (length / 3).max(1);
+    let lowercase = target.to_lowercase();
+
+    let mut best: Option<(bool, usize, &'a str)> = None;
+    for candidate in candidates {
+        if candidate == target {
+            continue;
+        }
+        let case_only = candidate.to_lowercase() == lowercase;
+        let distance = damerau_levenshtein_distance(target, candidate);
+        if !case_only && (distance > threshold || distance >= length || distance >= candidate.chars().count()) {
+            continue;
+        }
+        let rank = (!case_only, distance);
+        if best.is_none_or(|(other_case, other_distance, _)| rank < (other_case, other_distance)) {
+            best = Some((!case_only, distance, candidate));
+        }
+    }
+
+    best.map(|(_, _, candidate)| candidate)
+}
+
+/// The structured fix for a misspelled name at `location`
+pub fn did_you_mean(location: SourceLocation, replacement: &str) -> CodeSuggestion {
+    CodeSuggestion {
+        message: format!("did you mean `{}`?", replacement),
+        location,
+        replacement: replacement.to_string(),
+    }
 }
 
 #[cfg(test)]
@@ -701,6 +859,23 @@ mod tests {
         assert_eq!(find_similar("xyz", &candidates), None); // Too different
     }
 
+    #[test]
+    fn test_suggest_similar() {
+        // Adjacent swaps are a single edit
+        assert_eq!(damerau_levenshtein_distance("recieve", "receive"), 1);
+        assert_eq!(suggest_similar("recieve", ["receive", "relieve"]), Some("receive"));
+
+        // A case-only difference beats an equally close candidate listed first
+        assert_eq!(suggest_similar("username", ["usernames", "userName"]), Some("userName"));
+        assert_eq!(suggest_similar("backgroundcolor", ["background", "backgroundColor"]), Some("backgroundColor"));
+
+        // The allowed distance grows with the name, and never covers all of it
+        assert_eq!(suggest_similar("lenght", ["length"]), Some("length"));
+        assert_eq!(suggest_similar("ab", ["cd", "xb"]), Some("xb"));
+        assert_eq!(suggest_similar("x", ["y", "z"]), None);
+        assert_eq!(suggest_similar("count", ["amount", "total"]), None);
+    }
+
     #[test]
     fn test_diagnostic_collector() {
         let mut collector = DiagnosticCollector::new();
@@ -869,4 +1044,23 @@ mod tests {
         assert_eq!(diag.code, Some("W005".to_string()));
         assert!(diag.suggestions[0].contains("new_api"));
     }
+
+    #[test]
+    fn test_code_suggestion_shows_patched_line() {
+        let source = "fn f() {\nif x = 5 {\n}\n}";
+        let location = SourceLocation { file: "a.jnc".to_string(), line: 2, column: 6, length: 1 };
+        let suggestion = CodeSuggestion {
+            message: "did you mean `==`?".to_string(),
+            location: location.clone(),
+            replacement: "==".to_string(),
+        };
+        assert_eq!(suggestion.apply_to_line(source).as_deref(), Some("if x == 5 {"));
+
+        let output = Diagnostic::error("Assignment used as a condition")
+            .at(location)
+            .with_code_suggestion(suggestion)
+            .display(Some(source));
+        assert!(output.contains("did you mean `==`?"));
+        assert!(output.contains("if x == 5 {"));
+    }
 }
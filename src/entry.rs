@@ -0,0 +1,401 @@
+// Entry Point - Finds and validates the function or component an app starts from
+//
+// By convention the entry is `fn main()`, or `component App()` when there is
+// no `main`. A project can name another one in jounce.toml:
+//
+//     [build.entry]
+//     name = "Dashboard"
+//     kind = "component"   # optional: inferred from the name's case
+//
+// Candidates are collected from the root file and every module it loads, so a
+// second `main` anywhere in the module graph is reported instead of silently
+// picking one.
+//
+// A file with no component, and nothing named like an entry, is a library
+// (a module other files import): it has no page to start, so it's built
+// without an entry rather than rejected.
+
+use crate::ast::{Program, Statement, TypeExpression};
+use crate::errors::CompileError;
+use crate::module_loader::{ExportedSymbol, Module};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Return types the runtime accepts from an entry besides unit
+const ELEMENT_TYPES: &[&str] = &["Element", "JSX", "VNode"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryKind {
+    Function,
+    Component,
+}
+
+impl EntryKind {
+    /// How `[build.entry] kind` spells it
+    fn noun(self) -> &'static str {
+        match self {
+            EntryKind::Function => "function",
+            EntryKind::Component => "component",
+        }
+    }
+}
+
+impl fmt::Display for EntryKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EntryKind::Function => write!(f, "fn"),
+            EntryKind::Component => write!(f, "component"),
+        }
+    }
+}
+
+/// `[build.entry]` in jounce.toml
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntryConfig {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<EntryKind>,
+}
+
+impl EntryConfig {
+    pub fn new(name: &str, kind: EntryKind) -> Self {
+        EntryConfig { name: name.to_string(), kind: Some(kind) }
+    }
+
+    /// The configured kind, or the one the name suggests (`App` is a component)
+    pub fn kind(&self) -> EntryKind {
+        self.kind.unwrap_or_else(|| {
+            if self.name.chars().next().is_some_and(|c| c.is_uppercase()) {
+                EntryKind::Component
+            } else {
+                EntryKind::Function
+            }
+        })
+    }
+
+    fn describe(&self) -> String {
+        format!("`{} {}`", self.kind(), self.name)
+    }
+}
+
+/// A top-level function or component that could start the app
+#[derive(Debug, Clone)]
+pub struct EntryCandidate {
+    pub name: String,
+    pub kind: EntryKind,
+    pub file: PathBuf,
+    pub parameters: Vec<String>,
+    pub return_type: Option<TypeExpression>,
+}
+
+/// The validated entry the client bundle starts from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryPoint {
+    pub name: String,
+    pub kind: EntryKind,
+    pub file: PathBuf,
+    /// Components, and functions returning an Element, are mounted; others are called
+    pub mounts: bool,
+}
+
+/// Functions and components defined in a program
+pub fn candidates_in(program: &Program, file: &Path) -> Vec<EntryCandidate> {
+    program.statements.iter()
+        .filter_map(|stmt| match stmt {
+            Statement::Function(func) => Some(EntryCandidate {
+                name: func.name.value.clone(),
+                kind: EntryKind::Function,
+                file: file.to_path_buf(),
+                parameters: func.parameters.iter().map(|p| p.name.value.clone()).collect(),
+                return_type: func.return_type.clone(),
+            }),
+            Statement::Component(comp) => Some(EntryCandidate {
+                name: comp.name.value.clone(),
+                kind: EntryKind::Component,
+                file: file.to_path_buf(),
+                parameters: comp.parameters.iter().map(|p| p.name.value.clone()).collect(),
+                return_type: None,
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Functions and components a loaded module defines itself (not re-exports)
+pub fn candidates_in_module(module: &Module) -> Vec<EntryCandidate> {
+    let mut candidates: Vec<EntryCandidate> = module.exports.iter()
+        .filter(|(name, _)| module.origins.get(*name).is_none_or(|origin| *origin == module.file_path))
+        .filter_map(|(_, symbol)| match symbol {
+            ExportedSymbol::Function(func) => Some(EntryCandidate {
+                name: func.name.value.clone(),
+                kind: EntryKind::Function,
+                file: module.file_path.clone(),
+                parameters: func.parameters.iter().map(|p| p.name.value.clone()).collect(),
+                return_type: func.return_type.clone(),
+            }),
+            ExportedSymbol::Component(comp) => Some(EntryCandidate {
+                name: comp.name.value.clone(),
+                kind: EntryKind::Component,
+                file: module.file_path.clone(),
+                parameters: comp.parameters.iter().map(|p| p.name.value.clone()).collect(),
+                return_type: None,
+            }),
+            _ => None,
+        })
+        .collect();
+    candidates.sort_by(|a, b| a.name.cmp(&b.name));
+    candidates
+}
+
+/// Whether the candidates make a library rather than an app: no entry is
+/// configured, there's no component to mount, and no function is named
+/// like an entry (`fn Main` is more likely a misspelled entry than a helper)
+pub fn is_library(candidates: &[EntryCandidate], config: Option<&EntryConfig>) -> bool {
+    config.is_none()
+        && candidates.iter().all(|c| {
+            c.kind == EntryKind::Function && !["main", "App"].iter().any(|name| c.name.eq_ignore_ascii_case(name))
+        })
+}
+
+/// Pick the single entry among the candidates and check its signature.
+/// Without a config, `fn main` wins and `component App` is the fallback.
+pub fn select_entry(candidates: &[EntryCandidate], config: Option<&EntryConfig>) -> Result<EntryPoint, CompileError> {
+    let wanted = match config {
+        Some(config) => vec![config.clone()],
+        None => vec![
+            EntryConfig::new("main", EntryKind::Function),
+            EntryConfig::new("App", EntryKind::Component),
+        ],
+    };
+
+    for spec in &wanted {
+        // The same module can be loaded under two paths (`./a` and `../src/a`)
+        let mut files = HashSet::new();
+        let matches: Vec<&EntryCandidate> = candidates.iter()
+            .filter(|c| c.name == spec.name && c.kind == spec.kind())
+            .filter(|c| files.insert(c.file.clone()))
+            .collect();
+
+        match matches.as_slice() {
+            [] => continue,
+            [entry] => {
+                check_signature(entry)?;
+                return Ok(EntryPoint {
+                    name: entry.name.clone(),
+                    kind: entry.kind,
+                    file: entry.file.clone(),
+                    mounts: entry.kind == EntryKind::Component || entry.return_type.as_ref().is_some_and(is_element_type),
+                });
+            }
+            several => return Err(multiple_entries_error(spec, several)),
+        }
+    }
+
+    Err(missing_entry_error(candidates, &wanted, config.is_some()))
+}
+
+fn check_signature(entry: &EntryCandidate) -> Result<(), CompileError> {
+    if !entry.parameters.is_empty() {
+        return Err(CompileError::Generic(format!(
+            "Entry point `{} {}` in {} takes parameters ({}), but the runtime starts the app without arguments; remove them or read the values inside the entry",
+            entry.kind,
+            entry.name,
+            entry.file.display(),
+            entry.parameters.join(", ")
+        )));
+    }
+
+    let returns_ok = match &entry.return_type {
+        None => true,
+        Some(TypeExpression::Tuple(items)) => items.is_empty(),
+        Some(ty) => is_element_type(ty),
+    };
+    if !returns_ok {
+        return Err(CompileError::Generic(format!(
+            "Entry point `{} {}` in {} returns {}, but the runtime expects unit or an Element; return nothing, or the root element to mount",
+            entry.kind,
+            entry.name,
+            entry.file.display(),
+            type_name(entry.return_type.as_ref().unwrap())
+        )));
+    }
+    Ok(())
+}
+
+fn is_element_type(ty: &TypeExpression) -> bool {
+    matches!(ty, TypeExpression::Named(ident) if ELEMENT_TYPES.contains(&ident.value.as_str()))
+}
+
+fn multiple_entries_error(spec: &EntryConfig, matches: &[&EntryCandidate]) -> CompileError {
+    let files: Vec<String> = matches.iter().map(|c| c.file.display().to_string()).collect();
+    CompileError::Generic(format!(
+        "Multiple entry points: {} is defined in {}; keep exactly one, or rename the others",
+        spec.describe(),
+        files.join(" and ")
+    ))
+}
+
+fn missing_entry_error(candidates: &[EntryCandidate], wanted: &[EntryConfig], configured: bool) -> CompileError {
+    let expected: Vec<String> = wanted.iter().map(|spec| format!("`{} {}()`", spec.kind(), spec.name)).collect();
+    let source = if configured { " (from jounce.toml)" } else { "" };
+    let mut message = format!("No entry point found: expected {}{}", expected.join(" or "), source);
+
+    // The entry's name on the other kind (`fn App`): a rename wouldn't help
+    let wrong_kind = candidates.iter().find_map(|c| wanted.iter().find(|spec| c.name == spec.name).map(|spec| (c, spec.kind())));
+    if let Some((candidate, expected)) = wrong_kind {
+        message.push_str(&format!(
+            "; `{} {}` in {} is a {}, not a {}: declare it as `{} {}`, or set `[build.entry] kind = \"{}\"` in jounce.toml",
+            candidate.kind,
+            candidate.name,
+            candidate.file.display(),
+            candidate.kind.noun(),
+            expected.noun(),
+            expected,
+            candidate.name,
+            candidate.kind.noun()
+        ));
+        return CompileError::Generic(message);
+    }
+
+    // A near miss in name (`fn Main`, `component app`), else the first component
+    let near_miss = candidates.iter().find(|c| wanted.iter().any(|spec| c.name.eq_ignore_ascii_case(&spec.name)));
+    let hint = match near_miss.or_else(|| candidates.iter().find(|c| c.kind == EntryKind::Component)) {
+        Some(candidate) => format!(
+            "; did you mean `{} {}` in {}? Rename it, or set `[build.entry] name = \"{}\"` in jounce.toml",
+            candidate.kind,
+            candidate.name,
+            candidate.file.display(),
+            candidate.name
+        ),
+        None => "; add `fn main() { ... }` or a `component App()` to start the app".to_string(),
+    };
+    message.push_str(&hint);
+    CompileError::Generic(message)
+}
+
+/// A type as it is spelled in source
+pub(crate) fn type_name(ty: &TypeExpression) -> String {
+    match ty {
+        TypeExpression::Named(ident) => ident.value.clone(),
+        TypeExpression::Generic(ident, args) => {
+            format!("{}<{}>", ident.value, args.iter().map(type_name).collect::<Vec<_>>().join(", "))
+        }
+        TypeExpression::Tuple(items) => format!("({})", items.iter().map(type_name).collect::<Vec<_>>().join(", ")),
+        TypeExpression::Reference(inner) => format!("&{}", type_name(inner)),
+        TypeExpression::MutableReference(inner) => format!("&mut {}", type_name(inner)),
+        TypeExpression::Slice(inner) => format!("[{}]", type_name(inner)),
+        TypeExpression::SizedArray(inner, size) => format!("[{}; {}]", type_name(inner), size),
+        TypeExpression::Function(params, ret) => format!(
+            "fn({}) -> {}",
+            params.iter().map(type_name).collect::<Vec<_>>().join(", "),
+            type_name(ret)
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::module_loader::ModuleLoader;
+    use crate::parser::Parser;
+    use std::fs;
+
+    fn parse(source: &str) -> Program {
+        let mut lexer = Lexer::new(source.to_string());
+        Parser::new(&mut lexer).parse_program().unwrap()
+    }
+
+    fn message(err: CompileError) -> String {
+        match err {
+            CompileError::Generic(message) => message,
+            other => panic!("Expected generic error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_main_preferred_over_app() {
+        let program = parse("component App() { return <div>hi</div>; }\nfn main() { }");
+        let entry = select_entry(&candidates_in(&program, Path::new("main.jnc")), None).unwrap();
+        assert_eq!(entry.name, "main");
+        assert_eq!(entry.kind, EntryKind::Function);
+
+        let config = EntryConfig { name: "App".to_string(), kind: None };
+        let entry = select_entry(&candidates_in(&program, Path::new("main.jnc")), Some(&config)).unwrap();
+        assert_eq!(entry.kind, EntryKind::Component);
+    }
+
+    #[test]
+    fn test_library_has_no_entry() {
+        let library = parse("pub fn add(a: i32, b: i32) -> i32 { return a + b; }\nstruct Todo { title: String }");
+        assert!(is_library(&candidates_in(&library, Path::new("math.jnc")), None));
+        assert!(is_library(&[], None));
+
+        // A component is something to mount, so its file needs an entry
+        let app = parse("component Counter() { return <div>0</div>; }");
+        assert!(!is_library(&candidates_in(&app, Path::new("main.jnc")), None));
+
+        // So does a function that looks like a misnamed entry, or a configured entry
+        let misnamed = parse("fn Main() { }");
+        assert!(!is_library(&candidates_in(&misnamed, Path::new("main.jnc")), None));
+        let config = EntryConfig::new("start", EntryKind::Function);
+        assert!(!is_library(&candidates_in(&library, Path::new("math.jnc")), Some(&config)));
+    }
+
+    #[test]
+    fn test_missing_entry() {
+        let program = parse("component Counter() { return <div>0</div>; }\nfn helper() -> i32 { return 1; }");
+        let err = message(select_entry(&candidates_in(&program, Path::new("main.jnc")), None).unwrap_err());
+        assert!(err.starts_with("No entry point found: expected `fn main()` or `component App()`"), "{}", err);
+        assert!(err.contains("did you mean `component Counter` in main.jnc"), "{}", err);
+        assert!(err.contains("[build.entry] name = \"Counter\""), "{}", err);
+
+        let program = parse("fn App() -> JSX { return <div>hi</div>; }");
+        let err = message(select_entry(&candidates_in(&program, Path::new("main.jnc")), None).unwrap_err());
+        assert!(
+            err.contains("`fn App` in main.jnc is a function, not a component: declare it as `component App`, or set `[build.entry] kind = \"function\"`"),
+            "{}",
+            err
+        );
+        assert!(!err.contains("Rename it"), "{}", err);
+    }
+
+    #[test]
+    fn test_duplicate_entry_across_modules() {
+        let dir = std::env::temp_dir().join(format!("jounce_entry_dup_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("tools.jnc"), "fn helper() -> i32 { return 1; }\nfn main() { }\n").unwrap();
+        let main_path = dir.join("main.jnc");
+        let mut program = parse("use ./tools::{helper};\nfn main() { }");
+
+        let mut candidates = candidates_in(&program, &main_path);
+        let mut loader = ModuleLoader::new(&dir);
+        loader.set_current_file(&main_path);
+        loader.merge_imports(&mut program).unwrap();
+        candidates.extend(loader.modules().flat_map(candidates_in_module));
+
+        let err = message(select_entry(&candidates, None).unwrap_err());
+        fs::remove_dir_all(&dir).ok();
+        assert!(err.starts_with("Multiple entry points: `fn main` is defined in"), "{}", err);
+        assert!(err.contains("main.jnc and"), "{}", err);
+        assert!(err.contains("tools.jnc"), "{}", err);
+    }
+
+    #[test]
+    fn test_entry_signature() {
+        let program = parse("fn main(port: i32) { }");
+        let err = message(select_entry(&candidates_in(&program, Path::new("main.jnc")), None).unwrap_err());
+        assert!(err.contains("`fn main` in main.jnc takes parameters (port)"), "{}", err);
+
+        let program = parse("fn main() -> i32 { return 0; }");
+        let err = message(select_entry(&candidates_in(&program, Path::new("main.jnc")), None).unwrap_err());
+        assert!(err.contains("returns i32, but the runtime expects unit or an Element"), "{}", err);
+
+        let program = parse("fn main() -> Element { return <div>hi</div>; }");
+        let entry = select_entry(&candidates_in(&program, Path::new("main.jnc")), None).unwrap();
+        assert!(entry.mounts);
+    }
+}
@@ -1,5 +1,6 @@
 use std::fmt;
-use crate::diagnostics::{Diagnostic, DiagnosticBuilder, SourceLocation};
+use crate::diagnostics::{CodeSuggestion, Diagnostic, DiagnosticBuilder, Label, SourceLocation};
+use crate::lexer::LexError;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum CompileError {
@@ -7,12 +8,28 @@ pub enum CompileError {
     ParserError { message: String, line: usize, column: usize },
     BorrowError(String),
     Generic(String),
-    /// Error with source location for better diagnostics
+    /// Error with source location for better diagnostics, plus secondary
+    /// labels. Locations without a file are filled in with the file name
+    /// when displayed.
     WithLocation {
         message: String,
         location: SourceLocation,
         suggestion: Option<String>,
+        labels: Vec<Label>,
     },
+    /// An error that comes with a machine-applicable fix
+    Fixable {
+        error: Box<CompileError>,
+        fix: CodeSuggestion,
+    },
+    /// Type error with secondary labels pointing at related declarations.
+    /// Label locations are filled in with the file name when displayed.
+    TypeError {
+        message: String,
+        labels: Vec<Label>,
+    },
+    /// Several independent errors, reported together
+    Multiple(Vec<CompileError>),
 }
 
 impl CompileError {
@@ -24,16 +41,14 @@ impl CompileError {
                     .with_code("E0001")
             }
             CompileError::ParserError { message, line, column } => {
-                DiagnosticBuilder::syntax_error(
-                    "",
-                    message,
-                    SourceLocation {
+                Diagnostic::error(message.clone())
+                    .at(SourceLocation {
                         file: file.to_string(),
                         line: *line,
                         column: *column,
                         length: 1,
-                    },
-                )
+                    })
+                    .with_code("E004")
             }
             CompileError::BorrowError(msg) => {
                 DiagnosticBuilder::borrow_error(
@@ -49,14 +64,76 @@ impl CompileError {
             CompileError::Generic(msg) => {
                 Diagnostic::error(msg.clone())
             }
-            CompileError::WithLocation { message, location, suggestion } => {
+            CompileError::WithLocation { message, location, suggestion, labels } => {
+                let mut primary = location.clone();
+                if primary.file.is_empty() {
+                    primary.file = file.to_string();
+                }
                 let mut diag = Diagnostic::error(message.clone())
-                    .at(location.clone());
+                    .at(primary);
+                for label in labels {
+                    let mut location = label.location.clone();
+                    if location.file.is_empty() {
+                        location.file = file.to_string();
+                    }
+                    diag = diag.with_label(location, label.message.clone());
+                }
                 if let Some(sugg) = suggestion {
                     diag = diag.with_suggestion(sugg.clone());
                 }
                 diag
             }
+            CompileError::TypeError { message, labels } => {
+                let mut diag = Diagnostic::error(message.clone())
+                    .with_code("E001");
+                for label in labels {
+                    let mut location = label.location.clone();
+                    location.file = file.to_string();
+                    diag = diag.with_label(location, label.message.clone());
+                }
+                diag
+            }
+            CompileError::Fixable { error, fix } => {
+                let mut fix = fix.clone();
+                fix.location.file = file.to_string();
+                error.to_diagnostic(file).with_code_suggestion(fix)
+            }
+            CompileError::Multiple(errors) => {
+                let mut diag = errors
+                    .first()
+                    .map(|e| e.to_diagnostic(file))
+                    .unwrap_or_else(|| Diagnostic::error("Compilation failed"));
+                if errors.len() > 1 {
+                    diag = diag.with_note(format!("{} more error(s) not shown", errors.len() - 1));
+                }
+                diag
+            }
+        }
+    }
+
+    /// Attach a machine-applicable fix, if there is one
+    pub fn with_fix(self, fix: Option<CodeSuggestion>) -> Self {
+        match fix {
+            Some(fix) => CompileError::Fixable { error: Box::new(self), fix },
+            None => self,
+        }
+    }
+
+    /// Convert to one Diagnostic per reported error
+    pub fn to_diagnostics(&self, file: &str) -> Vec<Diagnostic> {
+        match self {
+            CompileError::Multiple(errors) => {
+                errors.iter().flat_map(|e| e.to_diagnostics(file)).collect()
+            }
+            _ => vec![self.to_diagnostic(file)],
+        }
+    }
+
+    /// The individual errors, flattening `Multiple`
+    pub fn errors(&self) -> Vec<&CompileError> {
+        match self {
+            CompileError::Multiple(errors) => errors.iter().flat_map(|e| e.errors()).collect(),
+            _ => vec![self],
         }
     }
 }
@@ -71,7 +148,29 @@ impl fmt::Display for CompileError {
             CompileError::BorrowError(msg) => write!(f, "Borrow Error: {}", msg),
             CompileError::Generic(msg) => write!(f, "Error: {}", msg),
             CompileError::WithLocation { message, .. } => write!(f, "Error: {}", message),
+            CompileError::Fixable { error, .. } => write!(f, "{}", error),
+            CompileError::TypeError { message, .. } => write!(f, "Type Error: {}", message),
+            CompileError::Multiple(errors) => {
+                for (i, error) in errors.iter().enumerate() {
+                    if i > 0 {
+                        writeln!(f)?;
+                    }
+                    write!(f, "{}", error)?;
+                }
+                Ok(())
+            }
         }
     }
 }
-impl std::error::Error for CompileError {}
\ No newline at end of file
+impl std::error::Error for CompileError {}
+
+impl From<LexError> for CompileError {
+    fn from(error: LexError) -> Self {
+        CompileError::WithLocation {
+            message: error.message,
+            location: SourceLocation { file: String::new(), line: error.line, column: error.column, length: 1 },
+            suggestion: None,
+            labels: Vec::new(),
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,76 @@
+// Escaping - The one set of rules for values interpolated into HTML and CSS
+//
+// Server rendering and CSS generation escape through `escape`; the JavaScript
+// runtimes apply the same rules (`escape` in runtime/shared-runtime.js).
+// Output is inert in its context: it cannot open a tag, leave an attribute
+// value, or end a CSS declaration. The only way to insert markup as-is is the
+// runtime's `unsafe_raw()` wrapper.
+
+/// Where an interpolated value ends up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EscapeMode {
+    /// Text content between tags
+    Text,
+    /// A double-quoted attribute value
+    Attribute,
+    /// A CSS declaration value, or the inside of a CSS string
+    CssValue,
+}
+
+/// Escape `input` for the given context
+pub fn escape(input: &str, mode: EscapeMode) -> String {
+    let mut out = String::with_capacity(input.len());
+    for ch in input.chars() {
+        match mode {
+            EscapeMode::Text | EscapeMode::Attribute => match ch {
+                '&' => out.push_str("&amp;"),
+                '<' => out.push_str("&lt;"),
+                '>' => out.push_str("&gt;"),
+                '"' => out.push_str("&quot;"),
+                '\'' => out.push_str("&#39;"),
+                // Attribute values would otherwise normalize these to spaces
+                '\n' | '\r' | '\t' if mode == EscapeMode::Attribute => {
+                    out.push_str(&format!("&#{};", ch as u32));
+                }
+                _ => out.push(ch),
+            },
+            EscapeMode::CssValue => match ch {
+                '\\' | '"' | '\'' | ';' | '{' | '}' | '<' | '>' | '`' => {
+                    out.push_str(&format!("\\{:x} ", ch as u32));
+                }
+                c if c.is_control() => out.push_str(&format!("\\{:x} ", c as u32)),
+                _ => out.push(ch),
+            },
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_script_in_text_is_inert() {
+        assert_eq!(
+            escape("<script>alert('x')</script>", EscapeMode::Text),
+            "&lt;script&gt;alert(&#39;x&#39;)&lt;/script&gt;"
+        );
+        assert_eq!(escape("Tom & Jerry", EscapeMode::Text), "Tom &amp; Jerry");
+    }
+
+    #[test]
+    fn test_attribute_value_cannot_close_quote() {
+        let escaped = escape("\" onmouseover=\"alert(1)\"><script>", EscapeMode::Attribute);
+        assert_eq!(escaped, "&quot; onmouseover=&quot;alert(1)&quot;&gt;&lt;script&gt;");
+        assert_eq!(escape("a\nb", EscapeMode::Attribute), "a&#10;b");
+    }
+
+    #[test]
+    fn test_css_value_cannot_end_declaration() {
+        let escaped = escape("red; } </style><script>alert(1)</script>", EscapeMode::CssValue);
+        assert_eq!(escaped, "red\\3b  \\7d  \\3c /style\\3e \\3c script\\3e alert(1)\\3c /script\\3e ");
+        assert_eq!(escape("10px solid #ccc", EscapeMode::CssValue), "10px solid #ccc");
+        assert_eq!(escape("a\"b", EscapeMode::CssValue), "a\\22 b");
+    }
+}
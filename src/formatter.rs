@@ -4,7 +4,8 @@
 /// Formats all language features including JSX, pattern matching, and async/await.
 
 use crate::ast::*;
-use crate::token::Token;
+use crate::html_entities;
+use crate::token::{Token, TokenKind};
 
 /// Formatting configuration
 #[derive(Debug, Clone)]
@@ -103,29 +104,70 @@ impl Formatter {
     // ==================== STATEMENT FORMATTERS ====================
 
     fn format_use_statement(&mut self, use_stmt: &UseStatement) {
+        if use_stmt.is_pub {
+            self.write("pub ");
+        }
         self.write("use ");
 
-        // Format path (e.g., raven_store)
-        let path_str: Vec<String> = use_stmt.path.iter()
-            .map(|id| id.value.clone())
-            .collect();
-        self.write(&path_str.join("::"));
+        // Format path (e.g., raven_store or ./ui::button)
+        self.write(&Self::use_path(&use_stmt.path));
 
-        // Format imports if any
-        if !use_stmt.imports.is_empty() {
-            self.write("::{");
-            for (i, import) in use_stmt.imports.iter().enumerate() {
-                if i > 0 {
-                    self.write(", ");
-                }
-                self.write(&import.value);
+        // Format the use-tree if any
+        match use_stmt.imports.as_slice() {
+            [] => {}
+            [UseTree::Glob] => self.write("::*"),
+            [UseTree::Name { name, alias: Some(alias) }] => {
+                self.write(&format!("::{} as {}", name.value, alias.value));
+            }
+            trees => {
+                self.write("::");
+                self.format_use_group(trees);
             }
-            self.write("}");
         }
 
         self.write(";");
     }
 
+    fn format_use_group(&mut self, trees: &[UseTree]) {
+        self.write("{");
+        for (i, tree) in trees.iter().enumerate() {
+            if i > 0 {
+                self.write(", ");
+            }
+            match tree {
+                UseTree::Name { name, alias } => {
+                    self.write(&name.value);
+                    if let Some(alias) = alias {
+                        self.write(&format!(" as {}", alias.value));
+                    }
+                }
+                UseTree::Glob => self.write("*"),
+                UseTree::Nested { path, items } => {
+                    self.write(&Self::use_path(path));
+                    self.write("::");
+                    match items.as_slice() {
+                        [UseTree::Glob] => self.write("*"),
+                        items => self.format_use_group(items),
+                    }
+                }
+            }
+        }
+        self.write("}");
+    }
+
+    /// `.`/`..` segments are written as `./` and `../`, the rest joined with `::`
+    fn use_path(path: &[Identifier]) -> String {
+        let mut out = String::new();
+        let mut segments = path.iter().peekable();
+        while let Some(segment) = segments.next_if(|id| id.value == "." || id.value == "..") {
+            out.push_str(&segment.value);
+            out.push('/');
+        }
+        let rest: Vec<&str> = segments.map(|id| id.value.as_str()).collect();
+        out.push_str(&rest.join("::"));
+        out
+    }
+
     fn format_let_statement(&mut self, let_stmt: &LetStatement) {
         self.write("let ");
 
@@ -267,6 +309,8 @@ impl Formatter {
     }
 
     fn format_struct_definition(&mut self, struct_def: &StructDefinition) {
+        self.format_doc(&struct_def.doc);
+        self.format_attributes(&struct_def.attributes);
         // Derive macros
         if !struct_def.derives.is_empty() {
             self.write("#[derive(");
@@ -334,6 +378,8 @@ impl Formatter {
     }
 
     fn format_enum_definition(&mut self, enum_def: &EnumDefinition) {
+        self.format_doc(&enum_def.doc);
+        self.format_attributes(&enum_def.attributes);
         // Derive macros
         if !enum_def.derives.is_empty() {
             self.write("#[derive(");
@@ -388,6 +434,7 @@ impl Formatter {
         self.indent_level += 1;
         for variant in &enum_def.variants {
             self.write_indent();
+            self.format_attributes(&variant.attributes);
             self.write(&variant.name.value);
 
             if let Some(fields) = &variant.fields {
@@ -418,8 +465,77 @@ impl Formatter {
         self.write("}");
     }
 
+    /// Write ` @allow(...)` after a JSX tag name, if the element silences any lints
+    fn format_element_allows(&mut self, allows: &[String]) {
+        if !allows.is_empty() {
+            self.write(&format!(" @allow({})", allows.join(", ")));
+        }
+    }
+
+    /// Write `@allow(...)` on its own line, if the item silences any lints
+    /// Writes an item's doc as `///` lines; docs from `//!` in its body move up here
+    fn format_doc(&mut self, doc: &Option<String>) {
+        for line in doc.iter().flat_map(|doc| doc.lines()) {
+            if line.is_empty() {
+                self.write("///");
+            } else {
+                self.write(&format!("/// {}", line));
+            }
+            self.newline();
+            self.write_indent();
+        }
+    }
+
+    /// Write `#[...]` attributes, one per line. `derive` and `route` are
+    /// written from the fields they set.
+    fn format_attributes(&mut self, attributes: &[Attribute]) {
+        for attribute in attributes {
+            if matches!(attribute.name.value.as_str(), "derive" | "route") {
+                continue;
+            }
+            self.write(&format!("#[{}", attribute.name.value));
+            if !attribute.tokens.is_empty() {
+                let mut text = String::new();
+                for (i, token) in attribute.tokens.iter().enumerate() {
+                    let previous = i.checked_sub(1).map(|j| &attribute.tokens[j].kind);
+                    if previous.is_some_and(|previous| Self::spaced_between(previous, &token.kind)) {
+                        text.push(' ');
+                    }
+                    match &token.kind {
+                        TokenKind::String(value) => text.push_str(&format!("{:?}", value)),
+                        TokenKind::Char(ch) => text.push_str(&format!("{:?}", ch)),
+                        _ => text.push_str(&token.lexeme),
+                    }
+                }
+                self.write(&format!("({})", text));
+            }
+            self.write("]");
+            self.newline();
+            self.write_indent();
+        }
+    }
+
+    /// Whether attribute tokens are written with a space between them:
+    /// `ttl = 60, key("users")`, not `ttl=60 ,key ( "users" )`
+    fn spaced_between(previous: &TokenKind, next: &TokenKind) -> bool {
+        let tight_after = matches!(previous, TokenKind::LParen | TokenKind::LBracket | TokenKind::Dot | TokenKind::DoubleColon);
+        let tight_before = matches!(next, TokenKind::LParen | TokenKind::RParen | TokenKind::LBracket | TokenKind::RBracket | TokenKind::Comma | TokenKind::Dot | TokenKind::DoubleColon);
+        !tight_after && !tight_before
+    }
+
+    fn format_allows(&mut self, allows: &[String]) {
+        if !allows.is_empty() {
+            self.write(&format!("@allow({})", allows.join(", ")));
+            self.newline();
+            self.write_indent();
+        }
+    }
+
     fn format_function_definition(&mut self, fn_def: &FunctionDefinition) {
+        self.format_doc(&fn_def.doc);
+        self.format_attributes(&fn_def.attributes);
         // Annotations
+        self.format_allows(&fn_def.allows);
         if fn_def.is_server {
             self.write("@server");
             self.newline();
@@ -430,6 +546,20 @@ impl Formatter {
             self.newline();
             self.write_indent();
         }
+        if let Some(route) = &fn_def.route {
+            self.write(&format!("@route({:?})", route.path));
+            self.newline();
+            self.write_indent();
+        }
+        if let Some(capacity) = fn_def.memo {
+            if capacity == DEFAULT_MEMO_CAPACITY {
+                self.write("@memo");
+            } else {
+                self.write(&format!("@memo({})", capacity));
+            }
+            self.newline();
+            self.write_indent();
+        }
 
         if fn_def.is_async {
             self.write("async ");
@@ -484,6 +614,8 @@ impl Formatter {
     }
 
     fn format_component_definition(&mut self, comp_def: &ComponentDefinition) {
+        self.format_doc(&comp_def.doc);
+        self.format_allows(&comp_def.allows);
         if comp_def.is_client {
             self.write("@client");
             self.newline();
@@ -498,6 +630,9 @@ impl Formatter {
             if i > 0 {
                 self.write(", ");
             }
+            if param.is_slot {
+                self.write("slot ");
+            }
             self.write(&param.name.value);
             self.write(": ");
             self.format_type_expression(&param.type_annotation);
@@ -593,6 +728,7 @@ impl Formatter {
         self.indent_level += 1;
         for method in &impl_block.methods {
             self.write_indent();
+            self.format_doc(&method.doc);
             self.write("fn ");
             self.write(&method.name.value);
             self.write("(");
@@ -624,6 +760,7 @@ impl Formatter {
     }
 
     fn format_trait_definition(&mut self, trait_def: &TraitDefinition) {
+        self.format_doc(&trait_def.doc);
         self.write("trait ");
         self.write(&trait_def.name.value);
 
@@ -664,6 +801,7 @@ impl Formatter {
         self.indent_level += 1;
         for method in &trait_def.methods {
             self.write_indent();
+            self.format_doc(&method.doc);
             self.write("fn ");
             self.write(&method.name.value);
             self.write("(");
@@ -705,6 +843,7 @@ impl Formatter {
                 self.write(&s.replace('"', "\\\""));
                 self.write("\"");
             }
+            Expression::Template(template) => self.format_template(template),
             Expression::BoolLiteral(b) => self.write(if *b { "true" } else { "false" }),
             Expression::UnitLiteral => self.write("()"),
             Expression::ArrayLiteral(arr) => self.format_array_literal(arr),
@@ -734,6 +873,8 @@ impl Formatter {
                 // CSS formatting will be implemented in Sprint 1 Task 1.6
                 self.write("css! { /* formatting not yet implemented */ }")
             }
+            Expression::InlineStyle(style) => self.format_inline_style(style),
+            Expression::Classes(classes) => self.format_classes_macro(classes),
             // Reactivity primitives (Phase 12)
             Expression::Signal(signal_expr) => {
                 self.write("signal");
@@ -828,7 +969,7 @@ impl Formatter {
 
     fn format_field_access(&mut self, field: &FieldAccessExpression) {
         self.format_expression(&field.object);
-        self.write(".");
+        self.write(if field.optional { "?." } else { "." });
         self.write(&field.field.value);
     }
 
@@ -913,34 +1054,73 @@ impl Formatter {
         false
     }
 
+    /// `name="text"`, `name={expr}`, a bare `name` for `true`, or `{...props}`
+    fn format_jsx_attribute(&mut self, attr: &JsxAttribute) {
+        if let Some(spread) = attr.spread() {
+            self.write("{...");
+            self.format_expression(spread);
+            self.write("}");
+            return;
+        }
+
+        self.write(&attr.name.value);
+
+        if let Expression::BoolLiteral(true) = attr.value {
+            return;
+        }
+
+        self.write("=");
+
+        match &attr.value {
+            Expression::StringLiteral(s) => {
+                self.write("\"");
+                self.write(s);
+                self.write("\"");
+            }
+            Expression::InlineStyle(style) => self.format_inline_style(style),
+            _ => {
+                self.write("{");
+                self.format_expression(&attr.value);
+                self.write("}");
+            }
+        }
+    }
+
+    /// The tag's name, with a generic component's type arguments
+    fn format_jsx_tag_name(&mut self, tag: &JsxOpeningTag) {
+        self.write(&tag.name.value);
+        if tag.type_args.is_empty() {
+            return;
+        }
+        self.write("<");
+        for (i, arg) in tag.type_args.iter().enumerate() {
+            if i > 0 {
+                self.write(", ");
+            }
+            self.format_type_expression(arg);
+        }
+        self.write(">");
+    }
+
+    /// Text among an element's children; a <style> or <script> keeps its own
+    fn format_jsx_text(&mut self, jsx: &JsxElement, text: &str) {
+        if jsx.has_raw_text() {
+            self.write(text);
+        } else {
+            self.write(&html_entities::encode_jsx_text(text));
+        }
+    }
+
     /// Format JSX element on a single line (for simple elements)
     fn format_jsx_element_inline(&mut self, jsx: &JsxElement) {
         self.write("<");
-        self.write(&jsx.opening_tag.name.value);
+        self.format_jsx_tag_name(&jsx.opening_tag);
+        self.format_element_allows(&jsx.opening_tag.allows);
 
         // Attributes
         for attr in &jsx.opening_tag.attributes {
             self.write(" ");
-            self.write(&attr.name.value);
-
-            if let Expression::BoolLiteral(true) = attr.value {
-                continue;
-            }
-
-            self.write("=");
-
-            match &attr.value {
-                Expression::StringLiteral(s) => {
-                    self.write("\"");
-                    self.write(s);
-                    self.write("\"");
-                }
-                _ => {
-                    self.write("{");
-                    self.format_expression(&attr.value);
-                    self.write("}");
-                }
-            }
+            self.format_jsx_attribute(attr);
         }
 
         if jsx.opening_tag.self_closing {
@@ -954,7 +1134,7 @@ impl Formatter {
         for child in &jsx.children {
             match child {
                 JsxChild::Element(elem) => self.format_jsx_element(elem),
-                JsxChild::Text(text) => self.write(text),
+                JsxChild::Text(text) => self.format_jsx_text(jsx, text),
                 JsxChild::Expression(expr) => {
                     self.write("{");
                     self.format_expression(expr);
@@ -974,7 +1154,8 @@ impl Formatter {
     /// Format JSX element on multiple lines (for complex elements)
     fn format_jsx_element_multiline(&mut self, jsx: &JsxElement) {
         self.write("<");
-        self.write(&jsx.opening_tag.name.value);
+        self.format_jsx_tag_name(&jsx.opening_tag);
+        self.format_element_allows(&jsx.opening_tag.allows);
 
         // Attributes - multi-line if >3
         if jsx.opening_tag.attributes.len() > 3 {
@@ -982,26 +1163,7 @@ impl Formatter {
             for attr in &jsx.opening_tag.attributes {
                 self.newline();
                 self.write_indent();
-                self.write(&attr.name.value);
-
-                if let Expression::BoolLiteral(true) = attr.value {
-                    continue;
-                }
-
-                self.write("=");
-
-                match &attr.value {
-                    Expression::StringLiteral(s) => {
-                        self.write("\"");
-                        self.write(s);
-                        self.write("\"");
-                    }
-                    _ => {
-                        self.write("{");
-                        self.format_expression(&attr.value);
-                        self.write("}");
-                    }
-                }
+                self.format_jsx_attribute(attr);
             }
             self.indent_level -= 1;
             self.newline();
@@ -1010,26 +1172,7 @@ impl Formatter {
             // Inline attributes if <=3
             for attr in &jsx.opening_tag.attributes {
                 self.write(" ");
-                self.write(&attr.name.value);
-
-                if let Expression::BoolLiteral(true) = attr.value {
-                    continue;
-                }
-
-                self.write("=");
-
-                match &attr.value {
-                    Expression::StringLiteral(s) => {
-                        self.write("\"");
-                        self.write(s);
-                        self.write("\"");
-                    }
-                    _ => {
-                        self.write("{");
-                        self.format_expression(&attr.value);
-                        self.write("}");
-                    }
-                }
+                self.format_jsx_attribute(attr);
             }
         }
 
@@ -1057,7 +1200,8 @@ impl Formatter {
                         self.format_jsx_element(elem);
                     }
                     JsxChild::Text(text) => {
-                        let trimmed = text.trim();
+                        let encoded = html_entities::encode_jsx_text(text);
+                        let trimmed = encoded.trim();
                         if !trimmed.is_empty() {
                             self.newline();
                             self.write_indent();
@@ -1081,7 +1225,7 @@ impl Formatter {
             for child in &jsx.children {
                 match child {
                     JsxChild::Element(elem) => self.format_jsx_element(elem),
-                    JsxChild::Text(text) => self.write(text),
+                    JsxChild::Text(text) => self.format_jsx_text(jsx, text),
                     JsxChild::Expression(expr) => {
                         self.write("{");
                         self.format_expression(expr);
@@ -1139,6 +1283,38 @@ impl Formatter {
         self.write(")");
     }
 
+    fn format_classes_macro(&mut self, classes: &ClassesMacro) {
+        self.write("classes!(");
+        for (i, entry) in classes.entries.iter().enumerate() {
+            if i > 0 {
+                self.write(", ");
+            }
+            self.format_expression(&entry.class);
+            if let Some(condition) = &entry.condition {
+                self.write(" => ");
+                self.format_expression(condition);
+            }
+        }
+        self.write(")");
+    }
+
+    fn format_template(&mut self, template: &TemplateLiteral) {
+        self.write("`");
+        for part in &template.parts {
+            match part {
+                TemplatePart::Text(text) => {
+                    self.write(&text.replace('\\', "\\\\").replace('`', "\\`").replace("${", "\\${"));
+                }
+                TemplatePart::Interpolation(expr) => {
+                    self.write("${");
+                    self.format_expression(expr);
+                    self.write("}");
+                }
+            }
+        }
+        self.write("`");
+    }
+
     fn format_lambda_expression(&mut self, lambda: &LambdaExpression) {
         self.write("|");
 
@@ -1221,6 +1397,56 @@ impl Formatter {
         self.format_expression(&await_expr.expression);
     }
 
+    /// Format an inline style attribute value: "color: {c}; padding: 4px"
+    fn format_inline_style(&mut self, style: &InlineStyle) {
+        if style.object {
+            return self.format_style_object(style);
+        }
+        self.write("\"");
+        for (i, decl) in style.declarations.iter().enumerate() {
+            if i > 0 {
+                self.write("; ");
+            }
+            self.write(&decl.property);
+            self.write(": ");
+            for part in &decl.value {
+                match part {
+                    InlineStylePart::Static(text) => self.write(text),
+                    InlineStylePart::Dynamic(expr) => {
+                        self.write("{");
+                        self.format_expression(expr);
+                        self.write("}");
+                    }
+                }
+            }
+        }
+        self.write("\"");
+    }
+
+    /// Format a style object, `{{ color: c, padding_top: "4px", opacity: 0.5 }}`,
+    /// with properties in snake_case and quoted where that can't spell them
+    fn format_style_object(&mut self, style: &InlineStyle) {
+        self.write("{{ ");
+        for (i, decl) in style.declarations.iter().enumerate() {
+            if i > 0 {
+                self.write(", ");
+            }
+            if decl.property.starts_with('-') {
+                self.format_expression(&Expression::StringLiteral(decl.property.clone()));
+            } else {
+                self.write(&decl.property.replace('-', "_"));
+            }
+            self.write(": ");
+            match decl.value.as_slice() {
+                [InlineStylePart::Static(text)] if !decl.number_is_px() && text.parse::<f64>().is_ok() => self.write(text),
+                [InlineStylePart::Static(text)] => self.format_expression(&Expression::StringLiteral(text.clone())),
+                [InlineStylePart::Dynamic(expr)] => self.format_expression(expr),
+                _ => {}
+            }
+        }
+        self.write(" }}");
+    }
+
     fn format_block_expression(&mut self, block: &BlockStatement) {
         self.write("{");
 
@@ -1412,13 +1638,9 @@ mod tests {
     fn test_format_simple_let() {
         let program = Program {
             statements: vec![Statement::Let(LetStatement {
-                pattern: Pattern::Identifier(Identifier {
-                    value: "x".to_string(),
-                }),
+                pattern: Pattern::Identifier(Identifier::new("x")),
                 mutable: false,
-                type_annotation: Some(TypeExpression::Named(Identifier {
-                    value: "i32".to_string(),
-                })),
+                type_annotation: Some(TypeExpression::Named(Identifier::new("i32"))),
                 value: Expression::IntegerLiteral(42),
             })],
         };
@@ -1432,42 +1654,40 @@ mod tests {
     fn test_format_function() {
         let program = Program {
             statements: vec![Statement::Function(FunctionDefinition {
-                name: Identifier {
-                    value: "add".to_string(),
-                },
+                name: Identifier::new("add"),
+                doc: None,
                 lifetime_params: vec![],
                 type_params: vec![],
                 parameters: vec![
                     FunctionParameter {
-                        name: Identifier {
-                            value: "a".to_string(),
-                        },
-                        type_annotation: TypeExpression::Named(Identifier {
-                            value: "i32".to_string(),
-                        }),
+                        name: Identifier::new("a"),
+                        type_annotation: TypeExpression::Named(Identifier::new("i32")),
+                        position: Position::default(),
+                        is_slot: false,
                     },
                     FunctionParameter {
-                        name: Identifier {
-                            value: "b".to_string(),
-                        },
-                        type_annotation: TypeExpression::Named(Identifier {
-                            value: "i32".to_string(),
-                        }),
+                        name: Identifier::new("b"),
+                        type_annotation: TypeExpression::Named(Identifier::new("i32")),
+                        position: Position::default(),
+                        is_slot: false,
                     },
                 ],
+                return_type: None,
+                return_type_position: Position::default(),
+                memo: None,
+                allows: vec![],
+                route: None,
+                attributes: vec![],
                 is_server: false,
                 is_client: false,
                 is_async: false,
                 body: BlockStatement {
                     statements: vec![Statement::Return(ReturnStatement {
                         value: Expression::Infix(InfixExpression {
-                            left: Box::new(Expression::Identifier(Identifier {
-                                value: "a".to_string(),
-                            })),
+                            left: Box::new(Expression::Identifier(Identifier::new("a"))),
                             operator: Token::new(TokenKind::Plus, "+".to_string(), 1, 1),
-                            right: Box::new(Expression::Identifier(Identifier {
-                                value: "b".to_string(),
-                            })),
+                            right: Box::new(Expression::Identifier(Identifier::new("b"))),
+                            assign_typo: false,
                         }),
                     })],
                 },
@@ -1485,30 +1705,22 @@ mod tests {
     fn test_format_struct() {
         let program = Program {
             statements: vec![Statement::Struct(StructDefinition {
-                name: Identifier {
-                    value: "Point".to_string(),
-                },
+                name: Identifier::new("Point"),
+                doc: None,
                 lifetime_params: vec![],
                 type_params: vec![],
                 fields: vec![
                     (
-                        Identifier {
-                            value: "x".to_string(),
-                        },
-                        TypeExpression::Named(Identifier {
-                            value: "i32".to_string(),
-                        }),
+                        Identifier::new("x"),
+                        TypeExpression::Named(Identifier::new("i32")),
                     ),
                     (
-                        Identifier {
-                            value: "y".to_string(),
-                        },
-                        TypeExpression::Named(Identifier {
-                            value: "i32".to_string(),
-                        }),
+                        Identifier::new("y"),
+                        TypeExpression::Named(Identifier::new("i32")),
                     ),
                 ],
                 derives: vec![],
+                attributes: vec![],
             })],
         };
 
@@ -1524,26 +1736,24 @@ mod tests {
     fn test_format_enum() {
         let program = Program {
             statements: vec![Statement::Enum(EnumDefinition {
-                name: Identifier {
-                    value: "Option".to_string(),
-                },
+                name: Identifier::new("Option"),
+                doc: None,
                 lifetime_params: vec![],
                 type_params: vec![],
                 variants: vec![
                     EnumVariant {
-                        name: Identifier {
-                            value: "Some".to_string(),
-                        },
+                        name: Identifier::new("Some"),
                         fields: None,
+                        attributes: vec![],
                     },
                     EnumVariant {
-                        name: Identifier {
-                            value: "None".to_string(),
-                        },
+                        name: Identifier::new("None"),
                         fields: None,
+                        attributes: vec![],
                     },
                 ],
                 derives: vec![],
+                attributes: vec![],
             })],
         };
 
@@ -1559,15 +1769,11 @@ mod tests {
     fn test_format_match_expression() {
         let program = Program {
             statements: vec![Statement::Let(LetStatement {
-                pattern: Pattern::Identifier(Identifier {
-                    value: "result".to_string(),
-                }),
+                pattern: Pattern::Identifier(Identifier::new("result")),
                 mutable: false,
                 type_annotation: None,
                 value: Expression::Match(MatchExpression {
-                    scrutinee: Box::new(Expression::Identifier(Identifier {
-                        value: "x".to_string(),
-                    })),
+                    scrutinee: Box::new(Expression::Identifier(Identifier::new("x"))),
                     arms: vec![
                         MatchArm {
                             patterns: vec![Pattern::Literal(Expression::IntegerLiteral(1))],
@@ -1594,23 +1800,22 @@ mod tests {
     fn test_format_jsx_simple_inline() {
         let program = Program {
             statements: vec![Statement::Let(LetStatement {
-                pattern: Pattern::Identifier(Identifier {
-                    value: "elem".to_string(),
-                }),
+                pattern: Pattern::Identifier(Identifier::new("elem")),
                 mutable: false,
                 type_annotation: None,
                 value: Expression::JsxElement(JsxElement {
                     opening_tag: JsxOpeningTag {
-                        name: Identifier {
-                            value: "Button".to_string(),
-                        },
+                        name: Identifier::new("Button"),
+                        type_args: vec![],
                         attributes: vec![],
                         self_closing: false,
+                        slash_omitted: false,
+                        position: Position::default(),
+                        allows: Vec::new(),
                     },
                     children: vec![JsxChild::Text("Click".to_string())],
-                    closing_tag: Some(Identifier {
-                        value: "Button".to_string(),
-                    }),
+                    closing_tag: Some(Identifier::new("Button")),
+                    unknown_entities: Vec::new(),
                 }),
             })],
         };
@@ -1622,49 +1827,85 @@ mod tests {
     }
 
     #[test]
-    fn test_format_jsx_multiline_nested() {
+    fn test_format_jsx_text_entities() {
         let program = Program {
             statements: vec![Statement::Let(LetStatement {
-                pattern: Pattern::Identifier(Identifier {
-                    value: "elem".to_string(),
+                pattern: Pattern::Identifier(Identifier::new("elem")),
+                mutable: false,
+                type_annotation: None,
+                value: Expression::JsxElement(JsxElement {
+                    opening_tag: JsxOpeningTag {
+                        name: Identifier::new("p"),
+                        type_args: vec![],
+                        attributes: vec![],
+                        self_closing: false,
+                        slash_omitted: false,
+                        position: Position::default(),
+                        allows: Vec::new(),
+                    },
+                    children: vec![JsxChild::Text("a < b & {c}\u{A0}&amp;".to_string())],
+                    closing_tag: Some(Identifier::new("p")),
+                    unknown_entities: Vec::new(),
                 }),
+            })],
+        };
+
+        let mut formatter = Formatter::new();
+        let formatted = formatter.format_program(&program);
+
+        // Written back so it reads as the same text
+        assert!(formatted.contains("let elem = <p>a &lt; b & &#123;c&#125;&nbsp;&amp;amp;</p>;"));
+    }
+
+    #[test]
+    fn test_format_jsx_multiline_nested() {
+        let program = Program {
+            statements: vec![Statement::Let(LetStatement {
+                pattern: Pattern::Identifier(Identifier::new("elem")),
                 mutable: false,
                 type_annotation: None,
                 value: Expression::JsxElement(JsxElement {
                     opening_tag: JsxOpeningTag {
-                        name: Identifier {
-                            value: "div".to_string(),
-                        },
+                        name: Identifier::new("div"),
+                        type_args: vec![],
                         attributes: vec![],
                         self_closing: false,
+                        slash_omitted: false,
+                        position: Position::default(),
+                        allows: Vec::new(),
                     },
                     children: vec![
                         JsxChild::Element(Box::new(JsxElement {
                             opening_tag: JsxOpeningTag {
-                                name: Identifier {
-                                    value: "Header".to_string(),
-                                },
+                                name: Identifier::new("Header"),
+                                type_args: vec![],
                                 attributes: vec![],
                                 self_closing: true,
+                                slash_omitted: false,
+                                position: Position::default(),
+                                allows: Vec::new(),
                             },
                             children: vec![],
                             closing_tag: None,
+                            unknown_entities: Vec::new(),
                         })),
                         JsxChild::Element(Box::new(JsxElement {
                             opening_tag: JsxOpeningTag {
-                                name: Identifier {
-                                    value: "Content".to_string(),
-                                },
+                                name: Identifier::new("Content"),
+                                type_args: vec![],
                                 attributes: vec![],
                                 self_closing: true,
+                                slash_omitted: false,
+                                position: Position::default(),
+                                allows: Vec::new(),
                             },
                             children: vec![],
                             closing_tag: None,
+                            unknown_entities: Vec::new(),
                         })),
                     ],
-                    closing_tag: Some(Identifier {
-                        value: "div".to_string(),
-                    }),
+                    closing_tag: Some(Identifier::new("div")),
+                    unknown_entities: Vec::new(),
                 }),
             })],
         };
@@ -1683,46 +1924,39 @@ mod tests {
     fn test_format_jsx_many_attributes() {
         let program = Program {
             statements: vec![Statement::Let(LetStatement {
-                pattern: Pattern::Identifier(Identifier {
-                    value: "elem".to_string(),
-                }),
+                pattern: Pattern::Identifier(Identifier::new("elem")),
                 mutable: false,
                 type_annotation: None,
                 value: Expression::JsxElement(JsxElement {
                     opening_tag: JsxOpeningTag {
-                        name: Identifier {
-                            value: "Component".to_string(),
-                        },
+                        name: Identifier::new("Component"),
+                        type_args: vec![],
                         attributes: vec![
                             JsxAttribute {
-                                name: Identifier {
-                                    value: "prop1".to_string(),
-                                },
+                                name: Identifier::new("prop1"),
                                 value: Expression::IntegerLiteral(1),
                             },
                             JsxAttribute {
-                                name: Identifier {
-                                    value: "prop2".to_string(),
-                                },
+                                name: Identifier::new("prop2"),
                                 value: Expression::IntegerLiteral(2),
                             },
                             JsxAttribute {
-                                name: Identifier {
-                                    value: "prop3".to_string(),
-                                },
+                                name: Identifier::new("prop3"),
                                 value: Expression::IntegerLiteral(3),
                             },
                             JsxAttribute {
-                                name: Identifier {
-                                    value: "prop4".to_string(),
-                                },
+                                name: Identifier::new("prop4"),
                                 value: Expression::IntegerLiteral(4),
                             },
                         ],
                         self_closing: true,
+                        slash_omitted: false,
+                        position: Position::default(),
+                        allows: Vec::new(),
                     },
                     children: vec![],
                     closing_tag: None,
+                    unknown_entities: Vec::new(),
                 }),
             })],
         };
@@ -1743,35 +1977,26 @@ mod tests {
     fn test_format_lambda() {
         let program = Program {
             statements: vec![Statement::Let(LetStatement {
-                pattern: Pattern::Identifier(Identifier {
-                    value: "add".to_string(),
-                }),
+                pattern: Pattern::Identifier(Identifier::new("add")),
                 mutable: false,
                 type_annotation: None,
                 value: Expression::Lambda(LambdaExpression {
                     parameters: vec![
                         LambdaParameter {
-                            name: Identifier {
-                                value: "x".to_string(),
-                            },
+                            name: Identifier::new("x"),
                             type_annotation: None,
                         },
                         LambdaParameter {
-                            name: Identifier {
-                                value: "y".to_string(),
-                            },
+                            name: Identifier::new("y"),
                             type_annotation: None,
                         },
                     ],
                     return_type: None,
                     body: Box::new(Expression::Infix(InfixExpression {
-                        left: Box::new(Expression::Identifier(Identifier {
-                            value: "x".to_string(),
-                        })),
+                        left: Box::new(Expression::Identifier(Identifier::new("x"))),
                         operator: Token::new(TokenKind::Plus, "+".to_string(), 1, 1),
-                        right: Box::new(Expression::Identifier(Identifier {
-                            value: "y".to_string(),
-                        })),
+                        right: Box::new(Expression::Identifier(Identifier::new("y"))),
+                        assign_typo: false,
                     })),
                     captures: vec![],
                 }),
@@ -1788,9 +2013,7 @@ mod tests {
     fn test_format_ternary() {
         let program = Program {
             statements: vec![Statement::Let(LetStatement {
-                pattern: Pattern::Identifier(Identifier {
-                    value: "result".to_string(),
-                }),
+                pattern: Pattern::Identifier(Identifier::new("result")),
                 mutable: false,
                 type_annotation: None,
                 value: Expression::Ternary(TernaryExpression {
@@ -1811,9 +2034,7 @@ mod tests {
     fn test_format_array_literal() {
         let program = Program {
             statements: vec![Statement::Let(LetStatement {
-                pattern: Pattern::Identifier(Identifier {
-                    value: "arr".to_string(),
-                }),
+                pattern: Pattern::Identifier(Identifier::new("arr")),
                 mutable: false,
                 type_annotation: None,
                 value: Expression::ArrayLiteral(ArrayLiteral {
@@ -1836,9 +2057,7 @@ mod tests {
     fn test_format_if_expression() {
         let program = Program {
             statements: vec![Statement::Let(LetStatement {
-                pattern: Pattern::Identifier(Identifier {
-                    value: "result".to_string(),
-                }),
+                pattern: Pattern::Identifier(Identifier::new("result")),
                 mutable: false,
                 type_annotation: None,
                 value: Expression::IfExpression(IfExpression {
@@ -1859,16 +2078,10 @@ mod tests {
     fn test_format_for_loop() {
         let program = Program {
             statements: vec![Statement::ForIn(ForInStatement {
-                variable: Identifier {
-                    value: "i".to_string(),
-                },
-                iterator: Expression::Identifier(Identifier {
-                    value: "items".to_string(),
-                }),
+                variable: Identifier::new("i"),
+                iterator: Expression::Identifier(Identifier::new("items")),
                 body: BlockStatement {
-                    statements: vec![Statement::Expression(Expression::Identifier(Identifier {
-                        value: "i".to_string(),
-                    }))],
+                    statements: vec![Statement::Expression(Expression::Identifier(Identifier::new("i")))],
                 },
             })],
         };
@@ -1885,9 +2098,7 @@ mod tests {
             statements: vec![Statement::While(WhileStatement {
                 condition: Expression::BoolLiteral(true),
                 body: BlockStatement {
-                    statements: vec![Statement::Expression(Expression::Identifier(Identifier {
-                        value: "x".to_string(),
-                    }))],
+                    statements: vec![Statement::Expression(Expression::Identifier(Identifier::new("x")))],
                 },
             })],
         };
@@ -1902,12 +2113,8 @@ mod tests {
     fn test_format_const() {
         let program = Program {
             statements: vec![Statement::Const(ConstDeclaration {
-                name: Identifier {
-                    value: "PI".to_string(),
-                },
-                type_annotation: Some(TypeExpression::Named(Identifier {
-                    value: "f64".to_string(),
-                })),
+                name: Identifier::new("PI"),
+                type_annotation: Some(TypeExpression::Named(Identifier::new("f64"))),
                 value: Expression::FloatLiteral("3.14".to_string()),
             })],
         };
@@ -1922,17 +2129,18 @@ mod tests {
     fn test_format_use_statement() {
         let program = Program {
             statements: vec![Statement::Use(UseStatement {
-                path: vec![Identifier {
-                    value: "raven_store".to_string(),
-                }],
+                path: vec![Identifier::new("raven_store")],
                 imports: vec![
-                    Identifier {
-                        value: "Signal".to_string(),
+                    UseTree::Name {
+                        name: Identifier::new("Signal"),
+                        alias: None,
                     },
-                    Identifier {
-                        value: "Computed".to_string(),
+                    UseTree::Name {
+                        name: Identifier::new("Computed"),
+                        alias: None,
                     },
                 ],
+                is_pub: false,
             })],
         };
 
@@ -1946,18 +2154,14 @@ mod tests {
     fn test_format_spread_operator() {
         let program = Program {
             statements: vec![Statement::Let(LetStatement {
-                pattern: Pattern::Identifier(Identifier {
-                    value: "arr".to_string(),
-                }),
+                pattern: Pattern::Identifier(Identifier::new("arr")),
                 mutable: false,
                 type_annotation: None,
                 value: Expression::ArrayLiteral(ArrayLiteral {
                     elements: vec![
                         Expression::IntegerLiteral(1),
                         Expression::Spread(SpreadExpression {
-                            expression: Box::new(Expression::Identifier(Identifier {
-                                value: "rest".to_string(),
-                            })),
+                            expression: Box::new(Expression::Identifier(Identifier::new("rest"))),
                         }),
                     ],
                 }),
@@ -1974,16 +2178,12 @@ mod tests {
     fn test_format_type_cast() {
         let program = Program {
             statements: vec![Statement::Let(LetStatement {
-                pattern: Pattern::Identifier(Identifier {
-                    value: "x".to_string(),
-                }),
+                pattern: Pattern::Identifier(Identifier::new("x")),
                 mutable: false,
                 type_annotation: None,
                 value: Expression::TypeCast(TypeCastExpression {
                     expression: Box::new(Expression::IntegerLiteral(42)),
-                    target_type: TypeExpression::Named(Identifier {
-                        value: "f64".to_string(),
-                    }),
+                    target_type: TypeExpression::Named(Identifier::new("f64")),
                 }),
             })],
         };
@@ -1998,26 +2198,18 @@ mod tests {
     fn test_format_struct_literal() {
         let program = Program {
             statements: vec![Statement::Let(LetStatement {
-                pattern: Pattern::Identifier(Identifier {
-                    value: "point".to_string(),
-                }),
+                pattern: Pattern::Identifier(Identifier::new("point")),
                 mutable: false,
                 type_annotation: None,
                 value: Expression::StructLiteral(StructLiteral {
-                    name: Identifier {
-                        value: "Point".to_string(),
-                    },
+                    name: Identifier::new("Point"),
                     fields: vec![
                         (
-                            Identifier {
-                                value: "x".to_string(),
-                            },
+                            Identifier::new("x"),
                             Expression::IntegerLiteral(10),
                         ),
                         (
-                            Identifier {
-                                value: "y".to_string(),
-                            },
+                            Identifier::new("y"),
                             Expression::IntegerLiteral(20),
                         ),
                     ],
@@ -2035,12 +2227,17 @@ mod tests {
     fn test_format_async_function() {
         let program = Program {
             statements: vec![Statement::Function(FunctionDefinition {
-                name: Identifier {
-                    value: "fetch_data".to_string(),
-                },
+                name: Identifier::new("fetch_data"),
+                doc: None,
                 lifetime_params: vec![],
                 type_params: vec![],
                 parameters: vec![],
+                return_type: None,
+                return_type_position: Position::default(),
+                memo: None,
+                allows: vec![],
+                route: None,
+                attributes: vec![],
                 is_server: false,
                 is_client: false,
                 is_async: true,
@@ -2062,9 +2259,7 @@ mod tests {
     fn test_format_tuple_literal() {
         let program = Program {
             statements: vec![Statement::Let(LetStatement {
-                pattern: Pattern::Identifier(Identifier {
-                    value: "tuple".to_string(),
-                }),
+                pattern: Pattern::Identifier(Identifier::new("tuple")),
                 mutable: false,
                 type_annotation: None,
                 value: Expression::TupleLiteral(TupleLiteral {
@@ -2082,4 +2277,34 @@ mod tests {
 
         assert!(formatted.contains("(1, \"hello\", true)"));
     }
+
+    #[test]
+    fn test_format_attributes() {
+        let source = "#[derive(Clone)]\nstruct Point {\n    x: i32,\n}\n#[cache(ttl = 60, key(\"users\"))]\nfn users() -> i32 {\n    return 1;\n}\nenum Status {\n    #[default]\n    Active,\n}";
+        let mut lexer = crate::lexer::Lexer::new(source.to_string());
+        let program = crate::parser::Parser::new(&mut lexer).parse_program().unwrap();
+
+        let mut formatter = Formatter::new();
+        let formatted = formatter.format_program(&program);
+
+        assert!(formatted.contains("#[derive(Clone)]\nstruct Point"), "{}", formatted);
+        assert!(formatted.contains("#[cache(ttl = 60, key(\"users\"))]\nfn users("), "{}", formatted);
+        assert!(formatted.contains("    #[default]\n    Active,"), "{}", formatted);
+    }
+
+    #[test]
+    fn test_format_style_object() {
+        let source = "let card = <div style={ { color: fg, paddingTop: 8, \"--accent\": \"red\", opacity: 0.5 } }>x</div>;";
+        let mut lexer = crate::lexer::Lexer::new(source.to_string());
+        let program = crate::parser::Parser::new(&mut lexer).parse_program().unwrap();
+
+        let mut formatter = Formatter::new();
+        let formatted = formatter.format_program(&program);
+
+        assert!(
+            formatted.contains("<div style={{ color: fg, padding_top: \"8px\", \"--accent\": \"red\", opacity: 0.5 }}>"),
+            "{}",
+            formatted
+        );
+    }
 }
@@ -0,0 +1,257 @@
+// Highlighting - Token classes for editors and highlighted docs
+//
+// `highlight` lexes source the way the parser would, but with nobody parsing:
+// a `<` right before a name where no operand precedes it opens a JSX tag, and
+// `css! {` opens a CSS body. Guesses can be wrong on odd code; they never fail.
+// Whitespace between tokens is left out of the result.
+
+use crate::lexer::Lexer;
+use crate::token::{HighlightClass, Span, Token, TokenKind};
+use crate::void_elements::is_void_element;
+
+/// A JSX tag being read
+struct Tag {
+    /// `</name>` rather than `<name ...>`
+    closing: bool,
+    /// Braces open in its attributes; a `>` inside them is a comparison
+    braces: usize,
+    /// `<` open in a generic component's type arguments, `<List<User>`
+    type_args: usize,
+    /// An element like `<br>` that ends with its tag, `/>` or not
+    void: bool,
+}
+
+/// The class of each token in `source`, in order. Comments are included,
+/// and characters the lexer doesn't know come out as `HighlightClass::Error`.
+pub fn highlight(source: &str) -> Vec<(Span, HighlightClass)> {
+    let mut lexer = Lexer::new(source.to_string()).with_comments(true);
+    let mut classes = Vec::new();
+    let mut tags: Vec<Tag> = Vec::new();
+    let mut previous: Option<Token> = None;
+    // The end of a tag name read so far; `.`, `:`, `-` and names right after it continue it
+    let mut tag_name_end: Option<usize> = None;
+
+    loop {
+        let token = lexer.next_token();
+        if token.kind == TokenKind::Eof {
+            break;
+        }
+        let mut class = token.kind.highlight_class();
+
+        let after_tag_name = tag_name_end == Some(token.span.start);
+        if after_tag_name
+            && matches!(token.kind, TokenKind::Identifier | TokenKind::Dot | TokenKind::Colon | TokenKind::Minus)
+        {
+            class = HighlightClass::JsxTag;
+            tag_name_end = Some(token.span.end);
+        } else {
+            tag_name_end = None;
+            match token.kind {
+                TokenKind::LAngle if lexer.is_jsx_mode() && lexer.peek_token().kind == TokenKind::Slash => {
+                    lexer.enter_closing_tag_mode();
+                    let slash = lexer.next_token();
+                    lexer.exit_closing_tag_mode();
+                    lexer.exit_jsx_mode();
+                    classes.push((token.span, HighlightClass::JsxTag));
+                    classes.push((slash.span, HighlightClass::JsxTag));
+                    tags.push(Tag { closing: true, braces: 0, type_args: 0, void: false });
+                    tag_name_end = Some(slash.span.end);
+                    previous = Some(slash);
+                    continue;
+                }
+                TokenKind::LAngle if after_tag_name && tags.last().is_some_and(|tag| !tag.closing) => {
+                    lexer.enter_jsx_type_args();
+                    tags.last_mut().expect("checked above").type_args += 1;
+                }
+                TokenKind::LAngle if tags.last().is_some_and(|tag| tag.type_args > 0) => {
+                    tags.last_mut().expect("checked above").type_args += 1;
+                }
+                TokenKind::RAngle if tags.last().is_some_and(|tag| tag.type_args > 0) => {
+                    tags.last_mut().expect("checked above").type_args -= 1;
+                }
+                TokenKind::LAngle if opens_tag(&token, previous.as_ref(), &mut lexer) => {
+                    let void = is_void_element(&lexer.peek_token().lexeme);
+                    lexer.enter_jsx_mode();
+                    class = HighlightClass::JsxTag;
+                    tags.push(Tag { closing: false, braces: 0, type_args: 0, void });
+                    tag_name_end = Some(token.span.end);
+                }
+                TokenKind::RAngle | TokenKind::JsxSelfClose if tags.last().is_some_and(|tag| tag.braces == 0) => {
+                    class = HighlightClass::JsxTag;
+                    let tag = tags.pop().expect("checked above");
+                    if (token.kind == TokenKind::JsxSelfClose || tag.void) && !tag.closing {
+                        lexer.exit_jsx_mode();
+                    }
+                }
+                TokenKind::LBrace | TokenKind::JsxOpenBrace => {
+                    if let Some(tag) = tags.last_mut() {
+                        tag.braces += 1;
+                    }
+                    if previous.as_ref().is_some_and(|previous| previous.kind == TokenKind::CssMacro) {
+                        lexer.enter_css_mode();
+                    }
+                }
+                TokenKind::RBrace | TokenKind::JsxCloseBrace => {
+                    if let Some(tag) = tags.last_mut() {
+                        tag.braces = tag.braces.saturating_sub(1);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        classes.push((token.span, class));
+        if !matches!(class, HighlightClass::Comment) {
+            previous = Some(token);
+        }
+    }
+    classes
+}
+
+/// Whether the `<` just read opens a JSX tag: a name or a fragment's `>`
+/// follows it directly, and it doesn't come after an operand, as the `<` of
+/// a comparison or of generics like `Vec<i32>` does
+fn opens_tag(angle: &Token, previous: Option<&Token>, lexer: &mut Lexer) -> bool {
+    let after_operand = previous.is_some_and(|previous| matches!(
+        previous.kind,
+        TokenKind::Identifier | TokenKind::Integer(_) | TokenKind::Float(_) | TokenKind::TypedNumber(..)
+            | TokenKind::String(_) | TokenKind::Char(_) | TokenKind::TemplateEnd | TokenKind::True | TokenKind::False
+            | TokenKind::SelfValue | TokenKind::SelfType | TokenKind::RParen | TokenKind::RBracket
+    ));
+    let next = lexer.peek_token();
+    !after_operand
+        && next.span.start == angle.span.end
+        && matches!(next.kind, TokenKind::Identifier | TokenKind::RAngle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// Each token's text and class
+    fn classes(source: &str) -> Vec<(&str, HighlightClass)> {
+        highlight(source).into_iter().map(|(span, class)| (&source[span.start..span.end], class)).collect()
+    }
+
+    #[test]
+    fn test_highlight_code() {
+        assert_eq!(classes("// add\nfn add(a: i32) -> i32 { a + 1 } \"s\""), vec![
+            ("// add", HighlightClass::Comment),
+            ("fn", HighlightClass::Keyword),
+            ("add", HighlightClass::Identifier),
+            ("(", HighlightClass::Punctuation),
+            ("a", HighlightClass::Identifier),
+            (":", HighlightClass::Punctuation),
+            ("i32", HighlightClass::Identifier),
+            (")", HighlightClass::Punctuation),
+            ("->", HighlightClass::Punctuation),
+            ("i32", HighlightClass::Identifier),
+            ("{", HighlightClass::Punctuation),
+            ("a", HighlightClass::Identifier),
+            ("+", HighlightClass::Punctuation),
+            ("1", HighlightClass::Number),
+            ("}", HighlightClass::Punctuation),
+            ("\"s\"", HighlightClass::String),
+        ]);
+    }
+
+    #[test]
+    fn test_highlight_jsx() {
+        let source = "return <div class=\"a\">Hi {n > 1} <br/></div>; if a <b {}";
+        assert_eq!(classes(source), vec![
+            ("return", HighlightClass::Keyword),
+            ("<", HighlightClass::JsxTag),
+            ("div", HighlightClass::JsxTag),
+            ("class", HighlightClass::Identifier),
+            ("=", HighlightClass::Punctuation),
+            ("\"a\"", HighlightClass::String),
+            (">", HighlightClass::JsxTag),
+            ("Hi ", HighlightClass::JsxText),
+            ("{", HighlightClass::Punctuation),
+            ("n", HighlightClass::Identifier),
+            (">", HighlightClass::Punctuation),
+            ("1", HighlightClass::Number),
+            ("}", HighlightClass::Punctuation),
+            (" ", HighlightClass::JsxText),
+            ("<", HighlightClass::JsxTag),
+            ("br", HighlightClass::JsxTag),
+            ("/>", HighlightClass::JsxTag),
+            ("<", HighlightClass::JsxTag),
+            ("/", HighlightClass::JsxTag),
+            ("div", HighlightClass::JsxTag),
+            (">", HighlightClass::JsxTag),
+            (";", HighlightClass::Punctuation),
+            ("if", HighlightClass::Keyword),
+            ("a", HighlightClass::Identifier),
+            ("<", HighlightClass::Punctuation),
+            ("b", HighlightClass::Identifier),
+            ("{", HighlightClass::Punctuation),
+            ("}", HighlightClass::Punctuation),
+        ]);
+    }
+
+    #[test]
+    fn test_highlight_generic_component() {
+        let classes = classes("<List<Vec<User>> items={a > b} />");
+        let tags: Vec<&str> = classes.iter().filter(|(_, class)| *class == HighlightClass::JsxTag).map(|(text, _)| *text).collect();
+        assert_eq!(tags, vec!["<", "List", "/>"]);
+        assert!(classes.contains(&("items", HighlightClass::Identifier)));
+        assert!(classes.contains(&("b", HighlightClass::Identifier)));
+    }
+
+    #[test]
+    fn test_highlight_void_element() {
+        let classes = classes("return <p>a<br>b</p>; let x = <hr>; x");
+        assert!(classes.contains(&("b", HighlightClass::JsxText)));
+        assert_eq!(classes[classes.len() - 5..], [
+            ("<", HighlightClass::JsxTag),
+            ("hr", HighlightClass::JsxTag),
+            (">", HighlightClass::JsxTag),
+            (";", HighlightClass::Punctuation),
+            ("x", HighlightClass::Identifier),
+        ]);
+    }
+
+    #[test]
+    fn test_highlight_css() {
+        assert_eq!(classes("let s = css! { .a { color: red; } }; x"), vec![
+            ("let", HighlightClass::Keyword),
+            ("s", HighlightClass::Identifier),
+            ("=", HighlightClass::Punctuation),
+            ("css!", HighlightClass::Keyword),
+            ("{", HighlightClass::Punctuation),
+            (".a ", HighlightClass::CssSelector),
+            ("{", HighlightClass::Punctuation),
+            ("color", HighlightClass::CssProperty),
+            (":", HighlightClass::Punctuation),
+            ("red", HighlightClass::CssValue),
+            (";", HighlightClass::Punctuation),
+            ("}", HighlightClass::Punctuation),
+            ("}", HighlightClass::Punctuation),
+            (";", HighlightClass::Punctuation),
+            ("x", HighlightClass::Identifier),
+        ]);
+    }
+
+    #[test]
+    fn test_highlight_errors() {
+        assert_eq!(classes("a ¤ 'xy' \u{200B}"), vec![
+            ("a", HighlightClass::Identifier),
+            ("¤", HighlightClass::Error),
+            ("'xy'", HighlightClass::Error),
+            ("\u{200B}", HighlightClass::Error),
+        ]);
+    }
+
+    proptest! {
+        #[test]
+        fn test_highlight_any_input(source in any::<String>()) {
+            let mut end = 0;
+            for (span, _) in highlight(&source) {
+                prop_assert!(span.start >= end && span.end <= source.len());
+                end = span.end;
+            }
+        }
+    }
+}
@@ -9,6 +9,10 @@ use tokio::sync::mpsc;
 use futures_util::{StreamExt, SinkExt};
 use tokio_tungstenite::tungstenite::Message;
 
+mod rebuild;
+
+pub use rebuild::{rebuild, BuildHashes, Rebuild, RebuildKind, UTILITY_CSS};
+
 /// HMR Server configuration
 pub struct HmrConfig {
     pub watch_paths: Vec<PathBuf>,
@@ -34,6 +38,7 @@ pub struct HmrServer {
     compiler: Arc<Compiler>,
     clients: Arc<Mutex<Vec<mpsc::UnboundedSender<Message>>>>,
     last_compile: Arc<Mutex<Option<Vec<u8>>>>,
+    last_hashes: Arc<Mutex<Option<BuildHashes>>>,
 }
 
 /// HMR Update message
@@ -46,7 +51,7 @@ pub struct HmrUpdate {
     pub css_content: Option<String>,
 }
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub enum UpdateType {
     WasmUpdate,
     CssUpdate,
@@ -61,6 +66,7 @@ impl HmrServer {
             compiler: Arc::new(Compiler::new()),
             clients: Arc::new(Mutex::new(Vec::new())),
             last_compile: Arc::new(Mutex::new(None)),
+            last_hashes: Arc::new(Mutex::new(None)),
         }
     }
 
@@ -81,6 +87,7 @@ impl HmrServer {
         let clients_for_file_handler = self.clients.clone(); // Clone for second spawn
         let compiler = self.compiler.clone();
         let last_compile = self.last_compile.clone();
+        let last_hashes = self.last_hashes.clone();
         let preserve_state = self.config.preserve_state;
 
         // Spawn WebSocket connection handler
@@ -140,6 +147,7 @@ impl HmrServer {
                 match Self::handle_file_change(
                     &compiler,
                     &last_compile,
+                    &last_hashes,
                     &clients_for_file_handler,
                     event,
                     preserve_state,
@@ -193,6 +201,7 @@ impl HmrServer {
     async fn handle_file_change(
         compiler: &Arc<Compiler>,
         last_compile: &Arc<Mutex<Option<Vec<u8>>>>,
+        last_hashes: &Arc<Mutex<Option<BuildHashes>>>,
         clients: &Arc<Mutex<Vec<mpsc::UnboundedSender<Message>>>>,
         file_path: PathBuf,
         preserve_state: bool,
@@ -201,36 +210,44 @@ impl HmrServer {
         let content = std::fs::read_to_string(&file_path)
             .map_err(|e| CompileError::LexerError(format!("Failed to read file: {}", e)))?;
 
-        // Compile
-        let wasm_bytes = compiler.compile_source(&content, BuildTarget::Client)?;
-
-        // Store last compile
-        *last_compile.lock().unwrap() = Some(wasm_bytes.clone());
-
         // Check if it's a CSS file
         let is_css = file_path.extension().and_then(|s| s.to_str()) == Some("css");
 
+        // Emit JS and CSS separately to see which of them changed
+        let (update_type, css_content) = if is_css {
+            (UpdateType::CssUpdate, Some(content.clone()))
+        } else {
+            let mut lexer = crate::lexer::Lexer::new(content.clone());
+            let program = crate::parser::Parser::new(&mut lexer).parse_program()?;
+            let output = rebuild(&program)?;
+            let kind = RebuildKind::classify(last_hashes.lock().unwrap().as_ref(), &output.hashes);
+            *last_hashes.lock().unwrap() = Some(output.hashes);
+            match kind.update_type(preserve_state) {
+                None => return Ok(()),
+                Some(UpdateType::CssUpdate) => (UpdateType::CssUpdate, Some(output.css)),
+                Some(update_type) => (update_type, None),
+            }
+        };
+
+        // Compile
+        if update_type != UpdateType::CssUpdate {
+            let wasm_bytes = compiler.compile_source(&content, BuildTarget::Client)?;
+
+            // Store last compile
+            *last_compile.lock().unwrap() = Some(wasm_bytes);
+        }
+
         // Create update message
         let update = HmrUpdate {
-            update_type: if is_css {
-                UpdateType::CssUpdate
-            } else if preserve_state {
-                UpdateType::WasmUpdate
-            } else {
-                UpdateType::FullReload
-            },
-            file_path: file_path.to_string_lossy().to_string(),
-            timestamp: current_timestamp(),
-            wasm_url: if !is_css {
+            wasm_url: if update_type == UpdateType::WasmUpdate {
                 Some(format!("/hmr/wasm?t={}", current_timestamp()))
             } else {
                 None
             },
-            css_content: if is_css {
-                Some(content)
-            } else {
-                None
-            },
+            update_type,
+            file_path: file_path.to_string_lossy().to_string(),
+            timestamp: current_timestamp(),
+            css_content,
         };
 
         // Broadcast to all clients
@@ -0,0 +1,198 @@
+// Granular watch-mode rebuilds: CSS and JS are emitted separately and
+// hashed, so a rebuild only rewrites (and hot-swaps) the part that changed
+
+use super::UpdateType;
+use crate::ast::Program;
+use crate::cache::compute_hash;
+use crate::codegen::CodeGenerator;
+use crate::errors::CompileError;
+use crate::js_emitter::JSEmitter;
+use crate::{utility_config, utility_generator};
+use std::collections::BTreeMap;
+
+/// Key for utility classes in `BuildHashes::css`
+pub const UTILITY_CSS: &str = "(utilities)";
+
+/// Content hashes of one build
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BuildHashes {
+    /// Server and client bundles together
+    pub js: u64,
+    /// CSS per owning item (component, function, style or theme block)
+    pub css: BTreeMap<String, u64>,
+}
+
+/// What a rebuild changed compared to the previous one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RebuildKind {
+    Unchanged,
+    /// Only CSS changed: swap the stylesheet in place
+    StyleOnly,
+    /// Only JS changed: styles.css stays as is and is not refreshed
+    ScriptOnly,
+    /// Both changed, or there is nothing to compare against
+    Full,
+}
+
+impl RebuildKind {
+    pub fn classify(previous: Option<&BuildHashes>, next: &BuildHashes) -> Self {
+        let Some(previous) = previous else {
+            return RebuildKind::Full;
+        };
+        match (previous.js != next.js, previous.css != next.css) {
+            (false, false) => RebuildKind::Unchanged,
+            (false, true) => RebuildKind::StyleOnly,
+            (true, false) => RebuildKind::ScriptOnly,
+            (true, true) => RebuildKind::Full,
+        }
+    }
+
+    pub fn writes_js(self) -> bool {
+        matches!(self, RebuildKind::ScriptOnly | RebuildKind::Full)
+    }
+
+    pub fn writes_css(self) -> bool {
+        matches!(self, RebuildKind::StyleOnly | RebuildKind::Full)
+    }
+
+    /// The hot update to send, if any
+    pub fn update_type(self, preserve_state: bool) -> Option<UpdateType> {
+        match self {
+            RebuildKind::Unchanged => None,
+            RebuildKind::StyleOnly => Some(UpdateType::CssUpdate),
+            RebuildKind::ScriptOnly if preserve_state => Some(UpdateType::WasmUpdate),
+            RebuildKind::ScriptOnly | RebuildKind::Full => Some(UpdateType::FullReload),
+        }
+    }
+}
+
+/// Output of `rebuild`
+pub struct Rebuild {
+    pub server_js: String,
+    pub client_js: String,
+    /// Utility classes followed by component, style and theme CSS
+    pub css: String,
+    pub hashes: BuildHashes,
+}
+
+/// Emit JS and CSS for a parsed program, hashing each separately
+pub fn rebuild(program: &Program) -> Result<Rebuild, CompileError> {
+    let emitter = JSEmitter::new(program);
+    let server_js = emitter.generate_server_js();
+    let client_js = emitter.generate_client_js();
+
+    let styles = CodeGenerator::css_only(program)?;
+    let mut utility_gen = utility_generator::UtilityGenerator::new(utility_config::UtilityConfig::load());
+    utility_gen.scan_for_utilities(program);
+    let utility_css = utility_gen.generate_css();
+
+    let mut css_hashes: BTreeMap<String, u64> = styles
+        .css_by_owner()
+        .iter()
+        .map(|(owner, css)| (owner.clone(), compute_hash(css.as_bytes())))
+        .collect();
+    if !utility_css.is_empty() {
+        css_hashes.insert(UTILITY_CSS.to_string(), compute_hash(utility_css.as_bytes()));
+    }
+
+    let component_css = styles.get_css_ast().to_css();
+    let css = if utility_css.is_empty() {
+        component_css
+    } else {
+        format!("{}\n{}", utility_css, component_css)
+    };
+
+    let hashes = BuildHashes {
+        js: compute_hash(format!("{}\0{}", server_js, client_js).as_bytes()),
+        css: css_hashes,
+    };
+    Ok(Rebuild { server_js, client_js, css, hashes })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    fn build(source: &str) -> Rebuild {
+        let mut lexer = Lexer::new(source.to_string());
+        let mut parser = Parser::new(&mut lexer);
+        let program = parser.parse_program().expect("parse");
+        rebuild(&program).expect("rebuild")
+    }
+
+    fn app(label: &str, color: &str) -> String {
+        format!(
+            r#"
+fn label() -> String {{
+    return "{}";
+}}
+
+component Card() {{
+    let styles = css! {{
+        .card {{ color: {}; }}
+    }};
+    return <div class="card">{{label()}}</div>;
+}}
+
+component Footer() {{
+    let styles = css! {{
+        .footer {{ margin: 4px; }}
+    }};
+    return <footer class="footer">"bye"</footer>;
+}}
+"#,
+            label, color
+        )
+    }
+
+    #[test]
+    fn test_js_only_edit_keeps_css_hashes() {
+        let before = build(&app("hello", "red"));
+        let after = build(&app("goodbye", "red"));
+
+        assert_ne!(before.hashes.js, after.hashes.js);
+        assert_eq!(before.hashes.css, after.hashes.css);
+        assert!(before.hashes.css.contains_key("Card"));
+        assert_eq!(before.css, after.css);
+
+        let kind = RebuildKind::classify(Some(&before.hashes), &after.hashes);
+        assert_eq!(kind, RebuildKind::ScriptOnly);
+        assert!(!kind.writes_css());
+        assert_eq!(kind.update_type(true), Some(UpdateType::WasmUpdate));
+    }
+
+    #[test]
+    fn test_css_only_edit_is_style_only_update() {
+        let before = build(&app("hello", "red"));
+        let after = build(&app("hello", "blue"));
+
+        assert_eq!(before.hashes.js, after.hashes.js);
+        assert_ne!(before.hashes.css["Card"], after.hashes.css["Card"]);
+        assert_eq!(before.hashes.css["Footer"], after.hashes.css["Footer"]);
+        assert!(after.css.contains("blue"));
+
+        let kind = RebuildKind::classify(Some(&before.hashes), &after.hashes);
+        assert_eq!(kind, RebuildKind::StyleOnly);
+        assert!(kind.writes_css() && !kind.writes_js());
+        assert_eq!(kind.update_type(true), Some(UpdateType::CssUpdate));
+
+        assert_eq!(RebuildKind::classify(Some(&after.hashes), &after.hashes), RebuildKind::Unchanged);
+        assert_eq!(RebuildKind::Unchanged.update_type(true), None);
+    }
+
+    #[test]
+    fn test_combined_edit_falls_back_to_full_reload() {
+        let before = build(&app("hello", "red"));
+        let after = build(&app("goodbye", "blue"));
+
+        let kind = RebuildKind::classify(Some(&before.hashes), &after.hashes);
+        assert_eq!(kind, RebuildKind::Full);
+        assert!(kind.writes_css() && kind.writes_js());
+        assert_eq!(kind.update_type(true), Some(UpdateType::FullReload));
+
+        // Nothing to compare against on the first build
+        assert_eq!(RebuildKind::classify(None, &after.hashes), RebuildKind::Full);
+    }
+}
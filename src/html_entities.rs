@@ -0,0 +1,245 @@
+// HTML Entities - `&amp;`, `&nbsp;` and `&#169;` in JSX text
+//
+// The lexer decodes references in JSX text, so the AST holds the characters
+// themselves and every output escapes them for its own context. Unknown
+// references, like `&foo;`, are kept as written; the parser records where each
+// one is on its element, and they're warned about here, after parsing, so
+// cached ASTs warn too. An `&` that doesn't start a reference at
+// all, as in `Tom & Jerry`, is a plain ampersand.
+
+use crate::ast::{JsxElement, JsxUnknownEntity, Position, Program};
+use crate::diagnostics::{Diagnostic, SourceLocation};
+use crate::visit::{walk_jsx_element, Visit};
+
+/// The HTML 4 named character references, plus `&apos;`, sorted by name
+const ENTITIES: &[(&str, char)] = &[
+    ("AElig", '\u{C6}'), ("Aacute", '\u{C1}'), ("Acirc", '\u{C2}'), ("Agrave", '\u{C0}'), ("Alpha", '\u{391}'),
+    ("Aring", '\u{C5}'), ("Atilde", '\u{C3}'), ("Auml", '\u{C4}'), ("Beta", '\u{392}'), ("Ccedil", '\u{C7}'),
+    ("Chi", '\u{3A7}'), ("Dagger", '\u{2021}'), ("Delta", '\u{394}'), ("ETH", '\u{D0}'), ("Eacute", '\u{C9}'),
+    ("Ecirc", '\u{CA}'), ("Egrave", '\u{C8}'), ("Epsilon", '\u{395}'), ("Eta", '\u{397}'), ("Euml", '\u{CB}'),
+    ("Gamma", '\u{393}'), ("Iacute", '\u{CD}'), ("Icirc", '\u{CE}'), ("Igrave", '\u{CC}'), ("Iota", '\u{399}'),
+    ("Iuml", '\u{CF}'), ("Kappa", '\u{39A}'), ("Lambda", '\u{39B}'), ("Mu", '\u{39C}'), ("Ntilde", '\u{D1}'),
+    ("Nu", '\u{39D}'), ("OElig", '\u{152}'), ("Oacute", '\u{D3}'), ("Ocirc", '\u{D4}'), ("Ograve", '\u{D2}'),
+    ("Omega", '\u{3A9}'), ("Omicron", '\u{39F}'), ("Oslash", '\u{D8}'), ("Otilde", '\u{D5}'), ("Ouml", '\u{D6}'),
+    ("Phi", '\u{3A6}'), ("Pi", '\u{3A0}'), ("Prime", '\u{2033}'), ("Psi", '\u{3A8}'), ("Rho", '\u{3A1}'),
+    ("Scaron", '\u{160}'), ("Sigma", '\u{3A3}'), ("THORN", '\u{DE}'), ("Tau", '\u{3A4}'), ("Theta", '\u{398}'),
+    ("Uacute", '\u{DA}'), ("Ucirc", '\u{DB}'), ("Ugrave", '\u{D9}'), ("Upsilon", '\u{3A5}'), ("Uuml", '\u{DC}'),
+    ("Xi", '\u{39E}'), ("Yacute", '\u{DD}'), ("Yuml", '\u{178}'), ("Zeta", '\u{396}'), ("aacute", '\u{E1}'),
+    ("acirc", '\u{E2}'), ("acute", '\u{B4}'), ("aelig", '\u{E6}'), ("agrave", '\u{E0}'), ("alefsym", '\u{2135}'),
+    ("alpha", '\u{3B1}'), ("amp", '\u{26}'), ("and", '\u{2227}'), ("ang", '\u{2220}'), ("apos", '\u{27}'),
+    ("aring", '\u{E5}'), ("asymp", '\u{2248}'), ("atilde", '\u{E3}'), ("auml", '\u{E4}'), ("bdquo", '\u{201E}'),
+    ("beta", '\u{3B2}'), ("brvbar", '\u{A6}'), ("bull", '\u{2022}'), ("cap", '\u{2229}'), ("ccedil", '\u{E7}'),
+    ("cedil", '\u{B8}'), ("cent", '\u{A2}'), ("chi", '\u{3C7}'), ("circ", '\u{2C6}'), ("clubs", '\u{2663}'),
+    ("cong", '\u{2245}'), ("copy", '\u{A9}'), ("crarr", '\u{21B5}'), ("cup", '\u{222A}'), ("curren", '\u{A4}'),
+    ("dArr", '\u{21D3}'), ("dagger", '\u{2020}'), ("darr", '\u{2193}'), ("deg", '\u{B0}'), ("delta", '\u{3B4}'),
+    ("diams", '\u{2666}'), ("divide", '\u{F7}'), ("eacute", '\u{E9}'), ("ecirc", '\u{EA}'), ("egrave", '\u{E8}'),
+    ("empty", '\u{2205}'), ("emsp", '\u{2003}'), ("ensp", '\u{2002}'), ("epsilon", '\u{3B5}'), ("equiv", '\u{2261}'),
+    ("eta", '\u{3B7}'), ("eth", '\u{F0}'), ("euml", '\u{EB}'), ("euro", '\u{20AC}'), ("exist", '\u{2203}'),
+    ("fnof", '\u{192}'), ("forall", '\u{2200}'), ("frac12", '\u{BD}'), ("frac14", '\u{BC}'), ("frac34", '\u{BE}'),
+    ("frasl", '\u{2044}'), ("gamma", '\u{3B3}'), ("ge", '\u{2265}'), ("gt", '\u{3E}'), ("hArr", '\u{21D4}'),
+    ("harr", '\u{2194}'), ("hearts", '\u{2665}'), ("hellip", '\u{2026}'), ("iacute", '\u{ED}'), ("icirc", '\u{EE}'),
+    ("iexcl", '\u{A1}'), ("igrave", '\u{EC}'), ("image", '\u{2111}'), ("infin", '\u{221E}'), ("int", '\u{222B}'),
+    ("iota", '\u{3B9}'), ("iquest", '\u{BF}'), ("isin", '\u{2208}'), ("iuml", '\u{EF}'), ("kappa", '\u{3BA}'),
+    ("lArr", '\u{21D0}'), ("lambda", '\u{3BB}'), ("lang", '\u{2329}'), ("laquo", '\u{AB}'), ("larr", '\u{2190}'),
+    ("lceil", '\u{2308}'), ("ldquo", '\u{201C}'), ("le", '\u{2264}'), ("lfloor", '\u{230A}'), ("lowast", '\u{2217}'),
+    ("loz", '\u{25CA}'), ("lrm", '\u{200E}'), ("lsaquo", '\u{2039}'), ("lsquo", '\u{2018}'), ("lt", '\u{3C}'),
+    ("macr", '\u{AF}'), ("mdash", '\u{2014}'), ("micro", '\u{B5}'), ("middot", '\u{B7}'), ("minus", '\u{2212}'),
+    ("mu", '\u{3BC}'), ("nabla", '\u{2207}'), ("nbsp", '\u{A0}'), ("ndash", '\u{2013}'), ("ne", '\u{2260}'),
+    ("ni", '\u{220B}'), ("not", '\u{AC}'), ("notin", '\u{2209}'), ("nsub", '\u{2284}'), ("ntilde", '\u{F1}'),
+    ("nu", '\u{3BD}'), ("oacute", '\u{F3}'), ("ocirc", '\u{F4}'), ("oelig", '\u{153}'), ("ograve", '\u{F2}'),
+    ("oline", '\u{203E}'), ("omega", '\u{3C9}'), ("omicron", '\u{3BF}'), ("oplus", '\u{2295}'), ("or", '\u{2228}'),
+    ("ordf", '\u{AA}'), ("ordm", '\u{BA}'), ("oslash", '\u{F8}'), ("otilde", '\u{F5}'), ("otimes", '\u{2297}'),
+    ("ouml", '\u{F6}'), ("para", '\u{B6}'), ("part", '\u{2202}'), ("permil", '\u{2030}'), ("perp", '\u{22A5}'),
+    ("phi", '\u{3C6}'), ("pi", '\u{3C0}'), ("piv", '\u{3D6}'), ("plusmn", '\u{B1}'), ("pound", '\u{A3}'),
+    ("prime", '\u{2032}'), ("prod", '\u{220F}'), ("prop", '\u{221D}'), ("psi", '\u{3C8}'), ("quot", '\u{22}'),
+    ("rArr", '\u{21D2}'), ("radic", '\u{221A}'), ("rang", '\u{232A}'), ("raquo", '\u{BB}'), ("rarr", '\u{2192}'),
+    ("rceil", '\u{2309}'), ("rdquo", '\u{201D}'), ("real", '\u{211C}'), ("reg", '\u{AE}'), ("rfloor", '\u{230B}'),
+    ("rho", '\u{3C1}'), ("rlm", '\u{200F}'), ("rsaquo", '\u{203A}'), ("rsquo", '\u{2019}'), ("sbquo", '\u{201A}'),
+    ("scaron", '\u{161}'), ("sdot", '\u{22C5}'), ("sect", '\u{A7}'), ("shy", '\u{AD}'), ("sigma", '\u{3C3}'),
+    ("sigmaf", '\u{3C2}'), ("sim", '\u{223C}'), ("spades", '\u{2660}'), ("sub", '\u{2282}'), ("sube", '\u{2286}'),
+    ("sum", '\u{2211}'), ("sup", '\u{2283}'), ("sup1", '\u{B9}'), ("sup2", '\u{B2}'), ("sup3", '\u{B3}'),
+    ("supe", '\u{2287}'), ("szlig", '\u{DF}'), ("tau", '\u{3C4}'), ("there4", '\u{2234}'), ("theta", '\u{3B8}'),
+    ("thetasym", '\u{3D1}'), ("thinsp", '\u{2009}'), ("thorn", '\u{FE}'), ("tilde", '\u{2DC}'), ("times", '\u{D7}'),
+    ("trade", '\u{2122}'), ("uArr", '\u{21D1}'), ("uacute", '\u{FA}'), ("uarr", '\u{2191}'), ("ucirc", '\u{FB}'),
+    ("ugrave", '\u{F9}'), ("uml", '\u{A8}'), ("upsih", '\u{3D2}'), ("upsilon", '\u{3C5}'), ("uuml", '\u{FC}'),
+    ("weierp", '\u{2118}'), ("xi", '\u{3BE}'), ("yacute", '\u{FD}'), ("yen", '\u{A5}'), ("yuml", '\u{FF}'),
+    ("zeta", '\u{3B6}'), ("zwj", '\u{200D}'), ("zwnj", '\u{200C}'),
+];
+
+/// The character a named reference stands for, by its name: `amp` for `&amp;`
+pub fn named(name: &str) -> Option<char> {
+    ENTITIES.binary_search_by(|(entity, _)| entity.cmp(&name)).ok().map(|index| ENTITIES[index].1)
+}
+
+/// Each reference in `text`, as written from `&` to `;`, with the character it
+/// stands for; `None` for one that stands for nothing, like `&foo;` or `&#0;`
+fn references(text: &str) -> impl Iterator<Item = (usize, &str, Option<char>)> {
+    text.match_indices('&').filter_map(move |(start, _)| {
+        let rest = &text[start + 1..];
+        let end = rest.find(|ch: char| !ch.is_ascii_alphanumeric() && ch != '#')?;
+        let body = &rest[..end];
+        if !rest[end..].starts_with(';') || body.is_empty() {
+            return None;
+        }
+        let decoded = if let Some(number) = body.strip_prefix('#') {
+            let code = match number.strip_prefix(['x', 'X']) {
+                Some(hex) if !hex.is_empty() && hex.chars().all(|ch| ch.is_ascii_hexdigit()) => u32::from_str_radix(hex, 16).ok(),
+                None if !number.is_empty() && number.chars().all(|ch| ch.is_ascii_digit()) => number.parse().ok(),
+                _ => return None,
+            };
+            code.filter(|&code| code != 0).and_then(char::from_u32)
+        } else if body.contains('#') {
+            return None;
+        } else {
+            named(body)
+        };
+        Some((start, &text[start..start + end + 2], decoded))
+    })
+}
+
+/// `text` with its references replaced by the characters they stand for.
+/// Unknown references and a bare `&` are left as they are.
+pub fn decode(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut copied = 0;
+    for (start, reference, ch) in references(text) {
+        if let Some(ch) = ch {
+            decoded.push_str(&text[copied..start]);
+            decoded.push(ch);
+            copied = start + reference.len();
+        }
+    }
+    decoded.push_str(&text[copied..]);
+    decoded
+}
+
+/// References in `text` that stand for no character, as written, each where
+/// it starts in the source when `text` starts at `start`
+pub fn unknown_entities(text: &str, start: Position) -> Vec<JsxUnknownEntity> {
+    references(text)
+        .filter(|(_, _, ch)| ch.is_none())
+        .map(|(offset, reference, _)| {
+            let before = &text[..offset];
+            let position = match before.rfind('\n') {
+                Some(newline) => Position { line: start.line + before.matches('\n').count(), column: before[newline + 1..].chars().count() + 1 },
+                None => Position { line: start.line, column: start.column + before.chars().count() },
+            };
+            JsxUnknownEntity { reference: reference.to_string(), position }
+        })
+        .collect()
+}
+
+/// `text` as JSX source that reads back as `text`: characters JSX text can't
+/// hold, and a non-breaking space that would otherwise look like a space, as
+/// references. An `&` is only written as `&amp;` where it would start one.
+pub fn encode_jsx_text(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for (index, ch) in text.char_indices() {
+        match ch {
+            '&' if references(&text[index..]).next().is_some_and(|(start, _, _)| start == 0) => encoded.push_str("&amp;"),
+            '<' => encoded.push_str("&lt;"),
+            '>' => encoded.push_str("&gt;"),
+            '{' => encoded.push_str("&#123;"),
+            '}' => encoded.push_str("&#125;"),
+            '\u{A0}' => encoded.push_str("&nbsp;"),
+            _ => encoded.push(ch),
+        }
+    }
+    encoded
+}
+
+/// Warn about references in JSX text that stand for no character
+pub fn check_program(program: &Program, file: &str) -> Vec<Diagnostic> {
+    let mut checker = Checker { file, diagnostics: Vec::new() };
+    checker.visit_program(program);
+    checker.diagnostics
+}
+
+struct Checker<'a> {
+    file: &'a str,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Visit for Checker<'_> {
+    fn visit_jsx_element(&mut self, jsx: &JsxElement) {
+        let tag = &jsx.opening_tag;
+        for entity in &jsx.unknown_entities {
+            let location = SourceLocation {
+                file: self.file.to_string(),
+                line: entity.position.line,
+                column: entity.position.column,
+                length: entity.reference.chars().count(),
+            };
+            let escaped = entity.reference.replacen('&', "&amp;", 1);
+            self.diagnostics.push(
+                Diagnostic::warning(format!("unknown HTML entity `{}` in the text of <{}>", entity.reference, tag.name.value))
+                    .at(location)
+                    .with_suggestion(format!("to show it as written, escape the ampersand: `{}`", escaped)),
+            );
+        }
+        walk_jsx_element(self, jsx);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(decode("Terms &amp; Conditions"), "Terms & Conditions");
+        assert_eq!(decode("&copy; &#169; &#xA9; &#XA9;"), "\u{A9} \u{A9} \u{A9} \u{A9}");
+        assert_eq!(decode("a&nbsp;b &lt;b&gt; &apos;&quot;"), "a\u{A0}b <b> '\"");
+        // Plain ampersands and references that stand for nothing stay as written
+        assert_eq!(decode("Tom & Jerry &amp &foo; &#0; &#xD800; &#; &#x;"), "Tom & Jerry &amp &foo; &#0; &#xD800; &#; &#x;");
+        let unknown: Vec<String> = unknown_entities("&foo; & &amp; &#0;", Position::default()).into_iter().map(|entity| entity.reference).collect();
+        assert_eq!(unknown, ["&foo;", "&#0;"]);
+        assert_eq!(named("Aacute"), Some('\u{C1}'));
+        assert_eq!(named("AACUTE"), None);
+    }
+
+    #[test]
+    fn test_encode_jsx_text_reads_back() {
+        for text in ["a < b && c > d", "x\u{A0}y", "{braces}", "&amp; written out", "Tom & Jerry"] {
+            let encoded = encode_jsx_text(text);
+            assert_eq!(decode(&encoded), text, "{}", encoded);
+        }
+        assert_eq!(encode_jsx_text("Tom & Jerry &amp;"), "Tom & Jerry &amp;amp;");
+    }
+
+    #[test]
+    fn test_unknown_entities_warn() {
+        let source = "component App() {\n    return <p>Fish &chips; &amp; &#0;</p>;\n}\n";
+        let mut lexer = Lexer::new(source.to_string());
+        let program = Parser::new(&mut lexer).parse_program().unwrap();
+        let warnings = check_program(&program, "app.jnc");
+        let messages: Vec<&str> = warnings.iter().map(|warning| warning.message.as_str()).collect();
+        assert_eq!(messages, ["unknown HTML entity `&chips;` in the text of <p>", "unknown HTML entity `&#0;` in the text of <p>"]);
+        // Each points at the reference itself
+        let locations: Vec<(&str, usize, usize, usize)> = warnings
+            .iter()
+            .map(|warning| warning.location.as_ref().unwrap())
+            .map(|location| (location.file.as_str(), location.line, location.column, location.length))
+            .collect();
+        assert_eq!(locations, [("app.jnc", 2, 20, 7), ("app.jnc", 2, 34, 4)]);
+        assert_eq!(warnings[0].suggestions, ["to show it as written, escape the ampersand: `&amp;chips;`"]);
+    }
+
+    #[test]
+    fn test_unknown_entity_located_across_lines() {
+        let source = "component App() {\n    return <div>\n        <b>&bold;</b> and\n          then &later;\n    </div>;\n}\n";
+        let mut lexer = Lexer::new(source.to_string());
+        let program = Parser::new(&mut lexer).parse_program().unwrap();
+        let warnings = check_program(&program, "app.jnc");
+        let found: Vec<(&str, usize, usize)> = warnings
+            .iter()
+            .map(|warning| (warning.message.as_str(), warning.location.as_ref().unwrap().line, warning.location.as_ref().unwrap().column))
+            .collect();
+        assert_eq!(found, [
+            ("unknown HTML entity `&later;` in the text of <div>", 4, 16),
+            ("unknown HTML entity `&bold;` in the text of <b>", 3, 12),
+        ]);
+    }
+}
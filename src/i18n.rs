@@ -0,0 +1,298 @@
+// Translatable messages: `t!("Add {count} items", count)` and extraction
+//
+// `jnc i18n-extract` walks the AST collecting every t! message and every bare
+// JSX text long enough to be worth translating into a catalog template (JSON,
+// one entry per message with its source references, like a PO template).
+// Translations live next to it as `<locale>.json` objects mapping each message
+// to its translation; the runtime's `I18n.t` looks them up for the active
+// locale, falling back to the message itself, and fills in `{name}`
+// placeholders from the values the t! call passed.
+
+use crate::ast::{Expression, JsxChild, JsxElement, MacroCall, Program};
+use crate::diagnostics::{Diagnostic, SourceLocation};
+use crate::visit::{walk_jsx_element, walk_macro_call, Visit};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The translation macro
+pub const TRANSLATE_MACRO: &str = "t";
+
+/// Default shortest JSX text (in characters, ignoring surrounding whitespace) that is extracted
+pub const DEFAULT_MIN_TEXT_LENGTH: usize = 2;
+
+/// One translatable message in the catalog template
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Message {
+    /// The source text, also the key translations are looked up by
+    pub id: String,
+    /// `{name}` placeholders in the message, in order of first use
+    pub placeholders: Vec<String>,
+    /// `file:line:column` of every use
+    pub references: Vec<String>,
+    /// Where it came from: `t!` or JSX `text`
+    pub source: &'static str,
+    #[serde(skip)]
+    pub locations: Vec<SourceLocation>,
+}
+
+/// Every message found, in order of first appearance
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Catalog {
+    pub messages: Vec<Message>,
+}
+
+impl Catalog {
+    pub fn message(&self, id: &str) -> Option<&Message> {
+        self.messages.iter().find(|message| message.id == id)
+    }
+
+    fn add(&mut self, id: String, source: &'static str, location: SourceLocation) {
+        let reference = format!("{}:{}:{}", location.file, location.line, location.column);
+        if let Some(message) = self.messages.iter_mut().find(|message| message.id == id) {
+            message.references.push(reference);
+            message.locations.push(location);
+            return;
+        }
+        self.messages.push(Message {
+            placeholders: placeholders(&id),
+            id,
+            references: vec![reference],
+            source,
+            locations: vec![location],
+        });
+    }
+
+    /// The catalog template as pretty-printed JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Collect a program's messages into `catalog`. Returns warnings for t!
+/// calls whose arguments don't supply exactly the message's placeholders.
+pub fn extract(program: &Program, file: &str, min_text_length: usize, catalog: &mut Catalog) -> Vec<Diagnostic> {
+    let mut extractor = Extractor { file, min_text_length, catalog, diagnostics: Vec::new() };
+    extractor.visit_program(program);
+    extractor.diagnostics
+}
+
+/// Warn about translations whose placeholders differ from their message's.
+/// `translations` maps messages to their translation in `locale`.
+pub fn check_translations(catalog: &Catalog, locale: &str, translations: &BTreeMap<String, String>) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for message in &catalog.messages {
+        let Some(translation) = translations.get(&message.id) else {
+            continue;
+        };
+        let expected: BTreeSet<String> = message.placeholders.iter().cloned().collect();
+        let found: BTreeSet<String> = placeholders(translation).into_iter().collect();
+        if expected == found {
+            continue;
+        }
+        let mut diagnostic = Diagnostic::warning(format!(
+            "The `{}` translation of \"{}\" has placeholders {} but the message has {}",
+            locale,
+            message.id,
+            describe(&found),
+            describe(&expected)
+        ))
+        .with_code("W013")
+        .with_suggestion(placeholder_help(&expected, &found));
+        if let Some(location) = message.locations.first() {
+            diagnostic = diagnostic.at(location.clone());
+        }
+        diagnostics.push(diagnostic);
+    }
+    diagnostics
+}
+
+/// Names of the `{name}` placeholders in a message, in order of first use
+pub fn placeholders(message: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut rest = message;
+    while let Some(start) = rest.find('{') {
+        rest = &rest[start + 1..];
+        let Some(end) = rest.find('}') else {
+            break;
+        };
+        let name = &rest[..end];
+        if !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_') && !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+        rest = &rest[end + 1..];
+    }
+    names
+}
+
+/// Placeholder name each t! value is passed as: a variable's name or a
+/// field's name, otherwise its position
+pub fn argument_names(call: &MacroCall) -> Vec<String> {
+    call.arguments
+        .iter()
+        .skip(1)
+        .enumerate()
+        .map(|(index, arg)| match arg {
+            Expression::Identifier(ident) => ident.value.clone(),
+            Expression::FieldAccess(access) => access.field.value.clone(),
+            _ => index.to_string(),
+        })
+        .collect()
+}
+
+fn describe(names: &BTreeSet<String>) -> String {
+    if names.is_empty() {
+        return "none".to_string();
+    }
+    names.iter().map(|name| format!("`{{{}}}`", name)).collect::<Vec<_>>().join(", ")
+}
+
+fn placeholder_help(expected: &BTreeSet<String>, found: &BTreeSet<String>) -> String {
+    let missing: BTreeSet<String> = expected.difference(found).cloned().collect();
+    let extra: BTreeSet<String> = found.difference(expected).cloned().collect();
+    match (missing.is_empty(), extra.is_empty()) {
+        (false, true) => format!("add {}", describe(&missing)),
+        (true, false) => format!("remove {}; nothing fills it in", describe(&extra)),
+        _ => format!("use {} instead of {}", describe(&missing), describe(&extra)),
+    }
+}
+
+fn unquote(text: &str) -> &str {
+    text.strip_prefix('"').and_then(|text| text.strip_suffix('"')).unwrap_or(text)
+}
+
+struct Extractor<'a> {
+    file: &'a str,
+    min_text_length: usize,
+    catalog: &'a mut Catalog,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Extractor<'_> {
+    fn location(&self, ident: &crate::ast::Identifier) -> SourceLocation {
+        SourceLocation { file: self.file.to_string(), ..SourceLocation::from_identifier(ident) }
+    }
+}
+
+impl Visit for Extractor<'_> {
+    fn visit_macro_call(&mut self, call: &MacroCall) {
+        if call.name.value == TRANSLATE_MACRO {
+            if let Some(Expression::StringLiteral(message)) = call.arguments.first() {
+                let location = self.location(&call.name);
+                let expected: BTreeSet<String> = placeholders(message).into_iter().collect();
+                let passed: BTreeSet<String> = argument_names(call).into_iter().collect();
+                if expected != passed {
+                    self.diagnostics.push(
+                        Diagnostic::warning(format!(
+                            "t! message \"{}\" has placeholders {} but is passed {}",
+                            message,
+                            describe(&expected),
+                            describe(&passed)
+                        ))
+                        .at(location.clone())
+                        .with_code("W013")
+                        .with_suggestion(placeholder_help(&expected, &passed)),
+                    );
+                }
+                self.catalog.add(message.clone(), "t!", location);
+            }
+        }
+        walk_macro_call(self, call);
+    }
+
+    fn visit_jsx_element(&mut self, jsx: &JsxElement) {
+        // Runs of text between tags and `{expressions}` are one message each
+        if !matches!(jsx.tag_name(), "style" | "script") {
+            let mut runs: Vec<String> = vec![String::new()];
+            for child in &jsx.children {
+                match child {
+                    // String literal children keep their quotes
+                    JsxChild::Text(text) => runs.last_mut().unwrap().push_str(unquote(text)),
+                    _ => runs.push(String::new()),
+                }
+            }
+            for run in runs {
+                let text = run.split_whitespace().collect::<Vec<_>>().join(" ");
+                if text.chars().count() >= self.min_text_length && text.chars().any(char::is_alphabetic) {
+                    let location = self.location(&jsx.opening_tag.name);
+                    self.catalog.add(text, "text", location);
+                }
+            }
+        }
+        walk_jsx_element(self, jsx);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    const FIXTURE: &str = r#"component Cart(count: i32) {
+    return <div>
+        <h1>Your cart</h1>
+        <p>{t!("Add {count} items", count)}</p>
+        <button>"OK"</button>
+        <span>"x"</span>
+        <p>{t!("Add {count} items", count)}</p>
+    </div>;
+}
+"#;
+
+    fn extract_source(source: &str) -> (Catalog, Vec<Diagnostic>) {
+        let mut lexer = Lexer::new(source.to_string());
+        let program = Parser::new(&mut lexer).parse_program().expect("parse");
+        let mut catalog = Catalog::default();
+        let warnings = extract(&program, "cart.jnc", DEFAULT_MIN_TEXT_LENGTH, &mut catalog);
+        (catalog, warnings)
+    }
+
+    #[test]
+    fn test_extraction_catalog() {
+        let (catalog, warnings) = extract_source(FIXTURE);
+        assert!(warnings.is_empty());
+
+        let ids: Vec<&str> = catalog.messages.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, vec!["Your cart", "Add {count} items", "OK"]);
+
+        let add = catalog.message("Add {count} items").unwrap();
+        assert_eq!(add.placeholders, vec!["count"]);
+        assert_eq!(add.source, "t!");
+        assert_eq!(add.references.len(), 2);
+        assert!(add.references[0].starts_with("cart.jnc:4:"), "{:?}", add.references);
+        assert!(add.references[1].starts_with("cart.jnc:7:"), "{:?}", add.references);
+
+        let json: serde_json::Value = serde_json::from_str(&catalog.to_json()).unwrap();
+        assert_eq!(json["messages"][0]["id"], "Your cart");
+        assert_eq!(json["messages"][0]["source"], "text");
+        assert_eq!(json["messages"][1]["placeholders"][0], "count");
+        assert!(json["messages"][0].get("locations").is_none());
+    }
+
+    #[test]
+    fn test_placeholder_mismatch_warnings() {
+        let (catalog, warnings) = extract_source(FIXTURE);
+        assert!(warnings.is_empty());
+
+        let translations = BTreeMap::from([
+            ("Your cart".to_string(), "Votre panier".to_string()),
+            ("Add {count} items".to_string(), "Ajouter {nombre} articles".to_string()),
+        ]);
+        let warnings = check_translations(&catalog, "fr", &translations);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].code.as_deref(), Some("W013"));
+        assert!(warnings[0].message.contains("`{nombre}`"), "{}", warnings[0].message);
+        assert_eq!(warnings[0].location.as_ref().unwrap().line, 4);
+
+        // A call that doesn't pass what its message uses
+        let (_, warnings) = extract_source("fn label(total: i32) -> String {\n    return t!(\"{count} left\", total);\n}\n");
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].message.contains("passed `{total}`"), "{}", warnings[0].message);
+    }
+
+    #[test]
+    fn test_placeholders() {
+        assert_eq!(placeholders("Hi {name}, {count} new ({count})"), vec!["name", "count"]);
+        assert!(placeholders("{ not one } {}").is_empty());
+    }
+}
@@ -584,6 +584,77 @@ mod tests {
                 "should include component function");
     }
 
+    #[test]
+    fn test_jsx_match_arms_returning_elements() {
+        let source = r#"
+            enum Status {
+                Ok,
+                Err(String),
+            }
+
+            component Badge(color: String) {
+                return <span class={color}>ok</span>;
+            }
+
+            component ErrorBox(msg: String) {
+                return <p class="error">{msg}</p>;
+            }
+
+            component StatusView(status: Status, retried: Status) {
+                return <div>
+                    {match status { Status::Ok => <Badge color="green" />, Status::Err(e) => <ErrorBox msg={e} />, }}
+                    {match status { Status::Ok => <Badge color="blue" />, Status::Err(e) => <ErrorBox msg={e} /> }} checked, {match retried {
+                        Status::Ok => { <Badge color="green" /> },
+                        Status::Err(e) => {
+                            let shown = e;
+                            <ErrorBox msg={shown} />
+                        },
+                    }}
+                    <footer>done</footer>
+                </div>;
+            }
+        "#;
+
+        let (_, client_js) = compile_source(source).expect("match arms returning JSX should compile");
+        assert!(client_js.contains("h(Badge, { color: \"green\" })"), "got:\n{}", client_js);
+        assert!(client_js.contains("h(Badge, { color: \"blue\" })"));
+        assert!(client_js.contains("h(ErrorBox, { msg: e })"));
+        assert!(client_js.contains("h(ErrorBox, { msg: shown })"));
+        // Text after a `/>` and `}}` keeps its leading space, and the element after the match is a child of the <div>
+        assert!(client_js.contains("\" checked, \""), "got:\n{}", client_js);
+        assert!(client_js.contains("h('footer', null, \"done\")"));
+    }
+
+    #[test]
+    fn test_jsx_elements_before_delimiters() {
+        let source = r#"
+            component Stat(value: i32) {
+                return <b>{value}</b>;
+            }
+
+            component Stats(n: i32) {
+                let stats = [ <Stat value={n} />, <Stat value={2} />, <i>three</i> ];
+                let first = if n > 0 { let shown = <Stat value={n} />; shown } else { <i>none</i> };
+                return <div>
+                    {stats}
+                    {first}
+                    <p>Done (3/4)</p>
+                    <p><Stat value={n} />, then <b>more</b>; ok (<Stat value={1} />)</p>
+                </div>;
+            }
+        "#;
+
+        let (_, client_js) = compile_source(source).expect("elements followed by delimiters should compile");
+        assert!(client_js.contains("let stats = [h(Stat, { value: n }), h(Stat, { value: 2 }), h('i', null, \"three\")];"), "got:\n{}", client_js);
+        assert!(client_js.contains("let shown = h(Stat, { value: n });"), "got:\n{}", client_js);
+        assert!(client_js.contains("h('p', null, \"Done (3/4)\")"));
+        assert!(
+            client_js.contains("h('p', null, h(Stat, { value: n }), \", then \", h('b', null, \"more\"), \"; ok (\", h(Stat, { value: 1 }), \")\")"),
+            "got:\n{}",
+            client_js
+        );
+    }
+
     // =============================================================================
     // Additional Control Flow Tests (8 tests)
     // =============================================================================
@@ -657,6 +728,32 @@ mod tests {
                 "should generate while loop");
     }
 
+    #[test]
+    fn test_loop_with_break_and_continue() {
+        let source = r#"
+            fn main() {
+                let mut count = 0;
+                loop {
+                    count = count + 1;
+                    if count == 2 {
+                        continue;
+                    }
+                    if count > 5 {
+                        break;
+                    }
+                }
+            }
+        "#;
+
+        let result = compile_source(source);
+        assert!(result.is_ok(), "loop with break and continue should compile successfully");
+
+        let (_, client_js) = result.unwrap();
+        assert!(client_js.contains("while (true)"), "should generate an endless loop");
+        assert!(client_js.contains("break"), "should generate break");
+        assert!(client_js.contains("continue"), "should generate continue");
+    }
+
     #[test]
     fn test_nested_loops_with_conditions() {
         let source = r#"
@@ -2265,6 +2362,47 @@ mod tests {
                 "should generate trait method");
     }
 
+    #[test]
+    fn test_impl_with_self_and_self_type() {
+        let source = r#"
+            struct Counter {
+                count: i32,
+            }
+
+            impl Counter {
+                fn new() -> Self {
+                    Self { count: 0 }
+                }
+
+                fn next(self: &Self) -> i32 {
+                    self.count + 1
+                }
+            }
+
+            fn main() {
+                let c = Counter::new();
+                let n = c.next();
+            }
+        "#;
+
+        let result = compile_source(source);
+        assert!(result.is_ok(), "impl using self and Self should compile: {:?}", result.err());
+
+        let (_, client_js) = result.unwrap();
+        assert!(client_js.contains("Counter.new = function()"), "should generate static method");
+        assert!(client_js.contains("Counter.prototype.next = function()"), "should generate instance method");
+        assert!(client_js.contains("self.count"), "should read the field through self");
+    }
+
+    #[test]
+    fn test_self_outside_methods_is_rejected() {
+        let err = compile_source("fn f() {\n    let self = 1;\n}").unwrap_err();
+        assert!(err.to_string().contains("`self` is a reserved keyword and cannot be used as a variable name"), "{}", err);
+
+        let err = compile_source("fn make() -> Self {\n    return 1;\n}").unwrap_err();
+        assert!(err.to_string().contains("`Self` can only be used inside an `impl` or `trait` block"), "{}", err);
+    }
+
     #[test]
     fn test_trait_with_multiple_methods() {
         let source = r#"
@@ -3766,6 +3904,26 @@ mod tests {
         assert!(client_js.contains("double(count.value)"), "Should preserve function call in computed");
     }
 
+    #[test]
+    fn test_reactivity_names_as_variables() {
+        // Only a call makes `computed` a primitive; otherwise it's a variable
+        let source = r#"
+            fn total(price: i32) -> i32 {
+                let computed = price * 2;
+                return computed + 1;
+            }
+
+            fn main() {
+                let count = signal(1);
+                let doubled = computed(() => count.value * 2);
+            }
+        "#;
+
+        let (_, client_js) = compile_source(source).expect("`computed` should be usable as a name");
+        assert!(client_js.contains("return (computed + 1);"), "got:\n{}", client_js);
+        assert!(client_js.contains("let doubled = computed(() => (count.value * 2));"), "got:\n{}", client_js);
+    }
+
     #[test]
     fn test_reactivity_todo_app_pattern() {
         let source = r#"
@@ -4470,4 +4628,970 @@ mod tests {
         assert!(!css.contains("10 px"), "Should not have space before px");
         assert!(!css.contains("5 em"), "Should not have space before em");
     }
+
+    // ============================================================================
+    // CSS AST (build-tool plugins)
+    // ============================================================================
+
+    const CSS_AST_FIXTURE: &str = r#"
+        fn main() {
+            let styles = css! {
+                @keyframes fadeIn {
+                    from { opacity: 0; }
+                    to { opacity: 1; }
+                }
+
+                .card {
+                    padding: 8px 16px;
+
+                    @media (min-width: 768px) {
+                        padding: 24px;
+                    }
+                }
+            };
+        }
+    "#;
+
+    #[test]
+    fn test_css_visitor_doubles_px_values() {
+        use crate::css_ast::{CssStyleDeclaration, CssVisitor};
+        use crate::{BuildTarget, Compiler};
+
+        struct DoublePx;
+        impl CssVisitor for DoublePx {
+            fn visit_declaration(&mut self, decl: &mut CssStyleDeclaration) {
+                decl.value = decl.value
+                    .split(' ')
+                    .map(|part| match part.strip_suffix("px").and_then(|n| n.parse::<f64>().ok()) {
+                        Some(n) => format!("{}px", n * 2.0),
+                        None => part.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ");
+            }
+        }
+
+        let mut module = Compiler::without_optimization()
+            .compile_module(CSS_AST_FIXTURE, BuildTarget::Client)
+            .expect("fixture should compile");
+        module.apply_css_visitor(&mut DoublePx);
+
+        let css = module.css();
+        assert!(css.contains("padding: 16px 32px;"), "got:\n{}", css);
+        assert!(css.contains("    padding: 48px;"), "media query declaration should be rewritten, got:\n{}", css);
+        assert!(css.contains("opacity: 0;"), "non-px values are untouched, got:\n{}", css);
+    }
+
+    #[test]
+    fn test_css_ast_json_snapshot() {
+        use crate::{BuildTarget, Compiler};
+
+        let module = Compiler::without_optimization()
+            .compile_module(CSS_AST_FIXTURE, BuildTarget::Client)
+            .expect("fixture should compile");
+        let expected = r#"{
+  "nodes": [
+    {
+      "type": "at_rule",
+      "name": "keyframes",
+      "prelude": "main_fadeIn_7271e7",
+      "children": [
+        {
+          "type": "rule",
+          "selector": "from",
+          "declarations": [
+            {
+              "property": "opacity",
+              "value": "0"
+            }
+          ],
+          "span": {
+            "line": 5,
+            "column": 21
+          }
+        },
+        {
+          "type": "rule",
+          "selector": "to",
+          "declarations": [
+            {
+              "property": "opacity",
+              "value": "1"
+            }
+          ],
+          "span": {
+            "line": 6,
+            "column": 21
+          }
+        }
+      ],
+      "span": {
+        "line": 4,
+        "column": 28
+      }
+    },
+    {
+      "type": "rule",
+      "selector": ".main_card_1ae74b",
+      "declarations": [
+        {
+          "property": "padding",
+          "value": "8px 16px"
+        }
+      ],
+      "span": {
+        "line": 9,
+        "column": 17
+      }
+    },
+    {
+      "type": "at_rule",
+      "name": "media",
+      "prelude": "(min-width: 768px)",
+      "children": [
+        {
+          "type": "rule",
+          "selector": ".main_card_1ae74b",
+          "declarations": [
+            {
+              "property": "padding",
+              "value": "24px"
+            }
+          ],
+          "span": {
+            "line": 12,
+            "column": 21
+          }
+        }
+      ],
+      "span": {
+        "line": 12,
+        "column": 21
+      }
+    }
+  ]
+}"#;
+        assert_eq!(module.css_ast().to_json(), expected);
+    }
+
+    #[test]
+    fn test_build_manifest_class_mapping() {
+        use crate::build_manifest::BuildManifest;
+        use crate::{BuildTarget, Compiler};
+
+        let source = r#"
+            component Button() {
+                let styles = css! {
+                    .button { color: blue; }
+                    .primary { font-weight: bold; }
+                };
+                return <button>"Save"</button>;
+            }
+        "#;
+
+        let module = Compiler::without_optimization()
+            .compile_module(source, BuildTarget::Client)
+            .expect("component should compile");
+
+        let mut manifest = BuildManifest::new("button.jnc");
+        manifest.classes = module.class_maps().clone();
+
+        let button = manifest.resolve_class(".button").expect(".button should be mapped");
+        assert!(button.starts_with("Button_button_"), "got {}", button);
+        assert_eq!(manifest.resolve_component_class("Button", "primary").map(|c| c.starts_with("Button_primary_")), Some(true));
+
+        // The manifest names must be the selectors actually emitted
+        let css = module.css();
+        assert!(css.contains(&format!(".{} {{", button)), "got:\n{}", css);
+    }
+
+    const SPECIFICITY_OVERRIDE_SOURCE: &str = r#"
+        component Card() {
+            let styles = css! {
+                .list {
+                    display: grid;
+                }
+                .list .card {
+                    color: green;
+                }
+                .card {
+                    color: blue;
+                }
+            };
+            return <div>"card"</div>;
+        }
+    "#;
+
+    #[test]
+    fn test_css_specificity_warning_and_allow() {
+        use crate::{BuildTarget, Compiler};
+
+        let module = Compiler::without_optimization()
+            .compile_module(SPECIFICITY_OVERRIDE_SOURCE, BuildTarget::Client)
+            .expect("component should compile");
+        let warnings = module.css_warnings();
+        assert_eq!(warnings.len(), 1, "got {:?}", warnings);
+        assert!(warnings[0].message.contains("(0,1,0) is lower than (0,2,0)"), "got {}", warnings[0].message);
+        assert_eq!(warnings[0].location.as_ref().map(|loc| loc.line), Some(10));
+        assert_eq!(warnings[0].labels[0].location.line, 7);
+
+        let allowed = format!("@allow(css_specificity){}", SPECIFICITY_OVERRIDE_SOURCE.trim_start());
+        let module = Compiler::without_optimization()
+            .compile_module(&allowed, BuildTarget::Client)
+            .expect("allowed component should compile");
+        assert!(module.css_warnings().is_empty());
+    }
+
+    #[test]
+    fn test_css_custom_properties_kept_verbatim() {
+        use crate::{BuildTarget, Compiler};
+
+        let source = r#"
+        component Card() {
+            let styles = css! {
+                :root {
+                    --primary: #3b82f6;
+                    --gap_size: 4px;
+                }
+                .card {
+                    --accent: var(--primary);
+                    color: var(--primary, blue);
+                    margin: calc(var(--gap_size) * 2) var(--edge, 0);
+                }
+            };
+            return <div class={styles.card}>"card"</div>;
+        }
+    "#;
+        let module = Compiler::without_optimization()
+            .compile_module(source, BuildTarget::Client)
+            .expect("component should compile");
+        let css = module.css();
+        assert!(css.contains(":root {\n  --primary: #3b82f6;\n  --gap_size: 4px;\n}"), "got:\n{}", css);
+        assert!(css.contains("  --accent: var(--primary);\n"), "got:\n{}", css);
+        assert!(css.contains("  color: var(--primary, blue);\n"), "got:\n{}", css);
+        assert!(css.contains("  margin: calc(var(--gap_size) * 2) var(--edge, 0);\n"), "got:\n{}", css);
+    }
+
+    #[test]
+    fn test_css_tooltip_pattern() {
+        use crate::{BuildTarget, Compiler};
+
+        let source = r#"
+        component Tip() {
+            let styles = css! {
+                .tooltip {
+                    position: relative;
+                }
+                .tooltip::after {
+                    content: "→";
+                    position: absolute;
+                }
+                .tooltip[data-side="left"]::after {
+                    right: 100%;
+                }
+                input[type="checkbox"]:checked {
+                    accent-color: red;
+                }
+            };
+            return <span class={styles.tooltip} data-side="left">"?"</span>;
+        }
+    "#;
+        let module = Compiler::without_optimization()
+            .compile_module(source, BuildTarget::Client)
+            .expect("component should compile");
+        let css = module.css();
+        let tooltip = css.lines()
+            .find_map(|line| line.strip_suffix(" {").filter(|selector| selector.starts_with(".Tip_tooltip_")))
+            .expect("the tooltip rule");
+        assert!(css.contains(&format!("{}::after {{\n  content: \"→\";\n  position: absolute;\n}}", tooltip)), "got:\n{}", css);
+        assert!(css.contains(&format!("{}[data-side=\"left\"]::after {{\n  right: 100%;\n}}", tooltip)), "got:\n{}", css);
+        assert!(css.contains("input[type=\"checkbox\"]:checked {\n  accent-color: red;\n}"), "got:\n{}", css);
+    }
+
+    #[test]
+    fn test_css_keyframes_scoped_per_component() {
+        use crate::{BuildTarget, Compiler};
+
+        let source = r#"
+        component Spinner() {
+            let styles = css! {
+                @keyframes spin {
+                    from { transform: rotate(0deg); }
+                    to { transform: rotate(360deg); }
+                }
+                .spinner { animation: spin 1s linear infinite; }
+            };
+            return <p class={styles.spinner}>"..."</p>;
+        }
+        component App() {
+            let styles = css! {
+                @keyframes spin {
+                    0%, 100% { opacity: 1; }
+                    50% { opacity: 0.5; }
+                }
+                .app { animation-name: spin; }
+            };
+            return <div class={styles.app}><Spinner /></div>;
+        }
+    "#;
+        let module = Compiler::without_optimization()
+            .compile_module(source, BuildTarget::Client)
+            .expect("components should compile");
+        let css = module.css();
+        let keyframes: Vec<_> = css.lines().filter_map(|line| line.strip_prefix("@keyframes ")?.strip_suffix(" {")).collect();
+        let [spinner, app] = keyframes.as_slice() else { panic!("got:\n{}", css) };
+        assert!(spinner.starts_with("Spinner_spin_") && app.starts_with("App_spin_"), "got:\n{}", css);
+        assert!(css.contains(&format!("animation: {} 1s linear infinite;", spinner)), "got:\n{}", css);
+        assert!(css.contains(&format!("animation-name: {};", app)), "got:\n{}", css);
+        assert!(css.contains("  0%, 100% {\n    opacity: 1;\n  }"), "got:\n{}", css);
+    }
+
+    #[test]
+    fn test_css_font_face_supports_and_import() {
+        use crate::{BuildTarget, Compiler};
+
+        let source = r#"
+        component App() {
+            let styles = css! {
+                @font-face {
+                    font-family: "Inter";
+                    src: url("/inter.woff2") format("woff2");
+                }
+                .grid { display: block; font-family: "Inter", sans-serif; }
+                @supports (display: grid) {
+                    .grid { display: grid; }
+                }
+                @page :first { margin: 1in; }
+                @layer base;
+                @import url("/theme.css");
+            };
+            return <div class={styles.grid}>"hi"</div>;
+        }
+    "#;
+        let module = Compiler::without_optimization()
+            .compile_module(source, BuildTarget::Client)
+            .expect("component should compile");
+        let css = module.css();
+        // Browsers ignore an @import after any other rule
+        assert!(css.starts_with("@import url(\"/theme.css\");\n"), "got:\n{}", css);
+        assert!(css.contains("@font-face {\n  font-family: \"Inter\";\n  src: url(\"/inter.woff2\") format(\"woff2\");\n}"), "got:\n{}", css);
+        assert!(css.contains("  font-family: \"Inter\", sans-serif;\n"), "got:\n{}", css);
+        let grid = module.class_maps()["App"]["grid"].clone();
+        assert!(css.contains(&format!("@supports (display: grid) {{\n  .{} {{\n    display: grid;\n  }}\n}}", grid)), "got:\n{}", css);
+        // Page rules style printed pages, not the component, so they aren't scoped
+        assert!(css.contains("@page :first {\n  margin: 1in;\n}"), "got:\n{}", css);
+
+        let warnings = module.css_warnings();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].code.as_deref(), Some("W015"));
+        assert!(warnings[0].message.contains("`@layer`"), "{}", warnings[0].message);
+        assert_eq!(warnings[0].location.as_ref().map(|location| (location.line, location.column)), Some((13, 17)));
+    }
+
+    #[test]
+    fn test_css_nested_page_skipped_with_warning() {
+        use crate::{BuildTarget, Compiler};
+
+        let source = r#"
+        component Report() {
+            let styles = css! {
+                .sheet { color: black; }
+                @page { size: A4; }
+                @media print {
+                    @page { margin: 0; }
+                }
+            };
+            return <div class={styles.sheet}>"report"</div>;
+        }
+    "#;
+        let module = Compiler::without_optimization()
+            .compile_module(source, BuildTarget::Client)
+            .expect("component should compile");
+        let css = module.css();
+        assert!(css.contains("@page {\n  size: A4;\n}"), "got:\n{}", css);
+        // The @media block held only the skipped @page, so it isn't emitted at all
+        assert!(!css.contains("@media"), "got:\n{}", css);
+
+        let warnings = module.css_warnings();
+        assert_eq!(warnings.len(), 1, "got {:?}", warnings);
+        assert_eq!(warnings[0].code.as_deref(), Some("W015"));
+        assert!(warnings[0].message.contains("only supported at the top"), "{}", warnings[0].message);
+        assert_eq!(warnings[0].location.as_ref().map(|location| (location.line, location.column, location.length)), Some((7, 21, 5)));
+    }
+
+    #[test]
+    fn test_css_nested_media_query_override() {
+        use crate::{BuildTarget, Compiler};
+
+        let source = r#"
+        component Card() {
+            let styles = css! {
+                .card {
+                    padding: 8px;
+                    color: black;
+                    @media screen and (min-width: 768px) {
+                        padding: 16px;
+                        &:hover { color: blue; }
+                    }
+                }
+                @media (hover: hover), print {
+                    .card { cursor: pointer; }
+                }
+            };
+            return <div class={styles.card}>"hi"</div>;
+        }
+    "#;
+        let module = Compiler::without_optimization()
+            .compile_module(source, BuildTarget::Client)
+            .expect("component should compile");
+        let css = module.css();
+        let card = format!(".{}", module.class_maps()["Card"]["card"]);
+        // The override is hoisted after the rule, under the rule's selector
+        let expected = format!(
+            "{card} {{\n  padding: 8px;\n  color: black;\n}}\n\n\
+             @media screen and (min-width: 768px) {{\n  {card} {{\n    padding: 16px;\n  }}\n  {card}:hover {{\n    color: blue;\n  }}\n}}\n\n\
+             @media (hover: hover), print {{\n  {card} {{\n    cursor: pointer;\n  }}\n}}\n",
+            card = card
+        );
+        assert!(css.contains(&expected), "got:\n{}", css);
+    }
+
+    #[test]
+    fn test_css_button_shorthands_round_trip() {
+        use crate::{BuildTarget, Compiler};
+
+        // Every value is kept as written, however many parts it has
+        let declarations = [
+            ("display", "inline-flex"),
+            ("margin", "0 auto"),
+            ("padding", ".5rem 1.25rem"),
+            ("border", "1px solid rgba(0,0,0,0.2)"),
+            ("border-radius", "6px / 4px"),
+            ("font", "600 14px/1.5 \"Inter\", system-ui, sans-serif"),
+            ("background", "linear-gradient(to bottom, #3b82f6, #2563eb) no-repeat"),
+            ("box-shadow", "0 1px 2px rgba(0, 0, 0, 0.1), inset 0 -1px 0 #1d4ed8"),
+            ("transition", "opacity 0.2s ease-in-out, transform 0.2s"),
+            ("grid-template-columns", "repeat(3, 1fr)"),
+            ("grid-area", "1 / 2 / 3"),
+            ("transform", "translate(-50%, -50%) scale(1.05)"),
+            ("margin-top", "-4px"),
+            ("width", "calc(100% - 2 * var(--gap, 8px))"),
+            ("opacity", ".9"),
+            ("z-index", "10"),
+            ("color", "#fff !important"),
+        ];
+        let body: String = declarations
+            .iter()
+            .map(|(property, value)| format!("                    {}: {};\n", property, value))
+            .collect();
+        let source = format!(
+            "component Button() {{\n    let styles = css! {{\n        .button {{\n{}        }}\n    }};\n    return <button class={{styles.button}}>\"ok\"</button>;\n}}\n",
+            body
+        );
+
+        let module = Compiler::without_optimization()
+            .compile_module(&source, BuildTarget::Client)
+            .expect("component should compile");
+        let css = module.css();
+        let expected: String = declarations
+            .iter()
+            .map(|(property, value)| format!("  {}: {};\n", property, value))
+            .collect();
+        let button = format!(".{}", module.class_maps()["Button"]["button"]);
+        assert!(css.contains(&format!("{} {{\n{}}}", button, expected)), "got:\n{}", css);
+    }
+
+    #[test]
+    fn test_css_three_level_nesting_flattens() {
+        use crate::{BuildTarget, Compiler};
+
+        let source = r#"
+        component Menu() {
+            let styles = css! {
+                .menu {
+                    display: flex;
+                    .entry {
+                        padding: 4px;
+                        &.active, &:hover { font-weight: bold; }
+                        & > a { color: inherit; }
+                        .dark & { color: white; }
+                    }
+                    input { &::placeholder { color: gray; } }
+                }
+            };
+            return <nav class={styles.menu}></nav>;
+        }
+    "#;
+        let module = Compiler::without_optimization()
+            .compile_module(source, BuildTarget::Client)
+            .expect("component should compile");
+        let css = module.css();
+        let classes = &module.class_maps()["Menu"];
+        let menu = format!(".{}", classes["menu"]);
+        let entry = format!("{} .{}", menu, classes["entry"]);
+        let active = format!(".{}", classes["active"]);
+        let dark = format!(".{}", classes["dark"]);
+        let expected = format!(
+            "{menu} {{\n  display: flex;\n}}\n\n\
+             {entry} {{\n  padding: 4px;\n}}\n\n\
+             {entry}{active}, {entry}:hover {{\n  font-weight: bold;\n}}\n\n\
+             {entry} > a {{\n  color: inherit;\n}}\n\n\
+             {dark} {entry} {{\n  color: white;\n}}\n\n\
+             {menu} input::placeholder {{\n  color: gray;\n}}\n",
+            menu = menu,
+            entry = entry,
+            active = active,
+            dark = dark
+        );
+        assert!(css.contains(&expected), "got:\n{}", css);
+    }
+
+    #[test]
+    fn test_css_handle_scopes_each_component_block() {
+        use crate::{BuildTarget, Compiler};
+
+        let source = |color: &str| format!(r#"
+        component PrimaryButton() {{
+            let styles = css! {{
+                .button {{ background: {}; }}
+                :global(.dark) .button {{ background: black; }}
+            }};
+            return <button class={{styles.button}}>"ok"</button>;
+        }}
+        component SecondaryButton() {{
+            let styles = css! {{
+                .button {{ background: gray; }}
+            }};
+            return <button class={{styles.button}}>"cancel"</button>;
+        }}
+    "#, color);
+        let compile = |color: &str| Compiler::without_optimization()
+            .compile_module(&source(color), BuildTarget::Client)
+            .expect("components should compile");
+        let module = compile("blue");
+        let primary = module.class_maps()["PrimaryButton"]["button"].clone();
+        let secondary = module.class_maps()["SecondaryButton"]["button"].clone();
+        assert!(primary.starts_with("PrimaryButton_button_"), "got {}", primary);
+        assert_ne!(primary, secondary);
+
+        // The global class is kept as written, and isn't part of the handle
+        let css = module.css();
+        assert!(css.contains(&format!(".{} {{\n  background: blue;\n}}", primary)), "got:\n{}", css);
+        assert!(css.contains(&format!(".dark .{} {{\n  background: black;\n}}", primary)), "got:\n{}", css);
+        assert!(css.contains(&format!(".{} {{\n  background: gray;\n}}", secondary)), "got:\n{}", css);
+        assert!(!module.class_maps()["PrimaryButton"].contains_key("dark"));
+        assert!(module.css_warnings().is_empty(), "got {:?}", module.css_warnings());
+
+        let (_, client_js) = compile_source(&source("blue")).expect("handle field access type checks");
+        assert!(client_js.contains(&format!("{{ \"button\": \"{}\" }}", primary)), "got:\n{}", client_js);
+        assert!(client_js.contains(&format!("{{ \"button\": \"{}\" }}", secondary)), "got:\n{}", client_js);
+
+        // Stable across builds, and across edits to the declarations
+        assert_eq!(compile("blue").class_maps()["PrimaryButton"]["button"], primary);
+        assert_eq!(compile("red").class_maps()["PrimaryButton"]["button"], primary);
+    }
+
+    #[test]
+    fn test_css_handle_rejects_unknown_class() {
+        let source = r#"
+        component Card() {
+            let styles = css! {
+                .card { padding: 8px; }
+            };
+            return <div class={styles.crad}></div>;
+        }
+    "#;
+        let error = compile_source(source).expect_err("unknown handle field should not type check");
+        assert!(
+            error.to_string().contains("css! block has no class `.crad`; did you mean `.card`?"),
+            "got {}",
+            error
+        );
+    }
+
+    #[test]
+    fn test_css_undeclared_class_warning_and_allow() {
+        use crate::{BuildTarget, Compiler};
+
+        let source = r#"
+        component List() {
+            let styles = css! {
+                .item { padding: 4px; }
+                .lsit > .item:not(.hidden) { margin: 0; }
+            };
+            return <ul class={styles.item}></ul>;
+        }
+        @allow(undeclared_css_class)
+        component Quiet() {
+            let styles = css! {
+                .wrapper .item { margin: 0; }
+            };
+            return <ul class={styles.item}></ul>;
+        }
+    "#;
+        let module = Compiler::without_optimization()
+            .compile_module(source, BuildTarget::Client)
+            .expect("component should compile");
+        let warnings = module.css_warnings();
+        let messages: Vec<_> = warnings.iter().map(|warning| warning.message.as_str()).collect();
+        assert_eq!(messages, [
+            "class `.lsit` is not declared in this css! block",
+            "class `.hidden` is not declared in this css! block",
+        ]);
+        assert_eq!(warnings[0].code.as_deref(), Some("W016"));
+        assert_eq!(warnings[0].location.as_ref().map(|location| (location.line, location.column)), Some((5, 17)));
+    }
+
+    #[test]
+    fn test_css_undeclared_class_points_at_each_class() {
+        use crate::{BuildTarget, Compiler};
+
+        let source = r#"
+        component Card() {
+            let styles = css! {
+                .card { padding: 4px; }
+                .zz .card, .yy .card { margin: 0; }
+            };
+            return <div class={styles.card}></div>;
+        }
+    "#;
+        let module = Compiler::without_optimization()
+            .compile_module(source, BuildTarget::Client)
+            .expect("component should compile");
+        let warnings = module.css_warnings();
+        let locations: Vec<_> = warnings
+            .iter()
+            .map(|warning| {
+                let location = warning.location.as_ref().expect("W016 should have a location");
+                (warning.message.as_str(), location.line, location.column, location.length)
+            })
+            .collect();
+        assert_eq!(locations, [
+            ("class `.zz` is not declared in this css! block", 5, 17, 3),
+            ("class `.yy` is not declared in this css! block", 5, 28, 3),
+        ]);
+    }
+
+    #[test]
+    fn test_css_blocks_collect_into_one_stylesheet() {
+        use crate::js_emitter::Stylesheet;
+        use crate::{BuildTarget, Compiler};
+        use std::collections::HashMap;
+        use std::path::Path;
+
+        let source = r#"
+        component Card() {
+            let styles = css! {
+                .title { color: red; }
+            };
+            return <h2 class={styles.title}>"card"</h2>;
+        }
+        component Panel() {
+            let styles = css! {
+                .heading { color: red; }
+            };
+            return <h2 class={styles.heading}>"panel"</h2>;
+        }
+    "#;
+        let mut generator = crate::codegen::CodeGenerator::new(BuildTarget::Client);
+        let program = Parser::new(&mut Lexer::new(source.to_string())).parse_program().unwrap();
+        generator.generate_program(&program).unwrap();
+        generator.record_css_sources(&HashMap::new(), Path::new("app.jnc"));
+        let mut module = Compiler::without_optimization()
+            .compile_module(source, BuildTarget::Client)
+            .expect("components should compile");
+        module.css_ast_mut().dedupe();
+
+        // Identical bodies share one rule, in source order
+        let card = module.class_maps()["Card"]["title"].clone();
+        let panel = module.class_maps()["Panel"]["heading"].clone();
+        let css = module.css();
+        assert!(css.contains(&format!(".{}, .{} {{\n  color: red;\n}}", card, panel)), "got:\n{}", css);
+        assert_eq!(css.matches("color: red").count(), 1);
+
+        // Dev builds point each rule back at its source
+        let mut with_sources = generator.get_css_ast().clone();
+        with_sources.dedupe();
+        assert!(with_sources.to_css_with_sources().contains("/* app.jnc:4, app.jnc:10 */"),
+            "got:\n{}", with_sources.to_css_with_sources());
+
+        let linked = JSEmitter::new(&program).with_stylesheet(Stylesheet::Linked("styles.css".to_string()));
+        assert!(linked.generate_client_js().contains("link.href = \"styles.css\";"));
+        let inline = JSEmitter::new(&program).with_stylesheet(Stylesheet::Inline(css.clone()));
+        let client_js = inline.generate_client_js();
+        assert!(client_js.contains("style.textContent = "), "got:\n{}", client_js);
+        assert!(!client_js.contains("styles.css"));
+    }
+
+    #[test]
+    fn test_css_top_level_media_keeps_source_order() {
+        use crate::{BuildTarget, Compiler};
+
+        let source = r#"
+        component Card() {
+            let styles = css! {
+                @media (min-width: 768px) {
+                    .card { color: red; }
+                }
+                .card { color: blue; }
+            };
+            return <div class={styles.card}>"hi"</div>;
+        }
+    "#;
+        let module = Compiler::without_optimization()
+            .compile_module(source, BuildTarget::Client)
+            .expect("component should compile");
+        let css = module.css();
+        let card = format!(".{}", module.class_maps()["Card"]["card"]);
+        // The later rule wins the cascade, as it does in the source
+        let expected = format!(
+            "@media (min-width: 768px) {{\n  {card} {{\n    color: red;\n  }}\n}}\n\n\
+             {card} {{\n  color: blue;\n}}\n",
+            card = card
+        );
+        assert!(css.contains(&expected), "got:\n{}", css);
+    }
+
+    #[test]
+    fn test_css_supports_keeps_source_order() {
+        use crate::{BuildTarget, Compiler};
+
+        let source = r#"
+        component Grid() {
+            let styles = css! {
+                @supports (display: grid) {
+                    .grid { display: grid; }
+                }
+                .grid { display: block; }
+            };
+            return <div class={styles.grid}>"hi"</div>;
+        }
+    "#;
+        let module = Compiler::without_optimization()
+            .compile_module(source, BuildTarget::Client)
+            .expect("component should compile");
+        let css = module.css();
+        let grid = format!(".{}", module.class_maps()["Grid"]["grid"]);
+        let expected = format!(
+            "@supports (display: grid) {{\n  {grid} {{\n    display: grid;\n  }}\n}}\n\n\
+             {grid} {{\n  display: block;\n}}\n",
+            grid = grid
+        );
+        assert!(css.contains(&expected), "got:\n{}", css);
+    }
+
+    #[test]
+    fn test_inline_style_unknown_property_warning_and_allow() {
+        use crate::{BuildTarget, Compiler};
+
+        let source = r#"
+        component Badge(color: String) {
+            return <span style="colr: red; background-color: {color}; --accent: blue; -webkit-user-select: none">"new"</span>;
+        }
+    "#;
+        let module = Compiler::without_optimization()
+            .compile_module(source, BuildTarget::Client)
+            .expect("component should compile");
+        let warnings = module.css_warnings();
+        assert_eq!(warnings.len(), 1, "got {:?}", warnings);
+        assert_eq!(warnings[0].message, "unknown CSS property `colr` in style attribute");
+        assert_eq!(warnings[0].code.as_deref(), Some("W007"));
+        assert_eq!(warnings[0].location.as_ref().map(|loc| (loc.line, loc.length)), Some((3, 4)));
+        let fix = &warnings[0].code_suggestions[0];
+        assert_eq!(fix.replacement, "color");
+        assert_eq!(fix.message, "did you mean `color`?");
+
+        let allowed = format!("@allow(unknown_css_property){}", source.trim_start());
+        let module = Compiler::without_optimization()
+            .compile_module(&allowed, BuildTarget::Client)
+            .expect("allowed component should compile");
+        assert!(module.css_warnings().is_empty());
+    }
+
+    #[test]
+    fn test_css_unknown_property_warning_allow_and_strict() {
+        use crate::{BuildTarget, Compiler};
+
+        let source = r#"
+        component Badge() {
+            let styles = css! {
+                .badge { color: red; --accent: blue; -moz-appearance: none; }
+                @media (min-width: 768px) {
+                    .badge { paddng: 8px; }
+                }
+            };
+            return <span class={styles.badge}>"new"</span>;
+        }
+    "#;
+        let module = Compiler::without_optimization()
+            .compile_module(source, BuildTarget::Client)
+            .expect("component should compile");
+        let mut warnings = module.css_warnings();
+        assert_eq!(warnings.len(), 1, "got {:?}", warnings);
+        assert_eq!(warnings[0].message, "unknown CSS property `paddng`");
+        assert_eq!(warnings[0].location.as_ref().map(|loc| (loc.line, loc.column, loc.length)), Some((6, 30, 6)));
+        assert_eq!(warnings[0].code_suggestions[0].replacement, "padding");
+
+        // --strict-css turns it into an error
+        assert!(crate::css_lint::deny_unknown_property(&mut warnings[0]));
+        assert_eq!(warnings[0].severity, crate::diagnostics::Severity::Error);
+
+        let allowed = format!("@allow(unknown_css_property){}", source.trim_start());
+        let module = Compiler::without_optimization()
+            .compile_module(&allowed, BuildTarget::Client)
+            .expect("allowed component should compile");
+        assert!(module.css_warnings().is_empty());
+    }
+
+    #[test]
+    fn test_css_unknown_property_in_global_rules() {
+        use crate::{BuildTarget, Compiler};
+
+        // :global(...) changes how a selector is scoped, not which properties exist
+        let source = r#"
+        component Theme() {
+            let styles = css! {
+                :global(.x) { colr: red }
+                .page {
+                    color: black;
+                    :global(.dark) & { bckground: black; }
+                }
+                @media print {
+                    :global(body) { margn: 0; }
+                }
+                .page:global(.wide) { widht: 100%; }
+            };
+            return <main class={styles.page}>"themed"</main>;
+        }
+    "#;
+        let module = Compiler::without_optimization()
+            .compile_module(source, BuildTarget::Client)
+            .expect("component should compile");
+        let mut warnings = module.css_warnings();
+        let unknown: Vec<_> = warnings.iter().map(|warning| (warning.message.as_str(), warning.location.as_ref().map(|loc| loc.line))).collect();
+        assert_eq!(unknown, vec![
+            ("unknown CSS property `colr`", Some(4)),
+            ("unknown CSS property `bckground`", Some(7)),
+            ("unknown CSS property `margn`", Some(10)),
+            ("unknown CSS property `widht`", Some(12)),
+        ]);
+        assert!(warnings.iter_mut().all(crate::css_lint::deny_unknown_property));
+    }
+
+    #[test]
+    fn test_css_minified_output_parses_back_to_same_rules() {
+        use crate::css_ast::CssNode;
+        use crate::css_minifier::CssMinifier;
+        use crate::{BuildTarget, Compiler};
+
+        let component = |css: &str| format!(
+            "component Card() {{\n    let styles = css! {{\n{}\n    }};\n    return <div>\"hi\"</div>;\n}}\n",
+            css
+        );
+        let compile = |source: &str| Compiler::without_optimization()
+            .compile_module(source, BuildTarget::Client)
+            .expect("component should compile");
+        // Selector, property and value of every rule, media rules included
+        fn rules(nodes: &[CssNode], out: &mut Vec<(String, String, String)>) {
+            for node in nodes {
+                match node {
+                    CssNode::Rule(rule) => out.extend(rule.declarations.iter().map(|decl| {
+                        (rule.selector.clone(), decl.property.clone(), decl.value.clone())
+                    })),
+                    CssNode::AtRule(at_rule) => rules(&at_rule.children, out),
+                }
+            }
+        }
+
+        let module = compile(&component(r#"
+        .card  >  .title { color: #FFFFFF; margin: 0px auto; }
+        .card {
+            padding: 0.0em 16px;
+            font: 600 14px / 1.5 "Inter", sans-serif;
+            width: calc(100% - 0px);
+            box-shadow: 0px 2px 4px rgba(0, 0, 0, 0.1);
+        }
+        .card:hover { border: 1px solid #aabbcc; }
+        @media (min-width: 768px) {
+            .card { padding: 24px; }
+        }
+    "#));
+        let minifier = CssMinifier::new();
+        let minified = minifier.minify(module.css_ast());
+        assert!(minified.contains("color:#FFF;margin:0 auto}"), "got:\n{}", minified);
+        assert!(minified.contains("width:calc(100% - 0px)"), "got:\n{}", minified);
+        assert!(!minified.contains('\n'));
+
+        // css! scopes the already scoped classes again; undo that, and the
+        // rules read back are the ones that were minified
+        let reparsed = compile(&component(&minified));
+        let mut expected = Vec::new();
+        rules(&minifier.minify_stylesheet(module.css_ast()).nodes, &mut expected);
+        let mut actual = Vec::new();
+        rules(&minifier.minify_stylesheet(reparsed.css_ast()).nodes, &mut actual);
+        let mut renames: Vec<(&String, &String)> = reparsed.class_maps()["Card"].iter().collect();
+        renames.sort_by_key(|(original, _)| std::cmp::Reverse(original.len()));
+        for (selector, _, _) in &mut actual {
+            for (original, scoped) in &renames {
+                *selector = selector.replace(scoped.as_str(), original);
+            }
+        }
+        assert_eq!(actual, expected);
+    }
+
+    /// The "did you mean" fix attached to the first fixable error
+    fn suggested_fix(error: &CompileError) -> Option<&crate::diagnostics::CodeSuggestion> {
+        error.errors().into_iter().find_map(|e| match e {
+            CompileError::Fixable { fix, .. } => Some(fix),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn test_misspelled_names_suggest_fixes() {
+        let cases = [
+            // Unresolved identifier: candidates come from the scope
+            ("fn total(items: i32, count: i32) -> i32 {\n    return itmes + count;\n}\n", "items", (2, 5)),
+            // Unknown struct field, in a literal and in a field access
+            ("struct User {\n    name: String,\n    email: String,\n}\n\nfn main() {\n    let user = User { name: \"a\", emial: \"b\" };\n}\n", "email", (7, 5)),
+            ("struct User {\n    name: String,\n}\n\nfn main() {\n    let user = User { name: \"a\" };\n    let n = user.nmae;\n}\n", "name", (7, 4)),
+            // Unknown component prop
+            ("component Card(title: String, subtitle: String) {\n    return <h1>{title}</h1>;\n}\n\ncomponent App() {\n    return <Card tilte=\"Hi\" subtitle=\"there\" />;\n}\n", "title", (6, 5)),
+            // Unknown enum variant in a pattern
+            ("enum Color {\n    Red,\n    Green,\n}\n\nfn name(c: Color) -> String {\n    return match c {\n        Color::Red => \"red\",\n        Color::Gren => \"green\",\n    };\n}\n", "Color::Green", (9, 11)),
+        ];
+        for (source, replacement, (line, length)) in cases {
+            let error = compile_source(source).expect_err(source);
+            let fix = suggested_fix(&error).unwrap_or_else(|| panic!("no fix for {:?}: {:?}", source, error));
+            assert_eq!(fix.replacement, replacement);
+            assert_eq!(fix.message, format!("did you mean `{}`?", replacement));
+            assert_eq!((fix.location.line, fix.location.length), (line, length), "{}", source);
+        }
+    }
+
+    #[test]
+    fn test_no_suggestion_when_nothing_is_close() {
+        let source = "struct User {\n    name: String,\n}\n\nfn main() {\n    let user = User { name: \"a\" };\n    let n = user.password;\n}\n";
+        let error = compile_source(source).expect_err("unknown field");
+        assert!(error.to_string().contains("has no field named 'password'"));
+        assert!(suggested_fix(&error).is_none());
+
+        // Unknown names and props with nothing close may be JS globals or
+        // pass-through attributes, so they are left alone
+        let source = "component Card(title: String) {\n    return <h1>{title}</h1>;\n}\n\ncomponent App() {\n    let width = window_width();\n    return <Card title=\"Hi\" key=\"1\" />;\n}\n";
+        assert!(compile_source(source).is_ok());
+        // Nor are JS globals, even next to a local that differs only in case
+        let source = "fn stamp() -> f64 {\n    let date = 3.0;\n    return Date.now() + date;\n}\n\nfn wait() {\n    let fetched = 1;\n    let timer = setTimeout(|| fetch(\"/ping\"), fetched);\n}\n";
+        assert!(compile_source(source).is_ok(), "{:?}", compile_source(source).err());
+    }
+
+    #[test]
+    fn test_parse_error_displays_its_own_message() {
+        let source = "fn main() {\n    let x = ;\n}\n";
+        let error = compile_source(source).expect_err("missing value");
+        let shown = crate::Compiler::display_error(&error, Some(source), "main.jnc");
+        assert!(shown.contains("main.jnc:2:"), "{}", shown);
+        assert!(!shown.contains("expected , found"), "{}", shown);
+        assert!(shown.contains("No prefix parse function for Semicolon"), "{}", shown);
+    }
 }
@@ -10,10 +10,100 @@
 // - server.js: Server-side code with HTTP server and RPC handlers
 // - client.js: Client-side code with RPC stubs and UI components
 
-use crate::ast::{Program, Statement, FunctionDefinition, ComponentDefinition, Expression, BlockStatement, Pattern, TypeExpression, ForInStatement, ForStatement, ImplBlock};
+use crate::ast::{Program, Statement, FunctionDefinition, ComponentDefinition, Expression, BlockStatement, Pattern, TypeExpression, ForInStatement, ForStatement, ImplBlock, Identifier, InlineStyle, InlineStylePart, JsxElement, LambdaExpression, MacroCall, TryOperatorExpression, DEFAULT_SLOT};
 use crate::code_splitter::CodeSplitter;
+use crate::codegen::CodeGenerator;
+use crate::css_generator::CssGenerator;
+use crate::entry::{type_name, EntryPoint};
+use crate::i18n;
 use crate::rpc_generator::RPCGenerator;
 use crate::source_map::SourceMapBuilder;
+use crate::token::KEYWORDS;
+use crate::visit::{walk_inline_style, walk_jsx_element, walk_macro_call, Visit};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Stdlib namespaces the client bundle defines (`json::parse` -> `json.parse`)
+pub const STDLIB_NAMESPACES: &[&str] = &["json", "crypto", "fs", "yaml"];
+
+/// Render timing helpers, inlined into client bundles built with runtime profiling
+const PROFILE_RUNTIME: &str = include_str!("../runtime/profile.js");
+/// Dev builds' devtools registry (runtime/devtools.js), which release builds don't ship
+const DEVTOOLS_IMPORT: &str = "import { enableDevtools, trackSignal as __jounce_track } from './devtools.js';\n";
+
+/// Which bundle an emitted item was written to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Bundle {
+    Server,
+    Client,
+}
+
+impl Bundle {
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            Bundle::Server => "server.js",
+            Bundle::Client => "client.js",
+        }
+    }
+}
+
+/// What kind of top-level item produced a piece of a bundle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItemKind {
+    Constant,
+    Struct,
+    Enum,
+    Impl,
+    Function,
+    Component,
+    /// A stdlib namespace object (`const json = { ... }`)
+    Stdlib,
+}
+
+/// The code one top-level item contributed to a bundle, for size reports
+#[derive(Debug, Clone)]
+pub struct EmittedItem {
+    pub bundle: Bundle,
+    pub kind: ItemKind,
+    /// Source-level name (the type name for impl blocks, the namespace for stdlib)
+    pub name: String,
+    pub code: String,
+    /// Stdlib namespaces the item's code refers to
+    pub stdlib_refs: BTreeSet<String>,
+}
+
+/// How the client bundle brings in the compiled CSS
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stylesheet {
+    /// A stylesheet file, linked by the bundle on pages that don't link it
+    Linked(String),
+    /// The CSS itself, put in a `<style>` element: the bundle needs no other file
+    Inline(String),
+}
+
+/// Where an extern function's signature uses `JsValue`: those arguments are
+/// passed to JavaScript as raw values and the result comes back wrapped
+#[derive(Debug, Clone, Default)]
+struct JsValueBoundary {
+    params: Vec<bool>,
+    returns: bool,
+    /// The extern returns `Promise<JsValue>`; the resolved value is wrapped
+    resolves: bool,
+}
+
+/// Runtime helpers the program refers to; the bundles import only these
+#[derive(Debug, Clone, Default)]
+struct RuntimeUses {
+    event_bus: bool,
+    js_value: bool,  // The JsValue type, or a JsValue:: call
+    create_context: bool,
+    use_context: bool,
+    provide: bool,  // <Provide> in JSX
+    on_cleanup: bool,
+    unsafe_raw: bool,  // Called by name
+    unsafe_html: bool,  // Some element has unsafe_html, lowered to an unsafe_raw() call
+    escape: bool,  // Some inline style interpolates a value, escaped at runtime
+}
 
 #[derive(Debug, Clone)]
 pub struct JSEmitter {
@@ -21,6 +111,21 @@ pub struct JSEmitter {
     pub source_file: String,  // Original .jnc source file path
     #[allow(dead_code)] // Used in future source map implementation
     current_line: usize,  // Track current line number during generation
+    test_ids: bool,  // Stamp data-jounce-component / data-jounce-key at runtime
+    devtools: bool,  // Register component instances and signals in window.__JOUNCE_DEVTOOLS__
+    profile_runtime: bool,  // Time component renders, effects and computeds (runtime/profile.js)
+    entry: Option<EntryPoint>,  // Validated entry to start from (otherwise the first component)
+    class_maps: BTreeMap<String, BTreeMap<String, String>>,  // component -> (css! class -> scoped class)
+    current_component: RefCell<Option<String>>,  // Component whose body is being generated
+    css_owner: RefCell<Option<String>>,  // Component or function whose css! blocks are being generated
+    emitted: RefCell<Vec<EmittedItem>>,  // Size accounting: each item's code, per bundle
+    stdlib_refs: RefCell<BTreeSet<String>>,  // Stdlib namespaces referenced since the last recorded item
+    js_value_externs: BTreeMap<String, JsValueBoundary>,  // Extern functions whose signature mentions JsValue
+    uses_i18n: bool,  // Program calls t! or I18n::, so the bundles import the runtime's I18n
+    uses_refs: bool,  // Some element has ref={...}, so the client bundle imports bindRef
+    runtime_uses: RuntimeUses,
+    rpc_signal: RefCell<Option<bool>>,  // Inside a client async function: whether its server calls used the signal
+    stylesheet: Option<Stylesheet>,  // How the client bundle loads the CSS, if there is any
 }
 
 impl JSEmitter {
@@ -31,6 +136,21 @@ impl JSEmitter {
             splitter,
             source_file: "input.jnc".to_string(),
             current_line: 1,
+            test_ids: false,
+            devtools: false,
+            profile_runtime: false,
+            entry: None,
+            class_maps: CodeGenerator::scoped_class_maps(program),
+            current_component: RefCell::new(None),
+            css_owner: RefCell::new(None),
+            emitted: RefCell::new(Vec::new()),
+            stdlib_refs: RefCell::new(BTreeSet::new()),
+            js_value_externs: Self::js_value_boundaries(program),
+            uses_i18n: Self::uses_i18n(program),
+            uses_refs: Self::uses_refs(program),
+            runtime_uses: Self::runtime_uses(program),
+            rpc_signal: RefCell::new(None),
+            stylesheet: None,
         }
     }
 
@@ -42,6 +162,336 @@ impl JSEmitter {
             splitter,
             source_file,
             current_line: 1,
+            test_ids: false,
+            devtools: false,
+            profile_runtime: false,
+            entry: None,
+            class_maps: CodeGenerator::scoped_class_maps(program),
+            current_component: RefCell::new(None),
+            css_owner: RefCell::new(None),
+            emitted: RefCell::new(Vec::new()),
+            stdlib_refs: RefCell::new(BTreeSet::new()),
+            js_value_externs: Self::js_value_boundaries(program),
+            uses_i18n: Self::uses_i18n(program),
+            uses_refs: Self::uses_refs(program),
+            runtime_uses: Self::runtime_uses(program),
+            rpc_signal: RefCell::new(None),
+            stylesheet: None,
+        }
+    }
+
+    /// Extern functions that take or return `JsValue`, and where
+    fn js_value_boundaries(program: &Program) -> BTreeMap<String, JsValueBoundary> {
+        fn is_js_value(ty: &TypeExpression) -> bool {
+            matches!(ty, TypeExpression::Named(ident) if ident.value == "JsValue")
+        }
+
+        let mut boundaries = BTreeMap::new();
+        for stmt in &program.statements {
+            let Statement::ExternBlock(block) = stmt else { continue };
+            for func in &block.functions {
+                let boundary = JsValueBoundary {
+                    params: func.parameters.iter().map(|p| is_js_value(&p.type_annotation)).collect(),
+                    returns: func.return_type.as_ref().is_some_and(is_js_value),
+                    resolves: matches!(
+                        &func.return_type,
+                        Some(TypeExpression::Generic(ident, args))
+                            if ident.value == "Promise" && args.len() == 1 && is_js_value(&args[0])
+                    ),
+                };
+                if boundary.returns || boundary.resolves || boundary.params.contains(&true) {
+                    boundaries.insert(func.name.value.clone(), boundary);
+                }
+            }
+        }
+        boundaries
+    }
+
+    /// Whether the program translates anything (t! or I18n::)
+    fn uses_i18n(program: &Program) -> bool {
+        struct Finder(bool);
+        impl Visit for Finder {
+            fn visit_macro_call(&mut self, call: &MacroCall) {
+                self.0 |= call.name.value == i18n::TRANSLATE_MACRO;
+                walk_macro_call(self, call);
+            }
+            fn visit_identifier(&mut self, ident: &Identifier) {
+                self.0 |= ident.value.starts_with("I18n::");
+            }
+        }
+        let mut finder = Finder(false);
+        finder.visit_program(program);
+        finder.0
+    }
+
+    /// Whether any HTML element takes a `ref`
+    fn uses_refs(program: &Program) -> bool {
+        struct Finder(bool);
+        impl Visit for Finder {
+            fn visit_jsx_element(&mut self, jsx: &JsxElement) {
+                self.0 |= jsx.element_ref().is_some();
+                walk_jsx_element(self, jsx);
+            }
+        }
+        let mut finder = Finder(false);
+        finder.visit_program(program);
+        finder.0
+    }
+
+    /// Which of the runtime's helpers the program refers to
+    fn runtime_uses(program: &Program) -> RuntimeUses {
+        struct Finder(RuntimeUses);
+        impl Visit for Finder {
+            fn visit_identifier(&mut self, ident: &Identifier) {
+                let uses = &mut self.0;
+                match ident.value.as_str() {
+                    "create_context" => uses.create_context = true,
+                    "use_context" => uses.use_context = true,
+                    "on_cleanup" => uses.on_cleanup = true,
+                    "unsafe_raw" => uses.unsafe_raw = true,
+                    "JsValue" => uses.js_value = true,
+                    "EventBus" => uses.event_bus = true,
+                    name => {
+                        uses.js_value |= name.starts_with("JsValue::");
+                        uses.event_bus |= name.starts_with("EventBus::");
+                    }
+                }
+            }
+            fn visit_jsx_element(&mut self, jsx: &JsxElement) {
+                self.0.provide |= jsx.tag_name() == "Provide";
+                self.0.unsafe_html |= jsx.unsafe_html().is_some();
+                walk_jsx_element(self, jsx);
+            }
+            fn visit_inline_style(&mut self, style: &InlineStyle) {
+                self.0.escape |= style.declarations.iter()
+                    .flat_map(|decl| &decl.value)
+                    .any(|part| matches!(part, InlineStylePart::Dynamic(_)));
+                walk_inline_style(self, style);
+            }
+        }
+        let mut finder = Finder(RuntimeUses::default());
+        finder.visit_program(program);
+        finder.0
+    }
+
+    /// Enable test ids: the client bundle turns on runtime stamping of
+    /// `data-jounce-component` on component roots and `data-jounce-key` on keyed items
+    pub fn with_test_ids(mut self, enabled: bool) -> Self {
+        self.test_ids = enabled;
+        self
+    }
+
+    /// Enable the devtools registry: components carry name and prop metadata,
+    /// `let` bindings of signals are tracked by name, and the runtime exposes
+    /// the live component tree on `window.__JOUNCE_DEVTOOLS__`. Dev builds only.
+    pub fn with_devtools(mut self, enabled: bool) -> Self {
+        self.devtools = enabled;
+        self
+    }
+
+    /// Compile runtime profiling into the client bundle: component renders and
+    /// the effects and computeds they create are timed with performance marks,
+    /// aggregated on `window.__JOUNCE_PROFILE__`. Off, nothing of it is emitted.
+    pub fn with_runtime_profiling(mut self, enabled: bool) -> Self {
+        self.profile_runtime = enabled;
+        self
+    }
+
+    /// Load the build's CSS from the client bundle, linked or inline
+    pub fn with_stylesheet(mut self, stylesheet: Stylesheet) -> Self {
+        self.stylesheet = Some(stylesheet);
+        self
+    }
+
+    /// Start the client bundle from a validated entry point: components are
+    /// mounted, functions are called
+    pub fn with_entry(mut self, entry: EntryPoint) -> Self {
+        self.entry = Some(entry);
+        self
+    }
+
+    /// Whether a build should stamp test ids. Release builds drop them
+    /// unless they are explicitly kept.
+    pub fn test_ids_for_build(requested: bool, release: bool, keep_in_release: bool) -> bool {
+        if release {
+            keep_in_release
+        } else {
+            requested || keep_in_release
+        }
+    }
+
+    /// The items written by the last `generate_server_js` / `generate_client_js`,
+    /// in bundle order. Whatever a bundle holds beyond these is runtime prelude.
+    pub fn emitted_items(&self) -> Vec<EmittedItem> {
+        self.emitted.borrow().clone()
+    }
+
+    /// Start accounting for a bundle, dropping what an earlier run recorded
+    fn begin_bundle(&self, bundle: Bundle) {
+        self.emitted.borrow_mut().retain(|item| item.bundle != bundle);
+        self.stdlib_refs.borrow_mut().clear();
+    }
+
+    /// Record the code an item just appended to a bundle, along with the stdlib
+    /// namespaces referenced while generating it
+    fn record_item(&self, bundle: Bundle, kind: ItemKind, name: &str, code: &str) {
+        let stdlib_refs = self.stdlib_refs.take();
+        self.emitted.borrow_mut().push(EmittedItem {
+            bundle,
+            kind,
+            name: name.to_string(),
+            code: code.to_string(),
+            stdlib_refs,
+        });
+    }
+
+    /// Startup line for the entry point, if one was set
+    fn entry_start(&self) -> Option<String> {
+        self.entry.as_ref().map(|entry| {
+            if entry.mounts {
+                format!("  mountComponent({});\n", entry.name)
+            } else {
+                format!("  {}();\n", entry.name)
+            }
+        })
+    }
+
+    /// Runtime import line for the client bundle
+    fn client_runtime_import(&self) -> String {
+        let mut names = vec!["h", "RPCClient", "mountComponent"];
+        if !self.defines_function("sleep") {
+            names.push("sleep");
+        }
+        let uses = &self.runtime_uses;
+        if uses.event_bus {
+            names.push("EventBus");
+        }
+        if uses.js_value {
+            names.push("JsValue");
+        }
+        if self.uses_i18n {
+            names.push("I18n");
+        }
+        if uses.create_context && !self.defines_function("create_context") {
+            names.push("createContext as create_context");
+        }
+        if uses.use_context && !self.defines_function("use_context") {
+            names.push("useContext as use_context");
+        }
+        if uses.provide && !self.is_client_component("Provide") {
+            names.push("Provide");
+        }
+        if uses.on_cleanup && !self.defines_function("on_cleanup") {
+            names.push("onCleanup as on_cleanup");
+        }
+        if !self.defines_function("unsafe_raw") {
+            if uses.unsafe_raw || uses.unsafe_html {
+                names.push("unsafe_raw");
+            }
+        } else if uses.unsafe_html {
+            // For unsafe_html, which needs the runtime's own
+            names.push("unsafe_raw as __jounce_unsafe_raw");
+        }
+        if uses.escape {
+            names.push("escape as __jounce_escape");
+        }
+        if self.test_ids {
+            names.push("enableTestIds");
+        }
+        if self.uses_memo() {
+            names.push("memoize as __jounce_memoize");
+        }
+        if self.uses_refs {
+            names.push("bindRef as __jounce_ref");
+        }
+        if !self.splitter.server_functions.is_empty() {
+            names.push("rpcSignal as __jounce_rpc_signal");
+        }
+        format!("import {{ {} }} from './client-runtime.js';\n", names.join(", "))
+    }
+
+    /// Runtime import line for the server bundle
+    fn server_runtime_import(&self) -> String {
+        let mut names = vec!["HttpServer", "loadWasm"];
+        if !self.defines_function("sleep") {
+            names.push("sleep");
+        }
+        if self.uses_memo() {
+            names.push("memoize: __jounce_memoize");
+        }
+        if self.runtime_uses.js_value {
+            names.push("JsValue");
+        }
+        if self.uses_i18n {
+            names.push("I18n");
+        }
+        if !self.route_handlers().is_empty() {
+            names.push("routeParam: __jounce_route_param");
+        }
+        format!("const {{ {} }} = require('./server-runtime.js');\n", names.join(", "))
+    }
+
+    /// Functions with `@route`, registered on the server
+    fn route_handlers(&self) -> Vec<FunctionDefinition> {
+        self.splitter.server_functions.iter()
+            .chain(&self.splitter.shared_functions)
+            .filter(|f| f.route.is_some())
+            .cloned()
+            .collect()
+    }
+
+    /// Whether the program defines a function itself, shadowing the runtime's
+    fn defines_function(&self, name: &str) -> bool {
+        self.splitter.server_functions.iter()
+            .chain(&self.splitter.client_functions)
+            .chain(&self.splitter.shared_functions)
+            .any(|f| f.name.value == name)
+    }
+
+    /// Whether any function is annotated with @memo
+    fn uses_memo(&self) -> bool {
+        self.splitter.server_functions.iter()
+            .chain(&self.splitter.client_functions)
+            .chain(&self.splitter.shared_functions)
+            .any(|f| f.memo.is_some())
+    }
+
+    /// How the memo cache compares each argument: structs that derive Eq are
+    /// compared field by field, everything else with === (primitives by value,
+    /// objects by identity)
+    fn memo_key_kinds(&self, func: &FunctionDefinition) -> String {
+        let kinds = func.parameters
+            .iter()
+            .map(|p| {
+                let mut ty = &p.type_annotation;
+                while let TypeExpression::Reference(inner) = ty {
+                    ty = inner;
+                }
+                let derives_eq = match ty {
+                    TypeExpression::Named(ident) | TypeExpression::Generic(ident, _) => {
+                        self.splitter.structs.iter().any(|s| {
+                            s.name.value == ident.value
+                                && s.derives.iter().any(|d| d == "Eq" || d == "PartialEq")
+                        })
+                    }
+                    _ => false,
+                };
+                if derives_eq { "'eq'" } else { "'ref'" }
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("[{}]", kinds)
+    }
+
+    /// An attribute name as an object key: quoted unless it's a plain name,
+    /// so `data-test-id` and `xlink:href` reach the element as written
+    fn property_key(name: &str) -> String {
+        let plain = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_' || c == '$')
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+        if plain {
+            name.to_string()
+        } else {
+            serde_json::to_string(name).unwrap_or_default()
         }
     }
 
@@ -65,17 +515,29 @@ impl JSEmitter {
         }
     }
 
+    /// A variable name. Jounce keywords only get here as raw identifiers
+    /// (`r#in`), and the ones JavaScript reserves get the same suffix as
+    /// function names.
+    fn variable_name(name: &str) -> String {
+        if KEYWORDS.contains_key(name) {
+            Self::escape_js_reserved_word(name)
+        } else {
+            name.to_string()
+        }
+    }
+
     /// Generates the complete server.js file
     pub fn generate_server_js(&self) -> String {
         let mut output = String::new();
         let source_map = SourceMapBuilder::new("server.js".to_string());
+        self.begin_bundle(Bundle::Server);
 
         // Header comment
         output.push_str("// Auto-generated Jounce Server Bundle\n");
         output.push_str("// DO NOT EDIT - Generated by Jounce compiler\n\n");
 
         // Import runtime
-        output.push_str("const { HttpServer, loadWasm } = require('./server-runtime.js');\n");
+        output.push_str(&self.server_runtime_import());
         output.push_str("const fs = require('fs');\n");
         output.push_str("const path = require('path');\n\n");
 
@@ -83,6 +545,7 @@ impl JSEmitter {
         if !self.splitter.structs.is_empty() {
             output.push_str("// Struct definitions\n");
             for struct_def in &self.splitter.structs {
+                let start = output.len();
                 let params: Vec<String> = struct_def.fields.iter()
                     .map(|(name, _)| name.value.clone())
                     .collect();
@@ -95,6 +558,7 @@ impl JSEmitter {
                     output.push_str(&format!("  this.{} = {};\n", field_name.value, field_name.value));
                 }
                 output.push_str("}\n\n");
+                self.record_item(Bundle::Server, ItemKind::Struct, &struct_def.name.value, &output[start..]);
             }
         }
 
@@ -102,8 +566,10 @@ impl JSEmitter {
         if !self.splitter.enums.is_empty() {
             output.push_str("// Enum definitions\n");
             for enum_def in &self.splitter.enums {
+                let start = output.len();
                 output.push_str(&self.generate_enum_js(enum_def));
                 output.push_str("\n");
+                self.record_item(Bundle::Server, ItemKind::Enum, &enum_def.name.value, &output[start..]);
             }
         }
 
@@ -111,7 +577,9 @@ impl JSEmitter {
         if !self.splitter.impl_blocks.is_empty() {
             output.push_str("// Implementations\n");
             for impl_block in &self.splitter.impl_blocks {
+                let start = output.len();
                 output.push_str(&self.generate_impl_block_js(impl_block));
+                self.record_item(Bundle::Server, ItemKind::Impl, &impl_block.type_name.value, &output[start..]);
             }
             output.push_str("\n");
         }
@@ -130,20 +598,24 @@ impl JSEmitter {
         // Generate server function implementations
         output.push_str("// Server function implementations\n");
         for func in &self.splitter.server_functions {
+            let start = output.len();
             output.push_str(&self.generate_function_impl(func, true));
             output.push_str("\n\n");
+            self.record_item(Bundle::Server, ItemKind::Function, &func.name.value, &output[start..]);
         }
 
         // Generate shared function implementations
         output.push_str("// Shared utility functions\n");
         for func in &self.splitter.shared_functions {
+            let start = output.len();
             output.push_str(&self.generate_function_impl(func, true));
             output.push_str("\n\n");
+            self.record_item(Bundle::Server, ItemKind::Function, &func.name.value, &output[start..]);
         }
 
         // Generate RPC handlers
         output.push_str("// RPC Server Setup\n");
-        let rpc_gen = RPCGenerator::new(self.splitter.server_functions.clone());
+        let rpc_gen = RPCGenerator::new(self.splitter.server_functions.clone()).with_routes(self.route_handlers());
         output.push_str(&rpc_gen.generate_server_handlers());
 
         // Add source map reference comment
@@ -168,7 +640,7 @@ impl JSEmitter {
         current_line += 2;
 
         // Import runtime
-        output.push_str("const { HttpServer, loadWasm } = require('./server-runtime.js');\n");
+        output.push_str(&self.server_runtime_import());
         current_line += 1;
         output.push_str("const fs = require('fs');\n");
         current_line += 1;
@@ -243,7 +715,7 @@ impl JSEmitter {
         // Generate RPC handlers
         output.push_str("// RPC Server Setup\n");
         current_line += 1;
-        let rpc_gen = RPCGenerator::new(self.splitter.server_functions.clone());
+        let rpc_gen = RPCGenerator::new(self.splitter.server_functions.clone()).with_routes(self.route_handlers());
         let rpc_code = rpc_gen.generate_server_handlers();
         output.push_str(&rpc_code);
         current_line += rpc_code.lines().count();
@@ -260,14 +732,26 @@ impl JSEmitter {
     pub fn generate_client_js(&self) -> String {
         let mut output = String::new();
         let source_map = SourceMapBuilder::new("client.js".to_string());
+        self.begin_bundle(Bundle::Client);
 
         // Header comment
         output.push_str("// Auto-generated Jounce Client Bundle\n");
         output.push_str("// DO NOT EDIT - Generated by Jounce compiler\n\n");
 
         // Import runtime
-        output.push_str("import { h, RPCClient, mountComponent } from './client-runtime.js';\n");
+        output.push_str(&self.client_runtime_import());
         output.push_str("import { signal, computed, effect, batch } from './reactivity.js';\n\n");
+        if self.test_ids {
+            output.push_str("enableTestIds();\n\n");
+        }
+        if self.devtools {
+            output.push_str(DEVTOOLS_IMPORT);
+            output.push_str("enableDevtools();\n\n");
+        }
+        if self.profile_runtime {
+            output.push_str(PROFILE_RUNTIME);
+            output.push('\n');
+        }
 
         // Node.js crypto module for hashing and random functions
         output.push_str("// Node.js crypto module (for tests and server-side code)\n");
@@ -533,6 +1017,9 @@ impl JSEmitter {
         output.push_str("if (!String.from_char_code) {\n");
         output.push_str("  String.from_char_code = function(code) { return String.fromCharCode(code); };\n");
         output.push_str("}\n");
+        output.push_str("if (!String.from_code_point) {\n");
+        output.push_str("  String.from_code_point = function(code) { return String.fromCodePoint(code); };\n");
+        output.push_str("}\n");
         output.push_str("if (!String.new) {\n");
         output.push_str("  String.new = function() {\n");
         output.push_str("    const sb = { __value: \"\" };\n");
@@ -580,6 +1067,14 @@ impl JSEmitter {
         output.push_str("Option.prototype.unwrap = function() { if (this.variant === \"Some\") return this.data; throw new Error(\"Called unwrap on None\"); };\n");
         output.push_str("Option.prototype.unwrap_or = function(default_val) { return this.variant === \"Some\" ? this.data : default_val; };\n\n");
 
+        output.push_str("// The ? operator: an Err or None returns it from the function, which catches this\n");
+        output.push_str("class __JounceEarlyReturn { constructor(value) { this.value = value; } }\n");
+        output.push_str("function __jounce_try(v) { if (v.variant === \"Ok\" || v.variant === \"Some\") return v.data; throw new __JounceEarlyReturn(v); }\n\n");
+        if self.runtime_uses.js_value {
+            // JsValue accessors hand back this bundle's Options
+            output.push_str("JsValue.useOption(Some, None);\n\n");
+        }
+
         // HashMap type alias (JavaScript Map)
         output.push_str("// HashMap<K, V> is a JavaScript Map\n");
         output.push_str("const HashMap = Map;\n");
@@ -610,8 +1105,10 @@ impl JSEmitter {
         if !self.splitter.shared_constants.is_empty() {
             output.push_str("// Shared constants\n");
             for const_decl in &self.splitter.shared_constants {
+                let start = output.len();
                 let value = self.generate_expression_js(&const_decl.value);
                 output.push_str(&format!("const {} = {};\n", const_decl.name.value, value));
+                self.record_item(Bundle::Client, ItemKind::Constant, &const_decl.name.value, &output[start..]);
             }
             output.push_str("\n");
         }
@@ -619,6 +1116,7 @@ impl JSEmitter {
         // Generate struct constructors
         output.push_str("// Struct definitions\n");
         for struct_def in &self.splitter.structs {
+            let start = output.len();
             // Generate constructor function
             let params: Vec<String> = struct_def.fields.iter()
                 .map(|(name, _)| name.value.clone())
@@ -632,41 +1130,52 @@ impl JSEmitter {
                 output.push_str(&format!("  this.{} = {};\n", field_name.value, field_name.value));
             }
             output.push_str("}\n\n");
+            self.record_item(Bundle::Client, ItemKind::Struct, &struct_def.name.value, &output[start..]);
         }
 
         // Generate enum definitions (BEFORE impl blocks!)
         output.push_str("// Enum definitions\n");
         for enum_def in &self.splitter.enums {
+            let start = output.len();
             output.push_str(&self.generate_enum_js(enum_def));
             output.push_str("\n");
+            self.record_item(Bundle::Client, ItemKind::Enum, &enum_def.name.value, &output[start..]);
         }
 
         // Generate impl blocks (after enums and structs are defined)
         output.push_str("// Implementations\n");
         for impl_block in &self.splitter.impl_blocks {
+            let start = output.len();
             output.push_str(&self.generate_impl_block_js(impl_block));
+            self.record_item(Bundle::Client, ItemKind::Impl, &impl_block.type_name.value, &output[start..]);
         }
 
         // Generate client function implementations
         output.push_str("// Client function implementations\n");
         for func in &self.splitter.client_functions {
+            let start = output.len();
             output.push_str(&self.generate_function_impl(func, false));
             output.push_str("\n\n");
+            self.record_item(Bundle::Client, ItemKind::Function, &func.name.value, &output[start..]);
         }
 
         // Generate shared function implementations
         output.push_str("// Shared utility functions\n");
         for func in &self.splitter.shared_functions {
+            let start = output.len();
             output.push_str(&self.generate_function_impl(func, false));
             output.push_str("\n\n");
+            self.record_item(Bundle::Client, ItemKind::Function, &func.name.value, &output[start..]);
         }
 
         // Create namespace objects for stdlib modules
         output.push_str("// Stdlib module namespaces\n");
+        let start = output.len();
         output.push_str("const json = {\n");
         output.push_str("  parse: typeof parse !== 'undefined' ? parse : undefined,\n");
         output.push_str("  stringify: typeof stringify !== 'undefined' ? stringify : undefined,\n");
         output.push_str("  stringify_pretty: typeof stringify_pretty !== 'undefined' ? stringify_pretty : undefined,\n");
+        output.push_str("  stringify_indented: typeof stringify_indented !== 'undefined' ? stringify_indented : undefined,\n");
         output.push_str("  // Helper functions for creating JSON values\n");
         output.push_str("  null_: () => ({ variant: 'Null' }),\n");
         output.push_str("  bool: (b) => ({ variant: 'Bool', data: b }),\n");
@@ -675,7 +1184,9 @@ impl JSEmitter {
         output.push_str("  array: (arr) => ({ variant: 'Array', data: arr || [] }),\n");
         output.push_str("  object: (obj) => ({ variant: 'Object', data: obj || {} }),\n");
         output.push_str("};\n\n");
+        self.record_item(Bundle::Client, ItemKind::Stdlib, "json", &output[start..]);
 
+        let start = output.len();
         output.push_str("const crypto = {\n");
         output.push_str("  sha256: typeof sha256 !== 'undefined' ? sha256 : undefined,\n");
         output.push_str("  sha1: typeof sha1 !== 'undefined' ? sha1 : undefined,\n");
@@ -695,7 +1206,9 @@ impl JSEmitter {
         output.push_str("  hash_password_auto: typeof hash_password_auto !== 'undefined' ? hash_password_auto : undefined,\n");
         output.push_str("  generate_salt: typeof generate_salt !== 'undefined' ? generate_salt : undefined,\n");
         output.push_str("};\n\n");
+        self.record_item(Bundle::Client, ItemKind::Stdlib, "crypto", &output[start..]);
 
+        let start = output.len();
         output.push_str("const fs = {\n");
         output.push_str("  read_to_string: typeof read_to_string !== 'undefined' ? read_to_string : undefined,\n");
         output.push_str("  read: typeof read !== 'undefined' ? read : undefined,\n");
@@ -723,7 +1236,9 @@ impl JSEmitter {
         output.push_str("  walk_dir: typeof walk_dir !== 'undefined' ? walk_dir : undefined,\n");
         output.push_str("  glob: typeof glob !== 'undefined' ? glob : undefined,\n");
         output.push_str("};\n\n");
+        self.record_item(Bundle::Client, ItemKind::Stdlib, "fs", &output[start..]);
 
+        let start = output.len();
         output.push_str("const yaml = {\n");
         output.push_str("  parse: typeof yaml_parse !== 'undefined' ? yaml_parse : undefined,\n");
         output.push_str("  stringify: typeof yaml_stringify !== 'undefined' ? yaml_stringify : undefined,\n");
@@ -735,21 +1250,28 @@ impl JSEmitter {
         output.push_str("  yaml_sequence: typeof yaml_sequence !== 'undefined' ? yaml_sequence : undefined,\n");
         output.push_str("  yaml_mapping: typeof yaml_mapping !== 'undefined' ? yaml_mapping : undefined,\n");
         output.push_str("};\n\n");
+        self.record_item(Bundle::Client, ItemKind::Stdlib, "yaml", &output[start..]);
 
         // Generate component implementations
         output.push_str("// UI Components\n");
         for comp in &self.splitter.client_components {
+            let start = output.len();
             output.push_str(&self.generate_component_impl(comp));
             output.push_str("\n\n");
+            self.record_item(Bundle::Client, ItemKind::Component, &comp.name.value, &output[start..]);
         }
 
+        output.push_str(&self.stylesheet_js());
+
         // Generate main entry point
         output.push_str("// Initialize application\n");
         output.push_str("window.addEventListener('DOMContentLoaded', () => {\n");
         output.push_str("  console.log('Jounce client initialized');\n");
 
-        // Auto-mount first component if exists
-        if let Some(comp) = self.splitter.client_components.first() {
+        // Start from the entry point, else auto-mount the first component
+        if let Some(start) = self.entry_start() {
+            output.push_str(&start);
+        } else if let Some(comp) = self.splitter.client_components.first() {
             output.push_str(&format!("  mountComponent({});\n", comp.name.value));
         } else {
             // Check if there's an App function (convention for main component)
@@ -785,9 +1307,23 @@ impl JSEmitter {
         current_line += 2;
 
         // Import runtime
-        output.push_str("import { h, RPCClient, mountComponent } from './client-runtime.js';\n");
+        output.push_str(&self.client_runtime_import());
         output.push_str("import { signal, computed, effect, batch } from './reactivity.js';\n\n");
         current_line += 2;
+        if self.test_ids {
+            output.push_str("enableTestIds();\n\n");
+            current_line += 2;
+        }
+        if self.devtools {
+            output.push_str(DEVTOOLS_IMPORT);
+            output.push_str("enableDevtools();\n\n");
+            current_line += 3;
+        }
+        if self.profile_runtime {
+            output.push_str(PROFILE_RUNTIME);
+            output.push('\n');
+            current_line += PROFILE_RUNTIME.lines().count() + 1;
+        }
 
         // Generate RPC client stubs
         output.push_str("// RPC Client Setup\n");
@@ -865,6 +1401,10 @@ impl JSEmitter {
             current_line += 2;
         }
 
+        let stylesheet = self.stylesheet_js();
+        output.push_str(&stylesheet);
+        current_line += stylesheet.lines().count();
+
         // Generate main entry point
         output.push_str("// Initialize application\n");
         current_line += 1;
@@ -873,8 +1413,11 @@ impl JSEmitter {
         output.push_str("  console.log('Jounce client initialized');\n");
         current_line += 1;
 
-        // Auto-mount first component if exists
-        if let Some(comp) = self.splitter.client_components.first() {
+        // Start from the entry point, else auto-mount the first component
+        if let Some(start) = self.entry_start() {
+            output.push_str(&start);
+            current_line += 1;
+        } else if let Some(comp) = self.splitter.client_components.first() {
             output.push_str(&format!("  mountComponent({});\n", comp.name.value));
             current_line += 1;
         } else {
@@ -910,7 +1453,27 @@ impl JSEmitter {
 
         let async_keyword = if func.is_async { "async " } else { "" };
         // Use generate_block_js_impl with is_function_body=true to handle implicit returns
-        let body = self.generate_block_js_impl(&func.body, true);
+        let outer = self.css_owner.replace(Some(func.name.value.clone()));
+        let body = if func.is_async && !is_server {
+            self.with_rpc_signal(|| self.generate_block_js_impl(&func.body, true))
+        } else {
+            self.generate_block_js_impl(&func.body, true)
+        };
+        self.css_owner.replace(outer);
+
+        if let Some(capacity) = func.memo {
+            // @memo: wrap in the runtime cache; recursive calls go through the
+            // memoized binding as well
+            let memoized = format!(
+                "__jounce_memoize({}function({}) {{\n{}\n}}, {}, {})",
+                async_keyword, params, body, capacity, self.memo_key_kinds(func)
+            );
+            return if is_server {
+                format!("const {} = {};\nmodule.exports.{} = {};", name, memoized, name, name)
+            } else {
+                format!("export const {} = {};", name, memoized)
+            };
+        }
 
         if is_server {
             // Server-side: module.exports.name = function() { ... }
@@ -930,18 +1493,121 @@ impl JSEmitter {
     /// Generates a JavaScript component implementation from AST
     fn generate_component_impl(&self, comp: &ComponentDefinition) -> String {
         let name = Self::escape_js_reserved_word(&comp.name.value);
-        let params = comp.parameters
+        // The runtime calls components with a props object: h(Card, { title }).
+        // Destructure it, or the first parameter would get the whole object.
+        // Named slots are props too; the children slot is the second argument.
+        let (default_slot, props): (Vec<_>, Vec<_>) = comp.parameters
             .iter()
-            .map(|p| Self::escape_js_reserved_word(&p.name.value))
-            .collect::<Vec<_>>()
-            .join(", ");
+            .partition(|p| p.is_slot && p.name.value == DEFAULT_SLOT);
+        let params = if props.is_empty() {
+            String::new()
+        } else {
+            let props = props
+                .iter()
+                .map(|p| {
+                    let local = Self::escape_js_reserved_word(&p.name.value);
+                    if local == p.name.value { local } else { format!("{}: {}", p.name.value, local) }
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("{{ {} }} = {{}}", props)
+        };
+        let params = match (default_slot.is_empty(), params.is_empty()) {
+            (true, _) => params,
+            (false, true) => format!("_props, {}", DEFAULT_SLOT),
+            (false, false) => format!("{}, {}", params, DEFAULT_SLOT),
+        };
 
-        let body = self.generate_block_js(&comp.body);
+        // Static class names in the body refer to this component's css! classes
+        let outer = self.current_component.replace(Some(comp.name.value.clone()));
+        let outer_owner = self.css_owner.replace(Some(comp.name.value.clone()));
+        let mut body = self.generate_block_js(&comp.body);
+        self.css_owner.replace(outer_owner);
+        self.current_component.replace(outer);
+        if self.profile_runtime {
+            body = format!("  return __jounce_measure({:?}, \"render\", () => {{\n{}\n  }});", comp.name.value, body);
+        }
 
-        format!(
+        let mut code = format!(
             "export function {}({}) {{\n{}\n}}",
             name, params, body
-        )
+        );
+        if self.devtools {
+            code.push('\n');
+            code.push_str(&Self::devtools_metadata(&name, comp));
+        }
+        code
+    }
+
+    /// Code that loads the stylesheet as the client bundle runs, so it's in
+    /// place before anything mounts
+    fn stylesheet_js(&self) -> String {
+        let mut output = String::new();
+        match &self.stylesheet {
+            None => {}
+            Some(Stylesheet::Linked(href)) => {
+                let href = serde_json::to_string(href).unwrap_or_default();
+                let selector = format!("link[rel=\"stylesheet\"][href={}]", href);
+                output.push_str("// Stylesheet\n");
+                output.push_str(&format!("if (!document.querySelector({})) {{\n", serde_json::to_string(&selector).unwrap_or_default()));
+                output.push_str("  const link = document.createElement('link');\n");
+                output.push_str("  link.rel = 'stylesheet';\n");
+                output.push_str(&format!("  link.href = {};\n", href));
+                output.push_str("  document.head.appendChild(link);\n");
+                output.push_str("}\n\n");
+            }
+            Some(Stylesheet::Inline(css)) => {
+                output.push_str("// Stylesheet, inlined\n");
+                output.push_str("{\n");
+                output.push_str("  const style = document.createElement('style');\n");
+                output.push_str(&format!("  style.textContent = {};\n", serde_json::to_string(css).unwrap_or_default()));
+                output.push_str("  document.head.appendChild(style);\n");
+                output.push_str("}\n\n");
+            }
+        }
+        output
+    }
+
+    /// An effect or computed function, timed under its component when profiling
+    fn profiled(&self, function: String, kind: &str) -> String {
+        match self.current_component.borrow().as_ref() {
+            Some(component) if self.profile_runtime => {
+                format!("__jounce_profiled({:?}, {:?}, {})", component, kind, function)
+            }
+            _ => function,
+        }
+    }
+
+    /// Generate a client async function body whose server calls pass the
+    /// signal of the component it was called for: the one rendering, or the
+    /// one whose event handler is running, when the body starts.
+    fn with_rpc_signal(&self, generate: impl FnOnce() -> String) -> String {
+        let outer = self.rpc_signal.replace(Some(false));
+        let body = generate();
+        if self.rpc_signal.replace(outer) == Some(true) {
+            format!("  const __jounce_signal = __jounce_rpc_signal();\n{}", body)
+        } else {
+            body
+        }
+    }
+
+    /// Arguments of a server call, plus the enclosing component's signal
+    fn rpc_call_args(&self, mut args: Vec<String>) -> String {
+        if let Some(used) = self.rpc_signal.borrow_mut().as_mut() {
+            *used = true;
+            args.push("__jounce_signal".to_string());
+        }
+        args.join(", ")
+    }
+
+    /// Component name and prop types for the devtools panel
+    fn devtools_metadata(name: &str, comp: &ComponentDefinition) -> String {
+        let props = comp.parameters
+            .iter()
+            .map(|p| serde_json::json!({ "name": p.name.value, "type": type_name(&p.type_annotation) }))
+            .collect::<Vec<_>>();
+        let meta = serde_json::json!({ "name": comp.name.value, "props": props });
+        format!("{}.__jounceMeta = {};", name, meta)
     }
 
     /// Generates JavaScript code for a block statement
@@ -977,9 +1643,32 @@ impl JSEmitter {
             output.push('\n');
         }
 
+        if is_function_body && Self::uses_try_operator(|finder| finder.visit_block(block)) {
+            let body: String = output.lines().map(|line| format!("  {}\n", line)).collect();
+            output = format!(
+                "  try {{\n{}  }} catch (e) {{\n    if (e instanceof __JounceEarlyReturn) return e.value;\n    throw e;\n  }}\n",
+                body
+            );
+        }
+
         output
     }
 
+    /// Whether a function or closure body uses `?` itself, not just in
+    /// closures it defines, which catch their own early returns
+    fn uses_try_operator(visit: impl FnOnce(&mut dyn Visit)) -> bool {
+        struct Finder(bool);
+        impl Visit for Finder {
+            fn visit_try_operator(&mut self, _try_expr: &TryOperatorExpression) {
+                self.0 = true;
+            }
+            fn visit_lambda(&mut self, _lambda: &LambdaExpression) {}
+        }
+        let mut finder = Finder(false);
+        visit(&mut finder);
+        finder.0
+    }
+
     /// Generates JavaScript code for a block statement (normal blocks, not function bodies)
     fn generate_block_js(&self, block: &BlockStatement) -> String {
         self.generate_block_js_impl(block, false)
@@ -1065,12 +1754,12 @@ impl JSEmitter {
 
                 // Generate pattern for left-hand side
                 let pattern_str = match &let_stmt.pattern {
-                    Pattern::Identifier(id) => id.value.clone(),
+                    Pattern::Identifier(id) => Self::variable_name(&id.value),
                     Pattern::Tuple(patterns) => {
                         // Generate tuple destructuring: let [a, b, c] = value;
                         let names: Vec<String> = patterns.iter().map(|p| {
                             match p {
-                                Pattern::Identifier(id) => id.value.clone(),
+                                Pattern::Identifier(id) => Self::variable_name(&id.value),
                                 _ => "_".to_string(),  // Nested patterns become wildcards for now
                             }
                         }).collect();
@@ -1082,6 +1771,9 @@ impl JSEmitter {
                 // Special case: if pattern is "_", just execute the expression (discard result)
                 if pattern_str == "_" {
                     format!("{};", value)
+                } else if self.devtools && matches!(let_stmt.value, Expression::Signal(_)) {
+                    // Owned by whichever component is rendering, under its binding name
+                    format!("let {} = __jounce_track({}, {:?});", pattern_str, value, pattern_str)
                 } else {
                     format!("let {} = {};", pattern_str, value)
                 }
@@ -1089,7 +1781,7 @@ impl JSEmitter {
             Statement::Const(const_decl) => {
                 // Emit const declarations as JavaScript const
                 let value = self.generate_expression_js(&const_decl.value);
-                format!("const {} = {};", const_decl.name.value, value)
+                format!("const {} = {};", Self::variable_name(&const_decl.name.value), value)
             }
             Statement::Assignment(assign_stmt) => {
                 let target = self.generate_expression_js(&assign_stmt.target);
@@ -1300,7 +1992,13 @@ impl JSEmitter {
             Expression::Identifier(ident) => {
                 // Handle namespaced identifiers (e.g., Stopwatch::new, json::parse)
                 // Convert :: to . for JavaScript (e.g., Stopwatch.new, json.parse)
-                ident.value.replace("::", ".")
+                if let Some((namespace, _)) = ident.value.split_once("::") {
+                    if STDLIB_NAMESPACES.contains(&namespace) {
+                        self.stdlib_refs.borrow_mut().insert(namespace.to_string());
+                    }
+                    return ident.value.replace("::", ".");
+                }
+                Self::variable_name(&ident.value)
             }
             Expression::IntegerLiteral(value) => value.to_string(),
             Expression::FloatLiteral(value) => value.clone(),
@@ -1314,6 +2012,20 @@ impl JSEmitter {
                     .replace('\t', "\\t");  // Escape tabs
                 format!("\"{}\"", escaped)
             }
+            Expression::Template(template) => {
+                // A JS template literal; text is escaped so only our ${} splice
+                let body = template.parts
+                    .iter()
+                    .map(|part| match part {
+                        crate::ast::TemplatePart::Text(text) => text
+                            .replace('\\', "\\\\")
+                            .replace('`', "\\`")
+                            .replace("${", "\\${"),
+                        crate::ast::TemplatePart::Interpolation(expr) => format!("${{{}}}", self.generate_expression_js(expr)),
+                    })
+                    .collect::<String>();
+                format!("`{}`", body)
+            }
             Expression::BoolLiteral(value) => value.to_string(),
             Expression::UnitLiteral => "undefined".to_string(),  // () maps to undefined in JS
             Expression::Infix(infix) => {
@@ -1339,10 +2051,42 @@ impl JSEmitter {
                     .collect::<Vec<_>>()
                     .join(", ");
 
-                // Check if this is a server function call from client-side
                 if let Expression::Identifier(ident) = &*call.function {
+                    // Check if this is a server function call from client-side
                     if self.is_server_function(&ident.value) {
-                        return format!("await {}({})", func, args);
+                        let args = call.arguments.iter().map(|arg| self.generate_expression_js(arg)).collect();
+                        return format!("await {}({})", func, self.rpc_call_args(args));
+                    }
+
+                    // JsValues cross into extern JavaScript as raw values and come back wrapped
+                    if let Some(boundary) = self.js_value_externs.get(&ident.value) {
+                        let args = call.arguments
+                            .iter()
+                            .enumerate()
+                            .map(|(i, arg)| {
+                                let arg = self.generate_expression_js(arg);
+                                if boundary.params.get(i) == Some(&true) {
+                                    format!("JsValue.unwrap({})", arg)
+                                } else {
+                                    arg
+                                }
+                            })
+                            .collect::<Vec<_>>()
+                            .join(", ");
+                        return if boundary.returns {
+                            format!("JsValue.wrap({}({}))", func, args)
+                        } else if boundary.resolves {
+                            format!("{}({}).then(JsValue.wrap)", func, args)
+                        } else {
+                            format!("{}({})", func, args)
+                        };
+                    }
+
+                    // Promise combinators get the server calls' promises, so they run concurrently
+                    if matches!(ident.value.as_str(), "Promise::all" | "Promise::race" | "Promise::any" | "Promise::allSettled") {
+                        if let [list] = call.arguments.as_slice() {
+                            return format!("{}({})", func, self.generate_pending_list_js(list));
+                        }
                     }
                 }
 
@@ -1399,6 +2143,19 @@ impl JSEmitter {
                         }
                     }
                     "panic" => format!("throw new Error({})", args.join(", ")),
+                    "t" => {
+                        // Values are passed by the placeholder they fill: t!("{count} items", count)
+                        let values = i18n::argument_names(macro_call)
+                            .iter()
+                            .zip(args.iter().skip(1))
+                            .map(|(name, value)| format!("{}: {}", name, value))
+                            .collect::<Vec<_>>();
+                        if values.is_empty() {
+                            format!("I18n.t({})", args[0])
+                        } else {
+                            format!("I18n.t({}, {{ {} }})", args[0], values.join(", "))
+                        }
+                    }
                     _ => format!("{}({})", macro_call.name.value, args.join(", ")),
                 }
             }
@@ -1454,7 +2211,8 @@ impl JSEmitter {
             }
             Expression::FieldAccess(field) => {
                 let object = self.generate_expression_js(&field.object);
-                format!("{}.{}", object, field.field.value)
+                let dot = if field.optional { "?." } else { "." };
+                format!("{}{}{}", object, dot, field.field.value)
             }
             Expression::JsxElement(jsx) => {
                 self.generate_jsx_js(jsx)
@@ -1465,11 +2223,10 @@ impl JSEmitter {
                 format!("await {}", inner)
             }
             Expression::TryOperator(try_expr) => {
-                // Generate JavaScript for ? operator (error propagation)
-                // For now, use unwrap() - proper early return requires statement-level transformation
-                // TODO: Implement proper early return at function body level
+                // ? operator: the Ok/Some value, or an early return of the
+                // Err/None, which the enclosing function body catches
                 let inner = self.generate_expression_js(&try_expr.expression);
-                format!("({}.unwrap())", inner)
+                format!("__jounce_try({})", inner)
             }
             Expression::Ternary(ternary) => {
                 // Generate JavaScript ternary expression: condition ? true_expr : false_expr
@@ -1572,20 +2329,19 @@ impl JSEmitter {
             }
             Expression::Computed(computed_expr) => {
                 let computation = self.generate_expression_js(&computed_expr.computation);
-                format!("computed({})", computation)
+                format!("computed({})", self.profiled(computation, "computed"))
             }
             Expression::Effect(effect_expr) => {
                 let callback = self.generate_expression_js(&effect_expr.callback);
-                format!("effect({})", callback)
+                format!("effect({})", self.profiled(callback, "effect"))
             }
             Expression::Batch(batch_expr) => {
                 let body = self.generate_expression_js(&batch_expr.body);
                 format!("batch({})", body)
             }
-            Expression::CssMacro(_) => {
-                // CSS macro handled separately
-                "/* CSS */".to_string()
-            }
+            Expression::CssMacro(css) => self.generate_css_handle_js(css),
+            Expression::InlineStyle(style) => self.generate_inline_style_js(style),
+            Expression::Classes(classes) => self.generate_classes_js(classes),
             Expression::Lambda(lambda_expr) => {
                 // Generate JavaScript arrow function: (param1, param2) => body
                 let params = lambda_expr.parameters
@@ -1594,20 +2350,45 @@ impl JSEmitter {
                     .collect::<Vec<_>>()
                     .join(", ");
 
-                let body = self.generate_expression_js(&lambda_expr.body);
+                // A closure using `?` returns the Err or None it stops at
+                if Self::uses_try_operator(|finder| finder.visit_expression(&lambda_expr.body)) {
+                    let body = match lambda_expr.body.as_ref() {
+                        Expression::Block(block) => self.generate_lambda_block_js(block),
+                        body => format!("return {};", self.generate_expression_js(body)),
+                    };
+                    return format!(
+                        "({}) => {{ try {{ {} }} catch (e) {{ if (e instanceof __JounceEarlyReturn) return e.value; throw e; }} }}",
+                        params, body
+                    );
+                }
 
-                // If body is a block statement, use braces
-                // Otherwise, use concise arrow function syntax
-                if matches!(*lambda_expr.body, Expression::Block(_)) {
-                    format!("({}) => {}", params, body)
-                } else {
-                    format!("({}) => {}", params, body)
+                // A block body keeps its braces, returning its last expression;
+                // anything else uses the concise arrow syntax
+                match lambda_expr.body.as_ref() {
+                    Expression::Block(block) if !matches!(block.statements.as_slice(), [Statement::Expression(_)]) => {
+                        format!("({}) => {{ {} }}", params, self.generate_lambda_block_js(block))
+                    }
+                    body => format!("({}) => {}", params, self.generate_expression_js(body)),
                 }
             }
             _ => "/* Unsupported expression */".to_string(),
         }
     }
 
+    /// A closure's block body on one line, returning its last expression
+    fn generate_lambda_block_js(&self, block: &BlockStatement) -> String {
+        let last = block.statements.len().saturating_sub(1);
+        block.statements
+            .iter()
+            .enumerate()
+            .map(|(i, stmt)| match stmt {
+                Statement::Expression(expr) if i == last => format!("return {};", self.generate_expression_js(expr)),
+                stmt => self.generate_statement_js(stmt).trim_end().to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
     /// Generates JavaScript code for a match expression
     /// Enums are represented as: { variant: "VariantName", data: value }
     fn generate_match_expression_js(&self, match_expr: &crate::ast::MatchExpression) -> String {
@@ -1810,36 +2591,313 @@ impl JSEmitter {
         let tag = &jsx.opening_tag.name.value;
 
         // Generate attributes
-        let attrs = if jsx.opening_tag.attributes.is_empty() {
-            String::new()
-        } else {
-            let attrs_str = jsx.opening_tag.attributes
-                .iter()
-                .map(|attr| {
-                    let val = self.generate_expression_js(&attr.value);
-                    format!("{}: {}", attr.name.value, val)
-                })
+        let mut attrs = jsx.opening_tag.attributes
+            .iter()
+            .flat_map(|attr| {
+                // Spread in place, so props after it override its keys and it overrides those before
+                if let Some(spread) = attr.spread() {
+                    return vec![format!("...{}", self.generate_expression_js(spread))];
+                }
+                if attr.name.value == crate::ast::BIND_VALUE {
+                    if let Some(binding) = jsx.value_binding() {
+                        return self.generate_value_binding_js(binding, &attr.value);
+                    }
+                }
+                // onSubmit|prevent={save}: the modifiers are lowered here, so the
+                // runtime only sees a handler and its listener options
+                let (event, modifiers) = attr.event_modifiers();
+                if !modifiers.is_empty() {
+                    return vec![format!("{}: {}", Self::property_key(event), self.generate_modified_handler_js(&attr.value, &modifiers))];
+                }
+                // ref={x} hands the element to x: a closure is called with it, and
+                // anything else gets a setter the runtime points at x's signal, if
+                // it holds one, or at x itself
+                if attr.name.value == crate::ast::JSX_REF && jsx.element_ref().is_some() {
+                    let target = self.generate_expression_js(&attr.value);
+                    if matches!(attr.value, Expression::Lambda(_)) {
+                        return vec![format!("ref: {}", target)];
+                    }
+                    return vec![format!("ref: __jounce_ref({}, (node) => {{ {} = node; }})", target, target)];
+                }
+                // The runtime parses inner_html only when unsafe_raw() vouches for it
+                if attr.name.value == crate::ast::UNSAFE_HTML && jsx.unsafe_html().is_some() {
+                    let unsafe_raw = if self.defines_function("unsafe_raw") { "__jounce_unsafe_raw" } else { "unsafe_raw" };
+                    return vec![format!(
+                        "inner_html: /* unsafe_html: set as markup, with no escaping */ {}({})",
+                        unsafe_raw,
+                        self.generate_expression_js(&attr.value)
+                    )];
+                }
+                let val = match &attr.value {
+                    Expression::StringLiteral(classes) if matches!(attr.name.value.as_str(), "class" | "className") => {
+                        self.generate_expression_js(&Expression::StringLiteral(self.scope_classes(classes)))
+                    }
+                    value => self.generate_expression_js(value),
+                };
+                vec![format!("{}: {}", Self::property_key(&attr.name.value), val)]
+            })
+            .collect::<Vec<_>>();
+
+        // <slot:header> children are passed to the component as a `header` prop
+        // holding their contents, rendered here so they see the caller's scope
+        let mut children = Vec::new();
+        for child in &jsx.children {
+            match child {
+                crate::ast::JsxChild::Element(elem) if elem.slot_name().is_some() => {
+                    let contents = elem.children.iter().map(|child| self.generate_jsx_child_js(child)).collect::<Vec<_>>();
+                    attrs.push(format!("{}: [{}]", elem.slot_name().unwrap_or_default(), contents.join(", ")));
+                }
+                child => children.push(self.generate_jsx_child_js(child)),
+            }
+        }
+        let attrs = if attrs.is_empty() { ", null".to_string() } else { format!(", {{ {} }}", attrs.join(", ")) };
+        let children = children.join(", ");
+
+        // <Provide> renders its children itself, once the value is in place
+        if tag == "Provide" && !self.is_client_component(tag) {
+            return format!("h(Provide{}, () => [{}])", attrs, children);
+        }
+
+        // Components are passed to h() as functions so the runtime can render
+        // (and stamp) them; everything else is a DOM tag name. A dotted name
+        // like `ui.Button` is always a component, reached by property access.
+        let tag = if tag.contains('.') {
+            tag.split('.').enumerate()
+                .map(|(i, part)| if i == 0 { Self::escape_js_reserved_word(part) } else { part.to_string() })
                 .collect::<Vec<_>>()
-                .join(", ");
-            format!(", {{ {} }}", attrs_str)
+                .join(".")
+        } else if self.is_client_component(tag) {
+            Self::escape_js_reserved_word(tag)
+        } else {
+            format!("'{}'", tag)
         };
 
-        // Generate children
-        let children = jsx.children
-            .iter()
-            .map(|child| match child {
-                crate::ast::JsxChild::Element(elem) => self.generate_jsx_js(elem),
-                crate::ast::JsxChild::Text(text) => format!("\"{}\"", text),
-                crate::ast::JsxChild::Expression(expr) => self.generate_expression_js(expr),
-            })
+        if children.is_empty() {
+            format!("h({}{})", tag, attrs)
+        } else {
+            format!("h({}{}, {})", tag, attrs, children)
+        }
+    }
+
+    /// `bind:value={sig}` becomes the signal as the field's `value` (or
+    /// `checked`) prop, which the runtime keeps in step with it, and a handler
+    /// writing the field's edits back
+    fn generate_value_binding_js(&self, binding: crate::ast::ValueBinding, bound: &Expression) -> Vec<String> {
+        use crate::ast::ValueBinding;
+
+        let sig = self.generate_expression_js(bound);
+        let write = |value: &str| format!("{}.value = {};", sig, value);
+        let body = match binding {
+            ValueBinding::Text | ValueBinding::Radio | ValueBinding::Select => write("event.target.value"),
+            // Skip what isn't a number yet, like an empty field or "1e"
+            ValueBinding::Number => format!(
+                "if (!Number.isNaN(event.target.valueAsNumber)) {{ {} }}",
+                write("event.target.valueAsNumber")
+            ),
+            ValueBinding::Checkbox => write("event.target.checked"),
+            ValueBinding::CheckboxGroup => write(&format!(
+                "event.target.checked ? [...{}.value, event.target.value] : {}.value.filter((item) => item !== event.target.value)",
+                sig, sig
+            )),
+            ValueBinding::SelectMultiple => write("Array.from(event.target.selectedOptions, (option) => option.value)"),
+        };
+        vec![
+            format!("{}: {}", binding.property(), sig),
+            format!("{}: (event) => {{ {} }}", binding.handler(), body),
+        ]
+    }
+
+    /// A handler with modifiers, as in `onClick|self|once={close}`: `self`,
+    /// `prevent` and `stop` become checks and calls before the handler runs,
+    /// and `once`, `capture` and `passive` go to addEventListener as
+    /// `{ handleEvent, once: true }`
+    fn generate_modified_handler_js(&self, handler: &Expression, modifiers: &[&str]) -> String {
+        let handler_js = self.generate_expression_js(handler);
+        let guards: Vec<&str> = [
+            ("self", "if (event.target !== event.currentTarget) { return; }"),
+            ("prevent", "event.preventDefault();"),
+            ("stop", "event.stopPropagation();"),
+        ]
+        .into_iter()
+        .filter(|(modifier, _)| modifiers.contains(modifier))
+        .map(|(_, guard)| guard)
+        .collect();
+        let handler_js = if guards.is_empty() {
+            handler_js
+        } else if matches!(handler, Expression::Identifier(_) | Expression::FieldAccess(_)) {
+            format!("(event) => {{ {} return {}(event); }}", guards.join(" "), handler_js)
+        } else {
+            format!("(event) => {{ {} return ({})(event); }}", guards.join(" "), handler_js)
+        };
+
+        let options: Vec<String> = ["once", "capture", "passive"]
+            .into_iter()
+            .filter(|option| modifiers.contains(option))
+            .map(|option| format!("{}: true", option))
+            .collect();
+        if options.is_empty() {
+            handler_js
+        } else {
+            format!("{{ handleEvent: {}, {} }}", handler_js, options.join(", "))
+        }
+    }
+
+    /// Text is a string literal of what was written (entities already
+    /// decoded) and an expression is passed as its value; the runtime makes
+    /// both text nodes, so neither is ever parsed as markup
+    fn generate_jsx_child_js(&self, child: &crate::ast::JsxChild) -> String {
+        match child {
+            crate::ast::JsxChild::Element(elem) => self.generate_jsx_js(elem),
+            crate::ast::JsxChild::Text(text) => serde_json::to_string(text).unwrap_or_default(),
+            crate::ast::JsxChild::Expression(expr) => self.generate_expression_js(expr),
+        }
+    }
+
+    /// A css! block evaluates to its handle: an object from each class the
+    /// block names to its scoped name, `{ "button": "Card_button_a3f5c9" }`.
+    /// The stylesheet itself is generated separately.
+    fn generate_css_handle_js(&self, css: &crate::ast::CssExpression) -> String {
+        let owner = self.css_owner.borrow().clone().unwrap_or_else(|| "App".to_string());
+        let mut generator = CssGenerator::new(owner);
+        generator.generate(css);
+        let classes: BTreeMap<_, _> = generator.get_class_map().iter().collect();
+        if classes.is_empty() {
+            return "{}".to_string();
+        }
+        let fields = classes
+            .into_iter()
+            .map(|(class, scoped)| format!("{}: {}", serde_json::to_string(class).unwrap_or_default(), serde_json::to_string(scoped).unwrap_or_default()))
             .collect::<Vec<_>>()
             .join(", ");
+        format!("{{ {} }}", fields)
+    }
 
-        if children.is_empty() {
-            format!("h('{}'{}, null)", tag, attrs)
-        } else {
-            format!("h('{}'{}, {})", tag, attrs, children)
+    /// Rewrites the classes the current component defines in css! to their
+    /// scoped names; other classes are kept as written
+    fn scope_classes(&self, classes: &str) -> String {
+        let current = self.current_component.borrow();
+        let Some(map) = current.as_ref().and_then(|component| self.class_maps.get(component)) else {
+            return classes.to_string();
+        };
+        classes
+            .split_whitespace()
+            .map(|class| map.get(class).map_or(class, String::as_str))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Generates a class list as a single string expression: bare literals are
+    /// joined (and scoped) at compile time, every other entry appends " class"
+    /// at runtime, or nothing when the class is empty, so there is no array and
+    /// no stray space
+    fn generate_classes_js(&self, classes: &crate::ast::ClassesMacro) -> String {
+        let mut fixed = Vec::new();
+        let mut pieces = Vec::new();
+        for entry in &classes.entries {
+            let class = match &entry.class {
+                Expression::StringLiteral(class) => {
+                    let class = self.scope_classes(class).split_whitespace().collect::<Vec<_>>().join(" ");
+                    if class.is_empty() {
+                        continue;
+                    }
+                    if entry.condition.is_none() {
+                        fixed.push(class);
+                        continue;
+                    }
+                    serde_json::to_string(&format!(" {}", class)).unwrap_or_default()
+                }
+                // `if primary { "btn-primary" } else { "" }` picks between literals,
+                // which are scoped like any other
+                Expression::IfExpression(if_expr) if Self::class_literal(&if_expr.then_expr).is_some()
+                    && if_expr.else_expr.as_deref().is_none_or(|other| Self::class_literal(other).is_some()) =>
+                {
+                    let branch = |expr: Option<&Expression>| {
+                        let class = self.scope_classes(expr.and_then(Self::class_literal).unwrap_or_default());
+                        let class = class.split_whitespace().collect::<Vec<_>>().join(" ");
+                        if class.is_empty() { "\"\"".to_string() } else { serde_json::to_string(&format!(" {}", class)).unwrap_or_default() }
+                    };
+                    format!(
+                        "({} ? {} : {})",
+                        self.generate_expression_js(&if_expr.condition),
+                        branch(Some(&if_expr.then_expr)),
+                        branch(if_expr.else_expr.as_deref())
+                    )
+                }
+                // A value that turns out empty adds no space
+                Expression::Identifier(_) | Expression::FieldAccess(_) | Expression::FunctionCall(_) if entry.condition.is_none() => {
+                    format!("(\" \" + {}).trimEnd()", self.generate_expression_js(&entry.class))
+                }
+                Expression::Identifier(_) | Expression::FieldAccess(_) | Expression::FunctionCall(_) => {
+                    format!("\" \" + {}", self.generate_expression_js(&entry.class))
+                }
+                class if entry.condition.is_none() => format!("(\" \" + ({})).trimEnd()", self.generate_expression_js(class)),
+                class => format!("\" \" + ({})", self.generate_expression_js(class)),
+            };
+            pieces.push(match &entry.condition {
+                Some(condition) => format!("({} ? {} : \"\")", self.generate_expression_js(condition), class),
+                None => class,
+            });
+        }
+
+        let fixed = serde_json::to_string(&fixed.join(" ")).unwrap_or_default();
+        match (pieces.is_empty(), fixed == "\"\"") {
+            (true, _) => fixed,
+            // Every piece starts with a space
+            (false, true) => format!("({}).slice(1)", pieces.join(" + ")),
+            (false, false) => format!("{} + {}", fixed, pieces.join(" + ")),
+        }
+    }
+
+    /// The string of a class literal, bare or as the only thing in a block
+    fn class_literal(expr: &Expression) -> Option<&str> {
+        match expr {
+            Expression::StringLiteral(class) => Some(class),
+            Expression::Block(block) => match block.statements.as_slice() {
+                [Statement::Expression(Expression::StringLiteral(class))] => Some(class),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Generates an inline style string: static declarations are emitted as
+    /// written, each interpolated value is CSS-escaped at runtime. In a style
+    /// object, a number in a length property is escaped as px.
+    fn generate_inline_style_js(&self, style: &crate::ast::InlineStyle) -> String {
+        use crate::ast::InlineStylePart;
+
+        let mut pieces = Vec::new();
+        let mut text = String::new();
+        for (i, decl) in style.declarations.iter().enumerate() {
+            if i > 0 {
+                text.push_str("; ");
+            }
+            text.push_str(&decl.property);
+            text.push_str(": ");
+            for part in &decl.value {
+                match part {
+                    InlineStylePart::Static(value) => text.push_str(value),
+                    InlineStylePart::Dynamic(expr) => {
+                        if !text.is_empty() {
+                            pieces.push(serde_json::to_string(&std::mem::take(&mut text)).unwrap_or_default());
+                        }
+                        let mode = if style.object && decl.number_is_px() { "css-length" } else { "css" };
+                        pieces.push(format!("__jounce_escape({}, '{}')", self.generate_expression_js(expr), mode));
+                    }
+                }
+            }
         }
+        if !text.is_empty() || pieces.is_empty() {
+            pieces.push(serde_json::to_string(&text).unwrap_or_default());
+        }
+        pieces.join(" + ")
+    }
+
+    /// Checks if a JSX tag names a client component
+    fn is_client_component(&self, name: &str) -> bool {
+        self.splitter.client_components
+            .iter()
+            .any(|c| c.name.value == name)
     }
 
     /// Checks if a function name is a server function
@@ -1849,6 +2907,30 @@ impl JSEmitter {
             .any(|f| f.name.value == name)
     }
 
+    /// Generates an array of promises, leaving server calls un-awaited
+    fn generate_pending_list_js(&self, list: &Expression) -> String {
+        let elements = match list {
+            Expression::ArrayLiteral(array) => &array.elements,
+            Expression::MacroCall(macro_call) if macro_call.name.value == "vec" => &macro_call.arguments,
+            _ => return self.generate_expression_js(list),
+        };
+        let elements = elements
+            .iter()
+            .map(|element| match element {
+                Expression::FunctionCall(call) if matches!(
+                    call.function.as_ref(),
+                    Expression::Identifier(ident) if self.is_server_function(&ident.value)
+                ) => {
+                    let args = call.arguments.iter().map(|arg| self.generate_expression_js(arg)).collect();
+                    format!("{}({})", self.generate_expression_js(&call.function), self.rpc_call_args(args))
+                }
+                _ => self.generate_expression_js(element),
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("[{}]", elements)
+    }
+
     /// Generates JavaScript for an impl block
     fn generate_impl_block_js(&self, impl_block: &ImplBlock) -> String {
         let type_name = &impl_block.type_name.value;
@@ -2006,4 +3088,920 @@ mod tests {
         assert_eq!(stats.shared_functions, 1);
         assert_eq!(stats.client_components, 0);
     }
+
+    const TEST_IDS_SOURCE: &str = r#"
+        component TodoRow(title: String) {
+            return <li class="row">{title}</li>;
+        }
+
+        component App() {
+            return <ul><TodoRow key="first" title="Write docs" /></ul>;
+        }
+    "#;
+
+    fn emitter_for(source: &str) -> JSEmitter {
+        let mut lexer = Lexer::new(source.to_string());
+        let mut parser = Parser::new(&mut lexer);
+        let program = parser.parse_program().expect("Parse failed");
+        JSEmitter::new(&program)
+    }
+
+    #[test]
+    fn test_components_passed_to_h_as_functions() {
+        let client_js = emitter_for(TEST_IDS_SOURCE).generate_client_js();
+        assert!(client_js.contains("h(TodoRow, { key: \"first\""), "got:\n{}", client_js);
+        assert!(client_js.contains("h('ul'"));
+    }
+
+    #[test]
+    fn test_component_props_and_children() {
+        let client_js = emitter_for(TEST_IDS_SOURCE).generate_client_js();
+        assert!(client_js.contains("export function TodoRow({ title } = {})"), "got:\n{}", client_js);
+        assert!(client_js.contains("export function App()"));
+        // Children always follow the props slot
+        assert!(client_js.contains("h('ul', null, h(TodoRow"), "got:\n{}", client_js);
+    }
+
+    #[test]
+    fn test_named_slots_passed_as_props() {
+        let client_js = emitter_for(r#"
+            component Modal(title: String, slot header: Element, slot footer: Option<Element>, slot children: Element) {
+                return <div><header>{header}</header>{children}<footer>{footer}</footer></div>;
+            }
+
+            component App(user: String) {
+                let count = 3;
+                return <Modal title="Hi">
+                    <slot:header><h1>{user}</h1></slot:header>
+                    <p>{count}</p>
+                    <slot:footer><b>{count}</b></slot:footer>
+                </Modal>;
+            }
+        "#).generate_client_js();
+
+        // Named slots arrive with the props, unnamed children as the children slot
+        assert!(client_js.contains("export function Modal({ title, header, footer } = {}, children) {"), "got:\n{}", client_js);
+        // Slot contents are rendered by the caller, so they see its locals
+        assert!(
+            client_js.contains("h(Modal, { title: \"Hi\", header: [h('h1', null, user)], footer: [h('b', null, count)] }, h('p', null, count))"),
+            "got:\n{}",
+            client_js
+        );
+        assert!(!client_js.contains("'slot:"));
+    }
+
+    #[test]
+    fn test_spread_attributes_merge_in_source_order() {
+        let client_js = emitter_for(r#"
+            component Button(label: String) {
+                return <button>{label}</button>;
+            }
+
+            component Toolbar(base: String, extra: String) {
+                return <div>
+                    <Button {...base} label="hi" />
+                    <input {...base} id="name" {...extra} onInput={|| save()} />
+                </div>;
+            }
+        "#).generate_client_js();
+
+        // Later keys win, so explicit props override a spread before them and not one after
+        assert!(client_js.contains("h(Button, { ...base, label: \"hi\" })"), "got:\n{}", client_js);
+        assert!(client_js.contains("h('input', { ...base, id: \"name\", ...extra, onInput: "), "got:\n{}", client_js);
+    }
+
+    #[test]
+    fn test_dotted_component_names_are_property_access() {
+        let client_js = emitter_for(r#"
+            component List(items: Vec<String>) {
+                return <Layout.Column>
+                    {items.map(|item| <ui.Item label={item} />)}
+                    <Icons.Arrow.Left />
+                </Layout.Column>;
+            }
+        "#).generate_client_js();
+
+        assert!(client_js.contains("h(Layout.Column, null, "), "got:\n{}", client_js);
+        assert!(client_js.contains("h(ui.Item, { label: item })"), "got:\n{}", client_js);
+        assert!(client_js.contains("h(Icons.Arrow.Left, null)"), "got:\n{}", client_js);
+        assert!(!client_js.contains("'ui.Item'"));
+    }
+
+    #[test]
+    fn test_component_type_arguments_are_erased() {
+        let client_js = emitter_for(r#"
+            component List(items: Vec<String>) {
+                return <ul />;
+            }
+            component Cell() {
+                return <li />;
+            }
+            component Users(users: Vec<String>) {
+                return <List<String> items={users}><Cell<Vec<i32>> /></List>;
+            }
+        "#).generate_client_js();
+
+        assert!(client_js.contains("h(List, { items: users }, h(Cell, null))"), "got:\n{}", client_js);
+    }
+
+    #[test]
+    fn test_attribute_names_kept_as_written() {
+        let client_js = emitter_for(r##"
+            component Icon() {
+                return <svg xmlns:xlink="http://www.w3.org/1999/xlink" aria-label="Close" hidden>
+                    <image xlink:href="#close" stroke-width="2" data-test-id="icon" />
+                </svg>;
+            }
+        "##).generate_client_js();
+
+        assert!(
+            client_js.contains("h('svg', { \"xmlns:xlink\": \"http://www.w3.org/1999/xlink\", \"aria-label\": \"Close\", hidden: true }"),
+            "got:\n{}",
+            client_js
+        );
+        assert!(
+            client_js.contains("h('image', { \"xlink:href\": \"#close\", \"stroke-width\": \"2\", \"data-test-id\": \"icon\" })"),
+            "got:\n{}",
+            client_js
+        );
+    }
+
+    #[test]
+    fn test_template_literal_emits_js_template() {
+        let client_js = emitter_for(r#"
+            component Greeting(name: String, count: i32) {
+                let label = `Hello, ${name}! \${not} \` ${`${count + 1} new`}`;
+                return <p title={`for ${name}`}>{label}</p>;
+            }
+        "#).generate_client_js();
+
+        // Escaped text stays text in JS; only the interpolations splice
+        assert!(client_js.contains(r"let label = `Hello, ${name}! \${not} \` ${`${(count + 1)} new`}`;"), "got:\n{}", client_js);
+        assert!(client_js.contains("{ title: `for ${name}` }"), "got:\n{}", client_js);
+    }
+
+    #[test]
+    fn test_raw_identifiers_emit_valid_names() {
+        let client_js = emitter_for(r#"
+            struct Binding { r#match: String, r#use: Int }
+            component Row(b: Binding) {
+                let r#in = b.r#use;
+                let r#match = r#in + 1;
+                return <p>{b.r#match} {r#match}</p>;
+            }
+        "#).generate_client_js();
+
+        assert!(client_js.contains("let in_ = b.use;"), "got:\n{}", client_js);
+        assert!(client_js.contains("let match = (in_ + 1);"), "got:\n{}", client_js);
+        assert!(client_js.contains("b.match"), "got:\n{}", client_js);
+        assert!(!client_js.contains("r#"), "got:\n{}", client_js);
+    }
+
+    #[test]
+    fn test_optional_chaining_and_defaults() {
+        let client_js = emitter_for(r#"
+            component Profile(user: User) {
+                let mut title = user?.nickname;
+                title ??= "guest";
+                return <p>{user?.profile.name ?? "anonymous"}</p>;
+            }
+        "#).generate_client_js();
+
+        assert!(client_js.contains("let title = user?.nickname;"), "got:\n{}", client_js);
+        assert!(client_js.contains("title = (title ?? \"guest\");"), "got:\n{}", client_js);
+        assert!(client_js.contains("(user?.profile.name ?? \"anonymous\")"), "got:\n{}", client_js);
+    }
+
+    #[test]
+    fn test_bind_value_expands_per_field() {
+        let client_js = emitter_for(r#"
+            component Form() {
+                let name = signal("");
+                let age = signal(30);
+                let agreed = signal(false);
+                let tags = signal(vec![]);
+                let color = signal("red");
+                return <form>
+                    <input type="text" bind:value={name} />
+                    <input type="number" bind:value={age} />
+                    <input type="checkbox" bind:value={agreed} />
+                    <input type="checkbox" value="news" bind:value={tags} />
+                    <select bind:value={color}><option value="red">Red</option></select>
+                    <select multiple bind:value={tags}><option value="a">A</option></select>
+                </form>;
+            }
+        "#).generate_client_js();
+
+        // Text: the value follows the signal, every keystroke writes back
+        assert!(
+            client_js.contains("h('input', { type: \"text\", value: name, oninput: (event) => { name.value = event.target.value; } })"),
+            "got:\n{}",
+            client_js
+        );
+        // Number: converted both ways, partial input skipped
+        assert!(
+            client_js.contains("value: age, oninput: (event) => { if (!Number.isNaN(event.target.valueAsNumber)) { age.value = event.target.valueAsNumber; } }"),
+            "got:\n{}",
+            client_js
+        );
+        // Checkboxes bind `checked`: alone to a bool, with a value to a list of values
+        assert!(client_js.contains("checked: agreed, onchange: (event) => { agreed.value = event.target.checked; }"), "got:\n{}", client_js);
+        assert!(
+            client_js.contains("value: \"news\", checked: tags, onchange: (event) => { tags.value = event.target.checked ? [...tags.value, event.target.value] : tags.value.filter((item) => item !== event.target.value); }"),
+            "got:\n{}",
+            client_js
+        );
+        // Selects write back on change, a multiple one the selected values
+        assert!(client_js.contains("h('select', { value: color, onchange: (event) => { color.value = event.target.value; } }"), "got:\n{}", client_js);
+        assert!(
+            client_js.contains("onchange: (event) => { tags.value = Array.from(event.target.selectedOptions, (option) => option.value); }"),
+            "got:\n{}",
+            client_js
+        );
+        assert!(!client_js.contains("bind:"));
+    }
+
+    #[test]
+    fn test_test_ids_enabled_in_dev_build() {
+        let test_ids = JSEmitter::test_ids_for_build(true, false, false);
+        let client_js = emitter_for(TEST_IDS_SOURCE).with_test_ids(test_ids).generate_client_js();
+        assert!(client_js.contains("enableTestIds } from './client-runtime.js'"));
+        assert!(client_js.contains("enableTestIds();"));
+    }
+
+    #[test]
+    fn test_test_ids_absent_in_release_build() {
+        let test_ids = JSEmitter::test_ids_for_build(true, true, false);
+        let client_js = emitter_for(TEST_IDS_SOURCE).with_test_ids(test_ids).generate_client_js();
+        assert!(!client_js.contains("enableTestIds"));
+
+        // Off by default, and kept in release only when asked for
+        assert!(!emitter_for(TEST_IDS_SOURCE).generate_client_js().contains("enableTestIds"));
+        assert!(JSEmitter::test_ids_for_build(true, true, true));
+    }
+
+    const DEVTOOLS_SOURCE: &str = r#"
+        component Counter(label: String, step: Option<i32>) {
+            let count = signal(0);
+            return <button>{label}</button>;
+        }
+    "#;
+
+    #[test]
+    fn test_devtools_metadata_in_dev_build() {
+        let client_js = emitter_for(DEVTOOLS_SOURCE).with_devtools(true).generate_client_js();
+        assert!(client_js.contains("import { enableDevtools, trackSignal as __jounce_track } from './devtools.js';"), "got:\n{}", client_js);
+        assert!(client_js.contains("enableDevtools();"));
+        assert!(client_js.contains("let count = __jounce_track(signal(0), \"count\");"), "got:\n{}", client_js);
+        assert!(client_js.contains(
+            r#"Counter.__jounceMeta = {"name":"Counter","props":[{"name":"label","type":"String"},{"name":"step","type":"Option<i32>"}]};"#
+        ), "got:\n{}", client_js);
+    }
+
+    const PROFILE_SOURCE: &str = r#"
+        component Counter(label: String) {
+            let count = signal(0);
+            let doubled = computed(() => count.value * 2);
+            effect(() => {
+                console::log(doubled.value);
+            });
+            return <button>{label}</button>;
+        }
+
+        fn helper() {
+            let ticks = signal(0);
+            effect(() => {
+                console::log(ticks.value);
+            });
+        }
+    "#;
+
+    #[test]
+    fn test_runtime_profiling_wraps_renders_and_effects() {
+        let client_js = emitter_for(PROFILE_SOURCE).with_runtime_profiling(true).generate_client_js();
+        assert!(client_js.contains("function __jounce_measure(component, kind, run) {"), "got:\n{}", client_js);
+        assert!(client_js.contains("window.__JOUNCE_PROFILE__ = __jounce_profile;"));
+        assert!(!client_js.contains("module.exports"), "the bundle is an ES module");
+        assert!(
+            client_js.contains("export function Counter({ label } = {}) {\n  return __jounce_measure(\"Counter\", \"render\", () => {\n"),
+            "got:\n{}",
+            client_js
+        );
+        assert!(client_js.contains("computed(__jounce_profiled(\"Counter\", \"computed\", () => "), "got:\n{}", client_js);
+        assert!(client_js.contains("effect(__jounce_profiled(\"Counter\", \"effect\", () => "), "got:\n{}", client_js);
+        // Effects outside components have no component to be named by
+        assert_eq!(client_js.matches("effect(__jounce_profiled(").count(), 1);
+    }
+
+    #[test]
+    fn test_runtime_profiling_absent_by_default() {
+        let client_js = emitter_for(PROFILE_SOURCE).generate_client_js();
+        assert!(!client_js.contains("__jounce_measure"));
+        assert!(!client_js.contains("__jounce_profiled"));
+        assert!(!client_js.contains("__JOUNCE_PROFILE__"));
+        assert!(!client_js.contains("performance."));
+    }
+
+    #[test]
+    fn test_devtools_absent_in_release_build() {
+        let client_js = emitter_for(DEVTOOLS_SOURCE).with_devtools(false).generate_client_js();
+        assert!(!client_js.contains("enableDevtools"));
+        assert!(!client_js.contains("devtools.js"));
+        assert!(!client_js.contains("__jounce_track"));
+        assert!(!client_js.contains("__jounceMeta"));
+        assert!(client_js.contains("let count = signal(0);"));
+    }
+
+    #[test]
+    fn test_memo_function_wrapped_with_cache() {
+        let emitter = emitter_for(r#"
+            @memo
+            fn fib(n: i32) -> i32 {
+                if n < 2 {
+                    return n;
+                }
+                return fib(n - 1) + fib(n - 2);
+            }
+        "#);
+
+        let client_js = emitter.generate_client_js();
+        assert!(client_js.contains("memoize as __jounce_memoize } from './client-runtime.js'"));
+        assert!(client_js.contains("export const fib = __jounce_memoize(function(n) {"));
+        assert!(client_js.contains("}, 64, ['ref']);"));
+
+        let server_js = emitter.generate_server_js();
+        assert!(server_js.contains("memoize: __jounce_memoize } = require('./server-runtime.js')"));
+        assert!(server_js.contains("const fib = __jounce_memoize(function(n) {"));
+        assert!(server_js.contains("module.exports.fib = fib;"));
+    }
+
+    #[test]
+    fn test_route_handler_parses_typed_params() {
+        let server_js = emitter_for(r#"
+            @route("/todos/:id/:slug")
+            @server
+            fn get_todo(id: i32, slug: String) -> String {
+                return slug;
+            }
+        "#).generate_server_js();
+
+        assert!(server_js.contains("routeParam: __jounce_route_param } = require('./server-runtime.js')"));
+        assert!(server_js.contains(
+            "server.route('/todos/:id/:slug', async (params) => {\n    \
+             const id = __jounce_route_param(params, 'id', 'int');\n    \
+             const slug = __jounce_route_param(params, 'slug', 'string');\n    \
+             return await get_todo(id, slug);\n});"
+        ), "{}", server_js);
+        // Registered before the server starts
+        assert!(server_js.find("server.route(").unwrap() < server_js.find("server.start();").unwrap());
+
+        let plain = emitter_for("fn add(a: i32, b: i32) -> i32 { return a + b; }").generate_server_js();
+        assert!(!plain.contains("__jounce_route_param"));
+    }
+
+    #[test]
+    fn test_memo_key_kinds_follow_struct_derives() {
+        let client_js = emitter_for(r#"
+            #[derive(Eq)]
+            struct Point { x: i32, y: i32 }
+
+            struct Size { w: i32, h: i32 }
+
+            @memo(8)
+            fn fits(p: Point, s: Size, label: String) -> bool {
+                return p.x < s.w;
+            }
+        "#).generate_client_js();
+
+        assert!(client_js.contains("}, 8, ['eq', 'ref', 'ref']);"), "got:\n{}", client_js);
+    }
+
+    #[test]
+    fn test_promise_all_keeps_server_calls_concurrent() {
+        let emitter = emitter_for(r#"
+            @server
+            fn get_a() -> i32 { return 1; }
+
+            @server
+            fn get_b() -> i32 { return 2; }
+
+            @server
+            fn get_c() -> i32 { return 3; }
+
+            async fn load() -> i32 {
+                let results = await Promise::all(vec![get_a(), get_b(), get_c()]);
+                let fastest = await Promise::race(vec![get_a(), sleep(500).then(|_| -1)]);
+                return results[0] + fastest;
+            }
+        "#);
+
+        let client_js = emitter.generate_client_js();
+        assert!(
+            client_js.contains("await Promise.all([get_a(__jounce_signal), get_b(__jounce_signal), get_c(__jounce_signal)])"),
+            "got:\n{}",
+            client_js
+        );
+        assert!(client_js.contains("await Promise.race([get_a(__jounce_signal), sleep(500).then((_) => (-1))])"));
+        assert!(client_js.contains("mountComponent, sleep, rpcSignal as __jounce_rpc_signal } from './client-runtime.js'"), "got:\n{}", client_js);
+
+        let server_js = emitter.generate_server_js();
+        assert!(server_js.contains("loadWasm, sleep } = require('./server-runtime.js')"));
+    }
+
+    #[test]
+    fn test_unsafe_raw_is_the_markup_bypass() {
+        let client_js = emitter_for(r#"
+            component Article(body: String) {
+                return <div class="post" inner_html={unsafe_raw(body)}></div>;
+            }
+        "#).generate_client_js();
+
+        assert!(client_js.contains("mountComponent, sleep, unsafe_raw } from './client-runtime.js'"), "got:\n{}", client_js);
+        assert!(client_js.contains("inner_html: unsafe_raw(body)"), "got:\n{}", client_js);
+    }
+
+    #[test]
+    fn test_unsafe_html_goes_through_unsafe_raw() {
+        let client_js = emitter_for(r#"
+            component Article(body: String) {
+                return <article unsafe_html={body} />;
+            }
+        "#).generate_client_js();
+        assert!(
+            client_js.contains("h('article', { inner_html: /* unsafe_html: set as markup, with no escaping */ unsafe_raw(body) })"),
+            "got:\n{}", client_js
+        );
+        assert!(client_js.contains("sleep, unsafe_raw } from './client-runtime.js'"), "got:\n{}", client_js);
+
+        // A program's own unsafe_raw doesn't take the runtime's place
+        let client_js = emitter_for(r#"
+            fn unsafe_raw(text: String) -> String { return text; }
+            component Article(body: String) {
+                return <article unsafe_html={body} />;
+            }
+        "#).generate_client_js();
+        assert!(client_js.contains("unsafe_raw as __jounce_unsafe_raw"), "got:\n{}", client_js);
+        assert!(client_js.contains("inner_html: /* unsafe_html: set as markup, with no escaping */ __jounce_unsafe_raw(body)"));
+        // Nor is the runtime's imported for a program that only calls its own
+        let client_js = emitter_for(r#"
+            fn unsafe_raw(text: String) -> String { return text; }
+            component Article(body: String) {
+                return <article>{unsafe_raw(body)}</article>;
+            }
+        "#).generate_client_js();
+        assert!(!client_js.contains("__jounce_unsafe_raw"), "got:\n{}", client_js);
+    }
+
+    #[test]
+    fn test_handlers_with_statement_bodies() {
+        let client_js = emitter_for(r#"
+            component Counter() {
+                let count = signal(0);
+                return <div>
+                    <button onclick={() => count.value = count.value + 1}>Add</button>
+                    <button onclick={|| { save(); count.value = 0; }}>Reset</button>
+                </div>;
+            }
+        "#).generate_client_js();
+        assert!(client_js.contains("onclick: () => { count.value = (count.value + 1); }"), "got:\n{}", client_js);
+        assert!(client_js.contains("onclick: () => { save(); count.value = 0; }"), "got:\n{}", client_js);
+    }
+
+    #[test]
+    fn test_try_operator_returns_errors_early() {
+        let client_js = emitter_for(r#"
+            fn halve(n: i32) -> Result<i32, String> {
+                if n % 2 == 0 { Ok(n / 2) } else { Err("odd") }
+            }
+            fn quarter(n: i32) -> Result<i32, String> {
+                let half = halve(n)?;
+                Ok(halve(half)?)
+            }
+        "#).generate_client_js();
+        assert!(client_js.contains("let half = __jounce_try(halve(n));"), "got:\n{}", client_js);
+        // The function catches the early return and hands back the Err
+        assert!(client_js.contains("  try {\n    let half = __jounce_try(halve(n));\n    return Ok(__jounce_try(halve(half)));\n  } catch (e) {"), "got:\n{}", client_js);
+        assert!(client_js.contains("if (e instanceof __JounceEarlyReturn) return e.value;"), "got:\n{}", client_js);
+        let halve = client_js.find("export function halve").unwrap();
+        assert!(!client_js[halve..].starts_with("export function halve(n) {\n  try"), "got:\n{}", client_js);
+    }
+
+    #[test]
+    fn test_try_operator_in_closures() {
+        let client_js = emitter_for(r#"
+            fn halve(n: i32) -> Result<i32, String> {
+                if n % 2 == 0 { Ok(n / 2) } else { Err("odd") }
+            }
+            fn quarters(items: Vec<i32>) -> Vec<Result<i32, String>> {
+                let quarter = |n: i32| {
+                    let half = halve(n)?;
+                    halve(half)
+                };
+                items.map(quarter)
+            }
+        "#).generate_client_js();
+        // The closure catches its own early return; the function has none to catch
+        assert!(client_js.contains(
+            "let quarter = (n) => { try { let half = __jounce_try(halve(n)); return halve(half); } catch (e) { if (e instanceof __JounceEarlyReturn) return e.value; throw e; } };"
+        ), "got:\n{}", client_js);
+        let quarters = client_js.find("export function quarters").unwrap();
+        assert!(!client_js[quarters..].starts_with("export function quarters(items) {\n  try"), "got:\n{}", client_js);
+    }
+
+    #[test]
+    fn test_boolean_and_shorthand_attributes() {
+        let client_js = emitter_for(r#"
+            component Modal(open: bool, disabled: bool) {
+                return <dialog open={open}>hi</dialog>;
+            }
+            component App(open: bool) {
+                return <div>
+                    <Modal {open} disabled />
+                    <button disabled={false}>Save</button>
+                </div>;
+            }
+        "#).generate_client_js();
+        assert!(client_js.contains("h(Modal, { open: open, disabled: true })"), "got:\n{}", client_js);
+        // The runtime leaves the attribute out for false
+        assert!(client_js.contains("h('button', { disabled: false }, \"Save\")"));
+    }
+
+    #[test]
+    fn test_keyed_list_passes_keys_to_the_runtime() {
+        let client_js = emitter_for(r#"
+            component Row(label: String, keys: i32) {
+                return <span>{label}</span>;
+            }
+            component App(items: Vec<String>) {
+                return <ul>{items.iter().map(|item| <li key={item}><Row key={item} label={item} keys={1} /></li>)}</ul>;
+            }
+        "#).generate_client_js();
+        // h() takes `key` out of the props and uses it to match up the items
+        assert!(client_js.contains("h('li', { key: item }, h(Row, { key: item, label: item, keys: 1 }))"), "got:\n{}", client_js);
+    }
+
+    #[test]
+    fn test_refs_in_branches_and_lists() {
+        let client_js = emitter_for(r#"
+            component Search(editing: bool, items: Vec<String>) {
+                let input_el = signal(None);
+                let last = None;
+                return <div>
+                    {if editing { <input ref={input_el} /> } else { <span>{"view"}</span> }}
+                    <ul>{items.iter().map(|item| <li ref={last} key={item}>{item}</li>)}</ul>
+                </div>;
+            }
+        "#).generate_client_js();
+        assert!(client_js.contains("h('input', { ref: __jounce_ref(input_el, (node) => { input_el = node; }) })"), "got:\n{}", client_js);
+        assert!(client_js.contains("h('li', { ref: __jounce_ref(last, (node) => { last = node; }), key: item }, item)"), "got:\n{}", client_js);
+        assert!(client_js.contains("bindRef as __jounce_ref } from './client-runtime.js'"));
+
+        // Without refs there's nothing to import
+        let client_js = emitter_for("component App() { return <p>hi</p>; }").generate_client_js();
+        assert!(!client_js.contains("bindRef"));
+    }
+
+    #[test]
+    fn test_event_modifiers() {
+        let client_js = emitter_for(r#"
+            component Form() {
+                return <form onSubmit|prevent={save}>
+                    <div onClick|self|stop={|e| close(e)} onScroll|passive={track}></div>
+                    <button onClick|once|capture={go}>Go</button>
+                </form>;
+            }
+        "#).generate_client_js();
+        assert!(client_js.contains("h('form', { onSubmit: (event) => { event.preventDefault(); return save(event); } }"), "got:\n{}", client_js);
+        assert!(client_js.contains("onClick: (event) => { if (event.target !== event.currentTarget) { return; } event.stopPropagation(); return ((e) => close(e))(event); }"), "got:\n{}", client_js);
+        assert!(client_js.contains("onScroll: { handleEvent: track, passive: true }"));
+        assert!(client_js.contains("h('button', { onClick: { handleEvent: go, once: true, capture: true } }, \"Go\")"));
+    }
+
+    #[test]
+    fn test_style_object() {
+        let client_js = emitter_for(r#"
+            component Card(fg: String, width: i32, z: i32) {
+                return <div style={{ color: fg, width: width, padding: 8, z_index: z }}>x</div>;
+            }
+        "#).generate_client_js();
+        assert!(
+            client_js.contains("style: \"color: \" + __jounce_escape(fg, 'css-length') + \"; width: \" + __jounce_escape(width, 'css-length') + \"; padding: 8px; z-index: \" + __jounce_escape(z, 'css')"),
+            "got:\n{}",
+            client_js
+        );
+    }
+
+    #[test]
+    fn test_conditional_rendering_without_parentheses() {
+        let client_js = emitter_for(r#"
+            component List(items: Vec<i32>) {
+                return <ul>{items.len()}</ul>;
+            }
+            component A() { return <b>a</b>; }
+            component B() { return <i>b</i>; }
+            component App(items: Vec<i32>, cond: bool) {
+                return <div>
+                    {items.len() > 0 && <List items={items} />}
+                    {cond ? <A /> : <B />}
+                </div>;
+            }
+        "#).generate_client_js();
+        assert!(client_js.contains("> 0) && h(List, { items: items })"), "got:\n{}", client_js);
+        assert!(client_js.contains("cond ? h(A, null) : h(B, null)"), "got:\n{}", client_js);
+    }
+
+    #[test]
+    fn test_jsx_text_is_a_literal_and_expressions_stay_values() {
+        let client_js = emitter_for(r#"
+            component Comment(author: String, body: String) {
+                return <p title={author} class="a&b">Tom &amp; &quot;Jerry&quot; &lt;script&gt; {body}</p>;
+            }
+        "#).generate_client_js();
+        // Text holds what was written, entities decoded; the runtime makes it a text node
+        assert!(
+            client_js.contains(r#"h('p', { title: author, class: "a&b" }, "Tom & \"Jerry\" <script> ", body)"#),
+            "got:\n{}", client_js
+        );
+        // Expressions are passed as values, never spliced into markup
+        assert!(!client_js.contains("innerHTML"));
+        assert!(!client_js.contains("inner_html"));
+    }
+
+    #[test]
+    fn test_inline_style_values_escaped_at_runtime() {
+        let client_js = emitter_for(r#"
+            component Badge(color: String, size: i32) {
+                return <span style="padding: 4px; color: {color}; width: {size}px">new</span>;
+            }
+        "#).generate_client_js();
+
+        assert!(client_js.contains("escape as __jounce_escape } from './client-runtime.js'"));
+        assert!(client_js.contains(
+            r#"{ style: "padding: 4px; color: " + __jounce_escape(color, 'css') + "; width: " + __jounce_escape(size, 'css') + "px" }"#
+        ), "got:\n{}", client_js);
+        let client_js = emitter_for(r#"component A() { return <p style="margin: 0"></p>; }"#).generate_client_js();
+        assert!(client_js.contains(r#"h('p', { style: "margin: 0" })"#));
+        assert!(!client_js.contains("__jounce_escape"), "got:\n{}", client_js);
+    }
+
+    #[test]
+    fn test_user_sleep_shadows_runtime() {
+        let client_js = emitter_for(r#"
+            fn sleep(ms: i32) -> i32 { return ms; }
+        "#).generate_client_js();
+
+        // Nothing else of the runtime is used, so nothing else is imported
+        assert!(client_js.contains("import { h, RPCClient, mountComponent } from './client-runtime.js'"), "got:\n{}", client_js);
+    }
+
+    #[test]
+    fn test_components_share_event_bus_channel() {
+        let client_js = emitter_for(r#"
+            component Toasts() {
+                let bus = EventBus::channel::<String>("toasts");
+                let sub = bus.subscribe(|message| console::log(message));
+                on_cleanup(|| sub.unsubscribe());
+                return <ul></ul>;
+            }
+
+            component SaveButton() {
+                let bus = EventBus::channel::<String>("toasts");
+                return <button onClick={|| bus.emit("Saved")}>Save</button>;
+            }
+        "#).generate_client_js();
+
+        assert!(client_js.contains("sleep, EventBus, onCleanup as on_cleanup } from './client-runtime.js'"), "got:\n{}", client_js);
+        assert_eq!(client_js.matches("let bus = EventBus.channel(\"toasts\");").count(), 2, "got:\n{}", client_js);
+        assert!(client_js.contains("bus.subscribe((message) => console.log(message))"));
+        assert!(client_js.contains("on_cleanup(() => sub.unsubscribe());"));
+        assert!(client_js.contains("bus.emit(\"Saved\")"));
+    }
+
+    #[test]
+    fn test_classes_macro_builds_one_string() {
+        let client_js = emitter_for(r#"
+            component Button(is_primary: bool, enabled: bool, size: String) {
+                return <button class={classes!("btn", "btn-primary" => is_primary, "disabled" => !enabled, size)}>Save</button>;
+            }
+
+            component Badge(active: bool, urgent: bool) {
+                return <span class={classes!("active" => active, "urgent" => urgent)}>1</span>;
+            }
+        "#).generate_client_js();
+
+        assert!(
+            client_js.contains("class: \"btn\" + (is_primary ? \" btn-primary\" : \"\") + ((!enabled) ? \" disabled\" : \"\") + (\" \" + size).trimEnd()"),
+            "got:\n{}",
+            client_js
+        );
+        // Only conditional entries: each adds a leading space, dropped once at the end
+        assert!(client_js.contains("class: ((active ? \" active\" : \"\") + (urgent ? \" urgent\" : \"\")).slice(1)"), "got:\n{}", client_js);
+        assert!(!client_js.contains("filter("), "no intermediate array");
+    }
+
+    #[test]
+    fn test_class_arrays_and_maps() {
+        let client_js = emitter_for(r#"
+            component Button(primary: bool, enabled: bool) {
+                let size = signal("lg");
+                let styles = css! {
+                    .btn { padding: 8px; }
+                    .btn-primary { color: white; }
+                    .disabled { opacity: 0.5; }
+                };
+                return <button class={["btn", if primary { "btn-primary" } else { "" }, size.value, {"disabled": !enabled, focus: primary}]}>Save</button>;
+            }
+            component Plain(primary: bool) {
+                return <b className={[if primary { "a" } else { "b c" }, {}]}>x</b>;
+            }
+        "#).generate_client_js();
+
+        let maps = CodeGenerator::scoped_class_maps(&{
+            let mut lexer = Lexer::new("component Button() { let styles = css! { .btn { padding: 8px; } .btn-primary { color: white; } .disabled { opacity: 0.5; } }; }".to_string());
+            Parser::new(&mut lexer).parse_program().unwrap()
+        });
+        let scoped = |class: &str| maps["Button"][class].clone();
+        // Literals, in the array, in if branches and as map keys, are scoped;
+        // a signal's value adds nothing when it's empty
+        assert!(
+            client_js.contains(&format!(
+                "class: \"{}\" + (primary ? \" {}\" : \"\") + (\" \" + size.value).trimEnd() + ((!enabled) ? \" {}\" : \"\") + (primary ? \" focus\" : \"\")",
+                scoped("btn"),
+                scoped("btn-primary"),
+                scoped("disabled")
+            )),
+            "got:\n{}",
+            client_js
+        );
+        assert!(client_js.contains("h('b', { className: ((primary ? \" a\" : \" b c\")).slice(1) }, \"x\")"), "got:\n{}", client_js);
+    }
+
+    #[test]
+    fn test_classes_macro_scopes_static_literals() {
+        let client_js = emitter_for(r#"
+            component Card(selected: bool) {
+                let styles = css! {
+                    .card { padding: 8px; }
+                    .selected { border: 1px solid blue; }
+                };
+                return <div class={classes!("card shadow", "selected" => selected)}><p class="card">Hi</p></div>;
+            }
+
+            component Plain(selected: bool) {
+                return <div class={classes!("card", "selected" => selected)}></div>;
+            }
+        "#).generate_client_js();
+
+        let maps = CodeGenerator::scoped_class_maps(&{
+            let mut lexer = Lexer::new("component Card() { let styles = css! { .card { padding: 8px; } .selected { color: blue; } }; }".to_string());
+            Parser::new(&mut lexer).parse_program().unwrap()
+        });
+        let card = &maps["Card"]["card"];
+        let selected = &maps["Card"]["selected"];
+        assert!(card.starts_with("Card_card_"), "got {}", card);
+
+        assert!(
+            client_js.contains(&format!("class: \"{} shadow\" + (selected ? \" {}\" : \"\")", card, selected)),
+            "got:\n{}",
+            client_js
+        );
+        assert!(client_js.contains(&format!("h('p', {{ class: \"{}\" }}, \"Hi\")", card)), "static class attribute scoped too");
+        // Components without css! keep their classes as written
+        assert!(client_js.contains("class: \"card\" + (selected ? \" selected\" : \"\")"), "got:\n{}", client_js);
+    }
+
+    #[test]
+    fn test_js_values_cross_the_extern_boundary() {
+        let emitter = emitter_for(r#"
+            extern "js" {
+                fn current_user() -> JsValue;
+                fn fetch_profile(id: i32) -> Promise<JsValue>;
+                fn log_value(label: String, value: JsValue);
+            }
+
+            fn user_age() -> f64 {
+                let user = current_user();
+                log_value("user", user.get("name"));
+                return user.get("age").as_f64().unwrap_or(0.0);
+            }
+
+            async fn load_profile() {
+                let profile = await fetch_profile(7);
+            }
+
+            component App() {
+                return <div>{user_age()}</div>;
+            }
+        "#);
+        let client_js = emitter.generate_client_js();
+
+        assert!(client_js.contains("sleep, JsValue } from './client-runtime.js'"), "got:\n{}", client_js);
+        assert!(client_js.contains("JsValue.useOption(Some, None);"));
+        assert!(client_js.contains("let user = JsValue.wrap(current_user());"), "got:\n{}", client_js);
+        assert!(client_js.contains("log_value(\"user\", JsValue.unwrap(user.get(\"name\")));"), "got:\n{}", client_js);
+        assert!(client_js.contains("user.get(\"age\").as_f64().unwrap_or(0.0)"), "got:\n{}", client_js);
+        assert!(client_js.contains("await fetch_profile(7).then(JsValue.wrap)"), "got:\n{}", client_js);
+        assert!(emitter.generate_server_js().contains("sleep, JsValue } = require('./server-runtime.js');"));
+
+        let client_js = emitter_for("component App() { return <div>hi</div>; }").generate_client_js();
+        assert!(!client_js.contains("JsValue"));
+        // A JsValue:: call with no JsValue extern still needs the runtime's JsValue
+        let emitter = emitter_for("fn convert(v: JsonValue) {\n    let j = JsValue::from_json(v);\n}\n");
+        let client_js = emitter.generate_client_js();
+        assert!(client_js.contains("JsValue.from_json(v)"), "got:\n{}", client_js);
+        assert!(client_js.contains("sleep, JsValue } from './client-runtime.js'"), "got:\n{}", client_js);
+        assert!(emitter.generate_server_js().contains("sleep, JsValue } = require('./server-runtime.js');"));
+    }
+
+    #[test]
+    fn test_provide_renders_children_inside_provider() {
+        let client_js = emitter_for(r#"
+            const mode_ctx = create_context("light");
+
+            component Label() {
+                let mode = use_context(mode_ctx);
+                return <span>{mode}</span>;
+            }
+
+            component App() {
+                return <Provide context={mode_ctx} value={"dark"}><Label /><p>Hi</p></Provide>;
+            }
+        "#).generate_client_js();
+
+        assert!(client_js.contains("sleep, createContext as create_context, useContext as use_context, Provide } from './client-runtime.js'"), "got:\n{}", client_js);
+        assert!(client_js.contains("let mode = use_context(mode_ctx);"), "got:\n{}", client_js);
+        assert!(client_js.contains("const mode_ctx = create_context(\"light\");"), "got:\n{}", client_js);
+        assert!(
+            client_js.contains("h(Provide, { context: mode_ctx, value: \"dark\" }, () => [h(Label, null), h('p', null, \"Hi\")])"),
+            "got:\n{}",
+            client_js
+        );
+    }
+
+    #[test]
+    fn test_translations_pass_placeholder_values() {
+        let emitter = emitter_for(r#"
+            component Cart(user: User, items: Vec<i32>) {
+                I18n::set_locale("fr", catalog());
+                let count = items.len();
+                return <p>{t!("Hi {name}, add {count} items", user.name, count)} {t!("Checkout")}</p>;
+            }
+        "#);
+        let client_js = emitter.generate_client_js();
+        assert!(client_js.contains("sleep, I18n } from './client-runtime.js'"), "got:\n{}", client_js);
+        assert!(client_js.contains("I18n.set_locale(\"fr\", catalog());"), "got:\n{}", client_js);
+        assert!(
+            client_js.contains("I18n.t(\"Hi {name}, add {count} items\", { name: user.name, count: count })"),
+            "got:\n{}",
+            client_js
+        );
+        assert!(client_js.contains("I18n.t(\"Checkout\")"), "got:\n{}", client_js);
+        assert!(emitter.generate_server_js().contains("sleep, I18n } = require('./server-runtime.js');"));
+
+        let client_js = emitter_for("component App() { return <div>hi</div>; }").generate_client_js();
+        assert!(!client_js.contains("I18n"));
+    }
+
+    #[test]
+    fn test_server_calls_carry_the_component_signal() {
+        let emitter = emitter_for(r#"
+            @server
+            fn load_count() -> i32 { return 0; }
+
+            @server
+            fn save(item: String) -> bool { return true; }
+
+            async fn refresh(count: Signal<i32>) {
+                count.set(load_count());
+            }
+
+            fn label() -> String { return "items"; }
+
+            component List() {
+                let count = signal(0);
+                return <button onClick={|| refresh(count)}>Refresh</button>;
+            }
+        "#);
+        let client_js = emitter.generate_client_js();
+
+        // Stubs take the signal of the component rendering when they're called
+        assert!(
+            client_js.contains("export async function save(item: string, __signal = __jounce_rpc_signal()) {\n    return await client.call('save', [item], __signal);"),
+            "got:\n{}",
+            client_js
+        );
+        assert!(client_js.contains("rpcSignal as __jounce_rpc_signal } from './client-runtime.js'"));
+
+        // Async functions take their caller's signal once and pass it along
+        assert!(
+            client_js.contains("export async function refresh(count) {\n  const __jounce_signal = __jounce_rpc_signal();\n  return count.set(await load_count(__jounce_signal));"),
+            "got:\n{}",
+            client_js
+        );
+        assert!(!client_js.contains("function label() {\n  const __jounce_signal"));
+        assert!(!client_js.contains("function List() {\n  const __jounce_signal"));
+
+        let client_js = emitter_for("component App() { return <div>hi</div>; }").generate_client_js();
+        assert!(!client_js.contains("__jounce_rpc_signal"));
+    }
 }
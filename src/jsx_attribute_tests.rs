@@ -0,0 +1,75 @@
+// Regression tests for JSX attribute expressions
+//
+// An attribute's `{` pushes `LexMode::JsxExpr` and its matching `}` pops back
+// through it, so whatever the expression opened in between (blocks, match
+// bodies, nested elements, comparisons taken for tags) is dropped with it.
+// Each test puts a gnarly expression on an element and checks that the text
+// after it, and the element after that, still lex as JSX.
+
+use crate::ast::{Expression, JsxChild, JsxElement, Statement};
+use crate::lexer::Lexer;
+use crate::parser::Parser;
+
+/// Parses `<div><p ATTRIBUTES>after</p><span>tail {x}</span></div>` and
+/// returns the `<p>`, after checking its text, its sibling, and that the
+/// lexer ended up back where it started
+fn element_with(attributes: &str) -> JsxElement {
+    let source = format!("let page = <div><p {}>after</p><span>tail {{x}}</span></div>;\nlet next = 1;", attributes);
+    let mut lexer = Lexer::new(source);
+    let program = Parser::new(&mut lexer).parse_program().unwrap_or_else(|err| panic!("{}: {:?}", attributes, err));
+    assert_eq!(lexer.debug_modes(), "Normal", "{}", attributes);
+    assert_eq!(program.statements.len(), 2, "{}", attributes);
+
+    let Statement::Let(page) = &program.statements[0] else { panic!("expected a let") };
+    let Expression::JsxElement(div) = &page.value else { panic!("expected JSX") };
+    let [JsxChild::Element(p), JsxChild::Element(span)] = div.children.as_slice() else {
+        panic!("{}: expected <p> and <span>, got {:?}", attributes, div.children);
+    };
+    assert!(matches!(p.children.as_slice(), [JsxChild::Text(text)] if text == "after"), "{}: {:?}", attributes, p.children);
+    assert_eq!(span.opening_tag.name.value, "span");
+    assert!(matches!(span.children.as_slice(), [JsxChild::Text(text), JsxChild::Expression(_)] if text == "tail "));
+    (**p).clone()
+}
+
+#[test]
+fn test_closure_with_early_return() {
+    let p = element_with("onClick={|e| { if busy { return; } submit(e) }}");
+    assert!(matches!(p.opening_tag.attributes[0].value, Expression::Lambda(_)));
+}
+
+#[test]
+fn test_match_with_braces_in_strings() {
+    element_with(r#"title={match mode { "open" => "{", "close" => "}", _ => "{}" }}"#);
+    element_with("title={|| match x { Some(v) => { v }, None => { 0 } }}");
+}
+
+#[test]
+fn test_nested_elements_with_their_own_attributes() {
+    element_with(r#"render={|item| <li key={item} class={if done { "x" } else { "y" }}>{item} {"}"}</li>}"#);
+    element_with("fallback={if ready { <b>{ \"}\" }</b> } else { <i>loading</i> }}");
+    element_with("items={list.iter().map(|i| <li>{i}</li>).collect::<Vec<i32>>()}");
+}
+
+#[test]
+fn test_multiline_closure_with_comments() {
+    element_with("onInput={|e| {\n        // a } in a comment\n        /* and a { */\n        let v = e.target.value;\n        if v == \"}\" { busy.value = true; }\n    }}");
+    element_with("\n    onClick={|e| {\n        match e {\n            1 => { <b>one</b> },\n            _ => { <i>{\"{\"}</i> },\n        }\n    }}\n");
+}
+
+#[test]
+fn test_comparisons_and_generics() {
+    // A `<` that could open a tag, and a `>` that could close one
+    element_with("hidden={a < b && { let c = 1; c > 2 }}");
+    element_with("hidden={if a < b { 1 } else { 2 }}");
+    element_with("hidden={a < b && <i>x</i>}");
+    element_with("hidden={|e| e.x > 1} shift={c >> 2}");
+    element_with("items={x as Vec<i32>}");
+}
+
+#[test]
+fn test_literals_holding_braces() {
+    element_with("title={`a ${ f({ 1 }) } }`} label={|e| { let s = `{${e}}`; s }}");
+    element_with("open={'{'} close={'}'}");
+    element_with(r#"data={ Point { x: 1, y: Inner { z: "}" } } }"#);
+    element_with("{/* } */} a={1}");
+}
@@ -1,196 +1,476 @@
-use crate::token::{Token, TokenKind, KEYWORDS};
+use std::collections::VecDeque;
+use std::fmt;
+use std::rc::Rc;
+use crate::ast::CssSelector;
+use crate::html_entities;
+use crate::token::{NumberSuffix, Span, Token, TokenKind, KEYWORDS};
+
+/// What a `LexError` is about
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LexErrorKind {
+    /// A string or template missing its closing quote, or a raw string
+    /// whose closing `#`s don't match its opening ones
+    UnterminatedString,
+    UnterminatedBlockComment,
+    InvalidEscape,
+    InvalidNumber,
+    /// A char or byte literal that doesn't hold exactly one character, or a
+    /// non-ASCII character in a byte string
+    InvalidChar,
+    UnexpectedCharacter(char),
+    /// A zero-width character, in a name or on its own
+    InvisibleCharacter(char),
+    /// `r#` with no name after it
+    EmptyRawIdentifier,
+}
+
+/// A mistake in the source text, found while tokenizing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LexError {
+    pub kind: LexErrorKind,
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+    /// The source line the error is on
+    pub snippet: String,
+}
+
+impl LexError {
+    /// The snippet with a caret under the offending column
+    pub fn caret(&self) -> String {
+        format!("{}\n{}^", self.snippet, " ".repeat(self.column.saturating_sub(1)))
+    }
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+/// Where the lexer is inside a template string
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TemplateState {
+    /// Reading text; the template opened on `line`
+    Text { line: usize },
+    /// Lexing a `${...}` expression, `braces` deep in its own `{`s
+    Interpolation { braces: usize },
+}
+
+/// Whether `ch` can start a name: `_` or a Unicode XID_Start character.
+/// Names aren't normalized, so look-alikes are different names: a
+/// precomposed `é` and `e` with a combining accent, or Latin `a` and
+/// Cyrillic `а`.
+fn is_identifier_start(ch: char) -> bool {
+    ch == '_' || unicode_ident::is_xid_start(ch)
+}
+
+/// Whether `ch` can continue a name: a Unicode XID_Continue character,
+/// which includes digits, `_` and `·`
+fn is_identifier_continue(ch: char) -> bool {
+    unicode_ident::is_xid_continue(ch)
+}
+
+/// Whether `ch` can be part of a CSS property or keyword, like
+/// `min-width` or a custom property such as `--brand_color`
+fn is_css_name_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '-' || ch == '_'
+}
+
+/// What a zero-width character is called, for the error about it: one in
+/// a name would make it differ from a name that looks the same
+fn invisible_character_name(ch: char) -> Option<&'static str> {
+    match ch {
+        '\u{200B}' => Some("zero width space"),
+        '\u{200C}' => Some("zero width non-joiner"),
+        '\u{200D}' => Some("zero width joiner"),
+        '\u{2060}' => Some("word joiner"),
+        '\u{FEFF}' => Some("zero width no-break space"),
+        _ => None,
+    }
+}
+
+/// JSX text as it renders: each line is trimmed where it meets a line break,
+/// lines left empty are dropped and the rest are joined with a space. So the
+/// indentation between tags goes, while `Hello <b>world</b>` keeps its space.
+fn collapse_jsx_whitespace(text: &str) -> String {
+    let is_blank = |ch: char| ch == ' ' || ch == '\t';
+    let lines: Vec<&str> = text.split('\n').map(|line| line.strip_suffix('\r').unwrap_or(line)).collect();
+    let last = lines.len() - 1;
+    let kept: Vec<&str> = lines
+        .iter()
+        .enumerate()
+        .map(|(index, &line)| {
+            let line = if index > 0 { line.trim_start_matches(is_blank) } else { line };
+            if index < last { line.trim_end_matches(is_blank) } else { line }
+        })
+        .filter(|line| !line.is_empty())
+        .collect();
+    kept.join(" ")
+}
+
+/// What the lexer is inside of. `Lexer` keeps a stack of these, innermost
+/// last, with `Normal` always at the bottom; tokens that open and close
+/// things push and pop them, and so does the parser through `enter_jsx_mode`
+/// and friends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LexMode {
+    /// Ordinary code
+    Normal,
+    /// After a `<` that may open a tag, up to its `>` or `/>`. The parser
+    /// decides whether it did, so a comparison pushes one too.
+    JsxTag,
+    /// A JSX element's children, where text is read as `JsxText`
+    JsxChildren,
+    /// A `{...}` expression among JSX children or attributes
+    JsxExpr,
+    /// A `{` inside a JSX expression: a block, object literal or match body
+    Block,
+    /// Braces around nothing but comments among JSX children or attributes,
+    /// `{/* TODO */}`. Neither brace makes a token.
+    JsxComment,
+    /// The parser is reading a closing tag, `</name>`
+    JsxClosingTag,
+    /// A component's type arguments, `<List<User>>`, one per `<`. Their
+    /// `>` closes this rather than the tag, and is never part of a `>>`.
+    JsxTypeArgs,
+    /// A `css!` body, `depth` braces and `parens` parentheses deep
+    Css { depth: usize, parens: usize },
+    /// An `@media` or `@container` condition, up to its `{`
+    CssMediaCondition,
+}
+
+/// Where `Lexer::new_in_mode` starts reading, for snippets cut out of a
+/// larger file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LexerStartMode {
+    /// Ordinary code, as `Lexer::new` reads it
+    Normal,
+    /// The children of a JSX element: text, `{...}` expressions and tags
+    JsxChildren,
+    /// The inside of a `css! { ... }` body: rules, selectors and @-rules
+    Css,
+}
 
 #[derive(Clone)]
 pub struct Lexer {
-    input: Vec<char>,
-    position: usize,
-    read_position: usize,
+    input: Rc<str>,           // Shared, so snapshots for lookahead are cheap
+    position: usize,          // Byte offset of `ch`
+    read_position: usize,     // Byte offset of the char after it
     ch: char,
     line: usize,
     column: usize,
-    jsx_mode: bool,           // Track if we're in JSX context
-    jsx_depth: usize,         // Track nesting depth of JSX elements
-    brace_depth: usize,       // Track braces in JSX expressions
-    jsx_in_tag: bool,         // Track if we're inside a tag (between < and >)
-    in_closing_tag: bool,     // Track if parser is currently parsing a closing tag
-    jsx_baseline_brace_depths: Vec<usize>, // Stack of brace depths when entering each JSX element
-    just_closed_jsx_expr: bool, // Track if we just emitted a JsxCloseBrace (allows delimiters as JSX text)
-    css_mode: bool,           // Track if we're in CSS context
-    css_depth: usize,         // Track brace nesting depth in CSS
-    css_paren_depth: usize,   // Track parenthesis depth in CSS (for media queries)
-    in_media_query: bool,     // Track if we're parsing @media condition (until we hit {)
+    modes: Vec<LexMode>,      // What we're inside of, innermost last
+    raw_text: Vec<usize>,     // Where in `modes` the children of <pre> and <textarea> elements are
+    raw_elements: Vec<(usize, String)>, // Where the children of <style> and <script> elements are, and the tag
+    templates: Vec<TemplateState>, // Template strings being read, innermost last
+    after_operand: bool,      // Last token ended an operand, so `.` after it is member access
+    opened_jsx_expr: bool,    // Last token opened a JSX expression, which may be after the element it's in
+    css_value: bool,          // Reading a CSS declaration's value: past its `:`, before its `;` or `}`
+    preserve_comments: bool,  // Emit comments as tokens instead of skipping them
+    errors: Vec<LexError>,    // Mistakes found so far; the tokens for them are error tokens
+    token_start: usize,       // Where the token being read starts, past whitespace and comments
+    token_line: usize,        // Line and column of that first char
+    token_column: usize,
+    lookahead: VecDeque<(Lexer, Token)>, // Peeked tokens, each with the lexer as it was before reading it
 }
 
 impl Lexer {
     pub fn new(input: String) -> Self {
         let mut lexer = Self {
-            input: input.chars().collect(),
+            input: input.into(),
             position: 0,
             read_position: 0,
             ch: '\0',
             line: 1,
             column: 0,
-            jsx_mode: false,
-            jsx_depth: 0,
-            brace_depth: 0,
-            jsx_in_tag: false,
-            in_closing_tag: false,
-            jsx_baseline_brace_depths: Vec::new(),
-            just_closed_jsx_expr: false,
-            css_mode: false,
-            css_depth: 0,
-            css_paren_depth: 0,
-            in_media_query: false,
+            modes: vec![LexMode::Normal],
+            raw_text: Vec::new(),
+            raw_elements: Vec::new(),
+            templates: Vec::new(),
+            after_operand: false,
+            opened_jsx_expr: false,
+            css_value: false,
+            preserve_comments: false,
+            errors: Vec::new(),
+            token_start: 0,
+            token_line: 1,
+            token_column: 1,
+            lookahead: VecDeque::new(),
         };
         lexer.read_char();
         lexer
     }
 
+    /// A lexer that reads `input` as if it were already inside `mode`, e.g.
+    /// the text and `{...}` expressions of a JSX element without the element
+    pub fn new_in_mode(input: String, mode: LexerStartMode) -> Self {
+        let mut lexer = Self::new(input);
+        match mode {
+            LexerStartMode::Normal => {}
+            LexerStartMode::JsxChildren => lexer.push_mode(LexMode::JsxChildren),
+            LexerStartMode::Css => lexer.push_mode(LexMode::Css { depth: 1, parens: 0 }),
+        }
+        lexer
+    }
+
+    /// Emit `//` and `/* */` comments as `LineComment` / `BlockComment`
+    /// tokens, for tools like formatters. The parser expects them skipped.
+    pub fn with_comments(mut self, enabled: bool) -> Self {
+        self.preserve_comments = enabled;
+        self
+    }
+
+    /// Everything reported so far. `next_token` is lenient: it hands back
+    /// `Illegal` and `Invalid*` tokens and carries on, so the parser can
+    /// recover; the errors behind them are collected here.
+    pub fn errors(&self) -> &[LexError] {
+        &self.errors
+    }
+
+    /// Like `next_token`, but fails on the first error the token brings up,
+    /// including an unterminated block comment skipped on the way to it
+    pub fn try_next_token(&mut self) -> Result<Token, LexError> {
+        let reported = self.visible().errors.len();
+        let token = self.next_token();
+        match self.errors.get(reported) {
+            Some(error) => Err(error.clone()),
+            None => Ok(token),
+        }
+    }
+
+    fn mark_token_start(&mut self) {
+        self.token_start = self.position;
+        self.token_line = self.line;
+        self.token_column = self.column;
+    }
+
+    /// Records an error at `line`/`column` and hands back its message for the error token
+    fn report(&mut self, kind: LexErrorKind, message: String, line: usize, column: usize) -> String {
+        let snippet = self.input
+            .split('\n')
+            .nth(line.saturating_sub(1))
+            .unwrap_or_default()
+            .to_string();
+        self.errors.push(LexError { kind, message: message.clone(), line, column, snippet });
+        message
+    }
+
     pub fn next_token(&mut self) -> Token {
-        // In JSX mode, handle text content differently
-        // Only read JSX text when we're not inside a tag (between < and >) AND we're actually inside a JSX element (jsx_depth > 0)
-        // Also don't read JSX text if we're currently parsing a closing tag
-        // Check if brace_depth is at or below the baseline for the current JSX element
-        let baseline_brace_depth = self.jsx_baseline_brace_depths.last().copied().unwrap_or(0);
-
-        // Read JSX text when at baseline brace depth (not inside expressions)
-        // OR when we've just finished parsing an opening tag (even if inside nested braces)
-        let at_baseline = self.brace_depth == baseline_brace_depth;
-
-        // CRITICAL: Don't read JSX text if current character is a delimiter/operator
-        // This prevents reading `)`, `}`, `]`, `,`, `;` as JSX text after self-closing tags in closures
-        // EXCEPTION: After closing a JSX expression with `}`, allow delimiters as JSX text
-        // Example: `Comments ({expr})` - the `)` after `}` should be read as text
-        let is_delimiter = !self.just_closed_jsx_expr && matches!(self.ch, ')' | ']' | ',' | ';');
-
-        // CRITICAL: Check if we would only read whitespace before a delimiter
-        // This prevents empty JSX text tokens after self-closing tags in expression contexts
+        match self.lookahead.pop_front() {
+            Some((_, token)) => token,
+            None => self.lex_token(),
+        }
+    }
+
+    /// The token `next_token` will return, without consuming it
+    pub fn peek_token(&mut self) -> &Token {
+        self.peek_nth(0)
+    }
+
+    /// The token `n` places after the next one (`peek_nth(0)` is the next
+    /// one), without consuming anything. Peeked tokens are read in the
+    /// current mode; switching modes with `enter_jsx_mode` and friends
+    /// throws them away, so they're read again in the new mode.
+    pub fn peek_nth(&mut self, n: usize) -> &Token {
+        while self.lookahead.len() <= n {
+            let peeked = std::mem::take(&mut self.lookahead);
+            let before = self.clone();
+            self.lookahead = peeked;
+            let token = self.lex_token();
+            self.lookahead.push_back((before, token));
+        }
+        &self.lookahead[n].1
+    }
+
+    /// Rewinds to before the peeked tokens, ahead of a mode switch
+    fn discard_lookahead(&mut self) {
+        if let Some((before, _)) = self.lookahead.pop_front() {
+            *self = before;
+        }
+    }
+
+    /// The lexer state callers see: from before any peeked tokens
+    fn visible(&self) -> &Lexer {
+        self.lookahead.front().map_or(self, |(before, _)| before)
+    }
+
+    fn lex_token(&mut self) -> Token {
+        self.mark_token_start();
+        let mut token = self.read_token();
+        // Whatever path read it, a token is placed at its first char
+        token.line = self.token_line;
+        token.column = self.token_column;
+        let end = self.position.min(self.input.len());
+        token.span = Span::new(self.token_start.min(end), end);
+        if matches!(token.kind, TokenKind::LineComment(_) | TokenKind::BlockComment(_) | TokenKind::DocComment { .. }) {
+            return token;
+        }
+        self.opened_jsx_expr = token.kind == TokenKind::JsxOpenBrace;
+        self.after_operand = matches!(
+            token.kind,
+            TokenKind::Identifier | TokenKind::Integer(_) | TokenKind::Float(_) | TokenKind::TypedNumber(..)
+                | TokenKind::String(_) | TokenKind::Char(_) | TokenKind::ByteString(_) | TokenKind::Byte(_)
+                | TokenKind::Bool(_) | TokenKind::True | TokenKind::False | TokenKind::TemplateEnd
+                | TokenKind::RParen | TokenKind::RBracket | TokenKind::RBrace
+        );
+        token
+    }
+
+    fn read_token(&mut self) -> Token {
+        // Template text is read as written, whatever mode the template sits in
+        if let Some(TemplateState::Text { line }) = self.templates.last().copied() {
+            return self.read_template_text(line);
+        }
+
+        // Text is read among an element's children: not inside a tag, a
+        // `{...}` expression or a closing tag, which would be on top of them.
+        // Delimiters there are text too, as in `Done (3/4)` or `</b>, and`;
+        // after an element that ends an expression rather than being a child,
+        // the parser lexes the next token outside the element instead.
+        // Whitespace before a tag is text; read_jsx_text drops it if it spans
+        // lines, and whitespace before the end of an expression isn't text.
         let would_read_only_whitespace = self.ch.is_whitespace() && {
-            let mut temp_pos = self.position;
-            let mut temp_ch = self.ch;
-            // Skip whitespace to see what's next
-            while temp_ch.is_whitespace() && temp_ch != '\0' {
-                temp_pos += 1;
-                temp_ch = if temp_pos < self.input.len() {
-                    self.input[temp_pos]
-                } else {
-                    '\0'
-                };
-            }
-            // Check if next non-whitespace is a delimiter or JSX-significant character
-            matches!(temp_ch, '}' | ')' | ']' | '<' | '\0')
+            let next = self.rest().chars().find(|ch| !ch.is_whitespace()).unwrap_or('\0');
+            matches!(next, '}' | '\0')
         };
 
-        let can_read_jsx_text = self.jsx_mode && self.jsx_depth > 0 && at_baseline && !self.jsx_in_tag && !self.in_closing_tag && !is_delimiter && !would_read_only_whitespace && self.ch != '<' && self.ch != '{' && self.ch != '}' && self.ch != '\0';
+        // The children of a <style> or <script> are one text token, up to its closing tag
+        if let Some(tag) = self.raw_element_tag() {
+            if self.ch != '\0' && !self.at_closing_tag(&tag) {
+                return self.read_raw_element_text(&tag);
+            }
+        }
+
+        let can_read_jsx_text = self.mode() == LexMode::JsxChildren && !would_read_only_whitespace && self.ch != '<' && self.ch != '{' && self.ch != '}' && self.ch != '\0';
 
         if can_read_jsx_text {
-            // Reset the flag since we're reading JSX text now
-            self.just_closed_jsx_expr = false;
             return self.read_jsx_text();
         }
 
-        // Reset the flag for non-JSX-text tokens (will be set again for JsxCloseBrace below)
-        self.just_closed_jsx_expr = false;
-
         // CSS mode handling
-        if self.css_mode {
+        if self.in_css() {
             self.skip_whitespace();
+            self.mark_token_start();
+            if let Some(comment) = self.read_comment() {
+                return comment;
+            }
             let start_col = self.column;
 
             // Handle CSS-specific tokens
             return match self.ch {
                 '{' => {
-                    self.css_depth += 1;
-                    self.in_media_query = false; // Exit media query mode when { is found
+                    // The `{` ends an @media condition
+                    if self.mode() == LexMode::CssMediaCondition {
+                        self.modes.pop();
+                    }
+                    if let Some(LexMode::Css { depth, .. }) = self.modes.last_mut() {
+                        *depth += 1;
+                    }
+                    self.css_value = false;
                     self.read_char();
                     Token::new(TokenKind::LBrace, "{".to_string(), self.line, start_col)
                 }
                 '}' => {
-                    if self.css_depth > 0 {
-                        self.css_depth -= 1;
-                    }
-                    if self.css_depth == 0 {
-                        self.css_mode = false;
+                    if let Some(LexMode::Css { depth, .. }) = self.modes.last_mut() {
+                        *depth = depth.saturating_sub(1);
+                        if *depth == 0 {
+                            self.pop_mode_through(|mode| matches!(mode, LexMode::Css { .. }));
+                        }
                     }
+                    self.css_value = false;
                     self.read_char();
                     Token::new(TokenKind::RBrace, "}".to_string(), self.line, start_col)
                 }
+                ',' => {
+                    // Between keyframe selectors, `0%, 100%`, or media queries
+                    self.read_char();
+                    Token::new(TokenKind::Comma, ",".to_string(), self.line, start_col)
+                }
                 ';' => {
+                    self.css_value = false;
                     self.read_char();
                     Token::new(TokenKind::Semicolon, ";".to_string(), self.line, start_col)
                 }
+                ':' if !self.in_css_condition() && self.css_rule_ahead() => {
+                    // Starts a selector, `:root {` or `:global(.dark) & {`
+                    self.read_css_selector()
+                }
                 ':' => {
+                    // Starts a declaration's value, unless it's in a condition
+                    // like `(min-width: 4px)`
                     self.read_char();
+                    self.css_value = !self.in_css_condition() && !self.css_rule_ahead();
                     Token::new(TokenKind::Colon, ":".to_string(), self.line, start_col)
                 }
                 '(' => {
-                    self.css_paren_depth += 1;
+                    if let Some(parens) = self.css_parens() {
+                        *parens += 1;
+                    }
                     self.read_char();
                     Token::new(TokenKind::LParen, "(".to_string(), self.line, start_col)
                 }
                 ')' => {
-                    if self.css_paren_depth > 0 {
-                        self.css_paren_depth -= 1;
+                    if let Some(parens) = self.css_parens() {
+                        *parens = parens.saturating_sub(1);
                     }
                     self.read_char();
                     Token::new(TokenKind::RParen, ")".to_string(), self.line, start_col)
                 }
-                '.' | '#' | '&' => {
-                    // CSS selector (including & for nesting)
+                '"' | '\'' if self.css_value && (self.ch == '\'' || !self.css_lone_string_ahead()) => {
+                    // A value with strings in it, `"Inter", sans-serif`, is
+                    // read whole; a value that's just a "string" is a string
+                    self.read_css_value()
+                }
+                _ if self.css_value && !matches!(self.ch, '"' | '\0') => {
+                    // The rest of a declaration's value is one token, whatever
+                    // it starts with: `0 auto`, `12px/1.5 sans-serif`, `.5`,
+                    // `#ff00aa`, `1px solid rgba(0,0,0,0.2)`
+                    self.read_css_value()
+                }
+                '.' | '#' | '&' | '[' => {
+                    // CSS selector (including & for nesting and [attributes])
                     self.read_css_selector()
                 }
-                '@' => {
-                    // Check if this is @media or @keyframes
-                    let pos = self.position;
-                    self.read_char(); // consume '@'
-                    let ident_token = self.read_identifier();
-
-                    match ident_token.lexeme.as_str() {
-                        "media" => {
-                            self.in_media_query = true; // Enter media query mode
-                            return Token::new(TokenKind::CssMedia, "@media".to_string(), self.line, start_col);
-                        }
-                        "container" => {
-                            self.in_media_query = true; // Use same flag (similar parsing logic)
-                            return Token::new(TokenKind::CssContainer, "@container".to_string(), self.line, start_col);
-                        }
-                        "keyframes" => {
-                            return Token::new(TokenKind::CssKeyframes, "@keyframes".to_string(), self.line, start_col);
-                        }
-                        _ => {
-                            // Not a recognized @-rule, reset
-                            self.position = pos;
-                            self.ch = '@';
-                            self.read_char();
-                            Token::new(TokenKind::At, "@".to_string(), self.line, start_col)
+                '@' => match self.read_css_at_rule() {
+                    Some(token) => {
+                        match token.kind {
+                            // @container and @supports conditions, and @page selectors, read like media ones
+                            TokenKind::CssMedia | TokenKind::CssContainer | TokenKind::CssSupports | TokenKind::CssPage => {
+                                self.push_mode(LexMode::CssMediaCondition);
+                            }
+                            // What's imported reads like a declaration's value
+                            TokenKind::CssImport => self.css_value = true,
+                            _ => {}
                         }
+                        token
                     }
-                }
+                    None => {
+                        self.read_char();
+                        Token::new(TokenKind::At, "@".to_string(), self.line, start_col)
+                    }
+                },
                 '\0' => Token::new(TokenKind::Eof, "".to_string(), self.line, start_col),
                 _ => {
-                    if self.ch.is_alphabetic() || self.ch == '-' {
+                    if is_css_name_char(self.ch) && !self.ch.is_ascii_digit() {
                         // When in media query mode or inside parentheses, read as CSS property (handles hyphens like min-width, and keywords like 'and')
-                        if self.css_paren_depth > 0 || self.in_media_query {
+                        if self.in_css_condition() {
                             return self.read_css_property();
                         }
 
                         // Could be a property name or selector
                         // Peek ahead to determine which
-                        let mut peek_pos = self.position;
-                        while peek_pos < self.input.len() && (self.input[peek_pos].is_alphanumeric() || self.input[peek_pos] == '-') {
-                            peek_pos += 1;
-                        }
-                        // Skip whitespace
-                        while peek_pos < self.input.len() && self.input[peek_pos].is_whitespace() {
-                            peek_pos += 1;
-                        }
+                        let after_name = self.rest()
+                            .trim_start_matches(is_css_name_char)
+                            // Skip whitespace
+                            .trim_start();
 
-                        if peek_pos < self.input.len() && self.input[peek_pos] == ':' {
+                        if self.css_rule_ahead() {
+                            // It's a selector, like `h1, h2 {` or `ul > li {`
+                            self.read_css_selector()
+                        } else if after_name.starts_with(':') {
                             // It's a property name (followed by colon)
                             self.read_css_property()
-                        } else if peek_pos < self.input.len() && self.input[peek_pos] == '{' {
-                            // It's a selector (followed by brace)
-                            self.read_css_selector()
                         } else {
                             // Assume it's a CSS value
                             self.read_css_value()
@@ -199,11 +479,7 @@ impl Lexer {
                         // String value
                         self.read_string()
                     } else if self.ch.is_ascii_digit() {
-                        // When in media query mode or inside parentheses, read as number
-                        if self.css_paren_depth > 0 || self.in_media_query {
-                            return self.read_number();
-                        }
-                        // Numeric value - read as CSS value
+                        // Numeric value in a condition, `(min-width: 768px)`, with its unit
                         let num_token = self.read_number();
                         let mut value = num_token.lexeme.clone();
 
@@ -224,6 +500,7 @@ impl Lexer {
                     } else {
                         // Unknown character
                         let ch = self.ch;
+                        self.report(LexErrorKind::UnexpectedCharacter(ch), format!("unexpected character '{}'", ch), self.line, start_col);
                         self.read_char();
                         Token::new(TokenKind::Illegal(ch), ch.to_string(), self.line, start_col)
                     }
@@ -232,6 +509,13 @@ impl Lexer {
         }
 
         self.skip_whitespace();
+        self.mark_token_start();
+        if self.at_doc_comment() {
+            return self.read_doc_comment();
+        }
+        if let Some(comment) = self.read_comment() {
+            return comment;
+        }
         let start_col = self.column;
         let token = match self.ch {
            ':' => {
@@ -262,6 +546,10 @@ impl Lexer {
                     self.read_char();
                     self.read_char();
                     return Token::new(TokenKind::PipePipe, "||".to_string(), self.line, start_col);
+                } else if self.peek() == '=' {
+                    self.read_char();
+                    self.read_char();
+                    return Token::new(TokenKind::PipeAssign, "|=".to_string(), self.line, start_col);
                 } else {
                     Token::new(TokenKind::Pipe, "|".to_string(), self.line, start_col)
                 }
@@ -284,24 +572,81 @@ impl Lexer {
                     }
                     // Just ..
                     return Token::new(TokenKind::DotDot, "..".to_string(), self.line, start_col);
+                } else if self.peek().is_ascii_digit() && !self.after_operand {
+                    // `.5` is a float, but `x.5` and `f().0` are member access
+                    return self.read_number();
                 } else {
                     Token::new(TokenKind::Dot, ".".to_string(), self.line, start_col)
                 }
             }
-            '+' => Token::new(TokenKind::Plus, "+".to_string(), self.line, start_col),
-            '*' => Token::new(TokenKind::Star, "*".to_string(), self.line, start_col),
-            '%' => Token::new(TokenKind::Percent, "%".to_string(), self.line, start_col),
+            '+' => {
+                if self.peek() == '=' {
+                    self.read_char();
+                    self.read_char();
+                    return Token::new(TokenKind::PlusAssign, "+=".to_string(), self.line, start_col);
+                } else {
+                    Token::new(TokenKind::Plus, "+".to_string(), self.line, start_col)
+                }
+            }
+            '*' => {
+                if self.peek() == '=' {
+                    self.read_char();
+                    self.read_char();
+                    return Token::new(TokenKind::StarAssign, "*=".to_string(), self.line, start_col);
+                } else {
+                    Token::new(TokenKind::Star, "*".to_string(), self.line, start_col)
+                }
+            }
+            '%' => {
+                if self.peek() == '=' {
+                    self.read_char();
+                    self.read_char();
+                    return Token::new(TokenKind::PercentAssign, "%=".to_string(), self.line, start_col);
+                } else {
+                    Token::new(TokenKind::Percent, "%".to_string(), self.line, start_col)
+                }
+            }
             '&' => {
                 if self.peek() == '&' {
                     self.read_char();
                     self.read_char();
                     return Token::new(TokenKind::AmpAmp, "&&".to_string(), self.line, start_col);
+                } else if self.peek() == '=' {
+                    self.read_char();
+                    self.read_char();
+                    return Token::new(TokenKind::AmpAssign, "&=".to_string(), self.line, start_col);
                 } else {
                     Token::new(TokenKind::Ampersand, "&".to_string(), self.line, start_col)
                 }
             }
-            '?' => Token::new(TokenKind::Question, "?".to_string(), self.line, start_col),
-            '^' => Token::new(TokenKind::Caret, "^".to_string(), self.line, start_col),
+            '?' => {
+                let after_dot = self.char_ahead(2);
+                if self.peek() == '?' {
+                    self.read_char();
+                    self.read_char();
+                    if self.ch == '=' {
+                        self.read_char();
+                        return Token::new(TokenKind::QuestionQuestionAssign, "??=".to_string(), self.line, start_col);
+                    }
+                    return Token::new(TokenKind::QuestionQuestion, "??".to_string(), self.line, start_col);
+                } else if self.peek() == '.' && !after_dot.is_ascii_digit() && after_dot != '.' {
+                    // Not in `cond ?.5 : 1`, where the dot starts a float
+                    self.read_char();
+                    self.read_char();
+                    return Token::new(TokenKind::QuestionDot, "?.".to_string(), self.line, start_col);
+                } else {
+                    Token::new(TokenKind::Question, "?".to_string(), self.line, start_col)
+                }
+            }
+            '^' => {
+                if self.peek() == '=' {
+                    self.read_char();
+                    self.read_char();
+                    return Token::new(TokenKind::CaretAssign, "^=".to_string(), self.line, start_col);
+                } else {
+                    Token::new(TokenKind::Caret, "^".to_string(), self.line, start_col)
+                }
+            }
             '!' => {
                 if self.peek() == '=' {
                     self.read_char();
@@ -314,15 +659,23 @@ impl Lexer {
             '(' => Token::new(TokenKind::LParen, "(".to_string(), self.line, start_col),
             ')' => Token::new(TokenKind::RParen, ")".to_string(), self.line, start_col),
             '{' => {
-                // Track brace depth for JSX expressions
-                if self.jsx_mode {
-                    let baseline = self.jsx_baseline_brace_depths.last().copied().unwrap_or(0);
-                    self.brace_depth += 1;
-                    // Only use JsxOpenBrace for the first level (opening a JSX expression)
-                    // Nested braces should be regular LBrace tokens (for blocks, match, etc.)
-                    if self.brace_depth == baseline + 1 {
+                if self.in_jsx() && self.mode_outside_tag() == LexMode::JsxChildren && self.braces_hold_only_comments() {
+                    self.push_mode(LexMode::JsxComment);
+                    self.read_char();
+                    self.mark_token_start();
+                    return self.read_token();
+                }
+                if let Some(TemplateState::Interpolation { braces }) = self.templates.last_mut() {
+                    *braces += 1;
+                }
+                // At an element's own level a `{` opens a JSX expression; inside
+                // one it's a regular LBrace (for blocks, match, etc.)
+                if self.in_jsx() {
+                    if self.mode_outside_tag() == LexMode::JsxChildren {
+                        self.push_mode(LexMode::JsxExpr);
                         Token::new(TokenKind::JsxOpenBrace, "{".to_string(), self.line, start_col)
                     } else {
+                        self.push_mode(LexMode::Block);
                         Token::new(TokenKind::LBrace, "{".to_string(), self.line, start_col)
                     }
                 } else {
@@ -330,23 +683,34 @@ impl Lexer {
                 }
             }
             '}' => {
-                // Track brace depth for JSX expressions
-                if self.jsx_mode && self.brace_depth > 0 {
-                    let baseline = self.jsx_baseline_brace_depths.last().copied().unwrap_or(0);
-                    // Only use JsxCloseBrace for the first level (closing a JSX expression)
-                    // Nested braces should be regular RBrace tokens
-                    let is_jsx_close = self.brace_depth == baseline + 1;
-                    let token = if is_jsx_close {
-                        // Set flag to allow delimiters as JSX text after closing a JSX expression
-                        self.just_closed_jsx_expr = true;
+                if self.mode() == LexMode::JsxComment {
+                    self.modes.pop();
+                    self.read_char();
+                    self.mark_token_start();
+                    return self.read_token();
+                }
+                // The `}` closing `${` goes back to the template's text; JSX braces never saw its `{`
+                match self.templates.last_mut() {
+                    Some(TemplateState::Interpolation { braces: 0 }) => {
+                        self.templates.pop();
+                        self.read_char();
+                        return Token::new(TokenKind::TemplateInterpolationEnd, "}".to_string(), self.line, start_col);
+                    }
+                    Some(TemplateState::Interpolation { braces }) => *braces -= 1,
+                    _ => {}
+                }
+                // Closing a JSX expression pops back to the element it's in, and
+                // into its tag if it was an attribute
+                match self.mode_outside_tag() {
+                    LexMode::JsxExpr => {
+                        self.pop_mode_through(|mode| mode == LexMode::JsxExpr);
                         Token::new(TokenKind::JsxCloseBrace, "}".to_string(), self.line, start_col)
-                    } else {
+                    }
+                    LexMode::Block => {
+                        self.pop_mode_through(|mode| mode == LexMode::Block);
                         Token::new(TokenKind::RBrace, "}".to_string(), self.line, start_col)
-                    };
-                    self.brace_depth -= 1;
-                    token
-                } else {
-                    Token::new(TokenKind::RBrace, "}".to_string(), self.line, start_col)
+                    }
+                    _ => Token::new(TokenKind::RBrace, "}".to_string(), self.line, start_col),
                 }
             }
             '[' => Token::new(TokenKind::LBracket, "[".to_string(), self.line, start_col),
@@ -359,45 +723,82 @@ impl Lexer {
                 } else if self.peek() == '<' {
                     self.read_char();
                     self.read_char();
+                    if self.ch == '=' {
+                        self.read_char();
+                        return Token::new(TokenKind::LeftShiftAssign, "<<=".to_string(), self.line, start_col);
+                    }
                     return Token::new(TokenKind::LeftShift, "<<".to_string(), self.line, start_col);
                 } else {
-                    // Check if this might be JSX: < followed by an alphabetic character or uppercase
-                    // This handles <div>, <Component>, etc.
-                    // Always set jsx_in_tag when we see <, as the parser will enable JSX mode if needed
-                    self.jsx_in_tag = true;
+                    // This might open a tag (<div>, <Component>, etc.); the parser
+                    // decides, and enters JSX mode if it does
+                    if self.mode() == LexMode::JsxTypeArgs {
+                        self.push_mode(LexMode::JsxTypeArgs);
+                    } else if self.mode() != LexMode::JsxTag {
+                        self.push_mode(LexMode::JsxTag);
+                    }
                     Token::new(TokenKind::LAngle, "<".to_string(), self.line, start_col)
                 }
             }
             '>' => {
-                if self.peek() == '=' {
+                if self.mode() == LexMode::JsxTypeArgs {
+                    self.read_char();
+                    self.modes.pop();
+                    return Token::new(TokenKind::RAngle, ">".to_string(), self.line, start_col);
+                }
+                // Ends the tag on top, or the one being read as a closing tag;
+                // a `>` comparison inside an attribute expression has the
+                // expression on top instead. A tag the parser has entered ends
+                // here whatever text follows (`<p>=</p>`), while the tag a `<`
+                // comparison may have opened can still end in `>=` or `>>`.
+                let len = self.modes.len();
+                let tail = &self.modes[len.saturating_sub(2)..];
+                let closing_tag = tail == [LexMode::JsxTag, LexMode::JsxClosingTag];
+                let ends_tag = closing_tag || tail == [LexMode::JsxChildren, LexMode::JsxTag];
+                if self.peek() == '=' && !ends_tag {
                     self.read_char();
                     self.read_char();
                     return Token::new(TokenKind::GtEq, ">=".to_string(), self.line, start_col);
-                } else if self.peek() == '>' {
+                } else if self.peek() == '>' && !ends_tag {
+                    // `>>` may close two generic lists (`Vec<Vec<i32>>`); the
+                    // parser splits it there, see `Parser::expect_closing_angle`
                     self.read_char();
                     self.read_char();
+                    if self.ch == '=' {
+                        self.read_char();
+                        return Token::new(TokenKind::RightShiftAssign, ">>=".to_string(), self.line, start_col);
+                    }
                     return Token::new(TokenKind::RightShift, ">>".to_string(), self.line, start_col);
                 } else {
-                    // Only mark that we're exiting a tag if we're at the baseline brace depth
-                    // This prevents `>` comparison operators inside attribute expressions from incorrectly
-                    // setting jsx_in_tag = false
-                    let baseline = self.jsx_baseline_brace_depths.last().copied().unwrap_or(0);
-                    if self.brace_depth == baseline {
-                        self.jsx_in_tag = false;
+                    if closing_tag {
+                        self.modes.remove(len - 2);
+                    } else if self.mode() == LexMode::JsxTag {
+                        self.modes.pop();
                     }
                     Token::new(TokenKind::RAngle, ">".to_string(), self.line, start_col)
                 }
             }
             '/' => {
                 // Check for self-closing JSX tag />
-                if self.peek() == '>' && self.jsx_mode {
+                if self.peek() == '>' && self.in_jsx() {
                     self.read_char();
                     self.read_char();
-                    // Don't automatically decrement jsx_depth here - let the parser manage it
-                    // via exit_jsx_mode() based on whether this element entered JSX mode
-                    // Mark that we're exiting a tag
-                    self.jsx_in_tag = false;
+                    // Ends the tag; the parser leaves the element itself with
+                    // exit_jsx_mode(), as it knows whether the element entered it
+                    if self.mode() == LexMode::JsxTag {
+                        self.modes.pop();
+                    }
+                    // A self-closed <pre> has no text; what follows is its parent's
+                    if self.raw_text.last() == Some(&(self.modes.len() - 1)) {
+                        self.raw_text.pop();
+                    }
+                    if self.raw_elements.last().map(|(index, _)| *index) == Some(self.modes.len() - 1) {
+                        self.raw_elements.pop();
+                    }
                     return Token::new(TokenKind::JsxSelfClose, "/>".to_string(), self.line, start_col);
+                } else if self.peek() == '=' {
+                    self.read_char();
+                    self.read_char();
+                    return Token::new(TokenKind::SlashAssign, "/=".to_string(), self.line, start_col);
                 } else {
                     Token::new(TokenKind::Slash, "/".to_string(), self.line, start_col)
                 }
@@ -407,6 +808,10 @@ impl Lexer {
                     self.read_char();
                     self.read_char();
                     return Token::new(TokenKind::Arrow, "->".to_string(), self.line, start_col);
+                } else if self.peek() == '=' {
+                    self.read_char();
+                    self.read_char();
+                    return Token::new(TokenKind::MinusAssign, "-=".to_string(), self.line, start_col);
                 } else {
                     Token::new(TokenKind::Minus, "-".to_string(), self.line, start_col)
                 }
@@ -414,47 +819,47 @@ impl Lexer {
             '@' => {
                 // Check if in CSS mode and if this is @media or @keyframes
                 if self.is_css_mode() {
-                    let pos = self.position;
-                    self.read_char(); // consume '@'
-                    let ident_token = self.read_identifier();
-
-                    match ident_token.lexeme.as_str() {
-                        "media" => {
-                            return Token::new(TokenKind::CssMedia, "@media".to_string(), self.line, start_col);
-                        }
-                        "container" => {
-                            return Token::new(TokenKind::CssContainer, "@container".to_string(), self.line, start_col);
-                        }
-                        "keyframes" => {
-                            return Token::new(TokenKind::CssKeyframes, "@keyframes".to_string(), self.line, start_col);
-                        }
-                        _ => {
-                            // Not a recognized @-rule, reset
-                            self.position = pos;
-                            self.ch = '@';
-                        }
+                    if let Some(token) = self.read_css_at_rule() {
+                        return token;
                     }
                 }
                 Token::new(TokenKind::At, "@".to_string(), self.line, start_col)
             }
+            '#' => Token::new(TokenKind::Pound, "#".to_string(), self.line, start_col),
             '\0' => Token::new(TokenKind::Eof, "".to_string(), self.line, start_col),
             '"' => return self.read_string(),
+            '`' => {
+                self.templates.push(TemplateState::Text { line: self.line });
+                Token::new(TokenKind::TemplateStart, "`".to_string(), self.line, start_col)
+            }
             '\'' => {
-                // Check if this is a lifetime (e.g., 'a, 'static)
-                if self.peek().is_alphabetic() || self.peek() == '_' {
+                // A lifetime ('a, 'static) has no closing quote; a char literal ('a', '\n') does
+                let closed = self.char_ahead(2) == '\'';
+                if is_identifier_start(self.peek()) && !closed {
                     return self.read_lifetime();
-                } else {
-                    // For now, treat single quote without identifier as illegal
-                    Token::new(TokenKind::Illegal(self.ch), self.ch.to_string(), self.line, start_col)
                 }
+                return self.read_char_literal();
             }
             _ => {
-                if self.ch.is_alphabetic() || self.ch == '_' {
+                if self.ch == 'r' && self.starts_raw_string() {
+                    return self.read_raw_string();
+                } else if self.ch == 'b' && self.peek() == '"' {
+                    return self.read_byte_string();
+                } else if self.ch == 'b' && self.peek() == '\'' {
+                    return self.read_byte_literal();
+                } else if is_identifier_start(self.ch) {
                     return self.read_identifier();
                 } else if self.ch.is_ascii_digit() {
                     return self.read_number();
+                } else if let Some(name) = invisible_character_name(self.ch) {
+                    let ch = self.ch;
+                    let message = format!("invisible character U+{:04X} ({}); delete it", ch as u32, name);
+                    self.report(LexErrorKind::InvisibleCharacter(ch), message, self.line, start_col);
+                    Token::new(TokenKind::Illegal(ch), ch.to_string(), self.line, start_col)
                 } else {
-                    Token::new(TokenKind::Illegal(self.ch), self.ch.to_string(), self.line, start_col)
+                    let ch = self.ch;
+                    self.report(LexErrorKind::UnexpectedCharacter(ch), format!("unexpected character '{}'", ch), self.line, start_col);
+                    Token::new(TokenKind::Illegal(ch), ch.to_string(), self.line, start_col)
                 }
             }
         };
@@ -463,62 +868,172 @@ impl Lexer {
     }
 
     fn read_char(&mut self) {
-        if self.read_position >= self.input.len() {
-            self.ch = '\0';
-        } else {
-            self.ch = self.input[self.read_position];
+        // At the end there's nothing to move past; `position` stays a valid
+        // index into `input` for the slices taken with it
+        if self.position >= self.input.len() && self.read_position > self.input.len() {
+            return;
         }
-        self.position = self.read_position;
-        self.read_position += 1;
+        // A newline belongs to the line it ends; the line after it starts with the next char
         if self.ch == '\n' {
             self.line += 1;
             self.column = 1;
         } else {
             self.column += 1;
         }
+        self.ch = self.peek();
+        self.position = self.read_position;
+        // Past the end, `read_position` is one beyond it, marking that `ch` is the end
+        self.read_position += self.ch.len_utf8();
+    }
+
+    /// The source from `ch` on
+    fn rest(&self) -> &str {
+        self.input.get(self.position..).unwrap_or_default()
+    }
+
+    /// The char `n` places after `ch` (`char_ahead(1)` is `peek()`)
+    fn char_ahead(&self, n: usize) -> char {
+        self.rest().chars().nth(n).unwrap_or('\0')
     }
 
     fn peek(&self) -> char {
-        if self.read_position >= self.input.len() {
-            '\0'
-        } else {
-            self.input[self.read_position]
-        }
+        self.input.get(self.read_position..).and_then(|rest| rest.chars().next()).unwrap_or('\0')
     }
 
     fn skip_whitespace(&mut self) {
         loop {
             if self.ch.is_whitespace() {
                 self.read_char();
-            } else if self.ch == '/' && self.peek() == '/' {
-                // Skip line comment //
-                while self.ch != '\n' && self.ch != '\0' {
-                    self.read_char();
-                }
-            } else if self.ch == '/' && self.peek() == '*' {
-                // Skip block comment /* */
-                self.read_char(); // consume /
-                self.read_char(); // consume *
-                while !(self.ch == '*' && self.peek() == '/') && self.ch != '\0' {
-                    self.read_char();
-                }
-                if self.ch == '*' {
-                    self.read_char(); // consume *
-                    self.read_char(); // consume /
-                }
+            } else if self.at_doc_comment() || (self.preserve_comments && self.ch == '/' && matches!(self.peek(), '/' | '*')) {
+                // Left for read_doc_comment / read_comment
+                break;
+            } else if self.ch == '/' && matches!(self.peek(), '/' | '*') {
+                self.skip_comment();
             } else {
                 break;
             }
         }
     }
 
-    fn read_identifier(&mut self) -> Token {
+    /// Skips the `//` or `/* */` comment starting here
+    fn skip_comment(&mut self) {
+        if self.peek() == '/' {
+            // Skip line comment //
+            while self.ch != '\n' && self.ch != '\0' {
+                self.read_char();
+            }
+            return;
+        }
+        // Skip block comment /* */
+        let (line, column) = (self.line, self.column);
+        self.read_char(); // consume /
+        self.read_char(); // consume *
+        while !(self.ch == '*' && self.peek() == '/') && self.ch != '\0' {
+            self.read_char();
+        }
+        if self.ch == '*' {
+            self.read_char(); // consume *
+            self.read_char(); // consume /
+        } else {
+            self.report_unterminated_comment(line, column);
+        }
+    }
+
+    fn report_unterminated_comment(&mut self, line: usize, column: usize) {
+        let message = format!("unterminated block comment starting on line {}; close it with */", line);
+        self.report(LexErrorKind::UnterminatedBlockComment, message, line, column);
+    }
+
+    /// `///` or `//!`, but not `////`. In CSS they're ordinary comments.
+    fn at_doc_comment(&self) -> bool {
+        let at = |offset: usize| self.char_ahead(offset);
+        !self.in_css() && self.ch == '/' && at(1) == '/' && (at(2) == '!' || (at(2) == '/' && at(3) != '/'))
+    }
+
+    fn read_doc_comment(&mut self) -> Token {
         let start_pos = self.position;
+        let (line, column) = (self.line, self.column);
+        self.read_char(); // consume /
+        self.read_char(); // consume /
+        let inner = self.ch == '!';
+        self.read_char(); // consume / or !
+
+        let text_start = self.position;
+        while self.ch != '\n' && self.ch != '\0' {
+            self.read_char();
+        }
+        let text: String = self.input[text_start..self.position].to_string();
+        let lexeme: String = self.input[start_pos..self.position].to_string();
+        Token::new(TokenKind::DocComment { inner, text }, lexeme, line, column)
+    }
+
+    /// With comments preserved, reads the comment starting here. The token
+    /// holds the text between the delimiters and sits where the comment starts.
+    fn read_comment(&mut self) -> Option<Token> {
+        if !self.preserve_comments || self.ch != '/' || !matches!(self.peek(), '/' | '*') {
+            return None;
+        }
+        let start_pos = self.position;
+        let (line, column) = (self.line, self.column);
+        let block = self.peek() == '*';
+        self.read_char();
+        self.read_char();
+
+        let text_start = self.position;
+        let kind = if block {
+            while !(self.ch == '*' && self.peek() == '/') && self.ch != '\0' {
+                self.read_char();
+            }
+            let text: String = self.input[text_start..self.position].to_string();
+            if self.ch == '*' {
+                self.read_char(); // consume *
+                self.read_char(); // consume /
+            } else {
+                self.report_unterminated_comment(line, column);
+            }
+            TokenKind::BlockComment(text)
+        } else {
+            while self.ch != '\n' && self.ch != '\0' {
+                self.read_char();
+            }
+            TokenKind::LineComment(self.input[text_start..self.position].to_string())
+        };
+        let lexeme: String = self.input[start_pos..self.position].to_string();
+        Some(Token::new(kind, lexeme, line, column))
+    }
+
+    fn read_identifier(&mut self) -> Token {
         let start_col = self.column;
-        while self.ch.is_alphanumeric() || self.ch == '_' {
+        // `r#match` is a raw identifier: a name even when it's a keyword
+        let raw = self.ch == 'r' && self.peek() == '#';
+        if raw {
+            self.read_char(); // consume r
+            self.read_char(); // consume #
+            if !is_identifier_start(self.ch) {
+                let message = "`r#` needs a name right after it, like `r#match`".to_string();
+                let message = self.report(LexErrorKind::EmptyRawIdentifier, message, self.line, start_col);
+                return Token::new(TokenKind::InvalidIdentifier(message), "r#".to_string(), self.line, start_col);
+            }
+        }
+        let start_pos = self.position;
+        // Zero-width characters are read as part of the name, to report it whole
+        while is_identifier_continue(self.ch) || invisible_character_name(self.ch).is_some() {
             self.read_char();
         }
-        let literal: String = self.input[start_pos..self.position].iter().collect();
+        let literal: String = self.input[start_pos..self.position].to_string();
+
+        if let Some((ch, name)) = literal.chars().find_map(|ch| Some((ch, invisible_character_name(ch)?))) {
+            let message = format!(
+                "name `{}` contains an invisible character U+{:04X} ({}); delete it",
+                literal.escape_debug(), ch as u32, name
+            );
+            let message = self.report(LexErrorKind::InvisibleCharacter(ch), message, self.line, start_col);
+            return Token::new(TokenKind::InvalidIdentifier(message), literal, self.line, start_col);
+        }
+        // The lexeme is the bare name, which is what gets emitted
+        if raw {
+            return Token::new(TokenKind::Identifier, literal, self.line, start_col);
+        }
 
         // Check for css! macro
         if literal == "css" && self.ch == '!' {
@@ -526,6 +1041,12 @@ impl Lexer {
             return Token::new(TokenKind::CssMacro, "css!".to_string(), self.line, start_col);
         }
 
+        // `_100` is a misplaced digit separator, not a name
+        if literal.starts_with('_') && literal.chars().any(|c| c.is_ascii_digit()) && literal.chars().all(|c| c.is_ascii_digit() || c == '_') {
+            let message = format!("numeric literal `{}` can't start with an underscore; underscores go between digits", literal);
+            return self.number_token(TokenKind::InvalidNumber(message), literal, start_col);
+        }
+
         // Check for boolean literals
         let kind = match literal.as_str() {
             "true" => TokenKind::Bool(true),
@@ -540,140 +1061,610 @@ impl Lexer {
         let start_pos = self.position;
         let start_col = self.column;
         let mut is_float = false;
-        let mut is_hex = false;
-
-        // Check for different number bases
-        let mut is_octal = false;
-        let mut is_binary = false;
 
+        // Hexadecimal 0x..., octal 0o... and binary 0b..., prefixes in either case
         if self.ch == '0' {
             match self.peek() {
-                // Hexadecimal: 0x...
-                'x' | 'X' => {
-                    is_hex = true;
-                    self.read_char(); // consume '0'
-                    self.read_char(); // consume 'x' or 'X'
-
-                    // Read hex digits (0-9, a-f, A-F)
-                    while self.ch.is_ascii_hexdigit() {
-                        self.read_char();
-                    }
-                }
-                // Octal: 0o...
-                'o' | 'O' => {
-                    is_octal = true;
-                    self.read_char(); // consume '0'
-                    self.read_char(); // consume 'o' or 'O'
-
-                    // Read octal digits (0-7)
-                    while self.ch >= '0' && self.ch <= '7' {
-                        self.read_char();
-                    }
-                }
-                // Binary: 0b...
-                'b' | 'B' => {
-                    is_binary = true;
-                    self.read_char(); // consume '0'
-                    self.read_char(); // consume 'b' or 'B'
-
-                    // Read binary digits (0-1)
-                    while self.ch == '0' || self.ch == '1' {
-                        self.read_char();
-                    }
-                }
-                _ => {
-                    // Regular decimal starting with 0
-                    while self.ch.is_ascii_digit() {
-                        self.read_char();
-                    }
+                'x' | 'X' => return self.read_radix_number(16, "hexadecimal"),
+                'o' | 'O' => return self.read_radix_number(8, "octal"),
+                'b' | 'B' => return self.read_radix_number(2, "binary"),
+                _ => {}
+            }
+        }
 
-                    // Check for decimal point
-                    if self.ch == '.' && self.peek().is_ascii_digit() {
-                        is_float = true;
-                        self.read_char(); // consume '.'
-                        while self.ch.is_ascii_digit() {
-                            self.read_char();
-                        }
-                    }
-                }
+        // Read decimal number; `_` separates digits (1_000_000) and is dropped from the value
+        self.read_digits(|c| c.is_ascii_digit());
+        let integer_part: String = self.input[start_pos..self.position].to_string();
+
+        // Check for decimal point. `1.` is a float too, unless the dot starts
+        // a method call (`1.max(2)`), a field or a range (`1..5`)
+        if self.ch == '.' && self.peek().is_ascii_digit() {
+            is_float = true;
+            self.read_char(); // consume '.'
+            self.read_digits(|c| c.is_ascii_digit());
+        } else if self.ch == '.' && !integer_part.is_empty() && !(self.peek().is_alphabetic() || matches!(self.peek(), '_' | '.')) {
+            is_float = true;
+            self.read_char(); // consume '.'
+        }
+
+        let number: String = self.input[start_pos..self.position].to_string();
+        // Written `.5` or `1.`, the value still has digits on both sides
+        let mut digits = number.replace('_', "");
+        if digits.starts_with('.') {
+            digits.insert(0, '0');
+        }
+        if digits.ends_with('.') {
+            digits.push('0');
+        }
+        let suffix = self.read_number_suffix();
+        let literal: String = self.input[start_pos..self.position].to_string();
+
+        let kind = if let Some(message) = Self::misplaced_underscore(&number, &integer_part) {
+            TokenKind::InvalidNumber(message)
+        } else if let Some(suffix) = suffix {
+            if is_float && !suffix.is_float() {
+                TokenKind::InvalidNumber(format!(
+                    "float literal `{}` can't have the integer suffix {}; use f32 or f64",
+                    literal, suffix.name()
+                ))
+            } else {
+                Self::typed_number(&literal, digits, suffix)
             }
+        } else if is_float {
+            TokenKind::Float(digits)
         } else {
-            // Read decimal number
-            while self.ch.is_ascii_digit() {
-                self.read_char();
-            }
+            TokenKind::Integer(digits.parse().unwrap_or(0))
+        };
+        self.number_token(kind, literal, start_col)
+    }
 
-            // Check for decimal point
-            if self.ch == '.' && self.peek().is_ascii_digit() {
-                is_float = true;
-                self.read_char(); // consume '.'
-                while self.ch.is_ascii_digit() {
-                    self.read_char();
-                }
+    /// The token for a number just read, reporting it if it's invalid
+    fn number_token(&mut self, kind: TokenKind, literal: String, start_col: usize) -> Token {
+        if let TokenKind::InvalidNumber(message) = &kind {
+            self.report(LexErrorKind::InvalidNumber, message.clone(), self.line, start_col);
+        }
+        Token::new(kind, literal, self.line, start_col)
+    }
+
+    /// Consumes a type suffix glued to a number (`42u8`, `3.0f32`). Only a
+    /// whole suffix counts: in `1foo` or `1f32x` the letters stay a name.
+    fn read_number_suffix(&mut self) -> Option<NumberSuffix> {
+        let name: String = self.rest()
+            .chars()
+            .take_while(|c| c.is_alphanumeric() || *c == '_')
+            .collect();
+        let suffix = NumberSuffix::from_name(&name)?;
+        for _ in 0..name.chars().count() {
+            self.read_char();
+        }
+        Some(suffix)
+    }
+
+    /// A suffixed literal, checked against the range of its suffix
+    fn typed_number(literal: &str, digits: String, suffix: NumberSuffix) -> TokenKind {
+        let Some(max) = suffix.max_value() else {
+            return TokenKind::TypedNumber(digits, suffix);
+        };
+        match digits.parse::<u64>() {
+            Ok(value) if value <= max && i64::try_from(value).is_ok() => TokenKind::TypedNumber(digits, suffix),
+            Ok(value) if value <= max => {
+                TokenKind::InvalidNumber(format!("`{}` is too large for an integer", literal))
             }
+            _ => TokenKind::InvalidNumber(format!(
+                "`{}` doesn't fit in {}, which goes up to {}",
+                literal, suffix.name(), max
+            )),
         }
+    }
 
-        let literal: String = self.input[start_pos..self.position].iter().collect();
-
-        if is_float {
-            Token::new(TokenKind::Float(literal.clone()), literal, self.line, start_col)
-        } else if is_hex {
-            // Parse hexadecimal (strip "0x" prefix)
-            let hex_str = &literal[2..]; // Remove "0x" prefix
-            let value = i64::from_str_radix(hex_str, 16).unwrap_or(0);
-            Token::new(TokenKind::Integer(value), literal, self.line, start_col)
-        } else if is_octal {
-            // Parse octal (strip "0o" prefix)
-            let octal_str = &literal[2..]; // Remove "0o" prefix
-            let value = i64::from_str_radix(octal_str, 8).unwrap_or(0);
-            Token::new(TokenKind::Integer(value), literal, self.line, start_col)
-        } else if is_binary {
-            // Parse binary (strip "0b" prefix)
-            let binary_str = &literal[2..]; // Remove "0b" prefix
-            let value = i64::from_str_radix(binary_str, 2).unwrap_or(0);
-            Token::new(TokenKind::Integer(value), literal, self.line, start_col)
+    /// Consumes a run of digits and `_` separators
+    fn read_digits(&mut self, is_digit: impl Fn(char) -> bool) {
+        while is_digit(self.ch) || self.ch == '_' {
+            self.read_char();
+        }
+    }
+
+    /// Underscores only go between digits: not at the end of `literal`, nor
+    /// after `integer_part` next to the decimal point
+    fn misplaced_underscore(literal: &str, integer_part: &str) -> Option<String> {
+        let fraction = literal[integer_part.len()..].strip_prefix('.');
+        if (integer_part.ends_with('_') && fraction.is_some()) || fraction.is_some_and(|fraction| fraction.starts_with('_')) {
+            Some(format!("numeric literal `{}` has an underscore next to the decimal point", literal))
+        } else if literal.ends_with('_') {
+            Some(format!("numeric literal `{}` can't end with an underscore; underscores go between digits", literal))
         } else {
-            let value = literal.parse().unwrap_or(0);
-            Token::new(TokenKind::Integer(value), literal, self.line, start_col)
+            None
         }
     }
-    
+
+    /// Reads a prefixed integer literal. The whole run of letters and digits
+    /// after the prefix is one token, so `0b102` is reported as a bad literal
+    /// rather than read as `0b10` followed by `2`.
+    fn read_radix_number(&mut self, radix: u32, name: &str) -> Token {
+        let start_pos = self.position;
+        let start_col = self.column;
+        self.read_char(); // consume '0'
+        self.read_char(); // consume the prefix letter
+
+        let digits_start = self.position;
+        self.read_digits(|c| c.is_ascii_alphanumeric());
+        let literal: String = self.input[start_pos..self.position].to_string();
+        let written: String = self.input[digits_start..self.position].to_string();
+
+        // An integer suffix ends the run (`0xFFu8`); `f32` can't, it's hex digits
+        let suffix = NumberSuffix::ALL.into_iter()
+            .filter(|suffix| !suffix.is_float())
+            .find(|suffix| written.len() > suffix.name().len() && written.ends_with(suffix.name()));
+        let written = match suffix {
+            Some(suffix) => &written[..written.len() - suffix.name().len()],
+            None => &written[..],
+        };
+        let digits = written.replace('_', "");
+
+        let error = if digits.is_empty() {
+            Some(format!("`{}` needs at least one {} digit after the prefix", literal, name))
+        } else if written.starts_with('_') || written.ends_with('_') {
+            Some(format!("numeric literal `{}` has an underscore outside its digits; underscores go between digits", literal))
+        } else {
+            digits.chars()
+                .find(|c| !c.is_digit(radix))
+                .map(|bad| format!("invalid digit '{}' in {} literal `{}`", bad, name, literal))
+        };
+        let kind = match (error, suffix) {
+            (Some(message), _) => TokenKind::InvalidNumber(message),
+            (None, Some(suffix)) => match u64::from_str_radix(&digits, radix) {
+                Ok(value) => Self::typed_number(&literal, value.to_string(), suffix),
+                Err(_) => TokenKind::InvalidNumber(format!("{} literal `{}` is too large for an integer", name, literal)),
+            },
+            (None, None) => match i64::from_str_radix(&digits, radix) {
+                Ok(value) => TokenKind::Integer(value),
+                Err(_) => TokenKind::InvalidNumber(format!("{} literal `{}` is too large for an integer", name, literal)),
+            },
+        };
+        self.number_token(kind, literal, start_col)
+    }
+
     fn read_string(&mut self) -> Token {
         let start_col = self.column;
+        let start_line = self.line;
         self.read_char(); // Consume opening '"'
 
         let mut result = String::new();
+        // The first bad escape; the string is still read to its end
+        let mut invalid_escape = None;
 
         while self.ch != '"' && self.ch != '\0' {
             if self.ch == '\\' {
-                // Handle escape sequences
+                let (escape_line, escape_col) = (self.line, self.column);
                 self.read_char(); // consume backslash
-                match self.ch {
-                    'n' => result.push('\n'),   // newline
-                    't' => result.push('\t'),   // tab
-                    'r' => result.push('\r'),   // carriage return
-                    '\\' => result.push('\\'),  // backslash
-                    '"' => result.push('"'),    // quote
-                    '\'' => result.push('\''),  // single quote
-                    '0' => result.push('\0'),   // null
-                    _ => {
-                        // Unknown escape sequence - include backslash and char
-                        result.push('\\');
-                        result.push(self.ch);
+                if self.ch == '\0' {
+                    break; // the source ends after the backslash
+                }
+                match self.read_escape() {
+                    Ok(ch) => result.push(ch),
+                    Err(message) if invalid_escape.is_none() => {
+                        invalid_escape = Some(self.report(LexErrorKind::InvalidEscape, format!("{} in string", message), escape_line, escape_col));
                     }
+                    Err(_) => {}
                 }
-                self.read_char();
             } else {
                 result.push(self.ch);
                 self.read_char();
             }
         }
 
+        if self.ch == '\0' {
+            let message = format!("unterminated string starting on line {}; close it with \"", start_line);
+            let message = self.report(LexErrorKind::UnterminatedString, message, start_line, start_col);
+            return Token::new(TokenKind::InvalidString(message), result, start_line, start_col);
+        }
+        if let Some(message) = invalid_escape {
+            self.read_char(); // Consume closing '"'
+            return Token::new(TokenKind::InvalidString(message), result, start_line, start_col);
+        }
         let token = Token::new(TokenKind::String(result.clone()), result, self.line, start_col);
         self.read_char(); // Consume closing '"'
         token
     }
 
+    /// Whether the `r` at the current position opens `r"..."` or `r#"..."#`
+    fn starts_raw_string(&self) -> bool {
+        let after_hashes = self.input[self.read_position..].chars().find(|&ch| ch != '#');
+        after_hashes == Some('"')
+    }
+
+    /// Reads a raw string: no escapes, and as many `#`s around the quotes as
+    /// it takes for the contents to hold `"`. The lexeme is the contents as written.
+    fn read_raw_string(&mut self) -> Token {
+        let start_col = self.column;
+        let start_line = self.line;
+        self.read_char(); // Consume 'r'
+        let mut hashes = 0;
+        while self.ch == '#' {
+            hashes += 1;
+            self.read_char();
+        }
+        self.read_char(); // Consume opening '"'
+
+        let contents_start = self.position;
+        // A closing quote with too few hashes, remembered in case the string never closes
+        let mut short_close: Option<(usize, usize)> = None;
+        loop {
+            if self.ch == '\0' {
+                let delimiter = format!("\"{}", "#".repeat(hashes));
+                let mut message = format!(
+                    "unterminated raw string starting on line {}; close it with `{}`",
+                    start_line, delimiter
+                );
+                if let Some((line, found)) = short_close {
+                    message.push_str(&format!(
+                        " (the `\"{}` on line {} has {} `#` where the opening `r{}\"` has {})",
+                        "#".repeat(found), line, found, "#".repeat(hashes), hashes
+                    ));
+                }
+                let message = self.report(LexErrorKind::UnterminatedString, message, start_line, start_col);
+                return Token::new(TokenKind::InvalidString(message), String::new(), start_line, start_col);
+            }
+            if self.ch == '"' {
+                let closing = self.input[self.read_position..].chars().take(hashes).take_while(|&ch| ch == '#').count();
+                if closing == hashes {
+                    break;
+                }
+                short_close.get_or_insert((self.line, closing));
+            }
+            self.read_char();
+        }
+
+        let contents: String = self.input[contents_start..self.position].to_string();
+        for _ in 0..=hashes {
+            self.read_char(); // Consume the closing '"' and its '#'s
+        }
+        if self.ch == '#' {
+            let mut extra = 0;
+            while self.ch == '#' {
+                extra += 1;
+                self.read_char();
+            }
+            let message = format!(
+                "raw string starting on line {} opens with {} `#` but closes with {}",
+                start_line, hashes, hashes + extra
+            );
+            let message = self.report(LexErrorKind::UnterminatedString, message, start_line, start_col);
+            return Token::new(TokenKind::InvalidString(message), contents, start_line, start_col);
+        }
+        Token::new(TokenKind::String(contents.clone()), contents, start_line, start_col)
+    }
+
+    /// Reads the next piece of a template string: a run of text, the `${`
+    /// opening an interpolation, or the closing backtick
+    fn read_template_text(&mut self, start_line: usize) -> Token {
+        let start_col = self.column;
+        let line = self.line;
+        if self.ch == '`' {
+            self.templates.pop();
+            self.read_char();
+            return Token::new(TokenKind::TemplateEnd, "`".to_string(), line, start_col);
+        }
+        if self.ch == '$' && self.peek() == '{' {
+            self.read_char();
+            self.read_char();
+            self.templates.push(TemplateState::Interpolation { braces: 0 });
+            return Token::new(TokenKind::TemplateInterpolationStart, "${".to_string(), line, start_col);
+        }
+
+        let mut text = String::new();
+        while self.ch != '`' && !(self.ch == '$' && self.peek() == '{') {
+            match self.ch {
+                '\0' => {
+                    self.templates.clear();
+                    let message = format!("unterminated template string starting on line {}; close it with `", start_line);
+                    let message = self.report(LexErrorKind::UnterminatedString, message, start_line, start_col);
+                    return Token::new(TokenKind::InvalidString(message), text, line, start_col);
+                }
+                '\\' => {
+                    let (escape_line, escape_col) = (self.line, self.column);
+                    self.read_char();
+                    let escaped = match self.ch {
+                        '$' | '`' => {
+                            let ch = self.ch;
+                            self.read_char();
+                            Ok(ch)
+                        }
+                        _ => self.read_escape(),
+                    };
+                    match escaped {
+                        Ok(ch) => text.push(ch),
+                        Err(message) => {
+                            self.templates.clear();
+                            let message = self.report(LexErrorKind::InvalidEscape, format!("{} in template string", message), escape_line, escape_col);
+                            return Token::new(TokenKind::InvalidString(message), text, line, start_col);
+                        }
+                    }
+                }
+                ch => {
+                    text.push(ch);
+                    self.read_char();
+                }
+            }
+        }
+        Token::new(TokenKind::TemplateChunk(text.clone()), text, line, start_col)
+    }
+
+    /// Reads `b"..."`: ASCII text and escapes of single bytes
+    fn read_byte_string(&mut self) -> Token {
+        let start_pos = self.position;
+        let start_col = self.column;
+        let start_line = self.line;
+        self.read_char(); // Consume 'b'
+        self.read_char(); // Consume opening '"'
+
+        let mut bytes = Vec::new();
+        let mut error = None;
+        while self.ch != '"' {
+            if self.ch == '\0' {
+                let message = format!("unterminated byte string starting on line {}; close it with \"", start_line);
+                let message = self.report(LexErrorKind::UnterminatedString, message, start_line, start_col);
+                return Token::new(TokenKind::InvalidString(message), String::new(), start_line, start_col);
+            }
+            match self.read_byte("byte string") {
+                Ok(byte) => bytes.push(byte),
+                Err(message) => {
+                    error.get_or_insert(message);
+                }
+            }
+        }
+        self.read_char(); // Consume closing '"'
+
+        let literal: String = self.input[start_pos..self.position].to_string();
+        let kind = match error {
+            Some(message) => TokenKind::InvalidString(message),
+            None => TokenKind::ByteString(bytes),
+        };
+        Token::new(kind, literal, start_line, start_col)
+    }
+
+    /// Reads `b'x'`, one ASCII character or byte escape
+    fn read_byte_literal(&mut self) -> Token {
+        let start_pos = self.position;
+        let start_col = self.column;
+        let line = self.line;
+        self.read_char(); // Consume 'b'
+        self.read_char(); // Consume opening '
+
+        let byte = match self.ch {
+            '\'' => {
+                let message = "empty byte literal `b''`; a byte literal holds exactly one character".to_string();
+                Err(self.report(LexErrorKind::InvalidChar, message, line, start_col))
+            }
+            '\n' | '\0' => {
+                let message = "unterminated byte literal; close it with '".to_string();
+                Err(self.report(LexErrorKind::InvalidChar, message, line, start_col))
+            }
+            _ => self.read_byte("byte literal"),
+        };
+        let kind = match byte {
+            Ok(_) if self.ch != '\'' => {
+                let mut rest = self.rest().chars().take_while(|&c| c != '\n' && c != '\0');
+                if let Some(close) = rest.position(|c| c == '\'') {
+                    for _ in 0..=close {
+                        self.read_char();
+                    }
+                    let contents: String = self.input[start_pos + 2..self.position - 1].to_string();
+                    let message = format!(
+                        "byte literal `b'{}'` holds more than one character; use a byte string: b\"{}\"",
+                        contents, contents
+                    );
+                    TokenKind::InvalidChar(self.report(LexErrorKind::InvalidChar, message, line, start_col))
+                } else {
+                    let literal: String = self.input[start_pos..self.position].to_string();
+                    let message = format!("unterminated byte literal `{}`; close it with '", literal);
+                    TokenKind::InvalidChar(self.report(LexErrorKind::InvalidChar, message, line, start_col))
+                }
+            }
+            Ok(byte) => {
+                self.read_char(); // Consume the closing '
+                TokenKind::Byte(byte)
+            }
+            Err(message) => TokenKind::InvalidChar(message),
+        };
+        let literal: String = self.input[start_pos..self.position].to_string();
+        Token::new(kind, literal, line, start_col)
+    }
+
+    /// Reads one byte of a byte string or literal (`what`): an ASCII character,
+    /// or an escape (`\n`, `\t`, `\r`, `\0`, `\\`, `\'`, `\"`, `\xFF`)
+    fn read_byte(&mut self, what: &str) -> Result<u8, String> {
+        let (line, column) = (self.line, self.column);
+        let ch = self.ch;
+        self.read_char();
+        if ch != '\\' {
+            return if ch.is_ascii() {
+                Ok(ch as u8)
+            } else {
+                let message = format!(
+                    "non-ASCII character '{}' in {} at line {}, column {}; write its bytes as \\x escapes",
+                    ch, what, line, column
+                );
+                Err(self.report(LexErrorKind::InvalidChar, message, line, column))
+            };
+        }
+
+        let escaped = self.ch;
+        self.read_char();
+        let message = match escaped {
+            'n' => return Ok(b'\n'),
+            't' => return Ok(b'\t'),
+            'r' => return Ok(b'\r'),
+            '0' => return Ok(0),
+            '\\' | '\'' | '"' => return Ok(escaped as u8),
+            '\0' => format!("`\\` at the end of the source in {} at line {}, column {} escapes nothing", what, line, column),
+            'x' => {
+                let digits: String = [self.ch, self.peek()].iter().collect();
+                match u8::from_str_radix(&digits, 16) {
+                    Ok(byte) if digits.chars().all(|c| c.is_ascii_hexdigit()) => {
+                        self.read_char();
+                        self.read_char();
+                        return Ok(byte);
+                    }
+                    _ => format!(
+                        "`\\x` in {} at line {}, column {} needs two hex digits, like \\x7F",
+                        what, line, column
+                    ),
+                }
+            }
+            other => format!(
+                "unknown escape `\\{}` in {} at line {}, column {}; bytes can use \\n, \\t, \\r, \\0, \\\\, \\', \\\" and \\xNN",
+                other, what, line, column
+            ),
+        };
+        Err(self.report(LexErrorKind::InvalidEscape, message, line, column))
+    }
+
+    /// Reads `'a'` or an escape like `'\n'` or `'\u{1F600}'`
+    fn read_char_literal(&mut self) -> Token {
+        let start_pos = self.position;
+        let start_col = self.column;
+        let line = self.line;
+        self.read_char(); // Consume the opening '
+
+        let value = match self.ch {
+            '\\' => {
+                let escape_col = self.column;
+                self.read_char();
+                let escaped = self.read_escape();
+                if escaped.is_err() && self.ch == '\'' {
+                    self.read_char(); // Consume the closing ', so it doesn't open another literal
+                }
+                escaped.map_err(|message| self.report(LexErrorKind::InvalidEscape, message, line, escape_col))
+            }
+            '\'' => {
+                self.read_char();
+                let message = "empty char literal `''`; a char holds exactly one character".to_string();
+                Err(self.report(LexErrorKind::InvalidChar, message, line, start_col))
+            }
+            '\n' | '\0' => {
+                let message = "unterminated char literal; close it with '".to_string();
+                Err(self.report(LexErrorKind::InvalidChar, message, line, start_col))
+            }
+            ch => {
+                self.read_char();
+                Ok(ch)
+            }
+        };
+        let kind = match value {
+            Ok(_) if self.ch != '\'' => {
+                let literal: String = self.input[start_pos..self.position].to_string();
+                let message = format!("unterminated char literal `{}`; close it with '", literal);
+                TokenKind::InvalidChar(self.report(LexErrorKind::InvalidChar, message, line, start_col))
+            }
+            Ok(ch) => {
+                self.read_char(); // Consume the closing '
+                TokenKind::Char(ch)
+            }
+            Err(message) => TokenKind::InvalidChar(message),
+        };
+        let literal: String = self.input[start_pos..self.position].to_string();
+        Token::new(kind, literal, line, start_col)
+    }
+
+    /// The character an escape stands for, with the lexer just past the backslash
+    fn read_escape(&mut self) -> Result<char, String> {
+        let escaped = match self.ch {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            '0' => '\0',
+            '\\' | '\'' | '"' => self.ch,
+            'x' => {
+                // An ASCII character, `\x1b`
+                let digits: String = [self.peek(), self.char_ahead(2)].iter().collect();
+                match u8::from_str_radix(&digits, 16) {
+                    Ok(code) if code <= 0x7F && digits.chars().all(|c| c.is_ascii_hexdigit()) => {
+                        self.read_char();
+                        self.read_char();
+                        code as char
+                    }
+                    _ => {
+                        self.read_char();
+                        return Err("`\\x` needs two hex digits up to 7F, like \\x1b".to_string());
+                    }
+                }
+            }
+            'u' if self.peek() == '{' => {
+                self.read_char(); // consume 'u'
+                self.read_char(); // consume '{'
+                let digits_start = self.position;
+                while self.ch.is_ascii_hexdigit() {
+                    self.read_char();
+                }
+                let digits: String = self.input[digits_start..self.position].to_string();
+                if self.ch != '}' {
+                    return Err(format!("unterminated unicode escape `\\u{{{}`; close it with }}", digits));
+                }
+                let code = u32::from_str_radix(&digits, 16).ok().filter(|_| (1..=6).contains(&digits.len()));
+                match code.and_then(char::from_u32) {
+                    Some(ch) => ch,
+                    None => {
+                        self.read_char();
+                        return Err(format!("`\\u{{{}}}` is not a unicode character", digits));
+                    }
+                }
+            }
+            '\0' => return Err("`\\` at the end of the source escapes nothing".to_string()),
+            other => {
+                self.read_char();
+                return Err(format!("unknown escape `\\{}`", other));
+            }
+        };
+        self.read_char();
+        Ok(escaped)
+    }
+
+    /// Whether the `{` at `ch` holds comments and nothing else, like `{/* TODO */}`
+    fn braces_hold_only_comments(&self) -> bool {
+        let mut rest = &self.rest()[1..];
+        let mut comments = 0;
+        loop {
+            rest = rest.trim_start();
+            let after = if let Some(block) = rest.strip_prefix("/*") {
+                block.find("*/").map(|end| &block[end + 2..])
+            } else if let Some(line) = rest.strip_prefix("//") {
+                line.find('\n').map(|end| &line[end..])
+            } else {
+                return comments > 0 && rest.starts_with('}');
+            };
+            match after {
+                Some(after) => rest = after,
+                None => return false,
+            }
+            comments += 1;
+        }
+    }
+
+    /// Reads the at-rule at `ch`: `@media`, `@font-face`, ... Names css!
+    /// doesn't support are read as `CssAtRule`, for the parser to skip. An
+    /// `@` without a name is left unread, so it's read as a plain `@` from
+    /// where it is.
+    fn read_css_at_rule(&mut self) -> Option<Token> {
+        let start_col = self.column;
+        let name: String = self.rest()[1..].chars().take_while(|&ch| is_css_name_char(ch)).collect();
+        let kind = match name.as_str() {
+            "" => return None,
+            "media" => TokenKind::CssMedia,
+            "container" => TokenKind::CssContainer,
+            "keyframes" => TokenKind::CssKeyframes,
+            "supports" => TokenKind::CssSupports,
+            "font-face" => TokenKind::CssFontFace,
+            "import" => TokenKind::CssImport,
+            "page" => TokenKind::CssPage,
+            _ => TokenKind::CssAtRule(name.clone()),
+        };
+        for _ in 0..=name.len() {
+            self.read_char();
+        }
+        Some(Token::new(kind, format!("@{}", name), self.line, start_col))
+    }
+
     fn read_lifetime(&mut self) -> Token {
         let start_pos = self.position;
         let start_col = self.column;
@@ -681,18 +1672,27 @@ impl Lexer {
         self.read_char(); // Consume the '
 
         // Read the lifetime name (identifier after the ')
-        while self.ch.is_alphanumeric() || self.ch == '_' {
+        while is_identifier_continue(self.ch) {
             self.read_char();
         }
 
-        let literal: String = self.input[start_pos..self.position].iter().collect();
+        let literal: String = self.input[start_pos..self.position].to_string();
         // Extract the lifetime name without the leading quote
         let lifetime_name = literal[1..].to_string();
 
+        // 'ab' is a char literal holding too much, not a lifetime
+        if self.ch == '\'' {
+            self.read_char();
+            let message = format!("char literal `{}'` holds more than one character; use a string: \"{}\"", literal, lifetime_name);
+            let message = self.report(LexErrorKind::InvalidChar, message, self.line, start_col);
+            return Token::new(TokenKind::InvalidChar(message), format!("{}'", literal), self.line, start_col);
+        }
+
         Token::new(TokenKind::Lifetime(lifetime_name.clone()), literal, self.line, start_col)
     }
 
     fn read_jsx_text(&mut self) -> Token {
+        let start_line = self.line;
         let start_col = self.column;
         let mut result = String::new();
 
@@ -702,75 +1702,220 @@ impl Lexer {
             self.read_char();
         }
 
-        // Trim the result to remove extra whitespace (but preserve intentional spacing)
-        let trimmed = result.trim().to_string();
+        // Entities are decoded after collapsing, so a `&nbsp;` at either end stays
+        let text = if self.in_raw_text() { result.clone() } else { collapse_jsx_whitespace(&result) };
+        if text.is_empty() {
+            // Only indentation and line breaks: no token, read what follows
+            self.mark_token_start();
+            return self.read_token();
+        }
+
+        Token::new(TokenKind::JsxText(html_entities::decode(&text)), result, start_line, start_col)
+    }
+
+    /// Text up to the `</tag` that closes a <style> or <script>, as written:
+    /// no entities, and no `{` or `<` start anything
+    fn read_raw_element_text(&mut self, tag: &str) -> Token {
+        let start_col = self.column;
+        let mut result = String::new();
+        while self.ch != '\0' && !self.at_closing_tag(tag) {
+            result.push(self.ch);
+            self.read_char();
+        }
+
+        if result.trim().is_empty() {
+            self.mark_token_start();
+            return self.read_token();
+        }
+        Token::new(TokenKind::JsxText(result.clone()), result, self.line, start_col)
+    }
+
+    /// The tag of the <style> or <script> whose children are being read
+    fn raw_element_tag(&self) -> Option<String> {
+        let (index, tag) = self.raw_elements.last()?;
+        (self.mode() == LexMode::JsxChildren && *index == self.modes.len() - 1).then(|| tag.clone())
+    }
+
+    /// At `</tag>` or `</tag `
+    fn at_closing_tag(&self, tag: &str) -> bool {
+        self.rest()
+            .strip_prefix("</")
+            .and_then(|rest| rest.strip_prefix(tag))
+            .is_some_and(|rest| rest.starts_with(|ch: char| ch == '>' || ch.is_whitespace()))
+    }
+
+    /// Inside the children of a <pre> or <textarea>, where text is kept as written
+    fn in_raw_text(&self) -> bool {
+        self.raw_text.iter().any(|&index| self.modes.get(index) == Some(&LexMode::JsxChildren))
+    }
+
+    /// The innermost mode
+    fn mode(&self) -> LexMode {
+        self.modes.last().copied().unwrap_or(LexMode::Normal)
+    }
+
+    /// The innermost mode, looking past a tag being read to what it's in
+    fn mode_outside_tag(&self) -> LexMode {
+        self.modes.iter().rev().copied().find(|&mode| mode != LexMode::JsxTag).unwrap_or(LexMode::Normal)
+    }
+
+    fn push_mode(&mut self, mode: LexMode) {
+        self.modes.push(mode);
+    }
+
+    /// Pops modes down to and including the innermost one `is_target`
+    /// accepts; nothing if there is none. `Normal` is never popped.
+    fn pop_mode_through(&mut self, is_target: impl Fn(LexMode) -> bool) {
+        if let Some(index) = self.modes.iter().rposition(|&mode| is_target(mode)) {
+            self.modes.truncate(index.max(1));
+        }
+    }
+
+    /// Inside a JSX element, however deep in expressions
+    fn in_jsx(&self) -> bool {
+        self.modes.contains(&LexMode::JsxChildren)
+    }
 
-        Token::new(TokenKind::JsxText(trimmed.clone()), trimmed, self.line, start_col)
+    fn in_css(&self) -> bool {
+        matches!(self.mode(), LexMode::Css { .. } | LexMode::CssMediaCondition)
+    }
+
+    /// In an @media condition or CSS parentheses, where words are read as
+    /// properties and numbers as numbers
+    fn in_css_condition(&self) -> bool {
+        self.mode() == LexMode::CssMediaCondition || matches!(self.mode(), LexMode::Css { parens, .. } if parens > 0)
+    }
+
+    /// The parenthesis depth of the CSS body being read
+    fn css_parens(&mut self) -> Option<&mut usize> {
+        self.modes.iter_mut().rev().find_map(|mode| match mode {
+            LexMode::Css { parens, .. } => Some(parens),
+            _ => None,
+        })
+    }
+
+    /// The mode stack, outermost first, e.g. `Normal > JsxChildren > JsxTag`.
+    /// For finding where the parser and lexer disagree about what they're in.
+    pub fn debug_modes(&self) -> String {
+        self.visible().modes.iter().map(|mode| format!("{:?}", mode)).collect::<Vec<_>>().join(" > ")
     }
 
     // Public methods for parser to manage JSX mode
+
+    /// Enters an element whose `<` was just read. Its tag stays on top,
+    /// unless the `>` after a fragment's `<` has already closed it.
     pub fn enter_jsx_mode(&mut self) {
-        self.jsx_mode = true;
-        self.jsx_depth += 1;
-        // Record the current brace depth as the baseline for this JSX element
-        self.jsx_baseline_brace_depths.push(self.brace_depth);
+        self.discard_lookahead();
+        let in_tag = self.mode() == LexMode::JsxTag;
+        if in_tag {
+            self.modes.pop();
+        }
+        self.push_mode(LexMode::JsxChildren);
+        if in_tag {
+            self.push_mode(LexMode::JsxTag);
+        }
     }
 
-    // Enter nested JSX (already in jsx_mode, just track nesting)
+    /// Enters an element nested in another, e.g. inside an expression like
+    /// `{cond ? (<div>...</div>) : ...}`
     pub fn enter_nested_jsx(&mut self) {
-        self.jsx_depth += 1;
-        // Push current brace depth as baseline for this nested JSX element
-        // This is CRITICAL for JSX inside expressions like: {cond ? (<div>...</div>) : ...}
-        self.jsx_baseline_brace_depths.push(self.brace_depth);
+        self.enter_jsx_mode();
     }
 
+    /// Leaves the innermost element, with anything still open inside it. A
+    /// tag on top, or an expression just opened, stays open: its `<` or `{`
+    /// was the token after the element.
     pub fn exit_jsx_mode(&mut self) {
-        if self.jsx_depth > 0 {
-            self.jsx_depth -= 1;
-            // Pop the baseline brace depth for this JSX element
-            self.jsx_baseline_brace_depths.pop();
-        }
-        if self.jsx_depth == 0 {
-            self.jsx_mode = false;
+        self.discard_lookahead();
+        let top = self.mode();
+        let keep = top == LexMode::JsxTag || (top == LexMode::JsxExpr && self.opened_jsx_expr);
+        self.pop_mode_through(|mode| mode == LexMode::JsxChildren);
+        if keep && self.mode() != top {
+            self.push_mode(top);
         }
     }
 
     pub fn is_jsx_mode(&self) -> bool {
-        self.jsx_mode
+        self.visible().in_jsx()
     }
 
     pub fn enter_closing_tag_mode(&mut self) {
-        self.in_closing_tag = true;
+        self.discard_lookahead();
+        self.push_mode(LexMode::JsxClosingTag);
     }
 
     pub fn exit_closing_tag_mode(&mut self) {
-        self.in_closing_tag = false;
+        self.discard_lookahead();
+        self.pop_mode_through(|mode| mode == LexMode::JsxClosingTag);
+    }
+
+    /// Reads a component's type arguments, `<List<User>>`, whose `<` was
+    /// just read, up to the `>` closing them
+    pub fn enter_jsx_type_args(&mut self) {
+        self.discard_lookahead();
+        self.push_mode(LexMode::JsxTypeArgs);
+    }
+
+    /// Keeps the whitespace of the text in the element being entered, whose
+    /// name is the next token: the children of a <pre> or <textarea>. Lasts
+    /// until exit_raw_text_mode(), or the tag's `/>` if it has no children.
+    pub fn enter_raw_text_mode(&mut self) {
+        self.discard_lookahead();
+        if let Some(index) = self.modes.iter().rposition(|&mode| mode == LexMode::JsxChildren) {
+            self.raw_text.push(index);
+        }
+    }
+
+    pub fn exit_raw_text_mode(&mut self) {
+        self.discard_lookahead();
+        self.raw_text.pop();
+    }
+
+    /// Reads the children of the <style> or <script> being entered as a
+    /// single text token, up to its closing tag. Lasts until
+    /// exit_raw_element_mode(), or the tag's `/>` if it has no children.
+    pub fn enter_raw_element_mode(&mut self, tag: &str) {
+        self.discard_lookahead();
+        if let Some(index) = self.modes.iter().rposition(|&mode| mode == LexMode::JsxChildren) {
+            self.raw_elements.push((index, tag.to_string()));
+        }
+    }
+
+    pub fn exit_raw_element_mode(&mut self) {
+        self.discard_lookahead();
+        self.raw_elements.pop();
     }
 
     pub fn increment_brace_depth(&mut self) {
-        self.brace_depth += 1;
+        self.discard_lookahead();
+        self.push_mode(LexMode::Block);
     }
 
     pub fn decrement_brace_depth(&mut self) {
-        if self.brace_depth > 0 {
-            self.brace_depth -= 1;
+        self.discard_lookahead();
+        if self.mode() == LexMode::Block {
+            self.modes.pop();
         }
     }
 
     // CSS mode management
+
+    /// Enters a CSS body whose `{` was just read
     pub fn enter_css_mode(&mut self) {
-        self.css_mode = true;
-        self.css_depth = 1; // Start at depth 1 (first opening brace)
+        self.discard_lookahead();
+        match self.modes.last_mut() {
+            Some(LexMode::Css { depth, .. }) => *depth = 1,
+            _ => self.push_mode(LexMode::Css { depth: 1, parens: 0 }),
+        }
     }
 
     pub fn exit_css_mode(&mut self) {
-        self.css_mode = false;
-        self.css_depth = 0;
-        self.css_paren_depth = 0;
+        self.discard_lookahead();
+        self.pop_mode_through(|mode| matches!(mode, LexMode::Css { .. }));
     }
 
     pub fn is_css_mode(&self) -> bool {
-        self.css_mode
+        self.visible().in_css()
     }
 
     // Read a CSS selector (.button, #id, div, .button:hover, .card .title, etc.)
@@ -779,18 +1924,45 @@ impl Lexer {
         let start_pos = self.position;
 
         // Read selector until we hit { (which indicates start of declarations)
-        // This allows for nested selectors like ".card .title"
-        while self.ch != '{' && self.ch != '\0' && self.ch != '\n' {
+        // This allows for nested selectors like ".card .title", and lists
+        // spanning lines like "h1,\n h2"; comments in it are left out,
+        // `.card /* header */ .title`. Attribute selectors are read whole,
+        // quoted values and all: `a[href$=".pdf;{"]`.
+        let mut selector = String::new();
+        let mut part_start = start_pos;
+        let mut brackets = 0usize;
+        while self.ch != '\0' {
+            match self.ch {
+                '{' | '}' | ';' if brackets == 0 => break,
+                '[' => brackets += 1,
+                ']' => brackets = brackets.saturating_sub(1),
+                '"' | '\'' if brackets > 0 => {
+                    self.skip_css_string();
+                    continue;
+                }
+                _ if brackets == 0 && self.at_css_comment() => {
+                    selector.push_str(&self.input[part_start..self.position]);
+                    self.skip_comment();
+                    part_start = self.position;
+                    continue;
+                }
+                _ => {}
+            }
             self.read_char();
         }
-
-        // Trim whitespace from the end
-        let mut end_pos = self.position;
-        while end_pos > start_pos && self.input[end_pos - 1].is_whitespace() {
-            end_pos -= 1;
+        selector.push_str(&self.input[part_start..self.position]);
+
+        // Runs of whitespace, line breaks in a list too, become one space;
+        // quoted attribute values are kept as written
+        let mut normalized = String::new();
+        for (_, ch, top) in CssSelector::top_level_chars(selector.trim()) {
+            if !(top && ch.is_whitespace()) {
+                normalized.push(ch);
+            } else if !normalized.ends_with(' ') {
+                normalized.push(' ');
+            }
         }
-
-        let selector: String = self.input[start_pos..end_pos].iter().collect();
+        let selector = normalized;
         Token::new(TokenKind::CssSelector(selector.clone()), selector, self.line, start_col)
     }
 
@@ -799,12 +1971,12 @@ impl Lexer {
         let start_col = self.column;
         let start_pos = self.position;
 
-        // Read property name (alphanumeric and hyphens)
-        while self.ch.is_alphanumeric() || self.ch == '-' {
+        // Read property name, custom ones like `--brand_color` too
+        while is_css_name_char(self.ch) {
             self.read_char();
         }
 
-        let property: String = self.input[start_pos..self.position].iter().collect();
+        let property: String = self.input[start_pos..self.position].to_string();
         Token::new(TokenKind::CssProperty(property.clone()), property, self.line, start_col)
     }
 
@@ -818,20 +1990,98 @@ impl Lexer {
             self.read_char();
         }
 
-        // Read until semicolon, closing brace, or newline
-        while self.ch != ';' && self.ch != '}' && self.ch != '\0' {
+        // Read until semicolon, closing brace, or a comment, which the next
+        // token skips; the parser joins the value around it. Function calls
+        // are read whole, `calc(var(--gap) * 2)` or `url(data:...;base64,...)`:
+        // inside their parentheses a `;` is part of the value and `/* */`
+        // comments are left out.
+        let mut value = String::new();
+        let mut part_start = start_pos;
+        let mut parens = 0usize;
+        while self.ch != '}' && self.ch != '\0' {
+            if parens == 0 && (self.ch == ';' || self.at_css_comment()) {
+                break;
+            }
+            if parens > 0 && self.ch == '/' && self.peek() == '*' {
+                value.push_str(self.input[part_start..self.position].trim_end());
+                self.skip_comment();
+                part_start = self.position;
+                continue;
+            }
+            match self.ch {
+                '(' => parens += 1,
+                ')' => parens = parens.saturating_sub(1),
+                '"' | '\'' => {
+                    // Strings are kept as written, `;` and `)` and all
+                    self.skip_css_string();
+                    continue;
+                }
+                _ => {}
+            }
             self.read_char();
         }
+        value.push_str(&self.input[part_start..self.position]);
 
-        let value: String = self.input[start_pos..self.position].iter().collect();
         let trimmed = value.trim().to_string();
         Token::new(TokenKind::CssValue(trimmed.clone()), trimmed, self.line, start_col)
     }
+
+    /// Whether the value here is a single quoted string, `content: "→";`,
+    /// with nothing after it but the end of the declaration
+    fn css_lone_string_ahead(&self) -> bool {
+        let rest = self.rest();
+        match CssSelector::top_level_chars(rest).skip(1).find(|&(_, ch, top)| top && !ch.is_whitespace()) {
+            Some((index, ch, _)) => matches!(ch, ';' | '}') || rest[index..].starts_with("/*") || rest[index..].starts_with("//"),
+            None => true,
+        }
+    }
+
+    /// Skips the quoted string starting here, escaped quotes and all
+    fn skip_css_string(&mut self) {
+        let quote = self.ch;
+        self.read_char();
+        while self.ch != quote && self.ch != '\n' && self.ch != '\0' {
+            if self.ch == '\\' {
+                self.read_char();
+            }
+            self.read_char();
+        }
+        if self.ch == quote {
+            self.read_char();
+        }
+    }
+
+    /// Whether a rule's `{` comes before the end of a declaration, as after
+    /// the selector `h1,\n h2` but not the value `red;`. A `{` right after a
+    /// `:` starts a `{expr}` value instead.
+    fn css_rule_ahead(&self) -> bool {
+        let rest = self.rest();
+        let end = CssSelector::top_level_chars(rest).find(|&(_, ch, top)| top && matches!(ch, '{' | ';' | '}'));
+        match end {
+            Some((end, '{', _)) => {
+                let before = rest[..end].trim_end();
+                !before.is_empty() && !before.ends_with(':')
+            }
+            _ => false,
+        }
+    }
+
+    /// A comment starting inside a selector or value: `/*`, or `//` except
+    /// right after `:` or `(`, so `url(https://...)` and `url(//cdn...)` keep
+    /// their slashes
+    fn at_css_comment(&self) -> bool {
+        self.ch == '/' && match self.peek() {
+            '*' => true,
+            '/' => !matches!(self.input[..self.position].chars().next_back(), Some(':' | '(')),
+            _ => false,
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     #[test]
     fn test_string_escape_sequences() {
@@ -946,8 +2196,14 @@ mod tests {
         lexer.enter_jsx_mode();
 
         let token = lexer.next_token();
-        // JSX text is trimmed
-        assert_eq!(token.kind, TokenKind::JsxText("Hello World".to_string()));
+        // Spaces on one line are kept; they separate the text from what's around it
+        assert_eq!(token.kind, TokenKind::JsxText("  Hello World  ".to_string()));
+
+        // Whitespace spanning lines collapses to one space between the lines' text,
+        // and disappears at either end
+        let mut lexer = Lexer::new("\n    Hello\n    World  \n  ".to_string());
+        lexer.enter_jsx_mode();
+        assert_eq!(lexer.next_token().kind, TokenKind::JsxText("Hello World".to_string()));
     }
 
     #[test]
@@ -1156,7 +2412,7 @@ mod tests {
         lexer.enter_jsx_mode();
 
         let token = lexer.next_token();
-        assert_eq!(token.kind, TokenKind::JsxText("Line 1\nLine 2\nLine 3".to_string()));
+        assert_eq!(token.kind, TokenKind::JsxText("Line 1 Line 2 Line 3".to_string()));
     }
 
     #[test]
@@ -1216,6 +2472,176 @@ mod tests {
         assert_eq!(token8.kind, TokenKind::Semicolon);
     }
 
+    #[test]
+    fn test_css_selector_lists() {
+        // A list spanning lines is one selector, up to its `{`
+        let source = "h1,\n  h2, .title {\n  margin: 0;\n}\nul > li { a: b; }";
+        let mut lexer = Lexer::new_in_mode(source.to_string(), LexerStartMode::Css);
+        let tokens: Vec<_> = std::iter::from_fn(|| Some(lexer.next_token())).take_while(|token| token.kind != TokenKind::Eof).collect();
+        assert_eq!((&tokens[0].kind, tokens[0].line, tokens[0].column), (&TokenKind::CssSelector("h1, h2, .title".to_string()), 1, 1));
+        assert_eq!(tokens[1].kind, TokenKind::LBrace);
+        assert_eq!(tokens[2].kind, TokenKind::CssProperty("margin".to_string()));
+        assert_eq!(tokens[7].kind, TokenKind::CssSelector("ul > li".to_string()));
+        assert_eq!(tokens[9].kind, TokenKind::CssProperty("a".to_string()));
+    }
+
+    #[test]
+    fn test_css_attribute_selectors() {
+        let selectors = |source: &str| {
+            let mut lexer = Lexer::new_in_mode(source.to_string(), LexerStartMode::Css);
+            std::iter::from_fn(|| Some(lexer.next_token().kind))
+                .take_while(|kind| *kind != TokenKind::Eof)
+                .filter_map(|kind| match kind {
+                    TokenKind::CssSelector(selector) => Some(selector),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(selectors(r#"input[type="checkbox"]:checked { a: b; } .tooltip::after { content: "x"; }"#), vec![
+            r#"input[type="checkbox"]:checked"#,
+            ".tooltip::after",
+        ]);
+        // Brackets and quotes hide `{`, `;`, `//` and spacing from the selector around them
+        assert_eq!(selectors(r#"a[href$=".pdf;{"],  [title='it\'s  // x'] { a: b; }"#), vec![
+            r#"a[href$=".pdf;{"], [title='it\'s  // x']"#,
+        ]);
+    }
+
+    #[test]
+    fn test_css_function_values() {
+        let values = |source: &str| {
+            let mut lexer = Lexer::new_in_mode(source.to_string(), LexerStartMode::Css);
+            std::iter::from_fn(|| Some(lexer.next_token().kind))
+                .take_while(|kind| *kind != TokenKind::Eof)
+                .filter_map(|kind| match kind {
+                    TokenKind::CssValue(value) => Some(value),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(values(".a { width: calc(100% - 24px); font-size: clamp(1rem, 2.5vw, 2rem); }"), vec![
+            "calc(100% - 24px)",
+            "clamp(1rem, 2.5vw, 2rem)",
+        ]);
+        assert_eq!(values(".a { margin: calc(var(--gap) * 2) min(4px, 1vw); width: calc(100% /* gutter */ - 1px) }"), vec![
+            "calc(var(--gap) * 2) min(4px, 1vw)",
+            "calc(100% - 1px)",
+        ]);
+        assert_eq!(values(".a { background: url(data:image/png;base64,iVBOR) no-repeat; color: red; }"), vec![
+            "url(data:image/png;base64,iVBOR) no-repeat",
+            "red",
+        ]);
+
+        // Media conditions are still read piece by piece, dimensions whole
+        let mut lexer = Lexer::new_in_mode("@media screen and (min-width: 4px), print { .a { width: max(1px, 2px); } }".to_string(), LexerStartMode::Css);
+        let tokens: Vec<_> = std::iter::from_fn(|| Some(lexer.next_token().kind)).take(12).collect();
+        assert_eq!(tokens, vec![
+            TokenKind::CssMedia,
+            TokenKind::CssProperty("screen".to_string()),
+            TokenKind::CssProperty("and".to_string()),
+            TokenKind::LParen,
+            TokenKind::CssProperty("min-width".to_string()),
+            TokenKind::Colon,
+            TokenKind::CssValue("4px".to_string()),
+            TokenKind::RParen,
+            TokenKind::Comma,
+            TokenKind::CssProperty("print".to_string()),
+            TokenKind::LBrace,
+            TokenKind::CssSelector(".a".to_string()),
+        ]);
+        assert_eq!(lexer.debug_modes(), "Normal > Css { depth: 2, parens: 0 }");
+    }
+
+    #[test]
+    fn test_css_values_with_strings() {
+        // A lone string stays a string; one with more after it is read whole,
+        // `;` and `)` inside quotes included
+        let kinds = |source: &str| {
+            let mut lexer = Lexer::new_in_mode(source.to_string(), LexerStartMode::Css);
+            std::iter::from_fn(|| Some(lexer.next_token().kind)).take_while(|kind| *kind != TokenKind::Eof).collect::<Vec<_>>()
+        };
+        assert_eq!(kinds(".a { content: \"→\"; font-family: \"Inter\", 'Fira Sans', serif; }"), vec![
+            TokenKind::CssSelector(".a".to_string()),
+            TokenKind::LBrace,
+            TokenKind::CssProperty("content".to_string()),
+            TokenKind::Colon,
+            TokenKind::String("→".to_string()),
+            TokenKind::Semicolon,
+            TokenKind::CssProperty("font-family".to_string()),
+            TokenKind::Colon,
+            TokenKind::CssValue("\"Inter\", 'Fira Sans', serif".to_string()),
+            TokenKind::Semicolon,
+            TokenKind::RBrace,
+        ]);
+        assert_eq!(kinds("@font-face { src: url(\"/a;b).woff2\") format(\"woff2\"); }"), vec![
+            TokenKind::CssFontFace,
+            TokenKind::LBrace,
+            TokenKind::CssProperty("src".to_string()),
+            TokenKind::Colon,
+            TokenKind::CssValue("url(\"/a;b).woff2\") format(\"woff2\")".to_string()),
+            TokenKind::Semicolon,
+            TokenKind::RBrace,
+        ]);
+        assert_eq!(kinds("@import url(\"/theme.css\") screen; @import \"/reset.css\";"), vec![
+            TokenKind::CssImport,
+            TokenKind::CssValue("url(\"/theme.css\") screen".to_string()),
+            TokenKind::Semicolon,
+            TokenKind::CssImport,
+            TokenKind::String("/reset.css".to_string()),
+            TokenKind::Semicolon,
+        ]);
+    }
+
+    #[test]
+    fn test_css_shorthand_values_are_one_token() {
+        // However a value starts, a number, `.5` or `-4px`, it runs to its `;`
+        let values = |source: &str| {
+            let mut lexer = Lexer::new_in_mode(source.to_string(), LexerStartMode::Css);
+            std::iter::from_fn(|| Some(lexer.next_token().kind))
+                .take_while(|kind| *kind != TokenKind::Eof)
+                .filter_map(|kind| match kind {
+                    TokenKind::CssValue(value) => Some(value),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            values(".a { margin: 0 auto; font: 12px/1.5 serif; opacity: .5; margin-top: -4px; grid-area: 1 / 3 }"),
+            vec!["0 auto", "12px/1.5 serif", ".5", "-4px", "1 / 3"]
+        );
+        assert_eq!(
+            values(".a { border: 1px solid rgba(0,0,0,0.2); transition: opacity 0.2s, transform 0.2s; }"),
+            vec!["1px solid rgba(0,0,0,0.2)", "opacity 0.2s, transform 0.2s"]
+        );
+    }
+
+    #[test]
+    fn test_css_hex_colors() {
+        // After a declaration's `:`, `#` starts a value; elsewhere an id selector
+        let source = "#main { color: #f0a; background: #fff url(\"x.png\") no-repeat; #logo { fill: #ff00aa80 } }";
+        let mut lexer = Lexer::new_in_mode(source.to_string(), LexerStartMode::Css);
+        let tokens: Vec<_> = std::iter::from_fn(|| Some(lexer.next_token().kind)).take_while(|kind| *kind != TokenKind::Eof).collect();
+        assert_eq!(tokens, vec![
+            TokenKind::CssSelector("#main".to_string()),
+            TokenKind::LBrace,
+            TokenKind::CssProperty("color".to_string()),
+            TokenKind::Colon,
+            TokenKind::CssValue("#f0a".to_string()),
+            TokenKind::Semicolon,
+            TokenKind::CssProperty("background".to_string()),
+            TokenKind::Colon,
+            TokenKind::CssValue("#fff url(\"x.png\") no-repeat".to_string()),
+            TokenKind::Semicolon,
+            TokenKind::CssSelector("#logo".to_string()),
+            TokenKind::LBrace,
+            TokenKind::CssProperty("fill".to_string()),
+            TokenKind::Colon,
+            TokenKind::CssValue("#ff00aa80".to_string()),
+            TokenKind::RBrace,
+            TokenKind::RBrace,
+        ]);
+    }
+
     #[test]
     fn test_octal_literals() {
         let mut lexer = Lexer::new("0o777 0o200 0o644".to_string());
@@ -1244,6 +2670,847 @@ mod tests {
         assert!(matches!(tok3.kind, TokenKind::Integer(0))); // 0b0 = 0
     }
 
+    #[test]
+    fn test_hex_literals() {
+        let mut lexer = Lexer::new("0xFF 0x1f 0XAB".to_string());
+        assert_eq!(lexer.next_token().kind, TokenKind::Integer(255));
+        assert_eq!(lexer.next_token().kind, TokenKind::Integer(31));
+        assert_eq!(lexer.next_token().kind, TokenKind::Integer(171));
+
+        // Uppercase octal and binary prefixes too
+        let mut lexer = Lexer::new("0O17 0B11".to_string());
+        assert_eq!(lexer.next_token().kind, TokenKind::Integer(15));
+        assert_eq!(lexer.next_token().kind, TokenKind::Integer(3));
+    }
+
+    #[test]
+    fn test_radix_literals_in_expression() {
+        let mut lexer = Lexer::new("let flags = 0xFF + 0b1010;".to_string());
+        let kinds: Vec<TokenKind> = std::iter::from_fn(|| {
+            let token = lexer.next_token();
+            (token.kind != TokenKind::Eof).then_some(token.kind)
+        })
+        .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::Let,
+                TokenKind::Identifier,
+                TokenKind::Assign,
+                TokenKind::Integer(255),
+                TokenKind::Plus,
+                TokenKind::Integer(10),
+                TokenKind::Semicolon,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_malformed_radix_literals() {
+        let invalid = |source: &str| {
+            let token = Lexer::new(source.to_string()).next_token();
+            match token.kind {
+                TokenKind::InvalidNumber(message) => (message, token.lexeme),
+                other => panic!("expected an invalid number for {}, got {:?}", source, other),
+            }
+        };
+
+        // The bad digit doesn't split the literal in two
+        assert_eq!(invalid("0b102;"), ("invalid digit '2' in binary literal `0b102`".to_string(), "0b102".to_string()));
+        assert_eq!(invalid("0o78").0, "invalid digit '8' in octal literal `0o78`");
+        assert_eq!(invalid("0xFG").0, "invalid digit 'G' in hexadecimal literal `0xFG`");
+        assert_eq!(invalid("0x;").0, "`0x` needs at least one hexadecimal digit after the prefix");
+        assert_eq!(invalid("0xFFFFFFFFFFFFFFFFF").0, "hexadecimal literal `0xFFFFFFFFFFFFFFFFF` is too large for an integer");
+
+        let mut lexer = Lexer::new("let x = 0b102;".to_string());
+        let error = crate::parser::Parser::new(&mut lexer).parse_program().expect_err("bad literal");
+        assert!(error.to_string().contains("invalid digit '2' in binary literal"), "{}", error);
+    }
+
+    #[test]
+    fn test_digit_separators() {
+        let token = |source: &str| Lexer::new(source.to_string()).next_token();
+
+        let million = token("1_000_000");
+        assert_eq!(million.kind, TokenKind::Integer(1_000_000));
+        assert_eq!(million.lexeme, "1_000_000");
+        let pi = token("3.141_592");
+        assert_eq!(pi.kind, TokenKind::Float("3.141592".to_string()));
+        assert_eq!(pi.lexeme, "3.141_592");
+        assert_eq!(token("0xDE_AD").kind, TokenKind::Integer(0xDEAD));
+        assert_eq!(token("0b1010_0101").kind, TokenKind::Integer(0b1010_0101));
+
+        // A name with letters is still a name
+        assert_eq!(token("_1st").kind, TokenKind::Identifier);
+    }
+
+    #[test]
+    fn test_misplaced_digit_separators() {
+        let invalid = |source: &str| {
+            let token = Lexer::new(source.to_string()).next_token();
+            match token.kind {
+                TokenKind::InvalidNumber(message) => (message, token.lexeme),
+                other => panic!("expected an invalid number for {}, got {:?}", source, other),
+            }
+        };
+
+        assert_eq!(
+            invalid("_100;"),
+            ("numeric literal `_100` can't start with an underscore; underscores go between digits".to_string(), "_100".to_string())
+        );
+        assert_eq!(invalid("100_;").0, "numeric literal `100_` can't end with an underscore; underscores go between digits");
+        assert_eq!(invalid("1_.5").0, "numeric literal `1_.5` has an underscore next to the decimal point");
+        assert_eq!(invalid("0x_FF").0, "numeric literal `0x_FF` has an underscore outside its digits; underscores go between digits");
+
+        let mut lexer = Lexer::new("let x = 100_;".to_string());
+        let error = crate::parser::Parser::new(&mut lexer).parse_program().expect_err("bad literal");
+        assert!(error.to_string().contains("can't end with an underscore"), "{}", error);
+    }
+
+    #[test]
+    fn test_char_literals() {
+        let kinds = |source: &str| {
+            let mut lexer = Lexer::new(source.to_string());
+            let mut kinds = Vec::new();
+            loop {
+                let token = lexer.next_token();
+                if token.kind == TokenKind::Eof {
+                    return kinds;
+                }
+                kinds.push(token.kind);
+            }
+        };
+
+        assert_eq!(kinds("'a'"), vec![TokenKind::Char('a')]);
+        assert_eq!(kinds("'1' ' '"), vec![TokenKind::Char('1'), TokenKind::Char(' ')]);
+        assert_eq!(
+            kinds(r"'\n' '\t' '\\' '\'' '\u{1F600}'"),
+            vec![TokenKind::Char('\n'), TokenKind::Char('\t'), TokenKind::Char('\\'), TokenKind::Char('\''), TokenKind::Char('😀')]
+        );
+
+        // Without a closing quote it's still a lifetime
+        assert_eq!(kinds("&'static str"), vec![TokenKind::Ampersand, TokenKind::Lifetime("static".to_string()), TokenKind::Identifier]);
+        assert_eq!(kinds("<'a>"), vec![TokenKind::LAngle, TokenKind::Lifetime("a".to_string()), TokenKind::RAngle]);
+    }
+
+    #[test]
+    fn test_malformed_char_literals() {
+        let invalid = |source: &str| match Lexer::new(source.to_string()).next_token().kind {
+            TokenKind::InvalidChar(message) => message,
+            other => panic!("expected an invalid char for {}, got {:?}", source, other),
+        };
+
+        assert_eq!(invalid("'1"), "unterminated char literal `'1`; close it with '");
+        assert_eq!(invalid(r"'\n"), r"unterminated char literal `'\n`; close it with '");
+        assert_eq!(invalid("''"), "empty char literal `''`; a char holds exactly one character");
+        assert_eq!(invalid("'ab'"), "char literal `'ab'` holds more than one character; use a string: \"ab\"");
+        assert_eq!(invalid(r"'\q'"), r"unknown escape `\q`");
+        assert_eq!(invalid(r"'\u{110000}'"), r"`\u{110000}` is not a unicode character");
+
+        let mut lexer = Lexer::new("let c = 'ab';".to_string());
+        let error = crate::parser::Parser::new(&mut lexer).parse_program().expect_err("bad char");
+        assert!(error.to_string().contains("holds more than one character"), "{}", error);
+    }
+
+    #[test]
+    fn test_raw_strings() {
+        let token = |source: &str| Lexer::new(source.to_string()).next_token();
+
+        let regex = token(r#"r"\d+\.\d*""#);
+        assert_eq!(regex.kind, TokenKind::String(r"\d+\.\d*".to_string()));
+        assert_eq!(regex.lexeme, r"\d+\.\d*");
+        assert_eq!(token(r#"r"C:\Users\n""#).kind, TokenKind::String(r"C:\Users\n".to_string()));
+
+        // Hashes let the contents hold quotes, and fewer hashes than opened don't close it
+        let quoted = token(r###"r#"say "hi""#"###);
+        assert_eq!(quoted.kind, TokenKind::String(r#"say "hi""#.to_string()));
+        assert_eq!(token(r###"r##"a "# b"##"###).kind, TokenKind::String(r##"a "# b"##.to_string()));
+
+        // Spanning lines, the token is on the line it starts on
+        let mut lexer = Lexer::new("let s = r\"one\ntwo\";\nlet t = 1;".to_string());
+        let tokens: Vec<Token> = std::iter::from_fn(|| Some(lexer.next_token())).take(8).collect();
+        assert_eq!(tokens[3].kind, TokenKind::String("one\ntwo".to_string()));
+        assert_eq!(tokens[3].line, 1);
+        assert_eq!(tokens[4].kind, TokenKind::Semicolon);
+
+        // `r` on its own is still a name
+        assert_eq!(token("r + 1").kind, TokenKind::Identifier);
+    }
+
+    #[test]
+    fn test_malformed_raw_strings() {
+        let invalid = |source: &str| match Lexer::new(source.to_string()).next_token().kind {
+            TokenKind::InvalidString(message) => message,
+            other => panic!("expected an invalid string for {}, got {:?}", source, other),
+        };
+
+        assert_eq!(
+            invalid("r\"never closed\n"),
+            "unterminated raw string starting on line 1; close it with `\"`"
+        );
+        assert_eq!(
+            invalid("r##\"text\n\"#\n"),
+            "unterminated raw string starting on line 1; close it with `\"##` (the `\"#` on line 2 has 1 `#` where the opening `r##\"` has 2)"
+        );
+        assert_eq!(invalid(r###"r#"text"##"###), "raw string starting on line 1 opens with 1 `#` but closes with 2");
+
+        let mut lexer = Lexer::new("fn f() {}\nlet s = r#\"oops\";".to_string());
+        let error = crate::parser::Parser::new(&mut lexer).parse_program().expect_err("unterminated");
+        assert!(error.to_string().contains("unterminated raw string starting on line 2"), "{}", error);
+    }
+
+    #[test]
+    fn test_byte_strings() {
+        let token = |source: &str| Lexer::new(source.to_string()).next_token();
+
+        let bytes = token(r#"b"GET \x00\xFF\n\"\\""#);
+        assert_eq!(bytes.kind, TokenKind::ByteString(b"GET \x00\xFF\n\"\\".to_vec()));
+        assert_eq!(bytes.lexeme, r#"b"GET \x00\xFF\n\"\\""#);
+        assert_eq!(token(r#"b"""#).kind, TokenKind::ByteString(Vec::new()));
+
+        assert_eq!(token("b'a'").kind, TokenKind::Byte(b'a'));
+        assert_eq!(token(r"b'\x7f'").kind, TokenKind::Byte(0x7f));
+        assert_eq!(token(r"b'\''").kind, TokenKind::Byte(b'\''));
+
+        // `b` on its own is still a name
+        assert_eq!(token("b + 1").kind, TokenKind::Identifier);
+
+        let mut lexer = Lexer::new("let header = b\"PK\";".to_string());
+        let program = crate::parser::Parser::new(&mut lexer).parse_program().expect("byte string");
+        assert!(format!("{:?}", program).contains("IntegerLiteral(80), IntegerLiteral(75)"));
+    }
+
+    #[test]
+    fn test_malformed_byte_strings() {
+        let invalid = |source: &str| match Lexer::new(source.to_string()).next_token().kind {
+            TokenKind::InvalidString(message) | TokenKind::InvalidChar(message) => message,
+            other => panic!("expected an invalid byte string for {}, got {:?}", source, other),
+        };
+
+        assert_eq!(
+            invalid("b\"caf\u{e9}\""),
+            "non-ASCII character '\u{e9}' in byte string at line 1, column 6; write its bytes as \\x escapes"
+        );
+        assert_eq!(invalid(r#"b"\xG0""#), r"`\x` in byte string at line 1, column 3 needs two hex digits, like \x7F");
+        assert!(invalid(r#"b"\u{41}""#).starts_with(r"unknown escape `\u` in byte string at line 1, column 3"));
+        assert_eq!(invalid("b\"open\n"), "unterminated byte string starting on line 1; close it with \"");
+        assert_eq!(invalid("b''"), "empty byte literal `b''`; a byte literal holds exactly one character");
+        assert_eq!(invalid("b'ab'"), "byte literal `b'ab'` holds more than one character; use a byte string: b\"ab\"");
+        assert_eq!(invalid("b'a"), "unterminated byte literal `b'a`; close it with '");
+        assert!(invalid("b'\u{e9}'").starts_with("non-ASCII character '\u{e9}' in byte literal at line 1"));
+
+        let mut lexer = Lexer::new("fn f() {}\nlet s = b\"\u{e9}\";".to_string());
+        let error = crate::parser::Parser::new(&mut lexer).parse_program().expect_err("non-ASCII");
+        assert!(error.to_string().contains("in byte string at line 2"), "{}", error);
+    }
+
+    #[test]
+    fn test_number_suffixes() {
+        let kinds = |source: &str| {
+            let mut lexer = Lexer::new(source.to_string());
+            std::iter::from_fn(|| Some(lexer.next_token().kind)).take_while(|kind| *kind != TokenKind::Eof).collect::<Vec<_>>()
+        };
+        let typed = |digits: &str, suffix| TokenKind::TypedNumber(digits.to_string(), suffix);
+
+        assert_eq!(kinds("42u8"), vec![typed("42", NumberSuffix::U8)]);
+        assert_eq!(kinds("1_000i64 3.0f32 2f64"), vec![
+            typed("1000", NumberSuffix::I64),
+            typed("3.0", NumberSuffix::F32),
+            typed("2", NumberSuffix::F64),
+        ]);
+        assert_eq!(kinds("0xFFu8 0b1010i32"), vec![typed("255", NumberSuffix::U8), typed("10", NumberSuffix::I32)]);
+        // In hex, f32 is digits rather than a suffix
+        assert_eq!(kinds("0x1f32"), vec![TokenKind::Integer(0x1f32)]);
+
+        // Only a whole suffix glued to the digits counts
+        assert_eq!(kinds("1 f32"), vec![TokenKind::Integer(1), TokenKind::Identifier]);
+        assert_eq!(kinds("1f32x"), vec![TokenKind::Integer(1), TokenKind::Identifier]);
+
+        // A method call on an integer is not a float
+        assert_eq!(
+            kinds("1.max(2)"),
+            vec![TokenKind::Integer(1), TokenKind::Dot, TokenKind::Identifier, TokenKind::LParen, TokenKind::Integer(2), TokenKind::RParen]
+        );
+        assert_eq!(kinds("1u8.max(2)")[..2], [typed("1", NumberSuffix::U8), TokenKind::Dot]);
+
+        let mut lexer = Lexer::new("let x = 3.5f32 + 2u8;".to_string());
+        let program = crate::parser::Parser::new(&mut lexer).parse_program().expect("suffixed numbers");
+        let debug = format!("{:?}", program);
+        assert!(debug.contains("FloatLiteral(\"3.5\")") && debug.contains("IntegerLiteral(2)"), "{}", debug);
+    }
+
+    #[test]
+    fn test_leading_and_trailing_dot_floats() {
+        let kinds = |source: &str| {
+            let mut lexer = Lexer::new(source.to_string());
+            std::iter::from_fn(|| Some(lexer.next_token().kind)).take_while(|kind| *kind != TokenKind::Eof).collect::<Vec<_>>()
+        };
+        let float = |digits: &str| TokenKind::Float(digits.to_string());
+
+        assert_eq!(kinds("(.5)"), vec![TokenKind::LParen, float("0.5"), TokenKind::RParen]);
+        assert_eq!(kinds("margin(.5)")[2], float("0.5"));
+        assert_eq!(kinds("-.25 * .5f32")[1..], [float("0.25"), TokenKind::Star, TokenKind::TypedNumber("0.5".to_string(), NumberSuffix::F32)]);
+        assert_eq!(kinds("[1., 2.]"), vec![TokenKind::LBracket, float("1.0"), TokenKind::Comma, float("2.0"), TokenKind::RBracket]);
+
+        // After an operand the dot is member access
+        assert_eq!(kinds("x.5"), vec![TokenKind::Identifier, TokenKind::Dot, TokenKind::Integer(5)]);
+        assert_eq!(kinds("pair().1")[3..], [TokenKind::Dot, TokenKind::Integer(1)]);
+
+        // Ranges and method calls on integers
+        assert_eq!(kinds("1..5"), vec![TokenKind::Integer(1), TokenKind::DotDot, TokenKind::Integer(5)]);
+        assert_eq!(kinds("1..=5"), vec![TokenKind::Integer(1), TokenKind::DotDotEq, TokenKind::Integer(5)]);
+        assert_eq!(
+            kinds("1.foo()"),
+            vec![TokenKind::Integer(1), TokenKind::Dot, TokenKind::Identifier, TokenKind::LParen, TokenKind::RParen]
+        );
+        assert_eq!(kinds("1._x")[..2], [TokenKind::Integer(1), TokenKind::Dot]);
+    }
+
+    #[test]
+    fn test_compound_assignment_tokens() {
+        let kinds = |source: &str| {
+            let mut lexer = Lexer::new(source.to_string());
+            std::iter::from_fn(|| Some(lexer.next_token().kind)).take_while(|kind| *kind != TokenKind::Eof).collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            kinds("a += 1 -= *= /= %="),
+            vec![
+                TokenKind::Identifier, TokenKind::PlusAssign, TokenKind::Integer(1),
+                TokenKind::MinusAssign, TokenKind::StarAssign, TokenKind::SlashAssign, TokenKind::PercentAssign,
+            ]
+        );
+        assert_eq!(
+            kinds("&= |= ^= <<= >>= << >> ^"),
+            vec![
+                TokenKind::AmpAssign, TokenKind::PipeAssign, TokenKind::CaretAssign, TokenKind::LeftShiftAssign,
+                TokenKind::RightShiftAssign, TokenKind::LeftShift, TokenKind::RightShift, TokenKind::Caret,
+            ]
+        );
+        assert_eq!(kinds("a + = b")[1..3], [TokenKind::Plus, TokenKind::Assign]);
+        assert_eq!(kinds("a ??= b")[1], TokenKind::QuestionQuestionAssign);
+        assert_eq!(kinds("x -> y")[1], TokenKind::Arrow);
+
+        // In a JSX tag `/>` still closes it
+        let mut lexer = Lexer::new("<br/>".to_string());
+        lexer.enter_jsx_mode();
+        let tokens: Vec<TokenKind> = std::iter::from_fn(|| Some(lexer.next_token().kind)).take(3).collect();
+        assert_eq!(tokens, vec![TokenKind::LAngle, TokenKind::Identifier, TokenKind::JsxSelfClose]);
+    }
+
+    #[test]
+    fn test_question_operators() {
+        let kinds = |source: &str| {
+            let mut lexer = Lexer::new(source.to_string());
+            std::iter::from_fn(|| Some(lexer.next_token().kind)).take_while(|kind| *kind != TokenKind::Eof).collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            kinds("user?.name ?? x"),
+            vec![TokenKind::Identifier, TokenKind::QuestionDot, TokenKind::Identifier, TokenKind::QuestionQuestion, TokenKind::Identifier]
+        );
+
+        // A lone `?` still propagates errors
+        assert_eq!(kinds("foo()?;")[3..], [TokenKind::Question, TokenKind::Semicolon]);
+
+        // Before a float, `?` is a ternary and the dot belongs to the number
+        assert_eq!(kinds("x ?.5 : 1")[1..3], [TokenKind::Question, TokenKind::Float("0.5".to_string())]);
+        assert_eq!(kinds("x? .5 : 1")[1..3], [TokenKind::Question, TokenKind::Float("0.5".to_string())]);
+        assert_eq!(kinds("x?..y")[1..3], [TokenKind::Question, TokenKind::DotDot]);
+    }
+
+    #[test]
+    fn test_invalid_number_suffixes() {
+        let invalid = |source: &str| match Lexer::new(source.to_string()).next_token().kind {
+            TokenKind::InvalidNumber(message) => message,
+            other => panic!("expected an invalid number for {}, got {:?}", source, other),
+        };
+
+        assert_eq!(invalid("256u8"), "`256u8` doesn't fit in u8, which goes up to 255");
+        assert_eq!(invalid("0x1_0000u16"), "`0x1_0000u16` doesn't fit in u16, which goes up to 65535");
+        assert_eq!(invalid("18446744073709551615u64"), "`18446744073709551615u64` is too large for an integer");
+        assert_eq!(invalid("3.5u8"), "float literal `3.5u8` can't have the integer suffix u8; use f32 or f64");
+        assert_eq!(invalid("1_u8"), "numeric literal `1_` can't end with an underscore; underscores go between digits");
+    }
+
+    #[test]
+    fn test_template_tokens() {
+        let kinds = |source: &str| {
+            let mut lexer = Lexer::new(source.to_string());
+            std::iter::from_fn(|| Some(lexer.next_token().kind)).take_while(|kind| *kind != TokenKind::Eof).collect::<Vec<_>>()
+        };
+        let chunk = |text: &str| TokenKind::TemplateChunk(text.to_string());
+
+        assert_eq!(
+            kinds("`Hello, ${name}!`"),
+            vec![
+                TokenKind::TemplateStart,
+                chunk("Hello, "),
+                TokenKind::TemplateInterpolationStart,
+                TokenKind::Identifier,
+                TokenKind::TemplateInterpolationEnd,
+                chunk("!"),
+                TokenKind::TemplateEnd,
+            ]
+        );
+        // Escaped `\$` and backtick are text; a lone `$` or `{` is too
+        assert_eq!(kinds(r"`cost: \${x} \` $5 {a}`"), vec![TokenKind::TemplateStart, chunk("cost: ${x} ` $5 {a}"), TokenKind::TemplateEnd]);
+        // Braces inside the expression don't close it, and templates nest
+        assert_eq!(
+            kinds("`a ${f({ x: `b ${c}` })}`"),
+            vec![
+                TokenKind::TemplateStart,
+                chunk("a "),
+                TokenKind::TemplateInterpolationStart,
+                TokenKind::Identifier,
+                TokenKind::LParen,
+                TokenKind::LBrace,
+                TokenKind::Identifier,
+                TokenKind::Colon,
+                TokenKind::TemplateStart,
+                chunk("b "),
+                TokenKind::TemplateInterpolationStart,
+                TokenKind::Identifier,
+                TokenKind::TemplateInterpolationEnd,
+                TokenKind::TemplateEnd,
+                TokenKind::RBrace,
+                TokenKind::RParen,
+                TokenKind::TemplateInterpolationEnd,
+                TokenKind::TemplateEnd,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_template_inside_jsx_expression() {
+        // The `}` closing `${` must not close the JSX expression
+        let mut lexer = Lexer::new("<p>{`Hi ${name}`} there</p>".to_string());
+        lexer.enter_jsx_mode();
+        let kinds: Vec<TokenKind> = std::iter::from_fn(|| Some(lexer.next_token().kind)).take(12).collect();
+        let close = kinds.iter().position(|kind| *kind == TokenKind::JsxCloseBrace).expect("JSX expression closes");
+        assert_eq!(kinds[close - 1], TokenKind::TemplateEnd);
+        assert_eq!(kinds[close - 2], TokenKind::TemplateInterpolationEnd);
+        assert_eq!(kinds[close + 1], TokenKind::JsxText(" there".to_string()));
+    }
+
+    /// Each token with the source text its span covers
+    fn spanned(source: &str, jsx: bool) -> Vec<(TokenKind, &str)> {
+        let mut lexer = Lexer::new(source.to_string());
+        if jsx {
+            lexer.enter_jsx_mode();
+        }
+        std::iter::from_fn(|| Some(lexer.next_token()))
+            .take_while(|token| token.kind != TokenKind::Eof)
+            .map(|token| (token.kind, &source[token.span.start..token.span.end]))
+            .collect()
+    }
+
+    #[test]
+    fn test_token_spans() {
+        let source = "if a == b => c ..= d";
+        let texts: Vec<&str> = spanned(source, false).into_iter().map(|(_, text)| text).collect();
+        assert_eq!(texts, vec!["if", "a", "==", "b", "=>", "c", "..=", "d"]);
+
+        // Offsets are in bytes, so they stay right after multi-byte characters
+        let source = "let café = \"naïve\nsecond line\";\n/* ünïcode */ x >= 1";
+        let spans = spanned(source, false);
+        assert_eq!(spans[1].1, "café");
+        assert_eq!(spans[3], (TokenKind::String("naïve\nsecond line".to_string()), "\"naïve\nsecond line\""));
+        assert_eq!(spans[4].1, ";");
+        assert_eq!(&spans[5..], &[
+            (TokenKind::Identifier, "x"),
+            (TokenKind::GtEq, ">="),
+            (TokenKind::Integer(1), "1"),
+        ]);
+
+        let source = "<img src={url} />";
+        let tags: Vec<&str> = spanned(source, true).into_iter().map(|(_, text)| text).collect();
+        assert_eq!(tags, vec!["<", "img", "src", "=", "{", "url", "}", "/>"]);
+    }
+
+    #[test]
+    fn test_token_positions() {
+        let positions = |source: &str| -> Vec<(String, usize, usize)> {
+            let mut lexer = Lexer::new(source.to_string());
+            std::iter::from_fn(|| Some(lexer.next_token()))
+                .take_while(|token| token.kind != TokenKind::Eof)
+                .map(|token| (token.lexeme, token.line, token.column))
+                .collect()
+        };
+        let at = |lexeme: &str, line: usize, column: usize| (lexeme.to_string(), line, column);
+
+        // The first char of a line is column 1, and a tab is one column
+        assert_eq!(positions("let a = 1;\nb\n\tc == d"), vec![
+            at("let", 1, 1), at("a", 1, 5), at("=", 1, 7), at("1", 1, 9), at(";", 1, 10),
+            at("b", 2, 1),
+            at("c", 3, 2), at("==", 3, 4), at("d", 3, 7),
+        ]);
+
+        // Tokens spanning newlines sit where they start
+        assert_eq!(positions("  x = \"one\ntwo\" + `a\nb`;\ny"), vec![
+            at("x", 1, 3), at("=", 1, 5), at("one\ntwo", 1, 7), at("+", 2, 6),
+            at("`", 2, 8), at("a\nb", 2, 9), at("`", 3, 2), at(";", 3, 3),
+            at("y", 4, 1),
+        ]);
+        assert_eq!(positions("/* a\n comment */ z\n/// doc\nw"), vec![
+            at("z", 2, 13), at("/// doc", 3, 1), at("w", 4, 1),
+        ]);
+    }
+
+    #[test]
+    fn test_peeking() {
+        let mut lexer = Lexer::new("let x = 1;".to_string());
+        assert_eq!(lexer.peek_token().kind, TokenKind::Let);
+        assert_eq!(lexer.peek_token().kind, TokenKind::Let, "peeking twice gives the same token");
+        assert_eq!(lexer.peek_nth(2).kind, TokenKind::Assign);
+        assert_eq!(lexer.next_token().kind, TokenKind::Let);
+        assert_eq!(lexer.peek_token().lexeme, "x");
+        let x = lexer.next_token();
+        assert_eq!((x.lexeme.as_str(), x.column, x.span.start), ("x", 5, 4));
+        assert_eq!(lexer.next_token().kind, TokenKind::Assign, "already peeked by peek_nth");
+        assert_eq!(lexer.next_token().kind, TokenKind::Integer(1));
+
+        // Tokens peeked before a mode switch are read again in the new mode
+        let mut lexer = Lexer::new("<p>hello world</p>".to_string());
+        assert_eq!(lexer.next_token().kind, TokenKind::LAngle);
+        assert_eq!(lexer.peek_nth(2).kind, TokenKind::Identifier, "`hello` outside JSX");
+        lexer.enter_jsx_mode();
+        assert!(lexer.is_jsx_mode());
+        assert_eq!(lexer.next_token().kind, TokenKind::Identifier);
+        assert_eq!(lexer.next_token().kind, TokenKind::RAngle);
+        assert_eq!(lexer.peek_token().kind, TokenKind::JsxText("hello world".to_string()));
+        assert_eq!(lexer.next_token().kind, TokenKind::JsxText("hello world".to_string()));
+    }
+
+    #[test]
+    fn test_unicode_identifiers() {
+        let lexemes = |source: &str| -> Vec<(TokenKind, String)> {
+            let mut lexer = Lexer::new(source.to_string());
+            std::iter::from_fn(|| Some(lexer.next_token()))
+                .take_while(|token| token.kind != TokenKind::Eof)
+                .map(|token| (token.kind, token.lexeme))
+                .collect()
+        };
+        let name = |lexeme: &str| (TokenKind::Identifier, lexeme.to_string());
+
+        assert_eq!(lexemes("αβγ λ_1 Δx"), vec![name("αβγ"), name("λ_1"), name("Δx")]);
+        assert_eq!(lexemes("变量 名前2 데이터"), vec![name("变量"), name("名前2"), name("데이터")]);
+        assert_eq!(lexemes("user_имя café·x _"), vec![name("user_имя"), name("café·x"), name("_")]);
+
+        // Keywords are exact ASCII: look-alikes and other cases are names
+        assert_eq!(lexemes("let ｌｅｔ Let lеt")[0].0, TokenKind::Let);
+        assert_eq!(&lexemes("let ｌｅｔ Let lеt")[1..], &[name("ｌｅｔ"), name("Let"), name("lеt")]);
+
+        // A digit from another script can continue a name but not start one
+        assert_eq!(lexemes("x٣")[0], name("x٣"));
+        assert!(matches!(lexemes("٣x")[0].0, TokenKind::Illegal('٣')));
+    }
+
+    #[test]
+    fn test_raw_identifiers() {
+        let mut lexer = Lexer::new("r#match r#in.r#use r#plain r\"s\" r#\"raw\"# r".to_string());
+        let tokens: Vec<Token> = std::iter::from_fn(|| Some(lexer.next_token())).take_while(|token| token.kind != TokenKind::Eof).collect();
+        let kinds: Vec<(TokenKind, &str)> = tokens.iter().map(|token| (token.kind.clone(), token.lexeme.as_str())).collect();
+        assert_eq!(kinds, vec![
+            (TokenKind::Identifier, "match"),
+            (TokenKind::Identifier, "in"),
+            (TokenKind::Dot, "."),
+            (TokenKind::Identifier, "use"),
+            (TokenKind::Identifier, "plain"),
+            (TokenKind::String("s".to_string()), "s"),
+            (TokenKind::String("raw".to_string()), "raw"),
+            (TokenKind::Identifier, "r"),
+        ]);
+        assert_eq!((tokens[0].span.start, tokens[0].span.end), (0, 7), "the span covers the `r#`");
+
+        for source in ["r#", "r# x", "r#1", "r#+"] {
+            let mut lexer = Lexer::new(source.to_string());
+            assert_eq!(
+                lexer.next_token().kind,
+                TokenKind::InvalidIdentifier("`r#` needs a name right after it, like `r#match`".to_string()),
+                "{}",
+                source
+            );
+            assert_eq!(lexer.errors()[0].kind, LexErrorKind::EmptyRawIdentifier);
+        }
+    }
+
+    #[test]
+    fn test_self_keywords() {
+        let mut lexer = Lexer::new("self Self selfish Selfie r#self".to_string());
+        let tokens: Vec<Token> = std::iter::from_fn(|| Some(lexer.next_token())).take_while(|token| token.kind != TokenKind::Eof).collect();
+        let kinds: Vec<(TokenKind, &str)> = tokens.iter().map(|token| (token.kind.clone(), token.lexeme.as_str())).collect();
+        assert_eq!(kinds, vec![
+            (TokenKind::SelfValue, "self"),
+            (TokenKind::SelfType, "Self"),
+            (TokenKind::Identifier, "selfish"),
+            (TokenKind::Identifier, "Selfie"),
+            (TokenKind::Identifier, "self"),
+        ]);
+    }
+
+    #[test]
+    fn test_zero_width_characters_are_rejected() {
+        let mut lexer = Lexer::new("let a\u{200D}b = 1;".to_string());
+        assert_eq!(lexer.next_token().kind, TokenKind::Let);
+        let token = lexer.next_token();
+        assert_eq!(
+            token.kind,
+            TokenKind::InvalidIdentifier(
+                "name `a\\u{200d}b` contains an invisible character U+200D (zero width joiner); delete it".to_string()
+            )
+        );
+        assert_eq!(lexer.next_token().kind, TokenKind::Assign, "the whole name is one token");
+        assert_eq!(lexer.errors()[0].kind, LexErrorKind::InvisibleCharacter('\u{200D}'));
+
+        let mut lexer = Lexer::new("x\u{200B}".to_string());
+        while lexer.next_token().kind != TokenKind::Eof {}
+        assert_eq!(lexer.errors()[0].message, "name `x\\u{200b}` contains an invisible character U+200B (zero width space); delete it");
+        let mut lexer = Lexer::new("x = \u{2060}1".to_string());
+        while lexer.next_token().kind != TokenKind::Eof {}
+        assert_eq!(lexer.errors()[0].message, "invisible character U+2060 (word joiner); delete it");
+
+        let mut lexer = Lexer::new("let total\u{200C} = 1;".to_string());
+        let error = crate::parser::Parser::new(&mut lexer).parse_program().expect_err("invisible character");
+        assert!(error.to_string().contains("U+200C (zero width non-joiner)"), "{}", error);
+    }
+
+    #[test]
+    fn test_lex_errors_are_collected() {
+        let source = "let a = 1 ~ 2;\nlet s = \"open\nlet n = 0x;";
+        let mut lexer = Lexer::new(source.to_string());
+        while lexer.next_token().kind != TokenKind::Eof {}
+        let kinds: Vec<LexErrorKind> = lexer.errors().iter().map(|error| error.kind.clone()).collect();
+        assert_eq!(kinds, vec![LexErrorKind::UnexpectedCharacter('~'), LexErrorKind::UnterminatedString]);
+
+        let unexpected = &lexer.errors()[0];
+        assert_eq!((unexpected.line, unexpected.column), (1, 11));
+        assert_eq!(unexpected.caret(), "let a = 1 ~ 2;\n          ^");
+        let unterminated = &lexer.errors()[1];
+        assert_eq!(unterminated.message, "unterminated string starting on line 2; close it with \"");
+        assert_eq!(unterminated.snippet, "let s = \"open");
+
+        let mut lexer = Lexer::new("let c = '\\q'; let n = 0b12; /* never closed".to_string());
+        while lexer.next_token().kind != TokenKind::Eof {}
+        let kinds: Vec<LexErrorKind> = lexer.errors().iter().map(|error| error.kind.clone()).collect();
+        assert_eq!(kinds, vec![LexErrorKind::InvalidEscape, LexErrorKind::InvalidNumber, LexErrorKind::UnterminatedBlockComment]);
+    }
+
+    #[test]
+    fn test_string_invalid_escape() {
+        let mut lexer = Lexer::new("let s = \"a\\qb\\zc\"; let t = \"\\x1b[0m\\u{e9}\";".to_string());
+        let tokens: Vec<Token> = std::iter::from_fn(|| Some(lexer.next_token())).take_while(|token| token.kind != TokenKind::Eof).collect();
+        // Reported once, at the backslash, and the string is still read to its closing quote
+        assert_eq!(tokens[3].kind, TokenKind::InvalidString("unknown escape `\\q` in string".to_string()));
+        assert_eq!(tokens[4].kind, TokenKind::Semicolon);
+        assert_eq!(tokens[8].kind, TokenKind::String("\u{1b}[0m\u{e9}".to_string()));
+        let [error] = lexer.errors() else { panic!("got {:?}", lexer.errors()) };
+        assert_eq!((error.kind.clone(), error.line, error.column), (LexErrorKind::InvalidEscape, 1, 11));
+        assert_eq!(error.caret(), "let s = \"a\\qb\\zc\"; let t = \"\\x1b[0m\\u{e9}\";\n          ^");
+
+        let mut lexer = Lexer::new("\"\\x80\"".to_string());
+        assert_eq!(lexer.next_token().kind, TokenKind::InvalidString("`\\x` needs two hex digits up to 7F, like \\x1b in string".to_string()));
+    }
+
+    #[test]
+    fn test_try_next_token_fails_on_errors() {
+        let mut lexer = Lexer::new("x /* open".to_string());
+        assert_eq!(lexer.try_next_token().map(|token| token.kind), Ok(TokenKind::Identifier));
+        let error = lexer.try_next_token().expect_err("comment never closes");
+        assert_eq!(error.kind, LexErrorKind::UnterminatedBlockComment);
+        assert_eq!(error.to_string(), "1:3: unterminated block comment starting on line 1; close it with */");
+
+        // Leniently, the parser still reports it
+        let mut lexer = Lexer::new("let x = 1;\n/* trailing".to_string());
+        let error = crate::parser::Parser::new(&mut lexer).parse_program().expect_err("comment never closes");
+        assert!(error.to_string().contains("unterminated block comment starting on line 2"), "{}", error);
+    }
+
+    #[test]
+    fn test_unterminated_template() {
+        let mut lexer = Lexer::new("let s = `open ${x} forever;\n".to_string());
+        let kinds: Vec<TokenKind> = std::iter::from_fn(|| Some(lexer.next_token().kind)).take_while(|kind| *kind != TokenKind::Eof).collect();
+        assert!(
+            kinds.contains(&TokenKind::InvalidString("unterminated template string starting on line 1; close it with `".to_string())),
+            "{:?}",
+            kinds
+        );
+        assert_eq!(kinds.last(), kinds.iter().find(|kind| matches!(kind, TokenKind::InvalidString(_))), "nothing after the error");
+
+        let mut lexer = Lexer::new("let s = `${}`;".to_string());
+        let error = crate::parser::Parser::new(&mut lexer).parse_program().expect_err("empty interpolation");
+        assert!(error.to_string().contains("Empty `${}`"), "{}", error);
+    }
+
+    #[test]
+    fn test_comments_kept_on_request() {
+        let source = "let a = 1; // one\n/* two\n lines */ let b = a;";
+        let kinds = |lexer: &mut Lexer| {
+            std::iter::from_fn(|| Some(lexer.next_token())).take_while(|token| token.kind != TokenKind::Eof).collect::<Vec<_>>()
+        };
+
+        // By default comments are skipped, as the parser expects
+        let skipped = kinds(&mut Lexer::new(source.to_string()));
+        assert!(skipped.iter().all(|token| !matches!(token.kind, TokenKind::LineComment(_) | TokenKind::BlockComment(_))));
+
+        let tokens = kinds(&mut Lexer::new(source.to_string()).with_comments(true));
+        let line = &tokens[5];
+        assert_eq!(line.kind, TokenKind::LineComment(" one".to_string()));
+        assert_eq!((line.lexeme.as_str(), line.line), ("// one", 1));
+        let block = &tokens[6];
+        assert_eq!(block.kind, TokenKind::BlockComment(" two\n lines ".to_string()));
+        assert_eq!(block.line, 2);
+        assert_eq!(tokens[7].kind, TokenKind::Let);
+        assert_eq!(tokens[7].line, 3);
+        assert_eq!(tokens.len(), skipped.len() + 2);
+
+        // A comment after an operand doesn't turn `.5` into a float
+        let tokens = kinds(&mut Lexer::new("x /* c */.5".to_string()).with_comments(true));
+        assert_eq!(tokens[2].kind, TokenKind::Dot);
+    }
+
+    #[test]
+    fn test_doc_comments() {
+        let source = "//! The module\n/// Adds.\n///\n/// Twice.\n//// not a doc\n// nor this\nfn add() {}";
+        let mut lexer = Lexer::new(source.to_string());
+        let tokens: Vec<Token> = std::iter::from_fn(|| Some(lexer.next_token())).take_while(|token| token.kind != TokenKind::Eof).collect();
+        let doc = |inner: bool, text: &str| TokenKind::DocComment { inner, text: text.to_string() };
+
+        // One token per line, so blank doc lines survive; plain comments are skipped
+        assert_eq!(tokens.iter().map(|token| token.kind.clone()).take(5).collect::<Vec<_>>(), vec![
+            doc(true, " The module"),
+            doc(false, " Adds."),
+            doc(false, ""),
+            doc(false, " Twice."),
+            TokenKind::Fn,
+        ]);
+        assert_eq!((tokens[3].line, tokens[3].lexeme.as_str()), (4, "/// Twice."));
+
+        // With comments kept, `////` is an ordinary line comment
+        let mut lexer = Lexer::new("/// doc\n//// plain".to_string()).with_comments(true);
+        assert_eq!(lexer.next_token().kind, doc(false, " doc"));
+        assert_eq!(lexer.next_token().kind, TokenKind::LineComment("// plain".to_string()));
+    }
+
+    #[test]
+    fn test_comments_between_jsx_attributes() {
+        let mut lexer = Lexer::new("<input /* name */ value={a} // bound\n />".to_string()).with_comments(true);
+        lexer.enter_jsx_mode();
+        let kinds: Vec<TokenKind> = std::iter::from_fn(|| Some(lexer.next_token().kind)).take(10).collect();
+        assert_eq!(kinds[..4], [
+            TokenKind::LAngle,
+            TokenKind::Identifier,
+            TokenKind::BlockComment(" name ".to_string()),
+            TokenKind::Identifier,
+        ]);
+        assert!(kinds.contains(&TokenKind::LineComment(" bound".to_string())), "{:?}", kinds);
+        assert!(kinds.contains(&TokenKind::JsxSelfClose), "{:?}", kinds);
+    }
+
+    #[test]
+    fn test_jsx_comment_braces() {
+        let kinds = |lexer: &mut Lexer| {
+            lexer.enter_jsx_mode();
+            std::iter::from_fn(|| Some(lexer.next_token().kind)).take_while(|kind| *kind != TokenKind::Eof).collect::<Vec<_>>()
+        };
+        let source = "a {/* note */} b {/* x */ y}";
+        assert_eq!(kinds(&mut Lexer::new(source.to_string())), vec![
+            TokenKind::JsxText("a ".to_string()),
+            TokenKind::JsxText(" b ".to_string()),
+            TokenKind::JsxOpenBrace,
+            TokenKind::Identifier,
+            TokenKind::JsxCloseBrace,
+        ]);
+        assert_eq!(kinds(&mut Lexer::new(source.to_string()).with_comments(true)), vec![
+            TokenKind::JsxText("a ".to_string()),
+            TokenKind::BlockComment(" note ".to_string()),
+            TokenKind::JsxText(" b ".to_string()),
+            TokenKind::JsxOpenBrace,
+            TokenKind::BlockComment(" x ".to_string()),
+            TokenKind::Identifier,
+            TokenKind::JsxCloseBrace,
+        ]);
+    }
+
+    #[test]
+    fn test_comments_inside_css_block() {
+        let mut lexer = Lexer::new("css! {\n    /* primary */\n    .button { color: blue; }\n}".to_string()).with_comments(true);
+        assert_eq!(lexer.next_token().kind, TokenKind::CssMacro);
+        assert_eq!(lexer.next_token().kind, TokenKind::LBrace);
+        lexer.enter_css_mode();
+
+        let comment = lexer.next_token();
+        assert_eq!(comment.kind, TokenKind::BlockComment(" primary ".to_string()));
+        assert_eq!(comment.line, 2);
+        assert!(matches!(lexer.next_token().kind, TokenKind::CssSelector(ref selector) if selector == ".button"));
+
+        // A comment ends the value it trails
+        let mut lexer = Lexer::new_in_mode("color: red /* temp */; // next\n".to_string(), LexerStartMode::Css).with_comments(true);
+        let tokens: Vec<_> = std::iter::from_fn(|| Some(lexer.next_token().kind)).take_while(|kind| *kind != TokenKind::Eof).collect();
+        assert_eq!(tokens, vec![
+            TokenKind::CssProperty("color".to_string()),
+            TokenKind::Colon,
+            TokenKind::CssValue("red".to_string()),
+            TokenKind::BlockComment(" temp ".to_string()),
+            TokenKind::Semicolon,
+            TokenKind::LineComment(" next".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_mode_stack() {
+        let mut lexer = Lexer::new("<p a={x > 1}>{ {y} }</p>; css! { @media (min-width: 4px) { a { b: c; } } }".to_string());
+        assert_eq!(lexer.debug_modes(), "Normal");
+        assert_eq!(lexer.next_token().kind, TokenKind::LAngle);
+        lexer.enter_jsx_mode();
+        assert_eq!(lexer.debug_modes(), "Normal > JsxChildren > JsxTag");
+        lexer.next_token(); // p
+        lexer.next_token(); // a
+        lexer.next_token(); // =
+        assert_eq!(lexer.next_token().kind, TokenKind::JsxOpenBrace);
+        lexer.next_token(); // x
+        assert_eq!(lexer.next_token().kind, TokenKind::RAngle);
+        assert_eq!(lexer.debug_modes(), "Normal > JsxChildren > JsxTag > JsxExpr");
+        lexer.next_token(); // 1
+        assert_eq!(lexer.next_token().kind, TokenKind::JsxCloseBrace);
+        assert_eq!(lexer.next_token().kind, TokenKind::RAngle);
+        assert_eq!(lexer.debug_modes(), "Normal > JsxChildren");
+
+        assert_eq!(lexer.next_token().kind, TokenKind::JsxOpenBrace);
+        assert_eq!(lexer.next_token().kind, TokenKind::LBrace);
+        assert_eq!(lexer.debug_modes(), "Normal > JsxChildren > JsxExpr > Block");
+        lexer.next_token(); // y
+        assert_eq!(lexer.next_token().kind, TokenKind::RBrace);
+        assert_eq!(lexer.next_token().kind, TokenKind::JsxCloseBrace);
+
+        assert_eq!(lexer.next_token().kind, TokenKind::LAngle);
+        lexer.enter_closing_tag_mode();
+        lexer.next_token(); // /
+        lexer.exit_closing_tag_mode();
+        lexer.exit_jsx_mode();
+        assert_eq!(lexer.debug_modes(), "Normal > JsxTag");
+        lexer.next_token(); // p
+        assert_eq!(lexer.next_token().kind, TokenKind::RAngle);
+        assert_eq!(lexer.debug_modes(), "Normal");
+
+        lexer.next_token(); // ;
+        assert_eq!(lexer.next_token().kind, TokenKind::CssMacro);
+        assert_eq!(lexer.next_token().kind, TokenKind::LBrace);
+        lexer.enter_css_mode();
+        assert_eq!(lexer.next_token().kind, TokenKind::CssMedia);
+        assert_eq!(lexer.debug_modes(), "Normal > Css { depth: 1, parens: 0 } > CssMediaCondition");
+        lexer.next_token(); // (
+        assert_eq!(lexer.next_token().kind, TokenKind::CssProperty("min-width".to_string()));
+        assert_eq!(lexer.debug_modes(), "Normal > Css { depth: 1, parens: 1 } > CssMediaCondition");
+        while lexer.next_token().kind != TokenKind::LBrace {}
+        assert_eq!(lexer.debug_modes(), "Normal > Css { depth: 2, parens: 0 }");
+        while lexer.is_css_mode() {
+            lexer.next_token();
+        }
+        assert_eq!(lexer.debug_modes(), "Normal");
+        assert_eq!(lexer.next_token().kind, TokenKind::Eof);
+    }
+
     #[test]
     fn test_style_keyword() {
         let input = "style Button { }".to_string();
@@ -1297,4 +3564,167 @@ mod tests {
         let token6 = lexer.next_token();
         assert_eq!(token6.kind, TokenKind::Identifier);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_new_in_mode() {
+        let kinds = |source: &str, mode: LexerStartMode| {
+            let mut lexer = Lexer::new_in_mode(source.to_string(), mode);
+            std::iter::from_fn(|| Some(lexer.next_token().kind)).take_while(|kind| *kind != TokenKind::Eof).collect::<Vec<_>>()
+        };
+
+        assert_eq!(kinds("Hi {name}!", LexerStartMode::Normal), vec![
+            TokenKind::Identifier,
+            TokenKind::LBrace,
+            TokenKind::Identifier,
+            TokenKind::RBrace,
+            TokenKind::Bang,
+        ]);
+
+        assert_eq!(kinds("Hi {name}! <b>bold</b>", LexerStartMode::JsxChildren), vec![
+            TokenKind::JsxText("Hi ".to_string()),
+            TokenKind::JsxOpenBrace,
+            TokenKind::Identifier,
+            TokenKind::JsxCloseBrace,
+            TokenKind::JsxText("! ".to_string()),
+            TokenKind::LAngle,
+            TokenKind::Identifier,
+            TokenKind::RAngle,
+            TokenKind::JsxText("bold".to_string()),
+            TokenKind::LAngle,
+            TokenKind::Slash,
+            TokenKind::Identifier,
+            TokenKind::RAngle,
+        ]);
+
+        // Closing a rule's `}` stays in CSS for the rules after it
+        let lexer = Lexer::new_in_mode(".a { color: red; }".to_string(), LexerStartMode::Css);
+        assert_eq!(lexer.debug_modes(), "Normal > Css { depth: 1, parens: 0 }");
+        assert_eq!(kinds(".a { color: red; } @media (min-width: 4px) {}", LexerStartMode::Css), vec![
+            TokenKind::CssSelector(".a".to_string()),
+            TokenKind::LBrace,
+            TokenKind::CssProperty("color".to_string()),
+            TokenKind::Colon,
+            TokenKind::CssValue("red".to_string()),
+            TokenKind::Semicolon,
+            TokenKind::RBrace,
+            TokenKind::CssMedia,
+            TokenKind::LParen,
+            TokenKind::CssProperty("min-width".to_string()),
+            TokenKind::Colon,
+            TokenKind::CssValue("4px".to_string()),
+            TokenKind::RParen,
+            TokenKind::LBrace,
+            TokenKind::RBrace,
+        ]);
+    }
+
+    #[test]
+    fn test_css_at_rule_tokens() {
+        // Any named at-rule is one token, for the parser to skip if it doesn't
+        // know it; looking past a lone `@` for a name must not move the lexer
+        let mut lexer = Lexer::new("css! {\n  @layer { a: b; }\n  @ x;\n  @font-face {}\n  @media (x) {}\n}".to_string());
+        lexer.next_token();
+        lexer.next_token();
+        lexer.enter_css_mode();
+        let layer = lexer.next_token();
+        assert_eq!((layer.kind, layer.line, layer.column, layer.span), (TokenKind::CssAtRule("layer".to_string()), 2, 3, Span::new(9, 15)));
+        while lexer.next_token().kind != TokenKind::RBrace {}
+        let at = lexer.next_token();
+        assert_eq!((at.kind, at.line, at.column, at.span), (TokenKind::At, 3, 3, Span::new(28, 29)));
+        while lexer.next_token().kind != TokenKind::Semicolon {}
+        let font_face = lexer.next_token();
+        assert_eq!((font_face.kind, font_face.lexeme.as_str(), font_face.line), (TokenKind::CssFontFace, "@font-face", 4));
+        lexer.next_token();
+        lexer.next_token();
+        let media = lexer.next_token();
+        assert_eq!((media.kind, media.lexeme.as_str(), media.line, media.column), (TokenKind::CssMedia, "@media", 5, 3));
+    }
+
+    #[test]
+    fn test_escape_at_end_of_source() {
+        let first = |source: &str| Lexer::new(source.to_string()).next_token().kind;
+        assert_eq!(first("'\\"), TokenKind::InvalidChar("`\\` at the end of the source escapes nothing".to_string()));
+        assert!(matches!(first("b'\\"), TokenKind::InvalidChar(message) if message.contains("escapes nothing")));
+        assert!(matches!(first("\"ab\\"), TokenKind::InvalidString(message) if message.starts_with("unterminated string")));
+        let mut lexer = Lexer::new("'\\".to_string());
+        lexer.next_token();
+        let eof = lexer.next_token();
+        assert_eq!((eof.kind, eof.span), (TokenKind::Eof, Span::new(2, 2)));
+    }
+
+    /// Bits of source the lexer treats specially, so random input reaches
+    /// its modes and edge cases rather than just identifiers
+    const FRAGMENTS: &[&str] = &[
+        "<", ">", "/>", "</", "{", "}", "(", ")", "[", "]", "@", "@media", "@container", "@keyframes", "@x",
+        "css!", "'", "'a", "\\", "\\u{", "\\x", "\"", "`", "${", "r#", "r\"", "br\"", "b'", "#", "/", "//", "/*", "*/",
+        "///", "0x", "1e", "1.5", "_", ".", "..", ":", ";", "=", "-", "a", "div", "self", " ", "\n", "\t", "é", "\u{200B}",
+    ];
+
+    /// What the parser can do to the lexer between tokens
+    #[derive(Debug, Clone, Copy)]
+    enum Switch { None, EnterJsx, ExitJsx, EnterClosingTag, ExitClosingTag, EnterCss, ExitCss, PushBrace, PopBrace }
+
+    fn switch_strategy() -> impl Strategy<Value = Switch> {
+        prop_oneof![
+            8 => Just(Switch::None),
+            1 => Just(Switch::EnterJsx),
+            1 => Just(Switch::ExitJsx),
+            1 => Just(Switch::EnterClosingTag),
+            1 => Just(Switch::ExitClosingTag),
+            1 => Just(Switch::EnterCss),
+            1 => Just(Switch::ExitCss),
+            1 => Just(Switch::PushBrace),
+            1 => Just(Switch::PopBrace),
+        ]
+    }
+
+    fn source_strategy() -> impl Strategy<Value = String> {
+        prop_oneof![
+            any::<String>(),
+            prop::collection::vec(prop::sample::select(FRAGMENTS), 0..40).prop_map(|parts| parts.concat()),
+        ]
+    }
+
+    /// Lexes to `Eof`, switching modes as told, and checks every token lies
+    /// inside the source in order, at the line and column it reports
+    fn lex_to_eof(source: &str, switches: &[Switch]) -> Result<(), TestCaseError> {
+        let mut lexer = Lexer::new(source.to_string()).with_comments(true);
+        let mut end = 0;
+        // Tokens take at least one char, bar an error token or two at the end
+        for step in 0..source.chars().count() + 4 {
+            match switches.get(step).copied().unwrap_or(Switch::None) {
+                Switch::None => {}
+                Switch::EnterJsx => lexer.enter_jsx_mode(),
+                Switch::ExitJsx => lexer.exit_jsx_mode(),
+                Switch::EnterClosingTag => lexer.enter_closing_tag_mode(),
+                Switch::ExitClosingTag => lexer.exit_closing_tag_mode(),
+                Switch::EnterCss => lexer.enter_css_mode(),
+                Switch::ExitCss => lexer.exit_css_mode(),
+                Switch::PushBrace => lexer.increment_brace_depth(),
+                Switch::PopBrace => lexer.decrement_brace_depth(),
+            }
+            let token = lexer.next_token();
+            prop_assert!(token.span.start >= end && token.span.end <= source.len(), "{:?} after {} in {:?}", token, end, source);
+            prop_assert!(source.is_char_boundary(token.span.start) && source.is_char_boundary(token.span.end), "{:?} in {:?}", token, source);
+            // Where the span says the token is, counted afresh
+            let before = &source[..token.span.start];
+            let line = before.matches('\n').count() + 1;
+            let column = before.rsplit('\n').next().unwrap_or_default().chars().count() + 1;
+            prop_assert_eq!((token.line, token.column), (line, column), "{:?} in {:?}", token, source);
+            if token.kind == TokenKind::Eof {
+                return Ok(());
+            }
+            end = token.span.end;
+        }
+        Err(TestCaseError::fail(format!("no Eof for {:?}", source)))
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(2000))]
+
+        #[test]
+        fn test_any_input_lexes_to_eof(source in source_strategy(), switches in prop::collection::vec(switch_strategy(), 0..60)) {
+            lex_to_eof(&source, &switches)?;
+        }
+    }
+}
@@ -1,11 +1,14 @@
 //#[macro_use]
 extern crate lazy_static;
 
+pub mod a11y_lint; // Opt-in accessibility warnings over JSX (--lint a11y)
 pub mod ast;
 pub mod borrow_checker;
 pub mod cache; // Compilation cache (Phase 9 Sprint 1)
 pub mod codegen;
 pub mod css_generator; // CSS generation (Phase 7.5)
+pub mod css_ast; // Structured CSS output for build-tool plugins
+pub mod css_lint; // Warnings over the collected stylesheet (specificity, ...)
 pub mod utility_config; // Utility class configuration (Phase 7.5 Sprint 3)
 pub mod utility_generator; // Utility class generation (Phase 7.5 Sprint 3)
 pub mod design_tokens; // Design token parser (Phase 8 Sprint 2)
@@ -32,6 +35,15 @@ pub mod lsp; // Language Server Protocol
 pub mod hmr; // Hot Module Replacement
 pub mod package_manager; // Package Manager
 pub mod module_loader; // Module loader for compile-time imports
+pub mod entry; // Entry point (fn main / component App) validation
+pub mod route_table; // Route collision and typed route param checks
+pub mod shadowing; // Warnings for locals that shadow a component rendered in JSX
+pub mod i18n; // t! translatable messages and catalog extraction
+pub mod escape; // HTML text / attribute and CSS value escaping
+pub mod runtime_files; // Runtime files written next to the bundles (shared-runtime.cjs, ...)
+pub mod html_entities; // &amp;, &nbsp; and &#169; in JSX text
+pub mod void_elements; // <br>, <img> and other elements that never have children
+pub mod condition_lint; // `if x = 5`, an assignment where a condition goes
 pub mod source_map; // Source map generation for debugging
 pub mod wasm_optimizer; // WASM optimization (DCE, inlining, constant folding)
 pub mod doc_generator; // Documentation generator (raven doc)
@@ -40,13 +52,20 @@ pub mod code_splitter; // Code splitting for server/client separation
 pub mod rpc_generator; // RPC stub generation for client/server communication
 pub mod js_emitter; // JavaScript code generation for server and client bundles
 pub mod js_minifier; // JavaScript minification for production builds
+pub mod css_minifier; // CSS minification for production builds
+pub mod build_manifest; // Build manifest (scoped class map, build flags)
+pub mod build_report; // Per-module size and dependency report (--report)
 pub mod formatter; // Code formatter for consistent style
+pub mod highlight; // Token classes for syntax highlighting in editors and docs
+pub mod visit; // Visit: read-only AST traversal for tools
+pub mod visit_mut; // VisitMut: in-place AST rewriting for tools
 pub mod watcher; // File watching and auto-recompilation
 pub mod test_framework; // Test framework for unit and integration testing (Phase 9 Sprint 2)
 
 use borrow_checker::BorrowChecker;
 use cache::CompilationCache;
 use codegen::CodeGenerator;
+use css_ast::{CssStylesheet, CssVisitor};
 use errors::CompileError;
 use lexer::Lexer;
 use parser::Parser;
@@ -54,6 +73,7 @@ use semantic_analyzer::SemanticAnalyzer;
 use type_checker::TypeChecker;
 use token::{Token, TokenKind};
 use wasm_optimizer::WasmOptimizer;
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
 // This enum is now public so the deployer can use it.
@@ -63,6 +83,88 @@ pub enum BuildTarget {
     Server,
 }
 
+/// A compiled module: WASM bytes plus the structured stylesheet, so build
+/// tools can inspect or rewrite CSS before it is emitted.
+pub struct CompiledModule {
+    pub wasm: Vec<u8>,
+    utility_css: String,
+    utility_css_minified: String,
+    css_ast: CssStylesheet,
+    class_maps: BTreeMap<String, BTreeMap<String, String>>,
+    css_warnings: Vec<diagnostics::Diagnostic>,
+}
+
+impl CompiledModule {
+    pub(crate) fn from_codegen(wasm: Vec<u8>, utility_gen: &mut utility_generator::UtilityGenerator, code_generator: &CodeGenerator) -> Self {
+        CompiledModule {
+            wasm,
+            utility_css: utility_gen.generate_css(),
+            utility_css_minified: utility_gen.generate_minified_css(),
+            css_ast: code_generator.get_css_ast().clone(),
+            class_maps: code_generator.get_class_maps().clone(),
+            css_warnings: code_generator.get_css_warnings().to_vec(),
+        }
+    }
+
+    /// Component, style-block and theme CSS as a structured stylesheet.
+    /// Utility classes are generated as text and are not part of the AST.
+    pub fn css_ast(&self) -> &CssStylesheet {
+        &self.css_ast
+    }
+
+    pub fn css_ast_mut(&mut self) -> &mut CssStylesheet {
+        &mut self.css_ast
+    }
+
+    /// Run a plugin over the stylesheet before it is emitted
+    pub fn apply_css_visitor(&mut self, visitor: &mut dyn CssVisitor) {
+        self.css_ast.accept(visitor);
+    }
+
+    /// Stylesheet warnings, e.g. overrides that lose on specificity,
+    /// unknown properties in inline `style` attributes, and at-rules css!
+    /// skipped.
+    /// Locations carry no file name; callers fill it in when reporting.
+    pub fn css_warnings(&self) -> Vec<diagnostics::Diagnostic> {
+        let mut warnings = css_lint::lint_stylesheet(&self.css_ast);
+        warnings.extend(self.css_warnings.iter().cloned());
+        warnings
+    }
+
+    /// Scoped class names per component, as recorded in the build manifest
+    pub fn class_maps(&self) -> &BTreeMap<String, BTreeMap<String, String>> {
+        &self.class_maps
+    }
+
+    /// The final CSS text: utility classes followed by the rendered stylesheet,
+    /// except `@import`s, which have to come before both
+    pub fn css(&self) -> String {
+        self.render_css(CssStylesheet::to_css)
+    }
+
+    /// The final CSS text like `css()`, each rule after a comment saying
+    /// where it was declared, for dev builds
+    pub fn css_with_sources(&self) -> String {
+        self.render_css(CssStylesheet::to_css_with_sources)
+    }
+
+    /// The final CSS text like `css()`, minified for release builds
+    pub fn css_minified(&self) -> String {
+        let minifier = css_minifier::CssMinifier::new();
+        let (imports, rest) = self.css_ast.split_imports();
+        format!("{}{}{}", minifier.minify(&imports), self.utility_css_minified, minifier.minify(&rest))
+    }
+
+    fn render_css(&self, render: fn(&CssStylesheet) -> String) -> String {
+        if self.utility_css.is_empty() {
+            render(&self.css_ast)
+        } else {
+            let (imports, rest) = self.css_ast.split_imports();
+            format!("{}{}\n{}", render(&imports), self.utility_css, render(&rest))
+        }
+    }
+}
+
 pub struct Compiler {
     pub optimize: bool,
     cache: Option<Arc<CompilationCache>>,
@@ -178,6 +280,13 @@ impl Compiler {
 
     /// Compile source code and return both WASM bytes and CSS output (Phase 7.5)
     pub fn compile_source_with_css(&self, source: &str, target: BuildTarget) -> Result<(Vec<u8>, String), CompileError> {
+        let module = self.compile_module(source, target)?;
+        let css_output = module.css();
+        Ok((module.wasm, css_output))
+    }
+
+    /// Compile source code into a CompiledModule, keeping the CSS as an AST
+    pub fn compile_module(&self, source: &str, target: BuildTarget) -> Result<CompiledModule, CompileError> {
         println!("   - Starting compilation for target: {:?}", target);
 
         // --- Lexing, Parsing, Macro Expansion ---
@@ -228,17 +337,6 @@ impl Compiler {
         let utility_config = utility_config::UtilityConfig::load();
         let mut utility_gen = utility_generator::UtilityGenerator::new(utility_config);
         utility_gen.scan_for_utilities(&program_ast);
-        let utility_css = utility_gen.generate_css();
-
-        // Extract CSS output (Phase 7.5)
-        let component_css = code_generator.get_css_output().to_string();
-
-        // Combine utility CSS and component CSS
-        let css_output = if utility_css.is_empty() {
-            component_css
-        } else {
-            format!("{}\n{}", utility_css, component_css)
-        };
 
         // --- Optimization ---
         if self.optimize {
@@ -262,15 +360,18 @@ impl Compiler {
             }
         }
 
-        Ok((wasm_bytes, css_output))
+        // Keep CSS output (Phase 7.5) structured alongside the WASM
+        Ok(CompiledModule::from_codegen(wasm_bytes, &mut utility_gen, &code_generator))
     }
 
     /// Display a compilation error with beautiful diagnostics
     pub fn display_error(error: &CompileError, source: Option<&str>, filename: &str) -> String {
-        
-
-        let diagnostic = error.to_diagnostic(filename);
-        diagnostic.display(source)
+        error
+            .to_diagnostics(filename)
+            .iter()
+            .map(|diagnostic| diagnostic.display(source))
+            .collect::<Vec<_>>()
+            .join("\n")
     }
 }
 
@@ -283,19 +384,25 @@ impl LexerExt for Lexer {
         let mut tokens = Vec::new();
         loop {
             let token = self.next_token();
-            let kind = token.kind.clone();
-            if let TokenKind::Illegal(ch) = kind {
-                return Err(CompileError::LexerError(format!("Illegal character: '{}'", ch)));
-            }
+            let done = token.kind == TokenKind::Eof;
             tokens.push(token);
-            if kind == TokenKind::Eof {
+            if done {
                 break;
             }
         }
-        Ok(tokens)
+        let mut errors: Vec<CompileError> = self.errors().iter().cloned().map(CompileError::from).collect();
+        match errors.len() {
+            0 => Ok(tokens),
+            1 => Err(errors.remove(0)),
+            _ => Err(CompileError::Multiple(errors)),
+        }
     }
 }
 
 // Integration tests - tests that compile full programs end-to-end
 #[cfg(test)]
-mod integration_tests;
\ No newline at end of file
+mod integration_tests;
+
+// Gnarly attribute expressions the lexer's JSX modes have to see through
+#[cfg(test)]
+mod jsx_attribute_tests;
\ No newline at end of file
@@ -1,6 +1,7 @@
 // Language Server Protocol implementation for Jounce
 // Provides IDE features: autocomplete, hover, diagnostics, etc.
 
+use crate::condition_lint;
 use crate::diagnostics::Diagnostic;
 use crate::lexer::Lexer;
 use crate::parser::Parser;
@@ -296,22 +297,23 @@ impl LanguageServer {
         let ast = match parser.parse_program() {
             Ok(ast) => ast,
             Err(e) => {
-                diagnostics.push(Diagnostic::error(format!("{:?}", e)));
+                diagnostics.extend(e.to_diagnostics(""));
                 return diagnostics;
             }
         };
+        diagnostics.extend(condition_lint::check_program(&ast, ""));
 
         // Semantic analysis
         let mut analyzer = SemanticAnalyzer::new();
         if let Err(e) = analyzer.analyze_program(&ast) {
-            diagnostics.push(Diagnostic::error(format!("{:?}", e)));
+            diagnostics.extend(e.to_diagnostics(""));
             return diagnostics;
         }
 
         // Type checking
         let mut type_checker = TypeChecker::new();
         if let Err(e) = type_checker.check_program(&ast.statements) {
-            diagnostics.push(Diagnostic::error(format!("{:?}", e)));
+            diagnostics.extend(e.to_diagnostics(""));
         }
 
         diagnostics
@@ -2189,6 +2191,22 @@ impl LanguageServer {
     ) -> Vec<CodeAction> {
         let mut actions = Vec::new();
 
+        // Fixes the compiler attached to the diagnostic apply as-is
+        for suggestion in &diagnostic.code_suggestions {
+            actions.push(CodeAction {
+                title: suggestion.message.clone(),
+                kind: CodeActionKind::QuickFix,
+                diagnostics: vec![diagnostic.clone()],
+                edit: WorkspaceEdit {
+                    changes: vec![TextEdit {
+                        range: suggestion.location.to_lsp_range(),
+                        new_text: suggestion.replacement.clone(),
+                    }],
+                },
+                is_preferred: true,
+            });
+        }
+
         // Check diagnostic message for patterns
         let message = &diagnostic.message;
 
@@ -3471,6 +3489,56 @@ mod tests {
         assert!(!ranges_overlap(range3, range4));
     }
 
+    #[test]
+    fn test_code_action_for_assignment_in_condition() {
+        let mut server = LanguageServer::new();
+        server.open_document(
+            "file:///test.jnc".to_string(),
+            "fn main() {\n    let x = 1;\n    if x = 5 {\n    }\n}".to_string(),
+            1,
+        );
+
+        let diagnostics = server.get_diagnostics("file:///test.jnc");
+        assert_eq!(diagnostics.len(), 1);
+        let fix = &diagnostics[0].code_suggestions[0];
+
+        let range = Range {
+            start: Position { line: 2, character: 0 },
+            end: Position { line: 2, character: 14 },
+        };
+        let actions = server.get_code_actions("file:///test.jnc", range);
+        let action = actions.iter().find(|a| a.title == "did you mean `==`?").expect("quick fix offered");
+        assert!(action.is_preferred);
+        assert_eq!(action.edit.changes.len(), 1);
+        assert_eq!(action.edit.changes[0].new_text, "==");
+        assert_eq!(action.edit.changes[0].range, fix.location.to_lsp_range());
+        assert_eq!(action.edit.changes[0].range.start.line, 2);
+    }
+
+    #[test]
+    fn test_code_action_for_misspelled_name() {
+        let mut server = LanguageServer::new();
+        server.open_document(
+            "file:///test.jnc".to_string(),
+            "fn total(items: i32) -> i32 {\n    return itmes;\n}".to_string(),
+            1,
+        );
+
+        let diagnostics = server.get_diagnostics("file:///test.jnc");
+        assert_eq!(diagnostics.len(), 1, "got {:?}", diagnostics);
+        assert_eq!(diagnostics[0].message, "Cannot find 'itmes' in this scope");
+        let fix = &diagnostics[0].code_suggestions[0];
+
+        let range = Range {
+            start: Position { line: 1, character: 0 },
+            end: Position { line: 1, character: 17 },
+        };
+        let actions = server.get_code_actions("file:///test.jnc", range);
+        let action = actions.iter().find(|a| a.title == "did you mean `items`?").expect("quick fix offered");
+        assert_eq!(action.edit.changes[0].new_text, "items");
+        assert_eq!(action.edit.changes[0].range, fix.location.to_lsp_range());
+    }
+
     #[test]
     fn test_code_action_kind_as_str() {
         assert_eq!(CodeActionKind::QuickFix.as_str(), "quickfix");
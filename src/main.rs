@@ -1,16 +1,29 @@
 use clap::Parser as ClapParser;
 use colored::Colorize;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process;
 use std::sync::Arc;
 use std::time::Instant;
-use jounce_compiler::{Compiler, deployer, BuildTarget}; // FIX: Corrected the import path
-use jounce_compiler::cache::{CompilationCache, compile_source_cached};
+use jounce_compiler::{Compiler, deployer, runtime_files, BuildTarget}; // FIX: Corrected the import path
+use jounce_compiler::build_manifest::{BuildManifest, MANIFEST_FILE};
+use jounce_compiler::build_report::{BuildReport, REPORT_FILE};
+use jounce_compiler::entry::{self, EntryConfig};
+use jounce_compiler::route_table::RouteTable;
+use jounce_compiler::shadowing::ShadowCheck;
+use jounce_compiler::a11y_lint;
+use jounce_compiler::css_lint;
+use jounce_compiler::html_entities;
+use jounce_compiler::void_elements;
+use jounce_compiler::condition_lint;
+use jounce_compiler::diagnostics::Diagnostic;
+use jounce_compiler::i18n;
+use jounce_compiler::cache::{BuildCache, BuildOutput, CompilationCache, compile_module_cached, BUILD_CACHE_DIR};
 use jounce_compiler::watcher::{FileWatcher, WatchConfig, CompileStats};
 use jounce_compiler::lexer::Lexer;
 use jounce_compiler::parser::Parser;
 use jounce_compiler::js_emitter::JSEmitter;
+use jounce_compiler::hmr::{self, BuildHashes, RebuildKind};
 
 #[derive(ClapParser)]
 #[command(name = "jnc", version, about)]
@@ -30,6 +43,28 @@ enum Commands {
         minify: bool,
         #[arg(short, long)]
         profile: bool,
+        /// Stamp data-jounce-component / data-jounce-key attributes for tests (dropped with --minify)
+        #[arg(long)]
+        test_ids: bool,
+        /// Keep test id attributes in minified (release) builds
+        #[arg(long)]
+        keep_test_ids: bool,
+        /// Write a per-module size and dependency report (jounce-report.json) and print it
+        #[arg(long)]
+        report: bool,
+        /// Turn on an opt-in lint group (a11y)
+        #[arg(long = "lint", value_name = "GROUP")]
+        lint: Vec<String>,
+        /// Time component renders, effects and computeds with performance marks;
+        /// window.__JOUNCE_PROFILE__.summary() aggregates them
+        #[arg(long)]
+        profile_runtime: bool,
+        /// Inline the collected css! stylesheet into client.js instead of writing styles.css
+        #[arg(long)]
+        inline_css: bool,
+        /// Fail the build on unknown CSS properties instead of warning
+        #[arg(long)]
+        strict_css: bool,
     },
     /// Creates a new Jounce project
     New {
@@ -95,6 +130,17 @@ enum Commands {
         fix: bool,
         path: Option<PathBuf>,
     },
+    /// Extract translatable messages (t! calls and JSX text) into a catalog
+    I18nExtract {
+        /// File or directory to scan (default: src)
+        path: Option<PathBuf>,
+        #[arg(short, long, default_value = "locales/messages.json")]
+        /// Catalog to write; other .json files next to it are checked as translations
+        output: PathBuf,
+        #[arg(long, default_value_t = i18n::DEFAULT_MIN_TEXT_LENGTH)]
+        /// Shortest JSX text (in characters) to extract
+        min_length: usize,
+    },
     /// Build the project for production
     Build {
         #[arg(short, long)]
@@ -176,21 +222,38 @@ fn main() {
     let cli = Cli::parse();
 
     match cli.command {
-        Commands::Compile { path, output, minify, profile } => {
-            use jounce_compiler::lexer::Lexer;
-            use jounce_compiler::parser::Parser;
-            use jounce_compiler::js_emitter::JSEmitter;
+        Commands::Compile { path, output, minify, profile, test_ids, keep_test_ids, report, lint, profile_runtime, inline_css, strict_css } => {
+            use jounce_compiler::js_emitter::{Bundle, JSEmitter, Stylesheet};
+            use jounce_compiler::css_minifier::CssMinifier;
             use jounce_compiler::js_minifier::JSMinifier;
 
             let compile_start = Instant::now();
+            let test_ids = JSEmitter::test_ids_for_build(test_ids, minify, keep_test_ids);
 
             println!("🔥 Compiling full-stack application: {}", path.display());
             if minify {
                 println!("   🗜️  Minification: enabled");
             }
+            if test_ids {
+                println!("   🏷️  Test ids: enabled");
+            }
             if profile {
                 println!("   📊 Profiling: enabled");
             }
+            if profile_runtime {
+                println!("   ⏱️  Runtime profiling: enabled");
+            }
+            if inline_css {
+                println!("   🎨 CSS: inlined into client.js");
+            }
+            if let Some(group) = lint.iter().find(|group| group.as_str() != a11y_lint::A11Y_LINT_GROUP) {
+                eprintln!("❌ Unknown lint group '{}' (available: {})", group, a11y_lint::A11Y_LINT_GROUP);
+                return;
+            }
+            let lint_a11y = !lint.is_empty();
+            if lint_a11y {
+                println!("   ♿ Lints: {}", a11y_lint::A11Y_LINT_GROUP);
+            }
             println!("   📦 Output: server.js + client.js + app.wasm\n");
 
             // Read source code
@@ -204,21 +267,21 @@ fn main() {
             };
             let io_time = io_start.elapsed();
 
-            // Parse the source
+            // Parse the source, or load its AST from an earlier build
             println!("   Parsing...");
-            let lex_start = Instant::now();
-            let mut lexer = Lexer::new(source_code.clone());
-            let lex_time = lex_start.elapsed();
-
+            let build_cache = Arc::new(BuildCache::new(
+                BUILD_CACHE_DIR,
+                format!("minify={} test_ids={} profile_runtime={} inline_css={} strict_css={}", minify, test_ids, profile_runtime, inline_css, strict_css),
+            ));
             let parse_start = Instant::now();
-            let mut parser = Parser::new(&mut lexer);
-            let mut program = match parser.parse_program() {
+            let mut program = match build_cache.parse(&path, &source_code) {
                 Ok(p) => {
                     println!("   ✓ Parsed {} statements", p.statements.len());
                     p
                 }
                 Err(e) => {
-                    eprintln!("❌ Parsing failed: {:?}", e);
+                    eprintln!("❌ Parsing failed:\n");
+                    eprintln!("{}", Compiler::display_error(&e, Some(&source_code), &path.to_string_lossy()));
                     return;
                 }
             };
@@ -227,23 +290,175 @@ fn main() {
             // Merge imported modules into the AST
             let module_start = Instant::now();
             use jounce_compiler::module_loader::ModuleLoader;
-            let mut module_loader = ModuleLoader::new("aloha-shirts");
+            let mut entry_candidates = entry::candidates_in(&program, &path);
+            let mut route_table = RouteTable::default();
+            route_table.add_program(&program, &path.to_string_lossy());
+            let mut shadow_check = ShadowCheck::default();
+            shadow_check.add_program(&program, &path.to_string_lossy());
+            let mut lint_diagnostics = html_entities::check_program(&program, &path.to_string_lossy());
+            lint_diagnostics.extend(void_elements::check_program(&program, &path.to_string_lossy()));
+            lint_diagnostics.extend(condition_lint::check_program(&program, &path.to_string_lossy()));
+            if lint_a11y {
+                lint_diagnostics.extend(a11y_lint::check_program(&program, &path.to_string_lossy()));
+            }
+            let mut module_loader = ModuleLoader::new("aloha-shirts").with_build_cache(build_cache.clone());
             module_loader.set_current_file(&path);
-            match module_loader.merge_imports(&mut program) {
-                Ok(_imported_files) => {
-                    // Dependencies tracked if using cached compilation
-                }
+            let imported_files = match module_loader.merge_imports(&mut program) {
+                Ok(imported_files) => imported_files,
                 Err(e) => {
-                    eprintln!("❌ Module import failed: {}", e);
+                    for diagnostic in e.to_diagnostics(&path.to_string_lossy()) {
+                        eprintln!("{}", diagnostic.display(diagnostic_source(&diagnostic, &path, &source_code).as_deref()));
+                    }
+                    eprintln!("❌ Module import failed");
                     return;
                 }
-            }
+            };
             let module_time = module_start.elapsed();
 
+            // Exactly one entry point across the module graph, unless it's a library
+            entry_candidates.extend(module_loader.modules().flat_map(entry::candidates_in_module));
+            let entry_config = entry_config_for(&path);
+            let entry_point = if entry::is_library(&entry_candidates, entry_config.as_ref()) {
+                println!("   ℹ️  No component or `fn main`: building as a library, with no entry point");
+                None
+            } else {
+                match entry::select_entry(&entry_candidates, entry_config.as_ref()) {
+                    Ok(entry_point) => Some(entry_point),
+                    Err(e) => {
+                        eprintln!("❌ {}", e);
+                        return;
+                    }
+                }
+            };
+
+            // Route collisions and route param types, shadowed components, unknown entities and opt-in lints, across the module graph
+            let mut modules: Vec<_> = module_loader.modules().collect();
+            modules.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+            for module in modules {
+                route_table.add_program(&module.ast, &module.file_path.to_string_lossy());
+                shadow_check.add_program(&module.ast, &module.file_path.to_string_lossy());
+                lint_diagnostics.extend(html_entities::check_program(&module.ast, &module.file_path.to_string_lossy()));
+                lint_diagnostics.extend(void_elements::check_program(&module.ast, &module.file_path.to_string_lossy()));
+                lint_diagnostics.extend(condition_lint::check_program(&module.ast, &module.file_path.to_string_lossy()));
+                if lint_a11y {
+                    lint_diagnostics.extend(a11y_lint::check_program(&module.ast, &module.file_path.to_string_lossy()));
+                }
+            }
+            let route_diagnostics = route_table.check();
+            lint_diagnostics.extend(shadow_check.check());
+            for diagnostic in route_diagnostics.iter().chain(&lint_diagnostics) {
+                eprintln!("{}", diagnostic.display(diagnostic_source(diagnostic, &path, &source_code).as_deref()));
+            }
+            if RouteTable::has_errors(&route_diagnostics) {
+                eprintln!("❌ Route check failed");
+                return;
+            }
+
+            // An unchanged project reuses the previous build's output.
+            // Reports and profiles need a full build, so they always run one.
+            let output_dir = output.unwrap_or_else(|| PathBuf::from("dist"));
+            // Through a Value so map keys are sorted and the key is stable across runs
+            let utility_config = serde_json::to_value(jounce_compiler::utility_config::UtilityConfig::load())
+                .map(|config| config.to_string())
+                .unwrap_or_default();
+            let build_key = build_cache.build_key(&format!("{:?}\0{}", entry_point, utility_config));
+            let module_stats = build_cache.stats();
+            println!("   ✓ {} module(s) cached, {} parsed", module_stats.hits, module_stats.misses);
+            if !report && !profile {
+                if let Some(cached) = build_cache.load_output(build_key) {
+                    println!("   ✓ Unchanged since the last build, reusing its output");
+                    if !report_css_warnings(&cached.warnings, strict_css, &source_code) {
+                        return;
+                    }
+                    if !write_build_output(&output_dir, &cached) {
+                        return;
+                    }
+                    println!("\n{} {} {}",
+                        "✨".bold(),
+                        "Compilation complete!".green().bold(),
+                        format!("({:.2?}, cached)", compile_start.elapsed()).dimmed());
+                    return;
+                }
+            }
+
+            // Compile to WASM with caching
+            println!("   {} {} {}",
+                "⚙️ ".dimmed(),
+                "Compiling to WebAssembly".bold(),
+                "(with caching)".dimmed());
+            let wasm_start = Instant::now();
+
+            // Initialize compilation cache
+            let cache_dir = PathBuf::from(".jounce/cache");
+            if let Err(e) = fs::create_dir_all(&cache_dir) {
+                eprintln!("⚠️  Warning: Could not create cache directory: {}", e);
+            }
+            let cache = Arc::new(CompilationCache::new(cache_dir));
+
+            let (wasm_bytes, css_output, stylesheet, manifest, css_warnings) = match compile_module_cached(&source_code, &path, BuildTarget::Client, &cache, false) {
+                Ok(mut module) => {
+                    // One stylesheet for every css! block; dev builds note where each rule came from
+                    module.css_ast_mut().dedupe();
+                    let css = if minify {
+                        let css = module.css_minified();
+                        let stats = CssMinifier::new().stats(&module.css(), &css);
+                        println!("   ✓ styles.css: {} → {} bytes (-{:.1}%)",
+                            stats.original_size, stats.minified_size, stats.reduction_percent);
+                        css
+                    } else {
+                        module.css_with_sources()
+                    };
+                    println!("   ✓ Generated WASM module ({} bytes)", module.wasm.len());
+                    if !css.is_empty() {
+                        println!("   ✓ Generated CSS output ({} bytes)", css.len());
+                    }
+                    let mut css_warnings = module.css_warnings();
+                    let file = path.to_string_lossy().to_string();
+                    for warning in &mut css_warnings {
+                        if let Some(location) = warning.location.as_mut() {
+                            location.file = file.clone();
+                        }
+                        for label in &mut warning.labels {
+                            label.location.file = file.clone();
+                        }
+                    }
+                    if !report_css_warnings(&css_warnings, strict_css, &source_code) {
+                        return;
+                    }
+                    let mut manifest = BuildManifest::new(path.to_string_lossy());
+                    manifest.release = minify;
+                    manifest.test_ids = test_ids;
+                    manifest.classes = module.class_maps().clone();
+                    let stylesheet = module.css_ast().clone();
+                    (module.wasm, css, stylesheet, manifest, css_warnings)
+                }
+                Err(e) => {
+                    eprintln!("\n❌ Compilation failed:\n");
+                    let diagnostic_output = Compiler::display_error(&e, Some(&source_code), &path.to_string_lossy());
+                    eprintln!("{}", diagnostic_output);
+                    return;
+                }
+            };
+            let wasm_time = wasm_start.elapsed();
+
             // Generate JavaScript bundles
             println!("   Generating JavaScript bundles...");
             let codegen_start = Instant::now();
-            let emitter = JSEmitter::new(&program);
+            let emitter = JSEmitter::new(&program)
+                .with_test_ids(test_ids)
+                .with_devtools(!minify)
+                .with_runtime_profiling(profile_runtime);
+            let emitter = match entry_point {
+                Some(entry_point) => emitter.with_entry(entry_point),
+                None => emitter,
+            };
+            let emitter = if css_output.is_empty() {
+                emitter
+            } else if inline_css {
+                emitter.with_stylesheet(Stylesheet::Inline(css_output.clone()))
+            } else {
+                emitter.with_stylesheet(Stylesheet::Linked("styles.css".to_string()))
+            };
             let mut server_js = emitter.generate_server_js();
             let mut client_js = emitter.generate_client_js();
             let codegen_time = codegen_start.elapsed();
@@ -252,6 +467,18 @@ fn main() {
             println!("   ✓ Split: {} server, {} client, {} shared functions",
                 stats.server_functions, stats.client_functions, stats.shared_functions);
 
+            // Size accounting happens before minification so both sizes are known
+            let build_report = report.then(|| {
+                let items = emitter.emitted_items();
+                let origins = module_loader.item_origins();
+                let mut build_report = BuildReport::new(path.to_string_lossy());
+                build_report.add_bundle(Bundle::Server, &server_js, &items, &origins);
+                build_report.add_bundle(Bundle::Client, &client_js, &items, &origins);
+                build_report.add_dependencies(&imported_files, &module_loader);
+                build_report.add_css(&css_output, &stylesheet, &manifest.classes);
+                build_report
+            });
+
             // Minify if requested
             let mut minify_time = std::time::Duration::ZERO;
             if minify {
@@ -275,114 +502,32 @@ fn main() {
                 minify_time = minify_start.elapsed();
             }
 
-            // Compile to WASM with caching
-            println!("   {} {} {}",
-                "⚙️ ".dimmed(),
-                "Compiling to WebAssembly".bold(),
-                "(with caching)".dimmed());
-            let wasm_start = Instant::now();
-
-            // Initialize compilation cache
-            let cache_dir = PathBuf::from(".jounce/cache");
-            if let Err(e) = fs::create_dir_all(&cache_dir) {
-                eprintln!("⚠️  Warning: Could not create cache directory: {}", e);
-            }
-            let cache = Arc::new(CompilationCache::new(cache_dir));
-
-            let (wasm_bytes, css_output) = match compile_source_cached(&source_code, &path, BuildTarget::Client, &cache, false) {
-                Ok((bytes, css)) => {
-                    println!("   ✓ Generated WASM module ({} bytes)", bytes.len());
-                    if !css.is_empty() {
-                        println!("   ✓ Generated CSS output ({} bytes)", css.len());
-                    }
-                    (bytes, css)
-                }
-                Err(e) => {
-                    eprintln!("\n❌ Compilation failed:\n");
-                    let diagnostic_output = Compiler::display_error(&e, Some(&source_code), &path.to_string_lossy());
-                    eprintln!("{}", diagnostic_output);
-                    return;
-                }
-            };
-            let wasm_time = wasm_start.elapsed();
-
-            // Determine output directory
-            let output_dir = output.unwrap_or_else(|| PathBuf::from("dist"));
-            if let Err(e) = fs::create_dir_all(&output_dir) {
-                eprintln!("❌ Failed to create output directory: {}", e);
-                return;
-            }
-
             // Write output files
             println!("\n   {} {}",
                 "📝".dimmed(),
                 "Writing output files...".bold());
             let write_start = Instant::now();
 
-            let server_path = output_dir.join("server.js");
-            if let Err(e) = fs::write(&server_path, server_js) {
-                eprintln!("❌ Failed to write server.js: {}", e);
-                return;
-            }
-            println!("   ✓ {}", server_path.display());
-
-            let client_path = output_dir.join("client.js");
-            if let Err(e) = fs::write(&client_path, client_js) {
-                eprintln!("❌ Failed to write client.js: {}", e);
-                return;
-            }
-            println!("   ✓ {}", client_path.display());
-
-            let wasm_path = output_dir.join("app.wasm");
-            if let Err(e) = fs::write(&wasm_path, wasm_bytes) {
-                eprintln!("❌ Failed to write app.wasm: {}", e);
+            let build_output = BuildOutput {
+                server_js,
+                client_js,
+                wasm: wasm_bytes,
+                css: if inline_css { String::new() } else { css_output },
+                manifest,
+                warnings: css_warnings,
+            };
+            if !write_build_output(&output_dir, &build_output) {
                 return;
             }
-            println!("   ✓ {}", wasm_path.display());
+            build_cache.store_output(build_key, &build_output);
 
-            // Write CSS output (Phase 7.5)
-            if !css_output.is_empty() {
-                let css_path = output_dir.join("styles.css");
-                if let Err(e) = fs::write(&css_path, css_output) {
-                    eprintln!("❌ Failed to write styles.css: {}", e);
-                    return;
+            if let Some(build_report) = &build_report {
+                let report_path = output_dir.join(REPORT_FILE);
+                if let Err(e) = fs::write(&report_path, build_report.to_json()) {
+                    eprintln!("⚠️  Warning: Failed to write {}: {}", REPORT_FILE, e);
+                } else {
+                    println!("   ✓ {}", report_path.display());
                 }
-                println!("   ✓ {}", css_path.display());
-            }
-
-            // Write embedded runtime files
-            const SERVER_RUNTIME: &str = include_str!("../runtime/server-runtime.js");
-            const CLIENT_RUNTIME: &str = include_str!("../runtime/client-runtime.js");
-            const REACTIVITY_RUNTIME: &str = include_str!("../runtime/reactivity.js");
-
-            let server_runtime_path = output_dir.join("server-runtime.js");
-            if let Err(e) = fs::write(&server_runtime_path, SERVER_RUNTIME) {
-                eprintln!("⚠️  Warning: Failed to write server-runtime.js: {}", e);
-            } else {
-                println!("   ✓ {}", server_runtime_path.display());
-            }
-
-            let client_runtime_path = output_dir.join("client-runtime.js");
-            if let Err(e) = fs::write(&client_runtime_path, CLIENT_RUNTIME) {
-                eprintln!("⚠️  Warning: Failed to write client-runtime.js: {}", e);
-            } else {
-                println!("   ✓ {}", client_runtime_path.display());
-            }
-
-            let reactivity_path = output_dir.join("reactivity.js");
-            if let Err(e) = fs::write(&reactivity_path, REACTIVITY_RUNTIME) {
-                eprintln!("⚠️  Warning: Failed to write reactivity.js: {}", e);
-            } else {
-                println!("   ✓ {}", reactivity_path.display());
-            }
-
-            // Create index.html
-            let html_content = generate_index_html();
-            let html_path = output_dir.join("index.html");
-            if let Err(e) = fs::write(&html_path, html_content) {
-                eprintln!("⚠️  Warning: Failed to write index.html: {}", e);
-            } else {
-                println!("   ✓ {}", html_path.display());
             }
             let write_time = write_start.elapsed();
 
@@ -393,7 +538,6 @@ fn main() {
                 println!("\n📊 Profiling Results");
                 println!("====================");
                 println!("  File I/O:      {:>8.2?}  ({:>5.1}%)", io_time, (io_time.as_secs_f64() / total_time.as_secs_f64()) * 100.0);
-                println!("  Lexing:        {:>8.2?}  ({:>5.1}%)", lex_time, (lex_time.as_secs_f64() / total_time.as_secs_f64()) * 100.0);
                 println!("  Parsing:       {:>8.2?}  ({:>5.1}%)", parse_time, (parse_time.as_secs_f64() / total_time.as_secs_f64()) * 100.0);
                 println!("  Modules:       {:>8.2?}  ({:>5.1}%)", module_time, (module_time.as_secs_f64() / total_time.as_secs_f64()) * 100.0);
                 println!("  Codegen:       {:>8.2?}  ({:>5.1}%)", codegen_time, (codegen_time.as_secs_f64() / total_time.as_secs_f64()) * 100.0);
@@ -407,6 +551,12 @@ fn main() {
                 println!();
             }
 
+            if let Some(build_report) = &build_report {
+                println!("\n📦 Build Report");
+                println!("===============");
+                print!("{}", build_report.to_table());
+            }
+
             println!("\n{} {} {}",
                 "✨".bold(),
                 "Compilation complete!".green().bold(),
@@ -515,6 +665,14 @@ fn main() {
                 process::exit(1);
             }
         }
+        Commands::I18nExtract { path, output, min_length } => {
+            let target = path.unwrap_or_else(|| PathBuf::from("src"));
+            println!("🌐 Extracting messages from {}...", target.display());
+            if let Err(e) = extract_messages(target, output, min_length) {
+                eprintln!("❌ Extraction failed: {}", e);
+                process::exit(1);
+            }
+        }
         Commands::Build { release } => {
             if release {
                 println!("📦 Building project (release mode)...");
@@ -550,7 +708,8 @@ fn main() {
             let program = match parser.parse_program() {
                 Ok(p) => p,
                 Err(e) => {
-                    eprintln!("❌ Parsing failed: {:?}", e);
+                    eprintln!("❌ Parsing failed:\n");
+                    eprintln!("{}", Compiler::display_error(&e, Some(&source_code), &path.to_string_lossy()));
                     process::exit(1);
                 }
             };
@@ -769,6 +928,96 @@ fn main() {
     }
 }
 
+/// Print a build's css! warnings. With `--strict-css`, unknown properties are
+/// errors; returns false when any were denied.
+fn report_css_warnings(warnings: &[Diagnostic], strict_css: bool, source_code: &str) -> bool {
+    let mut denied = 0;
+    for warning in warnings {
+        let mut warning = warning.clone();
+        if strict_css && css_lint::deny_unknown_property(&mut warning) {
+            denied += 1;
+        }
+        eprintln!("{}", warning.display(Some(source_code)));
+    }
+    if denied > 0 {
+        eprintln!("❌ Compilation failed: {} unknown CSS propert{} (--strict-css)", denied, if denied == 1 { "y" } else { "ies" });
+        return false;
+    }
+    true
+}
+
+/// Write a build's output and the runtime files next to it, printing each
+/// path. Returns false (after printing why) if a required file can't be written.
+fn write_build_output(output_dir: &std::path::Path, output: &BuildOutput) -> bool {
+    if let Err(e) = fs::create_dir_all(output_dir) {
+        eprintln!("❌ Failed to create output directory: {}", e);
+        return false;
+    }
+
+    let server_path = output_dir.join("server.js");
+    if let Err(e) = fs::write(&server_path, &output.server_js) {
+        eprintln!("❌ Failed to write server.js: {}", e);
+        return false;
+    }
+    println!("   ✓ {}", server_path.display());
+
+    let client_path = output_dir.join("client.js");
+    if let Err(e) = fs::write(&client_path, &output.client_js) {
+        eprintln!("❌ Failed to write client.js: {}", e);
+        return false;
+    }
+    println!("   ✓ {}", client_path.display());
+
+    let wasm_path = output_dir.join("app.wasm");
+    if let Err(e) = fs::write(&wasm_path, &output.wasm) {
+        eprintln!("❌ Failed to write app.wasm: {}", e);
+        return false;
+    }
+    println!("   ✓ {}", wasm_path.display());
+
+    // Write CSS output (Phase 7.5)
+    if !output.css.is_empty() {
+        let css_path = output_dir.join("styles.css");
+        if let Err(e) = fs::write(&css_path, &output.css) {
+            eprintln!("❌ Failed to write styles.css: {}", e);
+            return false;
+        }
+        println!("   ✓ {}", css_path.display());
+    }
+
+    let manifest_path = output_dir.join(MANIFEST_FILE);
+    if let Err(e) = fs::write(&manifest_path, output.manifest.to_json()) {
+        eprintln!("⚠️  Warning: Failed to write {}: {}", MANIFEST_FILE, e);
+    } else {
+        println!("   ✓ {}", manifest_path.display());
+    }
+
+    // Write embedded runtime files; release builds leave out devtools.js,
+    // including one an earlier dev build wrote here
+    let devtools = !output.manifest.release;
+    if !devtools {
+        let _ = fs::remove_file(output_dir.join(runtime_files::DEVTOOLS_FILE));
+    }
+    for (name, contents) in runtime_files::runtime_files(devtools) {
+        let path = output_dir.join(name);
+        if let Err(e) = fs::write(&path, contents) {
+            eprintln!("⚠️  Warning: Failed to write {}: {}", name, e);
+        } else {
+            println!("   ✓ {}", path.display());
+        }
+    }
+
+    // Create index.html
+    let html_content = generate_index_html(!output.css.is_empty());
+    let html_path = output_dir.join("index.html");
+    if let Err(e) = fs::write(&html_path, html_content) {
+        eprintln!("⚠️  Warning: Failed to write index.html: {}", e);
+    } else {
+        println!("   ✓ {}", html_path.display());
+    }
+    true
+}
+
 // The create_new_project function is unchanged
 fn create_new_project(name: &str) -> std::io::Result<()> {
     let root = PathBuf::from(name);
@@ -792,7 +1041,7 @@ fn create_new_project(name: &str) -> std::io::Result<()> {
 
     fs::write(
         root.join(".gitignore"),
-        "/dist\n/target\n",
+        "/dist\n/target\n/.jounce-cache\n",
     )?;
 
     fs::write(
@@ -833,7 +1082,9 @@ fn watch_and_compile(
     println!("   Output: {}", output.display());
     println!();
 
-    let compile_result = compile_file(&path, &output, verbose);
+    // Hashes of the last build, so rebuilds only rewrite what changed
+    let mut hashes = None;
+    let compile_result = compile_file(&path, &output, &mut hashes, verbose);
     display_compile_result(&compile_result, clear);
 
     println!("\n👀 Watching for changes... (Ctrl+C to stop)\n");
@@ -859,7 +1110,7 @@ fn watch_and_compile(
             };
 
             println!("⚡ Recompiling...");
-            let compile_result = compile_file(&target_path, &output, verbose);
+            let compile_result = compile_file(&target_path, &output, &mut hashes, verbose);
             display_compile_result(&compile_result, clear);
 
             println!("\n👀 Watching for changes... (Ctrl+C to stop)\n");
@@ -867,7 +1118,28 @@ fn watch_and_compile(
     }
 }
 
-fn compile_file(path: &PathBuf, output_dir: &PathBuf, verbose: bool) -> CompileStats {
+/// `[build.entry]` from the nearest jounce.toml above the source file
+fn entry_config_for(source: &std::path::Path) -> Option<EntryConfig> {
+    use jounce_compiler::package_manager::PackageManager;
+
+    let start = source.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(std::path::Path::new("."));
+    let start = start.canonicalize().unwrap_or_else(|_| start.to_path_buf());
+    let project_root = start.ancestors().find(|dir| dir.join("jounce.toml").exists())?;
+    match PackageManager::new(project_root).load_manifest() {
+        Ok(manifest) => manifest.build.entry,
+        Err(e) => {
+            eprintln!("⚠️  Warning: Could not read {}: {}", project_root.join("jounce.toml").display(), e);
+            None
+        }
+    }
+}
+
+fn compile_file(
+    path: &PathBuf,
+    output_dir: &PathBuf,
+    previous: &mut Option<BuildHashes>,
+    verbose: bool,
+) -> CompileStats {
     let start = Instant::now();
     let mut stats = CompileStats::default();
 
@@ -895,10 +1167,18 @@ fn compile_file(path: &PathBuf, output_dir: &PathBuf, verbose: bool) -> CompileS
         }
     };
 
-    // Generate JavaScript
-    let emitter = JSEmitter::new(&program);
-    let server_js = emitter.generate_server_js();
-    let client_js = emitter.generate_client_js();
+    // Generate JavaScript and CSS
+    let output = match hmr::rebuild(&program) {
+        Ok(output) => output,
+        Err(e) => {
+            eprintln!("✗ Compile error: {:?}", e);
+            stats.success = false;
+            stats.duration_ms = start.elapsed().as_millis() as u64;
+            return stats;
+        }
+    };
+    let kind = RebuildKind::classify(previous.as_ref(), &output.hashes);
+    *previous = Some(output.hashes);
 
     // Create output directory if it doesn't exist
     if let Err(e) = fs::create_dir_all(output_dir) {
@@ -908,22 +1188,28 @@ fn compile_file(path: &PathBuf, output_dir: &PathBuf, verbose: bool) -> CompileS
         return stats;
     }
 
-    // Write output files
-    let server_path = output_dir.join("server.js");
-    let client_path = output_dir.join("client.js");
-
-    if let Err(e) = fs::write(&server_path, server_js) {
-        eprintln!("✗ Failed to write server.js: {}", e);
-        stats.success = false;
-        stats.duration_ms = start.elapsed().as_millis() as u64;
-        return stats;
+    // Write only the output files that changed, so the dev server pushes a
+    // style-only update for CSS edits and leaves the stylesheet alone otherwise
+    let mut written = Vec::new();
+    if kind.writes_js() {
+        written.push(("server.js", output.server_js));
+        written.push(("client.js", output.client_js));
+    }
+    if kind.writes_css() && !output.css.is_empty() {
+        written.push(("styles.css", output.css));
     }
 
-    if let Err(e) = fs::write(&client_path, client_js) {
-        eprintln!("✗ Failed to write client.js: {}", e);
-        stats.success = false;
-        stats.duration_ms = start.elapsed().as_millis() as u64;
-        return stats;
+    for (name, contents) in written {
+        let file_path = output_dir.join(name);
+        if let Err(e) = fs::write(&file_path, contents) {
+            eprintln!("✗ Failed to write {}: {}", name, e);
+            stats.success = false;
+            stats.duration_ms = start.elapsed().as_millis() as u64;
+            return stats;
+        }
+        if verbose {
+            println!("  → {}", file_path.display());
+        }
     }
 
     stats.compiled = 1;
@@ -931,8 +1217,12 @@ fn compile_file(path: &PathBuf, output_dir: &PathBuf, verbose: bool) -> CompileS
     stats.duration_ms = start.elapsed().as_millis() as u64;
 
     if verbose {
-        println!("  → {}", server_path.display());
-        println!("  → {}", client_path.display());
+        match kind {
+            RebuildKind::Unchanged => println!("  No output changes"),
+            RebuildKind::StyleOnly => println!("  CSS changed (style-only update)"),
+            RebuildKind::ScriptOnly => println!("  JS changed (styles unchanged)"),
+            RebuildKind::Full => {}
+        }
     }
 
     stats
@@ -1003,7 +1293,7 @@ fn run_tests(
     verbose: bool,
     filter: Option<String>,
 ) -> std::io::Result<()> {
-    use jounce_compiler::test_framework::{TestDiscovery, TestRunner, generate_assertion_library};
+    use jounce_compiler::test_framework::{TestDiscovery, TestRunner, generate_assertion_library, generate_snapshot_library};
 
     // Check if test directory exists
     if !test_path.exists() {
@@ -1206,6 +1496,8 @@ fn test_subtraction() {
     let mut test_js = String::new();
     test_js.push_str(&generate_assertion_library());
     test_js.push_str("\n\n");
+    test_js.push_str(&generate_snapshot_library());
+    test_js.push_str("\n\n");
     test_js.push_str(&test_functions_js);
     test_js.push_str("\n\n");
     test_js.push_str(&runner.generate_runner_code_js());
@@ -1213,6 +1505,8 @@ fn test_subtraction() {
     // Write executable test file
     let test_runner_path = temp_dir.join("test_runner.js");
     fs::write(&test_runner_path, test_js)?;
+    // The snapshot library escapes with the runtime's escape()
+    fs::write(temp_dir.join("shared-runtime.cjs"), runtime_files::shared_runtime_commonjs())?;
 
     if verbose {
         println!("📝 Test runner generated at {}", test_runner_path.display());
@@ -1401,6 +1695,15 @@ fn format_file(path: &PathBuf, mode: FormatMode) -> std::io::Result<FormatResult
     }
 }
 
+/// Source of the file a diagnostic points into: the file being compiled,
+/// or a module it imports
+fn diagnostic_source(diagnostic: &Diagnostic, path: &Path, source_code: &str) -> Option<String> {
+    match diagnostic.location.as_ref().map(|location| location.file.as_str()) {
+        Some(file) if file != path.to_string_lossy() => fs::read_to_string(file).ok(),
+        _ => Some(source_code.to_string()),
+    }
+}
+
 /// Visit all files in a directory recursively
 fn visit_dirs(dir: &PathBuf, cb: &mut dyn FnMut(&PathBuf)) -> std::io::Result<()> {
     if dir.is_dir() {
@@ -1417,6 +1720,74 @@ fn visit_dirs(dir: &PathBuf, cb: &mut dyn FnMut(&PathBuf)) -> std::io::Result<()
     Ok(())
 }
 
+fn extract_messages(path: PathBuf, output: PathBuf, min_length: usize) -> std::io::Result<()> {
+    let mut files = Vec::new();
+    if path.is_file() {
+        files.push(path.clone());
+    } else if path.is_dir() {
+        visit_dirs(&path, &mut |entry_path: &PathBuf| {
+            if entry_path.extension().is_some_and(|ext| ext == "jnc") {
+                files.push(entry_path.clone());
+            }
+        })?;
+    } else {
+        eprintln!("❌ Path not found: {}", path.display());
+        process::exit(1);
+    }
+    files.sort();
+
+    let mut catalog = i18n::Catalog::default();
+    let mut warnings = 0;
+    for file in &files {
+        let source = fs::read_to_string(file)?;
+        let mut lexer = Lexer::new(source.clone());
+        let program = match Parser::new(&mut lexer).parse_program() {
+            Ok(program) => program,
+            Err(e) => {
+                eprintln!("  ❌ Parse error in {}: {:?}", file.display(), e);
+                continue;
+            }
+        };
+        for diagnostic in i18n::extract(&program, &file.to_string_lossy(), min_length, &mut catalog) {
+            eprintln!("{}", diagnostic.display(Some(&source)));
+            warnings += 1;
+        }
+    }
+
+    // Every other catalog in the output directory is a locale's translations
+    let locales_dir = output.parent().map(PathBuf::from).unwrap_or_default();
+    let mut locale_files: Vec<PathBuf> = fs::read_dir(&locales_dir)
+        .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+        .unwrap_or_default();
+    locale_files.retain(|file| file.extension().is_some_and(|ext| ext == "json") && *file != output);
+    locale_files.sort();
+    for file in locale_files {
+        let locale = file.file_stem().unwrap_or_default().to_string_lossy().to_string();
+        let translations: std::collections::BTreeMap<String, String> = match serde_json::from_str(&fs::read_to_string(&file)?) {
+            Ok(translations) => translations,
+            Err(e) => {
+                eprintln!("  ⚠️  {} is not a translation catalog: {}", file.display(), e);
+                continue;
+            }
+        };
+        for diagnostic in i18n::check_translations(&catalog, &locale, &translations) {
+            let source = diagnostic.location.as_ref().and_then(|location| fs::read_to_string(&location.file).ok());
+            eprintln!("{}", diagnostic.display(source.as_deref()));
+            warnings += 1;
+        }
+    }
+
+    if !locales_dir.as_os_str().is_empty() {
+        fs::create_dir_all(&locales_dir)?;
+    }
+    fs::write(&output, catalog.to_json())?;
+    println!("✅ {} message(s) from {} file(s) written to {}", catalog.messages.len(), files.len(), output.display());
+    if warnings > 0 {
+        println!("⚠️  {} placeholder warning(s)", warnings);
+    }
+    Ok(())
+}
+
 fn lint_code(path: PathBuf, fix: bool) -> std::io::Result<()> {
     let mut issues = 0;
     let mut fixed = 0;
@@ -1603,8 +1974,9 @@ fn serve_project(port: u16, open: bool) -> Result<(), Box<dyn std::error::Error>
     Ok(())
 }
 
-fn generate_index_html() -> String {
-    r#"<!DOCTYPE html>
+/// The page shell; links styles.css only when the build wrote one.
+fn generate_index_html(link_stylesheet: bool) -> String {
+    let html = r#"<!DOCTYPE html>
 <html lang="en">
 <head>
     <meta charset="UTF-8">
@@ -1634,7 +2006,12 @@ fn generate_index_html() -> String {
     </div>
     <script type="module" src="client.js"></script>
 </body>
-</html>"#.to_string()
+</html>"#;
+    if link_stylesheet {
+        html.to_string()
+    } else {
+        html.replace("    <link rel=\"stylesheet\" href=\"styles.css\">\n", "")
+    }
 }
 
 fn run_doctor() {
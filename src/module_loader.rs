@@ -1,13 +1,16 @@
 // Module Loader for Jounce
 // Handles compile-time module resolution and import processing
 
-use crate::ast::{Program, Statement, FunctionDefinition, StructDefinition, EnumDefinition, ConstDeclaration, Identifier, UseStatement};
+use crate::ast::{Program, Statement, FunctionDefinition, StructDefinition, EnumDefinition, ConstDeclaration, ComponentDefinition, Identifier, UseItem};
+use crate::cache::BuildCache;
+use crate::diagnostics::{Label, SourceLocation};
 use crate::errors::CompileError;
 use crate::lexer::Lexer;
 use crate::parser::Parser;
 use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 /// Represents an exported symbol from a module
 #[derive(Debug, Clone)]
@@ -16,18 +19,73 @@ pub enum ExportedSymbol {
     Struct(StructDefinition),
     Enum(EnumDefinition),
     Const(ConstDeclaration),
+    Component(ComponentDefinition),
     Type(Identifier),
 }
 
+impl ExportedSymbol {
+    /// The same definition under another name (for `use a::B as C`)
+    pub fn renamed(mut self, name: &str) -> Self {
+        let ident = match &mut self {
+            ExportedSymbol::Function(func) => &mut func.name,
+            ExportedSymbol::Struct(struct_def) => &mut struct_def.name,
+            ExportedSymbol::Enum(enum_def) => &mut enum_def.name,
+            ExportedSymbol::Const(const_decl) => &mut const_decl.name,
+            ExportedSymbol::Component(component) => &mut component.name,
+            ExportedSymbol::Type(ident) => ident,
+        };
+        ident.value = name.to_string();
+        self
+    }
+
+    /// The definition as a top-level statement, ready to merge into a program
+    fn into_statement(self) -> Option<Statement> {
+        match self {
+            ExportedSymbol::Function(func) => Some(Statement::Function(func)),
+            ExportedSymbol::Struct(struct_def) => Some(Statement::Struct(struct_def)),
+            ExportedSymbol::Enum(enum_def) => Some(Statement::Enum(enum_def)),
+            ExportedSymbol::Const(const_decl) => Some(Statement::Const(const_decl)),
+            ExportedSymbol::Component(component) => Some(Statement::Component(component)),
+            // Type aliases - skip for now
+            // TODO: Add type alias support
+            ExportedSymbol::Type(_) => None,
+        }
+    }
+}
+
 /// Represents a loaded module with its exports
 #[derive(Debug, Clone)]
 pub struct Module {
     pub name: String,
     pub file_path: PathBuf,
     pub exports: HashMap<String, ExportedSymbol>,
+    /// File that defines each export (another module's for `pub use` re-exports)
+    pub origins: HashMap<String, PathBuf>,
+    /// Definitions the exports rely on that the module imports without re-exporting
+    pub dependencies: Vec<(String, ExportedSymbol)>,
+    /// Files this module's `use` declarations load, in source order
+    pub imports: Vec<PathBuf>,
     pub ast: Program,
 }
 
+/// A name brought into scope by a `use` declaration
+#[derive(Debug, Clone)]
+struct ResolvedImport {
+    /// Local name (the alias, if renamed)
+    name: String,
+    symbol: ExportedSymbol,
+    origin: PathBuf,
+    is_pub: bool,
+}
+
+/// Everything a program's `use` declarations resolve to
+#[derive(Debug, Default)]
+struct ResolvedImports {
+    items: Vec<ResolvedImport>,
+    dependencies: Vec<(String, ExportedSymbol)>,
+    files: Vec<PathBuf>,
+}
+
 /// Module loader for resolving and loading Jounce modules
 pub struct ModuleLoader {
     /// Root directory for package resolution (usually project root or aloha-shirts/)
@@ -38,6 +96,10 @@ pub struct ModuleLoader {
     loading_stack: HashSet<String>,
     /// Current file being processed (for relative path resolution)
     current_file: Option<PathBuf>,
+    /// Defining file of each name `merge_imports` brought in, keyed by local name
+    merged_origins: HashMap<String, PathBuf>,
+    /// On-disk cache of parsed modules, shared with the rest of the build
+    build_cache: Option<Arc<BuildCache>>,
 }
 
 impl ModuleLoader {
@@ -48,9 +110,17 @@ impl ModuleLoader {
             module_cache: HashMap::new(),
             loading_stack: HashSet::new(),
             current_file: None,
+            merged_origins: HashMap::new(),
+            build_cache: None,
         }
     }
 
+    /// Load modules' ASTs from (and store them in) an on-disk build cache
+    pub fn with_build_cache(mut self, cache: Arc<BuildCache>) -> Self {
+        self.build_cache = Some(cache);
+        self
+    }
+
     /// Set the current file being processed (for relative path resolution)
     pub fn set_current_file<P: AsRef<Path>>(&mut self, file_path: P) {
         self.current_file = Some(file_path.as_ref().to_path_buf());
@@ -186,16 +256,36 @@ impl ModuleLoader {
                 module_key, e
             )))?;
 
-        // Parse the module
-        let mut lexer = Lexer::new(source);
-        let mut parser = Parser::new(&mut lexer);
-        let mut ast = parser.parse_program()?;
-
-        // Process imports in this module (recursive)
-        let _nested_imports = self.merge_imports(&mut ast)?;
+        // Parse the module, unless an earlier build already did
+        let mut ast = match &self.build_cache {
+            Some(cache) => cache.parse(&file_path, &source)?,
+            None => {
+                let mut lexer = Lexer::new(source);
+                let mut parser = Parser::new(&mut lexer);
+                parser.parse_program()?
+            }
+        };
 
-        // Extract exports
-        let exports = self.extract_exports(&ast)?;
+        // Extract the module's own exports, then process its imports (recursive).
+        // Only `pub use` imports are re-exported; the rest are kept as dependencies
+        // so importers still get the definitions the exports call into.
+        let mut exports = self.extract_exports(&ast)?;
+        let imports = self.resolve_imports(&ast)?;
+        Self::insert_imports(&mut ast, &imports);
+
+        let mut origins: HashMap<String, PathBuf> = exports.keys()
+            .map(|name| (name.clone(), file_path.clone()))
+            .collect();
+        let mut dependencies = imports.dependencies;
+        let module_imports = dedup_files(imports.files);
+        for import in imports.items {
+            if import.is_pub && !exports.contains_key(&import.name) {
+                origins.insert(import.name.clone(), import.origin);
+                exports.insert(import.name, import.symbol);
+            } else {
+                dependencies.push((import.name, import.symbol));
+            }
+        }
 
         // Restore previous file context
         self.current_file = previous_file;
@@ -205,6 +295,9 @@ impl ModuleLoader {
             name: module_key.clone(),
             file_path,
             exports,
+            origins,
+            dependencies,
+            imports: module_imports,
             ast,
         };
 
@@ -237,6 +330,9 @@ impl ModuleLoader {
                 Statement::Const(const_decl) => {
                     exports.insert(const_decl.name.value.clone(), ExportedSymbol::Const(const_decl.clone()));
                 }
+                Statement::Component(component) => {
+                    exports.insert(component.name.value.clone(), ExportedSymbol::Component(component.clone()));
+                }
                 // TODO: Handle type aliases
                 _ => {}
             }
@@ -245,6 +341,27 @@ impl ModuleLoader {
         Ok(exports)
     }
 
+    /// Every module loaded so far
+    pub fn modules(&self) -> impl Iterator<Item = &Module> {
+        self.module_cache.values()
+    }
+
+    /// The file that defines each top-level name merged into a program:
+    /// explicit imports (by local name, so aliases resolve), plus everything
+    /// defined in a loaded module. Names defined by the program itself are absent.
+    pub fn item_origins(&self) -> HashMap<String, PathBuf> {
+        let mut origins: HashMap<String, PathBuf> = HashMap::new();
+        for module in self.module_cache.values() {
+            for (name, origin) in &module.origins {
+                if *origin == module.file_path {
+                    origins.insert(name.clone(), origin.clone());
+                }
+            }
+        }
+        origins.extend(self.merged_origins.iter().map(|(name, origin)| (name.clone(), origin.clone())));
+        origins
+    }
+
     /// Get a specific export from a module
     pub fn get_export(&mut self, module_path: &[String], symbol_name: &str) -> Result<ExportedSymbol, CompileError> {
         let module = self.load_module(module_path)?;
@@ -290,6 +407,25 @@ impl ModuleLoader {
         self.loading_stack.clear();
     }
 
+    /// Treat `use ui::Button;` as importing item `Button` from `ui` when
+    /// `ui::Button` is not itself a module
+    pub fn resolve_use_item(&self, item: UseItem) -> UseItem {
+        if let UseItem::Glob { module } = &item {
+            if let Some((name, parent)) = module.split_last() {
+                let parent_is_module = parent.last().is_some_and(|segment| segment != "." && segment != "..")
+                    && self.resolve_module_path(parent).is_ok();
+                if parent_is_module && self.resolve_module_path(module).is_err() {
+                    return UseItem::Name {
+                        module: parent.to_vec(),
+                        name: name.clone(),
+                        alias: name.clone(),
+                    };
+                }
+            }
+        }
+        item
+    }
+
     /// Merge imported module definitions into a program's AST
     ///
     /// This processes all `use` statements and adds the imported definitions
@@ -297,102 +433,248 @@ impl ModuleLoader {
     ///
     /// Returns: Vec<PathBuf> - List of file paths that were imported
     pub fn merge_imports(&mut self, program: &mut Program) -> Result<Vec<PathBuf>, CompileError> {
-        // Track imported files for dependency tracking
-        let mut imported_files: Vec<PathBuf> = Vec::new();
-
-        // First, collect all use statements and find insertion point
-        let mut use_statements: Vec<UseStatement> = Vec::new();
-        let mut last_use_index = 0;
+        let imports = self.resolve_imports(program)?;
+        Self::insert_imports(program, &imports);
+        self.merged_origins.extend(imports.items.iter().map(|import| (import.name.clone(), import.origin.clone())));
+        Ok(dedup_files(imports.files))
+    }
 
-        for (i, stmt) in program.statements.iter().enumerate() {
-            if let Statement::Use(use_stmt) = stmt {
-                use_statements.push(use_stmt.clone());
-                last_use_index = i + 1; // Insert after the last use statement
+    /// Resolve every `use` in a program to the definitions it names
+    fn resolve_imports(&mut self, program: &Program) -> Result<ResolvedImports, CompileError> {
+        let mut resolved = ResolvedImports::default();
+        // Glob imports, with the module path as written (for ambiguity errors)
+        let mut globbed: Vec<(ResolvedImport, String)> = Vec::new();
+
+        for stmt in &program.statements {
+            let Statement::Use(use_stmt) = stmt else { continue };
+
+            for item in use_stmt.items() {
+                match self.resolve_use_item(item) {
+                    UseItem::Name { module: module_path, name, alias } => {
+                        let module = self.load_module(&module_path)?;
+                        let symbol = module.exports.get(&name).cloned().ok_or_else(|| {
+                            CompileError::Generic(format!(
+                                "Symbol '{}' not found in module {}",
+                                name,
+                                display_module_path(&module_path)
+                            ))
+                        })?;
+                        resolved.items.push(ResolvedImport {
+                            symbol: symbol.renamed(&alias),
+                            origin: module.origins.get(&name).cloned().unwrap_or_else(|| module.file_path.clone()),
+                            name: alias,
+                            is_pub: use_stmt.is_pub,
+                        });
+                        resolved.dependencies.extend(module.dependencies.iter().cloned());
+                        resolved.files.push(module.file_path.clone());
+                    }
+                    UseItem::Glob { module: module_path } => {
+                        let module = self.load_module(&module_path)?;
+                        let mut exports: Vec<(&String, &ExportedSymbol)> = module.exports.iter().collect();
+                        exports.sort_by(|a, b| a.0.cmp(b.0));
+                        for (name, symbol) in exports {
+                            let import = ResolvedImport {
+                                name: name.clone(),
+                                symbol: symbol.clone(),
+                                origin: module.origins.get(name).cloned().unwrap_or_else(|| module.file_path.clone()),
+                                is_pub: use_stmt.is_pub,
+                            };
+                            globbed.push((import, display_module_path(&module_path)));
+                        }
+                        resolved.dependencies.extend(module.dependencies.iter().cloned());
+                        resolved.files.push(module.file_path.clone());
+                    }
+                }
             }
         }
 
-        // Keep track of which symbols we've already added to avoid duplicates
-        let mut imported_symbols: HashMap<String, bool> = HashMap::new();
-
-        // Collect all imported statements first
-        let mut statements_to_insert: Vec<Statement> = Vec::new();
-
-        // For each use statement, load the module and add imported definitions
-        for use_stmt in use_statements {
-            let module_path: Vec<String> = use_stmt.path.iter()
-                .map(|ident| ident.value.clone())
-                .collect();
-
-            // Load the module
-            let module = self.load_module(&module_path)?;
+        self.check_name_conflicts(program, &resolved.items)?;
 
-            // Track this imported file
-            imported_files.push(module.file_path.clone());
+        // Local definitions and explicit imports shadow glob imports; two globs
+        // bringing in different definitions under the same name are ambiguous
+        let mut shadowing = local_names(program);
+        shadowing.extend(resolved.items.iter().map(|import| import.name.clone()));
 
-            // Determine which symbols to import
-            let symbols_to_import: Vec<(String, ExportedSymbol)> = if use_stmt.imports.is_empty() {
-                // Wildcard import - import all exports
-                module.exports.clone().into_iter().collect()
-            } else {
-                // Selective import - only import specified symbols
-                use_stmt.imports.iter()
-                    .filter_map(|ident| {
-                        let name = ident.value.clone();
-                        module.exports.get(&name)
-                            .map(|export| (name, export.clone()))
-                    })
-                    .collect()
-            };
+        let mut sources: HashMap<&str, Vec<(&PathBuf, &str)>> = HashMap::new();
+        for (import, module) in &globbed {
+            if shadowing.contains(&import.name) {
+                continue;
+            }
+            let entry = sources.entry(import.name.as_str()).or_default();
+            if !entry.iter().any(|(origin, _)| **origin == import.origin) {
+                entry.push((&import.origin, module.as_str()));
+            }
+        }
 
-            // Add each imported symbol to the collection
-            for (symbol_name, export) in symbols_to_import {
-                // Skip if already imported
-                if imported_symbols.contains_key(&symbol_name) {
-                    continue;
-                }
+        let mut conflicts: Vec<(&str, Vec<&str>)> = sources.into_iter()
+            .filter(|(_, modules)| modules.len() > 1)
+            .map(|(name, modules)| (name, modules.into_iter().map(|(_, module)| module).collect()))
+            .collect();
+        if !conflicts.is_empty() {
+            conflicts.sort();
+            let listed: Vec<String> = conflicts.iter()
+                .map(|(name, modules)| format!("'{}' (from {})", name, modules.join(" and ")))
+                .collect();
+            let (first_name, first_modules) = &conflicts[0];
+            return Err(CompileError::Generic(format!(
+                "Ambiguous glob imports: {} is exported by more than one module; import the one you mean by name, e.g. `use {}::{};`",
+                listed.join(", "),
+                first_modules[0],
+                first_name
+            )));
+        }
 
-                // Add the definition to the collection based on symbol type
-                let statement = match export {
-                    ExportedSymbol::Function(func) => {
-                        Some(Statement::Function(func))
-                    }
-                    ExportedSymbol::Struct(struct_def) => {
-                        Some(Statement::Struct(struct_def))
-                    }
-                    ExportedSymbol::Enum(enum_def) => {
-                        Some(Statement::Enum(enum_def))
-                    }
-                    ExportedSymbol::Const(const_decl) => {
-                        Some(Statement::Const(const_decl))
-                    }
-                    ExportedSymbol::Type(_) => {
-                        // Type aliases - skip for now
-                        // TODO: Add type alias support
-                        None
-                    }
-                };
+        resolved.items.extend(globbed.into_iter()
+            .filter(|(import, _)| !shadowing.contains(&import.name))
+            .map(|(import, _)| import));
+        Ok(resolved)
+    }
 
-                if let Some(stmt) = statement {
-                    statements_to_insert.push(stmt);
+    /// Functions and components share one namespace, so `<Card/>` and
+    /// `Card(...)` always name the same item: a name defined twice in it, or
+    /// both defined and explicitly imported, is an error rather than one
+    /// definition silently winning
+    fn check_name_conflicts(&self, program: &Program, imports: &[ResolvedImport]) -> Result<(), CompileError> {
+        let file = self.current_file.clone().unwrap_or_default();
+        let locals = program.statements.iter().filter_map(|stmt| match stmt {
+            Statement::Function(func) => Some(("function", &func.name, &file)),
+            Statement::Component(comp) => Some(("component", &comp.name, &file)),
+            _ => None,
+        });
+        let imported = imports.iter().filter_map(|import| match &import.symbol {
+            ExportedSymbol::Function(func) => Some(("function", &func.name, &import.origin)),
+            ExportedSymbol::Component(comp) => Some(("component", &comp.name, &import.origin)),
+            _ => None,
+        }).zip(imports.iter().map(|import| import.name.as_str()));
+
+        let mut seen: HashMap<&str, (&str, &Identifier, &PathBuf)> = HashMap::new();
+        let items = locals.map(|item| (item, item.1.value.as_str())).chain(imported);
+        for (item, name) in items {
+            match seen.get(name) {
+                // The same definition imported twice
+                Some(first) if first.0 == item.0 && first.2 == item.2 && first.1.position == item.1.position => {}
+                Some(first) => return Err(name_conflict(name, *first, item)),
+                None => {
+                    seen.insert(name, item);
                 }
-
-                imported_symbols.insert(symbol_name, true);
             }
         }
+        Ok(())
+    }
+
+    /// Insert resolved definitions after the last `use`, skipping names the
+    /// program already defines and duplicates
+    fn insert_imports(program: &mut Program, imports: &ResolvedImports) {
+        let last_use_index = program.statements.iter()
+            .rposition(|stmt| matches!(stmt, Statement::Use(_)))
+            .map_or(0, |i| i + 1);
+
+        let mut seen = local_names(program);
+        let statements_to_insert: Vec<Statement> = imports.items.iter()
+            .map(|import| (&import.name, &import.symbol))
+            .chain(imports.dependencies.iter().map(|(name, symbol)| (name, symbol)))
+            .filter(|(name, _)| seen.insert((*name).clone()))
+            .filter_map(|(_, symbol)| symbol.clone().into_statement())
+            .collect();
 
         // Insert all imported statements after the last use statement
         // This ensures they're available before any code that uses them
-        for (i, stmt) in statements_to_insert.into_iter().enumerate() {
-            program.statements.insert(last_use_index + i, stmt);
-        }
+        program.statements.splice(last_use_index..last_use_index, statements_to_insert);
+    }
+}
 
-        Ok(imported_files)
+/// Error for two items under one name, pointing at both definitions
+fn name_conflict(name: &str, first: (&str, &Identifier, &PathBuf), second: (&str, &Identifier, &PathBuf)) -> CompileError {
+    let location = |(_, ident, file): (&str, &Identifier, &PathBuf)| SourceLocation {
+        file: file.to_string_lossy().to_string(),
+        ..SourceLocation::from_identifier(ident)
+    };
+    let message = if first.0 == second.0 {
+        format!("'{}' is defined more than once", name)
+    } else {
+        format!(
+            "'{}' is both a {} and a {}; functions and components share one namespace, so <{}/> and {}(...) would name different items",
+            name, first.0, second.0, name, name
+        )
+    };
+    CompileError::WithLocation {
+        message,
+        location: location(second),
+        suggestion: Some("rename one of them, or import one under another name with `use ... as`".to_string()),
+        labels: vec![Label {
+            location: location(first),
+            message: format!("{} '{}' first defined here", first.0, name),
+        }],
     }
 }
 
+/// Files in first-seen order, without repeats (one `use` per item can name the same module)
+fn dedup_files(files: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut seen = HashSet::new();
+    files.into_iter().filter(|file| seen.insert(file.clone())).collect()
+}
+
+/// Names of the top-level definitions in a program
+fn local_names(program: &Program) -> HashSet<String> {
+    program.statements.iter()
+        .filter_map(|stmt| match stmt {
+            Statement::Function(func) => Some(func.name.value.clone()),
+            Statement::Struct(struct_def) => Some(struct_def.name.value.clone()),
+            Statement::Enum(enum_def) => Some(enum_def.name.value.clone()),
+            Statement::Const(const_decl) => Some(const_decl.name.value.clone()),
+            Statement::Component(component) => Some(component.name.value.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Module path as written in source: `./ui::button`, `raven_store::store`
+fn display_module_path(module_path: &[String]) -> String {
+    let relative = module_path.iter().take_while(|segment| *segment == "." || *segment == "..").count();
+    let prefix: String = module_path[..relative].iter().map(|segment| format!("{}/", segment)).collect();
+    format!("{}{}", prefix, module_path[relative..].join("::"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::js_emitter::JSEmitter;
+
+    /// Write a tree of modules under a fresh temp dir and return its root
+    fn write_modules(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let root = std::env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&root);
+        for (path, source) in files {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, source).unwrap();
+        }
+        root
+    }
+
+    /// Parse `source` as `root/main.jnc` and merge its imports
+    fn merge(root: &Path, source: &str) -> Result<Program, CompileError> {
+        let mut lexer = Lexer::new(source.to_string());
+        let mut program = Parser::new(&mut lexer).parse_program()?;
+        let mut loader = ModuleLoader::new(root);
+        loader.set_current_file(root.join("main.jnc"));
+        loader.merge_imports(&mut program)?;
+        Ok(program)
+    }
+
+    fn defined_names(program: &Program) -> HashSet<String> {
+        local_names(program)
+    }
+
+    const BUTTON_MODULE: &str = r#"
+struct ButtonProps {
+    label: String,
+}
+
+component Button(label: String) {
+    <button>{label}</button>
+}
+"#;
 
     #[test]
     fn test_module_path_resolution() {
@@ -413,4 +695,121 @@ mod tests {
         assert!(path.to_string_lossy().contains("raven-router"));
         assert!(!path.to_string_lossy().contains("raven_router"));
     }
+
+    #[test]
+    fn test_nested_use_tree() {
+        let root = write_modules("jounce_use_tree_nested", &[
+            ("ui/button.jnc", BUTTON_MODULE),
+            ("ui/card.jnc", "fn card_width() -> i32 {\n    return 320;\n}\n\nfn card_height() -> i32 {\n    return 200;\n}\n"),
+        ]);
+        let source = "use ./ui::{button::{Button, ButtonProps}, card::*};\n";
+
+        let mut lexer = Lexer::new(source.to_string());
+        let program = Parser::new(&mut lexer).parse_program().unwrap();
+        let Statement::Use(use_stmt) = &program.statements[0] else { panic!("expected use") };
+        assert_eq!(use_stmt.items(), vec![
+            UseItem::Name { module: vec![".".into(), "ui".into(), "button".into()], name: "Button".into(), alias: "Button".into() },
+            UseItem::Name { module: vec![".".into(), "ui".into(), "button".into()], name: "ButtonProps".into(), alias: "ButtonProps".into() },
+            UseItem::Glob { module: vec![".".into(), "ui".into(), "card".into()] },
+        ]);
+
+        let program = merge(&root, source).unwrap();
+        let names = defined_names(&program);
+        for name in ["Button", "ButtonProps", "card_width", "card_height"] {
+            assert!(names.contains(name), "missing {} in {:?}", name, names);
+        }
+    }
+
+    #[test]
+    fn test_alias_used_in_jsx() {
+        let root = write_modules("jounce_use_tree_alias", &[("ui/button.jnc", BUTTON_MODULE)]);
+        let program = merge(&root, r#"
+use ./ui::button::Button as UiButton;
+
+component App() {
+    <UiButton label="Save" />
+}
+"#).unwrap();
+
+        let names = defined_names(&program);
+        assert!(names.contains("UiButton"));
+        assert!(!names.contains("Button"));
+
+        let client_js = JSEmitter::new(&program).generate_client_js();
+        assert!(client_js.contains("function UiButton("), "{}", client_js);
+        assert!(client_js.contains("h(UiButton"), "{}", client_js);
+    }
+
+    #[test]
+    fn test_pub_use_reexport() {
+        let root = write_modules("jounce_use_tree_reexport", &[
+            ("ui/button.jnc", BUTTON_MODULE),
+            ("ui/colors.jnc", "fn accent() -> String {\n    return \"teal\";\n}\n"),
+            ("ui/index.jnc", "pub use ./button::{Button, ButtonProps as Props};\nuse ./colors::{accent};\n\nfn themed() -> String {\n    return accent();\n}\n"),
+        ]);
+
+        let program = merge(&root, "use ./ui::index::{Button, Props, themed};\n").unwrap();
+        let names = defined_names(&program);
+        for name in ["Button", "Props", "themed", "accent"] {
+            assert!(names.contains(name), "missing {} in {:?}", name, names);
+        }
+
+        // A plain `use` inside the module is not re-exported
+        let err = merge(&root, "use ./ui::index::{accent};\n").unwrap_err();
+        assert!(err.to_string().contains("Symbol 'accent' not found in module ./ui::index"), "{}", err);
+    }
+
+    #[test]
+    fn test_ambiguous_glob_imports() {
+        let root = write_modules("jounce_use_tree_ambiguous", &[
+            ("a.jnc", "fn format_label() -> i32 {\n    return 1;\n}\n\nfn parse_label() -> i32 {\n    return 2;\n}\n\nfn only_a() -> i32 {\n    return 3;\n}\n"),
+            ("b.jnc", "fn format_label() -> i32 {\n    return 4;\n}\n\nfn parse_label() -> i32 {\n    return 5;\n}\n"),
+        ]);
+
+        let err = merge(&root, "use ./a::*;\nuse ./b::*;\n").unwrap_err().to_string();
+        assert!(err.contains("Ambiguous glob imports"), "{}", err);
+        assert!(err.contains("'format_label' (from ./a and ./b)"), "{}", err);
+        assert!(err.contains("'parse_label' (from ./a and ./b)"), "{}", err);
+        assert!(!err.contains("only_a"), "{}", err);
+
+        // Naming the ones you mean resolves the ambiguity
+        let program = merge(&root, "use ./a::*;\nuse ./b::*;\nuse ./b::{format_label, parse_label};\n").unwrap();
+        assert!(defined_names(&program).contains("only_a"));
+    }
+
+    #[test]
+    fn test_function_and_component_share_a_namespace() {
+        let root = write_modules("jounce_name_conflicts", &[
+            ("card_fn.jnc", "fn Card(title: String) -> String {\n    return title;\n}\n"),
+            ("card.jnc", "component Card(title: String) {\n    <div>{title}</div>\n}\n"),
+        ]);
+
+        // Imported from two modules: an error at both definitions
+        let err = merge(&root, "use ./card_fn::{Card};\nuse ./card::{Card};\n").unwrap_err();
+        let CompileError::WithLocation { message, location, labels, .. } = &err else {
+            panic!("expected a located error, got {:?}", err);
+        };
+        assert!(message.contains("'Card' is both a function and a component"), "{}", message);
+        assert_eq!((location.file.ends_with("card.jnc"), location.line), (true, 1));
+        assert!(labels[0].location.file.ends_with("card_fn.jnc"), "{:?}", labels);
+        assert_eq!(labels[0].message, "function 'Card' first defined here");
+        let diagnostic = err.to_diagnostic("main.jnc");
+        assert!(diagnostic.location.unwrap().file.ends_with("card.jnc"));
+
+        // Defined here and imported
+        let err = merge(&root, "use ./card::{Card};\n\nfn Card() -> i32 {\n    return 1;\n}\n").unwrap_err();
+        assert!(err.to_string().contains("'Card' is both a function and a component"), "{}", err);
+
+        // Defined twice in one file
+        let err = merge(&root, "fn Card() -> i32 {\n    return 1;\n}\n\ncomponent Card() {\n    <div></div>\n}\n").unwrap_err();
+        let CompileError::WithLocation { location, labels, .. } = &err else {
+            panic!("expected a located error, got {:?}", err);
+        };
+        assert_eq!((location.line, labels[0].location.line), (5, 1));
+
+        // One definition, imported twice or under another name, is fine
+        merge(&root, "use ./card::{Card};\nuse ./card::{Card};\n").unwrap();
+        let program = merge(&root, "use ./card_fn::{Card as card_title};\nuse ./card::{Card};\n").unwrap();
+        assert!(defined_names(&program).contains("card_title"));
+    }
 }
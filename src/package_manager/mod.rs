@@ -4,6 +4,7 @@
 pub mod registry;
 
 use serde::{Deserialize, Serialize};
+use crate::entry::EntryConfig;
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -71,6 +72,9 @@ pub struct BuildConfig {
     pub ssr: bool,
     #[serde(default)]
     pub hydrate: bool,
+    /// `[build.entry]`: the function or component the app starts from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entry: Option<EntryConfig>,
 }
 
 fn default_target() -> String {
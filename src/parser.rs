@@ -1,12 +1,16 @@
 use crate::ast::*;
+use crate::diagnostics::{Label, SourceLocation};
 use crate::errors::CompileError;
-use crate::lexer::Lexer;
-use crate::token::{Token, TokenKind};
-use std::collections::HashMap;
+use crate::html_entities;
+use crate::lexer::{LexErrorKind, Lexer};
+use crate::token::{Span, Token, TokenKind, KEYWORDS};
+use crate::void_elements::is_void_element;
+use std::collections::{HashMap, HashSet};
 
 #[derive(PartialEq, PartialOrd, Clone, Copy, Debug)]
 enum Precedence {
     Lowest,
+    Coalesce,    // ??
     LogicalOr,   // ||
     LogicalAnd,  // &&
     BitwiseOr,   // |
@@ -23,6 +27,7 @@ enum Precedence {
 lazy_static::lazy_static! {
     static ref PRECEDENCES: HashMap<TokenKind, Precedence> = {
         let mut m = HashMap::new();
+        m.insert(TokenKind::QuestionQuestion, Precedence::Coalesce);  // ??
         m.insert(TokenKind::PipePipe, Precedence::LogicalOr);     // ||
         m.insert(TokenKind::AmpAmp, Precedence::LogicalAnd);      // &&
         m.insert(TokenKind::Pipe, Precedence::BitwiseOr);         // |
@@ -47,28 +52,109 @@ lazy_static::lazy_static! {
     };
 }
 
+/// Elements whose text keeps its whitespace, as HTML renders it
+fn keeps_whitespace(tag: &str) -> bool {
+    matches!(tag, "pre" | "textarea")
+}
+
 pub struct Parser<'a> {
     lexer: &'a mut Lexer,
     current: Token,
     peek: Token,
+    /// The lexer as it was right after reading `current`, when that's a `>`
+    /// or `/>` that may end an element: `peek` was read inside the element,
+    /// and is read again from here once the parser leaves it
+    after_current: Option<Lexer>,
+    /// `>` operators in the JSX attribute expression being parsed, for the
+    /// "did a '>' end the tag?" diagnostic
+    jsx_attribute_angles: Option<Vec<Token>>,
+    /// The elements whose children are being read, outermost first: each
+    /// one's name, placed at its `<`. For the "unclosed <div>" diagnostics.
+    jsx_open_tags: Vec<Identifier>,
+    /// Errors parsing continued past, such as a keyword used as a name;
+    /// reported together with whatever stops the parse
+    recovered_errors: Vec<CompileError>,
+    /// Keywords the item being parsed used as names; they stand for those
+    /// names in expressions until the end of the item
+    recovered_names: HashSet<String>,
+    /// Doc comments read just before `current` and `peek`, for the item
+    /// they document
+    current_docs: Vec<Token>,
+    peek_docs: Vec<Token>,
+    /// The file's own `//!` docs
+    module_doc: Option<String>,
+    /// The type `Self` stands for: the type of the enclosing impl, or `Self`
+    /// itself in a trait
+    self_type: Option<String>,
+    /// At-rules skipped in the css! block being parsed, to be warned about
+    css_unknown_at_rules: Vec<CssUnknownAtRule>,
+    /// Set while reading a condition itself, where an `=` is a mistyped `==`
+    assign_is_eq: bool,
 }
 
 impl<'a> Parser<'a> {
     pub fn new(lexer: &'a mut Lexer) -> Self {
-        let current = lexer.next_token();
-        let peek = lexer.next_token();
-        Self { lexer, current, peek }
+        let (current, current_docs) = Self::lex(lexer);
+        let (peek, peek_docs) = Self::lex(lexer);
+        Self {
+            lexer,
+            current,
+            peek,
+            after_current: None,
+            jsx_attribute_angles: None,
+            jsx_open_tags: Vec::new(),
+            recovered_errors: Vec::new(),
+            recovered_names: HashSet::new(),
+            current_docs,
+            peek_docs,
+            module_doc: None,
+            self_type: None,
+            css_unknown_at_rules: Vec::new(),
+            assign_is_eq: false,
+        }
+    }
+
+    /// The `//!` doc comments opening the parsed file
+    pub fn module_doc(&self) -> Option<&str> {
+        self.module_doc.as_deref()
     }
 
     pub fn parse_program(&mut self) -> Result<Program, CompileError> {
+        self.module_doc = self.take_inner_doc();
         let mut statements = Vec::new();
         while self.current_token().kind != TokenKind::Eof {
-            statements.push(self.parse_statement()?);
+            match self.parse_statement() {
+                Ok(statement) => statements.push(statement),
+                Err(e) => return Err(self.take_errors(Some(e))),
+            }
+            self.recovered_names.clear();
+        }
+        // A comment running to the end of the file leaves no token to fail on
+        let unterminated_comments = self.lexer.errors().iter()
+            .filter(|error| error.kind == LexErrorKind::UnterminatedBlockComment)
+            .cloned()
+            .map(CompileError::from);
+        self.recovered_errors.extend(unterminated_comments);
+        if !self.recovered_errors.is_empty() {
+            return Err(self.take_errors(None));
         }
         Ok(Program { statements })
     }
 
+    /// The recovered errors, followed by the one that stopped the parse
+    fn take_errors(&mut self, fatal: Option<CompileError>) -> CompileError {
+        let mut errors = std::mem::take(&mut self.recovered_errors);
+        errors.extend(fatal);
+        if errors.len() == 1 {
+            errors.remove(0)
+        } else {
+            CompileError::Multiple(errors)
+        }
+    }
+
     fn parse_statement(&mut self) -> Result<Statement, CompileError> {
+        let doc = self.take_outer_doc()?;
+        self.use_recovered_name();
         let stmt = match self.current_token().kind {
             TokenKind::Use => self.parse_use_statement().map(Statement::Use),
             TokenKind::Struct => self.parse_struct_definition().map(Statement::Struct),
@@ -76,12 +162,9 @@ impl<'a> Parser<'a> {
             TokenKind::Impl => self.parse_impl_block().map(Statement::ImplBlock),
             TokenKind::Trait => self.parse_trait_definition().map(Statement::Trait),
             TokenKind::Component => self.parse_component_definition().map(Statement::Component),
-            TokenKind::At => {
-                // Annotations can be on functions or components
-                // Try parsing as function first, which handles @server/@client fn
-                // Components are marked with "component" keyword, not @client
-                self.parse_function_definition().map(Statement::Function)
-            }
+            TokenKind::Extern => self.parse_extern_block().map(Statement::ExternBlock),
+            TokenKind::At => self.parse_annotated_item(),
+            TokenKind::Pound => self.parse_attributed_item(),
             TokenKind::Fn | TokenKind::Server | TokenKind::Client | TokenKind::Async => self.parse_function_definition().map(Statement::Function),
             TokenKind::Let => self.parse_let_statement().map(Statement::Let),
             TokenKind::Const => self.parse_const_declaration().map(Statement::Const),
@@ -108,7 +191,13 @@ impl<'a> Parser<'a> {
             },
             TokenKind::Style => self.parse_style_block().map(Statement::Style),  // Phase 13
             TokenKind::Theme => self.parse_theme_block().map(Statement::Theme),  // Phase 13
-            TokenKind::Identifier => {
+            TokenKind::Pub if self.peek_token().kind == TokenKind::Use => {
+                // pub use a::B; re-exports B from this module
+                self.next_token(); // consume 'pub'
+                self.parse_use_statement().map(|use_stmt| Statement::Use(UseStatement { is_pub: true, ..use_stmt }))
+            },
+            TokenKind::Pub => self.parse_pub_item(),
+            TokenKind::Identifier | TokenKind::SelfValue | TokenKind::SelfType => {
                 // Parse as expression first, then check if it's actually an assignment
                 // This handles both simple assignments (x = 5) and complex ones (obj.field = 5, arr[0] = 5)
                 let expr = self.parse_expression(Precedence::Lowest)?;
@@ -123,6 +212,16 @@ impl<'a> Parser<'a> {
                     });
                     // Don't return early - let the semicolon be consumed below
                     Ok(stmt)
+                } else if let Some((kind, symbol)) = self.current_token().kind.compound_operator() {
+                    // `x += 1` is sugar for `x = x + 1`
+                    let token = self.current_token();
+                    let operator = Token::new(kind, symbol.to_string(), token.line, token.column);
+                    self.next_token(); // consume +=, -=, ...
+                    let value = self.parse_expression(Precedence::Lowest)?;
+                    Ok(Statement::Assignment(AssignmentStatement {
+                        target: expr.clone(),
+                        value: Expression::Infix(InfixExpression { left: Box::new(expr), operator, right: Box::new(value), assign_typo: false }),
+                    }))
                 } else {
                     // Otherwise it's just an expression statement
                     Ok(Statement::Expression(expr))
@@ -131,7 +230,91 @@ impl<'a> Parser<'a> {
             _ => self.parse_expression_statement().map(Statement::Expression),
         }?;
         self.consume_if_matches(&TokenKind::Semicolon);
-        Ok(stmt)
+        Ok(Self::attach_doc(stmt, doc))
+    }
+
+    /// Reads the next token, setting aside the doc comments before it
+    fn lex(lexer: &mut Lexer) -> (Token, Vec<Token>) {
+        let mut docs = Vec::new();
+        loop {
+            let token = lexer.next_token();
+            if !matches!(token.kind, TokenKind::DocComment { .. }) {
+                return (token, docs);
+            }
+            docs.push(token);
+        }
+    }
+
+    /// The `///` docs before the current token. A `//!` there is misplaced:
+    /// it documents what encloses it, so it opens a file or an item's body.
+    fn take_outer_doc(&mut self) -> Result<Option<String>, CompileError> {
+        let docs = std::mem::take(&mut self.current_docs);
+        if let Some(inner) = docs.iter().find(|doc| matches!(doc.kind, TokenKind::DocComment { inner: true, .. })) {
+            return Err(CompileError::ParserError {
+                message: "`//!` documents the enclosing item, so it goes at the start of a file or an item's body; use `///` for the item after it".to_string(),
+                line: inner.line,
+                column: inner.column,
+            });
+        }
+        Ok(Self::join_docs(&docs))
+    }
+
+    /// The `//!` docs before the current token, leaving any `///` ones
+    fn take_inner_doc(&mut self) -> Option<String> {
+        let (inner, outer) = std::mem::take(&mut self.current_docs)
+            .into_iter()
+            .partition(|doc| matches!(doc.kind, TokenKind::DocComment { inner: true, .. }));
+        self.current_docs = outer;
+        Self::join_docs(&inner)
+    }
+
+    /// One line per doc token, without the space after `///`; blank lines stay
+    fn join_docs(docs: &[Token]) -> Option<String> {
+        let lines: Vec<&str> = docs
+            .iter()
+            .filter_map(|doc| match &doc.kind {
+                TokenKind::DocComment { text, .. } => Some(text.strip_prefix(' ').unwrap_or(text)),
+                _ => None,
+            })
+            .collect();
+        (!lines.is_empty()).then(|| lines.join("\n"))
+    }
+
+    /// Puts the `///` docs before an item ahead of the `//!` ones from its body
+    fn attach_doc(mut stmt: Statement, outer: Option<String>) -> Statement {
+        if outer.is_none() {
+            return stmt;
+        }
+        let doc = match &mut stmt {
+            Statement::Function(function) => &mut function.doc,
+            Statement::Component(component) => &mut component.doc,
+            Statement::Struct(struct_def) => &mut struct_def.doc,
+            Statement::Enum(enum_def) => &mut enum_def.doc,
+            Statement::Trait(trait_def) => &mut trait_def.doc,
+            // Docs on other statements document nothing the AST keeps
+            _ => return stmt,
+        };
+        *doc = Self::attach_inner_doc(outer, doc.take());
+        stmt
+    }
+
+    fn attach_inner_doc(outer: Option<String>, inner: Option<String>) -> Option<String> {
+        match (outer, inner) {
+            (Some(outer), Some(inner)) => Some(format!("{}\n{}", outer, inner)),
+            (outer, inner) => outer.or(inner),
+        }
+    }
+
+    /// `pub fn`, `pub struct`, ... Every top-level item is already exported,
+    /// so the marker is accepted and dropped.
+    fn parse_pub_item(&mut self) -> Result<Statement, CompileError> {
+        self.expect_and_consume(&TokenKind::Pub)?;
+        match self.current_token().kind {
+            TokenKind::Fn | TokenKind::Server | TokenKind::Client | TokenKind::Async
+            | TokenKind::Struct | TokenKind::Enum | TokenKind::Trait | TokenKind::Component
+            | TokenKind::Const | TokenKind::At => self.parse_statement(),
+            _ => Err(self.error("`pub` goes before an item such as `fn`, `struct`, `enum` or `const`")),
+        }
     }
 
     fn parse_use_statement(&mut self) -> Result<UseStatement, CompileError> {
@@ -147,11 +330,11 @@ impl<'a> Parser<'a> {
                 if self.current_token().kind == TokenKind::DotDot {
                     // .. means parent directory
                     self.next_token();
-                    path.push(Identifier { value: "..".to_string() });
+                    path.push(Identifier::new(".."));
                 } else {
                     // . means current directory
                     self.next_token();
-                    path.push(Identifier { value: ".".to_string() });
+                    path.push(Identifier::new("."));
                 }
 
                 // Consume '/' if present (for ./file or ../file syntax)
@@ -174,21 +357,69 @@ impl<'a> Parser<'a> {
         // Parse the module path (identifiers separated by ::)
         path.push(self.parse_identifier()?);
         while self.consume_if_matches(&TokenKind::DoubleColon) {
-            if self.current_token().kind == TokenKind::LBrace { break; }
+            if matches!(self.current_token().kind, TokenKind::LBrace | TokenKind::Star) { break; }
             path.push(self.parse_identifier()?);
         }
 
-        // Parse selective imports { A, B, C }
+        // Parse the use-tree: { A, B as C, sub::{D}, * }, a glob, or `Name as Alias`
         let mut imports = Vec::new();
-        if self.consume_if_matches(&TokenKind::LBrace) {
-            while self.current_token().kind != TokenKind::RBrace {
-                imports.push(self.parse_identifier()?);
-                if !self.consume_if_matches(&TokenKind::Comma) { break; }
+        if self.current_token().kind == TokenKind::LBrace {
+            imports = self.parse_use_group()?;
+        } else if self.consume_if_matches(&TokenKind::Star) {
+            imports.push(UseTree::Glob);
+        } else if self.consume_if_matches(&TokenKind::As) {
+            let alias = self.parse_identifier()?;
+            let name = path.pop().filter(|_| !path.is_empty()).ok_or_else(|| {
+                self.error("Expected a module path before the aliased name, e.g. `use ui::Button as UiButton;`")
+            })?;
+            imports.push(UseTree::Name { name, alias: Some(alias) });
+        }
+
+        Ok(UseStatement { path, imports, is_pub: false })
+    }
+
+    /// Parse `{ tree, tree, ... }` in a use declaration
+    fn parse_use_group(&mut self) -> Result<Vec<UseTree>, CompileError> {
+        self.expect_and_consume(&TokenKind::LBrace)?;
+        let mut trees = Vec::new();
+        while self.current_token().kind != TokenKind::RBrace {
+            trees.push(self.parse_use_tree()?);
+            if !self.consume_if_matches(&TokenKind::Comma) { break; }
+        }
+        self.expect_and_consume(&TokenKind::RBrace)?;
+        Ok(trees)
+    }
+
+    /// Parse one entry of a use group: `*`, `Name`, `Name as Alias`, or `sub::tree`
+    fn parse_use_tree(&mut self) -> Result<UseTree, CompileError> {
+        if self.consume_if_matches(&TokenKind::Star) {
+            return Ok(UseTree::Glob);
+        }
+
+        let mut path = vec![self.parse_identifier()?];
+        while self.consume_if_matches(&TokenKind::DoubleColon) {
+            if self.current_token().kind == TokenKind::LBrace {
+                let items = self.parse_use_group()?;
+                return Ok(UseTree::Nested { path, items });
             }
-            self.expect_and_consume(&TokenKind::RBrace)?;
+            if self.consume_if_matches(&TokenKind::Star) {
+                return Ok(UseTree::Nested { path, items: vec![UseTree::Glob] });
+            }
+            path.push(self.parse_identifier()?);
         }
 
-        Ok(UseStatement { path, imports })
+        let alias = if self.consume_if_matches(&TokenKind::As) {
+            Some(self.parse_identifier()?)
+        } else {
+            None
+        };
+        let name = path.pop().unwrap();
+        let tree = UseTree::Name { name, alias };
+        if path.is_empty() {
+            Ok(tree)
+        } else {
+            Ok(UseTree::Nested { path, items: vec![tree] })
+        }
     }
     
     fn parse_type_params(&mut self) -> Result<Vec<TypeParam>, CompileError> {
@@ -197,7 +428,7 @@ impl<'a> Parser<'a> {
         }
 
         let mut type_params = Vec::new();
-        while self.current_token().kind != TokenKind::RAngle {
+        while !self.at_closing_angle() {
             let name = self.parse_identifier()?;
 
             // Parse optional trait bounds: T: Display or T: Display + Clone
@@ -218,7 +449,7 @@ impl<'a> Parser<'a> {
                 break;
             }
         }
-        self.expect_and_consume(&TokenKind::RAngle)?;
+        self.expect_closing_angle()?;
         Ok(type_params)
     }
 
@@ -229,14 +460,15 @@ impl<'a> Parser<'a> {
         self.expect_and_consume(&TokenKind::LBrace)?;
         let mut fields = Vec::new();
         while self.current_token().kind != TokenKind::RBrace {
-            let field_name = self.parse_identifier()?;
+            self.consume_if_matches(&TokenKind::Pub); // fields are always visible
+            let field_name = self.parse_name("a field name")?;
             self.expect_and_consume(&TokenKind::Colon)?;
             let field_type = self.parse_type_expression()?;
             fields.push((field_name, field_type));
             if !self.consume_if_matches(&TokenKind::Comma) { break; }
         }
         self.expect_and_consume(&TokenKind::RBrace)?;
-        Ok(StructDefinition { name, lifetime_params: Vec::new(), type_params, fields, derives: Vec::new() })
+        Ok(StructDefinition { name, doc: None, lifetime_params: Vec::new(), type_params, fields, derives: Vec::new(), attributes: Vec::new() })
     }
 
     fn parse_enum_definition(&mut self) -> Result<EnumDefinition, CompileError> {
@@ -247,6 +479,7 @@ impl<'a> Parser<'a> {
 
         let mut variants = Vec::new();
         while self.current_token().kind != TokenKind::RBrace {
+            let attributes = self.parse_attributes()?;
             let variant_name = self.parse_identifier()?;
 
             // Check if this variant has associated data
@@ -254,7 +487,8 @@ impl<'a> Parser<'a> {
                 // Struct-style variant: Name { field1: Type, field2: Type }
                 let mut variant_fields = Vec::new();
                 while self.current_token().kind != TokenKind::RBrace {
-                    let field_name = self.parse_identifier()?;
+                    self.consume_if_matches(&TokenKind::Pub); // fields are always visible
+                    let field_name = self.parse_name("a field name")?;
                     self.expect_and_consume(&TokenKind::Colon)?;
                     let field_type = self.parse_type_expression()?;
                     variant_fields.push((field_name, field_type));
@@ -270,7 +504,7 @@ impl<'a> Parser<'a> {
                     let field_type = self.parse_type_expression()?;
                     // For tuple variants, use numeric field names
                     variant_fields.push((
-                        Identifier { value: index.to_string() },
+                        Identifier::new(index.to_string()),
                         field_type,
                     ));
                     index += 1;
@@ -286,13 +520,14 @@ impl<'a> Parser<'a> {
             variants.push(EnumVariant {
                 name: variant_name,
                 fields,
+                attributes,
             });
 
             if !self.consume_if_matches(&TokenKind::Comma) { break; }
         }
         self.expect_and_consume(&TokenKind::RBrace)?;
 
-        Ok(EnumDefinition { name, lifetime_params: Vec::new(), type_params, variants, derives: Vec::new() })
+        Ok(EnumDefinition { name, doc: None, lifetime_params: Vec::new(), type_params, variants, derives: Vec::new(), attributes: Vec::new() })
     }
 
     fn parse_impl_block(&mut self) -> Result<ImplBlock, CompileError> {
@@ -328,9 +563,21 @@ impl<'a> Parser<'a> {
 
         self.expect_and_consume(&TokenKind::LBrace)?;
 
+        let outer_self_type = self.self_type.replace(type_name.value.clone());
+        let methods = self.parse_impl_methods();
+        self.self_type = outer_self_type;
+        let methods = methods?;
+
+        self.expect_and_consume(&TokenKind::RBrace)?;
+        Ok(ImplBlock { trait_name, lifetime_params: Vec::new(), type_params, type_name, methods })
+    }
+
+    fn parse_impl_methods(&mut self) -> Result<Vec<ImplMethod>, CompileError> {
         let mut methods = Vec::new();
         while self.current_token().kind != TokenKind::RBrace {
             // Parse method: fn method_name(...) -> ReturnType { body }
+            let doc = self.take_outer_doc()?;
+            self.consume_if_matches(&TokenKind::Pub); // methods are always visible
             self.expect_and_consume(&TokenKind::Fn)?;
             let method_name = self.parse_identifier()?;
 
@@ -338,12 +585,15 @@ impl<'a> Parser<'a> {
             self.expect_and_consume(&TokenKind::LParen)?;
             let mut parameters = Vec::new();
             while self.current_token().kind != TokenKind::RParen {
-                let param_name = self.parse_identifier()?;
+                let position = self.position();
+                let param_name = self.parse_method_parameter_name(parameters.is_empty())?;
                 self.expect_and_consume(&TokenKind::Colon)?;
                 let param_type = self.parse_type_expression()?;
                 parameters.push(FunctionParameter {
                     name: param_name,
                     type_annotation: param_type,
+                    position,
+                    is_slot: false,
                 });
                 if !self.consume_if_matches(&TokenKind::Comma) {
                     break;
@@ -360,6 +610,7 @@ impl<'a> Parser<'a> {
 
             // Parse method body (block statement)
             self.expect_and_consume(&TokenKind::LBrace)?;
+            let doc = Self::attach_inner_doc(doc, self.take_inner_doc());
             let mut statements = Vec::new();
             while self.current_token().kind != TokenKind::RBrace {
                 statements.push(self.parse_statement()?);
@@ -368,14 +619,13 @@ impl<'a> Parser<'a> {
 
             methods.push(ImplMethod {
                 name: method_name,
+                doc,
                 parameters,
                 return_type,
                 body: BlockStatement { statements },
             });
         }
-
-        self.expect_and_consume(&TokenKind::RBrace)?;
-        Ok(ImplBlock { trait_name, lifetime_params: Vec::new(), type_params, type_name, methods })
+        Ok(methods)
     }
 
     fn parse_trait_definition(&mut self) -> Result<TraitDefinition, CompileError> {
@@ -385,9 +635,20 @@ impl<'a> Parser<'a> {
         let type_params = self.parse_type_params()?;
         self.expect_and_consume(&TokenKind::LBrace)?;
 
+        let outer_self_type = self.self_type.replace("Self".to_string());
+        let methods = self.parse_trait_methods();
+        self.self_type = outer_self_type;
+        let methods = methods?;
+
+        self.expect_and_consume(&TokenKind::RBrace)?;
+        Ok(TraitDefinition { name, doc: None, lifetime_params: Vec::new(), type_params, methods })
+    }
+
+    fn parse_trait_methods(&mut self) -> Result<Vec<TraitMethod>, CompileError> {
         let mut methods = Vec::new();
         while self.current_token().kind != TokenKind::RBrace {
             // Parse method signature: fn method_name(...) -> ReturnType;
+            let doc = self.take_outer_doc()?;
             self.expect_and_consume(&TokenKind::Fn)?;
             let method_name = self.parse_identifier()?;
 
@@ -395,12 +656,15 @@ impl<'a> Parser<'a> {
             self.expect_and_consume(&TokenKind::LParen)?;
             let mut parameters = Vec::new();
             while self.current_token().kind != TokenKind::RParen {
-                let param_name = self.parse_identifier()?;
+                let position = self.position();
+                let param_name = self.parse_method_parameter_name(parameters.is_empty())?;
                 self.expect_and_consume(&TokenKind::Colon)?;
                 let param_type = self.parse_type_expression()?;
                 parameters.push(FunctionParameter {
                     name: param_name,
                     type_annotation: param_type,
+                    position,
+                    is_slot: false,
                 });
                 if !self.consume_if_matches(&TokenKind::Comma) {
                     break;
@@ -420,13 +684,12 @@ impl<'a> Parser<'a> {
 
             methods.push(TraitMethod {
                 name: method_name,
+                doc,
                 parameters,
                 return_type,
             });
         }
-
-        self.expect_and_consume(&TokenKind::RBrace)?;
-        Ok(TraitDefinition { name, lifetime_params: Vec::new(), type_params, methods })
+        Ok(methods)
     }
 
     fn parse_component_definition(&mut self) -> Result<ComponentDefinition, CompileError> {
@@ -443,17 +706,32 @@ impl<'a> Parser<'a> {
         self.expect_and_consume(&TokenKind::LParen)?;
         let mut parameters = Vec::new();
         while self.current_token().kind != TokenKind::RParen {
-            let param_name = self.parse_identifier()?;
+            let position = self.position();
+            // `slot header: Element`; a parameter can still be called `slot`
+            let is_slot = self.current_token().lexeme == "slot" && self.peek_token().kind != TokenKind::Colon;
+            if is_slot {
+                self.next_token();
+            }
+            let param_name = self.parse_name("a parameter name")?;
             self.expect_and_consume(&TokenKind::Colon)?;
             let param_type = self.parse_type_expression()?;
+            if is_slot && !Self::is_slot_type(&param_type) {
+                return Err(self.error(&format!(
+                    "Slot '{}' must be typed Element, or Option<Element> if callers may leave it out",
+                    param_name.value
+                )));
+            }
             parameters.push(FunctionParameter {
                 name: param_name,
                 type_annotation: param_type,
+                position,
+                is_slot,
             });
             if !self.consume_if_matches(&TokenKind::Comma) { break; }
         }
         self.expect_and_consume(&TokenKind::RParen)?;
         self.expect_and_consume(&TokenKind::LBrace)?;
+        let doc = self.take_inner_doc();
 
         // Parse component body as a block of statements
         let mut statements = Vec::new();
@@ -464,30 +742,176 @@ impl<'a> Parser<'a> {
 
         Ok(ComponentDefinition {
             name,
+            doc,
             parameters,
+            allows: Vec::new(),
             is_client,
             body: BlockStatement { statements },
         })
     }
 
-    fn parse_function_definition(&mut self) -> Result<FunctionDefinition, CompileError> {
-        // Check for optional @ symbol (for @server or @client)
-        let has_at = self.consume_if_matches(&TokenKind::At);
+    fn is_slot_type(ty: &TypeExpression) -> bool {
+        match ty {
+            TypeExpression::Named(name) => name.value == "Element",
+            TypeExpression::Generic(name, args) if name.value == "Option" => {
+                matches!(args.as_slice(), [TypeExpression::Named(inner)] if inner.value == "Element")
+            }
+            _ => false,
+        }
+    }
 
-        // Check for server/client annotations (with or without @)
-        let is_server = if has_at {
-            self.consume_if_matches(&TokenKind::Server)
-        } else {
-            self.consume_if_matches(&TokenKind::Server)
-        };
+    /// Parses an item that starts with an annotation: @allow(...) on functions
+    /// and components, and @server / @client / @memo on functions
+    fn parse_annotated_item(&mut self) -> Result<Statement, CompileError> {
+        match self.peek_token().lexeme.as_str() {
+            "allow" => {
+                self.next_token(); // consume '@'
+                let allows = self.parse_allow_list()?;
+                let mut item = match self.current_token().kind {
+                    TokenKind::Component => self.parse_component_definition().map(Statement::Component)?,
+                    TokenKind::At => self.parse_annotated_item()?,
+                    _ => self.parse_function_definition().map(Statement::Function)?,
+                };
+                match &mut item {
+                    Statement::Function(func) => func.allows.extend(allows),
+                    Statement::Component(comp) => comp.allows.extend(allows),
+                    _ => return Err(self.error("@allow can only be applied to a function or component")),
+                }
+                Ok(item)
+            }
+            // Components are marked with "component" keyword, not @client
+            _ => self.parse_function_definition().map(Statement::Function),
+        }
+    }
 
-        let is_client = if has_at && !is_server {
-            self.consume_if_matches(&TokenKind::Client)
-        } else if !has_at {
-            self.consume_if_matches(&TokenKind::Client)
-        } else {
-            false
-        };
+    /// Parses `allow(lint, ...)` after its `@`: the lints to silence for an
+    /// item, or for a JSX element when written among its attributes
+    fn parse_allow_list(&mut self) -> Result<Vec<String>, CompileError> {
+        self.next_token(); // consume 'allow'
+        self.expect_and_consume(&TokenKind::LParen)?;
+        let mut allows = Vec::new();
+        while self.current_token().kind != TokenKind::RParen {
+            allows.push(self.parse_identifier()?.value);
+            if !self.consume_if_matches(&TokenKind::Comma) { break; }
+        }
+        self.expect_and_consume(&TokenKind::RParen)?;
+        Ok(allows)
+    }
+
+    /// Parses `#[...]` attributes and the struct, enum or function after them.
+    /// `derive` sets the item's derives, and `route` means the same as `@route`.
+    fn parse_attributed_item(&mut self) -> Result<Statement, CompileError> {
+        let first = self.current_token().clone();
+        let attributes = self.parse_attributes()?;
+        let mut item = self.parse_statement()?;
+        match &mut item {
+            Statement::Struct(struct_def) => {
+                struct_def.derives.extend(Self::derive_names(&attributes));
+                struct_def.attributes.extend(attributes);
+            }
+            Statement::Enum(enum_def) => {
+                enum_def.derives.extend(Self::derive_names(&attributes));
+                enum_def.attributes.extend(attributes);
+            }
+            Statement::Function(func) => {
+                for attribute in attributes.iter().filter(|attribute| attribute.name.value == "route") {
+                    func.route = Some(Self::route_from_attribute(attribute)?);
+                }
+                func.attributes.extend(attributes);
+            }
+            _ => return Err(CompileError::ParserError {
+                message: "attributes can only be applied to a struct, enum, enum variant or function".to_string(),
+                line: first.line,
+                column: first.column,
+            }),
+        }
+        Ok(item)
+    }
+
+    /// Parses any `#[name]` or `#[name(...)]` attributes, keeping the tokens
+    /// inside the parentheses
+    fn parse_attributes(&mut self) -> Result<Vec<Attribute>, CompileError> {
+        let mut attributes = Vec::new();
+        while self.consume_if_matches(&TokenKind::Pound) {
+            self.expect_and_consume(&TokenKind::LBracket)?;
+            let name = self.parse_identifier()?;
+            let mut tokens = Vec::new();
+            if self.consume_if_matches(&TokenKind::LParen) {
+                let mut depth = 0;
+                loop {
+                    match self.current_token().kind {
+                        TokenKind::RParen if depth == 0 => break,
+                        TokenKind::LParen | TokenKind::LBracket | TokenKind::LBrace => depth += 1,
+                        TokenKind::RParen | TokenKind::RBracket | TokenKind::RBrace => depth -= 1,
+                        TokenKind::Eof => return Err(self.error(&format!("unclosed `(` in attribute `#[{}]`", name.value))),
+                        _ => {}
+                    }
+                    tokens.push(self.current_token().clone());
+                    self.next_token();
+                }
+                self.next_token(); // consume ')'
+            }
+            self.expect_and_consume(&TokenKind::RBracket)?;
+            attributes.push(Attribute { name, tokens });
+        }
+        Ok(attributes)
+    }
+
+    /// The names listed in `#[derive(...)]` attributes
+    fn derive_names(attributes: &[Attribute]) -> Vec<String> {
+        attributes
+            .iter()
+            .filter(|attribute| attribute.name.value == "derive")
+            .flat_map(|attribute| &attribute.tokens)
+            .filter(|token| token.kind == TokenKind::Identifier)
+            .map(|token| token.lexeme.clone())
+            .collect()
+    }
+
+    /// The path of a `#[route("/path")]` attribute
+    fn route_from_attribute(attribute: &Attribute) -> Result<RouteAttribute, CompileError> {
+        match attribute.tokens.as_slice() {
+            [token @ Token { kind: TokenKind::String(path), .. }] => Ok(RouteAttribute {
+                path: path.clone(),
+                position: Position { line: token.line, column: token.column },
+            }),
+            _ => Err(CompileError::ParserError {
+                message: "#[route] takes a string path, e.g. #[route(\"/todos/:id\")]".to_string(),
+                line: attribute.name.position.line,
+                column: attribute.name.position.column,
+            }),
+        }
+    }
+
+    fn parse_function_definition(&mut self) -> Result<FunctionDefinition, CompileError> {
+        // Annotations, with or without @: @server, @client, @memo / @memo(N),
+        // @route("/path")
+        let mut is_server = false;
+        let mut is_client = false;
+        let mut memo = None;
+        let mut allows = Vec::new();
+        let mut route = None;
+        loop {
+            let has_at = self.consume_if_matches(&TokenKind::At);
+            if !is_client && self.consume_if_matches(&TokenKind::Server) {
+                is_server = true;
+            } else if !is_server && self.consume_if_matches(&TokenKind::Client) {
+                is_client = true;
+            } else if has_at && self.current_token().lexeme == "memo" {
+                self.next_token(); // consume 'memo'
+                memo = Some(self.parse_memo_capacity()?);
+            } else if has_at && self.current_token().lexeme == "allow" {
+                allows.extend(self.parse_allow_list()?);
+            } else if has_at && self.current_token().lexeme == "route" {
+                route = Some(self.parse_route_attribute()?);
+            } else if has_at {
+                return Err(self.error(&format!(
+                    "Unknown function annotation '@{}'", self.current_token().lexeme
+                )));
+            } else {
+                break;
+            }
+        }
 
         let is_async = self.consume_if_matches(&TokenKind::Async);
 
@@ -501,22 +925,12 @@ impl<'a> Parser<'a> {
         let type_params = self.parse_type_params()?;
 
         // Parse parameter list
-        self.expect_and_consume(&TokenKind::LParen)?;
-        let mut parameters = Vec::new();
-        while self.current_token().kind != TokenKind::RParen {
-            let param_name = self.parse_identifier()?;
-            self.expect_and_consume(&TokenKind::Colon)?;
-            let param_type = self.parse_type_expression()?;
-            parameters.push(FunctionParameter {
-                name: param_name,
-                type_annotation: param_type,
-            });
-            if !self.consume_if_matches(&TokenKind::Comma) { break; }
-        }
-        self.expect_and_consume(&TokenKind::RParen)?;
+        let parameters = self.parse_parameter_list()?;
 
         // Parse optional return type (-> Type)
-        let _return_type = if self.consume_if_matches(&TokenKind::Arrow) {
+        let mut return_type_position = Position::default();
+        let return_type = if self.consume_if_matches(&TokenKind::Arrow) {
+            return_type_position = self.position();
             Some(self.parse_type_expression()?)
         } else {
             None
@@ -524,6 +938,7 @@ impl<'a> Parser<'a> {
 
         // Parse function body (block statement)
         self.expect_and_consume(&TokenKind::LBrace)?;
+        let doc = self.take_inner_doc();
         let mut statements = Vec::new();
         while self.current_token().kind != TokenKind::RBrace {
             statements.push(self.parse_statement()?);
@@ -532,9 +947,16 @@ impl<'a> Parser<'a> {
 
         Ok(FunctionDefinition {
             name,
+            doc,
             lifetime_params: Vec::new(),
             type_params,
             parameters,
+            return_type,
+            return_type_position,
+            memo,
+            allows,
+            route,
+            attributes: Vec::new(),
             is_server,
             is_client,
             is_async,
@@ -542,6 +964,86 @@ impl<'a> Parser<'a> {
         })
     }
 
+    /// Parses `(name: Type, ...)`
+    fn parse_parameter_list(&mut self) -> Result<Vec<FunctionParameter>, CompileError> {
+        self.expect_and_consume(&TokenKind::LParen)?;
+        let mut parameters = Vec::new();
+        while self.current_token().kind != TokenKind::RParen {
+            let position = self.position();
+            let param_name = self.parse_name("a parameter name")?;
+            self.expect_and_consume(&TokenKind::Colon)?;
+            let param_type = self.parse_type_expression()?;
+            parameters.push(FunctionParameter {
+                name: param_name,
+                type_annotation: param_type,
+                position,
+                is_slot: false,
+            });
+            if !self.consume_if_matches(&TokenKind::Comma) { break; }
+        }
+        self.expect_and_consume(&TokenKind::RParen)?;
+        Ok(parameters)
+    }
+
+    /// Parses `extern "js" { fn name(params) -> Type; ... }`: functions provided
+    /// by the host environment. The ABI string defaults to "js".
+    fn parse_extern_block(&mut self) -> Result<ExternBlock, CompileError> {
+        self.expect_and_consume(&TokenKind::Extern)?;
+        let abi = match &self.current_token().kind {
+            TokenKind::String(abi) => {
+                let abi = abi.clone();
+                self.next_token();
+                abi
+            }
+            _ => "js".to_string(),
+        };
+        self.expect_and_consume(&TokenKind::LBrace)?;
+
+        let mut functions = Vec::new();
+        while self.current_token().kind != TokenKind::RBrace {
+            self.expect_and_consume(&TokenKind::Fn)?;
+            let name = self.parse_identifier()?;
+            let parameters = self.parse_parameter_list()?;
+            let return_type = if self.consume_if_matches(&TokenKind::Arrow) {
+                Some(self.parse_type_expression()?)
+            } else {
+                None
+            };
+            self.expect_and_consume(&TokenKind::Semicolon)?;
+            functions.push(FunctionDeclaration { name, parameters, return_type });
+        }
+        self.expect_and_consume(&TokenKind::RBrace)?;
+
+        Ok(ExternBlock { abi, functions })
+    }
+
+    /// Parses `route("/path")` after its `@`
+    fn parse_route_attribute(&mut self) -> Result<RouteAttribute, CompileError> {
+        self.next_token(); // consume 'route'
+        self.expect_and_consume(&TokenKind::LParen)?;
+        let position = self.position();
+        let TokenKind::String(path) = self.current_token().kind.clone() else {
+            return Err(self.error("@route takes a string path, e.g. @route(\"/todos/:id\")"));
+        };
+        self.next_token();
+        self.expect_and_consume(&TokenKind::RParen)?;
+        Ok(RouteAttribute { path, position })
+    }
+
+    /// Parses the optional `(N)` after `@memo`
+    fn parse_memo_capacity(&mut self) -> Result<usize, CompileError> {
+        if !self.consume_if_matches(&TokenKind::LParen) {
+            return Ok(DEFAULT_MEMO_CAPACITY);
+        }
+        let capacity = match self.current_token().kind {
+            TokenKind::Integer(n) if n > 0 => n as usize,
+            _ => return Err(self.error("@memo capacity must be a positive integer, e.g. @memo(32)")),
+        };
+        self.next_token();
+        self.expect_and_consume(&TokenKind::RParen)?;
+        Ok(capacity)
+    }
+
     fn parse_type_expression(&mut self) -> Result<TypeExpression, CompileError> {
         // Check if this is a function type: fn(T1, T2) -> R or fn()
         if self.consume_if_matches(&TokenKind::Fn) {
@@ -638,14 +1140,20 @@ impl<'a> Parser<'a> {
             return Ok(TypeExpression::Tuple(types));
         }
 
+        if self.current_token().kind == TokenKind::SelfType {
+            let name = self.identifier_in_scope(self.current_token())?;
+            self.next_token();
+            return Ok(TypeExpression::Named(name));
+        }
+
         let name = self.parse_identifier()?;
         if self.consume_if_matches(&TokenKind::LAngle) {
             let mut args = Vec::new();
-            while self.current_token().kind != TokenKind::RAngle {
+            while !self.at_closing_angle() {
                 args.push(self.parse_type_expression()?);
                 if !self.consume_if_matches(&TokenKind::Comma) { break; }
             }
-            self.expect_and_consume(&TokenKind::RAngle)?;
+            self.expect_closing_angle()?;
             Ok(TypeExpression::Generic(name, args))
         } else {
             Ok(TypeExpression::Named(name))
@@ -677,7 +1185,7 @@ impl<'a> Parser<'a> {
         self.expect_and_consume(&TokenKind::Const)?;
 
         // Parse constant name (must be an identifier)
-        let name = self.parse_identifier()?;
+        let name = self.parse_name("a constant name")?;
 
         // Parse optional type annotation: const MAX_SIZE: i32 = 100
         let type_annotation = if self.consume_if_matches(&TokenKind::Colon) {
@@ -714,7 +1222,7 @@ impl<'a> Parser<'a> {
             Ok(Pattern::Tuple(patterns))
         } else {
             // Simple identifier pattern
-            let ident = self.parse_identifier()?;
+            let ident = self.parse_name("a variable name")?;
             Ok(Pattern::Identifier(ident))
         }
     }
@@ -738,7 +1246,7 @@ impl<'a> Parser<'a> {
         self.expect_and_consume(&TokenKind::If)?;
         // Disable struct literal parsing in if conditions to avoid ambiguity with the then block
         // Example: `if x { }` should parse `x` as condition and `{ }` as block, not `x {}` as struct literal
-        let condition = self.parse_expression_no_struct_literals()?;
+        let condition = self.parse_condition(false)?;
         self.expect_and_consume(&TokenKind::LBrace)?;
 
         // Parse then branch
@@ -780,7 +1288,7 @@ impl<'a> Parser<'a> {
 
     fn parse_while_statement(&mut self) -> Result<WhileStatement, CompileError> {
         self.expect_and_consume(&TokenKind::While)?;
-        let condition = self.parse_expression(Precedence::Lowest)?;
+        let condition = self.parse_condition(true)?;
         self.expect_and_consume(&TokenKind::LBrace)?;
 
         // Parse loop body
@@ -914,7 +1422,7 @@ impl<'a> Parser<'a> {
             if paren_level == 0 && brace_level == 0 { break; }
         }
         Ok(Statement::MacroInvocation(MacroInvocation {
-            name: Identifier { value: macro_token.lexeme },
+            name: Identifier::new(macro_token.lexeme),
             input_tokens,
         }))
     }
@@ -928,38 +1436,74 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_expression_internal(&mut self, precedence: Precedence, allow_struct_literals: bool) -> Result<Expression, CompileError> {
-        let mut left_expr = self.parse_prefix_internal(allow_struct_literals)?;
+        // A condition's `=` is read as `==` between its operands, not inside
+        // them: `if (x = 5)`, `if f(x = 5)` and `if |x| x = 5` keep theirs
+        let assign_is_eq = std::mem::take(&mut self.assign_is_eq);
+        let left_expr = self.parse_prefix_internal(allow_struct_literals);
+        self.assign_is_eq = assign_is_eq;
+        let mut left_expr = left_expr?;
         while self.current_token().kind != TokenKind::Semicolon && precedence < self.current_precedence() {
             left_expr = self.parse_infix(left_expr, allow_struct_literals)?;
         }
+
+        // Ternary: the whole binary expression is the condition (a > b ? x : y)
+        if precedence == Precedence::Lowest && self.current_token().kind == TokenKind::Question {
+            self.next_token(); // consume the ?
+            self.assign_is_eq = false;
+            let true_expr = self.parse_expression(Precedence::Lowest);
+            let branches = true_expr.and_then(|true_expr| {
+                self.expect_and_consume(&TokenKind::Colon)?;
+                Ok((true_expr, self.parse_expression_internal(Precedence::Lowest, allow_struct_literals)?))
+            });
+            self.assign_is_eq = assign_is_eq;
+            let (true_expr, false_expr) = branches?;
+            left_expr = Expression::Ternary(TernaryExpression {
+                condition: Box::new(left_expr),
+                true_expr: Box::new(true_expr),
+                false_expr: Box::new(false_expr),
+            });
+        }
         Ok(left_expr)
     }
 
+    /// The condition of an `if` or `while`, or of `&&` / `?:` in a JSX child.
+    /// There are no assignment expressions, so an `=` between its operands
+    /// (`if x = 5`) is a mistyped `==`: it's read as one, and warned about.
+    fn parse_condition(&mut self, allow_struct_literals: bool) -> Result<Expression, CompileError> {
+        self.assign_is_eq = true;
+        let condition = self.parse_expression_internal(Precedence::Lowest, allow_struct_literals);
+        self.assign_is_eq = false;
+        condition
+    }
+
     fn parse_prefix(&mut self) -> Result<Expression, CompileError> {
         self.parse_prefix_internal(true)
     }
 
     fn parse_prefix_internal(&mut self, allow_struct_literals: bool) -> Result<Expression, CompileError> {
+        self.use_recovered_name();
         let token = self.current_token().clone();
 
         // Step 1: Parse the base/atomic expression
         let mut expr = match &token.kind {
-            TokenKind::Identifier => {
+            TokenKind::Identifier | TokenKind::SelfValue | TokenKind::SelfType => {
+                let ident = self.identifier_in_scope(&token)?;
                 self.next_token();
-                let ident = Identifier { value: token.lexeme.clone() };
 
-                // Check for reactivity primitives (Phase 12)
+                // Check for reactivity primitives (Phase 12); not followed by
+                // `(` or `<`, the name is an ordinary variable (`computed.hash`)
+                let called = matches!(self.current_token().kind, TokenKind::LParen | TokenKind::LAngle);
                 match token.lexeme.as_str() {
-                    "signal" => {
+                    "signal" if called => {
                         return self.parse_signal_expression();
                     },
-                    "computed" => {
+                    "computed" if called => {
                         return self.parse_computed_expression();
                     },
-                    "effect" => {
+                    "effect" if called => {
                         return self.parse_effect_expression();
                     },
-                    "batch" => {
+                    "batch" if called => {
                         return self.parse_batch_expression();
                     },
                     _ => {}
@@ -974,7 +1518,20 @@ impl<'a> Parser<'a> {
             },
             TokenKind::Integer(val) => { self.next_token(); Expression::IntegerLiteral(*val) },
             TokenKind::Float(val) => { self.next_token(); Expression::FloatLiteral(val.clone()) },
+            // The lexer has range-checked the value, so only int vs float is left
+            TokenKind::TypedNumber(digits, suffix) if suffix.is_float() => { self.next_token(); Expression::FloatLiteral(digits.clone()) },
+            TokenKind::TypedNumber(digits, _) => { self.next_token(); Expression::IntegerLiteral(digits.parse().unwrap_or(0)) },
             TokenKind::String(val) => { self.next_token(); Expression::StringLiteral(val.clone()) },
+            TokenKind::TemplateStart => self.parse_template_literal()?,
+            // Bytes are integers; a byte string is the array of them
+            TokenKind::Byte(byte) => { self.next_token(); Expression::IntegerLiteral(i64::from(*byte)) },
+            TokenKind::ByteString(bytes) => {
+                let elements = bytes.iter().map(|byte| Expression::IntegerLiteral(i64::from(*byte))).collect();
+                self.next_token();
+                Expression::ArrayLiteral(ArrayLiteral { elements })
+            },
+            // JS has no char type, so a char is a one-character string
+            TokenKind::Char(ch) => { self.next_token(); Expression::StringLiteral(ch.to_string()) },
             TokenKind::Bool(val) => { self.next_token(); Expression::BoolLiteral(*val) },
             TokenKind::Minus | TokenKind::Bang => {
                 // Parse prefix expressions: -x or !x
@@ -1069,7 +1626,7 @@ impl<'a> Parser<'a> {
             TokenKind::If => {
                 // Parse if-expression: if cond { then_expr } else { else_expr }
                 self.next_token(); // consume if
-                let condition = Box::new(self.parse_expression(Precedence::Lowest)?);
+                let condition = Box::new(self.parse_condition(true)?);
 
                 // Parse then block
                 self.expect_and_consume(&TokenKind::LBrace)?;
@@ -1120,6 +1677,10 @@ impl<'a> Parser<'a> {
                 Expression::Block(BlockStatement { statements })
             },
             TokenKind::CssMacro => self.parse_css_macro()?,
+            TokenKind::InvalidNumber(message) | TokenKind::InvalidChar(message) | TokenKind::InvalidString(message)
+                | TokenKind::InvalidIdentifier(message) => {
+                return Err(self.error(message))
+            }
             _ => return Err(self.error(&format!("No prefix parse function for {:?}", token.kind))),
         };
 
@@ -1129,12 +1690,14 @@ impl<'a> Parser<'a> {
                 TokenKind::LParen => {
                     expr = self.parse_function_call(expr, None)?;
                 }
-                TokenKind::Dot => {
+                TokenKind::Dot | TokenKind::QuestionDot => {
+                    let optional = self.current_token().kind == TokenKind::QuestionDot;
                     self.next_token(); // consume the dot
-                    let field = self.parse_identifier()?;
+                    let field = self.parse_member_name()?;
                     expr = Expression::FieldAccess(FieldAccessExpression {
                         object: Box::new(expr),
                         field,
+                        optional,
                     });
                 }
                 TokenKind::DoubleColon => {
@@ -1146,11 +1709,11 @@ impl<'a> Parser<'a> {
                         let mut type_params = Vec::new();
 
                         // Parse type parameters
-                        while self.current_token().kind != TokenKind::RAngle {
+                        while !self.at_closing_angle() {
                             type_params.push(self.parse_type_expression()?);
                             if !self.consume_if_matches(&TokenKind::Comma) { break; }
                         }
-                        self.expect_and_consume(&TokenKind::RAngle)?;
+                        self.expect_closing_angle()?;
 
                         // After turbofish, we must have a function call
                         expr = self.parse_function_call(expr, Some(type_params))?;
@@ -1161,7 +1724,8 @@ impl<'a> Parser<'a> {
                         // Build the full namespaced identifier
                         if let Expression::Identifier(base_ident) = expr {
                             let namespaced_name = Identifier {
-                                value: format!("{}::{}", base_ident.value, next_ident.value)
+                                value: format!("{}::{}", base_ident.value, next_ident.value),
+                                position: base_ident.position,
                             };
                             expr = Expression::Identifier(namespaced_name);
                         } else {
@@ -1169,6 +1733,7 @@ impl<'a> Parser<'a> {
                             expr = Expression::FieldAccess(FieldAccessExpression {
                                 object: Box::new(expr),
                                 field: next_ident,
+                                optional: false,
                             });
                         }
                     }
@@ -1217,35 +1782,19 @@ impl<'a> Parser<'a> {
                     });
                 }
                 TokenKind::Question => {
-                    self.next_token(); // consume the ?
-
                     // Distinguish between try operator (x?) and ternary (x ? y : z)
                     // Try operator: ? is followed by semicolon, comma, closing brace/paren, or end of statement
-                    // Ternary: ? is followed by an expression
-                    match self.current_token().kind {
-                        TokenKind::Semicolon | TokenKind::Comma | TokenKind::RBrace |
+                    // Ternary: ? is followed by an expression, and binds looser than every
+                    // binary operator, so it's left to parse_expression_internal
+                    match self.peek_token().kind {
+                        TokenKind::Semicolon | TokenKind::Comma | TokenKind::RBrace | TokenKind::JsxCloseBrace |
                         TokenKind::RParen | TokenKind::RBracket | TokenKind::Eof => {
-                            // Try operator
+                            self.next_token(); // consume the ?
                             expr = Expression::TryOperator(TryOperatorExpression {
                                 expression: Box::new(expr),
                             });
                         }
-                        _ => {
-                            // Ternary operator - parse true branch
-                            let true_expr = Box::new(self.parse_expression(Precedence::Lowest)?);
-
-                            // Expect colon
-                            self.expect_and_consume(&TokenKind::Colon)?;
-
-                            // Parse false branch
-                            let false_expr = Box::new(self.parse_expression(Precedence::Lowest)?);
-
-                            expr = Expression::Ternary(TernaryExpression {
-                                condition: Box::new(expr),
-                                true_expr,
-                                false_expr,
-                            });
-                        }
+                        _ => break,
                     }
                 }
                 TokenKind::Bang => {
@@ -1292,6 +1841,9 @@ impl<'a> Parser<'a> {
         };
 
         self.expect_and_consume(&open_delimiter)?;
+        if name.value == "classes" {
+            return self.parse_classes_macro(&close_delimiter);
+        }
         let mut arguments = Vec::new();
 
         while self.current_token().kind != close_delimiter {
@@ -1301,12 +1853,71 @@ impl<'a> Parser<'a> {
 
         self.expect_and_consume(&close_delimiter)?;
 
+        // The message is the catalog key, so it must be known at compile time
+        if name.value == "t" && !matches!(arguments.first(), Some(Expression::StringLiteral(_))) {
+            return Err(self.error("t! takes a string literal message first, e.g. t!(\"Add {count} items\", count)"));
+        }
+
         Ok(Expression::MacroCall(MacroCall {
             name,
             arguments,
         }))
     }
 
+    /// `classes!` entries: `"class"` or `"class" => condition`, comma separated
+    fn parse_classes_macro(&mut self, close_delimiter: &TokenKind) -> Result<Expression, CompileError> {
+        let mut entries = Vec::new();
+        while &self.current_token().kind != close_delimiter {
+            let class = self.parse_expression(Precedence::Lowest)?;
+            let condition = if self.consume_if_matches(&TokenKind::FatArrow) {
+                Some(self.parse_expression(Precedence::Lowest)?)
+            } else {
+                None
+            };
+            entries.push(ClassEntry { class, condition });
+            if !self.consume_if_matches(&TokenKind::Comma) { break; }
+        }
+        self.expect_and_consume(close_delimiter)?;
+        Ok(Expression::Classes(ClassesMacro { entries }))
+    }
+
+    /// Parse `["btn", size, {"disabled": !enabled}]` in a class attribute,
+    /// from its `[`, into the entries of a classes! macro: each item is a
+    /// class, and each `class: condition` of a map is a class kept while its
+    /// condition holds. A map's keys are class names, quoted or bare.
+    fn parse_class_list(&mut self) -> Result<Expression, CompileError> {
+        self.expect_and_consume(&TokenKind::LBracket)?;
+        let mut entries = Vec::new();
+        while self.current_token().kind != TokenKind::RBracket {
+            if !self.consume_if_matches(&TokenKind::LBrace) {
+                let class = self.parse_expression(Precedence::Lowest)?;
+                entries.push(ClassEntry { class, condition: None });
+            } else {
+                while self.current_token().kind != TokenKind::RBrace {
+                    let key = self.current_token().clone();
+                    let class = match &key.kind {
+                        TokenKind::String(class) => class.clone(),
+                        TokenKind::Identifier => key.lexeme.clone(),
+                        _ => return Err(CompileError::ParserError {
+                            message: format!("Expected a class name in the class map, found `{}`; write `\"name\": condition`", key.lexeme),
+                            line: key.line,
+                            column: key.column,
+                        }),
+                    };
+                    self.next_token();
+                    self.expect_and_consume(&TokenKind::Colon)?;
+                    let condition = self.parse_expression(Precedence::Lowest)?;
+                    entries.push(ClassEntry { class: Expression::StringLiteral(class), condition: Some(condition) });
+                    if !self.consume_if_matches(&TokenKind::Comma) { break; }
+                }
+                self.expect_and_consume(&TokenKind::RBrace)?;
+            }
+            if !self.consume_if_matches(&TokenKind::Comma) { break; }
+        }
+        self.expect_and_consume(&TokenKind::RBracket)?;
+        Ok(Expression::Classes(ClassesMacro { entries }))
+    }
+
     fn parse_lambda_or_grouped(&mut self) -> Result<Expression, CompileError> {
         self.expect_and_consume(&TokenKind::LParen)?;
 
@@ -1315,7 +1926,7 @@ impl<'a> Parser<'a> {
             // Empty parameter list for lambda: () =>
             self.expect_and_consume(&TokenKind::RParen)?;
             if self.consume_if_matches(&TokenKind::FatArrow) {
-                let body = self.parse_expression(Precedence::Lowest)?;
+                let body = self.parse_lambda_body()?;
                 return Ok(Expression::Lambda(LambdaExpression {
                     parameters: vec![],
                     return_type: None,
@@ -1360,7 +1971,7 @@ impl<'a> Parser<'a> {
                 };
 
                 self.expect_and_consume(&TokenKind::FatArrow)?;
-                let body = self.parse_expression(Precedence::Lowest)?;
+                let body = self.parse_lambda_body()?;
 
                 return Ok(Expression::Lambda(LambdaExpression {
                     parameters,
@@ -1397,7 +2008,7 @@ impl<'a> Parser<'a> {
         // Check if this is actually a lambda with single param: (x) => body
         if self.consume_if_matches(&TokenKind::FatArrow) {
             if let Expression::Identifier(param_name) = first_expr {
-                let body = self.parse_expression(Precedence::Lowest)?;
+                let body = self.parse_lambda_body()?;
                 return Ok(Expression::Lambda(LambdaExpression {
                     parameters: vec![LambdaParameter { name: param_name, type_annotation: None }],
                     return_type: None,
@@ -1442,7 +2053,7 @@ impl<'a> Parser<'a> {
         // Check for => or just use the next expression
         self.consume_if_matches(&TokenKind::FatArrow);
 
-        let body = self.parse_expression(Precedence::Lowest)?;
+        let body = self.parse_lambda_body()?;
         Ok(Expression::Lambda(LambdaExpression {
             parameters,
             return_type,
@@ -1451,10 +2062,24 @@ impl<'a> Parser<'a> {
         }))
     }
 
-    fn parse_array_literal(&mut self) -> Result<Expression, CompileError> {
-        self.expect_and_consume(&TokenKind::LBracket)?;
-        let mut elements = Vec::new();
-
+    /// A lambda's body: an expression, or an assignment like
+    /// `() => count.value = count.value + 1`, which becomes a block holding it
+    fn parse_lambda_body(&mut self) -> Result<Expression, CompileError> {
+        let body = self.parse_expression(Precedence::Lowest)?;
+        if self.current_token().kind != TokenKind::Assign {
+            return Ok(body);
+        }
+        self.next_token(); // consume =
+        let value = self.parse_expression(Precedence::Lowest)?;
+        Ok(Expression::Block(BlockStatement {
+            statements: vec![Statement::Assignment(AssignmentStatement { target: body, value })],
+        }))
+    }
+
+    fn parse_array_literal(&mut self) -> Result<Expression, CompileError> {
+        self.expect_and_consume(&TokenKind::LBracket)?;
+        let mut elements = Vec::new();
+
         // Handle empty array []
         if self.current_token().kind == TokenKind::RBracket {
             self.expect_and_consume(&TokenKind::RBracket)?;
@@ -1485,7 +2110,11 @@ impl<'a> Parser<'a> {
 
         // Parse comma-separated field: value pairs
         while self.current_token().kind != TokenKind::RBrace {
-            let field_name = self.parse_identifier()?;
+            let field_name = if self.peek_token().kind == TokenKind::Colon {
+                self.parse_member_name()?
+            } else {
+                self.parse_identifier()?
+            };
 
             // Check for field shorthand: if followed by comma or }, use field_name as both key and value
             if self.current_token().kind == TokenKind::Comma || self.current_token().kind == TokenKind::RBrace {
@@ -1513,9 +2142,9 @@ impl<'a> Parser<'a> {
     fn parse_simple_expression(&mut self) -> Result<Expression, CompileError> {
         let token = self.current_token().clone();
         match &token.kind {
-            TokenKind::Identifier => {
+            TokenKind::Identifier | TokenKind::SelfValue | TokenKind::SelfType => {
+                let ident = self.identifier_in_scope(&token)?;
                 self.next_token();
-                let ident = Identifier { value: token.lexeme };
                 let mut expr = Expression::Identifier(ident);
 
                 // Check for postfix operations but NOT struct literals
@@ -1524,12 +2153,14 @@ impl<'a> Parser<'a> {
                         TokenKind::LParen => {
                             expr = self.parse_function_call(expr, None)?;
                         }
-                        TokenKind::Dot => {
+                        TokenKind::Dot | TokenKind::QuestionDot => {
+                            let optional = self.current_token().kind == TokenKind::QuestionDot;
                             self.next_token();
-                            let field = self.parse_identifier()?;
+                            let field = self.parse_member_name()?;
                             expr = Expression::FieldAccess(FieldAccessExpression {
                                 object: Box::new(expr),
                                 field,
+                                optional,
                             });
                         }
                         TokenKind::DoubleColon => {
@@ -1537,7 +2168,8 @@ impl<'a> Parser<'a> {
                             let next_ident = self.parse_identifier()?;
                             if let Expression::Identifier(base_ident) = expr {
                                 expr = Expression::Identifier(Identifier {
-                                    value: format!("{}::{}", base_ident.value, next_ident.value)
+                                    value: format!("{}::{}", base_ident.value, next_ident.value),
+                                    position: base_ident.position,
                                 });
                             }
                         }
@@ -1632,8 +2264,9 @@ impl<'a> Parser<'a> {
                 Ok(Pattern::Wildcard)
             }
             // Identifier (variable binding or enum variant)
-            TokenKind::Identifier => {
-                let first_ident = self.parse_identifier()?;
+            TokenKind::Identifier | TokenKind::SelfType => {
+                let first_ident = self.identifier_in_scope(&token)?;
+                self.next_token();
 
                 // Check for :: (enum variant like Result::Ok)
                 if self.consume_if_matches(&TokenKind::DoubleColon) {
@@ -1654,7 +2287,8 @@ impl<'a> Parser<'a> {
 
                     // Combine enum name and variant name
                     let full_name = Identifier {
-                        value: format!("{}::{}", first_ident.value, variant_name.value)
+                        value: format!("{}::{}", first_ident.value, variant_name.value),
+                        position: first_ident.position,
                     };
 
                     Ok(Pattern::EnumVariant {
@@ -1681,7 +2315,7 @@ impl<'a> Parser<'a> {
                 }
             }
             // Literal patterns
-            TokenKind::Integer(_) | TokenKind::Float(_) | TokenKind::String(_) |
+            TokenKind::Integer(_) | TokenKind::Float(_) | TokenKind::TypedNumber(..) | TokenKind::String(_) | TokenKind::Char(_) | TokenKind::Byte(_) |
             TokenKind::Bool(_) | TokenKind::True | TokenKind::False => {
                 let literal_expr = self.parse_expression(Precedence::Lowest)?;
                 Ok(Pattern::Literal(literal_expr))
@@ -1706,15 +2340,38 @@ impl<'a> Parser<'a> {
             }));
         }
 
+        if operator.kind == TokenKind::RAngle {
+            if let Some(angles) = self.jsx_attribute_angles.as_mut() {
+                angles.push(operator.clone());
+            }
+        }
+
         let precedence = self.current_precedence();
         self.next_token();
         let right = self.parse_expression_internal(precedence, allow_struct_literals)?;
-        Ok(Expression::Infix(InfixExpression { left: Box::new(left), operator, right: Box::new(right) }))
+        let assign_typo = operator.kind == TokenKind::Assign;
+        let operator = if assign_typo { Token { kind: TokenKind::Eq, lexeme: "==".to_string(), ..operator } } else { operator };
+        Ok(Expression::Infix(InfixExpression { left: Box::new(left), operator, right: Box::new(right), assign_typo }))
     }
 
     fn parse_jsx_element(&mut self) -> Result<Expression, CompileError> {
+        // An element in an expression starts a tree of its own: a closing tag
+        // inside it never closes an element around the expression
+        let outer = std::mem::take(&mut self.jsx_open_tags);
+        let element = self.parse_jsx_child_element();
+        self.jsx_open_tags = outer;
+        let element = element?;
+        // No `<` and `/` can follow an expression, so this is `<br></br>`
+        if element.opening_tag.slash_omitted && self.current_token().kind == TokenKind::LAngle && self.peek_token().kind == TokenKind::Slash {
+            return Err(Self::void_closing_tag_error(&element.opening_tag.name.value, self.current_token()));
+        }
+        Ok(Expression::JsxElement(element))
+    }
+
+    fn parse_jsx_child_element(&mut self) -> Result<JsxElement, CompileError> {
         // Check if we need to enter JSX mode for the root element
         let was_jsx_mode = self.lexer.is_jsx_mode();
+        let start = self.position();
 
         let opening_tag = self.parse_jsx_opening_tag_with_mode_check(was_jsx_mode)?;
 
@@ -1722,24 +2379,143 @@ impl<'a> Parser<'a> {
         // parse_jsx_opening_tag_with_mode_check() BEFORE consuming the final token,
         // to prevent lexer from generating JSX text tokens in the lookahead buffer
 
-        let children = if opening_tag.self_closing { vec![] } else { self.parse_jsx_children()? };
-        let closing_tag = if opening_tag.self_closing { None } else {
-            Some(self.parse_jsx_closing_tag_with_mode_check(was_jsx_mode)?)
+        if opening_tag.self_closing {
+            return Ok(JsxElement { opening_tag, children: vec![], closing_tag: None, unknown_entities: vec![] });
+        }
+        self.jsx_open_tags.push(Identifier { value: opening_tag.name.value.clone(), position: start });
+        let first_child = self.current_token().clone();
+        let children = self.parse_jsx_children();
+        if keeps_whitespace(&opening_tag.name.value) {
+            self.lexer.exit_raw_text_mode();
+        }
+        if is_raw_text_element(&opening_tag.name.value) {
+            self.lexer.exit_raw_element_mode();
+        }
+        let opened = self.jsx_open_tags.pop().expect("pushed above");
+        let (children, mut unknown_entities) = children?;
+        let closing_tag = self.parse_jsx_closing_tag_for(&opened, was_jsx_mode)?;
+        // The text of a <style> or <script> is kept as written, references and all
+        if is_raw_text_element(&opening_tag.name.value) {
+            unknown_entities.clear();
+        }
+
+        let element = JsxElement { opening_tag, children, closing_tag: Some(closing_tag), unknown_entities };
+        if let Some(attr) = element.unsafe_html().filter(|_| !element.children.is_empty()) {
+            self.recovered_errors.push(Self::unsafe_html_with_children_error(element.tag_name(), attr, &first_child));
+        }
+        Ok(element)
+    }
+
+    /// `<div unsafe_html={markup}>text</div>`: the markup replaces the
+    /// children, so they can't both be there
+    fn unsafe_html_with_children_error(tag: &str, attr: &JsxAttribute, first_child: &Token) -> CompileError {
+        // Text starts with the whitespace after the tag; point past it
+        let blank = &first_child.lexeme[..first_child.lexeme.len() - first_child.lexeme.trim_start().len()];
+        let (line, column) = match blank.rfind('\n') {
+            Some(newline) => (first_child.line + blank.matches('\n').count(), blank[newline + 1..].chars().count() + 1),
+            None => (first_child.line, first_child.column + blank.chars().count()),
+        };
+        CompileError::WithLocation {
+            message: format!("<{}> has both `{}` and children", tag, UNSAFE_HTML),
+            location: SourceLocation { file: String::new(), line, column, length: 1 },
+            suggestion: Some(format!("`{}` replaces the element's contents; remove the children, or make them part of the markup", UNSAFE_HTML)),
+            labels: vec![Label {
+                location: SourceLocation::from_identifier(&attr.name),
+                message: format!("`{}` set here", UNSAFE_HTML),
+            }],
+        }
+    }
+
+    /// The closing tag of the element `opened` (its name, placed at its `<`).
+    /// Another element's closing tag there is reported. If it closes an
+    /// element further out, `opened` is taken to be missing its own: it ends
+    /// here, and the closing tag is left to the element it belongs to.
+    fn parse_jsx_closing_tag_for(&mut self, opened: &Identifier, was_jsx_mode: bool) -> Result<Identifier, CompileError> {
+        let angle = self.current_token().clone();
+        let checkpoint = (self.lexer.clone(), self.current.clone(), self.peek.clone());
+        let name = self.parse_jsx_closing_tag_with_mode_check(was_jsx_mode)?;
+        if name.value == opened.value {
+            return Ok(name);
+        }
+
+        if is_void_element(&name.value) {
+            return Err(Self::void_closing_tag_error(&name.value, &angle));
+        }
+        let closes_outer = self.jsx_open_tags.iter().any(|open| open.value == name.value);
+        let error = Self::unclosed_element_error(opened, &angle, &name.value, closes_outer);
+        if !closes_outer {
+            return Err(error);
+        }
+        self.recovered_errors.push(error);
+        (*self.lexer, self.current, self.peek) = checkpoint;
+        self.after_current = None;
+        self.lexer.exit_jsx_mode();
+        Ok(Identifier { value: opened.value.clone(), position: opened.position })
+    }
+
+    /// "unclosed <div> opened at line 12:5, found </section> at 40:3", on the
+    /// closing tag whose `<` is `angle`
+    fn unclosed_element_error(opened: &Identifier, angle: &Token, found: &str, closes_outer: bool) -> CompileError {
+        let Position { line, column } = opened.position;
+        let suggestion = if closes_outer {
+            format!("add `</{}>` before `</{}>`", opened.value, found)
+        } else {
+            format!("change it to `</{}>`", opened.value)
         };
-        if !opening_tag.self_closing && opening_tag.name.value != closing_tag.as_ref().unwrap().value {
-            return Err(self.error("Mismatched closing tag"));
+        CompileError::WithLocation {
+            message: format!(
+                "unclosed <{}> opened at line {}:{}, found </{}> at {}:{}",
+                opened.value, line, column, found, angle.line, angle.column
+            ),
+            location: SourceLocation { file: String::new(), line: angle.line, column: angle.column, length: found.len() + 3 },
+            suggestion: Some(suggestion),
+            labels: vec![Label { location: Self::opening_location(opened), message: format!("<{}> opened here", opened.value) }],
         }
+    }
 
-        Ok(Expression::JsxElement(JsxElement { opening_tag, children, closing_tag }))
+    /// `</br>`, at its `<`: a void element ends with its own tag
+    fn void_closing_tag_error(tag: &str, angle: &Token) -> CompileError {
+        CompileError::WithLocation {
+            message: format!("<{}> is a void element and can't have a closing tag", tag),
+            location: SourceLocation { file: String::new(), line: angle.line, column: angle.column, length: tag.len() + 3 },
+            suggestion: Some(format!("remove `</{}>`, and write the element as `<{} />`", tag, tag)),
+            labels: Vec::new(),
+        }
     }
 
-    fn parse_jsx_opening_tag_with_mode_check(&mut self, was_jsx_mode: bool) -> Result<JsxOpeningTag, CompileError> {
-        self.expect_and_consume(&TokenKind::LAngle)?;
+    /// The file ends inside the elements being read: reported at the
+    /// innermost, with the ones around it
+    fn unclosed_at_end_error(&self) -> CompileError {
+        let Some((opened, outer)) = self.jsx_open_tags.split_last() else {
+            return self.error("Unclosed JSX element");
+        };
+        let Position { line, column } = opened.position;
+        CompileError::WithLocation {
+            message: format!("unclosed <{}> opened at line {}:{}, found the end of the file", opened.value, line, column),
+            location: Self::opening_location(opened),
+            suggestion: Some(format!("close it with `</{}>`, or end the tag with `/>` if it has no children", opened.value)),
+            labels: outer.iter().rev().map(|open| Label {
+                location: Self::opening_location(open),
+                message: format!("<{}> is not closed either", open.value),
+            }).collect(),
+        }
+    }
+
+    /// The `<` and name of an element being read
+    fn opening_location(opened: &Identifier) -> SourceLocation {
+        SourceLocation {
+            file: String::new(),
+            line: opened.position.line,
+            column: opened.position.column,
+            length: opened.value.len() + 1,
+        }
+    }
 
-        // CRITICAL FIX: Enter JSX mode BEFORE parsing tag name
-        // This ensures peek is fetched in JSX mode when we parse the identifier
+    fn parse_jsx_opening_tag_with_mode_check(&mut self, was_jsx_mode: bool) -> Result<JsxOpeningTag, CompileError> {
+        // CRITICAL FIX: Enter JSX mode BEFORE consuming the `<`
+        // The tag name is already the peek token; everything after it, from the
+        // first attribute on, has to be lexed in JSX mode
         // Without this, {expr} children are tokenized as JsxText instead of separate tokens
-        // ALSO: Always push baseline for nested JSX (e.g. JSX inside ternary/expressions)
         if !was_jsx_mode {
             self.lexer.enter_jsx_mode();
         } else {
@@ -1747,14 +2523,35 @@ impl<'a> Parser<'a> {
             // This pushes a new baseline for JSX inside expressions: {cond ? (<div>...</div>) : ...}
             self.lexer.enter_nested_jsx();
         }
+        self.expect_and_consume(&TokenKind::LAngle)?;
 
-        let name = self.parse_identifier()?;
+        // The text of a <pre> is lexed as written. The token after the name is
+        // already read, but the one after that, maybe the first child, isn't.
+        // A `/>` read later ends it in the lexer.
+        if keeps_whitespace(&self.current_token().lexeme) && self.peek_token().kind != TokenKind::JsxSelfClose {
+            self.lexer.enter_raw_text_mode();
+        }
+        // Nor is the CSS or JavaScript of a <style> or <script> JSX at all
+        if is_raw_text_element(&self.current_token().lexeme) && self.peek_token().kind != TokenKind::JsxSelfClose {
+            let tag = self.current_token().lexeme.clone();
+            self.lexer.enter_raw_element_mode(&tag);
+        }
+        let position = self.position();
+        let name = self.parse_jsx_tag_name()?;
+        let void = is_void_element(&name.value);
+        let type_args = self.parse_jsx_type_args()?;
 
         let mut attributes = vec![];
+        let mut allows = vec![];
         while self.current_token().kind != TokenKind::RAngle &&
               self.current_token().kind != TokenKind::Slash &&
               self.current_token().kind != TokenKind::JsxSelfClose {
-            attributes.push(self.parse_jsx_attribute()?);
+            if self.current_token().kind == TokenKind::At && self.peek_token().lexeme == "allow" {
+                self.next_token(); // consume '@'
+                allows.extend(self.parse_allow_list()?);
+                continue;
+            }
+            attributes.push(self.parse_jsx_attribute(&name)?);
         }
 
         // Check for self-closing tag />
@@ -1765,7 +2562,7 @@ impl<'a> Parser<'a> {
 
         let self_closing = if is_self_closing {
             // Exit JSX mode FIRST, before consuming any tokens
-            self.lexer.exit_jsx_mode();
+            self.exit_jsx_mode_at_current();
 
             if self.consume_if_matches(&TokenKind::JsxSelfClose) {
                 // Self-closing tag with />
@@ -1778,21 +2575,54 @@ impl<'a> Parser<'a> {
                 // Should not reach here
                 false
             }
+        } else if void {
+            // A void element like <br> has no children, so its `>` ends it as
+            // `/>` would, and the element's JSX mode ends before it too
+            self.exit_jsx_mode_at_current();
+            self.expect_and_consume(&TokenKind::RAngle)?;
+            true
         } else {
             // Regular opening tag - consume >
             self.expect_and_consume(&TokenKind::RAngle)?;
             false
         };
-        Ok(JsxOpeningTag { name, attributes, self_closing })
+        let slash_omitted = void && !is_self_closing;
+        Ok(JsxOpeningTag { name, type_args, attributes, self_closing, slash_omitted, position, allows })
     }
 
-    fn parse_jsx_attribute(&mut self) -> Result<JsxAttribute, CompileError> {
-        let name = self.parse_identifier()?;
+    fn parse_jsx_attribute(&mut self, tag: &Identifier) -> Result<JsxAttribute, CompileError> {
+        // {...props}: the brace holds a spread expression
+        if matches!(self.current_token().kind, TokenKind::JsxOpenBrace | TokenKind::LBrace)
+            && self.peek_token().kind == TokenKind::DotDotDot
+        {
+            let name = Identifier { value: JSX_SPREAD.to_string(), position: self.position() };
+            self.next_token(); // consume the brace
+            let value = self.parse_expression(Precedence::Lowest)?;
+            if !self.consume_if_matches(&TokenKind::JsxCloseBrace) {
+                self.expect_and_consume(&TokenKind::RBrace)?;
+            }
+            return Ok(JsxAttribute { name, value });
+        }
+
+        // {open}: shorthand for open={open}
+        if matches!(self.current_token().kind, TokenKind::JsxOpenBrace | TokenKind::LBrace) {
+            let open_brace = self.current_token().clone();
+            self.next_token(); // consume the brace
+            let value = self.parse_expression(Precedence::Lowest)?;
+            let Expression::Identifier(name) = &value else {
+                return Err(Self::shorthand_attribute_error(&open_brace));
+            };
+            let name = name.clone();
+            if !self.consume_if_matches(&TokenKind::JsxCloseBrace) {
+                self.expect_and_consume(&TokenKind::RBrace)?;
+            }
+            return Ok(JsxAttribute { name, value });
+        }
+
+        let name = self.parse_jsx_attribute_name(tag)?;
 
-        // Check if this is a boolean attribute (no = sign)
-        // Boolean attributes like `disabled`, `readonly`, `checked` don't have values
+        // An attribute without a value, like `disabled`, is `true`
         if self.current_token().kind != TokenKind::Assign {
-            // Boolean attribute - defaults to true
             let value = Expression::BoolLiteral(true);
             return Ok(JsxAttribute { name, value });
         }
@@ -1800,8 +2630,40 @@ impl<'a> Parser<'a> {
         self.expect_and_consume(&TokenKind::Assign)?;
 
         // Check if value is wrapped in curly braces for expression interpolation
+        let open_brace = self.current_token().clone();
         if self.consume_if_matches(&TokenKind::JsxOpenBrace) || self.consume_if_matches(&TokenKind::LBrace) {
-            let value = self.parse_expression(Precedence::Lowest)?;
+            // class={["btn", {"active": on}]}: the same as classes!("btn", "active" => on)
+            if matches!(name.value.as_str(), "class" | "className") && self.current_token().kind == TokenKind::LBracket {
+                let classes = self.parse_class_list()?;
+                if !self.consume_if_matches(&TokenKind::JsxCloseBrace) {
+                    self.expect_and_consume(&TokenKind::RBrace)?;
+                }
+                return Ok(JsxAttribute { name, value: classes });
+            }
+            // style={{ color: fg }}: the inner braces are the style's properties
+            if name.value == "style" && self.current_token().kind == TokenKind::LBrace {
+                let style = self.parse_style_object()?;
+                if !self.consume_if_matches(&TokenKind::JsxCloseBrace) {
+                    self.expect_and_consume(&TokenKind::RBrace)?;
+                }
+                return Ok(JsxAttribute { name, value: Expression::InlineStyle(style) });
+            }
+            let outer_angles = self.jsx_attribute_angles.replace(Vec::new());
+            let value = self.parse_expression(Precedence::Lowest);
+            let angles = std::mem::replace(&mut self.jsx_attribute_angles, outer_angles).unwrap_or_default();
+
+            // An expression that runs past where the tag should have closed usually
+            // swallowed the tag's `>`; point at that instead of wherever parsing gave up
+            let closed = matches!(self.current_token().kind, TokenKind::JsxCloseBrace | TokenKind::RBrace);
+            let terminated = value.is_ok() && closed;
+            let already_reported = matches!(value, Err(CompileError::WithLocation { .. }));
+            if !terminated && !already_reported {
+                if let Some(angle) = angles.last() {
+                    return Err(Self::unterminated_attribute_error(&open_brace, angle));
+                }
+            }
+
+            let value = value?;
             if !self.consume_if_matches(&TokenKind::JsxCloseBrace) {
                 self.expect_and_consume(&TokenKind::RBrace)?;
             }
@@ -1809,13 +2671,420 @@ impl<'a> Parser<'a> {
         } else {
             // Parse just the prefix (string literal, identifier, etc.) without infix operators
             // This prevents treating > or / as operators
+            let value_token = self.current_token().clone();
             let value = self.parse_prefix()?;
+            if let (Expression::StringLiteral(text), "style") = (&value, name.value.as_str()) {
+                let style = Self::parse_inline_style(text, &value_token)?;
+                return Ok(JsxAttribute { name, value: Expression::InlineStyle(style) });
+            }
             Ok(JsxAttribute { name, value })
         }
     }
 
-    fn parse_jsx_children(&mut self) -> Result<Vec<JsxChild>, CompileError> {
+    /// Parse a `style="..."` attribute into declarations. Static values go
+    /// through the css! declaration parser; `{expr}` values are parsed as
+    /// expressions and stay single values (escaped at runtime), so a `;` inside
+    /// one cannot add declarations.
+    fn parse_inline_style(text: &str, token: &Token) -> Result<InlineStyle, CompileError> {
+        let error = |offset: usize, message: String| {
+            let (line, column) = Self::inline_style_position(text, token, offset);
+            CompileError::ParserError { message, line, column }
+        };
+
+        let mut declarations = Vec::new();
+        for (start, source) in Self::split_inline_style(text, ';').map_err(|(at, msg)| error(at, msg))? {
+            let trimmed = source.trim_start();
+            if trimmed.trim().is_empty() {
+                continue;
+            }
+            let start = start + (source.len() - trimmed.len());
+            let Some((colon, _)) = Self::split_inline_style(trimmed, ':')
+                .map_err(|(at, msg)| error(start + at, msg))?
+                .into_iter()
+                .nth(1)
+            else {
+                return Err(error(start, format!(
+                    "Expected `property: value` in style attribute, found `{}`",
+                    trimmed.trim()
+                )));
+            };
+
+            let property = trimmed[..colon - 1].trim().to_string();
+            let valid_name = property.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+                && property.chars().any(|c| c.is_ascii_alphabetic())
+                && !property.starts_with(|c: char| c.is_ascii_digit());
+            if !valid_name {
+                return Err(error(start, format!("Invalid CSS property name `{}` in style attribute", property)));
+            }
+
+            let value_start = start + colon;
+            let value = Self::parse_inline_style_value(&trimmed[colon..])
+                .map_err(|(at, msg)| error(value_start + at, msg))?;
+            match value.as_slice() {
+                [] => return Err(error(value_start, format!("Missing value for `{}` in style attribute", property))),
+                [InlineStylePart::Static(text)] => {
+                    Self::check_css_declaration(&property, text)
+                        .map_err(|msg| error(value_start, format!("Invalid value for `{}` in style attribute: {}", property, msg)))?;
+                }
+                _ => {}
+            }
+
+            let (line, column) = Self::inline_style_position(text, token, start);
+            declarations.push(InlineStyleDeclaration { property, value, line, column });
+        }
+        Ok(InlineStyle { declarations, object: false })
+    }
+
+    /// Parse the object of `style={{ color: fg, padding_top: 8 }}`, from its
+    /// `{`. Keys are properties in snake_case or camelCase, or quoted as
+    /// written, like `"--accent"`. A number literal is a length in px unless
+    /// it's 0 or the property is unitless; literal values are validated like
+    /// the declarations of `style="..."`, and the rest stay expressions.
+    fn parse_style_object(&mut self) -> Result<InlineStyle, CompileError> {
+        self.expect_and_consume(&TokenKind::LBrace)?;
+        let mut declarations = Vec::new();
+        while self.current_token().kind != TokenKind::RBrace {
+            let key = self.current_token().clone();
+            let error = |message: String| CompileError::ParserError { message, line: key.line, column: key.column };
+            let property = match &key.kind {
+                TokenKind::String(text) => text.clone(),
+                TokenKind::Identifier => Self::style_property_name(&key.lexeme),
+                _ if KEYWORDS.contains_key(key.lexeme.as_str()) => Self::style_property_name(&key.lexeme),
+                _ => return Err(error(format!("Expected a CSS property name in the style object, found `{}`", key.lexeme))),
+            };
+            let valid_name = property.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+                && property.chars().any(|c| c.is_ascii_alphabetic());
+            if !valid_name {
+                return Err(error(format!("Invalid CSS property name `{}` in style object", property)));
+            }
+            self.next_token();
+            self.expect_and_consume(&TokenKind::Colon)?;
+
+            let mut declaration = InlineStyleDeclaration { property, value: Vec::new(), line: key.line, column: key.column };
+            let value = match self.parse_expression(Precedence::Lowest)? {
+                Expression::StringLiteral(text) => Some(text),
+                Expression::IntegerLiteral(0) => Some("0".to_string()),
+                Expression::IntegerLiteral(n) if declaration.number_is_px() => Some(format!("{}px", n)),
+                Expression::IntegerLiteral(n) => Some(n.to_string()),
+                Expression::FloatLiteral(n) if declaration.number_is_px() => Some(format!("{}px", n)),
+                Expression::FloatLiteral(n) => Some(n),
+                expr => {
+                    declaration.value.push(InlineStylePart::Dynamic(Box::new(expr)));
+                    None
+                }
+            };
+            if let Some(text) = value {
+                Self::check_css_declaration(&declaration.property, &text).map_err(|msg| {
+                    error(format!("Invalid value for `{}` in style object: {}", declaration.property, msg))
+                })?;
+                declaration.value.push(InlineStylePart::Static(text));
+            }
+            declarations.push(declaration);
+
+            if !self.consume_if_matches(&TokenKind::Comma) {
+                break;
+            }
+        }
+        self.expect_and_consume(&TokenKind::RBrace)?;
+        Ok(InlineStyle { declarations, object: true })
+    }
+
+    /// `padding_top` and `paddingTop` are `padding-top`, and a capital or `ms`
+    /// up front is a vendor prefix: `WebkitTransition` is `-webkit-transition`
+    fn style_property_name(key: &str) -> String {
+        let mut name = String::new();
+        for c in key.chars() {
+            match c {
+                '_' => name.push('-'),
+                c if c.is_ascii_uppercase() => {
+                    name.push('-');
+                    name.push(c.to_ascii_lowercase());
+                }
+                c => name.push(c),
+            }
+        }
+        if name.starts_with("ms-") {
+            name.insert(0, '-');
+        }
+        name
+    }
+
+    /// Split on `separator` outside `{...}`, parentheses and quotes, keeping
+    /// each piece's byte offset. Errors carry the offset of the problem.
+    fn split_inline_style(text: &str, separator: char) -> Result<Vec<(usize, &str)>, (usize, String)> {
+        let mut pieces = Vec::new();
+        let mut braces = Vec::new();
+        let mut parens = 0usize;
+        let mut quote = None;
+        let mut start = 0;
+        for (i, c) in text.char_indices() {
+            match (quote, c) {
+                (Some(q), c) if c == q => quote = None,
+                (Some(_), _) => {}
+                (None, '"') | (None, '\'') => quote = Some(c),
+                (None, '{') => braces.push(i),
+                (None, '}') if braces.pop().is_none() => {
+                    return Err((i, "Unmatched `}` in style attribute".to_string()));
+                }
+                (None, '(') => parens += 1,
+                (None, ')') => parens = parens.saturating_sub(1),
+                (None, c) if c == separator && braces.is_empty() && parens == 0 => {
+                    pieces.push((start, &text[start..i]));
+                    start = i + c.len_utf8();
+                }
+                _ => {}
+            }
+        }
+        if let Some(open) = braces.first() {
+            return Err((*open, "Unclosed `{` in style attribute".to_string()));
+        }
+        pieces.push((start, &text[start..]));
+        Ok(pieces)
+    }
+
+    /// Split a declaration value into static text and `{expr}` interpolations
+    fn parse_inline_style_value(value: &str) -> Result<Vec<InlineStylePart>, (usize, String)> {
+        let leading = value.len() - value.trim_start().len();
+        let value = value.trim();
+        let mut parts = Vec::new();
+        let mut rest = value;
+        let mut offset = leading;
+        while let Some(open) = rest.find('{') {
+            if open > 0 {
+                parts.push(InlineStylePart::Static(rest[..open].to_string()));
+            }
+            let mut depth = 0;
+            let close = rest[open..]
+                .char_indices()
+                .find_map(|(i, c)| {
+                    match c {
+                        '{' => depth += 1,
+                        '}' => depth -= 1,
+                        _ => {}
+                    }
+                    (depth == 0).then_some(open + i)
+                })
+                .ok_or((offset + open, "Unclosed `{` in style attribute".to_string()))?;
+
+            let source = &rest[open + 1..close];
+            let mut lexer = Lexer::new(source.to_string());
+            let mut parser = Parser::new(&mut lexer);
+            let expr = parser
+                .parse_expression(Precedence::Lowest)
+                .ok()
+                .filter(|_| parser.current_token().kind == TokenKind::Eof)
+                .ok_or((offset + open, format!("Invalid expression `{{{}}}` in style attribute", source.trim())))?;
+            parts.push(InlineStylePart::Dynamic(Box::new(expr)));
+
+            offset += close + 1;
+            rest = &rest[close + 1..];
+        }
+        if !rest.is_empty() {
+            parts.push(InlineStylePart::Static(rest.to_string()));
+        }
+        Ok(parts)
+    }
+
+    /// Validate a static declaration with the css! parser
+    fn check_css_declaration(property: &str, value: &str) -> Result<(), String> {
+        let mut lexer = Lexer::new(format!("css! {{ .inline {{ {}: {}; }} }}", property, value));
+        let mut parser = Parser::new(&mut lexer);
+        match parser.parse_expression(Precedence::Lowest) {
+            Ok(Expression::CssMacro(css)) if css.rules().next().is_some_and(|rule| rule.declarations.len() == 1) => Ok(()),
+            Ok(_) => Err(format!("`{}` is not a single value", value)),
+            Err(CompileError::ParserError { message, .. }) => Err(message),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    /// Source line and column of a byte offset inside a string literal token
+    fn inline_style_position(text: &str, token: &Token, offset: usize) -> (usize, usize) {
+        let before = &text[..offset.min(text.len())];
+        match before.rfind('\n') {
+            Some(newline) => (token.line + before.matches('\n').count(), before[newline + 1..].chars().count() + 1),
+            None => (token.line, token.column + 1 + before.chars().count()),
+        }
+    }
+
+    /// `{a + b}` alone in a tag: only a name can be its own attribute
+    fn shorthand_attribute_error(open_brace: &Token) -> CompileError {
+        CompileError::WithLocation {
+            message: "only a variable can stand alone in a tag, as in `{open}` for `open={open}`".to_string(),
+            location: SourceLocation { file: String::new(), line: open_brace.line, column: open_brace.column, length: 1 },
+            suggestion: Some("name the attribute: `name={...}`".to_string()),
+            labels: Vec::new(),
+        }
+    }
+
+    fn unterminated_attribute_error(open_brace: &Token, angle: &Token) -> CompileError {
+        let location = |token: &Token| SourceLocation {
+            file: String::new(),
+            line: token.line,
+            column: token.column,
+            length: token.lexeme.len().max(1),
+        };
+        CompileError::WithLocation {
+            message: format!(
+                "JSX attribute expression starting at line {} appears unterminated; did a '>' inside it end the tag?",
+                open_brace.line
+            ),
+            location: location(angle),
+            suggestion: Some("close the expression with `}` before the tag's `>`".to_string()),
+            labels: vec![Label {
+                location: location(open_brace),
+                message: "attribute expression starts here".to_string(),
+            }],
+        }
+    }
+
+    /// Whether the `=` at the current token is on the left of a `&&` or `?:`
+    /// in a JSX child, like `{x = 5 && <p />}`, so it stands for a condition
+    fn assignment_is_jsx_condition(&mut self) -> bool {
+        if self.current_token().kind != TokenKind::Assign {
+            return false;
+        }
+        let mut depth = 0usize;
+        let mut kind = self.peek_token().kind.clone();
+        for ahead in 0.. {
+            match kind {
+                TokenKind::AmpAmp | TokenKind::Question if depth == 0 => return true,
+                TokenKind::LParen | TokenKind::LBracket | TokenKind::LBrace | TokenKind::JsxOpenBrace => depth += 1,
+                TokenKind::RParen | TokenKind::RBracket | TokenKind::RBrace | TokenKind::JsxCloseBrace if depth > 0 => depth -= 1,
+                TokenKind::RParen | TokenKind::RBracket | TokenKind::RBrace | TokenKind::JsxCloseBrace | TokenKind::Eof => return false,
+                _ => {}
+            }
+            kind = self.lexer.peek_nth(ahead).kind.clone();
+        }
+        false
+    }
+
+    /// Parses an attribute name. HTML attributes such as `for`, `type` or `class`
+    /// collide with Jounce keywords, so keyword tokens are accepted by lexeme on
+    /// plain elements. Component props become parameter names, which can't be
+    /// keywords, so those are still rejected.
+    fn parse_jsx_attribute_name(&mut self, tag: &Identifier) -> Result<Identifier, CompileError> {
+        let token = self.current_token().clone();
+        let mut name = if token.kind == TokenKind::Identifier {
+            let mut name = self.parse_identifier()?;
+            // bind:value={signal}
+            if name.value == "bind" && self.consume_if_matches(&TokenKind::Colon) {
+                let target = self.parse_name("a binding name")?;
+                name.value = format!("bind:{}", target.value);
+                return Ok(name);
+            }
+            name
+        } else if KEYWORDS.contains_key(token.lexeme.as_str()) {
+            let is_component = tag.value.chars().next().is_some_and(|c| c.is_uppercase()) || tag.value.contains('.');
+            if is_component {
+                return self.parse_name(&format!("a prop name on component `{}`", tag.value));
+            }
+            self.next_token();
+            Self::identifier_at(&token)
+        } else {
+            return Err(self.error(&format!("Expected attribute name, found {:?}", token.kind)));
+        };
+
+        // The rest of a name like `data-test-id`, `stroke-width` or `xlink:href`,
+        // which lexes as words joined by `-` and at most one `:`, with no spaces
+        let mut end = token.span.end;
+        let mut namespaced = false;
+        loop {
+            let (separator, part) = (self.current_token(), self.peek_token());
+            let joins = match separator.kind {
+                TokenKind::Minus => true,
+                TokenKind::Colon => !namespaced,
+                _ => false,
+            };
+            let is_word = matches!(part.kind, TokenKind::Identifier | TokenKind::Integer(_))
+                || KEYWORDS.contains_key(part.lexeme.as_str());
+            if !joins || !is_word || separator.span.start != end || part.span.start != separator.span.end {
+                break;
+            }
+            namespaced |= separator.kind == TokenKind::Colon;
+            name.value.push_str(&separator.lexeme);
+            name.value.push_str(&part.lexeme);
+            end = part.span.end;
+            self.next_token();
+            self.next_token();
+        }
+
+        // Event modifiers: onSubmit|prevent|once
+        let mut modifiers = Vec::new();
+        while self.current_token().kind == TokenKind::Pipe && self.current_token().span.start == end {
+            let pipe = self.current_token().clone();
+            let modifier = self.peek_token().clone();
+            let is_word = modifier.kind == TokenKind::Identifier || KEYWORDS.contains_key(modifier.lexeme.as_str());
+            if !is_word || modifier.span.start != pipe.span.end {
+                return Err(Self::event_modifier_error(
+                    &pipe,
+                    format!("`{}|` needs a modifier after the `|`", name.value),
+                    format!("the modifiers are {}", Self::modifier_list()),
+                ));
+            }
+            let is_component = tag.value.starts_with(|c: char| c.is_uppercase()) || tag.value.contains('.');
+            if is_component {
+                return Err(Self::event_modifier_error(
+                    &pipe,
+                    format!("event modifiers only work on HTML elements, and <{}> is a component", tag.value),
+                    format!("handle the event on an element inside <{}>", tag.value),
+                ));
+            }
+            if !name.value.starts_with("on") {
+                return Err(Self::event_modifier_error(
+                    &pipe,
+                    format!("`{}` isn't an event handler, so it can't take `|{}`", name.value, modifier.lexeme),
+                    format!("modifiers go on handlers, as in `onSubmit|{}={{save}}`", modifier.lexeme),
+                ));
+            }
+            if !EVENT_MODIFIERS.contains(&modifier.lexeme.as_str()) {
+                return Err(Self::event_modifier_error(
+                    &modifier,
+                    format!("Unknown event modifier `{}`", modifier.lexeme),
+                    format!("the modifiers are {}", Self::modifier_list()),
+                ));
+            }
+            modifiers.push(modifier.clone());
+            name.value.push('|');
+            name.value.push_str(&modifier.lexeme);
+            end = modifier.span.end;
+            self.next_token();
+            self.next_token();
+        }
+        // A passive listener's preventDefault() is ignored
+        if let (Some(prevent), Some(_)) = (
+            modifiers.iter().find(|m| m.lexeme == "prevent"),
+            modifiers.iter().find(|m| m.lexeme == "passive"),
+        ) {
+            return Err(Self::event_modifier_error(
+                prevent,
+                format!("`{}` can't prevent the default action of a passive listener", name.value),
+                "drop `prevent` or `passive`".to_string(),
+            ));
+        }
+        Ok(name)
+    }
+
+    fn event_modifier_error(token: &Token, message: String, suggestion: String) -> CompileError {
+        CompileError::WithLocation {
+            message,
+            location: SourceLocation { file: String::new(), line: token.line, column: token.column, length: token.lexeme.len().max(1) },
+            suggestion: Some(suggestion),
+            labels: Vec::new(),
+        }
+    }
+
+    /// `prevent`, `stop`, ... and `passive`, for error messages
+    fn modifier_list() -> String {
+        let quoted: Vec<String> = EVENT_MODIFIERS.iter().map(|m| format!("`{}`", m)).collect();
+        let (last, rest) = quoted.split_last().expect("there are modifiers");
+        format!("{} and {}", rest.join(", "), last)
+    }
+
+    /// The children up to the closing tag, and the unknown references in
+    /// their text
+    fn parse_jsx_children(&mut self) -> Result<(Vec<JsxChild>, Vec<JsxUnknownEntity>), CompileError> {
         let mut children = Vec::new();
+        let mut unknown_entities = Vec::new();
 
         loop {
             // Check if we've reached the closing tag
@@ -1824,7 +3093,7 @@ impl<'a> Parser<'a> {
             }
 
             if self.current_token().kind == TokenKind::Eof {
-                return Err(self.error("Unclosed JSX element"));
+                return Err(self.unclosed_at_end_error());
             }
 
             // Check for JSX text - any content that's not a tag or expression
@@ -1834,6 +3103,7 @@ impl<'a> Parser<'a> {
                     if !text.is_empty() {
                         children.push(JsxChild::Text(text.clone()));
                     }
+                    unknown_entities.extend(html_entities::unknown_entities(&self.current_token().lexeme, self.position()));
                     self.next_token();
                     continue;
                 }
@@ -1875,7 +3145,13 @@ impl<'a> Parser<'a> {
             let is_lbrace = self.current_token().kind == TokenKind::LBrace;
             if is_jsx_open_brace || is_lbrace {
                 self.next_token(); // Consume the brace
-                let expr = self.parse_expression(Precedence::Lowest)?;
+                let checkpoint = (self.lexer.clone(), self.current.clone(), self.peek.clone());
+                let mut expr = self.parse_expression(Precedence::Lowest)?;
+                if self.assignment_is_jsx_condition() {
+                    (*self.lexer, self.current, self.peek) = checkpoint;
+                    self.after_current = None;
+                    expr = self.parse_condition(true)?;
+                }
                 if !self.consume_if_matches(&TokenKind::JsxCloseBrace) {
                     self.expect_and_consume(&TokenKind::RBrace)?;
                 }
@@ -1890,10 +3166,8 @@ impl<'a> Parser<'a> {
                     break; // This is actually the closing tag, exit
                 }
                 // This is a nested element
-                let child_expr = self.parse_jsx_element()?;
-                if let Expression::JsxElement(el) = child_expr {
-                    children.push(JsxChild::Element(Box::new(el)));
-                }
+                let child = self.parse_jsx_child_element()?;
+                children.push(JsxChild::Element(Box::new(child)));
                 continue;
             }
 
@@ -1901,7 +3175,7 @@ impl<'a> Parser<'a> {
             break;
         }
 
-        Ok(children)
+        Ok((children, unknown_entities))
     }
 
     fn parse_jsx_closing_tag_with_mode_check(&mut self, was_jsx_mode: bool) -> Result<Identifier, CompileError> {
@@ -1910,44 +3184,233 @@ impl<'a> Parser<'a> {
 
         self.expect_and_consume(&TokenKind::LAngle)?;
         self.expect_and_consume(&TokenKind::Slash)?;
-        let name = self.parse_identifier()?;
 
-        // CRITICAL: Exit BOTH closing tag mode AND JSX mode BEFORE consuming `>`
-        // This ensures the next token (created during consume) has the correct lexer state
-        // including the correct baseline brace depth for at_baseline calculation
+        // CRITICAL: Exit BOTH closing tag mode AND JSX mode BEFORE consuming the name.
+        // The `>` is already in the lookahead, so the token after it is lexed while
+        // consuming the name; it needs the correct lexer state, including the parent's
+        // baseline brace depth (e.g. the `}` in `render={|| <b>x</b>}`)
         self.lexer.exit_closing_tag_mode();
         self.lexer.exit_jsx_mode();
+        let name = self.parse_jsx_tag_name()?;
         self.expect_and_consume(&TokenKind::RAngle)?;
 
         Ok(name)
     }
 
+    /// `Hello, ${name}!`: the lexer hands over the text runs and, between
+    /// `${` and `}`, the tokens of each interpolated expression
+    fn parse_template_literal(&mut self) -> Result<Expression, CompileError> {
+        self.next_token(); // consume the opening `
+        let mut parts = Vec::new();
+        loop {
+            match self.current_token().kind.clone() {
+                TokenKind::TemplateChunk(text) => {
+                    self.next_token();
+                    parts.push(TemplatePart::Text(text));
+                }
+                TokenKind::TemplateInterpolationStart => {
+                    self.next_token();
+                    if self.current_token().kind == TokenKind::TemplateInterpolationEnd {
+                        return Err(self.error("Empty `${}` in template string; put an expression inside"));
+                    }
+                    let expr = self.parse_expression(Precedence::Lowest)?;
+                    self.expect_and_consume(&TokenKind::TemplateInterpolationEnd)?;
+                    parts.push(TemplatePart::Interpolation(Box::new(expr)));
+                }
+                TokenKind::TemplateEnd => {
+                    self.next_token();
+                    return Ok(Expression::Template(TemplateLiteral { parts }));
+                }
+                TokenKind::InvalidString(message) => return Err(self.error(&message)),
+                other => return Err(self.error(&format!("Expected `}}` to close `${{` in template string, found {:?}", other))),
+            }
+        }
+    }
+
+    /// A tag name, `slot:name` for the element filling a component's slot, or
+    /// a dotted path like `ui.Button` to a component
+    fn parse_jsx_tag_name(&mut self) -> Result<Identifier, CompileError> {
+        // `style` starts style blocks, but `<style>` is the HTML element
+        let mut name = if self.current_token().kind == TokenKind::Style {
+            let token = self.current_token().clone();
+            self.next_token();
+            Self::identifier_at(&token)
+        } else {
+            self.parse_identifier()?
+        };
+        if name.value == "slot" && self.consume_if_matches(&TokenKind::Colon) {
+            let slot = self.parse_name("a slot name")?;
+            name.value = format!("slot:{}", slot.value);
+        }
+        // `ui.Button`, `Icons.Arrow.Left`: a component reached through a path
+        while self.current_token().kind == TokenKind::Dot {
+            self.next_token();
+            let member = self.parse_identifier()?;
+            name.value = format!("{}.{}", name.value, member.value);
+        }
+        Ok(name)
+    }
+
+    /// A generic component's type arguments, `<List<User> ...>`, if its name
+    /// is followed by a `<`. The first type is already read; the lexer reads
+    /// the rest in a mode where their `>` doesn't end the tag.
+    fn parse_jsx_type_args(&mut self) -> Result<Vec<TypeExpression>, CompileError> {
+        if self.current_token().kind != TokenKind::LAngle {
+            return Ok(Vec::new());
+        }
+        self.lexer.enter_jsx_type_args();
+        self.next_token(); // consume <
+        let mut type_args = Vec::new();
+        while self.current_token().kind != TokenKind::RAngle {
+            type_args.push(self.parse_type_expression()?);
+            if !self.consume_if_matches(&TokenKind::Comma) { break; }
+        }
+        self.expect_and_consume(&TokenKind::RAngle)?;
+        Ok(type_args)
+    }
+
     fn parse_identifier(&mut self) -> Result<Identifier, CompileError> {
         let token = self.current_token();
         if let TokenKind::Identifier = &token.kind {
-            let ident = Identifier { value: token.lexeme.clone() };
+            let ident = Self::identifier_at(token);
             self.next_token();
             Ok(ident)
+        } else if let TokenKind::InvalidIdentifier(message) = &token.kind {
+            Err(self.error(message))
         } else {
             Err(self.error(&format!("Expected Identifier, found {:?}", token.kind)))
         }
     }
 
-    fn current_token(&self) -> &Token { &self.current }
-    fn peek_token(&self) -> &Token { &self.peek }
-    fn current_precedence(&self) -> Precedence { PRECEDENCES.get(&self.current_token().kind).cloned().unwrap_or(Precedence::Lowest) }
-    fn next_token(&mut self) {
-        self.current = self.peek.clone();
-        self.peek = self.lexer.next_token();
+    /// Parses a name in a position only an identifier can take (`what` is e.g.
+    /// "a variable name"). A keyword there is reported, then used as the name
+    /// so the rest of the item is still parsed and checked.
+    fn parse_name(&mut self, what: &str) -> Result<Identifier, CompileError> {
+        let token = self.current_token().clone();
+        if token.kind == TokenKind::Identifier || !KEYWORDS.contains_key(token.lexeme.as_str()) {
+            return self.parse_identifier();
+        }
+        self.recovered_errors.push(CompileError::WithLocation {
+            message: format!("`{}` is a reserved keyword and cannot be used as {}", token.lexeme, what),
+            location: SourceLocation {
+                file: String::new(),
+                line: token.line,
+                column: token.column,
+                length: token.lexeme.len(),
+            },
+            suggestion: Some(format!("rename it, e.g. `{}_`, or write it as a raw identifier, `r#{}`", token.lexeme, token.lexeme)),
+            labels: Vec::new(),
+        });
+        self.recovered_names.insert(token.lexeme.clone());
+        self.next_token();
+        Ok(Self::identifier_at(&token))
     }
 
-    /// Refresh the peek token (needed after changing lexer modes)
-    fn refresh_peek_token(&mut self) {
-        self.peek = self.lexer.next_token();
+    /// Reads a keyword reported by `parse_name` as the name it declared, so
+    /// its uses aren't reported too: `return match + 1;`. Only where a value
+    /// ends, as in `match;` or `{theme}`; `match x {` is still a match.
+    fn use_recovered_name(&mut self) {
+        if self.current.kind == TokenKind::Identifier || !self.recovered_names.contains(&self.current.lexeme) {
+            return;
+        }
+        let follows_value = match &self.peek.kind {
+            TokenKind::Semicolon | TokenKind::Comma | TokenKind::Colon | TokenKind::Dot | TokenKind::QuestionDot
+            | TokenKind::Question | TokenKind::RParen | TokenKind::RBracket | TokenKind::RBrace | TokenKind::JsxCloseBrace
+            | TokenKind::Assign | TokenKind::QuestionQuestionAssign | TokenKind::Eof => true,
+            TokenKind::LAngle | TokenKind::Minus | TokenKind::Pipe | TokenKind::Ampersand => false,
+            kind => kind.compound_operator().is_some() || PRECEDENCES.contains_key(kind),
+        };
+        if follows_value {
+            self.current.kind = TokenKind::Identifier;
+        }
     }
 
-    fn expect_and_consume(&mut self, expected: &TokenKind) -> Result<(), CompileError> {
-        if &self.current_token().kind == expected {
+    /// Parses a field name after `.` or `?.`, or a `key:` in a struct
+    /// literal. Nothing is declared there, so keywords are fine (`event.type`).
+    fn parse_member_name(&mut self) -> Result<Identifier, CompileError> {
+        let token = self.current_token().clone();
+        if token.kind != TokenKind::Identifier && KEYWORDS.contains_key(token.lexeme.as_str()) {
+            self.next_token();
+            return Ok(Self::identifier_at(&token));
+        }
+        self.parse_identifier()
+    }
+
+    fn identifier_at(token: &Token) -> Identifier {
+        Identifier {
+            value: token.lexeme.clone(),
+            position: Position { line: token.line, column: token.column },
+        }
+    }
+
+    /// A method's parameter name, where `self` may come first
+    fn parse_method_parameter_name(&mut self, first: bool) -> Result<Identifier, CompileError> {
+        if self.current_token().kind != TokenKind::SelfValue {
+            return self.parse_name("a parameter name");
+        }
+        if !first {
+            return Err(self.error("`self` must be the first parameter of a method"));
+        }
+        let ident = Self::identifier_at(self.current_token());
+        self.next_token();
+        Ok(ident)
+    }
+
+    /// The identifier an expression or pattern token stands for. `Self` is
+    /// replaced by the name of the type it refers to.
+    fn identifier_in_scope(&self, token: &Token) -> Result<Identifier, CompileError> {
+        if token.kind != TokenKind::SelfType {
+            return Ok(Self::identifier_at(token));
+        }
+        match &self.self_type {
+            Some(name) => Ok(Identifier {
+                value: name.clone(),
+                position: Position { line: token.line, column: token.column },
+            }),
+            None => Err(CompileError::ParserError {
+                message: "`Self` can only be used inside an `impl` or `trait` block".to_string(),
+                line: token.line,
+                column: token.column,
+            }),
+        }
+    }
+
+    fn current_token(&self) -> &Token { &self.current }
+    fn peek_token(&self) -> &Token { &self.peek }
+    fn current_precedence(&self) -> Precedence {
+        match &self.current_token().kind {
+            TokenKind::Assign if self.assign_is_eq => Precedence::Equals,
+            kind => PRECEDENCES.get(kind).cloned().unwrap_or(Precedence::Lowest),
+        }
+    }
+    fn next_token(&mut self) {
+        self.current = self.peek.clone();
+        self.current_docs = std::mem::take(&mut self.peek_docs);
+        self.after_current = matches!(self.current.kind, TokenKind::RAngle | TokenKind::JsxSelfClose)
+            .then(|| self.lexer.clone());
+        (self.peek, self.peek_docs) = Self::lex(self.lexer);
+    }
+
+    /// Leaves the element whose `/>` or `>` is the current token, reading
+    /// the token after it again outside the element: a `}` or `,` there
+    /// belongs to the expression around it, not to the element's children
+    fn exit_jsx_mode_at_current(&mut self) {
+        if let Some(lexer) = self.after_current.take() {
+            *self.lexer = lexer;
+            self.lexer.exit_jsx_mode();
+            self.refresh_peek_token();
+        } else {
+            self.lexer.exit_jsx_mode();
+        }
+    }
+
+    /// Refresh the peek token (needed after changing lexer modes)
+    fn refresh_peek_token(&mut self) {
+        (self.peek, self.peek_docs) = Self::lex(self.lexer);
+    }
+
+    fn expect_and_consume(&mut self, expected: &TokenKind) -> Result<(), CompileError> {
+        if &self.current_token().kind == expected {
             self.next_token();
             Ok(())
         } else {
@@ -1955,6 +3418,31 @@ impl<'a> Parser<'a> {
         }
     }
 
+    /// Whether the current token closes a generic list: a `>`, or one glued
+    /// to what follows (`>>`, `>=`, `>>=`)
+    fn at_closing_angle(&self) -> bool {
+        matches!(
+            self.current_token().kind,
+            TokenKind::RAngle | TokenKind::RightShift | TokenKind::GtEq | TokenKind::RightShiftAssign
+        )
+    }
+
+    /// Consumes the `>` closing a generic list. The lexer reads `>>` as a
+    /// shift, so in `Vec<Vec<i32>>` only its first `>` is taken here and the
+    /// rest is left as the current token for the outer list.
+    fn expect_closing_angle(&mut self) -> Result<(), CompileError> {
+        let rest = match self.current_token().kind {
+            TokenKind::RightShift => (TokenKind::RAngle, ">"),
+            TokenKind::GtEq => (TokenKind::Assign, "="),
+            TokenKind::RightShiftAssign => (TokenKind::GtEq, ">="),
+            _ => return self.expect_and_consume(&TokenKind::RAngle),
+        };
+        let token = self.current_token();
+        let span = Span::new(token.span.start + 1, token.span.end);
+        self.current = Token { span, ..Token::new(rest.0, rest.1.to_string(), token.line, token.column + 1) };
+        Ok(())
+    }
+
     fn consume_if_matches(&mut self, kind: &TokenKind) -> bool {
         if &self.current_token().kind == kind {
             self.next_token();
@@ -1969,27 +3457,30 @@ impl<'a> Parser<'a> {
     // - } (empty struct)
     // - Identifier followed by : or , or } (field name with colon, shorthand, or single field)
     // Keywords like if/while/for/let/return or Identifier followed by = indicate a block
-    fn is_struct_literal_ahead(&self) -> bool {
+    fn is_struct_literal_ahead(&mut self) -> bool {
         // Current token should be {, peek token tells us what's inside
         match self.peek_token().kind {
             TokenKind::RBrace => true,  // Empty struct literal: Name {}
             TokenKind::Identifier => {
-                // Need to look ahead 2 tokens to distinguish struct literal from block
-                // Clone the lexer to peek ahead without affecting the real one
-                // NOTE: The lexer is positioned AFTER peek, so next_token() gives us
-                // the token that comes after peek (which is the identifier after {)
-                let mut temp_lexer = self.lexer.clone();
-
-                // Get the token after peek (which is after the identifier)
-                let after_ident = temp_lexer.next_token();
+                // Need to look ahead 2 tokens to distinguish struct literal from block.
+                // The lexer is positioned AFTER peek, so its next token is the one
+                // after the identifier
+                let mut ahead = 0;
+                while matches!(self.lexer.peek_nth(ahead).kind, TokenKind::DocComment { .. }) {
+                    ahead += 1;
+                }
 
                 // Check what follows the identifier
                 matches!(
-                    after_ident.kind,
+                    self.lexer.peek_nth(ahead).kind,
                     TokenKind::Colon | TokenKind::Comma | TokenKind::RBrace
                 )
             }
-            _ => false,  // Keywords or other tokens: not a struct literal
+            // A keyword can still be a key: Event { type: "click" }
+            _ if KEYWORDS.contains_key(self.peek_token().lexeme.as_str()) => {
+                matches!(self.lexer.peek_nth(0).kind, TokenKind::Colon)
+            }
+            _ => false,  // Other tokens: not a struct literal
         }
     }
 
@@ -2014,35 +3505,49 @@ impl<'a> Parser<'a> {
         // Consume the opening brace
         self.expect_and_consume(&TokenKind::LBrace)?;
 
-        // Parse CSS rules and keyframes
-        let mut rules = Vec::new();
-        let mut keyframes = Vec::new();
+        // Parse CSS rules, keyframes and other at-rules
+        let mut items = Vec::new();
+        let mut imports = Vec::new();
+        let mut font_faces = Vec::new();
+        let mut pages = Vec::new();
 
         while self.current_token().kind != TokenKind::RBrace && self.current_token().kind != TokenKind::Eof {
             match &self.current_token().kind {
                 TokenKind::CssKeyframes => {
                     // Lexer recognized @keyframes as a single token
                     self.next_token(); // consume @keyframes
-                    keyframes.push(self.parse_css_keyframes()?);
+                    items.push(CssItem::Keyframes(self.parse_css_keyframes()?));
+                }
+                TokenKind::CssImport => imports.push(self.parse_css_import()?),
+                TokenKind::CssFontFace => font_faces.push(self.parse_css_font_face()?),
+                TokenKind::CssPage => pages.push(self.parse_css_page()?),
+                TokenKind::CssSupports => items.push(CssItem::Supports(self.parse_css_supports()?)),
+                TokenKind::CssAtRule(_) => self.skip_css_unknown_at_rule(),
+                TokenKind::CssMedia => {
+                    // Out here a media query holds whole rules
+                    let media_query = self.parse_css_media_query()?;
+                    if !media_query.declarations.is_empty() {
+                        return Err(self.error(&format!(
+                            "Declarations in `@media {}` need a rule around them, like `.name {{ ... }}`",
+                            media_query.condition
+                        )));
+                    }
+                    items.push(CssItem::Media(media_query));
                 }
-                TokenKind::CssMedia | TokenKind::At => {
-                    // Check if it's @keyframes or @media by looking at the next token
+                TokenKind::At => {
+                    // Check if it's @keyframes by looking at the next token
                     let next_token = self.peek_token().clone();
                     if next_token.lexeme == "keyframes" {
                         // It's @keyframes, consume @ and 'keyframes'
                         self.next_token(); // consume @
                         self.next_token(); // consume 'keyframes'
-                        keyframes.push(self.parse_css_keyframes()?);
-                    } else if next_token.lexeme == "media" {
-                        // It's @media, this should be handled by parse_css_rule
-                        // For now, fall through to parse_css_rule
-                        rules.push(self.parse_css_rule()?);
+                        items.push(CssItem::Keyframes(self.parse_css_keyframes()?));
                     } else {
                         return Err(self.error(&format!("Unknown @-rule: @{}", next_token.lexeme)));
                     }
                 }
                 _ => {
-                    rules.push(self.parse_css_rule()?);
+                    items.push(CssItem::Rule(self.parse_css_rule()?));
                 }
             }
         }
@@ -2050,11 +3555,14 @@ impl<'a> Parser<'a> {
         // Expect closing brace (CSS mode will auto-exit when depth reaches 0)
         self.expect_and_consume(&TokenKind::RBrace)?;
 
-        Ok(Expression::CssMacro(CssExpression { rules, keyframes }))
+        let unknown_at_rules = std::mem::take(&mut self.css_unknown_at_rules);
+        Ok(Expression::CssMacro(CssExpression { items, imports, font_faces, pages, unknown_at_rules }))
     }
 
     /// Parse a CSS rule: .button { property: value; } or with nesting
     fn parse_css_rule(&mut self) -> Result<CssRule, CompileError> {
+        let span = self.css_span();
+
         // Parse selector
         let selector = self.parse_css_selector()?;
 
@@ -2077,6 +3585,8 @@ impl<'a> Parser<'a> {
             } else if self.current_token().kind == TokenKind::CssContainer {
                 // Parse container query: @container (condition) { ... }
                 container_queries.push(self.parse_css_container_query()?);
+            } else if matches!(self.current_token().kind, TokenKind::CssAtRule(_) | TokenKind::CssPage) {
+                self.skip_css_unknown_at_rule();
             } else if self.is_nested_rule_start() {
                 // Parse nested rule recursively
                 nested_rules.push(self.parse_css_rule()?);
@@ -2098,9 +3608,20 @@ impl<'a> Parser<'a> {
             nested_rules,
             media_queries,
             container_queries,
+            span,
         })
     }
 
+    /// Position of the current token, recorded on declarations for diagnostics
+    fn position(&self) -> Position {
+        Position { line: self.current_token().line, column: self.current_token().column }
+    }
+
+    /// Position of the current token, recorded on CSS nodes for tooling
+    fn css_span(&self) -> CssSpan {
+        CssSpan { line: self.current_token().line, column: self.current_token().column }
+    }
+
     /// Check if the current token indicates the start of a nested CSS rule
     fn is_nested_rule_start(&self) -> bool {
         match &self.current_token().kind {
@@ -2117,36 +3638,7 @@ impl<'a> Parser<'a> {
         match &token.kind {
             TokenKind::CssSelector(selector_str) => {
                 self.next_token();
-
-                // Parse the selector string to determine type
-                if selector_str.starts_with('.') {
-                    // Class selector: .button
-                    Ok(CssSelector::Class(selector_str[1..].to_string()))
-                } else if selector_str.starts_with('#') {
-                    // ID selector: #main
-                    Ok(CssSelector::Id(selector_str[1..].to_string()))
-                } else if selector_str.starts_with("::") {
-                    // Pseudo-element: ::before, ::after
-                    Ok(CssSelector::PseudoElement(selector_str[2..].to_string()))
-                } else if selector_str.starts_with(':') {
-                    // Pseudo-class: :hover, :focus
-                    Ok(CssSelector::PseudoClass(selector_str[1..].to_string()))
-                } else if selector_str.starts_with('&') {
-                    // Nested selector: & (Sprint 2)
-                    Ok(CssSelector::Nested(selector_str.clone()))
-                } else if selector_str.contains(' ') {
-                    // Nested/descendant selector: ".card .title"
-                    Ok(CssSelector::Nested(selector_str.clone()))
-                } else if selector_str.contains(':') {
-                    // Compound selector with pseudo: .button:hover
-                    self.parse_compound_selector_from_string(selector_str)
-                } else if selector_str.matches('.').count() > 1 {
-                    // Compound selector with multiple classes: .button.primary
-                    self.parse_compound_selector_from_string(selector_str)
-                } else {
-                    // Element selector: div, button, span
-                    Ok(CssSelector::Element(selector_str.clone()))
-                }
+                self.css_selector_from_str(selector_str)
             }
             TokenKind::Colon => {
                 // Check for :: (pseudo-element) vs : (pseudo-class)
@@ -2193,9 +3685,34 @@ impl<'a> Parser<'a> {
         }
     }
 
+    /// The selector a CssSelector token holds, by its type
+    fn css_selector_from_str(&self, selector_str: &str) -> Result<CssSelector, CompileError> {
+        let list = CssSelector::split_list(selector_str);
+        if list.len() > 1 {
+            // Selector list: h1, h2, .title
+            return list.into_iter()
+                .map(|selector| self.css_selector_from_str(selector))
+                .collect::<Result<_, _>>()
+                .map(CssSelector::List);
+        }
+
+        if selector_str.starts_with('&') {
+            // Nested selector: & (Sprint 2)
+            Ok(CssSelector::Nested(selector_str.to_string()))
+        } else if CssSelector::has_combinator(selector_str) {
+            // Nested/descendant selector or other combinators: ".card .title", ".list>li"
+            Ok(CssSelector::Nested(selector_str.to_string()))
+        } else {
+            // A simple selector, .button, #main, div, :hover, ::before or
+            // [type="text"], or several of them together: .button:hover
+            self.parse_compound_selector_from_string(selector_str)
+        }
+    }
+
     /// Parse CSS declaration: property: value;
     fn parse_css_declaration(&mut self) -> Result<CssDeclaration, CompileError> {
         // Parse property name
+        let span = self.css_span();
         let property_token = self.current_token().clone();
         let property = match &property_token.kind {
             TokenKind::CssProperty(prop) => {
@@ -2211,7 +3728,7 @@ impl<'a> Parser<'a> {
         // Parse value
         let value = self.parse_css_value()?;
 
-        Ok(CssDeclaration { property, value })
+        Ok(CssDeclaration { property, value, span })
     }
 
     /// Parse CSS value: blue, 12px, "Arial", etc.
@@ -2300,117 +3817,28 @@ impl<'a> Parser<'a> {
     fn parse_css_media_query(&mut self) -> Result<CssMediaQuery, CompileError> {
         use crate::ast::CssMediaQuery;
 
+        let span = self.css_span();
+
         // Expect @media token
         self.expect_and_consume(&TokenKind::CssMedia)?;
 
-        // Expect opening parenthesis
-        self.expect_and_consume(&TokenKind::LParen)?;
-
-        // Read the condition as a string until we hit the closing paren
-        // Now that lexer properly tokenizes inside parens, we can read tokens normally
-        let mut condition = String::from("(");
-        let mut paren_depth = 1;
-        let mut iterations = 0;
-
-        while paren_depth > 0 && self.current_token().kind != TokenKind::Eof {
-            iterations += 1;
-            if iterations > 100 {
-                return Err(self.error("Media query condition parsing exceeded iteration limit"));
-            }
-            let token = self.current_token().clone();
-
-            match &token.kind {
-                TokenKind::LParen => {
-                    condition.push('(');
-                    paren_depth += 1;
-                }
-                TokenKind::RParen => {
-                    paren_depth -= 1;
-                    if paren_depth > 0 {
-                        condition.push(')');
-                    }
-                }
-                _ => {
-                    // Add token lexeme with space
-                    if !condition.ends_with('(') {
-                        condition.push(' ');
-                    }
-                    condition.push_str(&token.lexeme);
-                }
-            }
-
-            self.next_token();
-        }
-
-        condition.push(')');
-
-        // Continue reading tokens for complex conditions like "and (max-width: 1024px)"
-        // Keep reading until we hit LBrace
-        while self.current_token().kind != TokenKind::LBrace && self.current_token().kind != TokenKind::Eof {
-            let token = self.current_token().clone();
-
-            // Handle "and" or "or" keywords
-            if let TokenKind::CssProperty(ref prop) = token.kind {
-                if prop == "and" || prop == "or" {
-                    condition.push(' ');
-                    condition.push_str(&token.lexeme);
-                    self.next_token();
-                    continue;
-                }
-            }
-
-            // Handle additional parenthesized conditions
-            if token.kind == TokenKind::LParen {
-                condition.push(' ');
-                condition.push('(');
-                paren_depth = 1;
-                self.next_token();
-
-                // Read until closing paren
-                while paren_depth > 0 && self.current_token().kind != TokenKind::Eof {
-                    let token = self.current_token().clone();
-                    match &token.kind {
-                        TokenKind::LParen => {
-                            condition.push('(');
-                            paren_depth += 1;
-                        }
-                        TokenKind::RParen => {
-                            paren_depth -= 1;
-                            if paren_depth > 0 {
-                                condition.push(')');
-                            }
-                        }
-                        _ => {
-                            if !condition.ends_with('(') {
-                                condition.push(' ');
-                            }
-                            condition.push_str(&token.lexeme);
-                        }
-                    }
-                    self.next_token();
-                }
-                condition.push(')');
-                continue;
-            }
-
-            // If we hit something else, break
-            break;
-        }
+        let condition = self.parse_css_condition("@media")?;
 
         // Expect opening brace for media query block
         self.expect_and_consume(&TokenKind::LBrace)?;
 
-        // Parse declarations within the media query
+        // Parse declarations and whole rules within the media query
         let mut declarations = Vec::new();
-        let mut decl_iterations = 0;
-
+        let mut rules = Vec::new();
         while self.current_token().kind != TokenKind::RBrace && self.current_token().kind != TokenKind::Eof {
-            decl_iterations += 1;
-            if decl_iterations > 100 {
-                return Err(self.error("Media query declaration parsing exceeded iteration limit"));
+            if matches!(self.current_token().kind, TokenKind::CssAtRule(_) | TokenKind::CssPage) {
+                self.skip_css_unknown_at_rule();
+            } else if self.is_nested_rule_start() {
+                rules.push(self.parse_css_rule()?);
+            } else {
+                declarations.push(self.parse_css_declaration()?);
+                self.consume_if_matches(&TokenKind::Semicolon);
             }
-            declarations.push(self.parse_css_declaration()?);
-            self.consume_if_matches(&TokenKind::Semicolon);
         }
 
         // Expect closing brace
@@ -2419,6 +3847,8 @@ impl<'a> Parser<'a> {
         Ok(CssMediaQuery {
             condition,
             declarations,
+            rules,
+            span,
         })
     }
 
@@ -2427,110 +3857,195 @@ impl<'a> Parser<'a> {
     fn parse_css_container_query(&mut self) -> Result<CssContainerQuery, CompileError> {
         use crate::ast::CssContainerQuery;
 
+        let span = self.css_span();
+
         // Expect @container token
         self.expect_and_consume(&TokenKind::CssContainer)?;
 
-        // Expect opening parenthesis
-        self.expect_and_consume(&TokenKind::LParen)?;
+        let condition = self.parse_css_condition("@container")?;
+        let declarations = self.parse_css_at_rule_declarations("@container")?;
 
-        // Read the condition as a string until we hit the closing paren
-        let mut condition = String::from("(");
-        let mut paren_depth = 1;
-        let mut iterations = 0;
+        Ok(CssContainerQuery {
+            condition,
+            declarations,
+            span,
+        })
+    }
 
-        while paren_depth > 0 && self.current_token().kind != TokenKind::Eof {
-            iterations += 1;
-            if iterations > 100 {
-                return Err(self.error("Container query condition parsing exceeded iteration limit"));
+    /// Parse CSS feature query: @supports (display: grid) { .grid { ... } }
+    fn parse_css_supports(&mut self) -> Result<CssSupports, CompileError> {
+        let span = self.css_span();
+
+        // Expect @supports token
+        self.expect_and_consume(&TokenKind::CssSupports)?;
+
+        let condition = self.parse_css_condition("@supports")?;
+
+        // Its block holds whole rules, like the css! block itself
+        self.expect_and_consume(&TokenKind::LBrace)?;
+        let mut rules = Vec::new();
+        while self.current_token().kind != TokenKind::RBrace && self.current_token().kind != TokenKind::Eof {
+            if matches!(self.current_token().kind, TokenKind::CssAtRule(_) | TokenKind::CssPage) {
+                self.skip_css_unknown_at_rule();
+            } else {
+                rules.push(self.parse_css_rule()?);
             }
-            let token = self.current_token().clone();
+        }
+        self.expect_and_consume(&TokenKind::RBrace)?;
 
-            match &token.kind {
-                TokenKind::LParen => {
-                    condition.push('(');
-                    paren_depth += 1;
-                }
-                TokenKind::RParen => {
-                    paren_depth -= 1;
-                    if paren_depth > 0 {
-                        condition.push(')');
-                    }
+        Ok(CssSupports {
+            condition,
+            rules,
+            span,
+        })
+    }
+
+    /// Parse CSS font face: @font-face { font-family: "Inter"; src: url(...) format("woff2"); }
+    fn parse_css_font_face(&mut self) -> Result<CssFontFace, CompileError> {
+        let span = self.css_span();
+
+        // Expect @font-face token
+        self.expect_and_consume(&TokenKind::CssFontFace)?;
+
+        let declarations = self.parse_css_at_rule_declarations("@font-face")?;
+
+        Ok(CssFontFace {
+            declarations,
+            span,
+        })
+    }
+
+    /// Parse CSS page rule: @page { margin: 1in; } or @page :first { ... }
+    fn parse_css_page(&mut self) -> Result<CssPage, CompileError> {
+        let span = self.css_span();
+
+        // Expect @page token
+        self.expect_and_consume(&TokenKind::CssPage)?;
+
+        // The page selector is optional: `:first`, `:left`, `cover`
+        let selector = if self.current_token().kind == TokenKind::LBrace {
+            String::new()
+        } else {
+            self.parse_css_condition("@page")?.replace(": ", ":")
+        };
+
+        // Margin boxes, like @top-center { ... }, aren't supported
+        self.expect_and_consume(&TokenKind::LBrace)?;
+        let mut declarations = Vec::new();
+        while self.current_token().kind != TokenKind::RBrace && self.current_token().kind != TokenKind::Eof {
+            if let TokenKind::CssAtRule(_) = self.current_token().kind {
+                self.skip_css_unknown_at_rule();
+            } else {
+                declarations.push(self.parse_css_declaration()?);
+                self.consume_if_matches(&TokenKind::Semicolon);
+            }
+        }
+        self.expect_and_consume(&TokenKind::RBrace)?;
+
+        Ok(CssPage {
+            selector,
+            declarations,
+            span,
+        })
+    }
+
+    /// Parse CSS import: @import url("/theme.css") screen; or @import "/theme.css";
+    fn parse_css_import(&mut self) -> Result<CssImport, CompileError> {
+        let span = self.css_span();
+
+        // Expect @import token
+        self.expect_and_consume(&TokenKind::CssImport)?;
+
+        // What's imported is read like a declaration's value
+        let source = self.parse_css_value()?;
+        self.consume_if_matches(&TokenKind::Semicolon);
+
+        Ok(CssImport {
+            source,
+            span,
+        })
+    }
+
+    /// Skip an at-rule css! doesn't support, up to its `;` or through its
+    /// block, recording it to be warned about
+    fn skip_css_unknown_at_rule(&mut self) {
+        // @page is supported only at the top of the block
+        let name = match &self.current_token().kind {
+            TokenKind::CssAtRule(name) => Some(name.clone()),
+            TokenKind::CssPage => Some("page".to_string()),
+            _ => None,
+        };
+        if let Some(name) = name {
+            self.css_unknown_at_rules.push(CssUnknownAtRule { name, span: self.css_span() });
+        }
+        self.next_token();
+
+        let mut depth = 0usize;
+        loop {
+            match self.current_token().kind {
+                TokenKind::Eof => return,
+                // `}` of the enclosing block: a statement at-rule missing its `;`
+                TokenKind::RBrace if depth == 0 => return,
+                TokenKind::Semicolon if depth == 0 => {
+                    self.next_token();
+                    return;
                 }
-                _ => {
-                    // Add token lexeme with space
-                    if !condition.ends_with('(') {
-                        condition.push(' ');
+                TokenKind::LBrace => depth += 1,
+                TokenKind::RBrace => {
+                    depth -= 1;
+                    if depth == 0 {
+                        self.next_token();
+                        return;
                     }
-                    condition.push_str(&token.lexeme);
                 }
+                _ => {}
             }
-
             self.next_token();
         }
+    }
 
-        condition.push(')');
-
-        // Continue reading tokens for complex conditions
-        while self.current_token().kind != TokenKind::LBrace && self.current_token().kind != TokenKind::Eof {
-            let token = self.current_token().clone();
+    /// Read the condition of an @media, @container or @supports rule up to
+    /// its `{`: `screen and (min-width: 768px), print`. `rule` is the
+    /// at-rule, for errors.
+    fn parse_css_condition(&mut self, rule: &str) -> Result<String, CompileError> {
+        let mut condition = String::new();
+        let mut iterations = 0;
 
-            // Handle "and" or "or" keywords
-            if let TokenKind::CssProperty(ref prop) = token.kind {
-                if prop == "and" || prop == "or" {
-                    condition.push(' ');
-                    condition.push_str(&token.lexeme);
-                    self.next_token();
-                    continue;
-                }
+        while !matches!(self.current_token().kind, TokenKind::LBrace | TokenKind::RBrace | TokenKind::Semicolon | TokenKind::Eof) {
+            iterations += 1;
+            if iterations > 100 {
+                return Err(self.error(&format!("{} condition parsing exceeded iteration limit", rule)));
             }
 
-            // Handle additional parenthesized conditions
-            if token.kind == TokenKind::LParen {
+            // Tokens are joined with spaces, except inside the edges of
+            // parentheses and before `:` and `,`: `(min-width: 768px), print`
+            let lexeme = &self.current_token().lexeme;
+            if !(condition.is_empty() || condition.ends_with('(') || matches!(lexeme.as_str(), ")" | ":" | ",")) {
                 condition.push(' ');
-                condition.push('(');
-                paren_depth = 1;
-                self.next_token();
-
-                while paren_depth > 0 && self.current_token().kind != TokenKind::Eof {
-                    let token = self.current_token().clone();
-                    match &token.kind {
-                        TokenKind::LParen => {
-                            condition.push('(');
-                            paren_depth += 1;
-                        }
-                        TokenKind::RParen => {
-                            paren_depth -= 1;
-                            if paren_depth > 0 {
-                                condition.push(')');
-                            }
-                        }
-                        _ => {
-                            if !condition.ends_with('(') {
-                                condition.push(' ');
-                            }
-                            condition.push_str(&token.lexeme);
-                        }
-                    }
-                    self.next_token();
-                }
-                condition.push(')');
-                continue;
             }
+            condition.push_str(lexeme);
+            self.next_token();
+        }
 
-            break;
+        if condition.is_empty() {
+            return Err(self.error(&format!("Expected a condition after {}", rule)));
         }
+        Ok(condition)
+    }
 
-        // Expect opening brace for container query block
+    /// Parse the `{ property: value; ... }` block of an at-rule. `rule` is
+    /// the at-rule, for errors.
+    fn parse_css_at_rule_declarations(&mut self, rule: &str) -> Result<Vec<CssDeclaration>, CompileError> {
+        // Expect opening brace for the at-rule's block
         self.expect_and_consume(&TokenKind::LBrace)?;
 
-        // Parse declarations within the container query
         let mut declarations = Vec::new();
         let mut decl_iterations = 0;
 
         while self.current_token().kind != TokenKind::RBrace && self.current_token().kind != TokenKind::Eof {
             decl_iterations += 1;
             if decl_iterations > 100 {
-                return Err(self.error("Container query declaration parsing exceeded iteration limit"));
+                return Err(self.error(&format!("{} declaration parsing exceeded iteration limit", rule)));
             }
             declarations.push(self.parse_css_declaration()?);
             self.consume_if_matches(&TokenKind::Semicolon);
@@ -2539,20 +4054,18 @@ impl<'a> Parser<'a> {
         // Expect closing brace
         self.expect_and_consume(&TokenKind::RBrace)?;
 
-        Ok(CssContainerQuery {
-            condition,
-            declarations,
-        })
+        Ok(declarations)
     }
 
     /// Parse @keyframes animation: @keyframes fadeIn { from { ... } to { ... } }
     /// Sprint 2 Task 2.6
     /// Note: @ and 'keyframes' tokens should already be consumed by caller
     fn parse_css_keyframes(&mut self) -> Result<CssKeyframes, CompileError> {
-        use crate::ast::{CssKeyframes, CssKeyframeRule, CssKeyframeSelector};
+        use crate::ast::{CssKeyframes, CssKeyframeRule};
 
         // Note: @keyframes token (or @ + keyframes) already consumed by dispatcher
         // Current token should be the animation name
+        let span = self.css_span();
 
         // Parse animation name (identifier or CSS selector)
         let name_token = self.current_token().clone();
@@ -2588,66 +4101,9 @@ impl<'a> Parser<'a> {
         let mut frames = Vec::new();
 
         while self.current_token().kind != TokenKind::RBrace && self.current_token().kind != TokenKind::Eof {
-            let selector_token = self.current_token().clone();
-
-            // Parse keyframe selector: from, to, or percentage
-            let selector = match &selector_token.kind {
-                TokenKind::Identifier => {
-                    if selector_token.lexeme == "from" {
-                        self.next_token();
-                        CssKeyframeSelector::From
-                    } else if selector_token.lexeme == "to" {
-                        self.next_token();
-                        CssKeyframeSelector::To
-                    } else {
-                        return Err(self.error(&format!("Expected 'from' or 'to', found '{}'", selector_token.lexeme)));
-                    }
-                }
-                TokenKind::CssSelector(sel) => {
-                    // In CSS mode, from/to might be read as CSS selectors
-                    if sel == "from" {
-                        self.next_token();
-                        CssKeyframeSelector::From
-                    } else if sel == "to" {
-                        self.next_token();
-                        CssKeyframeSelector::To
-                    } else {
-                        return Err(self.error(&format!("Expected 'from' or 'to', found '{}'", sel)));
-                    }
-                }
-                TokenKind::CssValue(val) => {
-                    // Could be "50%" or just "0" (percentage without %)
-                    if val.ends_with('%') {
-                        // Parse percentage: "50%" -> 50.0
-                        self.next_token();
-                        let num_str = val.trim_end_matches('%');
-                        let percentage = num_str.parse::<f64>()
-                            .map_err(|_| self.error(&format!("Invalid percentage: {}", val)))?;
-                        CssKeyframeSelector::Percentage(percentage)
-                    } else {
-                        // Just a number, expect % next
-                        let num = val.parse::<f64>()
-                            .map_err(|_| self.error(&format!("Invalid number: {}", val)))?;
-                        self.next_token();
-                        // Optionally consume % if present
-                        if self.current_token().kind == TokenKind::Percent {
-                            self.next_token();
-                        }
-                        CssKeyframeSelector::Percentage(num)
-                    }
-                }
-                TokenKind::Integer(n) => {
-                    // Just a number like 0, 50, 100 (assume %)
-                    let num = *n as f64;
-                    self.next_token();
-                    // Expect % sign
-                    if self.current_token().kind == TokenKind::Percent {
-                        self.next_token();
-                    }
-                    CssKeyframeSelector::Percentage(num)
-                }
-                _ => return Err(self.error(&format!("Expected keyframe selector (from/to/percentage), found {:?}", selector_token.kind)))
-            };
+            // Parse keyframe selectors: from, to, or percentages, maybe several: 0%, 100%
+            let frame_span = self.css_span();
+            let selectors = self.parse_css_keyframe_selectors()?;
 
             // Expect opening brace for keyframe declarations
             self.expect_and_consume(&TokenKind::LBrace)?;
@@ -2663,59 +4119,125 @@ impl<'a> Parser<'a> {
             self.expect_and_consume(&TokenKind::RBrace)?;
 
             frames.push(CssKeyframeRule {
-                selector,
+                selectors,
                 declarations,
+                span: frame_span,
             });
         }
 
         // Expect closing brace for keyframes block
         self.expect_and_consume(&TokenKind::RBrace)?;
 
-        Ok(CssKeyframes { name, frames })
+        Ok(CssKeyframes { name, frames, span })
     }
 
-    /// Parse a compound selector from a string like ".button:hover" or ".button.primary"
-    /// Returns a Compound variant containing the individual selectors
-    fn parse_compound_selector_from_string(&self, selector_str: &str) -> Result<CssSelector, CompileError> {
+    /// Parse the comma-separated selectors of a keyframe: `0%, 100%` or `from, to`
+    fn parse_css_keyframe_selectors(&mut self) -> Result<Vec<CssKeyframeSelector>, CompileError> {
         let mut selectors = Vec::new();
-        let mut current = String::new();
-
-        let chars: Vec<char> = selector_str.chars().collect();
-        let mut i = 0;
-        while i < chars.len() {
-            let ch = chars[i];
-            match ch {
-                '.' => {
-                    // New class selector
-                    if !current.is_empty() {
-                        selectors.push(self.selector_from_string(&current)?);
-                    }
-                    current = String::from(".");
-                    i += 1;
+        loop {
+            if let TokenKind::CssSelector(list) = &self.current_token().kind {
+                // In CSS mode, from/to and whatever follows them on the line are read as one selector
+                for part in CssSelector::split_list(list) {
+                    selectors.push(self.css_keyframe_selector_from_str(part)?);
                 }
-                ':' => {
-                    // Check if next char is also : (::)
-                    if !current.is_empty() {
-                        selectors.push(self.selector_from_string(&current)?);
-                    }
-                    if i + 1 < chars.len() && chars[i + 1] == ':' {
-                        current = String::from("::");
-                        i += 2; // skip both colons
-                    } else {
-                        current = String::from(":");
-                        i += 1;
+                self.next_token();
+            } else {
+                selectors.push(self.parse_css_keyframe_selector()?);
+            }
+
+            if !self.consume_if_matches(&TokenKind::Comma) {
+                return Ok(selectors);
+            }
+        }
+    }
+
+    /// Read a keyframe selector lexed as a CSS selector: from, to or 50%
+    fn css_keyframe_selector_from_str(&self, selector: &str) -> Result<CssKeyframeSelector, CompileError> {
+        match selector {
+            "from" => Ok(CssKeyframeSelector::From),
+            "to" => Ok(CssKeyframeSelector::To),
+            _ => selector.strip_suffix('%')
+                .and_then(|percentage| percentage.parse::<f64>().ok())
+                .map(CssKeyframeSelector::Percentage)
+                .ok_or_else(|| self.error(&format!("Expected 'from' or 'to', found '{}'", selector))),
+        }
+    }
+
+    /// Parse a keyframe selector: from, to, or a percentage like 50%
+    fn parse_css_keyframe_selector(&mut self) -> Result<CssKeyframeSelector, CompileError> {
+        let selector_token = self.current_token().clone();
+
+        let selector = match &selector_token.kind {
+            TokenKind::Identifier => {
+                if selector_token.lexeme == "from" {
+                    self.next_token();
+                    CssKeyframeSelector::From
+                } else if selector_token.lexeme == "to" {
+                    self.next_token();
+                    CssKeyframeSelector::To
+                } else {
+                    return Err(self.error(&format!("Expected 'from' or 'to', found '{}'", selector_token.lexeme)));
+                }
+            }
+            TokenKind::CssValue(val) => {
+                // Could be "50%" or just "0" (percentage without %)
+                if val.ends_with('%') {
+                    // Parse percentage: "50%" -> 50.0
+                    self.next_token();
+                    let num_str = val.trim_end_matches('%');
+                    let percentage = num_str.parse::<f64>()
+                        .map_err(|_| self.error(&format!("Invalid percentage: {}", val)))?;
+                    CssKeyframeSelector::Percentage(percentage)
+                } else {
+                    // Just a number, expect % next
+                    let num = val.parse::<f64>()
+                        .map_err(|_| self.error(&format!("Invalid number: {}", val)))?;
+                    self.next_token();
+                    // Optionally consume % if present
+                    if self.current_token().kind == TokenKind::Percent {
+                        self.next_token();
                     }
+                    CssKeyframeSelector::Percentage(num)
                 }
-                _ => {
-                    current.push(ch);
-                    i += 1;
+            }
+            TokenKind::Integer(n) => {
+                // Just a number like 0, 50, 100 (assume %)
+                let num = *n as f64;
+                self.next_token();
+                // Expect % sign
+                if self.current_token().kind == TokenKind::Percent {
+                    self.next_token();
                 }
+                CssKeyframeSelector::Percentage(num)
             }
+            _ => return Err(self.error(&format!("Expected keyframe selector (from/to/percentage), found {:?}", selector_token.kind)))
+        };
+
+        Ok(selector)
+    }
+
+    /// Parse a compound selector from a string like ".button:hover" or ".button.primary"
+    /// Returns a Compound variant containing the individual selectors
+    fn parse_compound_selector_from_string(&self, selector_str: &str) -> Result<CssSelector, CompileError> {
+        let mut selectors = Vec::new();
+        let mut start = 0;
+
+        // A new selector starts at each top-level `.`, `:` or `[`; the second
+        // `:` of `::after` and whatever is in parentheses, brackets or quotes
+        // stay with the one they're in
+        let mut previous = None;
+        for (index, ch, top) in CssSelector::top_level_chars(selector_str) {
+            let starts_selector = top && matches!(ch, '.' | ':' | '[') && !(ch == ':' && previous == Some(':'));
+            if starts_selector && index > start {
+                selectors.push(self.selector_from_string(&selector_str[start..index])?);
+                start = index;
+            }
+            previous = Some(ch);
         }
 
         // Don't forget the last selector
-        if !current.is_empty() {
-            selectors.push(self.selector_from_string(&current)?);
+        if start < selector_str.len() {
+            selectors.push(self.selector_from_string(&selector_str[start..])?);
         }
 
         if selectors.len() > 1 {
@@ -2729,14 +4251,16 @@ impl<'a> Parser<'a> {
 
     /// Convert a selector string fragment to a CssSelector
     fn selector_from_string(&self, s: &str) -> Result<CssSelector, CompileError> {
-        if s.starts_with('.') {
-            Ok(CssSelector::Class(s[1..].to_string()))
-        } else if s.starts_with('#') {
-            Ok(CssSelector::Id(s[1..].to_string()))
-        } else if s.starts_with("::") {
-            Ok(CssSelector::PseudoElement(s[2..].to_string()))
-        } else if s.starts_with(':') {
-            Ok(CssSelector::PseudoClass(s[1..].to_string()))
+        if let Some(class) = s.strip_prefix('.') {
+            Ok(CssSelector::Class(class.to_string()))
+        } else if let Some(id) = s.strip_prefix('#') {
+            Ok(CssSelector::Id(id.to_string()))
+        } else if let Some(pseudo_element) = s.strip_prefix("::") {
+            Ok(CssSelector::PseudoElement(pseudo_element.to_string()))
+        } else if let Some(pseudo_class) = s.strip_prefix(':') {
+            Ok(CssSelector::PseudoClass(pseudo_class.to_string()))
+        } else if let Some(attribute) = s.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+            Ok(CssSelector::Attribute(attribute.to_string()))
         } else {
             Ok(CssSelector::Element(s.to_string()))
         }
@@ -2830,7 +4354,7 @@ impl<'a> Parser<'a> {
     fn parse_type_parameters_single(&mut self) -> Result<TypeExpression, CompileError> {
         self.expect_and_consume(&TokenKind::LAngle)?;
         let type_expr = self.parse_type_expression()?;
-        self.expect_and_consume(&TokenKind::RAngle)?;
+        self.expect_closing_angle()?;
         Ok(type_expr)
     }
 
@@ -3074,6 +4598,127 @@ mod tests {
         parser.parse_expression(Precedence::Lowest)
     }
 
+    #[test]
+    fn test_template_literal() {
+        let Expression::Template(template) = parse_expr("`${count} item${ if count == 1 { `` } else { `s` } }`").unwrap() else {
+            panic!("expected a template");
+        };
+        assert_eq!(template.parts.len(), 3);
+        assert!(matches!(template.parts[0], TemplatePart::Interpolation(ref expr) if matches!(**expr, Expression::Identifier(_))));
+        assert!(matches!(template.parts[1], TemplatePart::Text(ref text) if text == " item"));
+        let TemplatePart::Interpolation(nested) = &template.parts[2] else {
+            panic!("expected an interpolation");
+        };
+        assert!(matches!(**nested, Expression::IfExpression(_)), "{:?}", nested);
+    }
+
+    #[test]
+    fn test_compound_assignment_desugars() {
+        let mut lexer = Lexer::new("fn f() { count += 1; items[0] -= step * 2; for (let mut i = 0; i < 9; i *= 3) {} }".to_string());
+        let program = Parser::new(&mut lexer).parse_program().unwrap();
+        let Statement::Function(function) = &program.statements[0] else {
+            panic!("expected a function");
+        };
+        let statements = &function.body.statements;
+        let Statement::Assignment(assign) = &statements[0] else {
+            panic!("expected an assignment, got {:?}", statements[0]);
+        };
+        assert!(matches!(assign.target, Expression::Identifier(ref name) if name.value == "count"));
+        let Expression::Infix(sum) = &assign.value else {
+            panic!("expected count + 1");
+        };
+        assert_eq!(sum.operator.kind, TokenKind::Plus);
+        assert!(matches!(*sum.left, Expression::Identifier(ref name) if name.value == "count"));
+
+        let Statement::Assignment(assign) = &statements[1] else {
+            panic!("expected an assignment");
+        };
+        assert!(matches!(assign.target, Expression::IndexAccess(_)));
+        assert!(matches!(assign.value, Expression::Infix(ref infix) if infix.operator.kind == TokenKind::Minus));
+
+        let Statement::For(for_loop) = &statements[2] else {
+            panic!("expected a for loop");
+        };
+        assert!(matches!(for_loop.update.as_deref(), Some(Statement::Assignment(_))));
+    }
+
+    #[test]
+    fn test_optional_chaining_and_coalescing() {
+        let Expression::Infix(default) = parse_expr("user?.name ?? a || b").unwrap() else {
+            panic!("expected ??");
+        };
+        // `??` binds loosest, so the default is `a || b`
+        assert_eq!(default.operator.kind, TokenKind::QuestionQuestion);
+        assert!(matches!(*default.right, Expression::Infix(ref or) if or.operator.kind == TokenKind::PipePipe));
+        let Expression::FieldAccess(access) = *default.left else {
+            panic!("expected user?.name");
+        };
+        assert!(access.optional);
+        assert_eq!(access.field.value, "name");
+
+        let Expression::FieldAccess(chain) = parse_expr("a?.b.c").unwrap() else {
+            panic!("expected a field access");
+        };
+        assert!(!chain.optional);
+        assert!(matches!(*chain.object, Expression::FieldAccess(ref inner) if inner.optional));
+    }
+
+    #[test]
+    fn test_doc_comments_attach_to_items() {
+        let source = "//! Math helpers.\n\n/// Adds two numbers.\n///\n/// Saturates.\n@memo\nfn add(a: i32, b: i32) -> i32 {\n    //! Pure.\n    /// not an item\n    let c = a + b;\n    return c;\n}\n\n/// A point.\nstruct Point { x: i32 }\n\nimpl Point {\n    /// The x.\n    fn get(self: Point) -> i32 { return self.x; }\n}\n";
+        let mut lexer = Lexer::new(source.to_string());
+        let mut parser = Parser::new(&mut lexer);
+        let program = parser.parse_program().unwrap();
+        assert_eq!(parser.module_doc(), Some("Math helpers."));
+
+        let Statement::Function(add) = &program.statements[0] else {
+            panic!("expected a function");
+        };
+        assert_eq!(add.doc.as_deref(), Some("Adds two numbers.\n\nSaturates.\nPure."));
+        assert_eq!(add.body.statements.len(), 2);
+        let Statement::Struct(point) = &program.statements[1] else {
+            panic!("expected a struct");
+        };
+        assert_eq!(point.doc.as_deref(), Some("A point."));
+        let Statement::ImplBlock(impl_block) = &program.statements[2] else {
+            panic!("expected an impl");
+        };
+        assert_eq!(impl_block.methods[0].doc.as_deref(), Some("The x."));
+
+        let mut lexer = Lexer::new("fn f() {\n    let x = 1;\n    //! late\n    let y = 2;\n}".to_string());
+        let error = Parser::new(&mut lexer).parse_program().expect_err("misplaced //!");
+        assert!(error.to_string().contains("`//!` documents the enclosing item"), "{}", error);
+    }
+
+    #[test]
+    fn test_shifts_and_nested_generics() {
+        let Expression::Infix(shift) = parse_expr("a >> 2").unwrap() else {
+            panic!("expected a shift");
+        };
+        assert_eq!(shift.operator.kind, TokenKind::RightShift);
+
+        let mut lexer = Lexer::new("fn f(m: HashMap<String, Vec<i32>>, x: i32) { let v: Vec<Vec<i32>>= m; x <<= 1; }".to_string());
+        let program = Parser::new(&mut lexer).parse_program().unwrap();
+        let Statement::Function(function) = &program.statements[0] else {
+            panic!("expected a function");
+        };
+        let TypeExpression::Generic(name, args) = &function.parameters[0].type_annotation else {
+            panic!("expected a generic type");
+        };
+        assert_eq!(name.value, "HashMap");
+        assert!(matches!(&args[1], TypeExpression::Generic(inner, _) if inner.value == "Vec"));
+        assert_eq!(function.parameters.len(), 2);
+
+        let Statement::Let(binding) = &function.body.statements[0] else {
+            panic!("expected a let");
+        };
+        assert!(matches!(binding.type_annotation, Some(TypeExpression::Generic(..))));
+        let Statement::Assignment(assign) = &function.body.statements[1] else {
+            panic!("expected an assignment");
+        };
+        assert!(matches!(assign.value, Expression::Infix(ref infix) if infix.operator.kind == TokenKind::LeftShift));
+    }
+
     #[test]
     fn test_jsx_empty_element() {
         let expr = parse_expr("<div></div>").unwrap();
@@ -3121,6 +4766,109 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_jsx_comments() {
+        let Expression::JsxElement(jsx) = parse_expr("<ul>{/* TODO */}<li>a</li>{/* two\n   lines */}\n{// line\n}</ul>").unwrap() else {
+            panic!("expected an element");
+        };
+        assert_eq!(jsx.children.len(), 1);
+        assert!(matches!(&jsx.children[0], JsxChild::Element(li) if li.opening_tag.name.value == "li"));
+
+        // A comment beside an expression is skipped like any other
+        let Expression::JsxElement(jsx) = parse_expr("<p>a {/* count */ count} b</p>").unwrap() else {
+            panic!("expected an element");
+        };
+        assert!(matches!(&jsx.children[1], JsxChild::Expression(count) if matches!(**count, Expression::Identifier(ref name) if name.value == "count")));
+
+        let Expression::JsxElement(jsx) = parse_expr("<input {/* first */} id=\"a\" {/* between */} value={/* v */ 1} />").unwrap() else {
+            panic!("expected an element");
+        };
+        let names: Vec<&str> = jsx.opening_tag.attributes.iter().map(|attribute| attribute.name.value.as_str()).collect();
+        assert_eq!(names, ["id", "value"]);
+        assert!(matches!(jsx.opening_tag.attributes[1].value, Expression::IntegerLiteral(1)));
+    }
+
+    #[test]
+    fn test_jsx_whitespace() {
+        let texts = |source: &str| -> Vec<String> {
+            let Expression::JsxElement(jsx) = parse_expr(source).unwrap() else {
+                panic!("expected an element");
+            };
+            fn collect(jsx: &JsxElement, texts: &mut Vec<String>) {
+                for child in &jsx.children {
+                    match child {
+                        JsxChild::Text(text) => texts.push(text.clone()),
+                        JsxChild::Element(element) => collect(element, texts),
+                        JsxChild::Expression(_) => texts.push("{}".to_string()),
+                    }
+                }
+            }
+            let mut texts = Vec::new();
+            collect(&jsx, &mut texts);
+            texts
+        };
+
+        // Spaces between inline content are kept; indentation and line breaks go
+        assert_eq!(texts("<p><span>Hello </span><span>world</span></p>"), ["Hello ", "world"]);
+        assert_eq!(texts("<p><b>a</b> <i>b</i> {c} d</p>"), ["a", " ", "b", " ", "{}", " d"]);
+        assert_eq!(texts("<ul>\n    <li>\n        One\n        two\n    </li>\n</ul>"), ["One two"]);
+        assert_eq!(texts("<div><img />\n    {x}\n</div>"), ["{}"]);
+
+        // <pre> and <textarea> keep their text as written, even inside other tags
+        assert_eq!(texts("<pre>  two  spaces  </pre>"), ["  two  spaces  "]);
+        assert_eq!(texts("<pre>\n  a\n  <b> b </b>\n</pre>"), ["\n  a\n  ", " b ", "\n"]);
+        assert_eq!(texts("<div><textarea />\n    x\n</div>"), ["x"]);
+        assert_eq!(texts("<div><textarea rows={3} />\n    x\n</div>"), ["x"]);
+        assert_eq!(texts("<div><pre> a </pre>\n    <p> b\n    </p></div>"), [" a ", " b"]);
+    }
+
+    #[test]
+    fn test_jsx_style_and_script_text_is_raw() {
+        let children = |source: &str| -> Vec<JsxChild> {
+            let Expression::JsxElement(jsx) = parse_expr(source).unwrap() else {
+                panic!("expected an element");
+            };
+            jsx.children
+        };
+        let text = |child: &JsxChild| match child {
+            JsxChild::Text(text) => text.clone(),
+            other => panic!("expected text, got {:?}", other),
+        };
+
+        // Braces, `<` and entities are CSS or JavaScript, up to the closing tag
+        let style = children("<style>\n  .a > b { color: red; } /* &amp; */\n</style>");
+        assert_eq!(style.len(), 1);
+        assert_eq!(text(&style[0]), "\n  .a > b { color: red; } /* &amp; */\n");
+        let script = children("<script>if (a < b) { x = {y: 1}; } // </scripts\n</script >");
+        assert_eq!(text(&script[0]), "if (a < b) { x = {y: 1}; } // </scripts\n");
+
+        // Only the element's own text; what follows is JSX again
+        let Expression::JsxElement(jsx) = parse_expr("<div><style />\n<style>.a { }</style><p>{x} &amp;</p></div>").unwrap() else {
+            panic!("expected an element");
+        };
+        assert_eq!(jsx.children.len(), 3);
+        let JsxChild::Element(p) = &jsx.children[2] else { panic!("expected <p>") };
+        assert!(matches!(p.children[0], JsxChild::Expression(_)));
+        assert_eq!(text(&p.children[1]), " &");
+    }
+
+    #[test]
+    fn test_jsx_html_entities() {
+        // `&lt;` is a `<` in the text, not the start of a tag
+        let Expression::JsxElement(jsx) = parse_expr("<p>a &lt;b&gt; c &amp; Tom & Jerry<b>x</b></p>").unwrap() else {
+            panic!("expected an element");
+        };
+        assert_eq!(jsx.children.len(), 2);
+        assert!(matches!(&jsx.children[0], JsxChild::Text(text) if text == "a <b> c & Tom & Jerry"));
+        assert!(matches!(&jsx.children[1], JsxChild::Element(b) if b.opening_tag.name.value == "b"));
+
+        // Decoded after whitespace is collapsed, so a non-breaking space at the edge stays
+        let Expression::JsxElement(jsx) = parse_expr("<td>\n    &nbsp;&#169;&#x2014;&bogus;\n</td>").unwrap() else {
+            panic!("expected an element");
+        };
+        assert!(matches!(&jsx.children[0], JsxChild::Text(text) if text == "\u{A0}\u{A9}\u{2014}&bogus;"));
+    }
+
     #[test]
     fn test_jsx_with_single_attribute() {
         let expr = parse_expr(r#"<div class="container"></div>"#).unwrap();
@@ -3151,6 +4899,91 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_jsx_dotted_component_names() {
+        let Expression::JsxElement(jsx) = parse_expr("<Icons.Arrow.Left size={2}><ui.Label for=\"x\" /></Icons.Arrow.Left>").unwrap() else {
+            panic!("expected an element");
+        };
+        assert_eq!(jsx.tag_name(), "Icons.Arrow.Left");
+        assert_eq!(jsx.closing_tag.as_ref().unwrap().value, "Icons.Arrow.Left");
+        assert_eq!(attribute_names(&jsx), vec!["size"]);
+        assert!(matches!(&jsx.children[0], JsxChild::Element(label) if label.tag_name() == "ui.Label" && label.is_self_closing()));
+
+        // The whole path has to match
+        assert!(parse_expr("<ui.Button></ui.Link>").is_err());
+        assert!(parse_expr("<ui.Button></ui>").is_err());
+
+        // Self-closing in a closure, where `)` after the tag closes the call
+        let Expression::JsxElement(jsx) = parse_expr("<ul>{items.map(|item| <ui.Item key={item} />)} <b>done</b></ul>").unwrap() else {
+            panic!("expected an element");
+        };
+        assert_eq!(jsx.children.len(), 3);
+        assert!(matches!(&jsx.children[2], JsxChild::Element(b) if b.tag_name() == "b"));
+    }
+
+    #[test]
+    fn test_jsx_generic_components() {
+        let Expression::JsxElement(jsx) = parse_expr("<List<User> items={users} />").unwrap() else {
+            panic!("expected an element");
+        };
+        assert_eq!(jsx.tag_name(), "List");
+        assert!(matches!(&jsx.opening_tag.type_args[..], [TypeExpression::Named(user)] if user.value == "User"));
+        assert_eq!(attribute_names(&jsx), vec!["items"]);
+        assert!(jsx.is_self_closing());
+
+        // Nested generics close with two `>` the lexer reads one at a time;
+        // the closing tag is the bare name
+        let Expression::JsxElement(jsx) = parse_expr("<Table<Map<String, Vec<i32>>, bool> rows={rows}>{n > 1} rows</Table>").unwrap() else {
+            panic!("expected an element");
+        };
+        assert_eq!(jsx.opening_tag.type_args.len(), 2);
+        assert!(matches!(&jsx.opening_tag.type_args[0], TypeExpression::Generic(map, args) if map.value == "Map" && args.len() == 2));
+        assert_eq!(attribute_names(&jsx), vec!["rows"]);
+        assert_eq!(jsx.children.len(), 2);
+        assert!(matches!(&jsx.children[1], JsxChild::Text(text) if text == " rows"));
+        assert!(parse_expr("<List<User>></List<User>>").is_err());
+    }
+
+    #[test]
+    fn test_jsx_hyphenated_and_namespaced_attributes() {
+        let Expression::JsxElement(jsx) = parse_expr(r#"<div data-test-id="x" aria-label="Close" hidden data-for={n} data-2="b"></div>"#).unwrap() else {
+            panic!("expected an element");
+        };
+        assert_eq!(attribute_names(&jsx), vec!["data-test-id", "aria-label", "hidden", "data-for", "data-2"]);
+        assert!(matches!(jsx.opening_tag.attributes[2].value, Expression::BoolLiteral(true)));
+
+        let Expression::JsxElement(jsx) = parse_expr(r##"<svg xmlns:xlink="http://www.w3.org/1999/xlink" stroke-width="2"><image xlink:href="#a" /></svg>"##).unwrap() else {
+            panic!("expected an element");
+        };
+        assert_eq!(attribute_names(&jsx), vec!["xmlns:xlink", "stroke-width"]);
+        let JsxChild::Element(image) = &jsx.children[0] else { panic!("expected an element") };
+        assert_eq!(attribute_names(image), vec!["xlink:href"]);
+
+        // Only one namespace, and only without spaces
+        assert!(parse_expr(r#"<a b:c:d="x"></a>"#).is_err());
+        assert!(parse_expr(r#"<a data -x="x"></a>"#).is_err());
+    }
+
+    #[test]
+    fn test_jsx_spread_attributes() {
+        let Expression::JsxElement(jsx) = parse_expr("<Button {...base} label=\"hi\" {...props.extra} onClick={|| save()} />").unwrap() else {
+            panic!("expected an element");
+        };
+        let attributes = &jsx.opening_tag.attributes;
+        assert_eq!(attribute_names(&jsx), vec!["...", "label", "...", "onClick"]);
+        assert!(matches!(attributes[0].spread(), Some(Expression::Identifier(name)) if name.value == "base"));
+        assert!(matches!(attributes[2].spread(), Some(Expression::FieldAccess(_))));
+        assert!(attributes[1].spread().is_none());
+        assert!(matches!(attributes[3].value, Expression::Lambda(_)));
+
+        // Children still follow a tag that ends with a spread
+        let Expression::JsxElement(jsx) = parse_expr("<div {...rest}>{count}</div>").unwrap() else {
+            panic!("expected an element");
+        };
+        assert!(jsx.opening_tag.attributes[0].spread().is_some());
+        assert!(matches!(&jsx.children[0], JsxChild::Expression(_)));
+    }
+
     #[test]
     fn test_jsx_self_closing_with_attribute() {
         let expr = parse_expr(r#"<img src="photo.jpg" />"#).unwrap();
@@ -3220,10 +5053,221 @@ mod tests {
     #[test]
     fn test_jsx_mismatched_tags() {
         let result = parse_expr("<div></span>");
-        assert!(result.is_err());
-        if let Err(CompileError::ParserError { message, .. }) = result {
-            assert!(message.contains("Mismatched"));
+        let Err(CompileError::WithLocation { message, suggestion, labels, .. }) = result else {
+            panic!("expected a located error, got {:?}", result);
+        };
+        assert_eq!(message, "unclosed <div> opened at line 1:1, found </span> at 1:6");
+        assert_eq!(suggestion.as_deref(), Some("change it to `</div>`"));
+        assert_eq!(labels[0].message, "<div> opened here");
+    }
+
+    #[test]
+    fn test_jsx_void_elements() {
+        let Expression::JsxElement(jsx) = parse_expr("<p>one<br>two <img src=\"a.png\" alt={alt}> three<hr/></p>").unwrap() else {
+            panic!("expected an element");
+        };
+        let tags: Vec<(&str, bool)> = jsx.children.iter().filter_map(|child| match child {
+            JsxChild::Element(el) => Some((el.tag_name(), el.opening_tag.slash_omitted)),
+            _ => None,
+        }).collect();
+        assert_eq!(tags, vec![("br", true), ("img", true), ("hr", false)]);
+        assert!(matches!(&jsx.children[2], JsxChild::Text(text) if text == "two "));
+        assert!(matches!(&jsx.children[4], JsxChild::Text(text) if text == " three"));
+
+        // Code goes on after one, and a <pre> keeps its text as written around one
+        let mut lexer = Lexer::new("component App() {\n    let x = <input value={v}>;\n    return <pre>a<br>  b</pre>;\n}".to_string());
+        let program = Parser::new(&mut lexer).parse_program().unwrap();
+        let Statement::Component(app) = &program.statements[0] else { panic!("expected a component") };
+        let Statement::Return(ret) = &app.body.statements[1] else { panic!("expected a return") };
+        let Expression::JsxElement(pre) = &ret.value else { panic!("expected an element") };
+        assert!(matches!(&pre.children[2], JsxChild::Text(text) if text == "  b"));
+    }
+
+    #[test]
+    fn test_jsx_text_after_element_ending_an_expression() {
+        // The token after `/>` or a void element's `>` is read outside the element
+        for source in ["<p>{<Badge />} after</p>", "<p>{ok(<br>)} after</p>", "<p>{if a { <i/> } else { <hr> }} after</p>"] {
+            let Ok(Expression::JsxElement(jsx)) = parse_expr(source) else { panic!("{} didn't parse", source) };
+            assert!(matches!(jsx.children.as_slice(), [JsxChild::Expression(_), JsxChild::Text(text)] if text == " after"), "{}: {:?}", source, jsx.children);
+        }
+    }
+
+    #[test]
+    fn test_jsx_delimiters_are_text_among_children() {
+        // Whatever comes before them, `)`, `]`, `,` and `;` among children are text
+        let Ok(Expression::JsxElement(jsx)) = parse_expr("<p><b>x</b>, and <br/>; then (<A />) [<i>y</i> ] Done (3/4)</p>") else {
+            panic!("expected an element");
+        };
+        let texts: Vec<&str> = jsx.children.iter()
+            .filter_map(|child| match child {
+                JsxChild::Text(text) => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(texts, vec![", and ", "; then (", ") [", " ] Done (3/4)"]);
+
+        // After an element that ends an expression they are the expression's
+        let Ok(Expression::ArrayLiteral(array)) = parse_expr("[ <A/>, <B />, <b>x</b> ]") else { panic!("expected an array") };
+        assert_eq!(array.elements.len(), 3);
+        let Ok(Expression::Match(arms)) = parse_expr("match n { 1 => <A />, 2 => <b>two</b>, _ => <B/> }") else { panic!("expected a match") };
+        assert_eq!(arms.arms.len(), 3);
+    }
+
+    #[test]
+    fn test_jsx_text_of_punctuation_and_numbers() {
+        for (source, expected) in [
+            ("<span>-</span>", "-"),
+            ("<td>42</td>", "42"),
+            ("<li>3.</li>", "3."),
+            ("<p>/</p>", "/"),
+            ("<p>=</p>", "="),
+            ("<p>></p>", ">"),
+            ("<p>1 / 2 // half</p>", "1 / 2 // half"),
+        ] {
+            let Ok(Expression::JsxElement(jsx)) = parse_expr(source) else {
+                panic!("expected an element from {}", source);
+            };
+            let [JsxChild::Text(text)] = jsx.children.as_slice() else {
+                panic!("{}: got {:?}", source, jsx.children);
+            };
+            assert_eq!(text, expected);
         }
+
+        // Outside an element `>=` and `>>` after a `<` comparison are operators still
+        let Ok(Expression::Infix(and)) = parse_expr("a < b && c >= d") else { panic!("expected &&") };
+        assert!(matches!(and.right.as_ref(), Expression::Infix(compare) if compare.operator.kind == TokenKind::GtEq));
+    }
+
+    #[test]
+    fn test_jsx_conditionals_without_parentheses() {
+        let Ok(Expression::JsxElement(jsx)) = parse_expr("<div>{items.len() > 0 && <List items={items} />} shown</div>") else {
+            panic!("expected an element");
+        };
+        let [JsxChild::Expression(condition), JsxChild::Text(text)] = jsx.children.as_slice() else {
+            panic!("got {:?}", jsx.children);
+        };
+        assert_eq!(text, " shown");
+        let Expression::Infix(and) = condition.as_ref() else { panic!("expected &&, got {:?}", condition) };
+        assert_eq!(and.operator.kind, TokenKind::AmpAmp);
+        assert!(matches!(and.left.as_ref(), Expression::Infix(compare) if compare.operator.kind == TokenKind::RAngle));
+        assert!(matches!(and.right.as_ref(), Expression::JsxElement(list) if list.opening_tag.name.value == "List"));
+
+        let Ok(Expression::JsxElement(jsx)) = parse_expr("<div>{cond ? <A /> : <B />} shown</div>") else {
+            panic!("expected an element");
+        };
+        let [JsxChild::Expression(choice), JsxChild::Text(text)] = jsx.children.as_slice() else {
+            panic!("got {:?}", jsx.children);
+        };
+        assert_eq!(text, " shown");
+        let Expression::Ternary(ternary) = choice.as_ref() else { panic!("expected a ternary, got {:?}", choice) };
+        assert!(matches!(ternary.true_expr.as_ref(), Expression::JsxElement(a) if a.opening_tag.name.value == "A"));
+        assert!(matches!(ternary.false_expr.as_ref(), Expression::JsxElement(b) if b.opening_tag.name.value == "B"));
+    }
+
+    #[test]
+    fn test_jsx_void_element_closing_tag() {
+        for source in ["<div><br></br></div>", "<br></br>", "<div><br/>text</br></div>"] {
+            let result = parse_expr(source);
+            let Err(CompileError::WithLocation { message, suggestion, .. }) = result else {
+                panic!("expected a located error for {}, got {:?}", source, result);
+            };
+            assert_eq!(message, "<br> is a void element and can't have a closing tag", "{}", source);
+            assert_eq!(suggestion.as_deref(), Some("remove `</br>`, and write the element as `<br />`"));
+        }
+    }
+
+    #[test]
+    fn test_jsx_unsafe_html_excludes_children() {
+        let Expression::JsxElement(jsx) = parse_expr("<div class=\"post\" unsafe_html={body}></div>").unwrap() else {
+            panic!("expected an element");
+        };
+        assert!(matches!(&jsx.unsafe_html().unwrap().value, Expression::Identifier(body) if body.value == "body"));
+        // A component takes it as a prop like any other
+        let mut lexer = Lexer::new("component App() {\n    return <Card unsafe_html={body}>text</Card>;\n}".to_string());
+        assert!(Parser::new(&mut lexer).parse_program().is_ok());
+
+        let mut lexer = Lexer::new("component App() {\n    return <div unsafe_html={body}>\n        <p>hi</p>\n    </div>;\n}".to_string());
+        let err = Parser::new(&mut lexer).parse_program().unwrap_err();
+        let CompileError::WithLocation { message, location, labels, .. } = err else {
+            panic!("expected a located error, got {:?}", err);
+        };
+        assert_eq!(message, "<div> has both `unsafe_html` and children");
+        assert_eq!((location.line, location.column), (3, 9));
+        assert_eq!((labels[0].location.line, labels[0].location.column), (2, 17));
+        assert_eq!(keyword_errors("fn f() {\n    let a = <p unsafe_html={x}>{y}</p>;\n}"), vec!["<p> has both `unsafe_html` and children"]);
+    }
+
+    #[test]
+    fn test_jsx_boolean_and_shorthand_attributes() {
+        let Expression::JsxElement(jsx) = parse_expr("<Modal disabled {open} title={open} {onClose} />").unwrap() else {
+            panic!("expected an element");
+        };
+        let attributes: Vec<(&str, &Expression)> = jsx.opening_tag.attributes.iter()
+            .map(|attr| (attr.name.value.as_str(), &attr.value))
+            .collect();
+        assert!(matches!(attributes[0], ("disabled", Expression::BoolLiteral(true))));
+        assert!(matches!(attributes[1], ("open", Expression::Identifier(open)) if open.value == "open"));
+        assert!(matches!(attributes[2], ("title", Expression::Identifier(_))));
+        assert!(matches!(attributes[3], ("onClose", Expression::Identifier(handler)) if handler.value == "onClose"));
+
+        let result = parse_expr("<input {a + b} />");
+        let Err(CompileError::WithLocation { message, .. }) = result else {
+            panic!("expected a located error, got {:?}", result);
+        };
+        assert_eq!(message, "only a variable can stand alone in a tag, as in `{open}` for `open={open}`");
+    }
+
+    #[test]
+    fn test_jsx_event_modifiers() {
+        let Expression::JsxElement(jsx) = parse_expr("<form onSubmit|prevent={save} onClick|self|once={close} on:x|stop={f}>x</form>").unwrap() else {
+            panic!("expected an element");
+        };
+        let names: Vec<&str> = jsx.opening_tag.attributes.iter().map(|attr| attr.name.value.as_str()).collect();
+        assert_eq!(names, vec!["onSubmit|prevent", "onClick|self|once", "on:x|stop"]);
+        assert_eq!(jsx.opening_tag.attributes[1].event_modifiers(), ("onClick", vec!["self", "once"]));
+
+        let error = |source: &str| match parse_expr(source) {
+            Err(CompileError::WithLocation { message, location, suggestion, .. }) => (message, location.column, suggestion.unwrap_or_default()),
+            other => panic!("expected a located error, got {:?}", other),
+        };
+        let (message, column, suggestion) = error("<form onSubmit|prevnt={save}></form>");
+        assert_eq!(message, "Unknown event modifier `prevnt`");
+        assert_eq!(column, 16);
+        assert_eq!(suggestion, "the modifiers are `prevent`, `stop`, `self`, `once`, `capture` and `passive`");
+
+        assert_eq!(error("<a title|once={t}>x</a>").0, "`title` isn't an event handler, so it can't take `|once`");
+        assert_eq!(error("<Modal onClose|stop={f} />").0, "event modifiers only work on HTML elements, and <Modal> is a component");
+        assert_eq!(error("<div onScroll|passive|prevent={f}></div>").0, "`onScroll|passive|prevent` can't prevent the default action of a passive listener");
+        assert_eq!(error("<div onScroll| ={f}></div>").0, "`onScroll|` needs a modifier after the `|`");
+    }
+
+    #[test]
+    fn test_jsx_unclosed_element_recovers() {
+        // The inner element ends where the outer one's closing tag is, so
+        // only the real mistake is reported, along with later ones
+        let source = "component App() {\n    return <section>\n        <div>\n            <p>hi</p>\n    </section>;\n}\nfn f() {\n    let x = ;\n}";
+        let errors = keyword_errors(source);
+        assert_eq!(errors.len(), 2, "{:?}", errors);
+        assert_eq!(errors[0], "unclosed <div> opened at line 3:9, found </section> at 5:5");
+
+        // An element in an attribute or child expression can't be closed by a tag outside it
+        assert_eq!(
+            keyword_errors("component App() {\n    return <div>{ok && <b></div>}</div>;\n}"),
+            vec!["unclosed <b> opened at line 2:24, found </div> at 2:27"]
+        );
+    }
+
+    #[test]
+    fn test_jsx_unclosed_at_end_of_file() {
+        let mut lexer = Lexer::new("component App() {\n    return <main>\n        <ul>\n            <li>one</li>".to_string());
+        let err = Parser::new(&mut lexer).parse_program().unwrap_err();
+        let CompileError::WithLocation { message, location, labels, .. } = err else {
+            panic!("expected a located error, got {:?}", err);
+        };
+        assert_eq!(message, "unclosed <ul> opened at line 3:9, found the end of the file");
+        assert_eq!((location.line, location.column, location.length), (3, 9, "<ul".len()));
+        assert_eq!(labels.len(), 1);
+        assert_eq!(labels[0].message, "<main> is not closed either");
     }
 
     #[test]
@@ -3302,12 +5346,411 @@ mod tests {
         // Verify it's a CssMacro expression
         match expr.unwrap() {
             Expression::CssMacro(css_expr) => {
-                assert_eq!(css_expr.rules.len(), 1, "Should have 1 CSS rule");
+                assert_eq!(css_expr.rules().count(), 1, "Should have 1 CSS rule");
             }
             _ => panic!("Expected CssMacro expression"),
         }
     }
 
+    #[test]
+    fn test_jsx_keyword_attribute_names() {
+        let expr = parse_expr(r#"<label for="email" class="field"><input type="text" /></label>"#).unwrap();
+        match expr {
+            Expression::JsxElement(jsx) => {
+                let names: Vec<_> = jsx.opening_tag.attributes.iter().map(|a| a.name.value.as_str()).collect();
+                assert_eq!(names, vec!["for", "class"]);
+                match &jsx.children[0] {
+                    JsxChild::Element(input) => {
+                        assert_eq!(input.opening_tag.attributes[0].name.value, "type");
+                        match &input.opening_tag.attributes[0].value {
+                            Expression::StringLiteral(s) => assert_eq!(s, "text"),
+                            _ => panic!("Expected string literal"),
+                        }
+                    }
+                    _ => panic!("Expected nested element"),
+                }
+            }
+            _ => panic!("Expected JsxElement"),
+        }
+    }
+
+    #[test]
+    fn test_jsx_keyword_component_prop_rejected() {
+        let err = keyword_errors(r#"component App() { return <Field for="email" />; }"#);
+        assert_eq!(err, vec!["`for` is a reserved keyword and cannot be used as a prop name on component `Field`"]);
+    }
+
+    /// Messages of the errors a program fails to parse with
+    fn keyword_errors(source: &str) -> Vec<String> {
+        let mut lexer = Lexer::new(source.to_string());
+        let err = Parser::new(&mut lexer).parse_program().unwrap_err();
+        err.errors().iter().map(|e| match e {
+            CompileError::WithLocation { message, .. } | CompileError::ParserError { message, .. } => message.clone(),
+            other => other.to_string(),
+        }).collect()
+    }
+
+    #[test]
+    fn test_keyword_as_name_positions() {
+        for (source, expected) in [
+            ("fn f() {\n    let match = find();\n}", "`match` is a reserved keyword and cannot be used as a variable name"),
+            ("fn f() {\n    let (a, loop) = pair();\n}", "`loop` is a reserved keyword and cannot be used as a variable name"),
+            ("const use = 1;", "`use` is a reserved keyword and cannot be used as a constant name"),
+            ("fn f(in: Int) {\n}", "`in` is a reserved keyword and cannot be used as a parameter name"),
+            ("component Card(style: String) {\n    return <div />;\n}", "`style` is a reserved keyword and cannot be used as a parameter name"),
+            ("struct Query {\n    match: String,\n}", "`match` is a reserved keyword and cannot be used as a field name"),
+            ("enum Shape {\n    Box { as: Int },\n}", "`as` is a reserved keyword and cannot be used as a field name"),
+            ("fn f() {\n    let type = kind();\n}", "`type` is a reserved keyword and cannot be used as a variable name"),
+            ("fn f(static: Bool) {\n}", "`static` is a reserved keyword and cannot be used as a parameter name"),
+            ("struct Package {\n    mod: String,\n}", "`mod` is a reserved keyword and cannot be used as a field name"),
+        ] {
+            assert_eq!(keyword_errors(source), vec![expected], "{}", source);
+        }
+
+        let mut lexer = Lexer::new("fn f() {\n    let match = find();\n}".to_string());
+        let err = Parser::new(&mut lexer).parse_program().unwrap_err();
+        let CompileError::WithLocation { location, suggestion, .. } = err else {
+            panic!("expected a located error, got {:?}", err);
+        };
+        assert_eq!((location.line, location.length), (2, "match".len()));
+        assert_eq!(suggestion.as_deref(), Some("rename it, e.g. `match_`, or write it as a raw identifier, `r#match`"));
+    }
+
+    #[test]
+    fn test_keyword_as_name_recovers() {
+        // Each keyword is reported once and parsing continues with it as the name
+        assert_eq!(
+            keyword_errors("struct Query {\n    match: String,\n    if: Int,\n}\nfn f(loop: Int) -> Int {\n    return loop + 1;\n}"),
+            vec![
+                "`match` is a reserved keyword and cannot be used as a field name",
+                "`if` is a reserved keyword and cannot be used as a field name",
+                "`loop` is a reserved keyword and cannot be used as a parameter name",
+            ]
+        );
+
+        // The rest of the item is still parsed, so a later mistake is reported too
+        let errors = keyword_errors("fn f() {\n    let match = find();\n    let total = ;\n}");
+        assert_eq!(errors.len(), 2, "{:?}", errors);
+        assert_eq!(errors[0], "`match` is a reserved keyword and cannot be used as a variable name");
+        assert!(!errors[1].contains("match"), "{:?}", errors);
+
+        // Uses of the recovered name parse as that name, so they aren't reported again
+        for source in [
+            "fn f() {\n    let match = find();\n    match = match * 2;\n    return match + 1;\n}",
+            "fn f(if: Bool) -> Bool {\n    return !if;\n}",
+            "component Card(theme: String) {\n    return <div class={theme}>{theme}</div>;\n}",
+        ] {
+            assert_eq!(keyword_errors(source).len(), 1, "{}", source);
+        }
+
+        // Where a value can't end, the keyword is still a keyword
+        let errors = keyword_errors("fn f(match: Int, x: Int) -> Int {\n    return match x {\n        0 => match,\n        _ => 1,\n    };\n}");
+        assert_eq!(errors, vec!["`match` is a reserved keyword and cannot be used as a parameter name"]);
+
+        // The name is the item's own
+        let errors = keyword_errors("fn f(loop: Int) {\n}\nfn g() -> Int {\n    return loop + 1;\n}");
+        assert_eq!(errors.len(), 2, "{:?}", errors);
+    }
+
+    #[test]
+    fn test_keyword_as_member_name() {
+        // Reading a field declares nothing, so keywords are fine after `.` and as struct literal keys
+        let source = "fn on_input(e: Event) {\n    let kind = e.type;\n    let name = e.target?.mod;\n    let copy = Event { type: kind, mod: name };\n}";
+        let mut lexer = Lexer::new(source.to_string());
+        let program = Parser::new(&mut lexer).parse_program().unwrap();
+        let Statement::Function(on_input) = &program.statements[0] else { panic!("expected a function") };
+        let [Statement::Let(kind), Statement::Let(name), Statement::Let(copy)] = on_input.body.statements.as_slice() else {
+            panic!("got {:?}", on_input.body.statements);
+        };
+        assert!(matches!(&kind.value, Expression::FieldAccess(access) if access.field.value == "type"));
+        assert!(matches!(&name.value, Expression::FieldAccess(access) if access.field.value == "mod" && access.optional));
+        let Expression::StructLiteral(literal) = &copy.value else { panic!("got {:?}", copy.value) };
+        let keys: Vec<_> = literal.fields.iter().map(|(key, _)| key.value.as_str()).collect();
+        assert_eq!(keys, vec!["type", "mod"]);
+
+        // Declaring one is still an error
+        assert_eq!(
+            keyword_errors("fn f(e: Event) {\n    let type = e.type;\n}"),
+            vec!["`type` is a reserved keyword and cannot be used as a variable name"]
+        );
+    }
+
+    #[test]
+    fn test_pub_items() {
+        let source = "pub struct Point {\n    pub x: Int,\n    y: Int,\n}\nimpl Point {\n    pub fn new() -> Point {\n        return Point { x: 0, y: 0 };\n    }\n}\n/// Doubles a number\npub fn double(n: Int) -> Int {\n    return n * 2;\n}\npub const LIMIT: Int = 10;";
+        let mut lexer = Lexer::new(source.to_string());
+        let program = Parser::new(&mut lexer).parse_program().unwrap();
+        assert_eq!(program.statements.len(), 4);
+        let Statement::Struct(point) = &program.statements[0] else { panic!("expected a struct") };
+        assert_eq!(point.fields.len(), 2);
+        let Statement::Function(double) = &program.statements[2] else { panic!("expected a function") };
+        assert_eq!(double.doc.as_deref(), Some("Doubles a number"));
+
+        let mut lexer = Lexer::new("pub let x = 1;".to_string());
+        let err = Parser::new(&mut lexer).parse_program().unwrap_err();
+        assert!(err.to_string().contains("`pub` goes before an item"), "{}", err);
+    }
+
+    #[test]
+    fn test_attributes_on_items() {
+        let source = "#[derive(Clone, Debug)]\nstruct Point {\n    x: Int,\n}\n#[route(\"/api/users\")]\n#[cache(ttl = 60, keys(a, b))]\nserver fn users() -> Int {\n    return 1;\n}\nenum Status {\n    #[default]\n    Active,\n    #[deprecated(\"use Active\")]\n    Live,\n}";
+        let mut lexer = Lexer::new(source.to_string());
+        let program = Parser::new(&mut lexer).parse_program().unwrap();
+
+        let Statement::Struct(point) = &program.statements[0] else { panic!("expected a struct") };
+        assert_eq!(point.derives, vec!["Clone", "Debug"]);
+        assert_eq!(point.attributes[0].name.value, "derive");
+
+        let Statement::Function(users) = &program.statements[1] else { panic!("expected a function") };
+        assert_eq!(users.route.as_ref().map(|route| route.path.as_str()), Some("/api/users"));
+        assert!(users.is_server);
+        let cache = &users.attributes[1];
+        assert_eq!(cache.name.value, "cache");
+        let lexemes: Vec<&str> = cache.tokens.iter().map(|token| token.lexeme.as_str()).collect();
+        assert_eq!(lexemes, vec!["ttl", "=", "60", ",", "keys", "(", "a", ",", "b", ")"]);
+
+        let Statement::Enum(status) = &program.statements[2] else { panic!("expected an enum") };
+        assert_eq!(status.variants[0].attributes[0].name.value, "default");
+        assert!(status.variants[0].attributes[0].tokens.is_empty());
+        assert_eq!(status.variants[1].attributes[0].tokens[0].kind, TokenKind::String("use Active".to_string()));
+
+        for (source, expected) in [
+            ("#[inline]\nlet x = 1;", "attributes can only be applied to a struct, enum, enum variant or function"),
+            ("#[route(42)]\nfn f() {\n}", "#[route] takes a string path"),
+            ("#[derive(Clone\nstruct Point {\n}", "unclosed `(` in attribute `#[derive]`"),
+        ] {
+            let mut lexer = Lexer::new(source.to_string());
+            let err = Parser::new(&mut lexer).parse_program().unwrap_err();
+            assert!(err.to_string().contains(expected), "{}: {}", source, err);
+        }
+    }
+
+    #[test]
+    fn test_loop_with_break_and_continue() {
+        let source = "fn f() {\n    loop {\n        if done() { break; }\n        continue;\n    }\n}";
+        let mut lexer = Lexer::new(source.to_string());
+        let program = Parser::new(&mut lexer).parse_program().unwrap();
+        let Statement::Function(f) = &program.statements[0] else { panic!("expected a function") };
+        let Statement::Loop(loop_stmt) = &f.body.statements[0] else { panic!("expected a loop") };
+        assert!(matches!(loop_stmt.body.statements[1], Statement::Continue));
+    }
+
+    fn attribute_names(jsx: &JsxElement) -> Vec<&str> {
+        jsx.opening_tag.attributes.iter().map(|a| a.name.value.as_str()).collect()
+    }
+
+    #[test]
+    fn test_jsx_attribute_ternary_with_comparison() {
+        let expr = parse_expr(r#"<div class={count > 0 ? "full" : "empty"} id="list">x</div>"#).unwrap();
+        let Expression::JsxElement(jsx) = expr else { panic!("Expected JsxElement") };
+        assert_eq!(attribute_names(&jsx), vec!["class", "id"]);
+        match &jsx.opening_tag.attributes[0].value {
+            Expression::Ternary(ternary) => match ternary.condition.as_ref() {
+                Expression::Infix(infix) => assert_eq!(infix.operator.lexeme, ">"),
+                other => panic!("Expected comparison condition, got {:?}", other),
+            },
+            other => panic!("Expected ternary, got {:?}", other),
+        }
+        assert!(matches!(&jsx.children[0], JsxChild::Text(text) if text == "x"));
+    }
+
+    #[test]
+    fn test_jsx_attribute_closure_and_turbofish() {
+        let expr = parse_expr(r#"<Row keep={|n| n >= 3} widest={max::<i32>(a, b)} id="r" />"#).unwrap();
+        let Expression::JsxElement(jsx) = expr else { panic!("Expected JsxElement") };
+        assert_eq!(attribute_names(&jsx), vec!["keep", "widest", "id"]);
+        assert!(matches!(&jsx.opening_tag.attributes[0].value, Expression::Lambda(_)));
+        match &jsx.opening_tag.attributes[1].value {
+            Expression::FunctionCall(call) => {
+                assert!(call.type_params.is_some());
+                assert_eq!(call.arguments.len(), 2);
+            }
+            other => panic!("Expected generic call, got {:?}", other),
+        }
+
+        let expr = parse_expr("<p>{max::<i32>(a, b)} items</p>").unwrap();
+        let Expression::JsxElement(jsx) = expr else { panic!("Expected JsxElement") };
+        assert_eq!(jsx.children.len(), 2);
+        assert!(matches!(&jsx.children[1], JsxChild::Text(text) if text == " items"));
+    }
+
+    #[test]
+    fn test_jsx_attribute_closure_returning_element() {
+        let expr = parse_expr(r#"<List render={|| <b title={a > 1}>x</b>} id="z" />"#).unwrap();
+        let Expression::JsxElement(jsx) = expr else { panic!("Expected JsxElement") };
+        assert_eq!(attribute_names(&jsx), vec!["render", "id"]);
+        assert!(jsx.opening_tag.self_closing);
+    }
+
+    #[test]
+    fn test_jsx_unterminated_attribute_expression() {
+        let err = parse_expr("<div title={a > b ? \"hi\" : \"lo\">\n  text\n</div>").unwrap_err();
+        match err {
+            CompileError::WithLocation { message, location, labels, .. } => {
+                assert!(message.contains("starting at line 1 appears unterminated"), "got: {}", message);
+                assert_eq!(location.column, 32);
+                assert_eq!(labels.len(), 1);
+                assert_eq!(labels[0].location.column, 12);
+            }
+            other => panic!("Expected located error, got {:?}", other),
+        }
+    }
+
+    fn inline_style(source: &str) -> InlineStyle {
+        match parse_expr(source).expect("parse failed") {
+            Expression::JsxElement(jsx) => match jsx.opening_tag.attributes.into_iter().next().map(|attr| attr.value) {
+                Some(Expression::InlineStyle(style)) => style,
+                other => panic!("Expected inline style, got {:?}", other),
+            },
+            other => panic!("Expected JSX element, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_jsx_class_list() {
+        let Expression::JsxElement(jsx) = parse_expr(r#"<a class={["btn", size, {"is-on": on, off: !on,}]}>x</a>"#).unwrap() else {
+            panic!("expected an element");
+        };
+        let Expression::Classes(classes) = &jsx.opening_tag.attributes[0].value else {
+            panic!("expected a class list, got {:?}", jsx.opening_tag.attributes[0].value);
+        };
+        let entries: Vec<String> = classes.entries.iter()
+            .map(|entry| match (&entry.class, &entry.condition) {
+                (Expression::StringLiteral(class), None) => class.clone(),
+                (Expression::Identifier(name), None) => format!("{{{}}}", name.value),
+                (Expression::StringLiteral(class), Some(_)) => format!("{}?", class),
+                other => panic!("unexpected entry {:?}", other),
+            })
+            .collect();
+        assert_eq!(entries, vec!["btn", "{size}", "is-on?", "off?"]);
+
+        // Arrays elsewhere are still arrays
+        let Expression::JsxElement(jsx) = parse_expr("<List items={[1, 2]} />").unwrap() else { panic!("expected an element") };
+        assert!(matches!(jsx.opening_tag.attributes[0].value, Expression::ArrayLiteral(_)));
+
+        match parse_expr("<a class={[{1: on}]}>x</a>") {
+            Err(CompileError::ParserError { message, .. }) => {
+                assert_eq!(message, "Expected a class name in the class map, found `1`; write `\"name\": condition`")
+            }
+            other => panic!("Expected a parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_jsx_style_object() {
+        let style = inline_style(r#"<div style={{ color: palette.fg, padding: "8px", margin_top: 4, zIndex: 2, width: 0, WebkitLineClamp: 3, "--gap": 2, if: 1.5 }}></div>"#);
+        assert!(style.object);
+        let declarations: Vec<_> = style.declarations.iter()
+            .map(|decl| match decl.value.as_slice() {
+                [InlineStylePart::Static(value)] => (decl.property.as_str(), value.as_str()),
+                [InlineStylePart::Dynamic(_)] => (decl.property.as_str(), "{...}"),
+                other => panic!("Expected a single value, got {:?}", other),
+            })
+            .collect();
+        assert_eq!(declarations, vec![
+            ("color", "{...}"),
+            ("padding", "8px"),
+            ("margin-top", "4px"),
+            ("z-index", "2"),
+            ("width", "0"),
+            ("-webkit-line-clamp", "3"),
+            ("--gap", "2"),
+            ("if", "1.5px"),
+        ]);
+
+        // No gap between the braces, and a trailing comma
+        assert_eq!(inline_style("<div style={{opacity: o,}}>x</div>").declarations.len(), 1);
+        assert!(inline_style("<div style={{}} />").declarations.is_empty());
+
+        match parse_expr(r#"<div style={{ color: "red; x" }}></div>"#) {
+            Err(CompileError::ParserError { message, .. }) => assert!(message.starts_with("Invalid value for `color` in style object"), "{}", message),
+            other => panic!("Expected a parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_jsx_inline_style_static_declarations() {
+        let style = inline_style(r#"<div style="padding: 4px 8px; color: rgba(0, 0, 0, 0.5);"></div>"#);
+        let declarations: Vec<_> = style.declarations.iter()
+            .map(|decl| match decl.value.as_slice() {
+                [InlineStylePart::Static(value)] => (decl.property.as_str(), value.as_str()),
+                other => panic!("Expected a static value, got {:?}", other),
+            })
+            .collect();
+        assert_eq!(declarations, vec![("padding", "4px 8px"), ("color", "rgba(0, 0, 0, 0.5)")]);
+        // Positioned at each property name
+        assert_eq!(style.declarations[1].column - style.declarations[0].column, "padding: 4px 8px; ".len());
+
+        for (source, expected) in [
+            (r#"<div style="color red"></div>"#, "Expected `property: value` in style attribute, found `color red`"),
+            (r#"<div style="co!lor: red"></div>"#, "Invalid CSS property name `co!lor`"),
+            (r#"<div style="color: ; margin: 0"></div>"#, "Missing value for `color`"),
+            (r#"<div style="width: {size px"></div>"#, "Unclosed `{` in style attribute"),
+        ] {
+            let err = parse_expr(source).unwrap_err().to_string();
+            assert!(err.contains(expected), "{}: got {}", source, err);
+        }
+    }
+
+    #[test]
+    fn test_assignment_in_condition_reads_as_eq() {
+        let mut lexer = Lexer::new("fn f() {\n    if x = 5 {\n        go();\n    }\n}".to_string());
+        let program = Parser::new(&mut lexer).parse_program().expect("read as a comparison");
+        let Statement::Function(f) = &program.statements[0] else { panic!("expected a function") };
+        let Statement::If(if_stmt) = &f.body.statements[0] else { panic!("expected an if") };
+        let Expression::Infix(infix) = &if_stmt.condition else { panic!("expected a comparison, got {:?}", if_stmt.condition) };
+        assert!(infix.assign_typo);
+        assert_eq!((infix.operator.kind.clone(), infix.operator.lexeme.as_str()), (TokenKind::Eq, "=="));
+        assert_eq!((infix.operator.line, infix.operator.column), (2, 10));
+
+        // Inside the condition's operands, an `=` is still an error
+        let mut lexer = Lexer::new("fn f() {\n    if f(x = 5) {\n    }\n}".to_string());
+        assert!(Parser::new(&mut lexer).parse_program().is_err());
+
+        // An assignment that isn't a condition, like a handler's body, is left alone
+        let mut lexer = Lexer::new("component C() {\n    return <button onclick={() => count.value = count.value + 1}>{n}</button>;\n}".to_string());
+        let program = Parser::new(&mut lexer).parse_program().expect("an assignment in a handler");
+        let Statement::Component(component) = &program.statements[0] else { panic!("expected a component") };
+        let Statement::Return(ret) = &component.body.statements[0] else { panic!("expected a return") };
+        let Expression::JsxElement(button) = &ret.value else { panic!("expected JSX") };
+        let Expression::Lambda(handler) = &button.opening_tag.attributes[0].value else { panic!("expected a lambda") };
+        let Expression::Block(body) = handler.body.as_ref() else { panic!("expected a block, got {:?}", handler.body) };
+        assert!(matches!(body.statements.as_slice(), [Statement::Assignment(_)]));
+    }
+
+    #[test]
+    fn test_classes_macro_entries() {
+        let Expression::Classes(classes) = parse_expr(r#"classes!("btn", "btn-primary" => is_primary && !busy, size,)"#).unwrap() else {
+            panic!("Expected a classes! expression");
+        };
+        let entries: Vec<_> = classes.entries.iter()
+            .map(|entry| (format!("{:?}", entry.class), entry.condition.is_some()))
+            .collect();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0], (r#"StringLiteral("btn")"#.to_string(), false));
+        assert!(entries[1].1, "btn-primary is conditional");
+        assert!(matches!(classes.entries[1].condition, Some(Expression::Infix(_))));
+        assert!(matches!(&classes.entries[2].class, Expression::Identifier(ident) if ident.value == "size"));
+
+        // Other macros keep plain arguments
+        assert!(parse_expr(r#"format!("{}", x => y)"#).is_err());
+    }
+
+    #[test]
+    fn test_jsx_inline_style_interpolations() {
+        let style = inline_style(r#"<div style="color: {palette.accent}; width: {size * 2}px"></div>"#);
+        assert_eq!(style.expressions().count(), 2);
+        match style.declarations[1].value.as_slice() {
+            [InlineStylePart::Dynamic(expr), InlineStylePart::Static(unit)] => {
+                assert!(matches!(**expr, Expression::Infix(_)));
+                assert_eq!(unit, "px");
+            }
+            other => panic!("Expected value and unit, got {:?}", other),
+        }
+        // A `;` in an interpolation does not split the declaration
+        assert_eq!(inline_style(r#"<p style="content: {\";\"}"></p>"#).declarations.len(), 1);
+    }
+
     #[test]
     fn test_css_selector_types() {
         // Test different selector types
@@ -3336,4 +5779,172 @@ mod tests {
             assert!(program.is_ok(), "Should parse {} selector", selector_type);
         }
     }
+
+    #[test]
+    fn test_css_selector_lists_and_combinators() {
+        let source = r#"css! {
+            h1,
+            h2, .title {
+                margin: 0;
+            }
+            .card > .title { color: blue; }
+            ul li, .a + .b, .a ~ .c { color: red; }
+        }"#;
+        let Ok(Expression::CssMacro(css)) = parse_expr(source) else { panic!("expected a css! block") };
+        let rules: Vec<_> = css.rules().collect();
+        let [grouped, child, mixed] = rules.as_slice() else { panic!("got {:?}", rules) };
+        let CssSelector::List(selectors) = &grouped.selector else { panic!("got {:?}", grouped.selector) };
+        assert!(matches!(selectors.as_slice(), [
+            CssSelector::Element(h1),
+            CssSelector::Element(h2),
+            CssSelector::Class(title),
+        ] if h1 == "h1" && h2 == "h2" && title == "title"), "got {:?}", selectors);
+        assert_eq!(grouped.declarations.len(), 1);
+        assert!(matches!(&child.selector, CssSelector::Nested(selector) if selector == ".card > .title"), "got {:?}", child.selector);
+        let CssSelector::List(selectors) = &mixed.selector else { panic!("got {:?}", mixed.selector) };
+        let nested: Vec<_> = selectors.iter()
+            .map(|selector| match selector {
+                CssSelector::Nested(selector) => selector.as_str(),
+                other => panic!("got {:?}", other),
+            })
+            .collect();
+        assert_eq!(nested, vec!["ul li", ".a + .b", ".a ~ .c"]);
+    }
+
+    #[test]
+    fn test_css_attribute_selectors_and_pseudo_elements() {
+        let source = r#"css! {
+            .tooltip::after { content: "→"; }
+            input[type="checkbox"]:checked { accent-color: red; }
+            li:nth-child(2n+1) { color: gray; }
+        }"#;
+        let Ok(Expression::CssMacro(css)) = parse_expr(source) else { panic!("expected a css! block") };
+        let selectors: Vec<_> = css.rules().map(|rule| format!("{:?}", rule.selector)).collect();
+        assert_eq!(selectors, vec![
+            r#"Compound([Class("tooltip"), PseudoElement("after")])"#,
+            r#"Compound([Element("input"), Attribute("type=\"checkbox\""), PseudoClass("checked")])"#,
+            r#"Compound([Element("li"), PseudoClass("nth-child(2n+1)")])"#,
+        ]);
+        assert!(matches!(&css.rules().next().unwrap().declarations[0].value, CssValue::String(content) if content == "→"));
+    }
+
+    #[test]
+    fn test_css_keyframe_selector_lists() {
+        let source = r#"css! {
+            @keyframes pulse {
+                0%, 100% { opacity: 1; }
+                50% { opacity: 0.5; }
+            }
+            @keyframes fade {
+                from, 25%,
+                to { opacity: 0; }
+            }
+        }"#;
+        let Ok(Expression::CssMacro(css)) = parse_expr(source) else { panic!("expected a css! block") };
+        let frames: Vec<Vec<_>> = css.keyframes()
+            .map(|keyframes| keyframes.frames.iter().map(|frame| format!("{:?}", frame.selectors)).collect())
+            .collect();
+        assert_eq!(frames, vec![
+            vec!["[Percentage(0.0), Percentage(100.0)]", "[Percentage(50.0)]"],
+            vec!["[From, Percentage(25.0), To]"],
+        ]);
+    }
+
+    #[test]
+    fn test_css_font_face_supports_and_import() {
+        let source = r#"css! {
+            @import url("/theme.css") screen;
+            @font-face {
+                font-family: "Inter";
+                src: url("/inter.woff2") format("woff2");
+            }
+            @supports (display: grid) {
+                .grid { display: grid; }
+            }
+            @page :first { margin: 1in; }
+            .a { @layer base { color: red; } color: blue; }
+        }"#;
+        let Ok(Expression::CssMacro(css)) = parse_expr(source) else { panic!("expected a css! block") };
+        assert!(matches!(&css.imports[..], [import] if matches!(&import.source, CssValue::Raw(source) if source == r#"url("/theme.css") screen"#)));
+        let [font_face] = css.font_faces.as_slice() else { panic!("got {:?}", css.font_faces) };
+        assert_eq!(format!("{:?}", font_face.declarations[0].value), r#"String("Inter")"#);
+        assert_eq!(format!("{:?}", font_face.declarations[1].value), r#"Raw("url(\"/inter.woff2\") format(\"woff2\")")"#);
+        let supports: Vec<_> = css.supports().collect();
+        let [supports] = supports.as_slice() else { panic!("got {:?}", supports) };
+        assert_eq!(supports.condition, "(display: grid)");
+        assert!(matches!(&supports.rules[..], [rule] if matches!(&rule.selector, CssSelector::Class(name) if name == "grid")));
+        let [page] = css.pages.as_slice() else { panic!("got {:?}", css.pages) };
+        assert_eq!((page.selector.as_str(), page.declarations.len(), page.span.line), (":first", 1, 10));
+        // Unknown at-rules are skipped, blocks and all, for a warning
+        let skipped: Vec<_> = css.unknown_at_rules.iter().map(|rule| (rule.name.as_str(), rule.span.line)).collect();
+        assert_eq!(skipped, vec![("layer", 11)]);
+        let rules: Vec<_> = css.rules().collect();
+        assert!(matches!(rules.as_slice(), [rule] if rule.declarations.len() == 1), "got {:?}", rules);
+    }
+
+    #[test]
+    fn test_css_media_queries() {
+        let source = r#"css! {
+            .card {
+                padding: 8px;
+                @media screen and (min-width: 768px), print {
+                    padding: 16px;
+                    &:hover { color: blue; }
+                }
+                @media not print { display: block; }
+            }
+            @media only screen and (hover: hover) {
+                .card:hover { opacity: 0.9; }
+            }
+        }"#;
+        let Ok(Expression::CssMacro(css)) = parse_expr(source) else { panic!("expected a css! block") };
+        let rules: Vec<_> = css.rules().collect();
+        let [card] = rules.as_slice() else { panic!("got {:?}", rules) };
+        assert_eq!(card.declarations.len(), 1);
+        let conditions: Vec<_> = card.media_queries.iter().map(|query| query.condition.as_str()).collect();
+        assert_eq!(conditions, vec!["screen and (min-width: 768px), print", "not print"]);
+        let override_query = &card.media_queries[0];
+        assert!(matches!(&override_query.declarations[..], [decl] if decl.property == "padding"));
+        assert!(matches!(&override_query.rules[..], [rule] if matches!(&rule.selector, CssSelector::Nested(selector) if selector == "&:hover")));
+
+        // At the top, a media query holds whole rules
+        let media_queries: Vec<_> = css.media_queries().collect();
+        let [top] = media_queries.as_slice() else { panic!("got {:?}", media_queries) };
+        assert_eq!(top.condition, "only screen and (hover: hover)");
+        assert!(top.declarations.is_empty());
+        assert_eq!(top.rules.len(), 1);
+
+        let Err(error) = parse_expr("css! { @media print { color: red; } }") else { panic!("declarations need a rule") };
+        assert!(error.to_string().contains("need a rule around them"), "{}", error);
+    }
+
+    #[test]
+    fn test_css_comments() {
+        let source = r#"css! {
+            /* card header */
+            .card /* outer */ .title {
+                color: red /* temp */;
+                padding: 4px /* y */ 8px; // then x
+                // the image
+                background: url(https://example.com/a.png);
+                margin: 0// none
+            }
+        }"#;
+        let Ok(Expression::CssMacro(css)) = parse_expr(source) else { panic!("expected a css! block") };
+        let rules: Vec<_> = css.rules().collect();
+        let [rule] = rules.as_slice() else { panic!("got {:?}", rules) };
+        assert!(matches!(&rule.selector, CssSelector::Nested(selector) if selector == ".card .title"), "got {:?}", rule.selector);
+        let declarations: Vec<_> = rule.declarations.iter()
+            .map(|declaration| match &declaration.value {
+                CssValue::Raw(value) => (declaration.property.as_str(), value.as_str()),
+                other => panic!("got {:?}", other),
+            })
+            .collect();
+        assert_eq!(declarations, vec![
+            ("color", "red"),
+            ("padding", "4px 8px"),
+            ("background", "url(https://example.com/a.png)"),
+            ("margin", "0"),
+        ]);
+    }
 }
\ No newline at end of file
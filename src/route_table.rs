@@ -0,0 +1,422 @@
+// Route Table - Checks every declared route before any code is emitted
+//
+// Routes come from `@route("/path")` on server functions and from
+// `<Route path="/path">` router elements with a literal path. Routes match in
+// declaration order, so besides exact duplicates a route can be unreachable:
+// `/todos/:id` declared before `/todos/new` also matches `/todos/new`.
+//
+// The params of an @route handler are typed by the handler's parameters. The
+// server bundle parses and validates each one before calling the handler, and
+// a value that doesn't parse (`/todos/abc` for `id: i32`) is a 404.
+
+use crate::ast::{
+    ComponentDefinition, Expression, FunctionDefinition, JsxChild, JsxElement, Position, Program,
+    Statement, TypeExpression,
+};
+use crate::diagnostics::{Diagnostic, Severity, SourceLocation};
+
+/// Router element whose literal `path` declares a route
+pub const ROUTE_ELEMENT: &str = "Route";
+
+/// How a route param is parsed before the handler sees it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamKind {
+    Int,
+    Float,
+    Bool,
+    Text,
+}
+
+impl ParamKind {
+    /// The kind for a handler parameter's type, if it can come from a URL
+    pub fn for_type(type_expr: &TypeExpression) -> Option<Self> {
+        let TypeExpression::Named(name) = type_expr else {
+            return None;
+        };
+        match name.value.as_str() {
+            "i8" | "i16" | "i32" | "i64" | "isize" | "u8" | "u16" | "u32" | "u64" | "usize" | "int" | "Int" => {
+                Some(ParamKind::Int)
+            }
+            "f32" | "f64" | "float" | "Float" => Some(ParamKind::Float),
+            "bool" | "Bool" => Some(ParamKind::Bool),
+            "String" | "str" | "string" => Some(ParamKind::Text),
+            _ => None,
+        }
+    }
+
+    /// Name passed to the runtime's `routeParam`
+    pub fn runtime_name(self) -> &'static str {
+        match self {
+            ParamKind::Int => "int",
+            ParamKind::Float => "float",
+            ParamKind::Bool => "bool",
+            ParamKind::Text => "string",
+        }
+    }
+}
+
+/// Where a route was declared
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RouteSource {
+    /// `@route` on this function
+    Handler(String),
+    /// A `<Route>` element rendered by this component or function
+    Element(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct DeclaredRoute {
+    pub path: String,
+    pub source: RouteSource,
+    pub file: String,
+    pub position: Position,
+}
+
+impl DeclaredRoute {
+    fn segments(&self) -> Vec<&str> {
+        self.path.split('/').filter(|segment| !segment.is_empty()).collect()
+    }
+
+    fn location(&self) -> SourceLocation {
+        let length = match self.source {
+            RouteSource::Handler(_) => self.path.len() + 2,
+            RouteSource::Element(_) => ROUTE_ELEMENT.len(),
+        };
+        SourceLocation { file: self.file.clone(), line: self.position.line, column: self.position.column, length }
+    }
+
+    /// Whether every path this route matches is matched by `earlier` too
+    fn covered_by(&self, earlier: &DeclaredRoute) -> bool {
+        let (mine, theirs) = (self.segments(), earlier.segments());
+        mine.len() == theirs.len()
+            && mine.iter().zip(&theirs).all(|(mine, theirs)| is_param(theirs) || mine == theirs)
+    }
+
+    /// Same segments, with params in the same places under any name
+    fn same_shape(&self, other: &DeclaredRoute) -> bool {
+        self.covered_by(other) && other.covered_by(self)
+    }
+}
+
+fn is_param(segment: &str) -> bool {
+    segment.starts_with(':')
+}
+
+/// Every route in a program, in declaration order
+#[derive(Debug, Default)]
+pub struct RouteTable {
+    pub routes: Vec<DeclaredRoute>,
+    handler_errors: Vec<Diagnostic>,
+}
+
+impl RouteTable {
+    pub fn collect(program: &Program) -> Self {
+        let mut table = RouteTable::default();
+        table.add_program(program, "");
+        table
+    }
+
+    /// Add the routes one file declares, after those already collected
+    pub fn add_program(&mut self, program: &Program, file: &str) {
+        for statement in &program.statements {
+            match statement {
+                Statement::Function(func) => {
+                    if func.route.is_some() {
+                        self.add_handler(func, file);
+                    }
+                    self.scan_statements(&func.body.statements, &RouteSource::Element(func.name.value.clone()), file);
+                }
+                Statement::Component(ComponentDefinition { name, body, .. }) => {
+                    self.scan_statements(&body.statements, &RouteSource::Element(name.value.clone()), file);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Duplicate routes and handler param errors, and warnings for routes an
+    /// earlier one shadows
+    pub fn check(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = self.handler_errors.clone();
+        for (index, route) in self.routes.iter().enumerate() {
+            if let Some(error) = invalid_path(&route.path) {
+                diagnostics.push(Diagnostic::error(error).at(route.location()).with_code("E019"));
+                continue;
+            }
+            let Some(earlier) = self.routes[..index].iter().find(|earlier| route.covered_by(earlier)) else {
+                continue;
+            };
+            let diagnostic = if route.same_shape(earlier) {
+                let message = if route.path == earlier.path {
+                    format!("Route `{}` is declared more than once", route.path)
+                } else {
+                    format!("Route `{}` matches the same paths as `{}`", route.path, earlier.path)
+                };
+                Diagnostic::error(message)
+                    .at(route.location())
+                    .with_code("E019")
+                    .with_label(earlier.location(), format!("`{}` is first declared here", earlier.path))
+            } else {
+                Diagnostic::warning(format!(
+                    "Route `{}` is unreachable: `{}` is declared earlier and matches every path it does",
+                    route.path, earlier.path
+                ))
+                .at(route.location())
+                .with_code("W008")
+                .with_label(earlier.location(), format!("`{}` is declared here", earlier.path))
+                .with_suggestion(format!("declare `{}` before `{}`", route.path, earlier.path))
+            };
+            diagnostics.push(diagnostic);
+        }
+        diagnostics
+    }
+
+    pub fn has_errors(diagnostics: &[Diagnostic]) -> bool {
+        diagnostics.iter().any(|d| d.severity == Severity::Error)
+    }
+
+    fn add_handler(&mut self, func: &FunctionDefinition, file: &str) {
+        let Some(route_attr) = &func.route else {
+            return;
+        };
+        let route = DeclaredRoute {
+            path: route_attr.path.clone(),
+            source: RouteSource::Handler(func.name.value.clone()),
+            file: file.to_string(),
+            position: route_attr.position,
+        };
+
+        if func.is_client {
+            self.handler_errors.push(
+                Diagnostic::error(format!("Route handler `{}` can't be a @client function", func.name.value))
+                    .at(route.location())
+                    .with_code("E019")
+                    .with_note("route handlers are served by the server bundle"),
+            );
+        }
+
+        let segments = route.segments();
+        for param in &func.parameters {
+            let name = &param.name.value;
+            let location = SourceLocation {
+                file: file.to_string(),
+                line: param.position.line,
+                column: param.position.column,
+                length: name.len(),
+            };
+            if !segments.iter().any(|segment| segment.strip_prefix(':') == Some(name.as_str())) {
+                self.handler_errors.push(
+                    Diagnostic::error(format!(
+                        "Parameter `{}` of route handler `{}` is not in its path `{}`",
+                        name, func.name.value, route.path
+                    ))
+                    .at(location)
+                    .with_code("E019")
+                    .with_label(route.location(), "route declared here")
+                    .with_suggestion(format!("add `:{}` to the path", name)),
+                );
+            } else if ParamKind::for_type(&param.type_annotation).is_none() {
+                self.handler_errors.push(
+                    Diagnostic::error(format!(
+                        "Route param `{}` of `{}` can't be parsed from a URL",
+                        name, func.name.value
+                    ))
+                    .at(location)
+                    .with_code("E019")
+                    .with_note("route params can be strings, integers, floats or bools"),
+                );
+            }
+        }
+
+        self.routes.push(route);
+    }
+
+    fn scan_statements(&mut self, statements: &[Statement], owner: &RouteSource, file: &str) {
+        for statement in statements {
+            self.scan_statement(statement, owner, file);
+        }
+    }
+
+    fn scan_statement(&mut self, statement: &Statement, owner: &RouteSource, file: &str) {
+        match statement {
+            Statement::Expression(expr) => self.scan_expression(expr, owner, file),
+            Statement::Return(ret) => self.scan_expression(&ret.value, owner, file),
+            Statement::Let(let_stmt) => self.scan_expression(&let_stmt.value, owner, file),
+            Statement::If(if_stmt) => {
+                self.scan_statements(&if_stmt.then_branch.statements, owner, file);
+                if let Some(else_branch) = &if_stmt.else_branch {
+                    self.scan_statement(else_branch, owner, file);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn scan_expression(&mut self, expr: &Expression, owner: &RouteSource, file: &str) {
+        match expr {
+            Expression::JsxElement(jsx) => self.scan_element(jsx, owner, file),
+            Expression::IfExpression(if_expr) => {
+                self.scan_expression(&if_expr.then_expr, owner, file);
+                if let Some(else_expr) = &if_expr.else_expr {
+                    self.scan_expression(else_expr, owner, file);
+                }
+            }
+            Expression::Ternary(ternary) => {
+                self.scan_expression(&ternary.true_expr, owner, file);
+                self.scan_expression(&ternary.false_expr, owner, file);
+            }
+            Expression::Block(block) => self.scan_statements(&block.statements, owner, file),
+            Expression::Lambda(lambda) => self.scan_expression(&lambda.body, owner, file),
+            _ => {}
+        }
+    }
+
+    fn scan_element(&mut self, jsx: &JsxElement, owner: &RouteSource, file: &str) {
+        let tag = &jsx.opening_tag;
+        for attr in &tag.attributes {
+            match (&attr.value, attr.name.value.as_str()) {
+                (Expression::StringLiteral(path), "path") if tag.name.value == ROUTE_ELEMENT => {
+                    self.routes.push(DeclaredRoute {
+                        path: path.clone(),
+                        source: owner.clone(),
+                        file: file.to_string(),
+                        position: tag.position,
+                    });
+                }
+                (value, _) => self.scan_expression(value, owner, file),
+            }
+        }
+        for child in &jsx.children {
+            match child {
+                JsxChild::Element(element) => self.scan_element(element, owner, file),
+                JsxChild::Expression(expr) => self.scan_expression(expr, owner, file),
+                JsxChild::Text(_) => {}
+            }
+        }
+    }
+}
+
+/// Why a route path can't be registered, if it can't
+fn invalid_path(path: &str) -> Option<String> {
+    if !path.starts_with('/') {
+        return Some(format!("Route path `{}` must start with `/`", path));
+    }
+    if let Some(c) = path.chars().find(|c| c.is_whitespace() || matches!(c, '\'' | '"' | '\\' | '?' | '#')) {
+        return Some(format!("Route path `{}` can't contain {:?}", path, c));
+    }
+    if path.split('/').any(|segment| segment == ":") {
+        return Some(format!("Route path `{}` has a param without a name", path));
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    fn check(source: &str) -> Vec<Diagnostic> {
+        let mut lexer = Lexer::new(source.to_string());
+        let program = Parser::new(&mut lexer).parse_program().expect("parse");
+        RouteTable::collect(&program).check()
+    }
+
+    #[test]
+    fn test_duplicate_routes() {
+        let diagnostics = check(
+            r#"
+@route("/todos/:id")
+@server
+fn get_todo(id: i32) -> String {
+    return "todo";
+}
+
+component App() {
+    return <Router>
+        <Route path="/todos/:slug" page={TodoPage} />
+        <Route path="/about" page={About} />
+        <Route path="/about" page={About} />
+    </Router>;
+}
+"#,
+        );
+
+        let messages: Vec<_> = diagnostics.iter().map(|d| (d.severity, d.message.as_str())).collect();
+        assert_eq!(
+            messages,
+            vec![
+                (Severity::Error, "Route `/todos/:slug` matches the same paths as `/todos/:id`"),
+                (Severity::Error, "Route `/about` is declared more than once"),
+            ]
+        );
+
+        // Both declarations are pointed at
+        let duplicate = &diagnostics[0];
+        assert_eq!(duplicate.location.as_ref().map(|l| l.line), Some(10));
+        assert_eq!(duplicate.labels[0].location.line, 2);
+        assert_eq!(duplicate.labels[0].location.length, "\"/todos/:id\"".len());
+        assert!(RouteTable::has_errors(&diagnostics));
+    }
+
+    #[test]
+    fn test_shadowed_route_warning() {
+        let diagnostics = check(
+            r#"
+component App() {
+    return <Router>
+        <Route path="/todos/:id" page={Todo} />
+        <Route path="/todos/new" page={NewTodo} />
+        <Route path="/users/new" page={NewUser} />
+        <Route path="/users/:id" page={User} />
+    </Router>;
+}
+"#,
+        );
+
+        assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+        let warning = &diagnostics[0];
+        assert_eq!(warning.severity, Severity::Warning);
+        assert_eq!(
+            warning.message,
+            "Route `/todos/new` is unreachable: `/todos/:id` is declared earlier and matches every path it does"
+        );
+        assert_eq!(warning.code.as_deref(), Some("W008"));
+        assert_eq!(warning.location.as_ref().map(|l| l.line), Some(5));
+        assert_eq!(warning.labels[0].location.line, 4);
+        assert!(!RouteTable::has_errors(&diagnostics));
+    }
+
+    #[test]
+    fn test_handler_params_are_typed() {
+        let diagnostics = check(
+            r#"
+struct Filter { done: bool }
+
+@route("/todos/:id/:filter")
+fn list(id: i64, filter: Filter, page: i32) -> String {
+    return "x";
+}
+
+@route("todos")
+fn bad_path() -> String {
+    return "x";
+}
+"#,
+        );
+        let messages: Vec<_> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Route param `filter` of `list` can't be parsed from a URL",
+                "Parameter `page` of route handler `list` is not in its path `/todos/:id/:filter`",
+                "Route path `todos` must start with `/`",
+            ]
+        );
+
+        let named = |name: &str| TypeExpression::Named(crate::ast::Identifier::new(name.to_string()));
+        assert_eq!(ParamKind::for_type(&named("u32")), Some(ParamKind::Int));
+        assert_eq!(ParamKind::for_type(&named("String")), Some(ParamKind::Text));
+        assert_eq!(ParamKind::for_type(&named("Filter")), None);
+    }
+}
@@ -3,18 +3,27 @@
 // This module generates:
 // 1. Client-side stubs: Functions that make HTTP calls to server functions
 // 2. Server-side handlers: Express-style route handlers for RPC endpoints
+//    and for @route functions
 
 #[allow(unused_imports)] // Identifier is used in tests
 use crate::ast::{FunctionDefinition, FunctionParameter, TypeExpression, Identifier};
+use crate::route_table::ParamKind;
 
 #[derive(Debug, Clone)]
 pub struct RPCGenerator {
     pub server_functions: Vec<FunctionDefinition>,
+    pub route_handlers: Vec<FunctionDefinition>,
 }
 
 impl RPCGenerator {
     pub fn new(server_functions: Vec<FunctionDefinition>) -> Self {
-        RPCGenerator { server_functions }
+        RPCGenerator { server_functions, route_handlers: Vec::new() }
+    }
+
+    /// Functions with `@route`, served at their paths
+    pub fn with_routes(mut self, route_handlers: Vec<FunctionDefinition>) -> Self {
+        self.route_handlers = route_handlers;
+        self
     }
 
     /// Generates client-side RPC stubs (async functions that call the server)
@@ -34,15 +43,18 @@ impl RPCGenerator {
         output
     }
 
-    /// Generates a single client stub function
+    /// Generates a single client stub function. The trailing `__signal` is the
+    /// calling component's AbortSignal, so unmounting it cancels the fetch.
     fn generate_client_stub(&self, func: &FunctionDefinition) -> String {
         let name = &func.name.value;
         let params = self.format_parameters(&func.parameters);
         let param_names = self.extract_parameter_names(&func.parameters);
+        let signal = "__signal = __jounce_rpc_signal()";
+        let params = if params.is_empty() { signal.to_string() } else { format!("{}, {}", params, signal) };
 
         format!(
             "export async function {}({}) {{\n\
-            \x20   return await client.call('{}', [{}]);\n\
+            \x20   return await client.call('{}', [{}], __signal);\n\
             }}",
             name, params, name, param_names
         )
@@ -62,6 +74,14 @@ impl RPCGenerator {
             output.push('\n');
         }
 
+        if !self.route_handlers.is_empty() {
+            output.push_str("// Route handlers\n");
+            for func in &self.route_handlers {
+                output.push_str(&self.generate_route_handler(func));
+                output.push('\n');
+            }
+        }
+
         // Start the server
         output.push_str("// Start RPC server\n");
         output.push_str("server.start();\n");
@@ -85,6 +105,29 @@ impl RPCGenerator {
         )
     }
 
+    /// Generates the handler for an @route function: each param is parsed as
+    /// its parameter's type first, and a value that doesn't parse is a 404
+    fn generate_route_handler(&self, func: &FunctionDefinition) -> String {
+        let Some(route) = &func.route else {
+            return String::new();
+        };
+        let name = &func.name.value;
+        let mut output = format!("server.route('{}', async (params) => {{\n", route.path);
+        for param in &func.parameters {
+            let kind = ParamKind::for_type(&param.type_annotation).unwrap_or(ParamKind::Text);
+            output.push_str(&format!(
+                "    const {} = __jounce_route_param(params, '{}', '{}');\n",
+                param.name.value, param.name.value, kind.runtime_name()
+            ));
+        }
+        output.push_str(&format!(
+            "    return await {}({});\n}});\n",
+            name,
+            self.extract_parameter_names(&func.parameters)
+        ));
+        output
+    }
+
     /// Formats function parameters as a string
     fn format_parameters(&self, params: &[FunctionParameter]) -> String {
         params
@@ -214,6 +257,7 @@ mod tests {
     use crate::lexer::Lexer;
     use crate::parser::Parser;
     use crate::code_splitter::CodeSplitter;
+    use crate::ast::Position;
 
     #[test]
     fn test_rpc_generation() {
@@ -242,9 +286,9 @@ mod tests {
 
         // Test client stubs
         let client_stubs = rpc_gen.generate_client_stubs();
-        assert!(client_stubs.contains("async function get_user(id: number)"));
-        assert!(client_stubs.contains("async function save_data(name: string, age: number)"));
-        assert!(client_stubs.contains("client.call('get_user'"));
+        assert!(client_stubs.contains("async function get_user(id: number, __signal = __jounce_rpc_signal())"));
+        assert!(client_stubs.contains("async function save_data(name: string, age: number, __signal = __jounce_rpc_signal())"));
+        assert!(client_stubs.contains("client.call('get_user', [id], __signal)"));
         assert!(client_stubs.contains("client.call('save_data'"));
 
         // Test server handlers
@@ -264,19 +308,19 @@ mod tests {
         let rpc_gen = RPCGenerator::new(vec![]);
 
         // Test basic types
-        let i32_type = TypeExpression::Named(Identifier { value: "i32".to_string() });
+        let i32_type = TypeExpression::Named(Identifier::new("i32"));
         assert_eq!(rpc_gen.format_type(&i32_type), "number");
 
-        let string_type = TypeExpression::Named(Identifier { value: "String".to_string() });
+        let string_type = TypeExpression::Named(Identifier::new("String"));
         assert_eq!(rpc_gen.format_type(&string_type), "string");
 
-        let bool_type = TypeExpression::Named(Identifier { value: "bool".to_string() });
+        let bool_type = TypeExpression::Named(Identifier::new("bool"));
         assert_eq!(rpc_gen.format_type(&bool_type), "boolean");
 
         // Test generic types
         let vec_i32 = TypeExpression::Generic(
-            Identifier { value: "Vec".to_string() },
-            vec![TypeExpression::Named(Identifier { value: "i32".to_string() })]
+            Identifier::new("Vec"),
+            vec![TypeExpression::Named(Identifier::new("i32"))]
         );
         assert_eq!(rpc_gen.format_type(&vec_i32), "Array<number>");
     }
@@ -285,12 +329,16 @@ mod tests {
     fn test_parameter_extraction() {
         let params = vec![
             FunctionParameter {
-                name: Identifier { value: "id".to_string() },
-                type_annotation: TypeExpression::Named(Identifier { value: "i32".to_string() }),
+                name: Identifier::new("id"),
+                type_annotation: TypeExpression::Named(Identifier::new("i32")),
+                position: Position::default(),
+                is_slot: false,
             },
             FunctionParameter {
-                name: Identifier { value: "name".to_string() },
-                type_annotation: TypeExpression::Named(Identifier { value: "String".to_string() }),
+                name: Identifier::new("name"),
+                type_annotation: TypeExpression::Named(Identifier::new("String")),
+                position: Position::default(),
+                is_slot: false,
             },
         ];
 
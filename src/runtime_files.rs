@@ -0,0 +1,103 @@
+// Runtime Files - The JavaScript runtime a build writes next to its bundles
+//
+// runtime/shared-runtime.js is an ES module, which the browser imports. The
+// server runtime and the test renderer are CommonJS, and Node only learned to
+// require() an ES module in 20.19, so they load a CommonJS copy of it instead:
+// shared-runtime.cjs, made from the ES module by `shared_runtime_commonjs`.
+//
+// runtime/devtools.js is for dev builds only: release builds neither import
+// nor ship it.
+
+const SERVER_RUNTIME: &str = include_str!("../runtime/server-runtime.js");
+const CLIENT_RUNTIME: &str = include_str!("../runtime/client-runtime.js");
+const REACTIVITY_RUNTIME: &str = include_str!("../runtime/reactivity.js");
+const DEVTOOLS_RUNTIME: &str = include_str!("../runtime/devtools.js");
+
+/// The helpers both runtimes use, as an ES module
+pub const SHARED_RUNTIME: &str = include_str!("../runtime/shared-runtime.js");
+
+/// The runtime file only dev builds ship
+pub const DEVTOOLS_FILE: &str = "devtools.js";
+
+/// The runtime files a build writes next to its bundles, by file name.
+/// `devtools` adds the devtools registry, which dev bundles import.
+pub fn runtime_files(devtools: bool) -> Vec<(&'static str, String)> {
+    let mut files = vec![
+        ("server-runtime.js", SERVER_RUNTIME.to_string()),
+        ("client-runtime.js", CLIENT_RUNTIME.to_string()),
+        ("reactivity.js", REACTIVITY_RUNTIME.to_string()),
+        ("shared-runtime.js", SHARED_RUNTIME.to_string()),
+        ("shared-runtime.cjs", shared_runtime_commonjs()),
+    ];
+    if devtools {
+        files.push((DEVTOOLS_FILE, DEVTOOLS_RUNTIME.to_string()));
+    }
+    files
+}
+
+/// shared-runtime.js as CommonJS: each `export` dropped, and the exported
+/// names collected into `module.exports`
+pub fn shared_runtime_commonjs() -> String {
+    let mut out = String::from("// Generated from shared-runtime.js by jnc; edit that file instead\n\n");
+    let mut names = Vec::new();
+    for line in SHARED_RUNTIME.lines() {
+        match line.strip_prefix("export ") {
+            Some(declaration) => {
+                let name = declaration
+                    .split_whitespace()
+                    .nth(1)
+                    .map(|name| name.split(|ch: char| !(ch.is_alphanumeric() || ch == '_' || ch == '$')).next().unwrap_or(name));
+                names.extend(name.map(str::to_string));
+                out.push_str(declaration);
+            }
+            None => out.push_str(line),
+        }
+        out.push('\n');
+    }
+    out.push_str(&format!("\nmodule.exports = {{ {} }};\n", names.join(", ")));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::js_emitter::JSEmitter;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    #[test]
+    fn test_release_build_has_no_devtools() {
+        let source = r#"
+            component Counter(label: String) {
+                let count = signal(0);
+                return <button>{label}</button>;
+            }
+        "#;
+        let program = Parser::new(&mut Lexer::new(source.to_string())).parse_program().unwrap();
+        let emitter = JSEmitter::new(&program).with_devtools(false);
+        let bundles = [("server.js", emitter.generate_server_js()), ("client.js", emitter.generate_client_js())];
+        for (name, contents) in bundles.into_iter().chain(runtime_files(false)) {
+            assert!(!contents.contains("__JOUNCE_DEVTOOLS__"), "{} mentions devtools", name);
+            assert!(!contents.contains("from './devtools.js'"), "{} imports devtools", name);
+        }
+
+        // Dev builds ship it and import it
+        assert!(runtime_files(true).iter().any(|(name, contents)| *name == DEVTOOLS_FILE && contents.contains("__JOUNCE_DEVTOOLS__")));
+        assert!(JSEmitter::new(&program).with_devtools(true).generate_client_js().contains("from './devtools.js'"));
+    }
+
+    #[test]
+    fn test_commonjs_copy_exports_each_helper() {
+        let commonjs = shared_runtime_commonjs();
+        assert!(!commonjs.lines().any(|line| line.starts_with("export ")));
+        assert!(commonjs.contains("\nfunction escape(value, mode = 'text') {"));
+        assert!(commonjs.ends_with("module.exports = { escape, RawHtml, unsafe_raw, memoize, JsValue, I18n };\n"));
+    }
+
+    #[test]
+    fn test_checked_in_commonjs_copy_is_current() {
+        // runtime/shared-runtime.cjs lets the runtime's own tests run from
+        // the repo; regenerate it when shared-runtime.js changes
+        assert_eq!(include_str!("../runtime/shared-runtime.cjs"), shared_runtime_commonjs());
+    }
+}
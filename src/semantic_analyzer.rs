@@ -1,4 +1,5 @@
 use crate::ast::*;
+use crate::diagnostics::{did_you_mean, suggest_similar, SourceLocation};
 use crate::errors::CompileError;
 use crate::module_loader::{ModuleLoader, ExportedSymbol};
 use std::collections::HashMap;
@@ -117,6 +118,25 @@ impl StructTable {
     fn exists(&self, struct_name: &str) -> bool {
         self.structs.contains_key(struct_name)
     }
+
+    /// Error for a field the struct doesn't declare, suggesting the closest one
+    fn unknown_field(&self, struct_name: &str, field: &Identifier) -> CompileError {
+        let mut fields: Vec<&str> = self
+            .structs
+            .get(struct_name)
+            .map(|fields| fields.keys().map(String::as_str).collect())
+            .unwrap_or_default();
+        fields.sort_unstable();
+        let location = SourceLocation::from_identifier(field);
+        let fix = suggest_similar(&field.value, fields).map(|similar| did_you_mean(location.clone(), similar));
+        CompileError::WithLocation {
+            message: format!("Struct '{}' has no field named '{}'", struct_name, field.value),
+            location,
+            suggestion: None,
+            labels: Vec::new(),
+        }
+        .with_fix(fix)
+    }
 }
 
 /// Tracks enum definitions for exhaustiveness checking
@@ -478,31 +498,24 @@ impl SemanticAnalyzer {
     }
 
     fn analyze_use_statement(&mut self, use_stmt: &UseStatement) -> Result<ResolvedType, CompileError> {
-        // Convert Identifier vec to String vec for module path
-        let module_path: Vec<String> = use_stmt.path.iter()
-            .map(|ident| ident.value.clone())
-            .collect();
-
-        // If no specific imports are specified, import all exports
-        if use_stmt.imports.is_empty() {
-            // Wildcard import (use module::*)
-            let exports = self.module_loader.get_all_exports(&module_path)
-                .map_err(|e| CompileError::Generic(format!("Failed to load module: {}", e)))?;
-
-            for (name, export) in exports {
-                self.import_symbol(&name, &export)?;
-            }
-        } else {
-            // Selective imports (use module::{A, B, C})
-            let import_names: Vec<String> = use_stmt.imports.iter()
-                .map(|ident| ident.value.clone())
-                .collect();
-
-            let exports = self.module_loader.get_exports(&module_path, &import_names)
-                .map_err(|e| CompileError::Generic(format!("Failed to load exports: {}", e)))?;
+        for item in use_stmt.items() {
+            match self.module_loader.resolve_use_item(item) {
+                UseItem::Glob { module } => {
+                    // Wildcard import (use module::* or use module;)
+                    let exports = self.module_loader.get_all_exports(&module)
+                        .map_err(|e| CompileError::Generic(format!("Failed to load module: {}", e)))?;
+
+                    for (name, export) in exports {
+                        self.import_symbol(&name, &export)?;
+                    }
+                }
+                UseItem::Name { module, name, alias } => {
+                    // Selective import, possibly renamed (use module::{A, B as C})
+                    let export = self.module_loader.get_export(&module, &name)
+                        .map_err(|e| CompileError::Generic(format!("Failed to load exports: {}", e)))?;
 
-            for (name, export) in exports {
-                self.import_symbol(&name, &export)?;
+                    self.import_symbol(&alias, &export.renamed(&alias))?;
+                }
             }
         }
 
@@ -533,6 +546,10 @@ impl SemanticAnalyzer {
                 // Type will be inferred from the constant's value
                 self.symbols.define(name.to_string(), ResolvedType::Unknown);
             }
+            ExportedSymbol::Component(_) => {
+                // Components are called from JSX; their props aren't tracked here
+                self.symbols.define(name.to_string(), ResolvedType::Unknown);
+            }
             ExportedSymbol::Type(_) => {
                 // Type alias - for now treat as Unknown
                 self.symbols.define(name.to_string(), ResolvedType::Unknown);
@@ -751,11 +768,7 @@ impl SemanticAnalyzer {
                         // TODO: Add type compatibility checking here
                         // For now, just accept any type
                     } else {
-                        return Err(CompileError::Generic(format!(
-                            "Struct '{}' has no field named '{}'",
-                            struct_lit.name.value,
-                            field_name.value
-                        )));
+                        return Err(self.structs.unknown_field(&struct_lit.name.value, field_name));
                     }
                 }
 
@@ -775,11 +788,7 @@ impl SemanticAnalyzer {
                         {
                             Ok(field_type)
                         } else {
-                            Err(CompileError::Generic(format!(
-                                "Struct '{}' has no field named '{}'",
-                                struct_name,
-                                field_access.field.value
-                            )))
+                            Err(self.structs.unknown_field(&struct_name, &field_access.field))
                         }
                     }
                     _ => {
@@ -819,6 +828,7 @@ impl SemanticAnalyzer {
                     self.analyze_expression_with_expected(&match_expr.scrutinee, None)?;
 
                 // Check exhaustiveness if matching on an enum
+                self.check_match_variants(match_expr)?;
                 self.check_match_exhaustiveness(match_expr, &scrutinee_type)?;
 
                 // Analyze all match arms and infer the result type
@@ -962,6 +972,24 @@ impl SemanticAnalyzer {
                 // Returns a styles object mapping class names to scoped names
                 Ok(ResolvedType::Unknown)
             }
+            Expression::InlineStyle(style) => {
+                for expr in style.expressions() {
+                    self.analyze_expression_with_expected(expr, None)?;
+                }
+                Ok(ResolvedType::Unknown)
+            }
+            Expression::Classes(classes) => {
+                for expr in classes.expressions() {
+                    self.analyze_expression_with_expected(expr, None)?;
+                }
+                Ok(ResolvedType::String)
+            }
+            Expression::Template(template) => {
+                for expr in template.expressions() {
+                    self.analyze_expression_with_expected(expr, None)?;
+                }
+                Ok(ResolvedType::String)
+            }
             // Reactivity primitives (Phase 12)
             Expression::Signal(signal_expr) => {
                 self.analyze_expression_with_expected(&signal_expr.initial_value, None)?;
@@ -1094,6 +1122,35 @@ impl SemanticAnalyzer {
         }
     }
 
+    /// Every `Enum::Variant` pattern must name a variant the enum declares
+    fn check_match_variants(&self, match_expr: &MatchExpression) -> Result<(), CompileError> {
+        for pattern in match_expr.arms.iter().flat_map(|arm| &arm.patterns) {
+            let Pattern::EnumVariant { name, .. } = pattern else {
+                continue;
+            };
+            let Some((enum_name, variant)) = name.value.split_once("::") else {
+                continue;
+            };
+            let Some(variants) = self.enums.get_variants(enum_name) else {
+                continue;
+            };
+            if variants.iter().any(|v| v == variant) {
+                continue;
+            }
+            let location = SourceLocation::from_identifier(name);
+            let fix = suggest_similar(variant, variants.iter().map(String::as_str))
+                .map(|similar| did_you_mean(location.clone(), &format!("{}::{}", enum_name, similar)));
+            return Err(CompileError::WithLocation {
+                message: format!("Enum '{}' has no variant named '{}'", enum_name, variant),
+                location,
+                suggestion: None,
+                labels: Vec::new(),
+            }
+            .with_fix(fix));
+        }
+        Ok(())
+    }
+
     fn check_match_exhaustiveness(&self, match_expr: &MatchExpression, _scrutinee_type: &ResolvedType) -> Result<(), CompileError> {
         // Collect all patterns from match arms
         let mut covered_variants: HashSet<String> = HashSet::new();
@@ -0,0 +1,168 @@
+// Shadowed components - warns when JSX renders a local instead of a component
+//
+// `<Card/>` compiles to a reference to whatever `Card` is in scope, so a local
+// variable or parameter named like a component silently replaces it:
+//
+//     let Card = load_card();
+//     return <Card />;   // renders the local, not `component Card`
+//
+// Components can come from any module, so files are added first and checked
+// together, like the route table.
+
+use crate::ast::{
+    BlockStatement, ComponentDefinition, ForInStatement, FunctionDefinition, Identifier, JsxElement, LambdaExpression,
+    LetStatement, Program, Statement,
+};
+use crate::diagnostics::{Diagnostic, SourceLocation};
+use crate::visit::{walk_block, walk_component, walk_for_in, walk_function, walk_jsx_element, walk_lambda, walk_let, Visit};
+use std::collections::HashSet;
+
+/// A JSX tag that resolves to a local binding
+#[derive(Debug, Clone)]
+struct LocalTag {
+    file: String,
+    binding: Identifier,
+    tag: Identifier,
+}
+
+#[derive(Debug, Default)]
+pub struct ShadowCheck {
+    components: HashSet<String>,
+    tags: Vec<LocalTag>,
+}
+
+impl ShadowCheck {
+    /// Add the components one file defines and the JSX tags it renders from locals
+    pub fn add_program(&mut self, program: &Program, file: &str) {
+        for statement in &program.statements {
+            if let Statement::Component(comp) = statement {
+                self.components.insert(comp.name.value.clone());
+            }
+        }
+        let mut finder = TagFinder { file, scopes: Vec::new(), tags: Vec::new() };
+        finder.visit_program(program);
+        for tag in finder.tags {
+            // Modules carry the definitions they import, so one body can be added twice
+            let seen = self.tags.iter().any(|seen| {
+                seen.tag.position == tag.tag.position && seen.binding.position == tag.binding.position
+            });
+            if !seen {
+                self.tags.push(tag);
+            }
+        }
+    }
+
+    /// A warning for every local rendered as a tag where a component has its name
+    pub fn check(&self) -> Vec<Diagnostic> {
+        self.tags
+            .iter()
+            .filter(|local| self.components.contains(&local.tag.value))
+            .map(|local| {
+                let at = |ident: &Identifier| SourceLocation { file: local.file.clone(), ..SourceLocation::from_identifier(ident) };
+                let name = &local.tag.value;
+                Diagnostic::warning(format!("<{}> renders the local '{}', not the component '{}'", name, name, name))
+                    .at(at(&local.tag))
+                    .with_code("W014")
+                    .with_label(at(&local.binding), format!("'{}' declared here, shadowing the component", name))
+                    .with_suggestion(format!("rename the local so <{}> refers to the component", name))
+            })
+            .collect()
+    }
+}
+
+/// Walks bodies keeping the locals in scope at each point
+struct TagFinder<'a> {
+    file: &'a str,
+    scopes: Vec<Vec<Identifier>>,
+    tags: Vec<LocalTag>,
+}
+
+impl TagFinder<'_> {
+    fn in_scope(&self, name: &str) -> Option<&Identifier> {
+        self.scopes.iter().rev().flat_map(|scope| scope.iter().rev()).find(|ident| ident.value == name)
+    }
+
+    fn with_scope(&mut self, bindings: Vec<Identifier>, walk: impl FnOnce(&mut Self)) {
+        self.scopes.push(bindings);
+        walk(self);
+        self.scopes.pop();
+    }
+}
+
+impl Visit for TagFinder<'_> {
+    fn visit_function(&mut self, func: &FunctionDefinition) {
+        let params = func.parameters.iter().map(|param| param.name.clone()).collect();
+        self.with_scope(params, |finder| walk_function(finder, func));
+    }
+
+    fn visit_component(&mut self, comp: &ComponentDefinition) {
+        let params = comp.parameters.iter().map(|param| param.name.clone()).collect();
+        self.with_scope(params, |finder| walk_component(finder, comp));
+    }
+
+    fn visit_block(&mut self, block: &BlockStatement) {
+        self.with_scope(Vec::new(), |finder| walk_block(finder, block));
+    }
+
+    fn visit_let(&mut self, let_stmt: &LetStatement) {
+        walk_let(self, let_stmt);
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.extend(let_stmt.pattern.bound_identifiers());
+        }
+    }
+
+    fn visit_for_in(&mut self, for_in: &ForInStatement) {
+        self.with_scope(vec![for_in.variable.clone()], |finder| walk_for_in(finder, for_in));
+    }
+
+    fn visit_lambda(&mut self, lambda: &LambdaExpression) {
+        let params = lambda.parameters.iter().map(|param| param.name.clone()).collect();
+        self.with_scope(params, |finder| walk_lambda(finder, lambda));
+    }
+
+    fn visit_jsx_element(&mut self, jsx: &JsxElement) {
+        let tag = &jsx.opening_tag.name;
+        if let Some(binding) = self.in_scope(&tag.value) {
+            self.tags.push(LocalTag { file: self.file.to_string(), binding: binding.clone(), tag: tag.clone() });
+        }
+        walk_jsx_element(self, jsx);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    fn check(source: &str) -> Vec<Diagnostic> {
+        let mut lexer = Lexer::new(source.to_string());
+        let program = Parser::new(&mut lexer).parse_program().expect("parse");
+        let mut shadows = ShadowCheck::default();
+        shadows.add_program(&program, "app.jnc");
+        shadows.check()
+    }
+
+    #[test]
+    fn test_local_shadowing_component_in_jsx() {
+        let warnings = check(
+            "component Card() {\n    return <div>card</div>;\n}\n\ncomponent App() {\n    let Card = 1;\n    return <div><Card /></div>;\n}\n",
+        );
+        assert_eq!(warnings.len(), 1);
+        let warning = &warnings[0];
+        assert_eq!(warning.code.as_deref(), Some("W014"));
+        assert!(warning.message.contains("<Card> renders the local 'Card'"), "{}", warning.message);
+        let location = warning.location.as_ref().unwrap();
+        assert_eq!((location.file.as_str(), location.line), ("app.jnc", 7));
+        assert_eq!(warning.labels[0].location.line, 6);
+    }
+
+    #[test]
+    fn test_no_warning_outside_the_locals_scope() {
+        // The local is gone by the time the tag renders, and other names don't matter
+        let warnings = check(
+            "component Card() {\n    return <div>card</div>;\n}\n\ncomponent App(show: bool) {\n    if show {\n        let Card = 1;\n    }\n    let Title = 2;\n    return <div><Card /></div>;\n}\n",
+        );
+        assert!(warnings.is_empty(), "{:?}", warnings);
+    }
+}
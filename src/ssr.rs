@@ -1,8 +1,10 @@
 // Server-Side Rendering (SSR) Module
 // Renders components to HTML on the server
 
+use crate::escape::{escape, EscapeMode};
 use crate::vdom::VNode;
-use crate::ast::{Expression, JsxChild, JsxElement};
+use crate::void_elements::is_void_element;
+use crate::ast::{is_raw_text_element, Expression, JsxChild, JsxElement, JSX_KEY, JSX_REF, UNSAFE_HTML};
 use std::collections::HashMap;
 
 /// Convert a JSX AST element to a VNode for rendering
@@ -11,12 +13,23 @@ pub fn jsx_to_vnode(jsx: &JsxElement) -> VNode {
     let tag = jsx.opening_tag.name.value.clone();
 
     // Convert attributes
+    // What a `{...props}` spread holds is only known at runtime, and
+    // `unsafe_html` is the element's contents rather than an attribute, and
+    // `key` and `ref` are only for the client runtime
+    let sets_contents = jsx.unsafe_html().is_some();
     let attrs: Vec<(String, String)> = jsx.opening_tag.attributes
         .iter()
-        .map(|attr| {
-            let key = attr.name.value.clone();
-            let value = expr_to_string(&attr.value);
-            (key, value)
+        .filter(|attr| attr.spread().is_none() && attr.name.value != JSX_KEY && attr.name.value != JSX_REF)
+        .filter(|attr| !(sets_contents && attr.name.value == UNSAFE_HTML))
+        .filter_map(|attr| {
+            let key = attr.event_modifiers().0.to_string();
+            // `disabled` is on by being there at all, so `disabled={false}` leaves it out
+            let value = match attr.value {
+                Expression::BoolLiteral(on) if !takes_boolean_text(&key) => on.then(String::new)?,
+                Expression::BoolLiteral(on) => on.to_string(),
+                _ => expr_to_string(&attr.value),
+            };
+            Some((key, value))
         })
         .collect();
 
@@ -29,13 +42,20 @@ pub fn jsx_to_vnode(jsx: &JsxElement) -> VNode {
     VNode::Element { tag, attrs, children }
 }
 
+/// Attributes that spell booleans out as "true" and "false", like
+/// `aria-hidden`; the rest are present or absent
+fn takes_boolean_text(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    name.starts_with("aria-") || name.starts_with("data-") || matches!(name.as_str(), "contenteditable" | "draggable" | "spellcheck")
+}
+
 /// Convert a JSX child to a VNode
 fn jsx_child_to_vnode(child: &JsxChild) -> Option<VNode> {
     match child {
         JsxChild::Element(el) => Some(jsx_to_vnode(el)),
         JsxChild::Text(text) => {
-            // Skip whitespace-only text nodes
-            if text.trim().is_empty() {
+            // The lexer already dropped whitespace that spans lines; what's left renders
+            if text.is_empty() {
                 None
             } else {
                 Some(VNode::Text(text.clone()))
@@ -111,8 +131,8 @@ impl SSRContext {
     pub fn add_meta(&mut self, name: &str, content: &str) {
         self.head_elements.push(format!(
             r#"<meta name="{}" content="{}">"#,
-            escape_html(name),
-            escape_html(content)
+            escape(name, EscapeMode::Attribute),
+            escape(content, EscapeMode::Attribute)
         ));
     }
 
@@ -142,7 +162,7 @@ pub fn render_to_string(vnode: &VNode, ctx: &mut SSRContext) -> String {
                 html.push(' ');
                 html.push_str(key);
                 html.push_str(r#"=""#);
-                html.push_str(&escape_html(value));
+                html.push_str(&escape(value, EscapeMode::Attribute));
                 html.push('"');
             }
 
@@ -154,9 +174,12 @@ pub fn render_to_string(vnode: &VNode, ctx: &mut SSRContext) -> String {
 
             html.push('>');
 
-            // Children
+            // Children; the text of a <style> or <script> is CSS or JavaScript, not HTML
             for child in children {
-                html.push_str(&render_to_string(child, ctx));
+                match child {
+                    VNode::Text(content) if is_raw_text_element(tag) => html.push_str(content),
+                    _ => html.push_str(&render_to_string(child, ctx)),
+                }
             }
 
             // Closing tag
@@ -166,7 +189,7 @@ pub fn render_to_string(vnode: &VNode, ctx: &mut SSRContext) -> String {
 
             html
         }
-        VNode::Text(content) => escape_html(content),
+        VNode::Text(content) => escape(content, EscapeMode::Text),
     }
 }
 
@@ -186,7 +209,7 @@ pub fn render_to_document(
     doc.push_str("<head>\n");
     doc.push_str("  <meta charset=\"UTF-8\">\n");
     doc.push_str("  <meta name=\"viewport\" content=\"width=device-width, initial-scale=1.0\">\n");
-    doc.push_str(&format!("  <title>{}</title>\n", escape_html(title)));
+    doc.push_str(&format!("  <title>{}</title>\n", escape(title, EscapeMode::Text)));
 
     // Add custom head elements
     for elem in &ctx.head_elements {
@@ -198,7 +221,7 @@ pub fn render_to_document(
     doc.push_str("</head>\n");
     doc.push_str("<body>\n");
     doc.push_str("  <div id=\"app\" data-component=\"");
-    doc.push_str(app_name);
+    doc.push_str(&escape(app_name, EscapeMode::Attribute));
     doc.push_str("\">\n");
     doc.push_str(&indent_html(&body_html, 2));
     doc.push_str("  </div>\n");
@@ -216,7 +239,7 @@ pub fn render_to_document(
 
     // Add preload scripts
     for script in &ctx.preload_scripts {
-        doc.push_str(&format!("  <script src=\"{}\" defer></script>\n", escape_html(script)));
+        doc.push_str(&format!("  <script src=\"{}\" defer></script>\n", escape(script, EscapeMode::Attribute)));
     }
 
     doc.push_str("</body>\n");
@@ -262,25 +285,6 @@ impl Default for SSRStream {
     }
 }
 
-/// Escape HTML special characters
-fn escape_html(input: &str) -> String {
-    input
-        .replace('&', "&amp;")
-        .replace('<', "&lt;")
-        .replace('>', "&gt;")
-        .replace('"', "&quot;")
-        .replace('\'', "&#39;")
-}
-
-/// Check if an element is void (self-closing)
-fn is_void_element(tag: &str) -> bool {
-    matches!(
-        tag,
-        "area" | "base" | "br" | "col" | "embed" | "hr" | "img" | "input"
-        | "link" | "meta" | "param" | "source" | "track" | "wbr"
-    )
-}
-
 /// Indent HTML for pretty printing
 fn indent_html(html: &str, spaces: usize) -> String {
     let indent = " ".repeat(spaces);
@@ -326,9 +330,96 @@ mod tests {
 
     #[test]
     fn test_escape_html() {
-        assert_eq!(escape_html("<div>"), "&lt;div&gt;");
-        assert_eq!(escape_html("a & b"), "a &amp; b");
-        assert_eq!(escape_html("'quote'"), "&#39;quote&#39;");
+        assert_eq!(escape("<div>", EscapeMode::Text), "&lt;div&gt;");
+        assert_eq!(escape("a & b", EscapeMode::Text), "a &amp; b");
+        assert_eq!(escape("'quote'", EscapeMode::Text), "&#39;quote&#39;");
+    }
+
+    #[test]
+    fn test_render_injected_markup_is_inert() {
+        let vnode = VNode::Element {
+            tag: "a".to_string(),
+            attrs: vec![("title".to_string(), "\"><script>alert(1)</script>".to_string())],
+            children: vec![VNode::Text("<img src=x onerror=alert(1)>".to_string())],
+        };
+
+        let mut ctx = SSRContext::new();
+        let html = render_to_string(&vnode, &mut ctx);
+        assert_eq!(
+            html,
+            "<a title=\"&quot;&gt;&lt;script&gt;alert(1)&lt;/script&gt;\">&lt;img src=x onerror=alert(1)&gt;</a>"
+        );
+        assert!(render_to_document(&vnode, &mut ctx, "</div><script>").contains("data-component=\"&lt;/div&gt;&lt;script&gt;\""));
+    }
+
+    #[test]
+    fn test_render_boolean_attributes() {
+        use crate::ast::{Expression, Statement};
+        use crate::lexer::Lexer;
+        use crate::parser::Parser;
+
+        let source = r#"let field = <input required disabled={false} aria-hidden={false} />;"#;
+        let mut lexer = Lexer::new(source.to_string());
+        let program = Parser::new(&mut lexer).parse_program().unwrap();
+        let Statement::Let(field) = &program.statements[0] else { panic!("expected a let") };
+        let Expression::JsxElement(jsx) = &field.value else { panic!("expected JSX") };
+
+        let html = render_to_string(&jsx_to_vnode(jsx), &mut SSRContext::new());
+        assert_eq!(html, "<input required=\"\" aria-hidden=\"false\" />");
+    }
+
+    #[test]
+    fn test_render_leaves_out_keys_and_refs() {
+        use crate::ast::{Expression, Statement};
+        use crate::lexer::Lexer;
+        use crate::parser::Parser;
+
+        let source = r#"let item = <li key={todo.id} ref={last} class="todo">{todo.title}</li>;"#;
+        let mut lexer = Lexer::new(source.to_string());
+        let program = Parser::new(&mut lexer).parse_program().unwrap();
+        let Statement::Let(item) = &program.statements[0] else { panic!("expected a let") };
+        let Expression::JsxElement(jsx) = &item.value else { panic!("expected JSX") };
+
+        let html = render_to_string(&jsx_to_vnode(jsx), &mut SSRContext::new());
+        assert!(html.starts_with("<li class=\"todo\">"), "{}", html);
+    }
+
+    #[test]
+    fn test_render_jsx_escapes_text_expressions_and_attributes() {
+        use crate::ast::{Expression, Statement};
+        use crate::lexer::Lexer;
+        use crate::parser::Parser;
+
+        let source = r#"let page = <p title="Tom & 'Jerry'">&lt;script&gt; &amp; &quot;x&quot; {"<script>alert('x')</script>"}</p>;"#;
+        let mut lexer = Lexer::new(source.to_string());
+        let program = Parser::new(&mut lexer).parse_program().unwrap();
+        let Statement::Let(page) = &program.statements[0] else { panic!("expected a let") };
+        let Expression::JsxElement(jsx) = &page.value else { panic!("expected JSX") };
+
+        let html = render_to_string(&jsx_to_vnode(jsx), &mut SSRContext::new());
+        assert_eq!(
+            html,
+            "<p title=\"Tom &amp; &#39;Jerry&#39;\">&lt;script&gt; &amp; &quot;x&quot; &lt;script&gt;alert(&#39;x&#39;)&lt;/script&gt;</p>"
+        );
+    }
+
+    #[test]
+    fn test_render_style_and_script_text_as_written() {
+        use crate::ast::{Expression, Statement};
+        use crate::lexer::Lexer;
+        use crate::parser::Parser;
+
+        let source = "let page = <div><style>.a > b { color: red; }</style><script>if (a < b && c) { go(); }</script><p>a > b</p></div>;";
+        let mut lexer = Lexer::new(source.to_string());
+        let program = Parser::new(&mut lexer).parse_program().unwrap();
+        let Statement::Let(page) = &program.statements[0] else { panic!("expected a let") };
+        let Expression::JsxElement(jsx) = &page.value else { panic!("expected JSX") };
+
+        let html = render_to_string(&jsx_to_vnode(jsx), &mut SSRContext::new());
+        assert_eq!(
+            html,
+            "<div><style>.a > b { color: red; }</style><script>if (a < b && c) { go(); }</script><p>a &gt; b</p></div>"
+        );
     }
 
     #[test]
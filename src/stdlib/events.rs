@@ -0,0 +1,49 @@
+//! Standard library events module: EventBus<T>
+//!
+//! An EventBus carries values of one type between components that don't share
+//! an ancestor to thread callbacks through (toasts, cache invalidation).
+//! Delivery is synchronous and in emit order; a value emitted from inside a
+//! handler is delivered after the current one reaches every subscriber. The
+//! type checker knows the payload type, so `emit` and the `subscribe` handler
+//! are checked against it; the JS runtime implements the bus.
+
+/// EventBus surface in Jounce syntax
+pub const EVENTS_DEFINITION: &str = r#"
+// Constructors
+//   EventBus::new() -> EventBus<T>
+//   EventBus::channel::<T>(name: String) -> EventBus<T>   (one shared bus per name)
+//
+// Methods
+//   bus.emit(value: T)
+//   bus.subscribe(handler: fn(T)) -> Subscription
+//   bus.subscriber_count() -> i32
+//   subscription.unsubscribe()
+//
+// A subscription made while a component renders is released when the
+// component unmounts, as if registered with on_cleanup.
+
+extern "js" {
+    // Runs `cleanup` when the component being rendered unmounts
+    fn on_cleanup(cleanup: fn());
+}
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_events_definition_parses() {
+        let mut lexer = crate::lexer::Lexer::new(EVENTS_DEFINITION.to_string());
+        let mut parser = crate::parser::Parser::new(&mut lexer);
+        let program = parser.parse_program().expect("events definition should parse");
+        assert_eq!(program.statements.len(), 1);
+    }
+
+    #[test]
+    fn test_events_definition_lists_api() {
+        for name in ["EventBus::new", "EventBus::channel::<T>", ".emit(", ".subscribe(", ".unsubscribe()", "fn on_cleanup("] {
+            assert!(EVENTS_DEFINITION.contains(name), "missing {}", name);
+        }
+    }
+}
@@ -14,6 +14,8 @@ pub enum HttpError {
     JsonParseError(String),
     StatusError { status: u16, body: String },
     RequestBuildError(String),
+    /// The request was aborted, e.g. because the component that made it unmounted
+    Cancelled,
 }
 
 impl std::fmt::Display for HttpError {
@@ -27,6 +29,7 @@ impl std::fmt::Display for HttpError {
                 write!(f, "HTTP error {}: {}", status, body)
             }
             HttpError::RequestBuildError(msg) => write!(f, "Request build error: {}", msg),
+            HttpError::Cancelled => write!(f, "Request cancelled"),
         }
     }
 }
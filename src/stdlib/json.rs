@@ -241,10 +241,20 @@ impl JsonParser {
         };
     }
 
-    // Parse JSON string into JsonValue
+    // Parse JSON string into JsonValue. Strict, per RFC 8259: one value,
+    // with nothing but whitespace after it
     fn parse(self: &mut JsonParser) -> Result<JsonValue, String> {
+        let value = self.parse_value()?;
         self.skip_whitespace();
-        return self.parse_value();
+        if !self.is_eof() {
+            return Err(self.error("Unexpected data after JSON value"));
+        }
+        return Ok(value);
+    }
+
+    // An error message saying where parsing stopped
+    fn error(self: &JsonParser, message: String) -> String {
+        return message + " at position " + self.position.to_string();
     }
 
     // Skip whitespace characters
@@ -273,7 +283,9 @@ impl JsonParser {
         self.skip_whitespace();
         let ch = self.peek();
 
-        if ch == "n" {
+        if self.is_eof() {
+            return Err(self.error("Unexpected end of input, expected a value"));
+        } else if ch == "n" {
             return self.parse_null();
         } else if ch == "t" || ch == "f" {
             return self.parse_bool();
@@ -283,8 +295,10 @@ impl JsonParser {
             return self.parse_array();
         } else if ch == "{" {
             return self.parse_object();
-        } else {
+        } else if ch == "-" || (ch >= "0" && ch <= "9") {
             return self.parse_number();
+        } else {
+            return Err(self.error("Unexpected character '" + ch + "', expected a value"));
         }
     }
 
@@ -306,43 +320,42 @@ impl JsonParser {
         }
     }
 
-    // Parse number
+    // Parse number: -?(0|[1-9][0-9]*)(\.[0-9]+)?([eE][+-]?[0-9]+)?
     fn parse_number(self: &mut JsonParser) -> Result<JsonValue, String> {
         let start_pos = self.position;
-        let mut has_dot = false;
-        let mut has_exp = false;
 
         // Handle negative sign
         if self.peek() == "-" {
             self.position = self.position + 1;
         }
 
-        // Parse digits
-        let mut has_digits = false;
-        while !self.is_eof() {
-            let ch = self.peek();
+        // Integer part: a single 0, or digits not starting with 0
+        if self.peek() == "0" {
+            self.position = self.position + 1;
+            if self.is_digit() {
+                return Err(self.error("Leading zeros are not allowed in numbers"));
+            }
+        } else if !self.skip_digits() {
+            return Err(self.error("Expected a digit"));
+        }
 
-            if ch >= "0" && ch <= "9" {
-                has_digits = true;
-                self.position = self.position + 1;
-            } else if ch == "." && !has_dot && !has_exp {
-                has_dot = true;
-                self.position = self.position + 1;
-            } else if (ch == "e" || ch == "E") && !has_exp && has_digits {
-                has_exp = true;
-                self.position = self.position + 1;
-                // Handle optional +/- after exponent
-                let next_ch = self.peek();
-                if next_ch == "+" || next_ch == "-" {
-                    self.position = self.position + 1;
-                }
-            } else {
-                break;
+        // Fraction
+        if self.peek() == "." {
+            self.position = self.position + 1;
+            if !self.skip_digits() {
+                return Err(self.error("Expected a digit after the decimal point"));
             }
         }
 
-        if !has_digits {
-            return Err("Invalid number");
+        // Exponent, with an optional sign
+        if self.peek() == "e" || self.peek() == "E" {
+            self.position = self.position + 1;
+            if self.peek() == "+" || self.peek() == "-" {
+                self.position = self.position + 1;
+            }
+            if !self.skip_digits() {
+                return Err(self.error("Expected a digit in the exponent"));
+            }
         }
 
         // Extract number string and parse it
@@ -352,10 +365,28 @@ impl JsonParser {
         return Ok(JsonValue::Number(num_value));
     }
 
+    // Whether the current character is 0-9
+    fn is_digit(self: &JsonParser) -> bool {
+        let ch = self.peek();
+        return ch != "" && ch >= "0" && ch <= "9";
+    }
+
+    // Skip a run of digits; false if there were none
+    fn skip_digits(self: &mut JsonParser) -> bool {
+        let start_pos = self.position;
+        while self.is_digit() {
+            self.position = self.position + 1;
+        }
+        return self.position > start_pos;
+    }
+
     // Parse string
     fn parse_string(self: &mut JsonParser) -> Result<JsonValue, String> {
         // Expect opening quote
-        self.expect("\"")?;
+        if self.peek() != "\"" {
+            return Err(self.error("Expected a string"));
+        }
+        self.position = self.position + 1;
 
         let mut result = "";
         while !self.is_eof() {
@@ -364,6 +395,9 @@ impl JsonParser {
             if ch == "\"" {
                 // End of string
                 return Ok(JsonValue::String(result));
+            } else if ch < " " {
+                self.position = self.position - 1;
+                return Err(self.error("Unescaped control character in string"));
             } else if ch == "\\" {
                 // Escape sequence
                 let escaped = self.advance();
@@ -380,70 +414,113 @@ impl JsonParser {
                 } else if escaped == "t" {
                     result = result + "\t";
                 } else if escaped == "b" {
-                    result = result + "\b";
+                    result = result + "\u{8}";
                 } else if escaped == "f" {
-                    result = result + "\f";
+                    result = result + "\u{c}";
                 } else if escaped == "u" {
-                    // Unicode escape \uXXXX (simplified - just skip for now)
-                    self.position = self.position + 4;  // Skip 4 hex digits
-                    result = result + "?";  // Placeholder
+                    // Unicode escape \uXXXX; a character outside the BMP is a
+                    // UTF-16 surrogate pair, two escapes in a row
+                    let mut code = self.parse_hex4()?;
+                    if code >= 0xD800 && code <= 0xDBFF && self.peek() == "\\" && self.char_at(self.position + 1) == "u" {
+                        let start_pos = self.position;
+                        self.position = self.position + 2;
+                        let low = self.parse_hex4()?;
+                        if low >= 0xDC00 && low <= 0xDFFF {
+                            code = 0x10000 + (code - 0xD800) * 0x400 + (low - 0xDC00);
+                        } else {
+                            // Not a pair; the second escape stands on its own
+                            self.position = start_pos;
+                        }
+                    }
+                    result = result + String::from_code_point(code);
                 } else {
-                    result = result + escaped;
+                    self.position = self.position - 1;
+                    return Err(self.error("Invalid escape '\\" + escaped + "' in string"));
                 }
             } else {
                 result = result + ch;
             }
         }
 
-        return Err("Unterminated string");
+        return Err(self.error("Unterminated string"));
     }
 
-    // Parse array
+    // Parse the 4 hex digits of a \uXXXX escape into their value
+    fn parse_hex4(self: &mut JsonParser) -> Result<i32, String> {
+        let mut value = 0;
+        for _ in 0..4 {
+            let digit = "0123456789abcdef".index_of(self.peek().to_lowercase());
+            if self.is_eof() || digit < 0 {
+                return Err(self.error("Expected 4 hex digits after \\u"));
+            }
+            value = value * 16 + digit;
+            self.position = self.position + 1;
+        }
+        return Ok(value);
+    }
+
+    // Parse array: values separated by commas, with none after the last
     fn parse_array(self: &mut JsonParser) -> Result<JsonValue, String> {
         self.position = self.position + 1; // Skip '['
         let arr = Vec::new();
 
-        loop {
-            self.skip_whitespace();
-            if self.peek() == "]" {
-                self.position = self.position + 1;
-                break;
-            }
+        self.skip_whitespace();
+        if self.peek() == "]" {
+            self.position = self.position + 1;
+            return Ok(JsonValue::Array(arr));
+        }
 
+        loop {
             let value = self.parse_value()?;
             arr.push(value);
 
             self.skip_whitespace();
-            if self.peek() == "," {
+            let ch = self.peek();
+            if ch == "," {
+                self.position = self.position + 1;
+                self.skip_whitespace();
+                if self.peek() == "]" {
+                    return Err(self.error("Trailing comma in array"));
+                }
+            } else if ch == "]" {
                 self.position = self.position + 1;
+                break;
+            } else if self.is_eof() {
+                return Err(self.error("Unterminated array, expected ',' or ']'"));
+            } else {
+                return Err(self.error("Expected ',' or ']' after array element"));
             }
         }
 
         return Ok(JsonValue::Array(arr));
     }
 
-    // Parse object
+    // Parse object: "key": value members separated by commas, with none after the last
     fn parse_object(self: &mut JsonParser) -> Result<JsonValue, String> {
         self.position = self.position + 1; // Skip '{'
         let obj = HashMap::new();
 
+        self.skip_whitespace();
+        if self.peek() == "}" {
+            self.position = self.position + 1;
+            return Ok(JsonValue::Object(obj));
+        }
+
         loop {
+            // Parse key
             self.skip_whitespace();
-            if self.peek() == "}" {
-                self.position = self.position + 1;
-                break;
+            if self.peek() != "\"" {
+                return Err(self.error("Expected a string key"));
             }
-
-            // Parse key
             let key_value = self.parse_string()?;
             let key = match key_value {
                 JsonValue::String(s) => s,
-                _ => return Err("Expected string key"),
+                _ => return Err(self.error("Expected a string key")),
             };
 
             self.skip_whitespace();
             if self.peek() != ":" {
-                return Err("Expected ':' after key");
+                return Err(self.error("Expected ':' after key"));
             }
             self.position = self.position + 1;
 
@@ -452,8 +529,20 @@ impl JsonParser {
             obj.insert(key, value);
 
             self.skip_whitespace();
-            if self.peek() == "," {
+            let ch = self.peek();
+            if ch == "," {
                 self.position = self.position + 1;
+                self.skip_whitespace();
+                if self.peek() == "}" {
+                    return Err(self.error("Trailing comma in object"));
+                }
+            } else if ch == "}" {
+                self.position = self.position + 1;
+                break;
+            } else if self.is_eof() {
+                return Err(self.error("Unterminated object, expected ',' or '}'"));
+            } else {
+                return Err(self.error("Expected ',' or '}' after object member"));
             }
         }
 
@@ -477,22 +566,13 @@ impl JsonParser {
         return self.position >= self.input.len();
     }
 
-    // Expect a specific character
-    fn expect(self: &mut JsonParser, expected: String) -> Result<(), String> {
-        let ch = self.advance();
-        if ch == expected {
-            return Ok(());
-        }
-        return Err("Unexpected character");
-    }
-
-    // Match a keyword
+    // Match a keyword: `true`, `false` or `null`, exactly
     fn match_keyword(self: &mut JsonParser, keyword: String) -> Result<(), String> {
         let start_pos = self.position;
         for i in 0..keyword.len() {
             if self.char_at(self.position) != keyword.substring(i, i + 1) {
                 self.position = start_pos;  // Reset on failure
-                return Err("Keyword mismatch");
+                return Err(self.error("Invalid literal, expected '" + keyword + "'"));
             }
             self.position = self.position + 1;
         }
@@ -503,14 +583,25 @@ impl JsonParser {
 // JSON serializer
 struct JsonSerializer {
     pretty: bool,
+    indent_width: i32,
     indent_level: i32,
 }
 
 impl JsonSerializer {
-    // Create a new serializer
+    // Create a new serializer; pretty output indents two spaces per level
     fn new(pretty: bool) -> JsonSerializer {
         return JsonSerializer {
             pretty: pretty,
+            indent_width: 2,
+            indent_level: 0,
+        };
+    }
+
+    // Create a pretty-printing serializer that indents `indent_width` spaces per level
+    fn with_indent(indent_width: i32) -> JsonSerializer {
+        return JsonSerializer {
+            pretty: true,
+            indent_width: indent_width,
             indent_level: 0,
         };
     }
@@ -557,9 +648,9 @@ impl JsonSerializer {
                 result = result + "\\r";
             } else if ch == "\t" {
                 result = result + "\\t";
-            } else if ch == "\b" {
+            } else if ch == "\u{8}" {
                 result = result + "\\b";
-            } else if ch == "\f" {
+            } else if ch == "\u{c}" {
                 result = result + "\\f";
             } else {
                 result = result + ch;
@@ -572,9 +663,10 @@ impl JsonSerializer {
 
     // Serialize array
     fn serialize_array(self: &mut JsonSerializer, arr: &Vec<JsonValue>) -> String {
-        let result = "[";
+        let mut result = "[";
+        self.indent_level = self.indent_level + 1;
 
-        let i = 0;
+        let mut i = 0;
         for value in arr {
             if i > 0 {
                 result = result + ",";
@@ -586,8 +678,14 @@ impl JsonSerializer {
             i = i + 1;
         }
 
+        // The closing bracket lines up with the line the array opened on;
+        // an empty array stays on one line
+        self.indent_level = self.indent_level - 1;
+        if i == 0 {
+            return "[]";
+        }
         if self.pretty {
-            result = result + "\n";
+            result = result + "\n" + self.indent();
         }
         result = result + "]";
         return result;
@@ -595,9 +693,10 @@ impl JsonSerializer {
 
     // Serialize object
     fn serialize_object(self: &mut JsonSerializer, obj: &HashMap<String, JsonValue>) -> String {
-        let result = "{";
+        let mut result = "{";
+        self.indent_level = self.indent_level + 1;
 
-        let i = 0;
+        let mut i = 0;
         let keys = obj.keys();
         for key in keys {
             if i > 0 {
@@ -613,29 +712,29 @@ impl JsonSerializer {
                 result = result + " ";
             }
 
-            // Value
-            match obj.get(key) {
-                Option::Some(value) => {
-                    result = result + self.serialize(value);
-                },
-                Option::None => {},
-            }
+            // Value; HashMap.get() returns it as is, as in JsonValue::get
+            result = result + self.serialize(obj.get(key));
 
             i = i + 1;
         }
 
+        // The closing brace lines up with the line the object opened on;
+        // an empty object stays on one line
+        self.indent_level = self.indent_level - 1;
+        if i == 0 {
+            return "{}";
+        }
         if self.pretty {
-            result = result + "\n";
+            result = result + "\n" + self.indent();
         }
         result = result + "}";
         return result;
     }
 
-    // Get indentation string
+    // Get indentation string for the current level
     fn indent(self: &JsonSerializer) -> String {
-        let spaces = "";
-        let i = 0;
-        for i in 0..(self.indent_level * 2) {
+        let mut spaces = "";
+        for _ in 0..(self.indent_level * self.indent_width) {
             spaces = spaces + " ";
         }
         return spaces;
@@ -675,12 +774,18 @@ fn stringify(value: &JsonValue) -> String {
     return serializer.serialize(value);
 }
 
-// Serialize JsonValue to pretty-printed JSON string
+// Serialize JsonValue to pretty-printed JSON string, indented two spaces per level
 fn stringify_pretty(value: &JsonValue) -> String {
     let mut serializer = JsonSerializer::new(true);
     return serializer.serialize(value);
 }
 
+// Serialize JsonValue to pretty-printed JSON string, indented `indent_width` spaces per level
+fn stringify_indented(value: &JsonValue, indent_width: i32) -> String {
+    let mut serializer = JsonSerializer::with_indent(indent_width);
+    return serializer.serialize(value);
+}
+
 // Create empty JSON object
 fn object() -> JsonValue {
     return JsonValue::Object(HashMap::new());
@@ -753,6 +858,23 @@ mod tests {
         assert!(JSON_DEFINITION.contains("fn stringify("));
     }
 
+    #[test]
+    fn test_json_definition_serializer_indents_nested_values() {
+        // Each level goes one indent deeper, and closing delimiters go back
+        assert!(JSON_DEFINITION.contains("self.indent_level = self.indent_level + 1;"));
+        assert!(JSON_DEFINITION.contains("self.indent_level = self.indent_level - 1;"));
+        assert!(JSON_DEFINITION.contains("fn stringify_indented(value: &JsonValue, indent_width: i32)"));
+    }
+
+    #[test]
+    fn test_json_definition_parser_is_strict() {
+        // Trailing commas and data after the value are rejected, with a position
+        assert!(JSON_DEFINITION.contains("Trailing comma in array"));
+        assert!(JSON_DEFINITION.contains("Trailing comma in object"));
+        assert!(JSON_DEFINITION.contains("Unexpected data after JSON value"));
+        assert!(JSON_DEFINITION.contains("\" at position \""));
+    }
+
     #[test]
     fn test_json_definition_contains_api() {
         assert!(JSON_DEFINITION.contains("fn parse(input: String)"));
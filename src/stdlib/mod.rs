@@ -8,6 +8,8 @@ pub mod http;
 pub mod db;
 pub mod auth;
 pub mod option;
+pub mod promise;
+pub mod events;
 pub mod result;
 pub mod iterator;
 pub mod vec;
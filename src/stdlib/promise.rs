@@ -0,0 +1,50 @@
+//! Standard library Promise<T> type
+//!
+//! Promise<T> is the native JavaScript Promise. Async functions return one,
+//! extern JS functions can declare one, and `await` unwraps it. The type checker
+//! knows the combinators below, so `Promise::all` over `Vec<Promise<T>>` resolves
+//! to `Vec<T>`; the JS emitter maps them onto the native Promise methods.
+
+/// Promise surface in Jounce syntax
+pub const PROMISE_DEFINITION: &str = r#"
+// Static functions (Promise.all, Promise.race, ... in JavaScript)
+//   Promise::all(promises: Vec<Promise<T>>) -> Promise<Vec<T>>
+//   Promise::race(promises: Vec<Promise<T>>) -> Promise<T>
+//   Promise::any(promises: Vec<Promise<T>>) -> Promise<T>
+//   Promise::allSettled(promises: Vec<Promise<T>>) -> Promise<Vec<any>>
+//   Promise::resolve(value: T) -> Promise<T>
+//   Promise::reject(error: E) -> Promise<any>
+//
+// Methods
+//   promise.then(f: fn(T) -> U) -> Promise<U>
+//   promise.catch(f: fn(E) -> T) -> Promise<T>
+//   promise.finally(f: fn()) -> Promise<T>
+//
+// Server function calls are awaited implicitly, except when passed straight
+// to a combinator: Promise::all(vec![a(), b()]) runs both calls concurrently.
+
+extern "js" {
+    // Resolves after `ms` milliseconds
+    fn sleep(ms: i32) -> Promise<()>;
+}
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_promise_definition_parses() {
+        let mut lexer = crate::lexer::Lexer::new(PROMISE_DEFINITION.to_string());
+        let mut parser = crate::parser::Parser::new(&mut lexer);
+        let program = parser.parse_program().expect("Promise definition should parse");
+        assert_eq!(program.statements.len(), 1);
+    }
+
+    #[test]
+    fn test_promise_definition_lists_combinators() {
+        for name in ["Promise::all", "Promise::race", ".then(", ".catch(", "fn sleep("] {
+            assert!(PROMISE_DEFINITION.contains(name), "missing {}", name);
+        }
+    }
+}
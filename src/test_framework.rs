@@ -143,6 +143,7 @@ impl TestRunner {
             if test.is_async {
                 // Wrap async tests in an async IIFE
                 code.push_str("await (async () => {\n");
+                code.push_str(&self.snapshot_context(test));
                 code.push_str("    const start_time = Date.now();\n");
                 code.push_str("    let result = 'passed';\n");
                 code.push_str("    try {\n");
@@ -163,6 +164,7 @@ impl TestRunner {
             } else {
                 // Regular sync tests
                 code.push_str("{\n");
+                code.push_str(&self.snapshot_context(test));
                 code.push_str("    const start_time = Date.now();\n");
                 code.push_str("    let result = 'passed';\n");
                 code.push_str("    try {\n");
@@ -198,6 +200,16 @@ impl TestRunner {
         code
     }
 
+    /// Tell `assert_snapshot` which test is running and where its snapshots live
+    fn snapshot_context(&self, test: &TestFunction) -> String {
+        let file = test.file_path.to_string_lossy();
+        format!(
+            "    __snapshot_context({}, {});\n",
+            serde_json::to_string(&test.name).unwrap_or_default(),
+            serde_json::to_string(file.as_ref()).unwrap_or_default()
+        )
+    }
+
     /// Generate test runner code (for backwards compatibility)
     pub fn generate_runner_code(&self) -> String {
         self.generate_runner_code_js()
@@ -219,6 +231,14 @@ impl TestRunner {
     }
 }
 
+/// Test-side JSX rendering (`render_to_string`) and `assert_snapshot!`
+/// (JavaScript); see runtime/snapshot.js for the snapshot file format
+pub fn generate_snapshot_library() -> String {
+    SNAPSHOT_LIBRARY.to_string()
+}
+
+const SNAPSHOT_LIBRARY: &str = include_str!("../runtime/snapshot.js");
+
 /// Built-in assertion functions (JavaScript)
 /// Note: Simplified version using only currently supported features
 pub fn generate_assertion_library() -> String {
@@ -341,4 +361,24 @@ mod tests {
         assert!(lib.contains("fn assert_eq"));
         assert!(lib.contains("fn assert_contains"));
     }
+
+    #[test]
+    fn test_runner_sets_snapshot_context() {
+        let suite = TestSuite {
+            tests: vec![TestFunction {
+                name: "test_card".to_string(),
+                file_path: PathBuf::from("tests/card_test.jnc"),
+                line: 0,
+                is_async: false,
+            }],
+            total_files: 1,
+        };
+        let code = TestRunner::new(suite).generate_runner_code_js();
+        let context = code.find("__snapshot_context(\"test_card\", \"tests/card_test.jnc\");").unwrap();
+        assert!(context < code.find("test_card();").unwrap());
+
+        let lib = generate_snapshot_library();
+        assert!(lib.contains("function assert_snapshot(actual, inline)"));
+        assert!(lib.contains("function render_to_string(node)"));
+    }
 }
@@ -1,35 +1,72 @@
 
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct Token {
     pub kind: TokenKind,
     pub lexeme: String,
     pub line: usize,
     pub column: usize,
+    /// Where the token was read from. For JSX text this includes the
+    /// whitespace trimmed off the lexeme.
+    #[serde(default)]
+    pub span: Span,
 }
 
 impl Token {
     pub fn new(kind: TokenKind, lexeme: String, line: usize, column: usize) -> Self {
-        Self { kind, lexeme, line, column }
+        Self { kind, lexeme, line, column, span: Span::default() }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// A range of UTF-8 byte offsets into the source, `end` exclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
 pub enum TokenKind {
     // Keywords
-    Let, Const, Fn, Struct, Enum, Impl, Trait, Component, Extern, Return, Server, Client, Async, Await, Use, True, False, If, Else, While, For, In, Match, Mut, As, Loop, Break, Continue, Style, Theme,
+    Let, Const, Fn, Struct, Enum, Impl, Trait, Component, Extern, Return, Server, Client, Async, Await, Use, True, False, If, Else, While, For, In, Match, Mut, As, Loop, Break, Continue, Style, Theme, Pub, Mod, Static, Type, SelfValue, SelfType,
 
     // Identifiers & Literals
     Identifier,
     Lifetime(String),  // Lifetime like 'a, 'b, 'static
     Integer(i64),
     Float(String), // Store as string to preserve precision during parsing
+    TypedNumber(String, NumberSuffix),  // 42u8, 3.0f32: digits without `_`, and the suffix
     String(String),
+    Char(char),
+    ByteString(Vec<u8>),  // b"..."
+    Byte(u8),             // b'x'
+    TemplateStart,                // ` opening a template string
+    TemplateChunk(String),        // Text of a template string, escapes resolved
+    TemplateInterpolationStart,   // ${ inside a template string
+    TemplateInterpolationEnd,     // } closing a ${
+    TemplateEnd,                  // ` closing a template string
     Bool(bool),
 
     // Symbols & Punctuation
     At,          // @
+    Pound,       // #, opening a #[...] attribute
     Assign,      // =
+    PlusAssign,  // +=
+    MinusAssign, // -=
+    StarAssign,  // *=
+    SlashAssign, // /=
+    PercentAssign, // %=
+    AmpAssign,   // &=
+    PipeAssign,  // |=
+    CaretAssign, // ^=
+    LeftShiftAssign,  // <<=
+    RightShiftAssign, // >>=
     Semicolon,   // ;
     Colon,       // :
     Comma,       // ,
@@ -43,6 +80,9 @@ pub enum TokenKind {
     Percent,     // %
     Bang,        // !
     Question,    // ?
+    QuestionDot, // ?. (optional chaining)
+    QuestionQuestion, // ?? (default for a missing value)
+    QuestionQuestionAssign, // ??=
     Ampersand,   // &
     AmpAmp,      // && (logical AND)
     Pipe,        // |
@@ -87,10 +127,168 @@ pub enum TokenKind {
     CssMedia,              // @media
     CssKeyframes,          // @keyframes (Sprint 2 Task 2.6)
     CssContainer,          // @container (Phase 8 Sprint 1 Task 1.4)
+    CssSupports,           // @supports
+    CssFontFace,           // @font-face
+    CssImport,             // @import
+    CssPage,               // @page
+    CssAtRule(String),     // Any other at-rule, like @layer: skipped with a warning
+
+    // Comments, only emitted when the lexer is asked to keep them
+    LineComment(String),   // // text
+    BlockComment(String),  // /* text */
+    // Doc comments are always emitted: `///` documents the next item and
+    // `//!` the enclosing one. One token per line, text after the marker.
+    DocComment { inner: bool, text: String },
 
     // Meta
     Eof,
     Illegal(char),
+    InvalidNumber(String),  // A malformed number literal like `0b102`, with what's wrong
+    InvalidChar(String),  // A malformed char literal like `''` or `'ab'`, with what's wrong
+    InvalidString(String),  // An unterminated string literal, with what's wrong
+    InvalidIdentifier(String),  // A name with a zero-width character in it, with what's wrong
+}
+
+/// The coarse kind of a token, for syntax highlighting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum HighlightClass {
+    Keyword,
+    Identifier,
+    String,
+    Number,
+    Comment,
+    Punctuation,
+    /// `<`, `>`, `/>` and the name of a JSX tag
+    JsxTag,
+    JsxText,
+    CssSelector,
+    CssProperty,
+    CssValue,
+    /// Characters the lexer doesn't know and malformed literals
+    Error,
+}
+
+/// The type written after a numeric literal, as in `42u8` or `3.0f32`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum NumberSuffix {
+    I8, I16, I32, I64, U8, U16, U32, U64, F32, F64,
+}
+
+impl TokenKind {
+    /// The operator a compound assignment applies: `Plus` for `+=`
+    pub fn compound_operator(&self) -> Option<(TokenKind, &'static str)> {
+        match self {
+            TokenKind::PlusAssign => Some((TokenKind::Plus, "+")),
+            TokenKind::MinusAssign => Some((TokenKind::Minus, "-")),
+            TokenKind::StarAssign => Some((TokenKind::Star, "*")),
+            TokenKind::SlashAssign => Some((TokenKind::Slash, "/")),
+            TokenKind::PercentAssign => Some((TokenKind::Percent, "%")),
+            TokenKind::AmpAssign => Some((TokenKind::Ampersand, "&")),
+            TokenKind::PipeAssign => Some((TokenKind::Pipe, "|")),
+            TokenKind::CaretAssign => Some((TokenKind::Caret, "^")),
+            TokenKind::LeftShiftAssign => Some((TokenKind::LeftShift, "<<")),
+            TokenKind::RightShiftAssign => Some((TokenKind::RightShift, ">>")),
+            TokenKind::QuestionQuestionAssign => Some((TokenKind::QuestionQuestion, "??")),
+            _ => None,
+        }
+    }
+
+    /// How to highlight the token on its own. A `<` or a name can't tell
+    /// whether it's part of a JSX tag; `highlight::highlight` works that out.
+    /// `Eof` has no text and counts as punctuation.
+    pub fn highlight_class(&self) -> HighlightClass {
+        match self {
+            TokenKind::Let | TokenKind::Const | TokenKind::Fn | TokenKind::Struct | TokenKind::Enum | TokenKind::Impl
+            | TokenKind::Trait | TokenKind::Component | TokenKind::Extern | TokenKind::Return | TokenKind::Server
+            | TokenKind::Client | TokenKind::Async | TokenKind::Await | TokenKind::Use | TokenKind::True | TokenKind::False
+            | TokenKind::If | TokenKind::Else | TokenKind::While | TokenKind::For | TokenKind::In | TokenKind::Match
+            | TokenKind::Mut | TokenKind::As | TokenKind::Loop | TokenKind::Break | TokenKind::Continue | TokenKind::Style
+            | TokenKind::Theme | TokenKind::Pub | TokenKind::Mod | TokenKind::Static | TokenKind::Type
+            | TokenKind::SelfValue | TokenKind::SelfType | TokenKind::Bool(_) | TokenKind::CssMacro
+            | TokenKind::CssMedia | TokenKind::CssKeyframes | TokenKind::CssContainer | TokenKind::CssSupports
+            | TokenKind::CssFontFace | TokenKind::CssImport | TokenKind::CssPage | TokenKind::CssAtRule(_) => HighlightClass::Keyword,
+            TokenKind::Identifier | TokenKind::Lifetime(_) => HighlightClass::Identifier,
+            TokenKind::String(_) | TokenKind::Char(_) | TokenKind::ByteString(_) | TokenKind::Byte(_)
+            | TokenKind::TemplateStart | TokenKind::TemplateChunk(_) | TokenKind::TemplateEnd => HighlightClass::String,
+            TokenKind::Integer(_) | TokenKind::Float(_) | TokenKind::TypedNumber(..) => HighlightClass::Number,
+            TokenKind::LineComment(_) | TokenKind::BlockComment(_) | TokenKind::DocComment { .. } => HighlightClass::Comment,
+            TokenKind::JsxSelfClose => HighlightClass::JsxTag,
+            TokenKind::JsxText(_) => HighlightClass::JsxText,
+            TokenKind::CssSelector(_) => HighlightClass::CssSelector,
+            TokenKind::CssProperty(_) => HighlightClass::CssProperty,
+            TokenKind::CssValue(_) => HighlightClass::CssValue,
+            TokenKind::Illegal(_) | TokenKind::InvalidNumber(_) | TokenKind::InvalidChar(_) | TokenKind::InvalidString(_)
+            | TokenKind::InvalidIdentifier(_) => HighlightClass::Error,
+            _ => HighlightClass::Punctuation,
+        }
+    }
+}
+
+impl HighlightClass {
+    /// A name for the class, e.g. `jsx-tag`, for use as a CSS class
+    pub fn name(self) -> &'static str {
+        match self {
+            HighlightClass::Keyword => "keyword",
+            HighlightClass::Identifier => "identifier",
+            HighlightClass::String => "string",
+            HighlightClass::Number => "number",
+            HighlightClass::Comment => "comment",
+            HighlightClass::Punctuation => "punctuation",
+            HighlightClass::JsxTag => "jsx-tag",
+            HighlightClass::JsxText => "jsx-text",
+            HighlightClass::CssSelector => "css-selector",
+            HighlightClass::CssProperty => "css-property",
+            HighlightClass::CssValue => "css-value",
+            HighlightClass::Error => "error",
+        }
+    }
+}
+
+impl NumberSuffix {
+    pub const ALL: [NumberSuffix; 10] = [
+        NumberSuffix::I8, NumberSuffix::I16, NumberSuffix::I32, NumberSuffix::I64,
+        NumberSuffix::U8, NumberSuffix::U16, NumberSuffix::U32, NumberSuffix::U64,
+        NumberSuffix::F32, NumberSuffix::F64,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            NumberSuffix::I8 => "i8",
+            NumberSuffix::I16 => "i16",
+            NumberSuffix::I32 => "i32",
+            NumberSuffix::I64 => "i64",
+            NumberSuffix::U8 => "u8",
+            NumberSuffix::U16 => "u16",
+            NumberSuffix::U32 => "u32",
+            NumberSuffix::U64 => "u64",
+            NumberSuffix::F32 => "f32",
+            NumberSuffix::F64 => "f64",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<NumberSuffix> {
+        Self::ALL.into_iter().find(|suffix| suffix.name() == name)
+    }
+
+    pub fn is_float(self) -> bool {
+        matches!(self, NumberSuffix::F32 | NumberSuffix::F64)
+    }
+
+    /// The largest literal an integer suffix allows; literals have no sign,
+    /// so this is the top of the type's range
+    pub fn max_value(self) -> Option<u64> {
+        match self {
+            NumberSuffix::I8 => Some(i8::MAX as u64),
+            NumberSuffix::I16 => Some(i16::MAX as u64),
+            NumberSuffix::I32 => Some(i32::MAX as u64),
+            NumberSuffix::I64 => Some(i64::MAX as u64),
+            NumberSuffix::U8 => Some(u8::MAX as u64),
+            NumberSuffix::U16 => Some(u16::MAX as u64),
+            NumberSuffix::U32 => Some(u32::MAX as u64),
+            NumberSuffix::U64 => Some(u64::MAX),
+            NumberSuffix::F32 | NumberSuffix::F64 => None,
+        }
+    }
 }
 
 lazy_static::lazy_static! {
@@ -126,6 +324,12 @@ lazy_static::lazy_static! {
         map.insert("continue", TokenKind::Continue);
         map.insert("style", TokenKind::Style);
         map.insert("theme", TokenKind::Theme);
+        map.insert("pub", TokenKind::Pub);
+        map.insert("mod", TokenKind::Mod);
+        map.insert("static", TokenKind::Static);
+        map.insert("type", TokenKind::Type);
+        map.insert("self", TokenKind::SelfValue);
+        map.insert("Self", TokenKind::SelfType);
         map
     };
 }
\ No newline at end of file
@@ -1,8 +1,9 @@
 // Type Checker with Hindley-Milner Type Inference
 
-use crate::ast::{Expression, Statement, InfixExpression, PrefixExpression, TypeExpression, TraitDefinition, ImplBlock};
+use crate::ast::{Expression, FunctionCall, BIND_VALUE, DEFAULT_SLOT, JSX_KEY, JSX_REF, Identifier, Statement, InfixExpression, PrefixExpression, TypeExpression, TraitDefinition, ImplBlock, JsxAttribute, JsxChild, JsxElement, Position, UseTree};
+use crate::diagnostics::{did_you_mean, suggest_similar, Label, SourceLocation};
 use crate::errors::CompileError;
-use crate::types::{Substitution, Type, TypeEnv};
+use crate::types::{Reactivity, Substitution, Type, TypeEnv};
 use std::collections::{HashSet, HashMap};
 
 // Information about a trait's methods
@@ -19,12 +20,59 @@ pub struct FunctionSignature {
     pub return_type: Type,
 }
 
+/// Accessors a `JsValue` supports; anything else is a direct field read
+const JS_VALUE_METHODS: &[&str] = &[
+    "get", "index", "call", "is_null", "is_undefined", "as_string", "as_f64", "as_bool", "to_json",
+];
+
 pub struct TypeChecker {
     env: TypeEnv,
     constraints: Vec<(Type, Type)>,
     traits: HashMap<String, TraitInfo>,  // Track trait definitions
     impls: HashMap<String, Vec<String>>,  // Track which traits are implemented for each type
     methods: HashMap<String, HashMap<String, FunctionSignature>>,  // type_name -> (method_name -> signature)
+    errors: Vec<CompileError>,  // Errors recorded while recovering, reported by check_program
+    function_params: HashMap<String, Vec<DeclaredName>>,  // function name -> parameter declarations
+    component_props: HashMap<String, Vec<DeclaredName>>,  // component name -> prop declarations
+    component_slots: HashMap<String, Vec<DeclaredSlot>>,  // component name -> slot declarations, for every component
+    item_positions: HashMap<String, Position>,  // Top-level function and component names -> where they're declared
+    expected_return: Option<ExpectedReturn>,  // Declared return type of the function being checked
+    server_functions: HashSet<String>,  // @server functions, whose calls are awaited implicitly
+    item_names: HashSet<String>,  // Top-level and imported names, which may be used before they are checked
+    glob_imports: bool,  // A `use` imports names that aren't known here
+    reported_names: HashSet<Position>,  // Unresolved names and unknown props already reported
+}
+
+// A parameter or prop declaration, kept so errors can point back at it
+#[derive(Debug, Clone)]
+struct DeclaredName {
+    name: String,
+    ty: Type,
+    position: Position,
+}
+
+// A component's `slot name: Element` parameter
+#[derive(Debug, Clone)]
+struct DeclaredSlot {
+    name: String,
+    optional: bool,
+    position: Position,
+}
+
+/// Lowercase globals JS provides, which a program can use without declaring
+const JS_GLOBALS: &[&str] = &[
+    "window", "document", "globalThis", "navigator", "location", "history", "localStorage",
+    "sessionStorage", "performance", "crypto", "fetch", "setTimeout", "clearTimeout", "setInterval",
+    "clearInterval", "requestAnimationFrame", "cancelAnimationFrame", "queueMicrotask", "structuredClone",
+    "alert", "confirm", "prompt", "parseInt", "parseFloat", "isNaN", "isFinite", "encodeURIComponent",
+    "decodeURIComponent", "undefined", "process", "require",
+];
+
+#[derive(Debug, Clone)]
+struct ExpectedReturn {
+    function: String,
+    ty: Type,
+    position: Position,
 }
 
 impl TypeChecker {
@@ -34,6 +82,9 @@ impl TypeChecker {
         // Add built-in types and functions
         env.bind("console".to_string(), Type::Any);
         env.bind("Math".to_string(), Type::Any);
+        env.bind("sleep".to_string(), Type::function(vec![Type::Int], Type::promise(Type::Void)));
+        env.bind("on_cleanup".to_string(), Type::function(vec![Type::function(vec![], Type::Any)], Type::Void));
+        env.bind("unsafe_raw".to_string(), Type::function(vec![Type::String], Type::Any));
 
         TypeChecker {
             env,
@@ -41,9 +92,73 @@ impl TypeChecker {
             traits: HashMap::new(),
             impls: HashMap::new(),
             methods: HashMap::new(),
+            errors: Vec::new(),
+            function_params: HashMap::new(),
+            component_props: HashMap::new(),
+            component_slots: HashMap::new(),
+            item_positions: HashMap::new(),
+            expected_return: None,
+            server_functions: HashSet::new(),
+            item_names: HashSet::new(),
+            glob_imports: false,
+            reported_names: HashSet::new(),
+        }
+    }
+
+    /// Secondary label at a declaration; the file is filled in when displayed
+    fn label(position: Position, message: String) -> Label {
+        Label {
+            location: SourceLocation {
+                file: String::new(),
+                line: position.line,
+                column: position.column,
+                length: 1,
+            },
+            message,
+        }
+    }
+
+    /// Whether a mismatch involving this type can be reported with confidence.
+    /// Named types aren't modelled precisely yet (`string`, `JSX`, user structs),
+    /// so they are only compared against themselves.
+    fn is_precise(ty: &Type) -> bool {
+        match ty {
+            Type::Any | Type::Named(_) | Type::Generic(_) | Type::Var(_) | Type::Component(_) | Type::Union(_) => false,
+            Type::Array(inner) | Type::Option(inner) | Type::Promise(inner) | Type::EventBus(inner) | Type::Context(inner) => Self::is_precise(inner),
+            Type::Tuple(types) => types.iter().all(Self::is_precise),
+            Type::Function { params, return_type } => {
+                params.iter().all(Self::is_precise) && Self::is_precise(return_type)
+            }
+            _ => true,
+        }
+    }
+
+    /// Parameter types a @memo cache can't key on
+    fn uncacheable_kind(type_expr: &TypeExpression) -> Option<&'static str> {
+        match type_expr {
+            TypeExpression::Function(_, _) => Some("closure"),
+            TypeExpression::MutableReference(_) => Some("mutable reference"),
+            TypeExpression::Reference(inner) => Self::uncacheable_kind(inner),
+            _ => None,
         }
     }
 
+    /// Check a list of statements, recording errors and carrying on with
+    /// `Type::Error` so every independent mistake in a body is reported
+    fn check_statements_recovering(&mut self, statements: &[Statement]) -> Type {
+        let mut last_type = Type::Void;
+        for stmt in statements {
+            last_type = match self.check_statement(stmt) {
+                Ok(ty) => ty,
+                Err(e) => {
+                    self.errors.push(e);
+                    Type::Error
+                }
+            };
+        }
+        last_type
+    }
+
     /// Convert TypeExpression from AST to Type
     fn type_expr_to_type(&self, type_expr: &TypeExpression) -> Type {
         match type_expr {
@@ -53,6 +168,7 @@ impl TypeChecker {
                     "f32" | "f64" => Type::Float,
                     "bool" => Type::Bool,
                     "str" | "String" => Type::String,
+                    "JsValue" => Type::JsValue,
                     _ => {
                         // Check if this is a generic type parameter in scope
                         // If so, return Type::Any (type erasure)
@@ -74,9 +190,20 @@ impl TypeChecker {
                     "Option" if args.len() == 1 => {
                         Type::Option(Box::new(self.type_expr_to_type(&args[0])))
                     }
+                    "Promise" if args.len() == 1 => {
+                        Type::promise(self.type_expr_to_type(&args[0]))
+                    }
+                    "EventBus" if args.len() == 1 => {
+                        Type::event_bus(self.type_expr_to_type(&args[0]))
+                    }
+                    "Context" if args.len() == 1 => {
+                        Type::context(self.type_expr_to_type(&args[0]))
+                    }
                     _ => Type::Named(ident.value.clone()),
                 }
             }
+            // The unit type () is Void, like the () literal
+            TypeExpression::Tuple(types) if types.is_empty() => Type::Void,
             TypeExpression::Tuple(types) => {
                 // Convert tuple type expression to Type::Tuple
                 let converted_types: Vec<Type> = types.iter()
@@ -115,21 +242,162 @@ impl TypeChecker {
     }
 
     /// Type check a program (list of statements)
+    ///
+    /// Errors are accumulated across the whole program; a single error is
+    /// returned as-is and several are wrapped in `CompileError::Multiple`.
     pub fn check_program(&mut self, statements: &[Statement]) -> Result<(), CompileError> {
+        // Server calls are recognised before their definitions are checked
         for stmt in statements {
-            self.check_statement(stmt)?;
+            if let Statement::Function(func_def) = stmt {
+                if func_def.is_server {
+                    self.server_functions.insert(func_def.name.value.clone());
+                }
+            }
+            self.collect_item_names(stmt);
         }
-        Ok(())
+
+        self.check_statements_recovering(statements);
+
+        let mut errors = std::mem::take(&mut self.errors);
+        match errors.len() {
+            0 => Ok(()),
+            1 => Err(errors.remove(0)),
+            _ => Err(CompileError::Multiple(errors)),
+        }
+    }
+
+    /// Record the names a top-level statement declares or imports
+    fn collect_item_names(&mut self, stmt: &Statement) {
+        let names: Vec<&Identifier> = match stmt {
+            Statement::Function(func) => vec![&func.name],
+            Statement::Component(comp) => vec![&comp.name],
+            Statement::Struct(def) => vec![&def.name],
+            Statement::Enum(def) => vec![&def.name],
+            Statement::Trait(def) => vec![&def.name],
+            Statement::Const(decl) => vec![&decl.name],
+            Statement::Style(block) => vec![&block.name],
+            Statement::Theme(block) => vec![&block.name],
+            Statement::ExternBlock(block) => block.functions.iter().map(|f| &f.name).collect(),
+            Statement::Let(let_stmt) => {
+                self.item_names.extend(let_stmt.pattern.bound_identifiers().into_iter().map(|ident| ident.value));
+                vec![]
+            }
+            Statement::Use(use_stmt) => {
+                if use_stmt.imports.is_empty() {
+                    self.glob_imports = true;
+                }
+                for tree in &use_stmt.imports {
+                    self.collect_imported_names(tree);
+                }
+                vec![]
+            }
+            _ => vec![],
+        };
+        if let Statement::Function(func) = stmt {
+            self.item_positions.insert(func.name.value.clone(), func.name.position);
+        }
+        if let Statement::Component(comp) = stmt {
+            self.item_positions.insert(comp.name.value.clone(), comp.name.position);
+            let slots = comp.parameters
+                .iter()
+                .filter(|param| param.is_slot)
+                .map(|param| DeclaredSlot {
+                    name: param.name.value.clone(),
+                    optional: param.is_optional_slot(),
+                    position: param.position,
+                })
+                .collect();
+            self.component_slots.insert(comp.name.value.clone(), slots);
+        }
+        self.item_names.extend(names.into_iter().map(|ident| ident.value.clone()));
+    }
+
+    fn collect_imported_names(&mut self, tree: &UseTree) {
+        match tree {
+            UseTree::Name { name, alias } => {
+                self.item_names.insert(alias.as_ref().unwrap_or(name).value.clone());
+            }
+            UseTree::Glob => self.glob_imports = true,
+            UseTree::Nested { items, .. } => {
+                for item in items {
+                    self.collect_imported_names(item);
+                }
+            }
+        }
+    }
+
+    /// Names from JS and forward references can't be resolved here, so an
+    /// unknown name is only reported when it looks like a typo of one in scope.
+    /// Capitalized names (`Date`, `JSON`) and the browser's own globals are
+    /// taken to be JS, whatever is in scope.
+    fn check_unresolved_name(&mut self, ident: &Identifier) {
+        if self.glob_imports
+            || ident.position == Position::default()
+            || ident.value.starts_with(|ch: char| ch.is_ascii_uppercase())
+            || JS_GLOBALS.contains(&ident.value.as_str())
+            || self.item_names.contains(&ident.value)
+            || self.reported_names.contains(&ident.position)
+        {
+            return;
+        }
+        let Some(similar) = suggest_similar(&ident.value, self.env.visible_names()) else {
+            return;
+        };
+        let location = SourceLocation::from_identifier(ident);
+        let fix = did_you_mean(location.clone(), similar);
+        self.reported_names.insert(ident.position);
+        self.errors.push(
+            CompileError::WithLocation {
+                message: format!("Cannot find '{}' in this scope", ident.value),
+                location,
+                suggestion: None,
+                labels: Vec::new(),
+            }
+            .with_fix(Some(fix)),
+        );
     }
 
     /// Infer the type of a statement
     pub fn check_statement(&mut self, stmt: &Statement) -> Result<Type, CompileError> {
         match stmt {
             Statement::Let(let_stmt) => {
-                let value_type = self.infer_expression(&let_stmt.value)?;
+                let mut value_type = self.infer_expression(&let_stmt.value)?;
+                // The annotation fills in a payload the value leaves open:
+                // let bus: EventBus<String> = EventBus::new();
+                if let (Type::EventBus(open), Some(annotation)) = (&value_type, &let_stmt.type_annotation) {
+                    if **open == Type::Any {
+                        if let declared @ Type::EventBus(_) = self.type_expr_to_type(annotation) {
+                            value_type = declared;
+                        }
+                    }
+                }
+                // A JsValue only becomes a typed value through an accessor
+                if let (Type::JsValue, Some(annotation)) = (&value_type, &let_stmt.type_annotation) {
+                    let declared = self.type_expr_to_type(annotation);
+                    if declared != Type::JsValue && declared != Type::Any {
+                        let accessor = match declared {
+                            Type::Float | Type::Int => ".as_f64()",
+                            Type::String => ".as_string()",
+                            Type::Bool => ".as_bool()",
+                            _ => ".to_json()",
+                        };
+                        return Err(CompileError::Generic(format!(
+                            "A JsValue is not a {}; convert it explicitly with {}",
+                            declared, accessor
+                        )));
+                    }
+                }
                 // Register all identifiers from the pattern
+                let reactivity = match &let_stmt.value {
+                    Expression::Signal(_) => Some(Reactivity::Signal),
+                    Expression::Computed(_) => Some(Reactivity::Computed),
+                    _ => None,
+                };
                 for ident in let_stmt.pattern.bound_identifiers() {
                     self.env.bind(ident.value.clone(), value_type.clone());
+                    if let Some(reactivity) = reactivity {
+                        self.env.mark_reactivity(&ident.value, reactivity);
+                    }
                 }
                 Ok(value_type)
             }
@@ -186,37 +454,110 @@ impl TypeChecker {
 
                 // Bind parameters to scope with their actual types
                 let mut param_types = Vec::new();
+                let mut declared = Vec::new();
                 for param in &func_def.parameters {
                     let param_type = self.type_expr_to_type(&param.type_annotation);
                     self.env.bind(param.name.value.clone(), param_type.clone());
+                    declared.push(DeclaredName {
+                        name: param.name.value.clone(),
+                        ty: param_type.clone(),
+                        position: param.position,
+                    });
                     param_types.push(param_type);
                 }
-
-                // Check body
-                let mut body_type = Type::Void;
-                for stmt in &func_def.body.statements {
-                    body_type = self.check_statement(stmt)?;
+                self.function_params.insert(func_def.name.value.clone(), declared);
+
+                // @memo results are cached by argument, so every parameter must be usable as a key
+                if func_def.memo.is_some() {
+                    for param in &func_def.parameters {
+                        if let Some(kind) = Self::uncacheable_kind(&param.type_annotation) {
+                            self.errors.push(CompileError::TypeError {
+                                message: format!(
+                                    "@memo function '{}' can't cache calls: parameter '{}' is a {}",
+                                    func_def.name.value, param.name.value, kind
+                                ),
+                                labels: vec![Self::label(
+                                    param.position,
+                                    format!("{}s can't be compared as cache keys", kind),
+                                )],
+                            });
+                        }
+                    }
                 }
 
+                // Explicit `return`s are checked against the declared return type;
+                // an async function returns the value its promise resolves to
+                let expected_return = func_def.return_type.as_ref().map(|ty| {
+                    let ty = self.type_expr_to_type(ty);
+                    ExpectedReturn {
+                        function: func_def.name.value.clone(),
+                        ty: if func_def.is_async { Self::settled(ty) } else { ty },
+                        position: func_def.return_type_position,
+                    }
+                });
+                let declared_return = expected_return.as_ref().map(|expected| expected.ty.clone());
+                let outer_return = std::mem::replace(&mut self.expected_return, expected_return);
+
+                // Check body, collecting every independent error
+                let body_type = self.check_statements_recovering(&func_def.body.statements);
+
+                self.expected_return = outer_return;
                 self.env.pop_scope();
 
-                let func_type = Type::function(param_types, body_type);
+                let return_type = if func_def.is_async {
+                    Type::promise(declared_return.unwrap_or(body_type))
+                } else {
+                    body_type
+                };
+                let func_type = Type::function(param_types, return_type);
                 self.env.bind(func_def.name.value.clone(), func_type.clone());
                 Ok(func_type)
             }
 
+            Statement::ExternBlock(extern_block) => {
+                // Host functions are typed by their declarations
+                for func in &extern_block.functions {
+                    let mut declared = Vec::new();
+                    for param in &func.parameters {
+                        declared.push(DeclaredName {
+                            name: param.name.value.clone(),
+                            ty: self.type_expr_to_type(&param.type_annotation),
+                            position: param.position,
+                        });
+                    }
+                    let return_type = func.return_type.as_ref()
+                        .map(|ty| self.type_expr_to_type(ty))
+                        .unwrap_or(Type::Void);
+                    let param_types = declared.iter().map(|param| param.ty.clone()).collect();
+                    self.env.bind(func.name.value.clone(), Type::function(param_types, return_type));
+                    self.function_params.insert(func.name.value.clone(), declared);
+                }
+                Ok(Type::Void)
+            }
+
             Statement::Component(comp_def) => {
                 self.env.push_scope();
 
-                // Bind parameters
+                // Bind parameters, remembering declared prop types for JSX call sites
+                let mut props = Vec::new();
                 for param in &comp_def.parameters {
                     self.env.bind(param.name.value.clone(), Type::Any);
+                    self.env.mark_reactivity(&param.name.value, Self::declared_reactivity(&param.type_annotation));
+                    if param.is_slot {
+                        continue;
+                    }
+                    props.push(DeclaredName {
+                        name: param.name.value.clone(),
+                        ty: self.type_expr_to_type(&param.type_annotation),
+                        position: param.position,
+                    });
                 }
+                self.component_props.insert(comp_def.name.value.clone(), props);
 
                 // Check body statements
-                for stmt in &comp_def.body.statements {
-                    self.check_statement(stmt)?;
-                }
+                let outer_return = self.expected_return.take();
+                self.check_statements_recovering(&comp_def.body.statements);
+                self.expected_return = outer_return;
 
                 self.env.pop_scope();
 
@@ -226,14 +567,34 @@ impl TypeChecker {
             }
 
             Statement::Return(ret_stmt) => {
-                self.infer_expression(&ret_stmt.value)
+                let value_type = self.infer_expression(&ret_stmt.value)?;
+
+                if let Some(expected) = self.expected_return.clone() {
+                    if Self::is_precise(&expected.ty)
+                        && Self::is_precise(&value_type)
+                        && self.unify(&value_type, &expected.ty).is_err()
+                    {
+                        return Err(CompileError::TypeError {
+                            message: format!(
+                                "Mismatched return type in '{}': expected {}, got {}",
+                                expected.function, expected.ty, value_type
+                            ),
+                            labels: vec![Self::label(
+                                expected.position,
+                                format!("expected {} because of this return type", expected.ty),
+                            )],
+                        });
+                    }
+                }
+
+                Ok(value_type)
             }
 
             Statement::Expression(expr) => self.infer_expression(expr),
 
             Statement::If(if_stmt) => {
                 let cond_type = self.infer_expression(&if_stmt.condition)?;
-                if cond_type != Type::Bool {
+                if !matches!(cond_type, Type::Bool | Type::Error) {
                     return Err(CompileError::Generic(format!(
                         "If condition must be bool, got {}",
                         cond_type
@@ -262,7 +623,7 @@ impl TypeChecker {
 
             Statement::While(while_stmt) => {
                 let cond_type = self.infer_expression(&while_stmt.condition)?;
-                if cond_type != Type::Bool {
+                if !matches!(cond_type, Type::Bool | Type::Error) {
                     return Err(CompileError::Generic(format!(
                         "While condition must be bool, got {}",
                         cond_type
@@ -284,7 +645,7 @@ impl TypeChecker {
 
                 // Check condition
                 let cond_type = self.infer_expression(&for_stmt.condition)?;
-                if cond_type != Type::Bool {
+                if !matches!(cond_type, Type::Bool | Type::Error) {
                     return Err(CompileError::Generic(format!(
                         "For loop condition must be bool, got {}",
                         cond_type
@@ -313,7 +674,7 @@ impl TypeChecker {
                     Type::Array(_) => {
                         // Valid array iterator
                     }
-                    Type::Any => {
+                    Type::Any | Type::Error => {
                         // Accept Any type (may be a range or other iterable)
                     }
                     _ => {
@@ -324,14 +685,26 @@ impl TypeChecker {
                     }
                 }
 
-                // Check body statements
+                // Check body statements with the loop variable bound
+                let element_type = match iterator_type {
+                    Type::Array(inner) => *inner,
+                    _ => Type::Any,
+                };
+                self.env.push_scope();
+                self.env.bind(for_in_stmt.variable.value.clone(), element_type);
                 for stmt in &for_in_stmt.body.statements {
                     self.check_statement(stmt)?;
                 }
+                self.env.pop_scope();
 
                 Ok(Type::Void)
             }
 
+            Statement::Const(const_decl) => {
+                self.env.bind(const_decl.name.value.clone(), Type::Any);
+                Ok(Type::Void)
+            }
+
             Statement::Trait(trait_def) => {
                 self.check_trait_definition(trait_def)?;
                 Ok(Type::Void)
@@ -352,6 +725,13 @@ impl TypeChecker {
             Expression::IntegerLiteral(_) => Ok(Type::Int),
             Expression::FloatLiteral(_) => Ok(Type::Float),
             Expression::StringLiteral(_) => Ok(Type::String),
+            // Any value can be spliced in; it is converted to a string
+            Expression::Template(template) => {
+                for expr in template.expressions() {
+                    self.infer_expression(expr)?;
+                }
+                Ok(Type::String)
+            }
             Expression::BoolLiteral(_) => Ok(Type::Bool),
             Expression::UnitLiteral => Ok(Type::Void),  // Unit type () maps to Void
 
@@ -374,6 +754,7 @@ impl TypeChecker {
                     // If not in environment, assume it's a forward reference or async function
                     // Return Type::Any to allow compilation to proceed
                     // TODO: Implement proper two-pass type checking to handle forward references
+                    self.check_unresolved_name(ident);
                     Ok(Type::Any)
                 }
             }
@@ -392,6 +773,41 @@ impl TypeChecker {
             }
 
             Expression::FunctionCall(call) => {
+                // Promise combinators and chaining infer the resolved type
+                if let Expression::Identifier(ident) = call.function.as_ref() {
+                    if let Some(method) = ident.value.strip_prefix("Promise::") {
+                        return self.check_promise_static(method, call);
+                    }
+                    if let Some(method) = ident.value.strip_prefix("EventBus::") {
+                        return self.check_event_bus_static(method, call);
+                    }
+                    if let Some(method) = ident.value.strip_prefix("JsValue::") {
+                        return self.check_js_value_static(method, call);
+                    }
+                    if matches!(ident.value.as_str(), "create_context" | "use_context")
+                        && !self.function_params.contains_key(&ident.value)
+                    {
+                        return self.check_context_call(&ident.value, call);
+                    }
+                }
+                if let Expression::FieldAccess(access) = call.function.as_ref() {
+                    if matches!(access.field.value.as_str(), "then" | "catch" | "finally") {
+                        if let Type::Promise(inner) = self.infer_expression(&access.object)? {
+                            return self.check_promise_method(*inner, &access.field.value, call);
+                        }
+                    }
+                    if matches!(access.field.value.as_str(), "emit" | "subscribe") {
+                        if let Type::EventBus(payload) = self.infer_expression(&access.object)? {
+                            return self.check_event_bus_method(*payload, &access.field.value, call);
+                        }
+                    }
+                    if JS_VALUE_METHODS.contains(&access.field.value.as_str())
+                        && self.infer_expression(&access.object)? == Type::JsValue
+                    {
+                        return self.check_js_value_method(&access.field.value, call);
+                    }
+                }
+
                 // Infer function type
                 let func_type = self.infer_expression(&call.function)?;
 
@@ -407,26 +823,69 @@ impl TypeChecker {
                             )));
                         }
 
-                        // Check argument types
+                        // Parameter declarations, when the callee is a known function
+                        let declared = match call.function.as_ref() {
+                            Expression::Identifier(ident) => self.function_params.get(&ident.value).cloned(),
+                            _ => None,
+                        };
+
+                        // Check argument types, reporting every mismatched argument
                         for (i, (arg, expected_type)) in call.arguments.iter().zip(params.iter()).enumerate() {
-                            let arg_type = self.infer_expression(arg)?;
+                            let arg_type = match self.infer_expression(arg) {
+                                Ok(ty) => ty,
+                                Err(e) => {
+                                    self.errors.push(e);
+                                    continue;
+                                }
+                            };
 
                             // Try to unify the argument type with expected type
                             if let Err(e) = self.unify(&arg_type, expected_type) {
-                                return Err(CompileError::Generic(format!(
+                                let message = format!(
                                     "Argument {} type mismatch: expected {}, got {}. {}",
                                     i + 1, expected_type, arg_type, e
-                                )));
+                                );
+                                let error = match declared.as_ref().and_then(|params| params.get(i)) {
+                                    Some(param) => CompileError::TypeError {
+                                        message,
+                                        labels: vec![Self::label(
+                                            param.position,
+                                            format!("parameter '{}' declared here as {}", param.name, param.ty),
+                                        )],
+                                    },
+                                    None => CompileError::Generic(message),
+                                };
+                                self.errors.push(error);
                             }
                         }
 
+                        // Server calls are awaited implicitly
+                        if self.is_server_call(expr) {
+                            return Ok(Self::settled((**return_type).clone()));
+                        }
+
                         // Return the return type
                         Ok((**return_type).clone())
                     }
-                    Type::Any => {
+                    Type::Any | Type::Error => {
                         // If function type is Any (e.g., from external functions), skip checking
                         Ok(Type::Any)
                     }
+                    Type::Component(_) => {
+                        // Components are rendered with JSX, so <Card/> and Card(...) can't diverge
+                        let Expression::Identifier(ident) = call.function.as_ref() else {
+                            return Err(CompileError::Generic(format!("Cannot call non-function type: {}", func_type)));
+                        };
+                        Err(CompileError::WithLocation {
+                            message: format!("'{}' is a component and can't be called like a function", ident.value),
+                            location: SourceLocation::from_identifier(ident),
+                            suggestion: Some(format!("render it with JSX: <{} ... />", ident.value)),
+                            labels: self.item_positions.get(&ident.value)
+                                .map(|position| Self::label(*position, format!("component '{}' declared here", ident.value)))
+                                .into_iter()
+                                .collect(),
+                        })
+                    }
                     _ => {
                         // Not a function type
                         Err(CompileError::Generic(format!(
@@ -437,7 +896,8 @@ impl TypeChecker {
                 }
             }
 
-            Expression::JsxElement(_) => {
+            Expression::JsxElement(jsx) => {
+                self.check_jsx_props(jsx);
                 // JSX elements return component instances
                 Ok(Type::Named("ReactElement".to_string()))
             }
@@ -496,10 +956,36 @@ impl TypeChecker {
             }
 
             Expression::FieldAccess(field_access) => {
-                // Infer object type
-                let object_type = self.infer_expression(&field_access.object)?;
+                // Infer object type; `a?.b` reads from what `a` holds, when it holds one
+                let mut object_type = self.infer_expression(&field_access.object)?;
+                if let (true, Type::Option(inner)) = (field_access.optional, &object_type) {
+                    object_type = (**inner).clone();
+                }
                 let field_name = &field_access.field.value;
 
+                // JsValue is opaque: fields are read with runtime-checked accessors
+                if object_type == Type::JsValue {
+                    return Err(CompileError::Generic(format!(
+                        "Cannot read `.{}` on a JsValue directly; use .get(\"{}\") and an accessor like .as_string()",
+                        field_name, field_name
+                    )));
+                }
+
+                // A css! handle has the block's classes, each a scoped class name
+                if let Type::Styles(classes) = &object_type {
+                    if classes.contains(field_name) {
+                        return Ok(Type::String);
+                    }
+                    let suggestion = match suggest_similar(field_name, classes.iter().map(String::as_str)) {
+                        Some(similar) => format!("; did you mean `.{}`?", similar),
+                        None => String::new(),
+                    };
+                    return Err(CompileError::Generic(format!(
+                        "css! block has no class `.{}`{}",
+                        field_name, suggestion
+                    )));
+                }
+
                 // For String methods, return function type with proper signature
                 if object_type == Type::String {
                     return Ok(match field_name.as_str() {
@@ -579,6 +1065,9 @@ impl TypeChecker {
                 match array_type {
                     Type::Array(elem_type) => Ok(*elem_type),
                     Type::Any => Ok(Type::Any),
+                    Type::JsValue => Err(CompileError::Generic(
+                        "Cannot index a JsValue directly; use .index(i)".to_string()
+                    )),
                     _ => Err(CompileError::Generic(format!(
                         "Cannot index into non-array type: {}",
                         array_type
@@ -656,10 +1145,22 @@ impl TypeChecker {
             }
 
             Expression::Await(await_expr) => {
-                // Process the inner expression recursively
-                // In a full implementation, we would verify that the inner expression
-                // returns a Future<T> type and extract the T type
-                self.infer_expression(&await_expr.expression)
+                let awaited = self.infer_expression(&await_expr.expression)?;
+
+                // Server calls are already awaited, so awaiting them again is harmless
+                if self.is_server_call(&await_expr.expression) {
+                    return Ok(awaited);
+                }
+
+                match awaited {
+                    Type::Promise(inner) => Ok(*inner),
+                    ty if Self::is_precise(&ty) => Err(CompileError::Generic(format!(
+                        "Cannot await a value of type {}: only promises can be awaited",
+                        ty
+                    ))),
+                    // Unknown or external values may be promises at runtime
+                    ty => Ok(ty),
+                }
             }
 
             Expression::IfExpression(if_expr) => {
@@ -694,17 +1195,55 @@ impl TypeChecker {
 
             Expression::MacroCall(macro_call) => {
                 // Type-check all macro arguments
+                let mut arg_types = Vec::new();
                 for arg in &macro_call.arguments {
-                    self.infer_expression(arg)?;
+                    arg_types.push(self.infer_expression(arg)?);
+                }
+                if macro_call.name.value == "vec" {
+                    return Ok(Type::array(self.common_type(&arg_types)));
+                }
+                if macro_call.name.value == "t" {
+                    return Ok(Type::String);
                 }
                 // For now, return Any type for macro calls
                 // In a full implementation, we'd expand the macro and infer its result type
                 Ok(Type::Any)
             }
 
-            Expression::CssMacro(_) => {
-                // CSS macro returns a styles object (handled in Sprint 1 Task 1.6)
-                Ok(Type::Any)
+            Expression::CssMacro(css) => {
+                // CSS macro returns a styles object, a field per class
+                let mut classes: Vec<String> = css.classes().into_iter().map(|class| class.name).collect();
+                classes.sort();
+                classes.dedup();
+                Ok(Type::Styles(classes))
+            }
+            Expression::InlineStyle(style) => {
+                // Any value can be interpolated; it is stringified and escaped
+                for expr in style.expressions() {
+                    self.infer_expression(expr)?;
+                }
+                Ok(Type::String)
+            }
+            Expression::Classes(classes) => {
+                for entry in &classes.entries {
+                    let class = self.infer_expression(&entry.class)?;
+                    if Self::is_precise(&class) && self.unify(&class, &Type::String).is_err() {
+                        return Err(CompileError::Generic(format!(
+                            "classes! entries must be class name strings, got {}",
+                            class
+                        )));
+                    }
+                    if let Some(condition) = &entry.condition {
+                        let condition = self.infer_expression(condition)?;
+                        if Self::is_precise(&condition) && self.unify(&condition, &Type::Bool).is_err() {
+                            return Err(CompileError::Generic(format!(
+                                "classes! condition must be bool, got {}",
+                                condition
+                            )));
+                        }
+                    }
+                }
+                Ok(Type::String)
             }
             // Reactivity primitives (Phase 12)
             Expression::Signal(signal_expr) => {
@@ -728,6 +1267,660 @@ impl TypeChecker {
         }
     }
 
+    /// Whether an expression calls a @server function (awaited implicitly)
+    fn is_server_call(&self, expr: &Expression) -> bool {
+        match expr {
+            Expression::FunctionCall(call) => matches!(
+                call.function.as_ref(),
+                Expression::Identifier(ident) if self.server_functions.contains(&ident.value)
+            ),
+            _ => false,
+        }
+    }
+
+    /// What awaiting a value produces: a promise's resolved type, or the value itself
+    fn settled(ty: Type) -> Type {
+        match ty {
+            Type::Promise(inner) => *inner,
+            ty => ty,
+        }
+    }
+
+    /// The type all of `types` unify with, or Any when they disagree
+    fn common_type(&mut self, types: &[Type]) -> Type {
+        let Some(first) = types.iter().find(|ty| !matches!(ty, Type::Any | Type::Error)).cloned() else {
+            return Type::Any;
+        };
+        if types.iter().all(|ty| self.unify(ty, &first).is_ok()) {
+            first
+        } else {
+            Type::Any
+        }
+    }
+
+    /// Resolved type of the values a Promise combinator waits on. Server calls
+    /// listed directly stay pending, so they run concurrently.
+    fn infer_settled_elements(&mut self, list: &Expression) -> Result<Type, CompileError> {
+        let elements = match list {
+            Expression::ArrayLiteral(array) => &array.elements,
+            Expression::MacroCall(macro_call) if macro_call.name.value == "vec" => &macro_call.arguments,
+            _ => {
+                return match self.infer_expression(list)? {
+                    Type::Array(element) => Ok(Self::settled(*element)),
+                    Type::Any | Type::Error => Ok(Type::Any),
+                    other => Err(CompileError::Generic(format!(
+                        "Promise combinators expect an array of promises, got {}",
+                        other
+                    ))),
+                };
+            }
+        };
+
+        let mut types = Vec::new();
+        for element in elements {
+            types.push(Self::settled(self.infer_expression(element)?));
+        }
+        Ok(self.common_type(&types))
+    }
+
+    /// `Promise::all`, `race`, `any`, `allSettled`, `resolve` and `reject`
+    fn check_promise_static(&mut self, method: &str, call: &FunctionCall) -> Result<Type, CompileError> {
+        let expected_args = match method {
+            "resolve" => 0..=1,
+            _ => 1..=1,
+        };
+        if !expected_args.contains(&call.arguments.len()) {
+            return Err(CompileError::Generic(format!(
+                "Promise::{} expects {} argument, got {}",
+                method,
+                expected_args.end(),
+                call.arguments.len()
+            )));
+        }
+
+        match method {
+            "all" => Ok(Type::promise(Type::array(self.infer_settled_elements(&call.arguments[0])?))),
+            "race" | "any" => Ok(Type::promise(self.infer_settled_elements(&call.arguments[0])?)),
+            "allSettled" => {
+                self.infer_settled_elements(&call.arguments[0])?;
+                Ok(Type::promise(Type::array(Type::Any)))
+            }
+            "resolve" => match call.arguments.first() {
+                Some(value) => Ok(Type::promise(Self::settled(self.infer_expression(value)?))),
+                None => Ok(Type::promise(Type::Void)),
+            },
+            "reject" => {
+                self.infer_expression(&call.arguments[0])?;
+                Ok(Type::promise(Type::Any))
+            }
+            _ => Err(CompileError::Generic(format!("Unknown Promise function 'Promise::{}'", method))),
+        }
+    }
+
+    /// `.then(f)`, `.catch(f)` and `.finally(f)` on a `Promise<inner>`
+    fn check_promise_method(&mut self, inner: Type, method: &str, call: &FunctionCall) -> Result<Type, CompileError> {
+        let max_args = if method == "then" { 2 } else { 1 };
+        if call.arguments.is_empty() || call.arguments.len() > max_args {
+            return Err(CompileError::Generic(format!(
+                "Promise .{}() expects a callback, got {} arguments",
+                method,
+                call.arguments.len()
+            )));
+        }
+
+        let mut callbacks = Vec::new();
+        for argument in &call.arguments {
+            callbacks.push(self.infer_expression(argument)?);
+        }
+
+        match method {
+            // The chained promise resolves to whatever the callback returns (flattened)
+            "then" => match &callbacks[0] {
+                Type::Function { return_type, .. } => Ok(Type::promise(Self::settled((**return_type).clone()))),
+                _ => Ok(Type::promise(Type::Any)),
+            },
+            _ => Ok(Type::promise(inner)),
+        }
+    }
+
+    /// `EventBus::new()` and `EventBus::channel("name")`; the payload type comes
+    /// from a turbofish (`EventBus::channel::<String>("toasts")`) or is left open
+    fn check_event_bus_static(&mut self, method: &str, call: &FunctionCall) -> Result<Type, CompileError> {
+        let payload = match call.type_params.as_deref() {
+            Some([payload]) => self.type_expr_to_type(payload),
+            _ => Type::Any,
+        };
+
+        match method {
+            "new" if call.arguments.is_empty() => Ok(Type::event_bus(payload)),
+            "channel" if call.arguments.len() == 1 => {
+                let name = self.infer_expression(&call.arguments[0])?;
+                if Self::is_precise(&name) && self.unify(&name, &Type::String).is_err() {
+                    return Err(CompileError::Generic(format!(
+                        "EventBus::channel expects a channel name string, got {}",
+                        name
+                    )));
+                }
+                Ok(Type::event_bus(payload))
+            }
+            "new" | "channel" => Err(CompileError::Generic(format!(
+                "EventBus::{} expects {} argument(s), got {}",
+                method,
+                if method == "new" { 0 } else { 1 },
+                call.arguments.len()
+            ))),
+            _ => Err(CompileError::Generic(format!("Unknown EventBus function 'EventBus::{}'", method))),
+        }
+    }
+
+    /// `.emit(value)` and `.subscribe(handler)` on an `EventBus<payload>`
+    fn check_event_bus_method(&mut self, payload: Type, method: &str, call: &FunctionCall) -> Result<Type, CompileError> {
+        if call.arguments.len() != 1 {
+            return Err(CompileError::Generic(format!(
+                "EventBus .{}() expects 1 argument, got {}",
+                method,
+                call.arguments.len()
+            )));
+        }
+
+        match method {
+            "emit" => {
+                let value = self.infer_expression(&call.arguments[0])?;
+                if Self::is_precise(&value) && Self::is_precise(&payload) && self.unify(&value, &payload).is_err() {
+                    return Err(CompileError::Generic(format!(
+                        "Cannot emit {} on an EventBus<{}>",
+                        value, payload
+                    )));
+                }
+                Ok(Type::Void)
+            }
+            _ => {
+                // The handler's parameter is the payload, so its body is checked against it
+                self.infer_callback(&call.arguments[0], &[payload])?;
+                Ok(Type::Named("Subscription".to_string()))
+            }
+        }
+    }
+
+    /// `JsValue::from_json(value)` turns plain JSON data into a JS value
+    fn check_js_value_static(&mut self, method: &str, call: &FunctionCall) -> Result<Type, CompileError> {
+        if method != "from_json" {
+            return Err(CompileError::Generic(format!("Unknown JsValue function 'JsValue::{}'", method)));
+        }
+        if call.arguments.len() != 1 {
+            return Err(CompileError::Generic(format!(
+                "JsValue::from_json expects 1 argument, got {}",
+                call.arguments.len()
+            )));
+        }
+        // JsonValue is a named type, so anything precise is something else
+        let value = self.infer_expression(&call.arguments[0])?;
+        if Self::is_precise(&value) {
+            return Err(CompileError::Generic(format!(
+                "JsValue::from_json expects a JsonValue, got {}",
+                value
+            )));
+        }
+        Ok(Type::JsValue)
+    }
+
+    /// Accessors on a `JsValue`. Nothing about the value is assumed: reads give
+    /// back another JsValue, and conversions to typed values are Options
+    fn check_js_value_method(&mut self, method: &str, call: &FunctionCall) -> Result<Type, CompileError> {
+        let (params, result) = match method {
+            "get" => (vec![Type::String], Type::JsValue),
+            "index" => (vec![Type::Int], Type::JsValue),
+            "call" => (vec![Type::Array(Box::new(Type::JsValue))], Type::JsValue),
+            "is_null" | "is_undefined" => (vec![], Type::Bool),
+            "as_string" => (vec![], Type::Option(Box::new(Type::String))),
+            "as_f64" => (vec![], Type::Option(Box::new(Type::Float))),
+            "as_bool" => (vec![], Type::Option(Box::new(Type::Bool))),
+            _ => (vec![], Type::Option(Box::new(Type::Named("JsonValue".to_string())))),
+        };
+
+        if call.arguments.len() != params.len() {
+            return Err(CompileError::Generic(format!(
+                "JsValue .{}() expects {} argument(s), got {}",
+                method,
+                params.len(),
+                call.arguments.len()
+            )));
+        }
+        for (argument, expected) in call.arguments.iter().zip(&params) {
+            let actual = self.infer_expression(argument)?;
+            if Self::is_precise(&actual) && self.unify(&actual, expected).is_err() {
+                return Err(CompileError::Generic(format!(
+                    "JsValue .{}() expects {}, got {}",
+                    method, expected, actual
+                )));
+            }
+        }
+        Ok(result)
+    }
+
+    /// `create_context(default)` makes a `Context<T>` from the default value (or
+    /// a turbofish, `create_context::<Theme>(light)`); `use_context(ctx)` reads
+    /// the `T` back
+    fn check_context_call(&mut self, name: &str, call: &FunctionCall) -> Result<Type, CompileError> {
+        if call.arguments.len() != 1 {
+            return Err(CompileError::Generic(format!(
+                "{} expects 1 argument, got {}",
+                name,
+                call.arguments.len()
+            )));
+        }
+
+        let argument = self.infer_expression(&call.arguments[0])?;
+        if name == "create_context" {
+            let Some([declared]) = call.type_params.as_deref() else {
+                return Ok(Type::context(argument));
+            };
+            let declared = self.type_expr_to_type(declared);
+            if Self::is_precise(&argument) && Self::is_precise(&declared) && self.unify(&argument, &declared).is_err() {
+                return Err(CompileError::Generic(format!(
+                    "Default value of a Context<{}> must be {}, got {}",
+                    declared, declared, argument
+                )));
+            }
+            return Ok(Type::context(declared));
+        }
+
+        match argument {
+            Type::Context(value) => Ok(*value),
+            other if Self::is_precise(&other) => Err(CompileError::Generic(format!(
+                "use_context expects a Context, got {}",
+                other
+            ))),
+            _ => Ok(Type::Any),
+        }
+    }
+
+    /// `<Provide context={ctx} value={v}>`: `v` must have the context's type
+    fn check_provide(&mut self, jsx: &JsxElement) {
+        let attribute = |name: &str| jsx.opening_tag.attributes.iter().find(|attr| attr.name.value == name);
+        let (Some(context), Some(value)) = (attribute("context"), attribute("value")) else {
+            self.errors.push(CompileError::Generic(
+                "<Provide> needs both a `context` and a `value` attribute".to_string(),
+            ));
+            return;
+        };
+
+        let types = self.infer_expression(&context.value)
+            .and_then(|context| Ok((context, self.infer_expression(&value.value)?)));
+        match types {
+            Ok((Type::Context(expected), value_type)) => {
+                if Self::is_precise(&expected)
+                    && Self::is_precise(&value_type)
+                    && self.unify(&value_type, &expected).is_err()
+                {
+                    self.errors.push(CompileError::Generic(format!(
+                        "<Provide> value for a Context<{}> must be {}, got {}",
+                        expected, expected, value_type
+                    )));
+                }
+            }
+            Ok((other, _)) if Self::is_precise(&other) => {
+                self.errors.push(CompileError::Generic(format!(
+                    "<Provide> context must be a Context, got {}",
+                    other
+                )));
+            }
+            Ok(_) => {}
+            Err(e) => self.errors.push(e),
+        }
+    }
+
+    /// Infer a callback argument, binding untyped lambda parameters to the
+    /// types the caller will pass
+    fn infer_callback(&mut self, callback: &Expression, params: &[Type]) -> Result<Type, CompileError> {
+        let Expression::Lambda(lambda) = callback else {
+            return self.infer_expression(callback);
+        };
+
+        self.env.push_scope();
+        let mut param_types = Vec::new();
+        for (i, param) in lambda.parameters.iter().enumerate() {
+            let param_type = match &param.type_annotation {
+                Some(type_expr) => self.type_expr_to_type(type_expr),
+                None => params.get(i).cloned().unwrap_or(Type::Any),
+            };
+            self.env.bind(param.name.value.clone(), param_type.clone());
+            param_types.push(param_type);
+        }
+        let body_type = self.infer_expression(&lambda.body);
+        self.env.pop_scope();
+
+        Ok(Type::function(param_types, body_type?))
+    }
+
+    /// A capitalized tag that names a function rather than a component would
+    /// render as an unknown element, while a call of the same name runs the
+    /// function; report it instead of letting the two diverge
+    fn check_jsx_tag_names_function(&mut self, tag: &Identifier) {
+        if !tag.value.starts_with(|c: char| c.is_ascii_uppercase()) || self.component_props.contains_key(&tag.value) {
+            return;
+        }
+        let Some(Type::Function { .. }) = self.env.lookup(&tag.value) else {
+            return;
+        };
+        self.errors.push(CompileError::WithLocation {
+            message: format!("<{}> names the function '{}', not a component", tag.value, tag.value),
+            location: SourceLocation::from_identifier(tag),
+            suggestion: Some(format!(
+                "declare it with `component {}(...)`, or call it inside braces: {{{}(...)}}",
+                tag.value, tag.value
+            )),
+            labels: self.item_positions.get(&tag.value)
+                .map(|position| Self::label(*position, format!("function '{}' declared here", tag.value)))
+                .into_iter()
+                .collect(),
+        });
+    }
+
+    /// Check props passed to known components against their declared types,
+    /// recording a mismatch for each bad prop (including in nested elements)
+    fn check_jsx_props(&mut self, jsx: &JsxElement) {
+        let component = &jsx.opening_tag.name.value;
+        self.check_jsx_tag_names_function(&jsx.opening_tag.name);
+        if let Some(props) = self.component_props.get(component).cloned() {
+            for attr in &jsx.opening_tag.attributes {
+                // A spread can hold any of the props; only what it spreads is checked
+                if attr.spread().is_some() {
+                    if let Err(e) = self.infer_expression(&attr.value) {
+                        self.errors.push(e);
+                    }
+                    continue;
+                }
+                let Some(prop) = props.iter().find(|p| p.name == attr.name.value) else {
+                    self.check_unknown_prop(component, &props, &attr.name);
+                    continue;
+                };
+                let value_type = match self.infer_expression(&attr.value) {
+                    Ok(ty) => ty,
+                    Err(e) => {
+                        self.errors.push(e);
+                        continue;
+                    }
+                };
+                if Self::is_precise(&prop.ty)
+                    && Self::is_precise(&value_type)
+                    && self.unify(&value_type, &prop.ty).is_err()
+                {
+                    self.errors.push(CompileError::TypeError {
+                        message: format!(
+                            "Prop '{}' of component '{}' expects {}, got {}",
+                            prop.name, component, prop.ty, value_type
+                        ),
+                        labels: vec![Self::label(
+                            prop.position,
+                            format!("prop '{}' declared here as {}", prop.name, prop.ty),
+                        )],
+                    });
+                }
+            }
+        }
+
+        // Class lists are checked on any element, not only on components, and
+        // so is an element's class, `class={styles.button}` reading a css! handle
+        let is_component = self.component_props.contains_key(component);
+        for attr in &jsx.opening_tag.attributes {
+            let element_class = !is_component && matches!(attr.name.value.as_str(), "class" | "className");
+            if matches!(attr.value, Expression::Classes(_)) || element_class {
+                if let Err(e) = self.infer_expression(&attr.value) {
+                    self.errors.push(e);
+                }
+            }
+            if attr.name.value.starts_with("bind:") {
+                self.check_value_binding(jsx, attr);
+            }
+            if attr.name.value == JSX_REF {
+                self.check_element_ref(jsx, attr);
+            }
+        }
+
+        if component == "Provide" && !self.component_props.contains_key(component) {
+            self.check_provide(jsx);
+        }
+
+        self.check_slots(jsx);
+
+        for child in &jsx.children {
+            if let JsxChild::Element(element) = child {
+                self.check_jsx_props(element);
+            }
+        }
+    }
+
+    /// Signals and computeds are typed Any, so a prop says which it is by its
+    /// declared type: `count: Signal<i32>`
+    fn declared_reactivity(type_expr: &TypeExpression) -> Reactivity {
+        match type_expr {
+            TypeExpression::Named(ident) | TypeExpression::Generic(ident, _) if ident.value == "Signal" => Reactivity::Signal,
+            TypeExpression::Named(ident) | TypeExpression::Generic(ident, _) if ident.value == "Computed" => Reactivity::Computed,
+            _ => Reactivity::Plain,
+        }
+    }
+
+    /// `bind:value={s}` shows `s` in a form field and writes the field's edits
+    /// back, so it has to be on an input, textarea or select, bound to a
+    /// signal that can be written. Values whose origin isn't known here (call
+    /// results, fields, names from JS) are taken on trust.
+    fn check_value_binding(&mut self, jsx: &JsxElement, attr: &JsxAttribute) {
+        let tag = jsx.tag_name();
+        let error = |message: String, suggestion: String| CompileError::WithLocation {
+            message,
+            location: SourceLocation::from_identifier(&attr.name),
+            suggestion: Some(suggestion),
+            labels: Vec::new(),
+        };
+        if attr.name.value != BIND_VALUE {
+            self.errors.push(error(
+                format!("Unknown binding `{}`", attr.name.value),
+                format!("the only binding is `{}`", BIND_VALUE),
+            ));
+            return;
+        }
+        let Some(binding) = jsx.value_binding() else {
+            let suggestion = if self.component_slots.contains_key(tag) {
+                format!("pass the signal to <{}> as a prop and bind it inside", tag)
+            } else {
+                "bind an <input>, <textarea> or <select>".to_string()
+            };
+            self.errors.push(error(format!("bind:value doesn't work on <{}>", tag), suggestion));
+            return;
+        };
+
+        // The binding sets the property and handles the event itself
+        for name in [binding.property(), binding.handler()] {
+            if let Some(other) = jsx.opening_tag.attributes.iter().find(|other| other.name.value.eq_ignore_ascii_case(name)) {
+                let suggestion = if name == binding.handler() {
+                    "react to changes with an effect() on the signal instead".to_string()
+                } else {
+                    format!("remove `{}`; the signal provides it", other.name.value)
+                };
+                self.errors.push(error(
+                    format!("<{}> has both bind:value and `{}`, which bind:value sets", tag, other.name.value),
+                    suggestion,
+                ));
+            }
+        }
+
+        let problem = match &attr.value {
+            Expression::Identifier(ident) => match self.env.reactivity(&ident.value) {
+                Some(Reactivity::Signal) => None,
+                Some(Reactivity::Computed) => Some((
+                    format!("'{}' is computed, which is read-only", ident.value),
+                    format!("bind a signal and compute '{}' from it", ident.value),
+                )),
+                Some(Reactivity::Plain) => Some((
+                    format!("the prop '{}' is not a signal", ident.value),
+                    format!("declare it as `{}: Signal<...>` and pass the signal itself", ident.value),
+                )),
+                None => match self.env.lookup(&ident.value) {
+                    Some(ty) if Self::is_precise(&ty) && !matches!(ty, Type::Any | Type::Error) => Some((
+                        format!("'{}' is a {}, not a signal", ident.value, ty),
+                        format!("declare it with `let {} = signal(...)`", ident.value),
+                    )),
+                    _ => None,
+                },
+            },
+            Expression::FieldAccess(_) | Expression::IndexAccess(_) | Expression::FunctionCall(_) | Expression::Signal(_) => None,
+            Expression::Computed(_) => Some((
+                "a computed value is read-only".to_string(),
+                "bind a signal and compute from it".to_string(),
+            )),
+            Expression::IntegerLiteral(_) | Expression::FloatLiteral(_) | Expression::StringLiteral(_) | Expression::BoolLiteral(_) => Some((
+                "a literal can't be written to".to_string(),
+                "bind a signal: `let text = signal(\"\")` and `bind:value={text}`".to_string(),
+            )),
+            _ => Some((
+                "this expression can't be written to".to_string(),
+                "bind a signal: `let text = signal(\"\")` and `bind:value={text}`".to_string(),
+            )),
+        };
+        if let Some((problem, suggestion)) = problem {
+            self.errors.push(error(format!("bind:value needs a writable signal, but {}", problem), suggestion));
+        }
+    }
+
+    /// `ref={x}` hands an HTML element to `x`, so `x` has to be a variable,
+    /// signal or field that can take it, or a closure called with it.
+    /// Components render elements of their own and don't take a ref.
+    fn check_element_ref(&mut self, jsx: &JsxElement, attr: &JsxAttribute) {
+        let tag = jsx.tag_name();
+        let error = |message: String, suggestion: String| CompileError::WithLocation {
+            message,
+            location: SourceLocation::from_identifier(&attr.name),
+            suggestion: Some(suggestion),
+            labels: Vec::new(),
+        };
+        if jsx.is_component() {
+            self.errors.push(error(
+                format!("`ref` doesn't work on <{}>, which is a component", tag),
+                format!("pass a signal to <{}> as a prop and put `ref` on one of its elements", tag),
+            ));
+            return;
+        }
+        let problem = match &attr.value {
+            Expression::Identifier(ident) if self.env.reactivity(&ident.value) == Some(Reactivity::Computed) => {
+                format!("'{}' is computed, which is read-only", ident.value)
+            }
+            Expression::Identifier(_) | Expression::FieldAccess(_) | Expression::IndexAccess(_) | Expression::Lambda(_) => return,
+            _ => "this expression can't hold it".to_string(),
+        };
+        self.errors.push(error(
+            format!("`ref` needs a variable or signal to hand <{}> to, but {}", tag, problem),
+            "declare one with `let el = signal(None)` and write `ref={el}`".to_string(),
+        ));
+    }
+
+    /// `<slot:name>` children fill the named slots of the component they're
+    /// directly inside and everything else fills its `children` slot. Every
+    /// slot filled must be declared, and every slot not typed Option filled.
+    fn check_slots(&mut self, jsx: &JsxElement) {
+        let component = jsx.tag_name();
+        let fills: Vec<&JsxElement> = jsx.children
+            .iter()
+            .filter_map(|child| match child {
+                JsxChild::Element(element) if element.slot_name().is_some() => Some(element.as_ref()),
+                _ => None,
+            })
+            .collect();
+        let Some(slots) = self.component_slots.get(component).cloned() else {
+            for fill in fills {
+                self.errors.push(CompileError::WithLocation {
+                    message: format!("<{}> fills a slot, but <{}> is not a component", fill.tag_name(), component),
+                    location: SourceLocation::from_identifier(&fill.opening_tag.name),
+                    suggestion: Some("put it directly inside the component whose slot it fills".to_string()),
+                    labels: Vec::new(),
+                });
+            }
+            return;
+        };
+
+        let mut filled: Vec<&str> = Vec::new();
+        for fill in fills {
+            let name = fill.slot_name().unwrap_or_default();
+            let location = SourceLocation::from_identifier(&fill.opening_tag.name);
+            if filled.contains(&name) {
+                self.errors.push(CompileError::WithLocation {
+                    message: format!("Slot '{}' of component '{}' is filled more than once", name, component),
+                    location,
+                    suggestion: Some(format!("put everything for '{}' in one <slot:{}>", name, name)),
+                    labels: Vec::new(),
+                });
+                continue;
+            }
+            filled.push(name);
+            if !slots.iter().any(|slot| slot.name == name) {
+                let suggestion = match suggest_similar(name, slots.iter().map(|slot| slot.name.as_str())) {
+                    Some(similar) => format!("did you mean <slot:{}>?", similar),
+                    None if slots.is_empty() => format!("'{}' declares no slots", component),
+                    None => format!(
+                        "its slots are {}",
+                        slots.iter().map(|slot| format!("'{}'", slot.name)).collect::<Vec<_>>().join(", ")
+                    ),
+                };
+                self.errors.push(CompileError::WithLocation {
+                    message: format!("Component '{}' has no slot named '{}'", component, name),
+                    location,
+                    suggestion: Some(suggestion),
+                    labels: self.item_positions.get(component)
+                        .map(|position| Self::label(*position, format!("component '{}' declared here", component)))
+                        .into_iter()
+                        .collect(),
+                });
+            }
+        }
+
+        let has_children = jsx.children.iter().any(|child| match child {
+            JsxChild::Element(element) => element.slot_name().is_none(),
+            JsxChild::Text(text) => !text.trim().is_empty(),
+            JsxChild::Expression(_) => true,
+        });
+        if has_children {
+            filled.push(DEFAULT_SLOT);
+        }
+        for slot in slots.iter().filter(|slot| !slot.optional && !filled.contains(&slot.name.as_str())) {
+            let suggestion = if slot.name == DEFAULT_SLOT {
+                format!("give <{}> children", component)
+            } else {
+                format!("fill it with <slot:{}>...</slot:{}>, or declare it Option<Element>", slot.name, slot.name)
+            };
+            self.errors.push(CompileError::WithLocation {
+                message: format!("<{}> is missing its '{}' slot", component, slot.name),
+                location: SourceLocation::from_identifier(&jsx.opening_tag.name),
+                suggestion: Some(suggestion),
+                labels: vec![Self::label(slot.position, format!("slot '{}' declared here", slot.name))],
+            });
+        }
+    }
+
+    /// Components accept attributes they don't declare (`key`, handlers passed
+    /// through), so an unknown prop is only reported when it looks like a typo
+    fn check_unknown_prop(&mut self, component: &str, props: &[DeclaredName], attr: &Identifier) {
+        if attr.value == JSX_KEY || attr.value == JSX_REF || attr.position == Position::default() || self.reported_names.contains(&attr.position) {
+            return;
+        }
+        let Some(prop) = suggest_similar(&attr.value, props.iter().map(|p| p.name.as_str()))
+            .and_then(|similar| props.iter().find(|p| p.name == similar))
+        else {
+            return;
+        };
+        let location = SourceLocation::from_identifier(attr);
+        let fix = did_you_mean(location.clone(), &prop.name);
+        self.reported_names.insert(attr.position);
+        self.errors.push(
+            CompileError::WithLocation {
+                message: format!("Component '{}' has no prop named '{}'", component, attr.value),
+                location,
+                suggestion: None,
+                labels: vec![Self::label(prop.position, format!("prop '{}' declared here", prop.name))],
+            }
+            .with_fix(Some(fix)),
+        );
+    }
+
     fn check_prefix_expression(&mut self, prefix: &PrefixExpression) -> Result<Type, CompileError> {
         let right_type = self.infer_expression(&prefix.right)?;
         let op = &prefix.operator.lexeme;
@@ -823,6 +2016,34 @@ impl TypeChecker {
                 Ok(Type::Bool)
             }
 
+            "??" => {
+                // `a ?? b` is what `a` holds, or `b` when it's missing
+                let value_type = match left_type {
+                    Type::Option(inner) => *inner,
+                    other => other,
+                };
+                if !value_type.is_compatible_with(&right_type) {
+                    return Err(CompileError::Generic(format!(
+                        "The default after `??` is {}, but the value it stands in for is {}",
+                        right_type, value_type
+                    )));
+                }
+                Ok(if value_type == Type::Any { right_type } else { value_type })
+            }
+
+            "&" | "|" | "^" | "<<" | ">>" => {
+                // Bitwise operations work on integers only
+                for operand in [&left_type, &right_type] {
+                    if !matches!(operand, Type::Int | Type::Any | Type::Error) {
+                        return Err(CompileError::Generic(format!(
+                            "Bitwise `{}` needs integers, got {}",
+                            op, operand
+                        )));
+                    }
+                }
+                Ok(Type::Int)
+            }
+
             "&&" | "||" => {
                 // Logical operations
                 if left_type != Type::Bool {
@@ -850,8 +2071,9 @@ impl TypeChecker {
             // Same types unify trivially
             (a, b) if a == b => Ok(Substitution::new()),
 
-            // Any unifies with everything
+            // Any unifies with everything, and so does Error to stop cascades
             (Type::Any, _) | (_, Type::Any) => Ok(Substitution::new()),
+            (Type::Error, _) | (_, Type::Error) => Ok(Substitution::new()),
 
             // Type variables
             (Type::Var(id), ty) | (ty, Type::Var(id)) => {
@@ -898,6 +2120,11 @@ impl TypeChecker {
             // Options
             (Type::Option(t1), Type::Option(t2)) => self.unify(t1, t2),
 
+            // Promises
+            (Type::Promise(t1), Type::Promise(t2)) => self.unify(t1, t2),
+            (Type::EventBus(t1), Type::EventBus(t2)) => self.unify(t1, t2),
+            (Type::Context(t1), Type::Context(t2)) => self.unify(t1, t2),
+
             // Tuples
             (Type::Tuple(t1), Type::Tuple(t2)) => {
                 if t1.len() != t2.len() {
@@ -927,7 +2154,7 @@ impl TypeChecker {
         match ty {
             Type::Var(id) => *id == var,
             Type::Array(inner) => self.occurs_check(var, inner),
-            Type::Option(inner) => self.occurs_check(var, inner),
+            Type::Option(inner) | Type::Promise(inner) | Type::EventBus(inner) | Type::Context(inner) => self.occurs_check(var, inner),
             Type::Function { params, return_type } => {
                 params.iter().any(|p| self.occurs_check(var, p))
                     || self.occurs_check(var, return_type)
@@ -960,7 +2187,7 @@ impl TypeChecker {
                 set
             }
             Type::Array(inner) => self.free_vars(inner),
-            Type::Option(inner) => self.free_vars(inner),
+            Type::Option(inner) | Type::Promise(inner) | Type::EventBus(inner) | Type::Context(inner) => self.free_vars(inner),
             Type::Function { params, return_type } => {
                 let mut set = HashSet::new();
                 for param in params {
@@ -1222,4 +2449,273 @@ mod tests {
         let recursive_type = Type::Array(Box::new(Type::Var(0)));
         assert!(checker.occurs_check(0, &recursive_type));
     }
+
+    fn check_source(source: &str) -> Result<(), CompileError> {
+        let mut lexer = crate::lexer::Lexer::new(source.to_string());
+        let mut parser = crate::parser::Parser::new(&mut lexer);
+        let program = parser.parse_program().expect("Parse failed");
+        TypeChecker::new().check_program(&program.statements)
+    }
+
+    fn single_label(error: &CompileError) -> &Label {
+        match error {
+            CompileError::TypeError { labels, .. } if labels.len() == 1 => &labels[0],
+            other => panic!("expected a type error with one label, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_component_and_call_syntax_name_one_item() {
+        // A component renders with JSX; calling it points back at the one definition
+        let source = "component Card(title: String) {\n    return <div>{title}</div>;\n}\n\ncomponent App() {\n    let x = Card(\"hi\");\n    return <div><Card title=\"a\" /></div>;\n}\n";
+        let error = check_source(source).expect_err("components aren't called");
+        let CompileError::WithLocation { message, location, labels, .. } = error else {
+            panic!("expected a located error, got {:?}", error);
+        };
+        assert!(message.contains("'Card' is a component and can't be called"), "{}", message);
+        assert_eq!((location.line, labels[0].location.line), (6, 1));
+
+        // A function called inside JSX is fine; used as a tag it would render an unknown element
+        check_source("fn Card(title: String) -> String {\n    return title;\n}\n\ncomponent App() {\n    return <div>{Card(\"a\")}</div>;\n}\n").unwrap();
+        let error = check_source("fn Card(title: String) -> String {\n    return title;\n}\n\ncomponent App() {\n    return <div><Card title=\"a\" /></div>;\n}\n")
+            .expect_err("functions aren't tags");
+        let CompileError::WithLocation { message, location, labels, .. } = error else {
+            panic!("expected a located error, got {:?}", error);
+        };
+        assert!(message.contains("<Card> names the function 'Card'"), "{}", message);
+        assert_eq!((location.line, labels[0].location.line), (6, 1));
+    }
+
+    const MODAL: &str = "component Modal(title: String, slot header: Element, slot footer: Option<Element>, slot children: Element) {\n    return <div><header>{header}</header>{children}<footer>{footer}</footer></div>;\n}\n\n";
+
+    #[test]
+    fn test_component_slots_filled_or_optional() {
+        // All three slots, then the optional footer left out
+        check_source(&format!("{}component App() {{\n    return <Modal title=\"Hi\"><slot:header><h1>Hi</h1></slot:header><p>Body</p><slot:footer><b>ok</b></slot:footer></Modal>;\n}}\n", MODAL)).unwrap();
+        check_source(&format!("{}component App() {{\n    return <Modal title=\"Hi\"><slot:header><h1>Hi</h1></slot:header><p>Body</p></Modal>;\n}}\n", MODAL)).unwrap();
+
+        // Without the required header
+        let error = check_source(&format!("{}component App() {{\n    return <Modal title=\"Hi\"><p>Body</p></Modal>;\n}}\n", MODAL))
+            .expect_err("header is required");
+        let CompileError::WithLocation { message, location, labels, .. } = error else {
+            panic!("expected a located error, got {:?}", error);
+        };
+        assert_eq!(message, "<Modal> is missing its 'header' slot");
+        assert_eq!((location.line, labels[0].location.line), (6, 1));
+    }
+
+    #[test]
+    fn test_unknown_slot_is_an_error() {
+        let error = check_source(&format!("{}component App() {{\n    return <Modal title=\"Hi\"><slot:header>x</slot:header><slot:sidebar>y</slot:sidebar><p>Body</p></Modal>;\n}}\n", MODAL))
+            .expect_err("no sidebar slot");
+        let CompileError::WithLocation { message, location, suggestion, .. } = error else {
+            panic!("expected a located error, got {:?}", error);
+        };
+        assert_eq!(message, "Component 'Modal' has no slot named 'sidebar'");
+        assert_eq!(location.line, 6);
+        assert_eq!(suggestion.as_deref(), Some("its slots are 'header', 'footer', 'children'"));
+
+        let error = check_source("component App() {\n    return <div><slot:header>x</slot:header></div>;\n}\n").expect_err("not a component");
+        assert!(error.to_string().contains("<slot:header> fills a slot, but <div> is not a component"), "{}", error);
+    }
+
+    #[test]
+    fn test_coalesce_default_matches_the_value() {
+        check_source("fn greet(name: Option<String>) -> String {\n    return name ?? \"anonymous\";\n}\n").expect("same type");
+        let error = check_source("fn greet(name: Option<String>) -> String {\n    return name ?? 5;\n}\n").expect_err("mismatched default");
+        assert!(error.to_string().contains("The default after `??` is int, but the value it stands in for is string"), "{}", error);
+    }
+
+    #[test]
+    fn test_bind_value_needs_a_writable_signal() {
+        check_source("component Form(query: Signal<String>) {\n    let name = signal(\"\");\n    return <div><input bind:value={name} /><textarea bind:value={query}></textarea></div>;\n}\n")
+            .expect("signals bind");
+
+        let error = check_source("component Form() {\n    let name = \"Ada\";\n    return <input bind:value={name} />;\n}\n")
+            .expect_err("a string isn't a signal");
+        let CompileError::WithLocation { message, location, suggestion, .. } = error else {
+            panic!("expected a located error, got {:?}", error);
+        };
+        assert_eq!(message, "bind:value needs a writable signal, but 'name' is a string, not a signal");
+        assert_eq!(location.line, 3);
+        assert_eq!(suggestion.as_deref(), Some("declare it with `let name = signal(...)`"));
+
+        let error = check_source("component Form() {\n    let count = signal(1);\n    let double = computed(() => count.value * 2);\n    return <input type=\"number\" bind:value={double} />;\n}\n")
+            .expect_err("computeds are read-only");
+        assert!(error.to_string().contains("'double' is computed, which is read-only"), "{}", error);
+
+        let error = check_source("component Form(title: String) {\n    return <input bind:value={title} />;\n}\n").expect_err("a plain prop");
+        assert!(error.to_string().contains("the prop 'title' is not a signal"), "{}", error);
+
+        let error = check_source("component Form() {\n    return <input bind:value={\"x\"} />;\n}\n").expect_err("a literal");
+        assert!(error.to_string().contains("but a literal can't be written to"), "{}", error);
+
+        let error = check_source("component Form() {\n    let name = signal(\"\");\n    return <div bind:value={name}></div>;\n}\n").expect_err("not a field");
+        assert!(error.to_string().contains("bind:value doesn't work on <div>"), "{}", error);
+    }
+
+    #[test]
+    fn test_ref_on_elements_only() {
+        check_source("component Search(items: Vec<String>) {\n    let input_el = signal(None);\n    let last = None;\n    return <div><input ref={input_el} />{items.iter().map(|item| <li ref={last}>{item}</li>)}<b ref={|node| node}>x</b></div>;\n}\n")
+            .expect("signals, variables and closures take refs");
+
+        let error = check_source(&format!("{}component App() {{\n    let el = signal(None);\n    return <Modal title=\"Hi\" ref={{el}}><slot:header>x</slot:header>y</Modal>;\n}}\n", MODAL))
+            .expect_err("components don't take refs");
+        let CompileError::WithLocation { message, location, suggestion, .. } = error else {
+            panic!("expected a located error, got {:?}", error);
+        };
+        assert_eq!(message, "`ref` doesn't work on <Modal>, which is a component");
+        assert_eq!(location.line, 7);
+        assert_eq!(suggestion.as_deref(), Some("pass a signal to <Modal> as a prop and put `ref` on one of its elements"));
+
+        let error = check_source("component Form() {\n    return <input ref={\"x\"} />;\n}\n").expect_err("a literal");
+        assert!(error.to_string().contains("`ref` needs a variable or signal to hand <input> to, but this expression can't hold it"), "{}", error);
+    }
+
+    #[test]
+    fn test_reports_independent_errors_in_one_body() {
+        let source = "fn takes_int(n: i32) -> i32 {\n    return n;\n}\n\nfn main() {\n    takes_int(\"one\");\n    let flag = true;\n    takes_int(flag);\n}\n";
+
+        let error = check_source(source).expect_err("both calls are ill-typed");
+        let errors = error.errors();
+        assert_eq!(errors.len(), 2, "got {:?}", errors);
+        assert!(errors[0].to_string().contains("expected int, got string"));
+        assert!(errors[1].to_string().contains("expected int, got bool"));
+    }
+
+    #[test]
+    fn test_argument_mismatch_points_at_parameter() {
+        let source = "fn greet(name: String, times: i32) {\n}\n\nfn main() {\n    greet(\"hi\", \"twice\");\n}\n";
+
+        let error = check_source(source).expect_err("second argument is a string");
+        let label = single_label(&error);
+        assert_eq!(label.location.line, 1);
+        assert_eq!(label.message, "parameter 'times' declared here as int");
+    }
+
+    #[test]
+    fn test_return_mismatch_points_at_return_type() {
+        let source = "fn count() -> i32 {\n    return \"none\";\n}\n";
+
+        let error = check_source(source).expect_err("returns a string");
+        assert!(error.to_string().contains("Mismatched return type in 'count'"));
+        let label = single_label(&error);
+        assert_eq!(label.location.line, 1);
+        assert_eq!(label.message, "expected int because of this return type");
+    }
+
+    #[test]
+    fn test_prop_mismatch_points_at_prop_declaration() {
+        let source = "component Counter(label: String,\n                  start: i32) {\n    return <span>{label}</span>;\n}\n\ncomponent App() {\n    return <div><Counter label=\"Clicks\" start={true} /></div>;\n}\n";
+
+        let error = check_source(source).expect_err("start is not an int");
+        assert!(error.to_string().contains("Prop 'start' of component 'Counter' expects int, got bool"));
+        let label = single_label(&error);
+        assert_eq!(label.location.line, 2);
+        assert_eq!(label.message, "prop 'start' declared here as int");
+    }
+
+    #[test]
+    fn test_memo_rejects_closure_parameter() {
+        let source = "@memo\nfn apply(n: i32,\n         f: fn(i32) -> i32) -> i32 {\n    return n;\n}\n";
+
+        let error = check_source(source).expect_err("closures can't be cache keys");
+        assert!(error.to_string().contains("@memo function 'apply' can't cache calls: parameter 'f' is a closure"));
+        assert_eq!(single_label(&error).location.line, 3);
+    }
+
+    const SERVER_CALLS: &str = "@server\nfn get_a() -> i32 {\n    return 1;\n}\n\n@server\nfn get_b() -> i32 {\n    return 2;\n}\n\n@server\nfn get_c() -> i32 {\n    return 3;\n}\n\n";
+
+    #[test]
+    fn test_promise_all_infers_element_type() {
+        let ok = format!("{}fn total(xs: Vec<i32>) -> i32 {{\n    return xs[0];\n}}\n\nasync fn load() -> i32 {{\n    let results = await Promise::all(vec![get_a(), get_b(), get_c()]);\n    return total(results);\n}}\n", SERVER_CALLS);
+        assert!(check_source(&ok).is_ok(), "got {:?}", check_source(&ok));
+
+        let mismatch = format!("{}fn names(xs: Vec<String>) {{\n}}\n\nasync fn load() {{\n    let results = await Promise::all(vec![get_a(), get_b(), get_c()]);\n    names(results);\n}}\n", SERVER_CALLS);
+        let error = check_source(&mismatch).expect_err("results are ints");
+        assert!(error.to_string().contains("expected string[], got int[]"), "got {}", error);
+    }
+
+    #[test]
+    fn test_promise_race_with_sleep_timeout() {
+        let source = format!("{}fn show(n: i32) {{\n}}\n\nasync fn load() {{\n    let winner = await Promise::race(vec![get_a(), sleep(500).then(|_| -1)]);\n    show(winner);\n    let late = sleep(10).then(|_| \"late\");\n    show(await late);\n}}\n", SERVER_CALLS);
+
+        let error = check_source(&source).expect_err("the second promise resolves to a string");
+        let errors = error.errors();
+        assert_eq!(errors.len(), 1, "got {:?}", errors);
+        assert!(errors[0].to_string().contains("expected int, got string"));
+    }
+
+    #[test]
+    fn test_await_requires_promise() {
+        let source = "extern \"js\" {\n    fn fetch_text(url: String) -> Promise<String>;\n}\n\nasync fn count() -> i32 {\n    return 3;\n}\n\nasync fn load() {\n    let text = await fetch_text(\"/hello\");\n    let n = await count();\n    let m = await n;\n}\n";
+
+        let error = check_source(source).expect_err("n is already an int");
+        let errors = error.errors();
+        assert_eq!(errors.len(), 1, "got {:?}", errors);
+        assert!(errors[0].to_string().contains("Cannot await a value of type int"));
+    }
+
+    #[test]
+    fn test_event_bus_payload_type() {
+        let source = "component Toasts() {\n    let bus = EventBus::channel::<String>(\"toasts\");\n    bus.subscribe(|message| message);\n    bus.emit(\"Saved\");\n    bus.emit(42);\n}\n\ncomponent Ticker() {\n    let ticks: EventBus<i32> = EventBus::new();\n    let sub = ticks.subscribe(|n| n + 1);\n    on_cleanup(|| sub.unsubscribe());\n    ticks.emit(\"tick\");\n}\n";
+
+        let error = check_source(source).expect_err("payload mismatches");
+        let messages: Vec<String> = error.errors().iter().map(|e| e.to_string()).collect();
+        assert_eq!(messages.len(), 2, "got {:?}", messages);
+        assert!(messages[0].contains("Cannot emit int on an EventBus<string>"), "{}", messages[0]);
+        assert!(messages[1].contains("Cannot emit string on an EventBus<int>"), "{}", messages[1]);
+
+        // Untyped buses accept anything
+        check_source("fn notify() {\n    let bus = EventBus::new();\n    bus.emit(1);\n    bus.emit(\"two\");\n}\n").unwrap();
+    }
+
+    #[test]
+    fn test_js_value_is_opaque() {
+        let externs = "extern \"js\" {\n    fn current_user() -> JsValue;\n}\n\n";
+        let source = format!(
+            "{}fn age() -> f64 {{\n    let user = current_user();\n    let years: f64 = user.get(\"age\");\n    return years;\n}}\n\nfn name() -> String {{\n    return current_user().name;\n}}\n\nfn id() -> f64 {{\n    return current_user().get(1).as_f64().unwrap_or(0.0);\n}}\n",
+            externs
+        );
+
+        let error = check_source(&source).expect_err("implicit conversions");
+        let messages: Vec<String> = error.errors().iter().map(|e| e.to_string()).collect();
+        assert_eq!(messages.len(), 3, "got {:?}", messages);
+        assert!(messages[0].contains("A JsValue is not a float; convert it explicitly with .as_f64()"), "{}", messages[0]);
+        assert!(messages[1].contains("Cannot read `.name` on a JsValue directly; use .get(\"name\")"), "{}", messages[1]);
+        assert!(messages[2].contains("JsValue .get() expects string, got int"), "{}", messages[2]);
+
+        // Accessors give typed values back
+        let source = format!(
+            "{}fn age() -> f64 {{\n    let user = current_user();\n    let title = user.get(\"title\").as_string().unwrap_or(\"\");\n    let first = user.get(\"tags\").index(0);\n    let greet = user.get(\"greet\").call(vec![first]);\n    if user.get(\"manager\").is_null() {{\n        return 0.0;\n    }}\n    return user.get(\"age\").as_f64().unwrap_or(0.0);\n}}\n",
+            externs
+        );
+        check_source(&source).unwrap();
+    }
+
+    #[test]
+    fn test_classes_macro_condition_is_bool() {
+        let error = check_source("component Tab() {\n    let count = 3;\n    return <a class={classes!(\"tab\", \"badge\" => count)}>Inbox</a>;\n}\n").expect_err("count is not a bool");
+        assert!(error.to_string().contains("classes! condition must be bool, got int"), "{}", error);
+
+        check_source("component Tab(count: i32, extra: String) {\n    return <a class={classes!(\"tab\", \"badge\" => count > 0, extra)}>Inbox</a>;\n}\n").unwrap();
+    }
+
+    #[test]
+    fn test_context_value_type() {
+        let source = "component Label() {\n    let mode_ctx = create_context(\"light\");\n    let mode = use_context(mode_ctx);\n    let wrong = use_context(mode);\n    return <Provide context={mode_ctx} value={42}><p>{mode}</p></Provide>;\n}\n\nfn sizes() {\n    let size_ctx = create_context::<i32>(\"large\");\n}\n";
+
+        let error = check_source(source).expect_err("context types mismatch");
+        let messages: Vec<String> = error.errors().iter().map(|e| e.to_string()).collect();
+        assert_eq!(messages.len(), 3, "got {:?}", messages);
+        assert!(messages[0].contains("use_context expects a Context, got string"), "{}", messages[0]);
+        assert!(messages[1].contains("<Provide> value for a Context<string> must be string, got int"), "{}", messages[1]);
+        assert!(messages[2].contains("Default value of a Context<int> must be int, got string"), "{}", messages[2]);
+
+        // Signals can be provided, and the consumer reads the context's type back
+        check_source("component App() {\n    let mode_ctx = create_context(\"light\");\n    let mode = signal(\"dark\");\n    let current = use_context(mode_ctx) + \"!\";\n    return <Provide context={mode_ctx} value={mode}><p>{current}</p></Provide>;\n}\n").unwrap();
+
+        // Contexts shared between components live at the top level
+        check_source("const mode_ctx = create_context(\"light\");\n\ncomponent Label() {\n    let mode = use_context(mode_ctx);\n    return <span>{mode}</span>;\n}\n\ncomponent App() {\n    return <Provide context={mode_ctx} value={\"dark\"}><Label /></Provide>;\n}\n").unwrap();
+    }
 }
@@ -13,6 +13,9 @@ pub enum Type {
     Bool,
     Void,
     Any,
+    // Result of an expression that already failed to type check; unifies with
+    // everything so one mistake doesn't cascade into follow-on errors
+    Error,
 
     // Component types
     Component(Vec<Type>), // Component with prop types
@@ -36,6 +39,22 @@ pub enum Type {
     // Optional type
     Option(Box<Type>),
 
+    // Native JavaScript promise resolving to the inner type
+    Promise(Box<Type>),
+
+    // Publish/subscribe channel carrying values of the inner type
+    EventBus(Box<Type>),
+
+    // Value handed down the component tree by <Provide>, read with use_context
+    Context(Box<Type>),
+
+    // Untyped value from extern JavaScript, read through runtime-checked accessors
+    JsValue,
+
+    // Handle a css! block evaluates to: a string field per class it names,
+    // holding the class's scoped name
+    Styles(Vec<String>),
+
     // Type variable (for inference)
     Var(usize),
 
@@ -52,6 +71,7 @@ impl fmt::Display for Type {
             Type::Bool => write!(f, "bool"),
             Type::Void => write!(f, "void"),
             Type::Any => write!(f, "any"),
+            Type::Error => write!(f, "{{error}}"),
             Type::Component(props) => {
                 write!(f, "Component<")?;
                 for (i, prop) in props.iter().enumerate() {
@@ -94,6 +114,11 @@ impl fmt::Display for Type {
                 Ok(())
             }
             Type::Option(inner) => write!(f, "{}?", inner),
+            Type::Promise(inner) => write!(f, "Promise<{}>", inner),
+            Type::EventBus(inner) => write!(f, "EventBus<{}>", inner),
+            Type::Context(inner) => write!(f, "Context<{}>", inner),
+            Type::JsValue => write!(f, "JsValue"),
+            Type::Styles(classes) => write!(f, "Styles {{ {} }}", classes.join(", ")),
             Type::Var(id) => write!(f, "τ{}", id),
             Type::Named(name) => write!(f, "{}", name),
         }
@@ -136,6 +161,15 @@ impl Type {
             // Arrays
             (Type::Array(a), Type::Array(b)) => a.is_compatible_with(b),
 
+            // Promises
+            (Type::Promise(a), Type::Promise(b)) => a.is_compatible_with(b),
+
+            // Event buses
+            (Type::EventBus(a), Type::EventBus(b)) => a.is_compatible_with(b),
+
+            // Contexts
+            (Type::Context(a), Type::Context(b)) => a.is_compatible_with(b),
+
             _ => false,
         }
     }
@@ -174,6 +208,21 @@ impl Type {
         Type::Option(Box::new(inner_type))
     }
 
+    /// Create a promise type
+    pub fn promise(inner_type: Type) -> Self {
+        Type::Promise(Box::new(inner_type))
+    }
+
+    /// Create an event bus type
+    pub fn event_bus(payload: Type) -> Self {
+        Type::EventBus(Box::new(payload))
+    }
+
+    /// Create a context type
+    pub fn context(value: Type) -> Self {
+        Type::Context(Box::new(value))
+    }
+
     /// Get free type variables in a type
     pub fn free_vars(&self) -> HashSet<usize> {
         match self {
@@ -183,7 +232,7 @@ impl Type {
                 set
             }
             Type::Array(inner) => inner.free_vars(),
-            Type::Option(inner) => inner.free_vars(),
+            Type::Option(inner) | Type::Promise(inner) | Type::EventBus(inner) | Type::Context(inner) => inner.free_vars(),
             Type::Function { params, return_type } => {
                 let mut set = HashSet::new();
                 for param in params {
@@ -211,10 +260,21 @@ impl Type {
     }
 }
 
+/// What a binding's value is reactively, where its type alone doesn't say:
+/// signals and computeds are both typed Any
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reactivity {
+    Signal,
+    Computed,
+    /// A component prop declared with a type other than Signal
+    Plain,
+}
+
 /// Type environment for tracking variable types in scopes
 #[derive(Debug, Clone)]
 pub struct TypeEnv {
     scopes: Vec<HashMap<String, TypeScheme>>,
+    reactivity: Vec<HashMap<String, Reactivity>>,  // Parallel to scopes
     next_var_id: usize,
 }
 
@@ -222,6 +282,7 @@ impl TypeEnv {
     pub fn new() -> Self {
         TypeEnv {
             scopes: vec![HashMap::new()],
+            reactivity: vec![HashMap::new()],
             next_var_id: 0,
         }
     }
@@ -229,24 +290,45 @@ impl TypeEnv {
     /// Enter a new scope
     pub fn push_scope(&mut self) {
         self.scopes.push(HashMap::new());
+        self.reactivity.push(HashMap::new());
     }
 
     /// Exit the current scope
     pub fn pop_scope(&mut self) {
         if self.scopes.len() > 1 {
             self.scopes.pop();
+            self.reactivity.pop();
         }
     }
 
     /// Add a variable binding in the current scope (monomorphic)
     pub fn bind(&mut self, name: String, ty: Type) {
+        if let Some(reactivity) = self.reactivity.last_mut() {
+            reactivity.remove(&name);
+        }
         if let Some(scope) = self.scopes.last_mut() {
             scope.insert(name, TypeScheme::monomorphic(ty));
         }
     }
 
+    /// Record what the binding just made for `name` holds
+    pub fn mark_reactivity(&mut self, name: &str, reactivity: Reactivity) {
+        if let Some(scope) = self.reactivity.last_mut() {
+            scope.insert(name.to_string(), reactivity);
+        }
+    }
+
+    /// What the binding `name` resolves to holds, if that was recorded
+    pub fn reactivity(&self, name: &str) -> Option<Reactivity> {
+        let depth = self.scopes.iter().rposition(|scope| scope.contains_key(name))?;
+        self.reactivity[depth].get(name).copied()
+    }
+
     /// Add a polymorphic variable binding in the current scope
     pub fn bind_scheme(&mut self, name: String, scheme: TypeScheme) {
+        if let Some(reactivity) = self.reactivity.last_mut() {
+            reactivity.remove(&name);
+        }
         if let Some(scope) = self.scopes.last_mut() {
             scope.insert(name, scheme);
         }
@@ -262,6 +344,17 @@ impl TypeEnv {
         None
     }
 
+    /// Names visible from the current scope, innermost scope first
+    pub fn visible_names(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        for scope in self.scopes.iter().rev() {
+            let mut scope_names: Vec<&str> = scope.keys().map(String::as_str).collect();
+            scope_names.sort_unstable();
+            names.extend(scope_names);
+        }
+        names
+    }
+
     /// Look up a variable's type (for backwards compatibility)
     pub fn lookup(&self, name: &str) -> Option<Type> {
         self.lookup_scheme(name).map(|scheme| {
@@ -359,6 +452,9 @@ impl Substitution {
             }
             Type::Array(inner) => Type::Array(Box::new(self.apply_with_depth(inner, depth + 1))),
             Type::Option(inner) => Type::Option(Box::new(self.apply_with_depth(inner, depth + 1))),
+            Type::Promise(inner) => Type::Promise(Box::new(self.apply_with_depth(inner, depth + 1))),
+            Type::EventBus(inner) => Type::EventBus(Box::new(self.apply_with_depth(inner, depth + 1))),
+            Type::Context(inner) => Type::Context(Box::new(self.apply_with_depth(inner, depth + 1))),
             Type::Function { params, return_type } => Type::Function {
                 params: params.iter().map(|p| self.apply_with_depth(p, depth + 1)).collect(),
                 return_type: Box::new(self.apply_with_depth(return_type, depth + 1)),
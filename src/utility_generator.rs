@@ -140,7 +140,18 @@ impl UtilityGenerator {
         // Scan attributes for "class" or "className"
         for attr in &jsx.opening_tag.attributes {
             if attr.name.value == "class" || attr.name.value == "className" {
-                if let Expression::StringLiteral(class_str) = &attr.value {
+                let literals: Vec<&String> = match &attr.value {
+                    Expression::StringLiteral(class_str) => vec![class_str],
+                    // Both the always-on and the conditional literals of classes!
+                    Expression::Classes(classes) => classes.entries.iter()
+                        .filter_map(|entry| match &entry.class {
+                            Expression::StringLiteral(class_str) => Some(class_str),
+                            _ => None,
+                        })
+                        .collect(),
+                    _ => Vec::new(),
+                };
+                for class_str in literals {
                     // Split by whitespace and collect each class name
                     for class_name in class_str.split_whitespace() {
                         self.used_utilities.insert(class_name.to_string());
@@ -164,6 +175,17 @@ impl UtilityGenerator {
         }
     }
 
+    /// Generate minified CSS for all used utilities, whatever the config
+    /// says, for release builds
+    pub fn generate_minified_css(&mut self) -> String {
+        let (minify, metrics) = (self.config.css.minify, self.metrics.clone());
+        self.config.css.minify = true;
+        let css = self.generate_css();
+        self.config.css.minify = minify;
+        self.metrics = metrics;
+        css
+    }
+
     /// Generate CSS for all used utilities
     pub fn generate_css(&mut self) -> String {
         if !self.config.css.utilities {
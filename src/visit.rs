@@ -0,0 +1,1125 @@
+//! AST visitors for tools built on the compiler (formatters, lint plugins, codemods)
+//!
+//! `Visit` walks a borrowed tree; `VisitMut` (in `visit_mut`) walks a mutable
+//! one so it can be rewritten in place. Both are generated from one macro
+//! (`make_visitor!`), so they cover the same nodes in the same order.
+//!
+//! Every method has a default that recurses into the node's children through
+//! the matching `walk_*` function. Override only the methods you need, and
+//! call the `walk_*` function from an override to keep recursing below it.
+//! A node kind added to the AST gets a new method with a default, so existing
+//! visitors keep compiling.
+
+use crate::ast::*;
+
+macro_rules! make_visitor {
+    ($visitor:ident $(, $mut:tt)?) => {
+        pub trait $visitor {
+            fn visit_program(&mut self, program: & $($mut)? Program) {
+                walk_program(self, program);
+            }
+
+            // --- Statements and items ---
+
+            fn visit_statement(&mut self, statement: & $($mut)? Statement) {
+                walk_statement(self, statement);
+            }
+
+            fn visit_block(&mut self, block: & $($mut)? BlockStatement) {
+                walk_block(self, block);
+            }
+
+            fn visit_use(&mut self, use_stmt: & $($mut)? UseStatement) {
+                walk_use(self, use_stmt);
+            }
+
+            fn visit_use_tree(&mut self, tree: & $($mut)? UseTree) {
+                walk_use_tree(self, tree);
+            }
+
+            fn visit_let(&mut self, let_stmt: & $($mut)? LetStatement) {
+                walk_let(self, let_stmt);
+            }
+
+            fn visit_const(&mut self, const_decl: & $($mut)? ConstDeclaration) {
+                walk_const(self, const_decl);
+            }
+
+            fn visit_assignment(&mut self, assignment: & $($mut)? AssignmentStatement) {
+                walk_assignment(self, assignment);
+            }
+
+            fn visit_return(&mut self, ret: & $($mut)? ReturnStatement) {
+                walk_return(self, ret);
+            }
+
+            fn visit_if(&mut self, if_stmt: & $($mut)? IfStatement) {
+                walk_if(self, if_stmt);
+            }
+
+            fn visit_while(&mut self, while_stmt: & $($mut)? WhileStatement) {
+                walk_while(self, while_stmt);
+            }
+
+            fn visit_for(&mut self, for_stmt: & $($mut)? ForStatement) {
+                walk_for(self, for_stmt);
+            }
+
+            fn visit_for_in(&mut self, for_in: & $($mut)? ForInStatement) {
+                walk_for_in(self, for_in);
+            }
+
+            fn visit_loop(&mut self, loop_stmt: & $($mut)? LoopStatement) {
+                walk_loop(self, loop_stmt);
+            }
+
+            fn visit_macro_invocation(&mut self, invocation: & $($mut)? MacroInvocation) {
+                walk_macro_invocation(self, invocation);
+            }
+
+            fn visit_struct(&mut self, def: & $($mut)? StructDefinition) {
+                walk_struct(self, def);
+            }
+
+            fn visit_enum(&mut self, def: & $($mut)? EnumDefinition) {
+                walk_enum(self, def);
+            }
+
+            fn visit_enum_variant(&mut self, variant: & $($mut)? EnumVariant) {
+                walk_enum_variant(self, variant);
+            }
+
+            fn visit_function(&mut self, func: & $($mut)? FunctionDefinition) {
+                walk_function(self, func);
+            }
+
+            fn visit_function_parameter(&mut self, param: & $($mut)? FunctionParameter) {
+                walk_function_parameter(self, param);
+            }
+
+            fn visit_component(&mut self, comp: & $($mut)? ComponentDefinition) {
+                walk_component(self, comp);
+            }
+
+            fn visit_extern_block(&mut self, block: & $($mut)? ExternBlock) {
+                walk_extern_block(self, block);
+            }
+
+            fn visit_function_declaration(&mut self, decl: & $($mut)? FunctionDeclaration) {
+                walk_function_declaration(self, decl);
+            }
+
+            fn visit_impl_block(&mut self, block: & $($mut)? ImplBlock) {
+                walk_impl_block(self, block);
+            }
+
+            fn visit_impl_method(&mut self, method: & $($mut)? ImplMethod) {
+                walk_impl_method(self, method);
+            }
+
+            fn visit_trait(&mut self, def: & $($mut)? TraitDefinition) {
+                walk_trait(self, def);
+            }
+
+            fn visit_trait_method(&mut self, method: & $($mut)? TraitMethod) {
+                walk_trait_method(self, method);
+            }
+
+            fn visit_style_block(&mut self, style: & $($mut)? StyleBlock) {
+                walk_style_block(self, style);
+            }
+
+            fn visit_style_property(&mut self, _property: & $($mut)? StyleProperty) {}
+
+            fn visit_nested_selector(&mut self, nested: & $($mut)? NestedSelector) {
+                walk_nested_selector(self, nested);
+            }
+
+            fn visit_theme_block(&mut self, theme: & $($mut)? ThemeBlock) {
+                walk_theme_block(self, theme);
+            }
+
+            fn visit_theme_property(&mut self, _property: & $($mut)? ThemeProperty) {}
+
+            // --- Names, types and patterns ---
+
+            fn visit_identifier(&mut self, _ident: & $($mut)? Identifier) {}
+
+            fn visit_lifetime(&mut self, _lifetime: & $($mut)? Lifetime) {}
+
+            fn visit_type_param(&mut self, param: & $($mut)? TypeParam) {
+                walk_type_param(self, param);
+            }
+
+            fn visit_type(&mut self, ty: & $($mut)? TypeExpression) {
+                walk_type(self, ty);
+            }
+
+            fn visit_pattern(&mut self, pattern: & $($mut)? Pattern) {
+                walk_pattern(self, pattern);
+            }
+
+            // --- Expressions ---
+
+            fn visit_expression(&mut self, expr: & $($mut)? Expression) {
+                walk_expression(self, expr);
+            }
+
+            fn visit_array_literal(&mut self, array: & $($mut)? ArrayLiteral) {
+                walk_array_literal(self, array);
+            }
+
+            fn visit_tuple_literal(&mut self, tuple: & $($mut)? TupleLiteral) {
+                walk_tuple_literal(self, tuple);
+            }
+
+            fn visit_struct_literal(&mut self, literal: & $($mut)? StructLiteral) {
+                walk_struct_literal(self, literal);
+            }
+
+            fn visit_prefix(&mut self, prefix: & $($mut)? PrefixExpression) {
+                walk_prefix(self, prefix);
+            }
+
+            fn visit_spread(&mut self, spread: & $($mut)? SpreadExpression) {
+                walk_spread(self, spread);
+            }
+
+            fn visit_infix(&mut self, infix: & $($mut)? InfixExpression) {
+                walk_infix(self, infix);
+            }
+
+            fn visit_field_access(&mut self, access: & $($mut)? FieldAccessExpression) {
+                walk_field_access(self, access);
+            }
+
+            fn visit_index(&mut self, index: & $($mut)? IndexExpression) {
+                walk_index(self, index);
+            }
+
+            fn visit_match(&mut self, match_expr: & $($mut)? MatchExpression) {
+                walk_match(self, match_expr);
+            }
+
+            fn visit_match_arm(&mut self, arm: & $($mut)? MatchArm) {
+                walk_match_arm(self, arm);
+            }
+
+            fn visit_if_expression(&mut self, if_expr: & $($mut)? IfExpression) {
+                walk_if_expression(self, if_expr);
+            }
+
+            fn visit_function_call(&mut self, call: & $($mut)? FunctionCall) {
+                walk_function_call(self, call);
+            }
+
+            fn visit_macro_call(&mut self, call: & $($mut)? MacroCall) {
+                walk_macro_call(self, call);
+            }
+
+            fn visit_lambda(&mut self, lambda: & $($mut)? LambdaExpression) {
+                walk_lambda(self, lambda);
+            }
+
+            fn visit_lambda_parameter(&mut self, param: & $($mut)? LambdaParameter) {
+                walk_lambda_parameter(self, param);
+            }
+
+            fn visit_captured_variable(&mut self, capture: & $($mut)? CapturedVariable) {
+                walk_captured_variable(self, capture);
+            }
+
+            fn visit_borrow(&mut self, borrow: & $($mut)? BorrowExpression) {
+                walk_borrow(self, borrow);
+            }
+
+            fn visit_mutable_borrow(&mut self, borrow: & $($mut)? MutableBorrowExpression) {
+                walk_mutable_borrow(self, borrow);
+            }
+
+            fn visit_dereference(&mut self, deref: & $($mut)? DereferenceExpression) {
+                walk_dereference(self, deref);
+            }
+
+            fn visit_range(&mut self, range: & $($mut)? RangeExpression) {
+                walk_range(self, range);
+            }
+
+            fn visit_try_operator(&mut self, try_expr: & $($mut)? TryOperatorExpression) {
+                walk_try_operator(self, try_expr);
+            }
+
+            fn visit_ternary(&mut self, ternary: & $($mut)? TernaryExpression) {
+                walk_ternary(self, ternary);
+            }
+
+            fn visit_type_cast(&mut self, cast: & $($mut)? TypeCastExpression) {
+                walk_type_cast(self, cast);
+            }
+
+            fn visit_await(&mut self, await_expr: & $($mut)? AwaitExpression) {
+                walk_await(self, await_expr);
+            }
+
+            fn visit_signal(&mut self, signal: & $($mut)? SignalExpression) {
+                walk_signal(self, signal);
+            }
+
+            fn visit_computed(&mut self, computed: & $($mut)? ComputedExpression) {
+                walk_computed(self, computed);
+            }
+
+            fn visit_effect(&mut self, effect: & $($mut)? EffectExpression) {
+                walk_effect(self, effect);
+            }
+
+            fn visit_batch(&mut self, batch: & $($mut)? BatchExpression) {
+                walk_batch(self, batch);
+            }
+
+            // --- JSX ---
+
+            fn visit_jsx_element(&mut self, jsx: & $($mut)? JsxElement) {
+                walk_jsx_element(self, jsx);
+            }
+
+            fn visit_jsx_opening_tag(&mut self, tag: & $($mut)? JsxOpeningTag) {
+                walk_jsx_opening_tag(self, tag);
+            }
+
+            fn visit_jsx_attribute(&mut self, attr: & $($mut)? JsxAttribute) {
+                walk_jsx_attribute(self, attr);
+            }
+
+            fn visit_jsx_child(&mut self, child: & $($mut)? JsxChild) {
+                walk_jsx_child(self, child);
+            }
+
+            // --- Styles ---
+
+            fn visit_css(&mut self, css: & $($mut)? CssExpression) {
+                walk_css(self, css);
+            }
+
+            fn visit_css_rule(&mut self, rule: & $($mut)? CssRule) {
+                walk_css_rule(self, rule);
+            }
+
+            fn visit_css_selector(&mut self, _selector: & $($mut)? CssSelector) {}
+
+            fn visit_css_media_query(&mut self, query: & $($mut)? CssMediaQuery) {
+                walk_css_media_query(self, query);
+            }
+
+            fn visit_css_container_query(&mut self, query: & $($mut)? CssContainerQuery) {
+                walk_css_container_query(self, query);
+            }
+
+            fn visit_css_keyframes(&mut self, keyframes: & $($mut)? CssKeyframes) {
+                walk_css_keyframes(self, keyframes);
+            }
+
+            fn visit_css_keyframe_rule(&mut self, frame: & $($mut)? CssKeyframeRule) {
+                walk_css_keyframe_rule(self, frame);
+            }
+
+            fn visit_css_declaration(&mut self, decl: & $($mut)? CssDeclaration) {
+                walk_css_declaration(self, decl);
+            }
+
+            fn visit_css_value(&mut self, value: & $($mut)? CssValue) {
+                walk_css_value(self, value);
+            }
+
+            fn visit_inline_style(&mut self, style: & $($mut)? InlineStyle) {
+                walk_inline_style(self, style);
+            }
+
+            fn visit_inline_style_declaration(&mut self, decl: & $($mut)? InlineStyleDeclaration) {
+                walk_inline_style_declaration(self, decl);
+            }
+
+            fn visit_template(&mut self, template: & $($mut)? TemplateLiteral) {
+                walk_template(self, template);
+            }
+
+            fn visit_classes(&mut self, classes: & $($mut)? ClassesMacro) {
+                walk_classes(self, classes);
+            }
+
+            fn visit_class_entry(&mut self, entry: & $($mut)? ClassEntry) {
+                walk_class_entry(self, entry);
+            }
+        }
+
+        pub fn walk_program<V: $visitor + ?Sized>(visitor: &mut V, program: & $($mut)? Program) {
+            for statement in & $($mut)? program.statements {
+                visitor.visit_statement(statement);
+            }
+        }
+
+        pub fn walk_statement<V: $visitor + ?Sized>(visitor: &mut V, statement: & $($mut)? Statement) {
+            match statement {
+                Statement::Use(use_stmt) => visitor.visit_use(use_stmt),
+                Statement::Let(let_stmt) => visitor.visit_let(let_stmt),
+                Statement::Const(const_decl) => visitor.visit_const(const_decl),
+                Statement::Assignment(assignment) => visitor.visit_assignment(assignment),
+                Statement::Return(ret) => visitor.visit_return(ret),
+                Statement::Expression(expr) => visitor.visit_expression(expr),
+                Statement::If(if_stmt) => visitor.visit_if(if_stmt),
+                Statement::While(while_stmt) => visitor.visit_while(while_stmt),
+                Statement::For(for_stmt) => visitor.visit_for(for_stmt),
+                Statement::ForIn(for_in) => visitor.visit_for_in(for_in),
+                Statement::Loop(loop_stmt) => visitor.visit_loop(loop_stmt),
+                Statement::Break | Statement::Continue => {}
+                Statement::MacroInvocation(invocation) => visitor.visit_macro_invocation(invocation),
+                Statement::Struct(def) => visitor.visit_struct(def),
+                Statement::Enum(def) => visitor.visit_enum(def),
+                Statement::Function(func) => visitor.visit_function(func),
+                Statement::Component(comp) => visitor.visit_component(comp),
+                Statement::ExternBlock(block) => visitor.visit_extern_block(block),
+                Statement::ImplBlock(block) => visitor.visit_impl_block(block),
+                Statement::Trait(def) => visitor.visit_trait(def),
+                Statement::Style(style) => visitor.visit_style_block(style),
+                Statement::Theme(theme) => visitor.visit_theme_block(theme),
+            }
+        }
+
+        pub fn walk_block<V: $visitor + ?Sized>(visitor: &mut V, block: & $($mut)? BlockStatement) {
+            for statement in & $($mut)? block.statements {
+                visitor.visit_statement(statement);
+            }
+        }
+
+        pub fn walk_use<V: $visitor + ?Sized>(visitor: &mut V, use_stmt: & $($mut)? UseStatement) {
+            for segment in & $($mut)? use_stmt.path {
+                visitor.visit_identifier(segment);
+            }
+            for tree in & $($mut)? use_stmt.imports {
+                visitor.visit_use_tree(tree);
+            }
+        }
+
+        pub fn walk_use_tree<V: $visitor + ?Sized>(visitor: &mut V, tree: & $($mut)? UseTree) {
+            match tree {
+                UseTree::Name { name, alias } => {
+                    visitor.visit_identifier(name);
+                    if let Some(alias) = alias {
+                        visitor.visit_identifier(alias);
+                    }
+                }
+                UseTree::Glob => {}
+                UseTree::Nested { path, items } => {
+                    for segment in path {
+                        visitor.visit_identifier(segment);
+                    }
+                    for item in items {
+                        visitor.visit_use_tree(item);
+                    }
+                }
+            }
+        }
+
+        pub fn walk_let<V: $visitor + ?Sized>(visitor: &mut V, let_stmt: & $($mut)? LetStatement) {
+            visitor.visit_pattern(& $($mut)? let_stmt.pattern);
+            if let Some(ty) = & $($mut)? let_stmt.type_annotation {
+                visitor.visit_type(ty);
+            }
+            visitor.visit_expression(& $($mut)? let_stmt.value);
+        }
+
+        pub fn walk_const<V: $visitor + ?Sized>(visitor: &mut V, const_decl: & $($mut)? ConstDeclaration) {
+            visitor.visit_identifier(& $($mut)? const_decl.name);
+            if let Some(ty) = & $($mut)? const_decl.type_annotation {
+                visitor.visit_type(ty);
+            }
+            visitor.visit_expression(& $($mut)? const_decl.value);
+        }
+
+        pub fn walk_assignment<V: $visitor + ?Sized>(visitor: &mut V, assignment: & $($mut)? AssignmentStatement) {
+            visitor.visit_expression(& $($mut)? assignment.target);
+            visitor.visit_expression(& $($mut)? assignment.value);
+        }
+
+        pub fn walk_return<V: $visitor + ?Sized>(visitor: &mut V, ret: & $($mut)? ReturnStatement) {
+            visitor.visit_expression(& $($mut)? ret.value);
+        }
+
+        pub fn walk_if<V: $visitor + ?Sized>(visitor: &mut V, if_stmt: & $($mut)? IfStatement) {
+            visitor.visit_expression(& $($mut)? if_stmt.condition);
+            visitor.visit_block(& $($mut)? if_stmt.then_branch);
+            if let Some(else_branch) = & $($mut)? if_stmt.else_branch {
+                visitor.visit_statement(else_branch);
+            }
+        }
+
+        pub fn walk_while<V: $visitor + ?Sized>(visitor: &mut V, while_stmt: & $($mut)? WhileStatement) {
+            visitor.visit_expression(& $($mut)? while_stmt.condition);
+            visitor.visit_block(& $($mut)? while_stmt.body);
+        }
+
+        pub fn walk_for<V: $visitor + ?Sized>(visitor: &mut V, for_stmt: & $($mut)? ForStatement) {
+            if let Some(init) = & $($mut)? for_stmt.init {
+                visitor.visit_statement(init);
+            }
+            visitor.visit_expression(& $($mut)? for_stmt.condition);
+            if let Some(update) = & $($mut)? for_stmt.update {
+                visitor.visit_statement(update);
+            }
+            visitor.visit_block(& $($mut)? for_stmt.body);
+        }
+
+        pub fn walk_for_in<V: $visitor + ?Sized>(visitor: &mut V, for_in: & $($mut)? ForInStatement) {
+            visitor.visit_identifier(& $($mut)? for_in.variable);
+            visitor.visit_expression(& $($mut)? for_in.iterator);
+            visitor.visit_block(& $($mut)? for_in.body);
+        }
+
+        pub fn walk_loop<V: $visitor + ?Sized>(visitor: &mut V, loop_stmt: & $($mut)? LoopStatement) {
+            visitor.visit_block(& $($mut)? loop_stmt.body);
+        }
+
+        /// The invocation's input stays as tokens; only its name is visited
+        pub fn walk_macro_invocation<V: $visitor + ?Sized>(visitor: &mut V, invocation: & $($mut)? MacroInvocation) {
+            visitor.visit_identifier(& $($mut)? invocation.name);
+        }
+
+        pub fn walk_struct<V: $visitor + ?Sized>(visitor: &mut V, def: & $($mut)? StructDefinition) {
+            visitor.visit_identifier(& $($mut)? def.name);
+            for lifetime in & $($mut)? def.lifetime_params {
+                visitor.visit_lifetime(lifetime);
+            }
+            for param in & $($mut)? def.type_params {
+                visitor.visit_type_param(param);
+            }
+            for (name, ty) in & $($mut)? def.fields {
+                visitor.visit_identifier(name);
+                visitor.visit_type(ty);
+            }
+        }
+
+        pub fn walk_enum<V: $visitor + ?Sized>(visitor: &mut V, def: & $($mut)? EnumDefinition) {
+            visitor.visit_identifier(& $($mut)? def.name);
+            for lifetime in & $($mut)? def.lifetime_params {
+                visitor.visit_lifetime(lifetime);
+            }
+            for param in & $($mut)? def.type_params {
+                visitor.visit_type_param(param);
+            }
+            for variant in & $($mut)? def.variants {
+                visitor.visit_enum_variant(variant);
+            }
+        }
+
+        pub fn walk_enum_variant<V: $visitor + ?Sized>(visitor: &mut V, variant: & $($mut)? EnumVariant) {
+            visitor.visit_identifier(& $($mut)? variant.name);
+            if let Some(fields) = & $($mut)? variant.fields {
+                for (name, ty) in fields {
+                    visitor.visit_identifier(name);
+                    visitor.visit_type(ty);
+                }
+            }
+        }
+
+        pub fn walk_function<V: $visitor + ?Sized>(visitor: &mut V, func: & $($mut)? FunctionDefinition) {
+            visitor.visit_identifier(& $($mut)? func.name);
+            for lifetime in & $($mut)? func.lifetime_params {
+                visitor.visit_lifetime(lifetime);
+            }
+            for param in & $($mut)? func.type_params {
+                visitor.visit_type_param(param);
+            }
+            for param in & $($mut)? func.parameters {
+                visitor.visit_function_parameter(param);
+            }
+            if let Some(ty) = & $($mut)? func.return_type {
+                visitor.visit_type(ty);
+            }
+            visitor.visit_block(& $($mut)? func.body);
+        }
+
+        pub fn walk_function_parameter<V: $visitor + ?Sized>(visitor: &mut V, param: & $($mut)? FunctionParameter) {
+            visitor.visit_identifier(& $($mut)? param.name);
+            visitor.visit_type(& $($mut)? param.type_annotation);
+        }
+
+        pub fn walk_component<V: $visitor + ?Sized>(visitor: &mut V, comp: & $($mut)? ComponentDefinition) {
+            visitor.visit_identifier(& $($mut)? comp.name);
+            for param in & $($mut)? comp.parameters {
+                visitor.visit_function_parameter(param);
+            }
+            visitor.visit_block(& $($mut)? comp.body);
+        }
+
+        pub fn walk_extern_block<V: $visitor + ?Sized>(visitor: &mut V, block: & $($mut)? ExternBlock) {
+            for decl in & $($mut)? block.functions {
+                visitor.visit_function_declaration(decl);
+            }
+        }
+
+        pub fn walk_function_declaration<V: $visitor + ?Sized>(visitor: &mut V, decl: & $($mut)? FunctionDeclaration) {
+            visitor.visit_identifier(& $($mut)? decl.name);
+            for param in & $($mut)? decl.parameters {
+                visitor.visit_function_parameter(param);
+            }
+            if let Some(ty) = & $($mut)? decl.return_type {
+                visitor.visit_type(ty);
+            }
+        }
+
+        pub fn walk_impl_block<V: $visitor + ?Sized>(visitor: &mut V, block: & $($mut)? ImplBlock) {
+            if let Some(trait_name) = & $($mut)? block.trait_name {
+                visitor.visit_identifier(trait_name);
+            }
+            for lifetime in & $($mut)? block.lifetime_params {
+                visitor.visit_lifetime(lifetime);
+            }
+            for param in & $($mut)? block.type_params {
+                visitor.visit_type_param(param);
+            }
+            visitor.visit_identifier(& $($mut)? block.type_name);
+            for method in & $($mut)? block.methods {
+                visitor.visit_impl_method(method);
+            }
+        }
+
+        pub fn walk_impl_method<V: $visitor + ?Sized>(visitor: &mut V, method: & $($mut)? ImplMethod) {
+            visitor.visit_identifier(& $($mut)? method.name);
+            for param in & $($mut)? method.parameters {
+                visitor.visit_function_parameter(param);
+            }
+            if let Some(ty) = & $($mut)? method.return_type {
+                visitor.visit_type(ty);
+            }
+            visitor.visit_block(& $($mut)? method.body);
+        }
+
+        pub fn walk_trait<V: $visitor + ?Sized>(visitor: &mut V, def: & $($mut)? TraitDefinition) {
+            visitor.visit_identifier(& $($mut)? def.name);
+            for lifetime in & $($mut)? def.lifetime_params {
+                visitor.visit_lifetime(lifetime);
+            }
+            for param in & $($mut)? def.type_params {
+                visitor.visit_type_param(param);
+            }
+            for method in & $($mut)? def.methods {
+                visitor.visit_trait_method(method);
+            }
+        }
+
+        pub fn walk_trait_method<V: $visitor + ?Sized>(visitor: &mut V, method: & $($mut)? TraitMethod) {
+            visitor.visit_identifier(& $($mut)? method.name);
+            for param in & $($mut)? method.parameters {
+                visitor.visit_function_parameter(param);
+            }
+            if let Some(ty) = & $($mut)? method.return_type {
+                visitor.visit_type(ty);
+            }
+        }
+
+        pub fn walk_style_block<V: $visitor + ?Sized>(visitor: &mut V, style: & $($mut)? StyleBlock) {
+            visitor.visit_identifier(& $($mut)? style.name);
+            for property in & $($mut)? style.properties {
+                visitor.visit_style_property(property);
+            }
+            for nested in & $($mut)? style.nested {
+                visitor.visit_nested_selector(nested);
+            }
+        }
+
+        pub fn walk_nested_selector<V: $visitor + ?Sized>(visitor: &mut V, nested: & $($mut)? NestedSelector) {
+            for property in & $($mut)? nested.properties {
+                visitor.visit_style_property(property);
+            }
+        }
+
+        pub fn walk_theme_block<V: $visitor + ?Sized>(visitor: &mut V, theme: & $($mut)? ThemeBlock) {
+            visitor.visit_identifier(& $($mut)? theme.name);
+            for property in & $($mut)? theme.properties {
+                visitor.visit_theme_property(property);
+            }
+        }
+
+        pub fn walk_type_param<V: $visitor + ?Sized>(visitor: &mut V, param: & $($mut)? TypeParam) {
+            visitor.visit_identifier(& $($mut)? param.name);
+            for bound in & $($mut)? param.bounds {
+                visitor.visit_identifier(bound);
+            }
+        }
+
+        pub fn walk_type<V: $visitor + ?Sized>(visitor: &mut V, ty: & $($mut)? TypeExpression) {
+            match ty {
+                TypeExpression::Named(name) => visitor.visit_identifier(name),
+                TypeExpression::Generic(name, args) => {
+                    visitor.visit_identifier(name);
+                    for arg in args {
+                        visitor.visit_type(arg);
+                    }
+                }
+                TypeExpression::Tuple(elements) => {
+                    for element in elements {
+                        visitor.visit_type(element);
+                    }
+                }
+                TypeExpression::Reference(inner)
+                | TypeExpression::MutableReference(inner)
+                | TypeExpression::Slice(inner)
+                | TypeExpression::SizedArray(inner, _) => visitor.visit_type(inner),
+                TypeExpression::Function(params, ret) => {
+                    for param in params {
+                        visitor.visit_type(param);
+                    }
+                    visitor.visit_type(ret);
+                }
+            }
+        }
+
+        pub fn walk_pattern<V: $visitor + ?Sized>(visitor: &mut V, pattern: & $($mut)? Pattern) {
+            match pattern {
+                Pattern::Identifier(ident) => visitor.visit_identifier(ident),
+                Pattern::Tuple(patterns) => {
+                    for pattern in patterns {
+                        visitor.visit_pattern(pattern);
+                    }
+                }
+                Pattern::Literal(expr) => visitor.visit_expression(expr),
+                Pattern::Wildcard => {}
+                Pattern::EnumVariant { name, fields } => {
+                    visitor.visit_identifier(name);
+                    if let Some(fields) = fields {
+                        for field in fields {
+                            visitor.visit_pattern(field);
+                        }
+                    }
+                }
+            }
+        }
+
+        pub fn walk_expression<V: $visitor + ?Sized>(visitor: &mut V, expr: & $($mut)? Expression) {
+            match expr {
+                Expression::Identifier(ident) => visitor.visit_identifier(ident),
+                Expression::IntegerLiteral(_)
+                | Expression::FloatLiteral(_)
+                | Expression::StringLiteral(_)
+                | Expression::BoolLiteral(_)
+                | Expression::UnitLiteral => {}
+                Expression::ArrayLiteral(array) => visitor.visit_array_literal(array),
+                Expression::TupleLiteral(tuple) => visitor.visit_tuple_literal(tuple),
+                Expression::StructLiteral(literal) => visitor.visit_struct_literal(literal),
+                Expression::Prefix(prefix) => visitor.visit_prefix(prefix),
+                Expression::Spread(spread) => visitor.visit_spread(spread),
+                Expression::Infix(infix) => visitor.visit_infix(infix),
+                Expression::FieldAccess(access) => visitor.visit_field_access(access),
+                Expression::IndexAccess(index) => visitor.visit_index(index),
+                Expression::Match(match_expr) => visitor.visit_match(match_expr),
+                Expression::IfExpression(if_expr) => visitor.visit_if_expression(if_expr),
+                Expression::JsxElement(jsx) => visitor.visit_jsx_element(jsx),
+                Expression::FunctionCall(call) => visitor.visit_function_call(call),
+                Expression::MacroCall(call) => visitor.visit_macro_call(call),
+                Expression::Lambda(lambda) => visitor.visit_lambda(lambda),
+                Expression::Borrow(borrow) => visitor.visit_borrow(borrow),
+                Expression::MutableBorrow(borrow) => visitor.visit_mutable_borrow(borrow),
+                Expression::Dereference(deref) => visitor.visit_dereference(deref),
+                Expression::Range(range) => visitor.visit_range(range),
+                Expression::TryOperator(try_expr) => visitor.visit_try_operator(try_expr),
+                Expression::Ternary(ternary) => visitor.visit_ternary(ternary),
+                Expression::TypeCast(cast) => visitor.visit_type_cast(cast),
+                Expression::Await(await_expr) => visitor.visit_await(await_expr),
+                Expression::Block(block) => visitor.visit_block(block),
+                Expression::CssMacro(css) => visitor.visit_css(css),
+                Expression::InlineStyle(style) => visitor.visit_inline_style(style),
+                Expression::Classes(classes) => visitor.visit_classes(classes),
+                Expression::Template(template) => visitor.visit_template(template),
+                Expression::Signal(signal) => visitor.visit_signal(signal),
+                Expression::Computed(computed) => visitor.visit_computed(computed),
+                Expression::Effect(effect) => visitor.visit_effect(effect),
+                Expression::Batch(batch) => visitor.visit_batch(batch),
+            }
+        }
+
+        pub fn walk_array_literal<V: $visitor + ?Sized>(visitor: &mut V, array: & $($mut)? ArrayLiteral) {
+            for element in & $($mut)? array.elements {
+                visitor.visit_expression(element);
+            }
+        }
+
+        pub fn walk_tuple_literal<V: $visitor + ?Sized>(visitor: &mut V, tuple: & $($mut)? TupleLiteral) {
+            for element in & $($mut)? tuple.elements {
+                visitor.visit_expression(element);
+            }
+        }
+
+        pub fn walk_struct_literal<V: $visitor + ?Sized>(visitor: &mut V, literal: & $($mut)? StructLiteral) {
+            visitor.visit_identifier(& $($mut)? literal.name);
+            for (name, value) in & $($mut)? literal.fields {
+                visitor.visit_identifier(name);
+                visitor.visit_expression(value);
+            }
+        }
+
+        pub fn walk_prefix<V: $visitor + ?Sized>(visitor: &mut V, prefix: & $($mut)? PrefixExpression) {
+            visitor.visit_expression(& $($mut)? prefix.right);
+        }
+
+        pub fn walk_spread<V: $visitor + ?Sized>(visitor: &mut V, spread: & $($mut)? SpreadExpression) {
+            visitor.visit_expression(& $($mut)? spread.expression);
+        }
+
+        pub fn walk_infix<V: $visitor + ?Sized>(visitor: &mut V, infix: & $($mut)? InfixExpression) {
+            visitor.visit_expression(& $($mut)? infix.left);
+            visitor.visit_expression(& $($mut)? infix.right);
+        }
+
+        pub fn walk_field_access<V: $visitor + ?Sized>(visitor: &mut V, access: & $($mut)? FieldAccessExpression) {
+            visitor.visit_expression(& $($mut)? access.object);
+            visitor.visit_identifier(& $($mut)? access.field);
+        }
+
+        pub fn walk_index<V: $visitor + ?Sized>(visitor: &mut V, index: & $($mut)? IndexExpression) {
+            visitor.visit_expression(& $($mut)? index.array);
+            visitor.visit_expression(& $($mut)? index.index);
+        }
+
+        pub fn walk_match<V: $visitor + ?Sized>(visitor: &mut V, match_expr: & $($mut)? MatchExpression) {
+            visitor.visit_expression(& $($mut)? match_expr.scrutinee);
+            for arm in & $($mut)? match_expr.arms {
+                visitor.visit_match_arm(arm);
+            }
+        }
+
+        pub fn walk_match_arm<V: $visitor + ?Sized>(visitor: &mut V, arm: & $($mut)? MatchArm) {
+            for pattern in & $($mut)? arm.patterns {
+                visitor.visit_pattern(pattern);
+            }
+            visitor.visit_expression(& $($mut)? arm.body);
+        }
+
+        pub fn walk_if_expression<V: $visitor + ?Sized>(visitor: &mut V, if_expr: & $($mut)? IfExpression) {
+            visitor.visit_expression(& $($mut)? if_expr.condition);
+            visitor.visit_expression(& $($mut)? if_expr.then_expr);
+            if let Some(else_expr) = & $($mut)? if_expr.else_expr {
+                visitor.visit_expression(else_expr);
+            }
+        }
+
+        pub fn walk_function_call<V: $visitor + ?Sized>(visitor: &mut V, call: & $($mut)? FunctionCall) {
+            visitor.visit_expression(& $($mut)? call.function);
+            if let Some(type_params) = & $($mut)? call.type_params {
+                for ty in type_params {
+                    visitor.visit_type(ty);
+                }
+            }
+            for arg in & $($mut)? call.arguments {
+                visitor.visit_expression(arg);
+            }
+        }
+
+        pub fn walk_macro_call<V: $visitor + ?Sized>(visitor: &mut V, call: & $($mut)? MacroCall) {
+            visitor.visit_identifier(& $($mut)? call.name);
+            for arg in & $($mut)? call.arguments {
+                visitor.visit_expression(arg);
+            }
+        }
+
+        pub fn walk_lambda<V: $visitor + ?Sized>(visitor: &mut V, lambda: & $($mut)? LambdaExpression) {
+            for param in & $($mut)? lambda.parameters {
+                visitor.visit_lambda_parameter(param);
+            }
+            if let Some(ty) = & $($mut)? lambda.return_type {
+                visitor.visit_type(ty);
+            }
+            visitor.visit_expression(& $($mut)? lambda.body);
+            for capture in & $($mut)? lambda.captures {
+                visitor.visit_captured_variable(capture);
+            }
+        }
+
+        pub fn walk_lambda_parameter<V: $visitor + ?Sized>(visitor: &mut V, param: & $($mut)? LambdaParameter) {
+            visitor.visit_identifier(& $($mut)? param.name);
+            if let Some(ty) = & $($mut)? param.type_annotation {
+                visitor.visit_type(ty);
+            }
+        }
+
+        pub fn walk_captured_variable<V: $visitor + ?Sized>(visitor: &mut V, capture: & $($mut)? CapturedVariable) {
+            visitor.visit_identifier(& $($mut)? capture.name);
+        }
+
+        pub fn walk_borrow<V: $visitor + ?Sized>(visitor: &mut V, borrow: & $($mut)? BorrowExpression) {
+            visitor.visit_expression(& $($mut)? borrow.expression);
+        }
+
+        pub fn walk_mutable_borrow<V: $visitor + ?Sized>(visitor: &mut V, borrow: & $($mut)? MutableBorrowExpression) {
+            visitor.visit_expression(& $($mut)? borrow.expression);
+        }
+
+        pub fn walk_dereference<V: $visitor + ?Sized>(visitor: &mut V, deref: & $($mut)? DereferenceExpression) {
+            visitor.visit_expression(& $($mut)? deref.expression);
+        }
+
+        pub fn walk_range<V: $visitor + ?Sized>(visitor: &mut V, range: & $($mut)? RangeExpression) {
+            if let Some(start) = & $($mut)? range.start {
+                visitor.visit_expression(start);
+            }
+            if let Some(end) = & $($mut)? range.end {
+                visitor.visit_expression(end);
+            }
+        }
+
+        pub fn walk_try_operator<V: $visitor + ?Sized>(visitor: &mut V, try_expr: & $($mut)? TryOperatorExpression) {
+            visitor.visit_expression(& $($mut)? try_expr.expression);
+        }
+
+        pub fn walk_ternary<V: $visitor + ?Sized>(visitor: &mut V, ternary: & $($mut)? TernaryExpression) {
+            visitor.visit_expression(& $($mut)? ternary.condition);
+            visitor.visit_expression(& $($mut)? ternary.true_expr);
+            visitor.visit_expression(& $($mut)? ternary.false_expr);
+        }
+
+        pub fn walk_type_cast<V: $visitor + ?Sized>(visitor: &mut V, cast: & $($mut)? TypeCastExpression) {
+            visitor.visit_expression(& $($mut)? cast.expression);
+            visitor.visit_type(& $($mut)? cast.target_type);
+        }
+
+        pub fn walk_await<V: $visitor + ?Sized>(visitor: &mut V, await_expr: & $($mut)? AwaitExpression) {
+            visitor.visit_expression(& $($mut)? await_expr.expression);
+        }
+
+        pub fn walk_signal<V: $visitor + ?Sized>(visitor: &mut V, signal: & $($mut)? SignalExpression) {
+            if let Some(ty) = & $($mut)? signal.type_annotation {
+                visitor.visit_type(ty);
+            }
+            visitor.visit_expression(& $($mut)? signal.initial_value);
+        }
+
+        pub fn walk_computed<V: $visitor + ?Sized>(visitor: &mut V, computed: & $($mut)? ComputedExpression) {
+            if let Some(ty) = & $($mut)? computed.type_annotation {
+                visitor.visit_type(ty);
+            }
+            visitor.visit_expression(& $($mut)? computed.computation);
+        }
+
+        pub fn walk_effect<V: $visitor + ?Sized>(visitor: &mut V, effect: & $($mut)? EffectExpression) {
+            visitor.visit_expression(& $($mut)? effect.callback);
+        }
+
+        pub fn walk_batch<V: $visitor + ?Sized>(visitor: &mut V, batch: & $($mut)? BatchExpression) {
+            visitor.visit_expression(& $($mut)? batch.body);
+        }
+
+        pub fn walk_jsx_element<V: $visitor + ?Sized>(visitor: &mut V, jsx: & $($mut)? JsxElement) {
+            visitor.visit_jsx_opening_tag(& $($mut)? jsx.opening_tag);
+            for child in & $($mut)? jsx.children {
+                visitor.visit_jsx_child(child);
+            }
+            if let Some(closing_tag) = & $($mut)? jsx.closing_tag {
+                visitor.visit_identifier(closing_tag);
+            }
+        }
+
+        pub fn walk_jsx_opening_tag<V: $visitor + ?Sized>(visitor: &mut V, tag: & $($mut)? JsxOpeningTag) {
+            visitor.visit_identifier(& $($mut)? tag.name);
+            for attr in & $($mut)? tag.attributes {
+                visitor.visit_jsx_attribute(attr);
+            }
+        }
+
+        pub fn walk_jsx_attribute<V: $visitor + ?Sized>(visitor: &mut V, attr: & $($mut)? JsxAttribute) {
+            visitor.visit_identifier(& $($mut)? attr.name);
+            visitor.visit_expression(& $($mut)? attr.value);
+        }
+
+        pub fn walk_jsx_child<V: $visitor + ?Sized>(visitor: &mut V, child: & $($mut)? JsxChild) {
+            match child {
+                JsxChild::Element(element) => visitor.visit_jsx_element(element),
+                JsxChild::Text(_) => {}
+                JsxChild::Expression(expr) => visitor.visit_expression(expr),
+            }
+        }
+
+        pub fn walk_css<V: $visitor + ?Sized>(visitor: &mut V, css: & $($mut)? CssExpression) {
+            for item in & $($mut)? css.items {
+                match item {
+                    CssItem::Rule(rule) => visitor.visit_css_rule(rule),
+                    CssItem::Media(query) => visitor.visit_css_media_query(query),
+                    CssItem::Keyframes(keyframes) => visitor.visit_css_keyframes(keyframes),
+                    CssItem::Supports(supports) => {
+                        for rule in & $($mut)? supports.rules {
+                            visitor.visit_css_rule(rule);
+                        }
+                    }
+                }
+            }
+            for import in & $($mut)? css.imports {
+                visitor.visit_css_value(& $($mut)? import.source);
+            }
+            for font_face in & $($mut)? css.font_faces {
+                for decl in & $($mut)? font_face.declarations {
+                    visitor.visit_css_declaration(decl);
+                }
+            }
+            for page in & $($mut)? css.pages {
+                for decl in & $($mut)? page.declarations {
+                    visitor.visit_css_declaration(decl);
+                }
+            }
+        }
+
+        pub fn walk_css_rule<V: $visitor + ?Sized>(visitor: &mut V, rule: & $($mut)? CssRule) {
+            visitor.visit_css_selector(& $($mut)? rule.selector);
+            for decl in & $($mut)? rule.declarations {
+                visitor.visit_css_declaration(decl);
+            }
+            for nested in & $($mut)? rule.nested_rules {
+                visitor.visit_css_rule(nested);
+            }
+            for query in & $($mut)? rule.media_queries {
+                visitor.visit_css_media_query(query);
+            }
+            for query in & $($mut)? rule.container_queries {
+                visitor.visit_css_container_query(query);
+            }
+        }
+
+        pub fn walk_css_media_query<V: $visitor + ?Sized>(visitor: &mut V, query: & $($mut)? CssMediaQuery) {
+            for decl in & $($mut)? query.declarations {
+                visitor.visit_css_declaration(decl);
+            }
+            for rule in & $($mut)? query.rules {
+                visitor.visit_css_rule(rule);
+            }
+        }
+
+        pub fn walk_css_container_query<V: $visitor + ?Sized>(visitor: &mut V, query: & $($mut)? CssContainerQuery) {
+            for decl in & $($mut)? query.declarations {
+                visitor.visit_css_declaration(decl);
+            }
+        }
+
+        pub fn walk_css_keyframes<V: $visitor + ?Sized>(visitor: &mut V, keyframes: & $($mut)? CssKeyframes) {
+            for frame in & $($mut)? keyframes.frames {
+                visitor.visit_css_keyframe_rule(frame);
+            }
+        }
+
+        pub fn walk_css_keyframe_rule<V: $visitor + ?Sized>(visitor: &mut V, frame: & $($mut)? CssKeyframeRule) {
+            for decl in & $($mut)? frame.declarations {
+                visitor.visit_css_declaration(decl);
+            }
+        }
+
+        pub fn walk_css_declaration<V: $visitor + ?Sized>(visitor: &mut V, decl: & $($mut)? CssDeclaration) {
+            visitor.visit_css_value(& $($mut)? decl.value);
+        }
+
+        pub fn walk_css_value<V: $visitor + ?Sized>(visitor: &mut V, value: & $($mut)? CssValue) {
+            match value {
+                CssValue::Function(_, args) => {
+                    for arg in args {
+                        visitor.visit_css_value(arg);
+                    }
+                }
+                CssValue::Dynamic(expr) => visitor.visit_expression(expr),
+                CssValue::Color(_)
+                | CssValue::Length(..)
+                | CssValue::String(_)
+                | CssValue::Number(_)
+                | CssValue::Keyword(_)
+                | CssValue::Raw(_) => {}
+            }
+        }
+
+        pub fn walk_inline_style<V: $visitor + ?Sized>(visitor: &mut V, style: & $($mut)? InlineStyle) {
+            for decl in & $($mut)? style.declarations {
+                visitor.visit_inline_style_declaration(decl);
+            }
+        }
+
+        pub fn walk_inline_style_declaration<V: $visitor + ?Sized>(visitor: &mut V, decl: & $($mut)? InlineStyleDeclaration) {
+            for part in & $($mut)? decl.value {
+                if let InlineStylePart::Dynamic(expr) = part {
+                    visitor.visit_expression(expr);
+                }
+            }
+        }
+
+        pub fn walk_template<V: $visitor + ?Sized>(visitor: &mut V, template: & $($mut)? TemplateLiteral) {
+            for part in & $($mut)? template.parts {
+                if let TemplatePart::Interpolation(expr) = part {
+                    visitor.visit_expression(expr);
+                }
+            }
+        }
+
+        pub fn walk_classes<V: $visitor + ?Sized>(visitor: &mut V, classes: & $($mut)? ClassesMacro) {
+            for entry in & $($mut)? classes.entries {
+                visitor.visit_class_entry(entry);
+            }
+        }
+
+        pub fn walk_class_entry<V: $visitor + ?Sized>(visitor: &mut V, entry: & $($mut)? ClassEntry) {
+            visitor.visit_expression(& $($mut)? entry.class);
+            if let Some(condition) = & $($mut)? entry.condition {
+                visitor.visit_expression(condition);
+            }
+        }
+    };
+}
+
+pub(crate) use make_visitor;
+
+make_visitor!(Visit);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    const FIXTURE: &str = r#"
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+fn add(a: i32, b: i32) -> i32 {
+    return a + b;
+}
+
+component Counter(start: i32) {
+    let total = add(start, 1);
+    let styles = css! {
+        .count { width: {total}; }
+    };
+    return <div class="count" onClick={|| add(total, 2)}>{total}</div>;
+}
+"#;
+
+    fn parse(source: &str) -> Program {
+        let mut lexer = Lexer::new(source.to_string());
+        Parser::new(&mut lexer).parse_program().expect("parse")
+    }
+
+    #[derive(Default)]
+    struct IdentifierCounter {
+        names: Vec<String>,
+    }
+
+    impl Visit for IdentifierCounter {
+        fn visit_identifier(&mut self, ident: &Identifier) {
+            self.names.push(ident.value.clone());
+        }
+    }
+
+    #[test]
+    fn test_visitor_counts_identifiers() {
+        let mut counter = IdentifierCounter::default();
+        counter.visit_program(&parse(FIXTURE));
+
+        // Point x i32 y i32 | add a i32 b i32 i32 a b | Counter start i32
+        // total add start | styles total | div class onClick add total total div
+        assert_eq!(counter.names.len(), 28, "{:?}", counter.names);
+        assert_eq!(counter.names.iter().filter(|name| *name == "total").count(), 4);
+        assert_eq!(counter.names.iter().filter(|name| *name == "add").count(), 3);
+    }
+}
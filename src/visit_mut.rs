@@ -0,0 +1,69 @@
+//! Mutable AST visitor: `VisitMut` and its `walk_*` functions mirror
+//! `crate::visit`, taking `&mut` nodes so a tool can rewrite the tree in place
+
+use crate::ast::*;
+
+crate::visit::make_visitor!(VisitMut, mut);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formatter::Formatter;
+    use crate::js_emitter::JSEmitter;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    const SOURCE: &str = r#"
+fn add(a: i32, b: i32) -> i32 {
+    return a + b;
+}
+
+fn main() {
+    let add_one = add(1, 1);
+    let twice = add(add(2, 3), 4);
+    console::log(twice);
+}
+"#;
+
+    /// Renames a function and every call of it, leaving other names alone
+    struct RenameFunction {
+        from: &'static str,
+        to: &'static str,
+    }
+
+    impl VisitMut for RenameFunction {
+        fn visit_function(&mut self, func: &mut FunctionDefinition) {
+            if func.name.value == self.from {
+                func.name.value = self.to.to_string();
+            }
+            walk_function(self, func);
+        }
+
+        fn visit_function_call(&mut self, call: &mut FunctionCall) {
+            if let Expression::Identifier(callee) = call.function.as_mut() {
+                if callee.value == self.from {
+                    callee.value = self.to.to_string();
+                }
+            }
+            walk_function_call(self, call);
+        }
+    }
+
+    #[test]
+    fn test_visit_mut_renames_function_and_call_sites() {
+        let mut lexer = Lexer::new(SOURCE.to_string());
+        let mut program = Parser::new(&mut lexer).parse_program().expect("parse");
+        RenameFunction { from: "add", to: "sum" }.visit_program(&mut program);
+
+        let source = Formatter::new().format_program(&program);
+        assert!(source.contains("fn sum(a: i32, b: i32)"), "{}", source);
+        assert!(source.contains("let add_one = sum(1, 1);"), "{}", source);
+        assert!(source.contains("sum(sum(2, 3), 4)"), "{}", source);
+        assert!(!source.contains("add("), "{}", source);
+
+        let js = JSEmitter::new(&program).generate_server_js();
+        assert!(js.contains("module.exports.sum = function(a, b)"), "{}", js);
+        assert!(js.contains("let twice = sum(sum(2, 3), 4);"), "{}", js);
+        assert!(!js.contains("add("), "{}", js);
+    }
+}
@@ -0,0 +1,71 @@
+// Void Elements - `<br>`, `<img>` and other HTML elements without children
+//
+// HTML writes them without a closing tag, so the parser takes `<br>` to be
+// `<br />`: their children are never read, and `</br>` is an error. JSX
+// spells them out self-closing, so the short form is warned about here,
+// after parsing, so cached ASTs warn too.
+
+use crate::ast::{JsxElement, Program};
+use crate::diagnostics::{Diagnostic, SourceLocation};
+use crate::visit::{walk_jsx_element, Visit};
+
+/// Whether `tag` is an HTML element that never has children
+pub fn is_void_element(tag: &str) -> bool {
+    matches!(
+        tag,
+        "area" | "base" | "br" | "col" | "embed" | "hr" | "img" | "input"
+        | "link" | "meta" | "param" | "source" | "track" | "wbr"
+    )
+}
+
+/// A warning for each void element written without its `/>`
+pub fn check_program(program: &Program, file: &str) -> Vec<Diagnostic> {
+    let mut checker = Checker { file, diagnostics: Vec::new() };
+    checker.visit_program(program);
+    checker.diagnostics
+}
+
+struct Checker<'a> {
+    file: &'a str,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Visit for Checker<'_> {
+    fn visit_jsx_element(&mut self, jsx: &JsxElement) {
+        let tag = &jsx.opening_tag;
+        if tag.slash_omitted {
+            let location = SourceLocation {
+                file: self.file.to_string(),
+                line: tag.position.line,
+                column: tag.position.column,
+                length: tag.name.value.len(),
+            };
+            self.diagnostics.push(
+                Diagnostic::warning(format!("<{}> is a void element written without `/>`", tag.name.value))
+                    .at(location)
+                    .with_suggestion(format!("close it the JSX way: `<{} ... />`", tag.name.value)),
+            );
+        }
+        walk_jsx_element(self, jsx);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    #[test]
+    fn test_void_elements_without_slash_warn() {
+        let source = "component App() {\n    return <p>a<br>b<img src=\"x.png\" alt=\"x\" /><hr></p>;\n}\n";
+        let mut lexer = Lexer::new(source.to_string());
+        let program = Parser::new(&mut lexer).parse_program().unwrap();
+        let warnings = check_program(&program, "app.jnc");
+        let messages: Vec<&str> = warnings.iter().map(|warning| warning.message.as_str()).collect();
+        assert_eq!(messages, ["<br> is a void element written without `/>`", "<hr> is a void element written without `/>`"]);
+        let location = warnings[0].location.as_ref().unwrap();
+        assert_eq!((location.file.as_str(), location.line), ("app.jnc", 2));
+        assert_eq!(warnings[0].suggestions, ["close it the JSX way: `<br ... />`"]);
+    }
+}